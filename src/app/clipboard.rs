@@ -1,17 +1,21 @@
-//! System clipboard integration: copy, paste text, and paste images.
+//! System clipboard integration: copy, paste text, paste images, and insert
+//! links from a copied URL.
 //!
 //! On macOS, uses NSPasteboard to grab raw PNG bytes directly for fast
 //! image paste (~100ms vs ~10s with decode/re-encode).
 
 use super::*;
+use crate::markdown::{link, transform};
 
 impl<'a> App<'a> {
     // ─── Clipboard helpers ───────────────────────────────────────────────
     // arboard::Clipboard is created on demand (not stored in App — it's not Send
     // and creating it is cheap).
 
-    /// Writes text to the system clipboard via arboard.
-    pub(super) fn copy_to_clipboard(&self, text: &str) {
+    /// Writes text to the system clipboard via arboard, and adds it to the
+    /// clipboard history.
+    pub(super) fn copy_to_clipboard(&mut self, text: &str) {
+        kill_ring::push_kill(&mut self.kill_ring, text);
         if let Ok(mut clip) = arboard::Clipboard::new() {
             let _ = clip.set_text(text.to_string());
         }
@@ -22,6 +26,169 @@ impl<'a> App<'a> {
         arboard::Clipboard::new().ok()?.get_text().ok()
     }
 
+    /// Pastes clipboard text with markdown syntax escaped, so it renders
+    /// literally instead of being formatted (Ctrl+Shift+V).
+    pub(super) fn paste_literal_from_clipboard(&mut self) {
+        match self.paste_from_clipboard() {
+            Some(text) => self.insert_literal(&text),
+            None => self.set_status("Clipboard has no text"),
+        }
+    }
+
+    /// Inserts `text` at the cursor with markdown special characters escaped.
+    pub(super) fn insert_literal(&mut self, text: &str) {
+        self.buf.textarea.insert_str(transform::escape_markdown(text));
+        self.update_modified();
+        self.auto_wrap_line();
+    }
+
+    /// Pastes clipboard text with the hard line breaks inside its
+    /// paragraphs joined, then wraps it to the editor width (Alt+Shift+V).
+    pub(super) fn paste_reflowed_from_clipboard(&mut self) {
+        match self.paste_from_clipboard() {
+            Some(text) => self.insert_reflowed(&text),
+            None => self.set_status("Clipboard has no text"),
+        }
+    }
+
+    /// Inserts `text` at the cursor reflowed (see `transform::reflow_paste`),
+    /// auto-wrapping each line as it goes in.
+    pub(super) fn insert_reflowed(&mut self, text: &str) {
+        for (i, line) in transform::reflow_paste(text).split('\n').enumerate() {
+            if i > 0 {
+                self.buf.textarea.insert_newline();
+            }
+            self.buf.textarea.insert_str(line);
+            self.auto_wrap_line();
+        }
+        self.update_modified();
+    }
+
+    /// Reads the X11/Wayland primary selection (for middle-click paste),
+    /// falling back to the regular clipboard when it's empty or unsupported.
+    #[cfg(target_os = "linux")]
+    pub(super) fn paste_from_primary_selection(&self) -> Option<String> {
+        use arboard::{GetExtLinux, LinuxClipboardKind};
+        let mut clip = arboard::Clipboard::new().ok()?;
+        clip.get()
+            .clipboard(LinuxClipboardKind::Primary)
+            .text()
+            .ok()
+            .filter(|t| !t.is_empty())
+            .or_else(|| clip.get_text().ok())
+    }
+
+    /// Moves the cursor to the clicked cell and inserts `text` there, as a
+    /// middle-click paste does. Clicks outside the editor content area are ignored.
+    #[cfg(target_os = "linux")]
+    pub(super) fn paste_at_mouse(&mut self, column: u16, row: u16, text: &str) {
+        let area = self.content_area;
+        if !self.editing()
+            || self.is_read_only()
+            || column < area.x
+            || column >= area.x + area.width
+            || row < area.y
+            || row >= area.y + area.height
+        {
+            return;
+        }
+        let (buffer_row, buffer_col) = self.mouse_to_buffer_pos(column, row);
+        self.buf.textarea.cancel_selection();
+        self.buf.textarea
+            .move_cursor(CursorMove::Jump(buffer_row, buffer_col));
+        self.buf.textarea.insert_str(text);
+        self.update_modified();
+        self.auto_wrap_line();
+    }
+
+    /// Inserts the URL on the clipboard at the cursor and fetches its page title
+    /// in the background; `apply_fetched_title` later rewrites it as a markdown
+    /// link. If the fetch fails the bare URL is simply left in place.
+    pub(super) fn insert_link_from_clipboard(&mut self) {
+        let url = match self.paste_from_clipboard() {
+            Some(text) if link::is_url(text.trim()) => text.trim().to_string(),
+            _ => {
+                self.set_status("Clipboard doesn't contain a URL");
+                return;
+            }
+        };
+        self.insert_link_with_fetcher(url, link::fetch_title);
+    }
+
+    /// Inserts `url` at the cursor and runs `fetch` on a background thread,
+    /// sending the result back through `link_title_tx`.
+    pub(super) fn insert_link_with_fetcher(
+        &mut self,
+        url: String,
+        fetch: fn(&str) -> Option<String>,
+    ) {
+        self.buf.textarea.cancel_selection();
+        let (row, col) = self.buf.textarea.cursor();
+        self.buf.textarea.insert_str(&url);
+        self.update_modified();
+        self.set_status("Fetching link title...");
+
+        let tx = self.link_title_tx.clone();
+        let file_path = self.buf.file_path.clone();
+        std::thread::spawn(move || {
+            let title = fetch(&url);
+            let _ = tx.send(FetchedTitle { file_path, row, col, url, title });
+        });
+    }
+
+    /// Replaces the bare URL inserted by `insert_link_with_fetcher` with
+    /// `[Title](url)` in the file it was inserted in, which may have been
+    /// parked since. Skipped if there is no title, the file was closed, or
+    /// the URL was edited away.
+    pub(super) fn apply_fetched_title(&mut self, fetched: FetchedTitle) {
+        if fetched.file_path == self.buf.file_path {
+            self.apply_fetched_title_here(fetched);
+        } else if let Some(i) = self.parked.iter().position(|b| b.file_path == fetched.file_path) {
+            std::mem::swap(&mut self.buf, &mut self.parked[i]);
+            self.apply_fetched_title_here(fetched);
+            std::mem::swap(&mut self.buf, &mut self.parked[i]);
+        }
+    }
+
+    /// `apply_fetched_title` for the URL's file once it's in `self.buf`.
+    fn apply_fetched_title_here(&mut self, fetched: FetchedTitle) {
+        let Some(title) = fetched.title else {
+            self.set_status_as("Couldn't fetch link title", Severity::Warning);
+            return;
+        };
+        let Some(line) = self.buf.textarea.lines().get(fetched.row) else {
+            return;
+        };
+        let url_len = fetched.url.chars().count();
+        let at_col: String = line.chars().skip(fetched.col).take(url_len).collect();
+        if at_col != fetched.url {
+            return;
+        }
+
+        let link_text = link::markdown_link(&title, &fetched.url);
+        let (cursor_row, cursor_col) = self.buf.textarea.cursor();
+        self.buf.textarea.cancel_selection();
+        self.buf.textarea
+            .move_cursor(CursorMove::Jump(fetched.row as u16, fetched.col as u16));
+        self.buf.textarea.start_selection();
+        self.buf.textarea.move_cursor(CursorMove::Jump(
+            fetched.row as u16,
+            (fetched.col + url_len) as u16,
+        ));
+        self.buf.textarea.insert_str(&link_text);
+
+        // Keep the cursor where the user left it, shifted if it was after the URL
+        let cursor_col = if cursor_row == fetched.row && cursor_col >= fetched.col + url_len {
+            cursor_col + link_text.chars().count() - url_len
+        } else {
+            cursor_col
+        };
+        self.buf.textarea
+            .move_cursor(CursorMove::Jump(cursor_row as u16, cursor_col as u16));
+        self.update_modified();
+        self.set_status("Inserted link");
+    }
+
     /// Returns a markdown image link immediately and spawns a background
     /// thread that saves the clipboard image as a PNG file.
     ///
@@ -31,7 +198,7 @@ impl<'a> App<'a> {
     /// The background thread also sends the decoded `DynamicImage` through the
     /// preview channel so the first render doesn't block on a redundant decode.
     pub(super) fn paste_image_from_clipboard(&self) -> Option<String> {
-        let parent = self.file_path.parent()?;
+        let parent = self.buf.file_path.parent()?;
         let images_dir = parent.join(".marko").join("images");
         std::fs::create_dir_all(&images_dir).ok()?;
 
@@ -43,7 +210,7 @@ impl<'a> App<'a> {
         let relative_url = format!(".marko/images/{}", filename);
         let md_text = format!("![screenshot]({})\n", relative_url);
 
-        let image_tx = self.preview.image_sender();
+        let image_tx = self.preview.images.sender();
         let url_hint = relative_url.clone();
 
         std::thread::spawn(move || {
@@ -63,9 +230,9 @@ impl<'a> App<'a> {
 
             let send_image = |img: Option<image::DynamicImage>| {
                 if let Some(ref i) = img {
-                    crate::components::preview::save_thumbnail(i, &file_path);
+                    crate::components::images::save_thumbnail(i, &file_path);
                 }
-                let _ = image_tx.send(crate::components::preview::DecodedImage {
+                let _ = image_tx.send(crate::components::images::DecodedImage {
                     path: file_path.clone(),
                     image: img,
                     url_hint: Some(url_hint.clone()),
@@ -82,7 +249,7 @@ impl<'a> App<'a> {
                         Ok(_) => log("PNG saved (raw)"),
                         Err(e) => log(&format!("write failed: {}", e)),
                     }
-                    let img = crate::components::preview::load_image_from_bytes(&raw_bytes);
+                    let img = crate::components::images::load_image_from_bytes(&raw_bytes);
                     send_image(img);
                 } else {
                     log("data is TIFF, transcoding to PNG");
@@ -92,7 +259,7 @@ impl<'a> App<'a> {
             } else {
                 log("no image data on clipboard, falling back to arboard");
                 save_clipboard_image_arboard(&file_path, &log);
-                let img = crate::components::preview::load_image(&file_path);
+                let img = crate::components::images::load_image(&file_path);
                 send_image(img);
             }
         });
@@ -0,0 +1,92 @@
+//! Merge conflict navigation and resolution.
+//!
+//! Alt+N / Alt+Shift+N jump to the next / previous conflict, wrapping around
+//! the buffer. With the cursor inside a conflict, Alt+O keeps our side,
+//! Alt+T theirs, and Alt+B both. Resolving goes through the undo history,
+//! so Ctrl+Z brings the markers back.
+
+use super::*;
+use crate::git::conflict::{self, Resolution};
+
+impl<'a> App<'a> {
+    // ─── Conflicts (Alt+N / Alt+O / Alt+T / Alt+B) ───────────────────────
+
+    /// True if the cursor is inside a conflict, markers included.
+    pub(super) fn cursor_in_conflict(&self) -> bool {
+        let row = self.buf.textarea.cursor().0;
+        conflict::find_conflicts(self.buf.textarea.lines())
+            .iter()
+            .any(|c| c.contains(row))
+    }
+
+    /// Moves the cursor to the start marker of the next (or previous)
+    /// conflict, wrapping around the buffer.
+    pub(super) fn jump_to_conflict(&mut self, forward: bool) {
+        let conflicts = conflict::find_conflicts(self.buf.textarea.lines());
+        if conflicts.is_empty() {
+            self.set_status("No conflicts");
+            return;
+        }
+        let row = self.buf.textarea.cursor().0;
+        let target = if forward {
+            conflicts.iter().find(|c| c.start > row).unwrap_or(&conflicts[0])
+        } else {
+            conflicts
+                .iter()
+                .rev()
+                .find(|c| c.start < row)
+                .unwrap_or(&conflicts[conflicts.len() - 1])
+        };
+        let index = conflicts.iter().position(|c| c == target).unwrap_or(0);
+        self.push_jump();
+        self.buf.textarea.cancel_selection();
+        self.buf.textarea
+            .move_cursor(CursorMove::Jump(target.start as u16, 0));
+        self.set_status(&format!("Conflict {} of {}", index + 1, conflicts.len()));
+    }
+
+    /// Resolves the conflict under the cursor, replacing it (markers and all)
+    /// with the side(s) chosen by `resolution`.
+    pub(super) fn resolve_conflict(&mut self, resolution: Resolution) {
+        let lines = self.buf.textarea.lines().to_vec();
+        let conflicts = conflict::find_conflicts(&lines);
+        let row = self.buf.textarea.cursor().0;
+        let Some(target) = conflicts.iter().find(|c| c.contains(row)) else {
+            self.set_status("No conflict at cursor");
+            return;
+        };
+        let kept = conflict::kept_lines(&lines, target, resolution);
+
+        // Select whole lines so the edit goes through the undo history
+        let line_len = |r: usize| lines[r].chars().count();
+        if target.end + 1 < lines.len() {
+            self.set_selection((target.start, 0), (target.end + 1, 0));
+            let mut text = kept.join("\n");
+            if !kept.is_empty() {
+                text.push('\n');
+            }
+            self.buf.textarea.insert_str(text);
+        } else if kept.is_empty() && target.start > 0 {
+            // Last lines of the buffer: also drop the newline before them
+            let prev = target.start - 1;
+            self.set_selection((prev, line_len(prev)), (target.end, line_len(target.end)));
+            self.buf.textarea.insert_str("");
+        } else {
+            self.set_selection((target.start, 0), (target.end, line_len(target.end)));
+            self.buf.textarea.insert_str(kept.join("\n"));
+        }
+        self.buf.textarea
+            .move_cursor(CursorMove::Jump(target.start as u16, 0));
+        self.buf.code_fence_dirty = true;
+        self.update_modified();
+
+        let side = match resolution {
+            Resolution::Ours => "ours",
+            Resolution::Theirs => "theirs",
+            Resolution::Both => "both",
+        };
+        let left = conflicts.len() - 1;
+        let noun = if left == 1 { "conflict" } else { "conflicts" };
+        self.set_status(&format!("Kept {} ({} {} left)", side, left, noun));
+    }
+}
@@ -0,0 +1,53 @@
+//! Element preview (Alt+E): the block under the cursor (a paragraph, list,
+//! table or code block) rendered on its own in a popup, to check its
+//! formatting without switching to the full preview. Any key closes it.
+
+use super::*;
+
+/// Widest the element preview popup gets.
+const ELEMENT_PREVIEW_WIDTH: u16 = 72;
+
+impl<'a> App<'a> {
+    // ─── Element preview (Alt+E) ─────────────────────────────────────────
+
+    /// The source of the block around the cursor, or None on a blank line.
+    pub(super) fn element_source_at_cursor(&self) -> Option<String> {
+        let (start, end) = self.element_bounds_at(self.buf.textarea.cursor().0)?;
+        Some(self.buf.textarea.lines()[start..=end].join("\n"))
+    }
+
+    /// Opens the popup rendering the block under the cursor.
+    pub(super) fn open_element_preview(&mut self) {
+        match self.element_source_at_cursor() {
+            Some(source) => self.element_preview = Some(source),
+            None => self.set_status("Nothing to preview on a blank line"),
+        }
+    }
+
+    /// Draws the element preview popup, centered and sized to its content.
+    pub(super) fn render_element_preview(&self, frame: &mut Frame) {
+        let Some(ref source) = self.element_preview else {
+            return;
+        };
+        let area = frame.area();
+        let width = ELEMENT_PREVIEW_WIDTH.min(area.width.saturating_sub(4));
+        let mut lines = renderer::render_lines(source, width.saturating_sub(2) as usize);
+        while lines.last().is_some_and(|l| l.width() == 0) {
+            lines.pop();
+        }
+        let height = (lines.len() as u16 + 2).min(area.height.saturating_sub(2));
+        let x = (area.width.saturating_sub(width)) / 2;
+        let y = (area.height.saturating_sub(height)) / 2;
+        let popup_area = Rect::new(x, y, width, height);
+
+        frame.render_widget(Clear, popup_area);
+
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(theme::active().border))
+            .title(" Preview ")
+            .style(theme::editor_style());
+
+        frame.render_widget(Paragraph::new(lines).block(block), popup_area);
+    }
+}
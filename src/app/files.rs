@@ -0,0 +1,300 @@
+//! Quick-switching between the files given on the command line, and telling
+//! same-named files apart by their path.
+//!
+//! Ctrl+PageDown / Ctrl+PageUp cycle through `file_list`. Each file keeps its
+//! own buffer: switching away parks it with its unsaved edits and undo
+//! history, and saving only ever writes the file shown. Alt+P toggles the
+//! header between the filename and the workspace-relative path, and
+//! Alt+Shift+A / Alt+Shift+P copy the absolute / relative path.
+
+use super::*;
+
+impl<'a> FileBuffer<'a> {
+    /// Loads `path` from disk, edited with `config`. Gutter marks are
+    /// computed in the background; code blocks are highlighted on first
+    /// render.
+    pub(super) fn load(file_path: PathBuf, config: Config) -> Self {
+        let content = std::fs::read_to_string(&file_path).unwrap_or_default();
+
+        // Content is loaded raw here; wrapping to fit the terminal width
+        // is deferred to the first render() call where we have the actual
+        // content_area dimensions (last_wrap_width = 0 forces this).
+        let lines: Vec<String> = if content.is_empty() {
+            vec![String::new()]
+        } else {
+            content.lines().map(String::from).collect()
+        };
+
+        let mut textarea = TextArea::new(lines.clone());
+        editor::configure_textarea(&mut textarea);
+
+        // Try to open the git repo for branch/status/gutter info
+        let git_repo = GitRepo::open(&file_path);
+        let git_branch = git_repo
+            .as_ref()
+            .map(|g| g.branch_name())
+            .unwrap_or_default();
+        let git_file_status = git_repo
+            .as_ref()
+            .map(|g| g.file_status(&file_path))
+            .unwrap_or_default();
+
+        // Spawn background thread for gutter marks (expensive git diff)
+        let gutter_handle = if git_repo.is_some() {
+            let fp = file_path.clone();
+            Some(std::thread::spawn(move || {
+                match git2::Repository::discover(&fp) {
+                    Ok(repo) => git::diff::compute_gutter_diff(&repo, &fp, None),
+                    Err(_) => GutterDiff::default(),
+                }
+            }))
+        } else {
+            None
+        };
+
+        // Code fence regions found immediately (cheap), but highlights deferred
+        // until syntect finishes loading in background (code_fence_dirty=true).
+        let code_fence_regions = code_highlight::find_code_fence_regions(&lines);
+
+        Self {
+            modeline: modeline::find(&lines),
+            marks: marks::load_marks(&file_path),
+            focus: focus::FocusTimer::new(focus::load_focus_time(&file_path)),
+            file_read_only: !is_writable(&file_path),
+            file_path,
+            textarea,
+            modified: false,
+            original_content: content.clone(),
+            wrapped_original: content,
+            auto_wrap: config.auto_wrap,
+            config,
+            docx_state: None,
+            gutter_marks: HashMap::new(),
+            gutter_deletions: Vec::new(),
+            diff_base: None,
+            last_edit: None,
+            git_repo,
+            git_branch,
+            git_file_status,
+            word_goal: None,
+            editor_scroll_top: 0,
+            preview_scroll: 0,
+            preview_collapsed: HashSet::new(),
+            last_wrap_width: 0,
+            gutter_handle,
+            code_fence_regions,
+            code_fence_highlights: vec![],
+            code_fence_handle: None,
+            code_fence_dirty: true,
+            code_fence_focus: None,
+            folded: BTreeSet::new(),
+            fold_snapshot: Vec::new(),
+            replaces: Vec::new(),
+            undone_replaces: Vec::new(),
+        }
+    }
+}
+
+impl<'a> App<'a> {
+    // ─── File switching ──────────────────────────────────────────────────
+
+    /// Switches to the next (`forward`) or previous file in `file_list`, wrapping around.
+    pub(super) fn cycle_file(&mut self, forward: bool) {
+        let count = self.file_list.len();
+        if count < 2 {
+            self.set_status("No other files open");
+            return;
+        }
+        let current = self
+            .file_list
+            .iter()
+            .position(|p| *p == self.buf.file_path)
+            .unwrap_or(0);
+        let next = if forward {
+            (current + 1) % count
+        } else {
+            (current + count - 1) % count
+        };
+        let path = self.file_list[next].clone();
+        self.switch_to_file(path);
+    }
+
+    /// Shows `path`, parking the current buffer (unsaved edits and undo
+    /// history included) until it is switched back to. A file not open yet
+    /// is loaded from disk, with its own project config.
+    pub(super) fn switch_to_file(&mut self, path: PathBuf) {
+        self.stop_focus_timer();
+        let parked = self.parked.iter().position(|b| b.file_path == path);
+        let mut buffer = match parked {
+            Some(i) => self.parked.remove(i),
+            None => {
+                let config = Config::load_for(&path).unwrap_or_else(|e| {
+                    self.set_status_as(&format!("Ignoring config: {}", e), Severity::Error);
+                    Config::default()
+                });
+                FileBuffer::load(path, config)
+            }
+        };
+        self.swap_preview_state();
+        std::mem::swap(&mut self.buf, &mut buffer);
+        self.swap_preview_state();
+        self.parked.push(buffer);
+        if parked.is_none() {
+            self.apply_diff_base();
+        }
+        self.apply_file_theme();
+
+        // Per-file UI doesn't carry over
+        self.search = None;
+        self.replace = None;
+        self.prompt = None;
+        self.outline = None;
+        self.renaming = false;
+        self.mouse_dragging = false;
+        self.rect_selection = None;
+        self.rect_dragging = false;
+        self.drag_auto_scroll = None;
+        self.split_synced_row = None;
+        self.pending_mark = None;
+        self.jump_list.clear();
+        self.jump_index = 0;
+        if self.header_path.is_some() {
+            self.refresh_header_path();
+        }
+
+        let (index, count) = self.buffer_position();
+        let name = self.display_name().to_string();
+        self.set_status(&format!("{} ({}/{})", name, index, count));
+        #[cfg(feature = "spellcheck")]
+        self.load_spell_dictionary();
+    }
+
+    /// Exchanges the preview's scroll and collapsed sections with the ones
+    /// `buf` keeps while parked.
+    fn swap_preview_state(&mut self) {
+        std::mem::swap(&mut self.preview.scroll_offset, &mut self.buf.preview_scroll);
+        std::mem::swap(&mut self.preview.collapsed, &mut self.buf.preview_collapsed);
+    }
+
+    /// 1-based position of the shown file in `file_list`, and its length.
+    pub fn buffer_position(&self) -> (usize, usize) {
+        let index = self
+            .file_list
+            .iter()
+            .position(|p| *p == self.buf.file_path)
+            .map_or(0, |i| i + 1);
+        (index, self.file_list.len())
+    }
+
+    /// Number of parked files with unsaved changes.
+    pub(super) fn unsaved_parked_count(&self) -> usize {
+        self.parked.iter().filter(|b| b.modified).count()
+    }
+
+    /// Saves every parked file with unsaved changes, swapping each in to go
+    /// through `save` (table formatting, .docx export). Returns how many
+    /// failed to save.
+    pub(super) fn save_parked(&mut self) -> usize {
+        let mut parked = std::mem::take(&mut self.parked);
+        let mut failed = 0;
+        for buffer in parked.iter_mut().filter(|b| b.modified) {
+            std::mem::swap(&mut self.buf, buffer);
+            self.save();
+            if self.buf.modified {
+                failed += 1;
+            }
+            std::mem::swap(&mut self.buf, buffer);
+        }
+        self.parked = parked;
+        failed
+    }
+
+    /// Points the current file's `file_list` entry at `new_path` after a rename.
+    pub(super) fn update_file_list_entry(&mut self, new_path: &std::path::Path) {
+        if let Some(entry) = self.file_list.iter_mut().find(|p| **p == self.buf.file_path) {
+            *entry = new_path.to_path_buf();
+        }
+    }
+
+    // ─── Header path ─────────────────────────────────────────────────────
+
+    /// Name of the open file as shown to the user: the .docx when editing
+    /// one, otherwise the markdown file.
+    pub fn display_name(&self) -> &str {
+        let path = match self.buf.docx_state {
+            Some(ref ds) => &ds.docx_path,
+            None => &self.buf.file_path,
+        };
+        path.file_name()
+            .and_then(|n| n.to_str())
+            .unwrap_or("untitled")
+    }
+
+    /// Toggles the header between the bare filename and the path relative to
+    /// the workspace root.
+    pub(super) fn toggle_header_path(&mut self) {
+        if self.header_path.is_some() {
+            self.header_path = None;
+        } else {
+            self.refresh_header_path();
+        }
+    }
+
+    /// Opens the directory containing the open file (the .docx when editing
+    /// one) in the system file manager.
+    pub(super) fn reveal_file_dir(&mut self) {
+        let path = match self.buf.docx_state {
+            Some(ref ds) => &ds.docx_path,
+            None => &self.buf.file_path,
+        };
+        let path = std::fs::canonicalize(path).unwrap_or_else(|_| path.clone());
+        let dir = match path.parent() {
+            Some(p) if !p.as_os_str().is_empty() => p.to_path_buf(),
+            _ => PathBuf::from("."),
+        };
+        match reveal::open_in_file_manager(&dir) {
+            Ok(()) => self.set_status(&format!("Opened {}", dir.display())),
+            Err(e) => self.set_status_as(&format!("Could not open file manager: {}", e), Severity::Error),
+        }
+    }
+
+    /// The open file's path (the .docx when editing one): absolute, or
+    /// relative to the workspace root.
+    pub(super) fn file_path_text(&self, relative: bool) -> String {
+        let path = match self.buf.docx_state {
+            Some(ref ds) => &ds.docx_path,
+            None => &self.buf.file_path,
+        };
+        let absolute = std::fs::canonicalize(path).unwrap_or_else(|_| {
+            std::env::current_dir().map_or_else(|_| path.clone(), |dir| dir.join(path))
+        });
+        let shown = if relative {
+            let root = workspace::root_for(&absolute);
+            let root = std::fs::canonicalize(&root).unwrap_or(root);
+            workspace::relative_path(&absolute, &root)
+        } else {
+            absolute
+        };
+        shown.display().to_string()
+    }
+
+    /// Copies the open file's absolute (Alt+Shift+A) or workspace-relative
+    /// (Alt+Shift+P) path to the system clipboard.
+    pub(super) fn copy_file_path(&mut self, relative: bool) {
+        let text = self.file_path_text(relative);
+        self.copy_to_clipboard(&text);
+        self.set_status(&format!("Copied {}", text));
+    }
+
+    /// Recomputes the workspace-relative path of the open file (the .docx
+    /// when editing one) for the header.
+    pub(super) fn refresh_header_path(&mut self) {
+        let path = match self.buf.docx_state {
+            Some(ref ds) => &ds.docx_path,
+            None => &self.buf.file_path,
+        };
+        let root = workspace::root_for(path);
+        let relative = workspace::relative_path(path, &root);
+        self.header_path = Some(relative.display().to_string());
+    }
+}
@@ -0,0 +1,73 @@
+//! Filter through a shell command (Alt+!), like vim's `!`: the selection, or
+//! the whole buffer without one, is piped through the command and replaced
+//! by its output. Handy for `sort`, a formatter, or `jq` on a JSON block.
+//!
+//! On failure the text is left alone and the command's stderr is shown.
+//! Replacing goes through the undo history.
+
+use super::*;
+
+impl<'a> App<'a> {
+    // ─── Filter (Alt+!) ──────────────────────────────────────────────────
+
+    /// Prompts for the command, remembering the selection it will replace.
+    pub(super) fn start_filter(&mut self) {
+        let selection = self.buf.textarea.selection_range();
+        self.start_prompt(Prompt::FilterCommand { selection });
+    }
+
+    /// Runs `command` on the text of `selection` (or the whole buffer) and
+    /// replaces that text with the command's output.
+    pub(super) fn filter_through(
+        &mut self,
+        command: &str,
+        selection: Option<((usize, usize), (usize, usize))>,
+    ) {
+        if command.is_empty() {
+            return;
+        }
+        // The whole buffer goes in as a file would, newline-terminated
+        let input = match selection {
+            Some((start, end)) => self.text_between(start, end),
+            None => format!("{}\n", self.textarea_content()),
+        };
+        let mut output = match shell::run(command, &input) {
+            Ok(out) => out,
+            Err(e) => {
+                self.set_status_as(&format!("Filter failed: {}", e), Severity::Error);
+                return;
+            }
+        };
+        // Don't let the command's final newline add a line to the buffer
+        if output.ends_with('\n') && !(selection.is_some() && input.ends_with('\n')) {
+            output.pop();
+        }
+
+        match selection {
+            Some((start, end)) => self.set_selection(start, end),
+            None => self.buf.textarea.select_all(),
+        }
+        self.buf.textarea.insert_str(output);
+        self.buf.code_fence_dirty = true;
+        self.update_modified();
+        self.set_status(&format!("Filtered through `{}`", command));
+    }
+
+    /// Buffer text from `start` to `end` (char positions), lines joined by
+    /// newlines.
+    fn text_between(&self, start: (usize, usize), end: (usize, usize)) -> String {
+        let mut text = String::new();
+        let rows = self.buf.textarea.lines().iter().enumerate();
+        for (row, line) in rows.skip(start.0).take(end.0 + 1 - start.0) {
+            let chars = line.chars();
+            let from = if row == start.0 { start.1 } else { 0 };
+            if row == end.0 {
+                text.extend(chars.take(end.1).skip(from));
+            } else {
+                text.extend(chars.skip(from));
+                text.push('\n');
+            }
+        }
+        text
+    }
+}
@@ -0,0 +1,194 @@
+//! Focus timer (F6): counts writing time while running, shown in the status
+//! bar. With `pomodoro_minutes` set, a reminder to take a break flashes in
+//! the status bar each time another interval of the session passes.
+//!
+//! Time spent on each file adds up across sessions in the `.marko` sidecar
+//! directory (`.marko/focus.toml`, seconds per file name). Stopping the
+//! timer, switching files or quitting records the session.
+
+use super::*;
+use std::collections::BTreeMap;
+use std::path::Path;
+
+/// The focus timer's state for one file.
+#[derive(Debug, Default)]
+pub(super) struct FocusTimer {
+    /// When the running session started; None while stopped.
+    started: Option<Instant>,
+    /// Time recorded on the file before this session.
+    recorded: Duration,
+    /// Pomodoro intervals of this session already reminded about.
+    reminded: u32,
+}
+
+impl FocusTimer {
+    /// A stopped timer with `recorded` time already spent on the file.
+    pub(super) fn new(recorded: Duration) -> Self {
+        Self { recorded, ..Self::default() }
+    }
+
+    pub(super) fn is_running(&self) -> bool {
+        self.started.is_some()
+    }
+
+    /// Starts a session at `now` (no-op if one is running).
+    pub(super) fn start(&mut self, now: Instant) {
+        if self.started.is_none() {
+            self.started = Some(now);
+            self.reminded = 0;
+        }
+    }
+
+    /// Ends the session at `now`, adding it to the recorded time, and
+    /// returns its length (zero if none was running).
+    pub(super) fn stop(&mut self, now: Instant) -> Duration {
+        let Some(started) = self.started.take() else {
+            return Duration::ZERO;
+        };
+        let session = now.saturating_duration_since(started);
+        self.recorded += session;
+        session
+    }
+
+    /// Length of the running session at `now`.
+    pub(super) fn session(&self, now: Instant) -> Duration {
+        self.started
+            .map_or(Duration::ZERO, |started| now.saturating_duration_since(started))
+    }
+
+    /// All time spent on the file, the running session included.
+    pub(super) fn total(&self, now: Instant) -> Duration {
+        self.recorded + self.session(now)
+    }
+
+    /// True once for each `interval` of the running session completed by
+    /// `now`.
+    pub(super) fn pomodoro_due(&mut self, now: Instant, interval: Duration) -> bool {
+        if interval.is_zero() {
+            return false;
+        }
+        let completed = (self.session(now).as_secs() / interval.as_secs().max(1)) as u32;
+        if completed > self.reminded {
+            self.reminded = completed;
+            return true;
+        }
+        false
+    }
+}
+
+/// `m:ss`, or `h:mm:ss` from an hour on.
+pub(super) fn format_elapsed(elapsed: Duration) -> String {
+    let secs = elapsed.as_secs();
+    let (hours, minutes, seconds) = (secs / 3600, secs / 60 % 60, secs % 60);
+    if hours > 0 {
+        format!("{}:{:02}:{:02}", hours, minutes, seconds)
+    } else {
+        format!("{}:{:02}", minutes, seconds)
+    }
+}
+
+/// Seconds spent per file name in the store at `path`; empty if it is
+/// missing or unreadable.
+fn read_store(path: &Path) -> BTreeMap<String, u64> {
+    std::fs::read_to_string(path)
+        .ok()
+        .and_then(|text| toml::from_str(&text).ok())
+        .unwrap_or_default()
+}
+
+/// Time recorded on `file` in earlier sessions.
+pub(super) fn load_focus_time(file: &Path) -> Duration {
+    let Some(name) = file.file_name().and_then(|n| n.to_str()) else {
+        return Duration::ZERO;
+    };
+    let secs = read_store(&marks::sidecar_path(file, "focus.toml"))
+        .get(name)
+        .copied()
+        .unwrap_or(0);
+    Duration::from_secs(secs)
+}
+
+/// Records `total` as the time spent on `file`.
+fn store_focus_time(file: &Path, total: Duration) -> std::io::Result<()> {
+    let Some(name) = file.file_name().and_then(|n| n.to_str()) else {
+        return Ok(());
+    };
+    let path = marks::sidecar_path(file, "focus.toml");
+    let mut store = read_store(&path);
+    store.insert(name.to_string(), total.as_secs());
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    let text = toml::to_string(&store).map_err(std::io::Error::other)?;
+    std::fs::write(path, text)
+}
+
+/// Moves the time recorded on `old` to `new` after a rename.
+pub(super) fn move_focus_time(old: &Path, new: &Path) {
+    let recorded = load_focus_time(old);
+    if recorded.is_zero() {
+        return;
+    }
+    let path = marks::sidecar_path(old, "focus.toml");
+    let mut store = read_store(&path);
+    if let Some(name) = old.file_name().and_then(|n| n.to_str()) {
+        store.remove(name);
+    }
+    if let Ok(text) = toml::to_string(&store) {
+        let _ = std::fs::write(&path, text);
+    }
+    let _ = store_focus_time(new, recorded);
+}
+
+impl<'a> App<'a> {
+    // ─── Focus timer (F6) ────────────────────────────────────────────────
+
+    /// Starts or stops the focus timer.
+    pub(super) fn toggle_focus_timer(&mut self) {
+        if self.buf.focus.is_running() {
+            self.stop_focus_timer();
+        } else {
+            self.buf.focus.start(Instant::now());
+            self.set_status("Focus timer started");
+        }
+    }
+
+    /// Stops a running focus timer and records the session.
+    pub fn stop_focus_timer(&mut self) {
+        if !self.buf.focus.is_running() {
+            return;
+        }
+        let now = Instant::now();
+        let session = self.buf.focus.stop(now);
+        let total = self.buf.focus.total(now);
+        let msg = match store_focus_time(&self.buf.file_path, total) {
+            Ok(()) => format!(
+                "Focus timer stopped: {} (total {})",
+                format_elapsed(session),
+                format_elapsed(total)
+            ),
+            Err(e) => format!("Focus timer stopped, but not saved: {}", e),
+        };
+        self.set_status(&msg);
+    }
+
+    /// Flashes the break reminder when a pomodoro interval ends. Called
+    /// from `tick`.
+    pub(super) fn tick_focus_timer(&mut self) {
+        let Some(minutes) = self.buf.config.pomodoro_minutes.filter(|&m| m > 0) else {
+            return;
+        };
+        let now = Instant::now();
+        if self.buf.focus.pomodoro_due(now, Duration::from_secs(minutes * 60)) {
+            let focused = self.buf.focus.session(now).as_secs() / 60;
+            self.set_status(&format!("{} minutes of focus: time for a break", focused));
+        }
+    }
+
+    /// The running session's length for the status bar.
+    pub(super) fn focus_elapsed(&self) -> Option<String> {
+        self.buf.focus
+            .is_running()
+            .then(|| format_elapsed(self.buf.focus.session(Instant::now())))
+    }
+}
@@ -0,0 +1,151 @@
+//! Folding code blocks (Alt+Z): a folded block shows only its opening fence
+//! followed by a "… (N lines)" summary; the body and closing fence are
+//! hidden.
+//!
+//! Folds are keyed by the line of the opening fence and kept in step with
+//! edits by diffing the buffer against a snapshot taken at the last check:
+//! folds below an edit shift with it, and an edit touching a folded block
+//! unfolds it. Up/Down step over folded blocks; any other way of moving the
+//! cursor into one unfolds it. Folds are not saved.
+
+use super::*;
+
+impl<'a> App<'a> {
+    // ─── Folding (Alt+Z) ─────────────────────────────────────────────────
+
+    /// Folds or unfolds the closed code block the cursor is in, leaving the
+    /// cursor on its opening fence.
+    pub(super) fn toggle_fold(&mut self) {
+        let row = self.buf.textarea.cursor().0;
+        let regions = code_highlight::find_code_fence_regions(self.buf.textarea.lines());
+        let Some(region) = regions
+            .iter()
+            .find(|r| (r.start_line..=r.end_line).contains(&row) && self.is_closed(r))
+        else {
+            self.set_status("Cursor is not in a code block");
+            return;
+        };
+        let start = region.start_line;
+        if !self.buf.folded.remove(&start) {
+            self.buf.folded.insert(start);
+            self.buf.textarea.cancel_selection();
+            self.buf.textarea.move_cursor(CursorMove::Jump(start as u16, 0));
+        }
+        self.buf.fold_snapshot = if self.buf.folded.is_empty() {
+            Vec::new()
+        } else {
+            self.buf.textarea.lines().to_vec()
+        };
+    }
+
+    /// True if `region` has a closing fence (an unclosed one runs to the
+    /// end of the buffer and can't fold).
+    fn is_closed(&self, region: &CodeFenceRegion) -> bool {
+        region.end_line > region.start_line
+            && self.buf.textarea.lines()[region.end_line]
+                .trim_start()
+                .starts_with("```")
+    }
+
+    /// `(start, end)` fence lines of every folded block, in order. Lines
+    /// `start + 1..=end` are hidden.
+    pub(super) fn fold_spans(&self) -> Vec<(usize, usize)> {
+        if self.buf.folded.is_empty() {
+            return Vec::new();
+        }
+        code_highlight::find_code_fence_regions(self.buf.textarea.lines())
+            .into_iter()
+            .filter(|r| self.buf.folded.contains(&r.start_line))
+            .map(|r| (r.start_line, r.end_line))
+            .collect()
+    }
+
+    /// Keeps folds in step with an edit, comparing the buffer with the
+    /// snapshot from the last check. Called from `update_modified`.
+    pub(super) fn adjust_folds_after_edit(&mut self) {
+        if self.buf.folded.is_empty() {
+            return;
+        }
+        let lines = self.buf.textarea.lines();
+        let old = std::mem::take(&mut self.buf.fold_snapshot);
+        // The edit replaced old[prefix..old.len() - suffix]
+        let prefix = old.iter().zip(lines).take_while(|(a, b)| a == b).count();
+        let max_suffix = old.len().min(lines.len()) - prefix;
+        let suffix = old
+            .iter()
+            .rev()
+            .zip(lines.iter().rev())
+            .take(max_suffix)
+            .take_while(|(a, b)| a == b)
+            .count();
+        if prefix == old.len() && prefix == lines.len() {
+            self.buf.fold_snapshot = old;
+            return;
+        }
+        let changed_end = old.len() - suffix;
+        let delta = lines.len() as isize - old.len() as isize;
+
+        let old_regions = code_highlight::find_code_fence_regions(&old);
+        self.buf.folded = self.buf
+            .folded
+            .iter()
+            .filter_map(|&start| {
+                let end = old_regions.iter().find(|r| r.start_line == start)?.end_line;
+                if end < prefix {
+                    Some(start)
+                } else if start >= changed_end {
+                    Some(start.checked_add_signed(delta)?)
+                } else {
+                    // The edit touched the folded block
+                    None
+                }
+            })
+            .collect();
+        // Drop any fold whose fence no longer opens a closed block
+        let spans = self.fold_spans();
+        self.buf.folded.retain(|s| spans.iter().any(|&(start, end)| start == *s && end > start));
+        if !self.buf.folded.is_empty() {
+            self.buf.fold_snapshot = self.buf.textarea.lines().to_vec();
+        }
+    }
+
+    /// After Up/Down-style movement, steps the cursor over a folded body it
+    /// landed in: past the block moving down, onto its fence moving up.
+    pub(super) fn skip_folded(&mut self, down: bool) {
+        let (row, col) = self.buf.textarea.cursor();
+        let Some(&(start, end)) = self
+            .fold_spans()
+            .iter()
+            .find(|&&(start, end)| row > start && row <= end)
+        else {
+            return;
+        };
+        let target = if down && end + 1 < self.buf.textarea.lines().len() {
+            end + 1
+        } else {
+            start
+        };
+        self.buf.textarea
+            .move_cursor(CursorMove::Jump(target as u16, col as u16));
+    }
+
+    /// Unfolds any block hiding the cursor line (e.g. after a search or
+    /// go-to-line landed inside it).
+    pub(super) fn reveal_cursor(&mut self) {
+        let row = self.buf.textarea.cursor().0;
+        for (start, end) in self.fold_spans() {
+            if row > start && row <= end {
+                self.buf.folded.remove(&start);
+            }
+        }
+        if self.buf.folded.is_empty() {
+            self.buf.fold_snapshot.clear();
+        }
+    }
+
+    /// Clears every fold (used when the buffer is re-wrapped wholesale).
+    pub(super) fn clear_folds(&mut self) {
+        self.buf.folded.clear();
+        self.buf.fold_snapshot.clear();
+    }
+}
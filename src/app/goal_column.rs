@@ -0,0 +1,24 @@
+//! Goal column: moving up or down through a shorter line clamps the cursor
+//! to that line's end, but the next line long enough gets the original
+//! column back. The goal lasts until the cursor moves some other way.
+
+use super::*;
+
+impl<'a> App<'a> {
+    // ─── Goal column ─────────────────────────────────────────────────────
+
+    /// After a vertical move from `before`, puts the cursor at the goal
+    /// column on its new row, clamped to the row's length. The goal is
+    /// `before`'s column, unless the last vertical move left the cursor at
+    /// `before`, in which case that move's goal carries on.
+    pub(super) fn apply_goal_column(&mut self, before: (usize, usize)) {
+        let goal = match self.goal_column {
+            Some((at, goal)) if at == before => goal,
+            _ => before.1,
+        };
+        let row = self.buf.textarea.cursor().0;
+        let col = goal.min(self.buf.textarea.lines()[row].chars().count());
+        self.buf.textarea.move_cursor(CursorMove::Jump(row as u16, col as u16));
+        self.goal_column = Some(((row, col), goal));
+    }
+}
@@ -16,10 +16,22 @@ impl<'a> App<'a> {
             }
             return;
         }
-        if self.mode == Mode::Editor {
-            self.textarea.insert_str(text);
-            self.update_modified();
-            self.auto_wrap_line();
+        if self.prompt.is_some() {
+            self.handle_prompt_paste(&text);
+            return;
+        }
+        if self.search.is_some() {
+            self.handle_search_paste(&text);
+            return;
+        }
+        if self.replace.is_some() {
+            self.handle_replace_paste(&text);
+            return;
+        }
+        if self.editing() && self.is_read_only() {
+            self.set_status("Read-only");
+        } else if self.editing() {
+            self.insert_paste(&text);
         }
     }
 
@@ -28,9 +40,42 @@ impl<'a> App<'a> {
     /// Main key handler. Processes modal states first, then Esc-as-back,
     /// then global keybindings, then delegates to mode-specific handlers.
     pub(super) fn handle_key(&mut self, key: KeyEvent) {
-        // Help modal: any key dismisses it (swallows the keypress)
+        // A quit armed by a failed save only lasts until the next key
+        let quit_pending = std::mem::take(&mut self.quit_pending);
+
+        // Macro keys work everywhere and are never recorded
+        if self.handle_macro_key(key) {
+            return;
+        }
+        self.record_macro_key(key);
+
+        // Help modal: arrows and PgUp/PgDn scroll it, any other key
+        // dismisses it (swallows the keypress)
         if self.show_help {
-            self.show_help = false;
+            match key.code {
+                KeyCode::Up => self.help_scroll = self.help_scroll.saturating_sub(1),
+                KeyCode::Down => self.help_scroll = self.help_scroll.saturating_add(1),
+                KeyCode::PageUp => self.help_scroll = self.help_scroll.saturating_sub(self.help_page),
+                KeyCode::PageDown => self.help_scroll = self.help_scroll.saturating_add(self.help_page),
+                _ => self.show_help = false,
+            }
+            return;
+        }
+
+        // Message log: any key dismisses it
+        if self.show_status_log {
+            self.show_status_log = false;
+            return;
+        }
+
+        // Element preview popup: any key dismisses it
+        if self.element_preview.take().is_some() {
+            return;
+        }
+
+        // Outline panel: all keys go to it
+        if self.outline.is_some() {
+            self.handle_outline_key(key);
             return;
         }
 
@@ -40,6 +85,40 @@ impl<'a> App<'a> {
             return;
         }
 
+        // Status-bar prompt: all keys go to its input
+        if self.prompt.is_some() {
+            self.handle_prompt_key(key);
+            return;
+        }
+
+        // Find bar: all keys go to its query
+        if self.search.is_some() {
+            self.handle_search_key(key);
+            return;
+        }
+
+        // Replace prompt: all keys go to its inputs
+        if self.replace.is_some() {
+            self.handle_replace_key(key);
+            return;
+        }
+
+        // Alt+K / Alt+J: the next key names the mark
+        if let Some(op) = self.pending_mark.take() {
+            self.handle_mark_key(op, key);
+            return;
+        }
+
+        // Vim layer: Normal mode keys, and Esc out of Insert mode
+        if self.buf.config.editing == Editing::Vim && self.editing() && self.handle_vim_key(key) {
+            return;
+        }
+
+        // Block selection: its own copy, cut, paste and editing
+        if self.editing() && self.handle_rect_key(key) {
+            return;
+        }
+
         // Esc: return to Editor mode (back/cancel)
         if key.code == KeyCode::Esc && key.modifiers.is_empty() {
             if self.mode != Mode::Editor {
@@ -51,24 +130,71 @@ impl<'a> App<'a> {
         // Global keybindings (work in all modes)
         match (key.modifiers, key.code) {
             (KeyModifiers::CONTROL, KeyCode::Char('q')) => {
-                if self.modified {
-                    self.save();
-                }
-                self.should_quit = true;
+                self.request_quit(quit_pending);
                 return;
             }
             (KeyModifiers::CONTROL, KeyCode::Char('s')) => {
                 self.save();
                 return;
             }
+            (KeyModifiers::CONTROL, KeyCode::PageDown) => {
+                self.cycle_file(true);
+                return;
+            }
+            (KeyModifiers::CONTROL, KeyCode::PageUp) => {
+                self.cycle_file(false);
+                return;
+            }
             (KeyModifiers::CONTROL, KeyCode::Char('t')) => {
                 self.start_rename();
                 return;
             }
+            (KeyModifiers::ALT, KeyCode::Char('p')) => {
+                self.toggle_header_path();
+                return;
+            }
+            (KeyModifiers::ALT, KeyCode::Char('r')) => {
+                self.reveal_file_dir();
+                return;
+            }
+            (m, KeyCode::Char(c @ ('A' | 'P'))) if m.contains(KeyModifiers::ALT) => {
+                self.copy_file_path(c == 'P');
+                return;
+            }
+            (KeyModifiers::ALT, KeyCode::Char('v')) => {
+                self.toggle_split();
+                return;
+            }
             (_, KeyCode::F(1)) => {
                 self.show_help = true;
+                self.help_scroll = 0;
+                return;
+            }
+            (_, KeyCode::F(2)) => {
+                self.show_status_log = true;
+                return;
+            }
+            (_, KeyCode::F(6)) => {
+                self.toggle_focus_timer();
+                return;
+            }
+            (_, KeyCode::F(8)) => {
+                self.cycle_theme();
                 return;
             }
+            // Indent a selection in the editor (Tab alone switches modes)
+            (_, KeyCode::Tab)
+                if self.editing()
+                    && self.buf.textarea.selection_range().is_some()
+                    && !self.is_read_only() =>
+            {
+                self.indent_selection(true);
+                return;
+            }
+            // In the preview, Tab moves between links while there are any
+            // (handled with the preview keys below)
+            (KeyModifiers::NONE, KeyCode::Tab)
+                if self.mode == Mode::Preview && !self.preview.links.is_empty() => {}
             (_, KeyCode::Tab) => {
                 // Toggle between Editor and Preview
                 let target = match self.mode {
@@ -83,7 +209,21 @@ impl<'a> App<'a> {
 
         // Mode-specific keybindings
         match self.mode {
-            Mode::Editor => self.handle_editor_key(key),
+            Mode::Editor | Mode::Split => {
+                let before = self.buf.textarea.cursor();
+                let yanked = self.buf.textarea.yank_text();
+                if (key.modifiers, key.code) != (KeyModifiers::ALT, KeyCode::Char('y')) {
+                    self.last_paste = None;
+                }
+                self.handle_editor_key(key);
+                self.record_edit(key, before);
+                // Cuts and deletions that the editor yanked join the history
+                let now_yanked = self.buf.textarea.yank_text();
+                if now_yanked != yanked {
+                    kill_ring::push_kill(&mut self.kill_ring, &now_yanked);
+                }
+                self.format_table_left(before);
+            }
             Mode::Preview => self.handle_preview_key(key),
         }
     }
@@ -92,35 +232,177 @@ impl<'a> App<'a> {
     /// BEFORE passing to tui-textarea, which has non-standard defaults:
     ///   tui-textarea: Ctrl+U=undo, Ctrl+Y=paste, Ctrl+V=PageDown, Ctrl+A=line-start
     ///   We remap:     Ctrl+Z=undo, Ctrl+Y=redo,  Ctrl+V=paste,    Ctrl+A=select-all
-    fn handle_editor_key(&mut self, key: KeyEvent) {
+    pub(super) fn handle_editor_key(&mut self, key: KeyEvent) {
+        if self.is_read_only() && !allowed_read_only(key) {
+            self.set_status("Read-only");
+            return;
+        }
         match (key.modifiers, key.code) {
             // Undo
             (KeyModifiers::CONTROL, KeyCode::Char('z')) => {
-                self.textarea.undo();
-                self.update_modified();
+                self.undo();
                 return;
             }
             // Redo
             (KeyModifiers::CONTROL, KeyCode::Char('y')) => {
-                self.textarea.redo();
-                self.update_modified();
+                self.redo();
                 return;
             }
             // Redo (alternative: Ctrl+Shift+Z)
             (m, KeyCode::Char('Z')) if m.contains(KeyModifiers::CONTROL) && m.contains(KeyModifiers::SHIFT) => {
-                self.textarea.redo();
-                self.update_modified();
+                self.redo();
+                return;
+            }
+            // Outline of the document's headings
+            (KeyModifiers::CONTROL, KeyCode::Char('o')) => {
+                self.open_outline();
+                return;
+            }
+            // Find in document
+            (KeyModifiers::CONTROL, KeyCode::Char('f')) => {
+                self.start_search();
+                return;
+            }
+            // Find and replace
+            (KeyModifiers::CONTROL, KeyCode::Char('r')) => {
+                self.start_replace();
                 return;
             }
             // Select all (overrides tui-textarea's Ctrl+A = move to line start)
             (KeyModifiers::CONTROL, KeyCode::Char('a')) => {
-                self.textarea.select_all();
+                self.buf.textarea.select_all();
                 return;
             }
             // Go to beginning of line
             (KeyModifiers::CONTROL, KeyCode::Char('l')) => {
-                self.textarea.cancel_selection();
-                self.textarea.move_cursor(CursorMove::Head);
+                self.buf.textarea.cancel_selection();
+                self.buf.textarea.move_cursor(CursorMove::Head);
+                return;
+            }
+            // Go to a source line number
+            (KeyModifiers::CONTROL, KeyCode::Char('g')) => {
+                self.start_prompt(Prompt::GoToLine);
+                return;
+            }
+            // Jump to start / end of document (render keeps the cursor in view)
+            (KeyModifiers::CONTROL, KeyCode::Home) => {
+                self.push_jump();
+                self.buf.textarea.cancel_selection();
+                self.buf.textarea.move_cursor(CursorMove::Top);
+                self.buf.textarea.move_cursor(CursorMove::Head);
+                return;
+            }
+            (KeyModifiers::CONTROL, KeyCode::End) => {
+                self.push_jump();
+                self.buf.textarea.cancel_selection();
+                self.buf.textarea.move_cursor(CursorMove::Bottom);
+                self.buf.textarea.move_cursor(CursorMove::End);
+                return;
+            }
+            // Set a mark / jump to one (the next key names it)
+            (KeyModifiers::ALT, KeyCode::Char('k')) => {
+                self.start_mark(MarkOp::Set);
+                return;
+            }
+            (KeyModifiers::ALT, KeyCode::Char('j')) => {
+                self.start_mark(MarkOp::Jump);
+                return;
+            }
+            // Step back / forward through the jump list
+            (KeyModifiers::ALT, KeyCode::Left) => {
+                self.jump_back();
+                return;
+            }
+            (KeyModifiers::ALT, KeyCode::Right) => {
+                self.jump_forward();
+                return;
+            }
+            // Repeat the last edit at the cursor
+            (KeyModifiers::ALT, KeyCode::Char('.')) => {
+                self.repeat_last_edit();
+                return;
+            }
+            // Toggle inline code span / fenced code block on the selection
+            (KeyModifiers::ALT, KeyCode::Char('c')) => {
+                self.toggle_code();
+                return;
+            }
+            // Outdent the selected lines (Tab indents them, see handle_key)
+            (_, KeyCode::BackTab) if self.buf.textarea.selection_range().is_some() => {
+                self.indent_selection(false);
+                return;
+            }
+            // Check / uncheck the task list item on the cursor line
+            (KeyModifiers::CONTROL, KeyCode::Char(' ')) => {
+                self.toggle_task_at_cursor();
+                return;
+            }
+            // Add / remove one blockquote level on the selected lines
+            (KeyModifiers::ALT, KeyCode::Char('q')) => {
+                self.toggle_quote_level(true);
+                return;
+            }
+            (m, KeyCode::Char('Q')) if m.contains(KeyModifiers::ALT) => {
+                self.toggle_quote_level(false);
+                return;
+            }
+            // Dim / restore markdown syntax markers
+            (KeyModifiers::ALT, KeyCode::Char('m')) => {
+                self.buf.config.dim_markers = !self.buf.config.dim_markers;
+                let state = if self.buf.config.dim_markers { "dimmed" } else { "shown" };
+                self.set_status(&format!("Markdown markers {}", state));
+                return;
+            }
+            // Set a word goal for the status bar progress bar
+            (KeyModifiers::ALT, KeyCode::Char('g')) => {
+                self.start_prompt(Prompt::WordGoal);
+                return;
+            }
+            // Fold / unfold the code block under the cursor
+            (KeyModifiers::ALT, KeyCode::Char('z')) => {
+                self.toggle_fold();
+                return;
+            }
+            #[cfg(feature = "spellcheck")]
+            (_, KeyCode::F(7)) => {
+                self.next_misspelling();
+                return;
+            }
+            // Run the code block under the cursor (after confirmation)
+            (KeyModifiers::ALT, KeyCode::Char('x')) => {
+                self.start_run_code_block();
+                return;
+            }
+            // Pipe the selection (or whole buffer) through a shell command
+            (m, KeyCode::Char('!')) if m.contains(KeyModifiers::ALT) => {
+                self.start_filter();
+                return;
+            }
+            // Insert the clipboard URL as a [Title](url) link
+            (KeyModifiers::ALT, KeyCode::Char('l')) => {
+                self.insert_link_from_clipboard();
+                return;
+            }
+            // Merge conflicts: jump between them, resolve the one at the cursor
+            (KeyModifiers::ALT, KeyCode::Char('n')) => {
+                self.jump_to_conflict(true);
+                return;
+            }
+            (m, KeyCode::Char('N')) if m.contains(KeyModifiers::ALT) => {
+                self.jump_to_conflict(false);
+                return;
+            }
+            (KeyModifiers::ALT, KeyCode::Char('o')) => {
+                self.resolve_conflict(Resolution::Ours);
+                return;
+            }
+            (KeyModifiers::ALT, KeyCode::Char('t')) => {
+                self.resolve_conflict(Resolution::Theirs);
+                return;
+            }
+            // Outside a conflict Alt+B stays tui-textarea's word-back
+            (KeyModifiers::ALT, KeyCode::Char('b')) if self.cursor_in_conflict() => {
+                self.resolve_conflict(Resolution::Both);
                 return;
             }
             // Copy selection to system clipboard (overrides tui-textarea's internal-only yank)
@@ -129,54 +411,88 @@ impl<'a> App<'a> {
                     self.copy_to_clipboard(&text);
                 }
                 // Also yank internally so Ctrl+V fallback works within the editor
-                self.textarea.copy();
+                self.buf.textarea.copy();
                 return;
             }
             // Paste from system clipboard (overrides tui-textarea's Ctrl+V = PageDown)
             (KeyModifiers::CONTROL, KeyCode::Char('v')) => {
                 if let Some(text) = self.paste_from_clipboard() {
-                    self.textarea.insert_str(text);
-                    self.update_modified();
-                    self.auto_wrap_line();
+                    self.insert_paste(&text);
                 } else if let Some(md_text) = self.paste_image_from_clipboard() {
-                    self.textarea.insert_str(md_text);
+                    self.buf.textarea.insert_str(md_text);
                     self.update_modified();
                 }
                 return;
             }
+            // Render the block under the cursor in a popup
+            (KeyModifiers::ALT, KeyCode::Char('e')) => {
+                self.open_element_preview();
+                return;
+            }
+            // Swap what was just pasted for the previous clipboard entry
+            (KeyModifiers::ALT, KeyCode::Char('y')) => {
+                self.cycle_paste();
+                return;
+            }
+            // Paste as literal text (markdown syntax escaped)
+            (m, KeyCode::Char('V')) if m.contains(KeyModifiers::CONTROL) && m.contains(KeyModifiers::SHIFT) => {
+                self.paste_literal_from_clipboard();
+                return;
+            }
+            // Paste with broken lines joined into paragraphs
+            (m, KeyCode::Char('V')) if m.contains(KeyModifiers::ALT) => {
+                self.paste_reflowed_from_clipboard();
+                return;
+            }
+            // Insert an empty table, asking for its size
+            (m, KeyCode::Char('T')) if m.contains(KeyModifiers::CONTROL) && m.contains(KeyModifiers::SHIFT) => {
+                self.start_prompt(Prompt::TableRows);
+                return;
+            }
+            // Toggle auto-wrap for future edits
+            (m, KeyCode::Char('W')) if m.contains(KeyModifiers::CONTROL) && m.contains(KeyModifiers::SHIFT) => {
+                self.buf.auto_wrap = !self.buf.auto_wrap;
+                let state = if self.buf.auto_wrap { "on" } else { "off" };
+                self.set_status(&format!("Auto-wrap {}", state));
+                return;
+            }
             // Delete word before cursor
             // On macOS, Ctrl+Backspace sends Ctrl+H (0x08), so we match both
             (KeyModifiers::CONTROL, KeyCode::Backspace)
             | (KeyModifiers::CONTROL, KeyCode::Char('h')) => {
-                self.textarea.delete_word();
+                self.buf.textarea.delete_word();
                 self.update_modified();
                 return;
             }
             // Delete word after cursor (forward)
             (KeyModifiers::CONTROL, KeyCode::Delete) => {
-                self.textarea.delete_next_word();
+                self.buf.textarea.delete_next_word();
                 self.update_modified();
                 return;
             }
             // Delete word after cursor (Mac-friendly: no forward-delete key on Magic Keyboard)
             (KeyModifiers::CONTROL, KeyCode::Char('d')) => {
-                self.textarea.delete_next_word();
+                self.buf.textarea.delete_next_word();
                 self.update_modified();
                 return;
             }
             // Enter: list/blockquote continuation
-            (KeyModifiers::NONE, KeyCode::Enter) => {
-                if self.handle_enter_continuation() {
-                    return;
-                }
+            (KeyModifiers::NONE, KeyCode::Enter) if self.handle_enter_continuation() => {
+                return;
+            }
+            // Backspace inside an empty pair deletes both halves
+            (KeyModifiers::NONE, KeyCode::Backspace) if self.delete_empty_pair() => {
+                return;
+            }
+            // A closer typed just before the same one steps over it
+            (KeyModifiers::NONE | KeyModifiers::SHIFT, KeyCode::Char(ch)) if self.type_over_closer(ch) => {
+                return;
             }
             // Auto-close pairs for bracket/quote characters
             (KeyModifiers::NONE, KeyCode::Char(ch))
-                if autocomplete::auto_close_pair(ch).is_some() =>
+                if autocomplete::auto_close_pair(ch).is_some() && self.handle_auto_close(ch) =>
             {
-                if self.handle_auto_close(ch) {
-                    return;
-                }
+                return;
             }
             _ => {}
         }
@@ -197,18 +513,56 @@ impl<'a> App<'a> {
                 | KeyCode::PageDown
         );
 
+        let before = self.buf.textarea.cursor();
         let input = Input::from(key);
-        self.textarea.input(input);
+        self.buf.textarea.input(input);
 
+        match key.code {
+            KeyCode::Down | KeyCode::PageDown => self.skip_folded(true),
+            KeyCode::Up | KeyCode::PageUp => self.skip_folded(false),
+            _ => {}
+        }
+        // Ctrl/Alt+Up/Down jump by paragraph or to the ends; no goal there
+        if matches!(key.code, KeyCode::Up | KeyCode::Down | KeyCode::PageUp | KeyCode::PageDown)
+            && !key.modifiers.intersects(KeyModifiers::CONTROL | KeyModifiers::ALT)
+        {
+            self.apply_goal_column(before);
+        }
         if !is_navigation {
             self.update_modified();
             self.auto_wrap_line();
         }
     }
 
-    /// Preview mode key handler: arrow key scrolling only.
+    /// Preview mode key handler: arrow key scrolling, Tab / Shift+Tab /
+    /// Enter to move between and open links, and Enter without a focused
+    /// link to collapse or expand the section at the top.
     fn handle_preview_key(&mut self, key: KeyEvent) {
         match key.code {
+            KeyCode::Tab | KeyCode::BackTab => {
+                let forward = key.code == KeyCode::Tab;
+                if !self.preview.focus_link(forward, self.viewport_height) {
+                    self.set_status("No links in the preview");
+                }
+            }
+            KeyCode::Enter => match self.preview.focused_url() {
+                Some(url) => {
+                    crate::components::preview::open_url(url);
+                    self.set_status(&format!("Opening {}", url));
+                }
+                None => match self.preview.section_at_top() {
+                    Some(source_line) => {
+                        self.preview.toggle_section(source_line);
+                    }
+                    None => self.set_status("Tab to a link, then Enter opens it"),
+                },
+            },
+            // Math as LaTeX source, to check what was written
+            KeyCode::Char('m') => {
+                self.preview.show_raw_math = !self.preview.show_raw_math;
+                let shown = if self.preview.show_raw_math { "LaTeX source" } else { "Unicode" };
+                self.set_status(&format!("Math shown as {}", shown));
+            }
             KeyCode::Up => self.preview.scroll_up(1),
             KeyCode::Down => self.preview.scroll_down(1, self.viewport_height),
             KeyCode::PageUp => self.preview.page_up(self.viewport_height),
@@ -231,29 +585,35 @@ impl<'a> App<'a> {
     pub(super) fn handle_mouse(&mut self, mouse: MouseEvent) {
         match mouse.kind {
             // Scroll wheel: delegate to tui-textarea in editor, manual in preview
+            MouseEventKind::ScrollUp if self.in_split_preview(mouse.column, mouse.row) => {
+                self.preview.scroll_up(SCROLL_LINES);
+            }
+            MouseEventKind::ScrollDown if self.in_split_preview(mouse.column, mouse.row) => {
+                self.preview.scroll_down(SCROLL_LINES, self.viewport_height);
+            }
             MouseEventKind::ScrollUp => match self.mode {
-                Mode::Editor => {
-                    self.textarea.input(Input {
+                Mode::Editor | Mode::Split => {
+                    self.buf.textarea.input(Input {
                         key: Key::MouseScrollUp,
                         ctrl: false,
                         alt: false,
                         shift: false,
                     });
-                    self.editor_scroll_top = self.editor_scroll_top.saturating_sub(1);
+                    self.buf.editor_scroll_top = self.buf.editor_scroll_top.saturating_sub(1);
                 }
                 Mode::Preview => self.preview.scroll_up(SCROLL_LINES),
             },
             MouseEventKind::ScrollDown => match self.mode {
-                Mode::Editor => {
-                    self.textarea.input(Input {
+                Mode::Editor | Mode::Split => {
+                    self.buf.textarea.input(Input {
                         key: Key::MouseScrollDown,
                         ctrl: false,
                         alt: false,
                         shift: false,
                     });
-                    let total_lines = self.textarea.lines().len() as u16;
+                    let total_lines = self.buf.textarea.lines().len() as u16;
                     let max_scroll = total_lines.saturating_sub(1);
-                    self.editor_scroll_top = (self.editor_scroll_top + 1).min(max_scroll);
+                    self.buf.editor_scroll_top = (self.buf.editor_scroll_top + 1).min(max_scroll);
                 }
                 Mode::Preview => self.preview.scroll_down(SCROLL_LINES, self.viewport_height),
             },
@@ -261,15 +621,21 @@ impl<'a> App<'a> {
             // Left click: header tabs/filename or editor cursor positioning + drag start
             MouseEventKind::Down(MouseButton::Left) => {
                 let area = self.content_area;
+                // Both panes in split view
+                let full = if self.preview_area.is_empty() {
+                    area
+                } else {
+                    area.union(self.preview_area)
+                };
 
                 // Ignore clicks outside the capped area's x-range
-                if mouse.column < area.x || mouse.column >= area.x + area.width {
+                if mouse.column < full.x || mouse.column >= full.x + full.width {
                     return;
                 }
 
                 // Click on header row (above content area)
                 if mouse.row < area.y {
-                    let right_edge = area.x + area.width;
+                    let right_edge = full.x + full.width;
                     let tabs_start = right_edge.saturating_sub(TAB_TOTAL_W);
 
                     if mouse.column >= tabs_start {
@@ -287,30 +653,71 @@ impl<'a> App<'a> {
                     return;
                 }
 
-                // Click on link in preview mode -> open URL
-                if self.mode == Mode::Preview {
+                // Click on link in preview mode (or pane) -> open URL
+                if self.mode == Mode::Preview || self.in_split_preview(mouse.column, mouse.row) {
                     if let Some(url) = self.preview.url_at(mouse.column, mouse.row) {
                         crate::components::preview::open_url(url);
+                    } else if let Some(source_line) = self.preview.heading_at(mouse.column, mouse.row) {
+                        self.preview.toggle_section(source_line);
                     }
                     return;
                 }
 
+                // Click on the editor scrollbar: jump there and start a scrollbar drag
+                if self.editing() && self.scrollbar_hit(mouse.column, mouse.row) {
+                    self.scroll_to_scrollbar_row(mouse.row);
+                    self.scrollbar_dragging = true;
+                    return;
+                }
+
                 // Click in editor content area: single/double/triple click handling
-                if self.mode == Mode::Editor
+                if self.editing()
                     && mouse.column >= area.x
                     && mouse.column < area.x + area.width
                     && mouse.row >= area.y
                     && mouse.row < area.y + area.height
                 {
+                    self.rect_selection = None;
+                    // Alt+click: start a block selection, grown by dragging
+                    if mouse.modifiers.contains(KeyModifiers::ALT) {
+                        let (buffer_row, buffer_col) =
+                            self.mouse_to_buffer_pos(mouse.column, mouse.row);
+                        let corner = (buffer_row as usize, buffer_col as usize);
+                        self.set_rect_selection(corner, corner);
+                        self.rect_dragging = true;
+                        return;
+                    }
+
+                    // Shift+click: extend from the existing anchor (or the cursor)
+                    // to the clicked position instead of starting a new selection
+                    if mouse.modifiers.contains(KeyModifiers::SHIFT) {
+                        if self.buf.textarea.selection_range().is_none() {
+                            self.buf.textarea.start_selection();
+                        }
+                        let (buffer_row, buffer_col) =
+                            self.mouse_to_buffer_pos(mouse.column, mouse.row);
+                        self.buf.textarea
+                            .move_cursor(CursorMove::Jump(buffer_row, buffer_col));
+                        self.click_count = 1;
+                        self.last_click_time = None;
+                        self.drag_granularity = SelectGranularity::Char;
+                        self.mouse_dragging = true;
+                        return;
+                    }
+
                     // Multi-click detection
                     let now = Instant::now();
+                    let (last_col, last_row) = self.last_click_pos;
                     let is_repeat = self
                         .last_click_time
-                        .map(|t| now.duration_since(t).as_millis() < MULTI_CLICK_MS as u128)
+                        .map(|t| {
+                            now.duration_since(t).as_millis() < self.buf.config.multi_click_ms as u128
+                        })
                         .unwrap_or(false)
-                        && self.last_click_pos == (mouse.column, mouse.row);
+                        && last_col.abs_diff(mouse.column) <= MULTI_CLICK_TOLERANCE
+                        && last_row.abs_diff(mouse.row) <= MULTI_CLICK_TOLERANCE;
                     self.click_count = if is_repeat {
-                        (self.click_count % 3) + 1
+                        (self.click_count % 4) + 1
                     } else {
                         1
                     };
@@ -322,79 +729,174 @@ impl<'a> App<'a> {
 
                     match self.click_count {
                         2 => {
-                            // Double-click: select word
-                            self.textarea
+                            // Double-click: select word, later drags extend by words
+                            self.buf.textarea
                                 .move_cursor(CursorMove::Jump(buffer_row, buffer_col));
                             self.select_word_at_cursor();
-                            self.mouse_dragging = false;
+                            self.drag_granularity = SelectGranularity::Word;
                         }
                         3 => {
-                            // Triple-click: select paragraph
-                            self.textarea
+                            // Triple-click: select paragraph, later drags extend by paragraphs
+                            self.buf.textarea
                                 .move_cursor(CursorMove::Jump(buffer_row, buffer_col));
                             self.select_paragraph_at_cursor();
+                            self.drag_granularity = SelectGranularity::Paragraph;
+                        }
+                        4 => {
+                            // Quadruple-click: select the whole document; nothing left to extend
+                            self.buf.textarea.select_all();
                             self.mouse_dragging = false;
+                            return;
                         }
                         _ => {
                             // Single click: position cursor + start drag selection
-                            self.textarea.cancel_selection();
-                            self.textarea
+                            self.buf.textarea.cancel_selection();
+                            self.buf.textarea
                                 .move_cursor(CursorMove::Jump(buffer_row, buffer_col));
-                            self.textarea.start_selection();
-                            self.mouse_dragging = true;
+                            self.buf.textarea.start_selection();
+                            self.drag_granularity = SelectGranularity::Char;
                         }
                     }
+                    let cursor = self.buf.textarea.cursor();
+                    self.drag_origin = self.buf.textarea.selection_range().unwrap_or((cursor, cursor));
+                    self.mouse_dragging = true;
                 }
             }
 
-            // Left drag: extend selection to current mouse position
-            MouseEventKind::Drag(MouseButton::Left) => {
-                if self.mode == Mode::Editor && self.mouse_dragging {
-                    let area = self.content_area;
-                    if mouse.column >= area.x
-                        && mouse.column < area.x + area.width
-                        && mouse.row >= area.y
-                        && mouse.row < area.y + area.height
-                    {
-                        let (buffer_row, buffer_col) = self.mouse_to_buffer_pos(mouse.column, mouse.row);
-                        self.textarea
-                            .move_cursor(CursorMove::Jump(buffer_row, buffer_col));
-                    }
+            // Scrollbar drag: follow the mouse row (clamped to the track)
+            MouseEventKind::Drag(MouseButton::Left) if self.scrollbar_dragging => {
+                self.scroll_to_scrollbar_row(mouse.row);
+            }
+
+            // Alt+drag: move the block's far corner to the mouse
+            MouseEventKind::Drag(MouseButton::Left) if self.editing() && self.rect_dragging => {
+                let area = self.content_area;
+                if area.width == 0 || area.height == 0 {
+                    return;
+                }
+                let column = mouse.column.clamp(area.x, area.x + area.width - 1);
+                let row = mouse.row.clamp(area.y, area.y + area.height - 1);
+                let (buffer_row, buffer_col) = self.mouse_to_buffer_pos(column, row);
+                self.set_rect_selection(self.rect_anchor, (buffer_row as usize, buffer_col as usize));
+            }
+
+            // Left drag: extend selection to current mouse position. Past the
+            // top/bottom edge, pin the cursor to the edge row and let tick()
+            // keep scrolling until the mouse comes back or is released.
+            MouseEventKind::Drag(MouseButton::Left)
+                if self.editing() && self.mouse_dragging =>
+            {
+                let area = self.content_area;
+                if area.width == 0 || area.height == 0 {
+                    return;
+                }
+                let edge_row = if mouse.row < area.y {
+                    self.drag_auto_scroll = Some(AutoScroll::Up);
+                    Some(area.y)
+                } else if mouse.row >= area.y + area.height {
+                    self.drag_auto_scroll = Some(AutoScroll::Down);
+                    Some(area.y + area.height - 1)
+                } else {
+                    self.drag_auto_scroll = None;
+                    None
+                };
+
+                if let Some(row) = edge_row {
+                    let column = mouse.column.clamp(area.x, area.x + area.width - 1);
+                    let (buffer_row, buffer_col) = self.mouse_to_buffer_pos(column, row);
+                    self.extend_drag_selection(buffer_row as usize, buffer_col as usize);
+                } else if mouse.column >= area.x && mouse.column < area.x + area.width {
+                    let (buffer_row, buffer_col) = self.mouse_to_buffer_pos(mouse.column, mouse.row);
+                    self.extend_drag_selection(buffer_row as usize, buffer_col as usize);
+                }
+            }
+
+            // Middle click: paste the primary selection at the click point (X11 convention)
+            #[cfg(target_os = "linux")]
+            MouseEventKind::Down(MouseButton::Middle) if self.editing() => {
+                if let Some(text) = self.paste_from_primary_selection() {
+                    self.paste_at_mouse(mouse.column, mouse.row, &text);
                 }
             }
 
+            MouseEventKind::Up(MouseButton::Left) if self.scrollbar_dragging => {
+                self.scrollbar_dragging = false;
+            }
+
+            MouseEventKind::Up(MouseButton::Left) if self.rect_dragging => {
+                self.rect_dragging = false;
+            }
+
             // Left release: finalize selection (cancel if it was just a click with no drag)
-            MouseEventKind::Up(MouseButton::Left) => {
-                if self.mouse_dragging {
-                    self.mouse_dragging = false;
-                    if let Some(((sr, sc), (er, ec))) = self.textarea.selection_range() {
-                        if sr == er && sc == ec {
-                            self.textarea.cancel_selection();
-                        }
-                    } else {
-                        self.textarea.cancel_selection();
+            MouseEventKind::Up(MouseButton::Left) if self.mouse_dragging => {
+                self.mouse_dragging = false;
+                self.drag_auto_scroll = None;
+                if let Some(((sr, sc), (er, ec))) = self.buf.textarea.selection_range() {
+                    if sr == er && sc == ec {
+                        self.buf.textarea.cancel_selection();
                     }
+                } else {
+                    self.buf.textarea.cancel_selection();
                 }
             }
             _ => {}
         }
     }
 
+    /// True if `(column, row)` is in the preview pane of the split view.
+    fn in_split_preview(&self, column: u16, row: u16) -> bool {
+        let area = self.preview_area;
+        column >= area.x
+            && column < area.x + area.width
+            && row >= area.y
+            && row < area.y + area.height
+    }
+
+    /// True if `(column, row)` is on the editor scrollbar (rightmost content
+    /// column), which is only shown when the buffer is taller than the viewport.
+    fn scrollbar_hit(&self, column: u16, row: u16) -> bool {
+        let area = self.content_area;
+        editor::scrollbar_shown(area, self.buf.textarea.lines().len())
+            && column == area.x + area.width - 1
+            && row >= area.y
+            && row < area.y + area.height
+    }
+
+    /// Scrolls the editor so the scrollbar thumb is centered on terminal `row`.
+    /// The selection is dropped since tui-textarea drags the cursor into view.
+    pub(super) fn scroll_to_scrollbar_row(&mut self, row: u16) {
+        let area = self.content_area;
+        let relative_row = row.saturating_sub(area.y).min(area.height.saturating_sub(1));
+        let total_lines = self.buf.textarea.lines().len();
+        let target = editor::scroll_for_scrollbar_row(area.height, total_lines, relative_row);
+        let delta = target as i32 - self.buf.editor_scroll_top as i32;
+        if delta == 0 {
+            return;
+        }
+        self.buf.textarea.cancel_selection();
+        self.buf.textarea
+            .scroll((delta.clamp(i16::MIN as i32, i16::MAX as i32) as i16, 0));
+        self.buf.editor_scroll_top = target as u16;
+    }
+
     /// Converts terminal mouse coordinates to buffer (row, col) positions,
-    /// accounting for the line number gutter width and scroll offset.
+    /// accounting for the line number gutter width, scroll offset and soft wrap.
     pub(super) fn mouse_to_buffer_pos(&self, column: u16, row: u16) -> (u16, u16) {
         let area = self.content_area;
-        let total_lines = self.textarea.lines().len();
+        let total_lines = self.buf.textarea.lines().len();
         // tui-textarea gutter = leading space + digits + trailing space
-        let gutter_width = if self.textarea.line_number_style().is_some() {
+        let gutter_width = if self.buf.textarea.line_number_style().is_some() {
             (total_lines as f64).log10() as u16 + 1 + 2
         } else {
             0
         };
         let relative_row = row - area.y;
-        let buffer_row = relative_row + self.editor_scroll_top;
         let relative_col = column - area.x;
         let buffer_col = relative_col.saturating_sub(gutter_width);
+        if self.soft_wrap() || !self.buf.folded.is_empty() {
+            return self.laid_out_pos(relative_row, buffer_col);
+        }
+        let buffer_row = relative_row + self.buf.editor_scroll_top;
         (buffer_row, buffer_col)
     }
 
@@ -403,8 +905,8 @@ impl<'a> App<'a> {
     /// Handles Enter key with list/blockquote continuation.
     /// Returns true if the key was handled (caller should not pass to tui-textarea).
     fn handle_enter_continuation(&mut self) -> bool {
-        let (row, col) = self.textarea.cursor();
-        let lines = self.textarea.lines();
+        let (row, col) = self.buf.textarea.cursor();
+        let lines = self.buf.textarea.lines();
         if row >= lines.len() {
             return false;
         }
@@ -417,17 +919,17 @@ impl<'a> App<'a> {
 
         match autocomplete::analyze_line_for_continuation(&line) {
             Continuation::Continue(prefix) => {
-                self.textarea.insert_newline();
-                self.textarea.insert_str(&prefix);
+                self.buf.textarea.insert_newline();
+                self.buf.textarea.insert_str(&prefix);
                 self.update_modified();
                 true
             }
             Continuation::ClearLine => {
                 // Select the entire line content and cut it
-                self.textarea.move_cursor(CursorMove::Head);
-                self.textarea.start_selection();
-                self.textarea.move_cursor(CursorMove::End);
-                self.textarea.cut();
+                self.buf.textarea.move_cursor(CursorMove::Head);
+                self.buf.textarea.start_selection();
+                self.buf.textarea.move_cursor(CursorMove::End);
+                self.buf.textarea.cut();
                 self.update_modified();
                 true
             }
@@ -444,8 +946,8 @@ impl<'a> App<'a> {
         };
 
         // Get the character before the cursor for context-sensitive skipping
-        let (row, col) = self.textarea.cursor();
-        let lines = self.textarea.lines();
+        let (row, col) = self.buf.textarea.cursor();
+        let lines = self.buf.textarea.lines();
         let prev_char = if row < lines.len() && col > 0 {
             lines[row].chars().nth(col - 1)
         } else {
@@ -463,41 +965,81 @@ impl<'a> App<'a> {
         }
 
         // Insert the pair and move cursor back between them
-        self.textarea.insert_char(ch);
-        self.textarea.insert_char(close);
-        self.textarea.move_cursor(CursorMove::Back);
+        self.buf.textarea.insert_char(ch);
+        self.buf.textarea.insert_char(close);
+        self.buf.textarea.move_cursor(CursorMove::Back);
+        self.update_modified();
+        true
+    }
+
+    /// Moves past the char after the cursor instead of typing `ch` when
+    /// it's the same closing bracket or quote (`type_over_closers`).
+    /// Returns true if it did.
+    fn type_over_closer(&mut self, ch: char) -> bool {
+        if !self.buf.config.type_over_closers
+            || !autocomplete::is_auto_close_closer(ch)
+            || self.buf.textarea.selection_range().is_some()
+        {
+            return false;
+        }
+        let (row, col) = self.buf.textarea.cursor();
+        if self.buf.textarea.lines()[row].chars().nth(col) != Some(ch) {
+            return false;
+        }
+        self.buf.textarea.move_cursor(CursorMove::Forward);
+        true
+    }
+
+    /// Deletes both halves of an auto-close pair (`()`, `""`, ...) when the
+    /// cursor sits between them with nothing in the middle, as right after
+    /// typing the opening one. Returns true if it did.
+    fn delete_empty_pair(&mut self) -> bool {
+        if self.buf.textarea.selection_range().is_some() {
+            return false;
+        }
+        let (row, col) = self.buf.textarea.cursor();
+        let Some(col) = col.checked_sub(1) else {
+            return false;
+        };
+        let mut around = self.buf.textarea.lines()[row].chars().skip(col);
+        let (Some(open), Some(close)) = (around.next(), around.next()) else {
+            return false;
+        };
+        if autocomplete::auto_close_pair(open) != Some(close) {
+            return false;
+        }
+        self.buf.textarea.delete_char();
+        self.buf.textarea.delete_next_char();
         self.update_modified();
         true
     }
 
     /// Auto-wraps the current line if it exceeds the visible text width.
     /// Called after text insertions to enforce line-width limits while typing.
+    /// Does nothing while auto-wrap is off or lines are soft-wrapped.
     pub(super) fn auto_wrap_line(&mut self) {
+        if !self.hard_wrapping() {
+            return;
+        }
         // Safety limit to prevent infinite loops on very long pastes
         for _ in 0..500 {
-            let (row, col) = self.textarea.cursor();
-            let lines = self.textarea.lines();
+            let (row, col) = self.buf.textarea.cursor();
+            let lines = self.buf.textarea.lines();
             if row >= lines.len() {
                 break;
             }
             let line = lines[row].to_string();
 
-            // Compute visible text width (content area minus gutter)
-            let total_lines = lines.len();
-            let gutter: usize = if self.textarea.line_number_style().is_some() {
-                (total_lines as f64).log10() as usize + 1 + 2
-            } else {
-                0
-            };
-            let text_width = (self.content_area.width as usize).saturating_sub(gutter);
+            let text_width = self.available_text_width();
             let line_chars: usize = line.chars().count();
             if text_width == 0 || line_chars <= text_width {
                 break;
             }
 
-            // Don't wrap table lines (tables are formatted separately on save)
+            // Don't wrap table lines (tables are formatted separately on save),
+            // or reference definitions and URLs, which wrapping would break
             let trimmed = line.trim_start();
-            if trimmed.starts_with('|') {
+            if trimmed.starts_with('|') || table_format::keeps_whole_line(&line) {
                 break;
             }
 
@@ -512,27 +1054,27 @@ impl<'a> App<'a> {
             };
 
             // Determine continuation indent for the new line
-            let indent = table_format::continuation_indent(&line);
+            let indent = table_format::continuation_indent(&line, self.buf.config.wrap_indent());
 
             // Split the line: move to the space, delete it, insert newline + indent
-            self.textarea
+            self.buf.textarea
                 .move_cursor(CursorMove::Jump(row as u16, break_pos as u16));
-            self.textarea.delete_next_char();
-            self.textarea.insert_newline();
+            self.buf.textarea.delete_next_char();
+            self.buf.textarea.insert_newline();
             if !indent.is_empty() {
-                self.textarea.insert_str(&indent);
+                self.buf.textarea.insert_str(&indent);
             }
 
             // Restore cursor to the equivalent position on the new line
             if col > break_pos {
                 let new_row = row + 1;
                 let new_col = indent.len() + (col - break_pos - 1);
-                let actual_len = self
+                let actual_len = self.buf
                     .textarea
                     .lines()
                     .get(new_row)
                     .map_or(0, |l| l.len());
-                self.textarea.move_cursor(CursorMove::Jump(
+                self.buf.textarea.move_cursor(CursorMove::Jump(
                     new_row as u16,
                     new_col.min(actual_len) as u16,
                 ));
@@ -540,3 +1082,26 @@ impl<'a> App<'a> {
         }
     }
 }
+
+/// Editor keys that still work read-only: moving and selecting, copying,
+/// finding, folding, and jumping around. Everything else could edit.
+fn allowed_read_only(key: KeyEvent) -> bool {
+    match (key.modifiers, key.code) {
+        (
+            _,
+            KeyCode::Left
+            | KeyCode::Right
+            | KeyCode::Up
+            | KeyCode::Down
+            | KeyCode::Home
+            | KeyCode::End
+            | KeyCode::PageUp
+            | KeyCode::PageDown
+            | KeyCode::F(7),
+        ) => true,
+        (KeyModifiers::CONTROL, KeyCode::Char(c)) => matches!(c, 'a' | 'c' | 'f' | 'g' | 'l' | 'o'),
+        (KeyModifiers::ALT, KeyCode::Char(c)) => matches!(c, 'e' | 'g' | 'j' | 'k' | 'm' | 'n' | 'z'),
+        (m, KeyCode::Char('N')) => m.contains(KeyModifiers::ALT),
+        _ => false,
+    }
+}
@@ -0,0 +1,67 @@
+//! Jump list: the cursor position before each big jump (go to line, find,
+//! the outline, conflicts, document start / end) is remembered, and
+//! Alt+Left / Alt+Right step back and forward through them like vim's
+//! Ctrl+O / Ctrl+I. (Ctrl+O opens the outline, and terminals send Ctrl+I as
+//! Tab.)
+//!
+//! Jumping somewhere new drops the positions ahead of the current one, as a
+//! browser's history does. The list is per file and not saved.
+
+use super::*;
+
+/// Most positions the jump list keeps; the oldest go first.
+const MAX_JUMPS: usize = 100;
+
+impl<'a> App<'a> {
+    // ─── Jump list (Alt+Left / Alt+Right) ────────────────────────────────
+
+    /// Remembers the cursor position before a jump.
+    pub(super) fn push_jump(&mut self) {
+        let pos = self.buf.textarea.cursor();
+        self.jump_list.truncate(self.jump_index);
+        if self.jump_list.last() != Some(&pos) {
+            self.jump_list.push(pos);
+        }
+        if self.jump_list.len() > MAX_JUMPS {
+            self.jump_list.remove(0);
+        }
+        self.jump_index = self.jump_list.len();
+    }
+
+    /// Moves back to the previous position in the jump list. Leaving the
+    /// newest end remembers the cursor so Alt+Right can return to it.
+    pub(super) fn jump_back(&mut self) {
+        if self.jump_index == self.jump_list.len() {
+            let pos = self.buf.textarea.cursor();
+            if self.jump_list.last() != Some(&pos) {
+                self.jump_list.push(pos);
+            }
+            self.jump_index = self.jump_list.len().saturating_sub(1);
+        }
+        if self.jump_index == 0 {
+            self.set_status("No earlier position");
+            return;
+        }
+        self.jump_index -= 1;
+        self.go_to_jump();
+    }
+
+    /// Moves forward again after `jump_back`.
+    pub(super) fn jump_forward(&mut self) {
+        if self.jump_index + 1 >= self.jump_list.len() {
+            self.set_status("No later position");
+            return;
+        }
+        self.jump_index += 1;
+        self.go_to_jump();
+    }
+
+    /// Puts the cursor on the jump list entry at `jump_index` (clamped to
+    /// the buffer, which may have shrunk since).
+    fn go_to_jump(&mut self) {
+        let (row, col) = self.jump_list[self.jump_index];
+        self.buf.textarea.cancel_selection();
+        self.buf.textarea.move_cursor(CursorMove::Jump(row as u16, col as u16));
+        self.reveal_cursor();
+    }
+}
@@ -0,0 +1,82 @@
+//! Clipboard history: the last `KILL_RING_SIZE` copied or cut strings, for
+//! this session only. Right after a paste, Alt+Y swaps the pasted text for
+//! the entry before it in the history, and again for the one before that,
+//! as Emacs's yank-pop does.
+//!
+//! Everything copied with Ctrl+C and everything the editor cuts (Ctrl+X,
+//! Ctrl+K, word deletions, `dd`) goes into the history. Text pasted from
+//! another program joins it too, so cycling always starts from what was
+//! pasted.
+
+use super::*;
+use std::collections::VecDeque;
+
+/// Entries kept in the clipboard history.
+pub const KILL_RING_SIZE: usize = 16;
+
+/// Where the last paste put its text, so Alt+Y can replace it.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(super) struct PasteRegion {
+    start: (usize, usize),
+    end: (usize, usize),
+    /// Index of the pasted entry in the history.
+    index: usize,
+}
+
+/// Adds `text` to the front of `ring`, unless it is already there. The
+/// oldest entries drop off past `KILL_RING_SIZE`.
+pub(super) fn push_kill(ring: &mut VecDeque<String>, text: &str) {
+    if text.is_empty() || ring.front().is_some_and(|front| front == text) {
+        return;
+    }
+    ring.push_front(text.to_string());
+    ring.truncate(KILL_RING_SIZE);
+}
+
+impl<'a> App<'a> {
+    // ─── Clipboard history (Alt+Y) ───────────────────────────────────────
+
+    /// Inserts pasted `text` at the cursor and auto-wraps it, remembering
+    /// where it went for Alt+Y.
+    pub(super) fn insert_paste(&mut self, text: &str) {
+        let index = match self.kill_ring.iter().position(|entry| entry == text) {
+            Some(index) => index,
+            None => {
+                push_kill(&mut self.kill_ring, text);
+                0
+            }
+        };
+        let start = self.buf.textarea.cursor();
+        self.buf.textarea.insert_str(text);
+        self.update_modified();
+        self.auto_wrap_line();
+        let end = self.buf.textarea.cursor();
+        self.last_paste = (start != end).then_some(PasteRegion { start, end, index });
+    }
+
+    /// Alt+Y: replaces the text just pasted with the previous entry in the
+    /// clipboard history, wrapping around to the newest.
+    pub(super) fn cycle_paste(&mut self) {
+        // Anything done since the paste (a click, say) ends the cycle
+        let cursor = self.buf.textarea.cursor();
+        let paste = self.last_paste.take();
+        let Some(paste) = paste.filter(|p| p.end == cursor && !self.kill_ring.is_empty()) else {
+            self.set_status("Alt+Y cycles what was just pasted");
+            return;
+        };
+        let index = (paste.index + 1) % self.kill_ring.len();
+        let text = self.kill_ring[index].clone();
+
+        self.buf.textarea.cancel_selection();
+        self.buf.textarea.move_cursor(CursorMove::Jump(paste.start.0 as u16, paste.start.1 as u16));
+        self.buf.textarea.start_selection();
+        self.buf.textarea.move_cursor(CursorMove::Jump(paste.end.0 as u16, paste.end.1 as u16));
+        self.buf.textarea.delete_next_char();
+        self.buf.textarea.insert_str(&text);
+        self.update_modified();
+        self.auto_wrap_line();
+        let end = self.buf.textarea.cursor();
+        self.last_paste = Some(PasteRegion { start: paste.start, end, index });
+        self.set_status(&format!("Pasted history entry {}/{}", index + 1, self.kill_ring.len()));
+    }
+}
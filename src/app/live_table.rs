@@ -0,0 +1,76 @@
+//! Live table formatting: when the cursor leaves a table row (arrowing
+//! off it, or Enter to start the next), the table around that row is
+//! reformatted the way saving does, so its columns line up while typing.
+//! Typing within a row never reformats it. Off with `live_table_format`.
+
+use super::*;
+
+/// The cell of table `line` that char column `col` is in, and how many
+/// chars into that cell's text (past its leading spaces).
+fn table_cell_at(line: &str, col: usize) -> (usize, usize) {
+    let before: String = line.chars().take(col).collect();
+    let cell = before.matches('|').count();
+    let start = before.rfind('|').map_or(0, |i| i + 1);
+    (cell, before[start..].trim_start().chars().count())
+}
+
+/// The char column `into` chars into cell `cell`'s text in table `line`,
+/// clamped to the end of the text. In a blank cell, that's just past its
+/// first space.
+pub(super) fn table_col_of(line: &str, cell: usize, into: usize) -> usize {
+    let chars: Vec<char> = line.chars().collect();
+    let start = match cell {
+        0 => 0,
+        _ => chars
+            .iter()
+            .enumerate()
+            .filter(|(_, &c)| c == '|')
+            .nth(cell - 1)
+            .map_or(chars.len(), |(i, _)| i + 1),
+    };
+    let end = chars[start..].iter().position(|&c| c == '|').map_or(chars.len(), |i| start + i);
+    let text = &chars[start..end];
+    let lead = text.iter().position(|&c| c != ' ').unwrap_or(text.len().min(1));
+    let len = text[lead..].iter().rposition(|&c| c != ' ').map_or(0, |i| i + 1);
+    start + lead + into.min(len)
+}
+
+impl<'a> App<'a> {
+    // ─── Live table formatting ───────────────────────────────────────────
+
+    /// Reformats the table around `before`'s row if the cursor just left
+    /// it. Moving to another row of the table, the cursor lands in the
+    /// same column's cell it was in at `before`.
+    pub(super) fn format_table_left(&mut self, before: (usize, usize)) {
+        let (row, before_col) = before;
+        let (cursor_row, cursor_col) = self.buf.textarea.cursor();
+        if !self.buf.config.live_table_format
+            || cursor_row == row
+            || self.is_read_only()
+            || self.buf.textarea.selection_range().is_some()
+        {
+            return;
+        }
+        let lines = self.buf.textarea.lines();
+        let is_row = |i: usize| lines.get(i).is_some_and(|line| line.contains('|'));
+        if !is_row(row) {
+            return;
+        }
+        let start = (0..row).rev().take_while(|&i| is_row(i)).last().unwrap_or(row);
+        let end = (row..lines.len()).take_while(|&i| is_row(i)).last().unwrap_or(row);
+        let table = lines[start..=end].join("\n");
+        let formatted = table_format::format_tables(&table, self.available_text_width());
+        if formatted == table {
+            return;
+        }
+
+        let in_table = (start..=end).contains(&cursor_row);
+        let cell = in_table.then(|| table_cell_at(&lines[row], before_col));
+        self.replace_lines(start, end, &formatted);
+        let col = match cell {
+            Some((cell, into)) => table_col_of(&self.buf.textarea.lines()[cursor_row], cell, into),
+            None => cursor_col,
+        };
+        self.buf.textarea.move_cursor(CursorMove::Jump(cursor_row as u16, col as u16));
+    }
+}
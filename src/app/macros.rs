@@ -0,0 +1,91 @@
+//! Keyboard macros: F3 starts and stops recording keystrokes, F4 replays
+//! them, and Shift+F4 asks how many times to replay.
+//!
+//! There is a single register. Replayed keys go through `handle_key` like
+//! typed ones, so they work in prompts and the find bar too. The macro keys
+//! themselves are never recorded and are ignored during a replay, so a
+//! macro can't trigger itself.
+
+use super::*;
+
+/// Most times one Shift+F4 replays the macro.
+const MAX_MACRO_REPEAT: usize = 1000;
+
+impl<'a> App<'a> {
+    // ─── Macros (F3 / F4) ────────────────────────────────────────────────
+
+    /// Handles the macro keys; returns true if `key` was one (and so must
+    /// not be recorded or handled further).
+    pub(super) fn handle_macro_key(&mut self, key: KeyEvent) -> bool {
+        let KeyCode::F(n @ (3 | 4)) = key.code else {
+            return false;
+        };
+        if self.replaying_macro {
+            return true;
+        }
+        match (n, key.modifiers.contains(KeyModifiers::SHIFT)) {
+            (3, _) => self.toggle_macro_recording(),
+            (4, false) => self.replay_macro(1),
+            (4, true) => {
+                if self.macro_recording.is_some() {
+                    self.set_status("Stop recording first (F3)");
+                } else if self.macro_keys.is_empty() {
+                    self.set_status("No macro recorded (F3)");
+                } else {
+                    self.start_prompt(Prompt::MacroRepeat);
+                }
+            }
+            _ => unreachable!(),
+        }
+        true
+    }
+
+    /// Records `key` if a macro is being recorded.
+    pub(super) fn record_macro_key(&mut self, key: KeyEvent) {
+        if let Some(ref mut keys) = self.macro_recording {
+            keys.push(key);
+        }
+    }
+
+    /// Starts recording, or stops and keeps what was recorded.
+    fn toggle_macro_recording(&mut self) {
+        match self.macro_recording.take() {
+            None => {
+                self.macro_recording = Some(Vec::new());
+                self.set_status("Recording macro (F3 to stop)");
+            }
+            Some(keys) if keys.is_empty() => {
+                self.set_status("Macro empty, previous one kept");
+            }
+            Some(keys) => {
+                let noun = if keys.len() == 1 { "key" } else { "keys" };
+                self.set_status(&format!("Recorded macro ({} {})", keys.len(), noun));
+                self.macro_keys = keys;
+            }
+        }
+    }
+
+    /// Feeds the recorded keys through `handle_key` `times` times (capped at
+    /// `MAX_MACRO_REPEAT`), stopping early if one of them quits.
+    pub(super) fn replay_macro(&mut self, times: usize) {
+        if self.macro_recording.is_some() {
+            self.set_status("Stop recording first (F3)");
+            return;
+        }
+        if self.macro_keys.is_empty() {
+            self.set_status("No macro recorded (F3)");
+            return;
+        }
+        let keys = self.macro_keys.clone();
+        self.replaying_macro = true;
+        'replay: for _ in 0..times.min(MAX_MACRO_REPEAT) {
+            for &key in &keys {
+                self.handle_key(key);
+                if self.should_quit {
+                    break 'replay;
+                }
+            }
+        }
+        self.replaying_macro = false;
+    }
+}
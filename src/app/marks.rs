@@ -0,0 +1,132 @@
+//! Named marks: Alt+K then a letter saves the cursor position under that
+//! letter, Alt+J then the letter jumps back to it (through the jump list,
+//! so Alt+Left returns).
+//!
+//! Marks are kept in the `.marko` sidecar directory beside the file
+//! (`.marko/marks.toml`, one table per file name) and reloaded when the file
+//! is opened. They don't follow later edits: a mark past the end of a file
+//! that has since shrunk lands on its last line.
+
+use super::*;
+use std::collections::BTreeMap;
+use std::path::Path;
+
+/// Marks of every file in a directory, by file name, then by letter.
+type MarkStore = BTreeMap<String, BTreeMap<String, (usize, usize)>>;
+
+/// The sidecar file `name` in the `.marko` directory beside `file`.
+pub(super) fn sidecar_path(file: &Path, name: &str) -> PathBuf {
+    let dir = match file.parent() {
+        Some(p) if !p.as_os_str().is_empty() => p,
+        _ => Path::new("."),
+    };
+    dir.join(".marko").join(name)
+}
+
+/// Where the marks of `file` and its siblings are stored.
+fn store_path(file: &Path) -> PathBuf {
+    sidecar_path(file, "marks.toml")
+}
+
+/// The marks table in `path`; empty if it is missing or unreadable.
+fn read_store(path: &Path) -> MarkStore {
+    std::fs::read_to_string(path)
+        .ok()
+        .and_then(|text| toml::from_str(&text).ok())
+        .unwrap_or_default()
+}
+
+/// The marks stored for `file`.
+pub(super) fn load_marks(file: &Path) -> BTreeMap<char, (usize, usize)> {
+    let Some(name) = file.file_name().and_then(|n| n.to_str()) else {
+        return BTreeMap::new();
+    };
+    read_store(&store_path(file))
+        .remove(name)
+        .unwrap_or_default()
+        .into_iter()
+        .filter_map(|(key, pos)| Some((key.chars().next()?, pos)))
+        .collect()
+}
+
+/// Replaces the stored marks of `file` with `marks` (removing its entry
+/// when there are none).
+fn store_marks(file: &Path, marks: &BTreeMap<char, (usize, usize)>) -> std::io::Result<()> {
+    let Some(name) = file.file_name().and_then(|n| n.to_str()) else {
+        return Ok(());
+    };
+    let path = store_path(file);
+    let mut store = read_store(&path);
+    if marks.is_empty() {
+        if store.remove(name).is_none() {
+            return Ok(());
+        }
+    } else {
+        let table = marks.iter().map(|(ch, &pos)| (ch.to_string(), pos)).collect();
+        store.insert(name.to_string(), table);
+    }
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    let text = toml::to_string(&store).map_err(std::io::Error::other)?;
+    std::fs::write(path, text)
+}
+
+impl<'a> App<'a> {
+    // ─── Marks (Alt+K / Alt+J) ───────────────────────────────────────────
+
+    /// Waits for the letter naming the mark to set (or jump to).
+    pub(super) fn start_mark(&mut self, op: MarkOp) {
+        self.pending_mark = Some(op);
+        let msg = match op {
+            MarkOp::Set => "Set mark: press a letter",
+            MarkOp::Jump => "Jump to mark: press a letter",
+        };
+        self.set_status(msg);
+    }
+
+    /// Handles the key after Alt+K / Alt+J: a letter sets or jumps to that
+    /// mark, anything else cancels.
+    pub(super) fn handle_mark_key(&mut self, op: MarkOp, key: KeyEvent) {
+        let letter = match (key.modifiers, key.code) {
+            (KeyModifiers::NONE | KeyModifiers::SHIFT, KeyCode::Char(ch))
+                if ch.is_ascii_alphabetic() =>
+            {
+                ch
+            }
+            _ => {
+                self.set_status("Cancelled");
+                return;
+            }
+        };
+        match op {
+            MarkOp::Set => {
+                self.buf.marks.insert(letter, self.buf.textarea.cursor());
+                match store_marks(&self.buf.file_path, &self.buf.marks) {
+                    Ok(()) => self.set_status(&format!("Mark '{}' set", letter)),
+                    Err(e) => self.set_status_as(&format!("Mark '{}' set, but not saved: {}", letter, e), Severity::Warning),
+                }
+            }
+            MarkOp::Jump => {
+                let Some(&(row, col)) = self.buf.marks.get(&letter) else {
+                    self.set_status(&format!("No mark '{}'", letter));
+                    return;
+                };
+                self.push_jump();
+                self.buf.textarea.cancel_selection();
+                self.buf.textarea.move_cursor(CursorMove::Jump(row as u16, col as u16));
+                self.reveal_cursor();
+            }
+        }
+    }
+
+    /// Moves the stored marks from `old_path` to the file's new name after a
+    /// rename.
+    pub(super) fn move_marks(&mut self, old_path: &Path) {
+        if self.buf.marks.is_empty() {
+            return;
+        }
+        let _ = store_marks(old_path, &BTreeMap::new());
+        let _ = store_marks(&self.buf.file_path, &self.buf.marks);
+    }
+}
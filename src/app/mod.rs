@@ -1,5 +1,6 @@
-use std::collections::HashMap;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet, VecDeque};
 use std::path::PathBuf;
+use std::sync::mpsc;
 use std::thread::JoinHandle;
 use std::time::{Duration, Instant};
 
@@ -7,6 +8,7 @@ use crossterm::event::{
     Event, KeyCode, KeyEvent, KeyModifiers, MouseButton, MouseEvent, MouseEventKind,
 };
 use ratatui::{
+    buffer::Buffer,
     layout::{Alignment, Constraint, Layout, Rect},
     style::{Modifier, Style},
     text::{Line, Span},
@@ -16,16 +18,28 @@ use ratatui::{
 
 use tui_textarea::{CursorMove, Input, Key, TextArea};
 
-use syntect::highlighting::ThemeSet;
+use syntect::highlighting::Theme;
 use syntect::parsing::SyntaxSet;
 
-use crate::components::{editor, header, preview, status};
-use crate::git::{self, diff::GutterMark, repo::GitRepo};
+use crate::components::{editor, header, images, preview, status};
+use crate::components::status::Severity;
+use crate::config::{Config, Editing};
+use crate::git::{self, conflict::Resolution, diff::{GutterDiff, GutterMark}, repo::GitRepo};
 use crate::markdown::autocomplete::{self, Continuation};
 use crate::markdown::code_highlight::{self, CodeFenceRegion};
+use crate::markdown::emphasis::{self, EmphasisKind};
+use crate::markdown::modeline::{self, Modeline};
+use crate::markdown::outline;
+use crate::markdown::renderer;
+#[cfg(feature = "spellcheck")]
+use crate::markdown::spellcheck;
 use crate::markdown::table_format;
+use crate::markdown::word_goal;
 use crate::pandoc;
+use crate::reveal;
+use crate::shell;
 use crate::theme;
+use crate::workspace;
 
 /// State for round-trip .docx editing.
 pub struct DocxState {
@@ -38,11 +52,18 @@ pub struct DocxState {
 /// How long status bar messages stay visible before auto-clearing.
 const STATUS_DURATION: Duration = Duration::from_secs(3);
 
+/// How long error messages stay, long enough to read a path or command.
+const ERROR_STATUS_DURATION: Duration = Duration::from_secs(10);
+
 /// Lines to scroll per mouse wheel tick in preview mode.
 const SCROLL_LINES: u16 = 3;
 
-/// Maximum time between clicks to count as multi-click (double/triple).
-const MULTI_CLICK_MS: u64 = 500;
+/// How long edits must settle before gutter marks are recomputed.
+const GUTTER_REFRESH_DELAY: Duration = Duration::from_millis(500);
+
+/// How far (in cells) a click may drift from the previous one and still count
+/// toward a double/triple-click. The time window comes from `Config::multi_click_ms`.
+const MULTI_CLICK_TOLERANCE: u16 = 1;
 
 // Header tab widths: " EDITOR " = 8, " PREVIEW " = 9
 const TAB_EDITOR_W: u16 = 8;
@@ -52,16 +73,177 @@ const TAB_TOTAL_W: u16 = TAB_EDITOR_W + TAB_PREVIEW_W;
 /// Maximum width for the UI content area. Wider terminals get centered, capped layout.
 const MAX_WIDTH: u16 = 120;
 
-/// The two top-level view modes, toggled via Tab or header tab clicks.
+/// Smallest terminal that can fit the header tabs, bars, and a few content rows.
+/// Below this, a "terminal too small" notice replaces the normal UI.
+const MIN_WIDTH: u16 = TAB_TOTAL_W + 8;
+const MIN_HEIGHT: u16 = 7;
+
+/// Direction the editor scrolls while a drag selection is held past the viewport edge.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AutoScroll {
+    Up,
+    Down,
+}
+
+/// Unit a drag selection snaps to, set by the click that started the drag.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum SelectGranularity {
+    Char,
+    Word,
+    Paragraph,
+}
+
+/// A page title fetched in the background for a URL inserted by Alt+L.
+/// `(row, col)` is where the bare URL was inserted, in `file_path`.
+struct FetchedTitle {
+    file_path: PathBuf,
+    row: usize,
+    col: usize,
+    url: String,
+    title: Option<String>,
+}
+
+/// What an open status-bar prompt is asking for, with the state needed to act
+/// on the answer.
 #[derive(Debug, Clone, PartialEq)]
-pub enum Mode {
-    Editor,
-    Preview,
+pub enum Prompt {
+    /// Language for wrapping lines `start_row..=end_row` in a fenced code block.
+    CodeFenceLanguage { start_row: usize, end_row: usize },
+    /// Source (unwrapped) line number to jump to.
+    GoToLine,
+    /// Shell command to pipe the selection (`start..end`), or the whole
+    /// buffer when None, through.
+    FilterCommand { selection: Option<((usize, usize), (usize, usize))> },
+    /// Confirmation before running the code block on lines `start..=end`
+    /// with the runner for `language`.
+    RunCodeBlock { start: usize, end: usize, language: String },
+    /// How many times to replay the recorded macro.
+    MacroRepeat,
+    /// Whether to save the other open files with unsaved changes before
+    /// quitting.
+    QuitUnsaved,
+    /// Words to aim for in this file (0 for no goal).
+    WordGoal,
+    /// A vim command line (`:w`, `:q`, ...), typed after `:` in Normal mode.
+    VimCommand,
+    /// Body rows for a new table (Ctrl+Shift+T).
+    TableRows,
+    /// Columns for a new table with `rows` body rows.
+    TableColumns { rows: usize },
 }
 
-pub struct App<'a> {
-    // --- Core state ---
-    pub mode: Mode,
+impl Prompt {
+    /// Text shown before the input.
+    pub fn label(&self) -> &'static str {
+        match self {
+            Prompt::CodeFenceLanguage { .. } => "Code block language:",
+            Prompt::GoToLine => "Go to line:",
+            Prompt::FilterCommand { .. } => "Filter through:",
+            Prompt::RunCodeBlock { .. } => "Run this code block? (y/n)",
+            Prompt::MacroRepeat => "Replay macro how many times:",
+            Prompt::QuitUnsaved => "Save changes to other open files? (y/n)",
+            Prompt::WordGoal => "Word goal (0 for none):",
+            Prompt::VimCommand => ":",
+            Prompt::TableRows => "Table rows:",
+            Prompt::TableColumns { .. } => "Table columns:",
+        }
+    }
+
+    /// Whether `ch` may be typed into the input.
+    pub fn accepts(&self, ch: char) -> bool {
+        match self {
+            Prompt::CodeFenceLanguage { .. }
+            | Prompt::FilterCommand { .. }
+            | Prompt::VimCommand => true,
+            Prompt::GoToLine
+            | Prompt::MacroRepeat
+            | Prompt::WordGoal
+            | Prompt::TableRows
+            | Prompt::TableColumns { .. } => ch.is_ascii_digit(),
+            Prompt::RunCodeBlock { .. } | Prompt::QuitUnsaved => {
+                matches!(ch, 'y' | 'Y' | 'n' | 'N')
+            }
+        }
+    }
+}
+
+/// What the letter typed after Alt+K / Alt+J does with that mark.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MarkOp {
+    Set,
+    Jump,
+}
+
+/// An edit Alt+. can repeat at the cursor (see `repeat.rs`).
+#[derive(Debug, Clone, PartialEq)]
+pub enum EditAction {
+    /// A run of typed text; `\n` for Enter.
+    Insert(String),
+    /// Ctrl+Backspace, or Ctrl+Delete / Ctrl+D when `forward`.
+    DeleteWord { forward: bool },
+    /// Alt+Q (quote) or Alt+Shift+Q (unquote).
+    Quote(bool),
+    /// Wrapping `lines` lines in a code block tagged `lang` (Alt+C).
+    WrapCodeBlock { lines: usize, lang: String },
+}
+
+/// State of the find bar (Ctrl+F). Matches are `(row, col)` char positions
+/// in the editor buffer, recomputed whenever the query or buffer changes.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SearchState {
+    pub query: String,
+    pub matches: Vec<(usize, usize)>,
+    /// Index into `matches` of the active match.
+    pub current: usize,
+    /// Byte offset of the input cursor within `query`.
+    pub cursor: usize,
+    pub case_sensitive: bool,
+}
+
+impl SearchState {
+    /// Text shown before the query: match position and case mode.
+    pub fn label(&self) -> String {
+        let case = if self.case_sensitive { " [Aa]" } else { "" };
+        if self.query.is_empty() {
+            format!("Find{}:", case)
+        } else if self.matches.is_empty() {
+            format!("Find{} (no matches):", case)
+        } else {
+            format!("Find{} ({}/{}):", case, self.current + 1, self.matches.len())
+        }
+    }
+}
+
+/// State of the replace prompt (Ctrl+R): two inputs, with Tab switching
+/// which one `cursor` (a byte offset) edits.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ReplaceState {
+    pub find: String,
+    pub replace: String,
+    /// Replace every match at once instead of one at a time.
+    pub replace_all: bool,
+    /// True while the replacement (second) input has focus.
+    pub editing_replace: bool,
+    pub cursor: usize,
+}
+
+/// State of the outline panel (Ctrl+O): the buffer's headings and the
+/// highlighted one.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct OutlineState {
+    pub headings: Vec<outline::Heading>,
+    /// Index into `headings`.
+    pub selected: usize,
+}
+
+/// Syntax colors of code fence regions, per line: [region_idx][line_offset]
+/// -> (color, text) spans.
+type FenceHighlights = Vec<Vec<Vec<(ratatui::style::Color, String)>>>;
+
+/// An open file and everything that belongs to it: its text and undo
+/// history, git state, caches, folds and marks. `App` shows `buf`; the
+/// other open files wait in `parked` until switched to (see `files.rs`).
+pub struct FileBuffer<'a> {
     pub file_path: PathBuf,
     pub textarea: TextArea<'a>,
     pub modified: bool,
@@ -69,61 +251,285 @@ pub struct App<'a> {
     pub original_content: String,
     /// `original_content` wrapped at `last_wrap_width`; used for modification detection.
     wrapped_original: String,
-    pub should_quit: bool,
+    /// True if the process can't write the file, which then opens
+    /// read-only too.
+    file_read_only: bool,
+
+    // --- Configuration ---
+    /// The user config with the file's project `.marko.toml` over it.
+    pub config: Config,
+    /// Options from the file's modeline, overriding `config` for this file.
+    pub modeline: Modeline,
+    /// Hard-wrap while typing, on save and on resize (see `hard_wrapping`).
+    /// Seeded from `config.auto_wrap`; turning it off never unwraps lines.
+    pub auto_wrap: bool,
 
     // --- Docx round-trip state ---
     pub docx_state: Option<DocxState>,
 
-    // --- Mode-specific state ---
-    pub preview: preview::PreviewState,
-
     // --- Git gutter marks ---
     pub gutter_marks: HashMap<usize, GutterMark>,
-
-    // --- Status bar ---
-    pub status_message: String,
-    pub status_time: Option<Instant>,
+    /// Line boundaries where HEAD lines were deleted (see `GutterDiff::deletions`).
+    pub gutter_deletions: Vec<usize>,
+    /// Rev the gutter diffs against, once `config.diff_base` has resolved;
+    /// None for HEAD.
+    pub diff_base: Option<String>,
+    /// When the buffer last changed with gutter marks not yet recomputed.
+    last_edit: Option<Instant>,
 
     // --- Git integration ---
     pub git_repo: Option<GitRepo>,
     pub git_branch: String,
     pub git_file_status: String,
 
+    // --- Marks (Alt+K / Alt+J) ---
+    /// Named cursor positions, saved to the `.marko` sidecar directory.
+    pub marks: BTreeMap<char, (usize, usize)>,
+
+    // --- Word goal (Alt+G) ---
+    /// Goal set with Alt+G, overriding the file's and the config's; 0 for
+    /// none at all.
+    pub word_goal: Option<usize>,
+
+    // --- Focus timer (F6) ---
+    /// Writing time on this file, counting while the timer runs.
+    focus: focus::FocusTimer,
+
+    // --- View position ---
+    /// Tracks tui-textarea's scroll position for mouse click → buffer position math.
+    editor_scroll_top: u16,
+    /// The preview's scroll and collapsed sections, kept here while the
+    /// file is parked (`PreviewState` holds the shown file's).
+    preview_scroll: u16,
+    preview_collapsed: HashSet<usize>,
+
+    // --- Wrap/reflow tracking ---
+    /// Text width used for the last hard_wrap, so we can detect resize and reflow.
+    last_wrap_width: usize,
+
+    // --- Background initialization ---
+    gutter_handle: Option<JoinHandle<GutterDiff>>,
+
+    // --- Syntax highlighting cache ---
+    code_fence_regions: Vec<CodeFenceRegion>,
+    /// Pre-computed highlight spans per region, per line: [region_idx][line_offset] -> spans.
+    code_fence_highlights: FenceHighlights,
+    /// Background highlighting of every region, applied by `tick` when done.
+    code_fence_handle: Option<JoinHandle<FenceHighlights>>,
+    code_fence_dirty: bool,
+    /// With `highlight_current_fence_only`, the region whose highlights are
+    /// cached (the cursor's when last rendered).
+    code_fence_focus: Option<usize>,
+
+    // --- Folding ---
+    /// Opening fence lines of folded code blocks.
+    folded: BTreeSet<usize>,
+    /// Buffer as of the last fold update, to tell where an edit happened.
+    fold_snapshot: Vec<String>,
+
+    // --- Undo ---
+    /// `replace_lines` calls undo (and redo) can still reach, newest last.
+    /// Each made two textarea edits that undo and redo take as one step.
+    undone_replaces: Vec<transform::LineReplace>,
+    replaces: Vec<transform::LineReplace>,
+}
+
+/// The top-level view modes: Editor and Preview are toggled via Tab or
+/// header tab clicks, Split (both side by side) via Alt+V.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Mode {
+    Editor,
+    Preview,
+    Split,
+}
+
+pub struct App<'a> {
+    // --- Core state ---
+    pub mode: Mode,
+    /// The file shown.
+    pub buf: FileBuffer<'a>,
+    pub should_quit: bool,
+    /// Set by `--read-only`: no file can be edited or saved.
+    pub read_only: bool,
+    /// Set when Ctrl+Q failed to save; a second Ctrl+Q right after quits
+    /// without saving.
+    quit_pending: bool,
+    /// Files given on the command line, in order (includes the current file).
+    /// Cycled with Ctrl+PageUp / Ctrl+PageDown.
+    pub file_list: Vec<PathBuf>,
+    /// The other files switched away from, with their unsaved edits.
+    parked: Vec<FileBuffer<'a>>,
+
+    // --- Mode-specific state ---
+    pub preview: preview::PreviewState,
+
+    // --- Status bar ---
+    pub status_message: String,
+    pub status_time: Option<Instant>,
+    pub status_severity: Severity,
+    /// Recent status messages, newest first, with when each was set.
+    status_log: VecDeque<(Instant, String, Severity)>,
+
     // --- Rename mode (Ctrl+T or click filename) ---
     pub renaming: bool,
     pub rename_buf: String,
     pub rename_cursor: usize,
+    /// Full path the rename would produce, recomputed on every keystroke.
+    pub rename_target: Option<PathBuf>,
+    /// True when `rename_target` already exists (renaming would fail or clobber).
+    pub rename_conflict: bool,
+
+    // --- Header path display (Alt+P) ---
+    /// Workspace-relative path shown in the header instead of the bare
+    /// filename, or None to show just the filename.
+    pub header_path: Option<String>,
+
+    // --- Theme (F8) ---
+    /// The theme chosen by config or F8, shown for files whose modeline
+    /// doesn't name one. Set the first time a file is shown.
+    session_theme: Option<&'static theme::Theme>,
 
     // --- Help modal (F1) ---
     pub show_help: bool,
+    /// Rows the help modal is scrolled down by (Up/Down, PgUp/PgDn).
+    pub help_scroll: u16,
+    /// Rows of help visible in the last render; the PgUp/PgDn step.
+    help_page: u16,
+
+    // --- Outline panel (Ctrl+O) ---
+    pub outline: Option<OutlineState>,
+
+    // --- Message log (F2) ---
+    pub show_status_log: bool,
+
+    // --- Element preview (Alt+E) ---
+    /// Source of the block shown rendered in the popup, while it's open.
+    pub element_preview: Option<String>,
+
+    // --- Keyboard macro (F3 / F4) ---
+    /// Keys recorded so far while recording, None otherwise.
+    macro_recording: Option<Vec<KeyEvent>>,
+    /// The last recorded macro.
+    macro_keys: Vec<KeyEvent>,
+    /// True while a macro replays (macro keys are ignored meanwhile).
+    replaying_macro: bool,
+
+    // --- Jump list (Alt+Left / Alt+Right) ---
+    /// Cursor positions from before big jumps, oldest first.
+    jump_list: Vec<(usize, usize)>,
+    /// Position in `jump_list` that Alt+Left / Alt+Right step from; its
+    /// length when not stepping.
+    jump_index: usize,
+
+    // --- Marks (Alt+K / Alt+J) ---
+    /// Set after Alt+K / Alt+J while waiting for the mark's letter.
+    pending_mark: Option<MarkOp>,
+
+    // --- Repeat last edit (Alt+.) ---
+    pub last_action: Option<EditAction>,
+    /// Cursor after the last keystroke of a typed run; typing from here
+    /// extends the run.
+    insert_end: Option<(usize, usize)>,
+
+    // --- Clipboard history (Alt+Y) ---
+    /// Recently copied and cut text, newest first.
+    pub kill_ring: VecDeque<String>,
+    /// The last paste, while Alt+Y may still replace it.
+    last_paste: Option<kill_ring::PasteRegion>,
+
+    // --- Rectangular selection (Alt+drag, Alt+Shift+arrows) ---
+    /// The selected block of columns, if any.
+    pub rect_selection: Option<rect_select::RectSelection>,
+    /// Corner the block was started from.
+    rect_anchor: (usize, usize),
+    /// Corner that moves; may be past the end of its line.
+    rect_head: (usize, usize),
+
+    // --- Goal column (Up / Down) ---
+    /// Where the last vertical move left the cursor, and the column it was
+    /// aiming for.
+    goal_column: Option<((usize, usize), usize)>,
+
+    // --- Vim editing (`editing = "vim"`) ---
+    /// In Normal mode: keys move and run commands instead of typing.
+    pub vim_normal: bool,
+    /// First key of a two-key Normal mode command (the `d` of `dd`).
+    vim_pending: Option<char>,
+
+    // --- Spell check (F7) ---
+    /// Loaded on first use: the checker, or why no dictionary could be read.
+    #[cfg(feature = "spellcheck")]
+    spell: Option<Result<spellcheck::Checker, String>>,
+
+    // --- Find bar (Ctrl+F) ---
+    pub search: Option<SearchState>,
+
+    // --- Replace prompt (Ctrl+R) ---
+    pub replace: Option<ReplaceState>,
+
+    // --- Status-bar prompt (e.g. Alt+C code block language) ---
+    pub prompt: Option<Prompt>,
+    pub prompt_buf: String,
+    pub prompt_cursor: usize,
+
+    // --- Link insertion (Alt+L) ---
+    /// Cloned into each title-fetch thread; results drained in tick().
+    link_title_tx: mpsc::Sender<FetchedTitle>,
+    link_title_rx: mpsc::Receiver<FetchedTitle>,
 
     // --- Internal tracking ---
     viewport_height: u16,
     /// Cached content area rect from last render (used for mouse hit-testing).
+    /// In split view, just the editor pane.
     content_area: Rect,
-    /// Tracks tui-textarea's scroll position for mouse click → buffer position math.
-    editor_scroll_top: u16,
+    /// Preview pane from last render in split view; empty otherwise.
+    preview_area: Rect,
+    /// True if the last render had room for the split view.
+    split_fits: bool,
+    /// Off-screen buffer the soft-wrapped editor renders into, reused across frames.
+    laid_out_buffer: Buffer,
+    /// Cursor line the split view's preview last scrolled to.
+    split_synced_row: Option<usize>,
     /// True while left mouse button is held down for drag selection.
     mouse_dragging: bool,
+    /// True while an Alt+drag is selecting a block.
+    rect_dragging: bool,
+    /// Set while a drag is held above/below the content area; tick() keeps
+    /// moving the cursor (extending the selection) in that direction.
+    drag_auto_scroll: Option<AutoScroll>,
+    /// True while the left button is held on the editor scrollbar.
+    scrollbar_dragging: bool,
+    /// Whether the current drag extends by characters, words, or paragraphs.
+    drag_granularity: SelectGranularity,
+    /// Selection made by the initiating double/triple-click (start, end),
+    /// kept selected while a granular drag extends past it.
+    drag_origin: ((usize, usize), (usize, usize)),
     /// Timestamp of last left-click in content area, for double/triple-click detection.
     last_click_time: Option<Instant>,
     /// Terminal position of last click, for multi-click detection.
     last_click_pos: (u16, u16),
-    /// Click count (1=single, 2=double, 3=triple), resets on timeout or position change.
+    /// Click count (1=single, 2=double, 3=triple, 4=quadruple), resets on timeout or position change.
     click_count: u8,
 
-    // --- Wrap/reflow tracking ---
-    /// Text width used for the last hard_wrap, so we can detect resize and reflow.
-    last_wrap_width: usize,
-
-    // --- Background initialization ---
-    gutter_handle: Option<JoinHandle<HashMap<usize, GutterMark>>>,
+    // --- Inline images ---
+    /// Thumbnails drawn under image references in the editor.
+    inline_images: images::Placements,
+}
 
-    // --- Syntax highlighting cache ---
-    code_fence_regions: Vec<CodeFenceRegion>,
-    /// Pre-computed highlight spans per region, per line: [region_idx][line_offset] -> spans.
-    code_fence_highlights: Vec<Vec<Vec<(ratatui::style::Color, String)>>>,
-    code_fence_dirty: bool,
+/// Status shown when a file opens read-only because it can't be written.
+const READ_ONLY_NOTE: &str = "Read-only: no permission to write this file";
+
+/// False if `path` exists but can't be written: it has no write permission
+/// bits, or opening it for writing is denied.
+fn is_writable(path: &std::path::Path) -> bool {
+    let Ok(meta) = std::fs::metadata(path) else {
+        return true;
+    };
+    !meta.permissions().readonly()
+        && std::fs::OpenOptions::new()
+            .append(true)
+            .open(path)
+            .map_or_else(|e| e.kind() != std::io::ErrorKind::PermissionDenied, |_| true)
 }
 
 /// Classifies a character for word-boundary detection (double-click selection).
@@ -140,87 +546,86 @@ fn char_class(c: char) -> u8 {
 
 impl<'a> App<'a> {
     pub fn new(file_path: PathBuf) -> Self {
-        let content = std::fs::read_to_string(&file_path).unwrap_or_default();
-
-        // Content is loaded raw here; wrapping to fit the terminal width
-        // is deferred to the first render() call where we have the actual
-        // content_area dimensions (last_wrap_width = 0 forces this).
-        let lines: Vec<String> = if content.is_empty() {
-            vec![String::new()]
-        } else {
-            content.lines().map(String::from).collect()
-        };
-
-        let mut textarea = TextArea::new(lines.clone());
-        editor::configure_textarea(&mut textarea);
-
-        // Try to open the git repo for branch/status/gutter info
-        let git_repo = GitRepo::open(&file_path);
-        let git_branch = git_repo
-            .as_ref()
-            .map(|g| g.branch_name())
-            .unwrap_or_default();
-        let git_file_status = git_repo
-            .as_ref()
-            .map(|g| g.file_status(&file_path))
-            .unwrap_or_default();
-
-        // Spawn background thread for gutter marks (expensive git diff)
-        let gutter_handle = if git_repo.is_some() {
-            let fp = file_path.clone();
-            Some(std::thread::spawn(move || {
-                match git2::Repository::discover(&fp) {
-                    Ok(repo) => git::diff::compute_gutter_marks(&repo, &fp),
-                    Err(_) => HashMap::new(),
-                }
-            }))
-        } else {
-            None
-        };
-
-        // Code fence regions found immediately (cheap), but highlights deferred
-        // until syntect finishes loading in background (code_fence_dirty=true).
-        let code_fence_regions = code_highlight::find_code_fence_regions(&lines);
+        let buf = FileBuffer::load(file_path, Config::default());
+        let (link_title_tx, link_title_rx) = mpsc::channel();
 
         Self {
             mode: Mode::Editor,
-            file_path,
-            textarea,
-            modified: false,
-            original_content: content.clone(),
-            wrapped_original: content,
+            file_list: vec![buf.file_path.clone()],
+            parked: Vec::new(),
             should_quit: false,
-            docx_state: None,
+            read_only: false,
+            quit_pending: false,
             preview: preview::PreviewState::new(),
-            gutter_marks: HashMap::new(),
-            status_message: "F1: help | Tab: switch mode | Ctrl+S: save | Ctrl+Q: quit"
-                .to_string(),
+            status_message: if buf.file_read_only {
+                READ_ONLY_NOTE.to_string()
+            } else {
+                Config::default().startup_hint(false)
+            },
             status_time: Some(Instant::now()),
-            git_repo,
-            git_branch,
-            git_file_status,
+            status_severity: Severity::Info,
+            status_log: VecDeque::new(),
+            buf,
             renaming: false,
             rename_buf: String::new(),
             rename_cursor: 0,
+            rename_target: None,
+            rename_conflict: false,
+            header_path: None,
+            session_theme: None,
             show_help: false,
+            help_scroll: 0,
+            help_page: 1,
+            show_status_log: false,
+            element_preview: None,
+            outline: None,
+            macro_recording: None,
+            macro_keys: Vec::new(),
+            replaying_macro: false,
+            pending_mark: None,
+            jump_list: Vec::new(),
+            jump_index: 0,
+            last_action: None,
+            insert_end: None,
+            kill_ring: VecDeque::new(),
+            last_paste: None,
+            rect_selection: None,
+            rect_anchor: (0, 0),
+            rect_head: (0, 0),
+            goal_column: None,
+            vim_normal: false,
+            vim_pending: None,
+            #[cfg(feature = "spellcheck")]
+            spell: None,
+            search: None,
+            replace: None,
+            prompt: None,
+            prompt_buf: String::new(),
+            prompt_cursor: 0,
+            link_title_tx,
+            link_title_rx,
             viewport_height: 0,
             content_area: Rect::default(),
-            editor_scroll_top: 0,
+            laid_out_buffer: Buffer::empty(Rect::default()),
+            preview_area: Rect::default(),
+            split_fits: true,
+            split_synced_row: None,
             mouse_dragging: false,
+            rect_dragging: false,
+            drag_auto_scroll: None,
+            scrollbar_dragging: false,
+            drag_granularity: SelectGranularity::Char,
+            drag_origin: ((0, 0), (0, 0)),
             last_click_time: None,
             last_click_pos: (0, 0),
             click_count: 0,
-            last_wrap_width: 0,
-            gutter_handle,
-            code_fence_regions,
-            code_fence_highlights: vec![],
-            code_fence_dirty: true,
+            inline_images: images::Placements::default(),
         }
     }
 
     /// Returns the full editor content as a single string.
     fn textarea_content(&self) -> String {
-        self.textarea.lines().join("\n")
+        self.buf.textarea.lines().join("\n")
     }
 
     // ─── Tick / timers ───────────────────────────────────────────────────
@@ -228,22 +633,65 @@ impl<'a> App<'a> {
     /// Called every 100ms from the main loop. Handles timer-based state cleanup.
     pub fn tick(&mut self) {
         // Drain decoded images from background threads
-        self.preview.poll_decoded_images();
+        for path in self.preview.poll_decoded_images() {
+            self.inline_images.invalidate(&path);
+        }
 
         // Poll background gutter marks computation
-        if let Some(ref handle) = self.gutter_handle {
+        if let Some(ref handle) = self.buf.gutter_handle {
             if handle.is_finished() {
-                if let Some(handle) = self.gutter_handle.take() {
-                    if let Ok(marks) = handle.join() {
-                        self.gutter_marks = marks;
+                if let Some(handle) = self.buf.gutter_handle.take() {
+                    if let Ok(gutter) = handle.join() {
+                        self.buf.gutter_marks = gutter.marks;
+                        self.buf.gutter_deletions = gutter.deletions;
                     }
                 }
             }
         }
 
-        // Auto-clear status messages after STATUS_DURATION
+        // Pick up code block colors highlighted in the background
+        if self.buf.code_fence_handle.as_ref().is_some_and(|h| h.is_finished()) {
+            if let Some(Ok(highlights)) = self.buf.code_fence_handle.take().map(JoinHandle::join) {
+                self.buf.code_fence_highlights = highlights;
+            }
+        }
+
+        // Once edits settle, recompute gutter marks against the unsaved buffer
+        let settled = self.buf
+            .last_edit
+            .is_some_and(|t| t.elapsed() >= GUTTER_REFRESH_DELAY);
+        if settled && self.buf.gutter_handle.is_none() {
+            self.buf.last_edit = None;
+            self.spawn_gutter_refresh();
+        }
+
+        // Turn bare URLs into [Title](url) as their page titles arrive
+        while let Ok(fetched) = self.link_title_rx.try_recv() {
+            self.apply_fetched_title(fetched);
+        }
+
+        // Edge auto-scroll: extend the drag selection one line per tick, by
+        // the drag's granularity
+        if let Some(direction) = self.drag_auto_scroll.filter(|_| self.mouse_dragging) {
+            let (row, col) = self.buf.textarea.cursor();
+            let row = match direction {
+                AutoScroll::Up => row.saturating_sub(1),
+                AutoScroll::Down => (row + 1).min(self.buf.textarea.lines().len() - 1),
+            };
+            let col = col.min(self.buf.textarea.lines()[row].chars().count());
+            self.extend_drag_selection(row, col);
+        }
+
+        self.tick_focus_timer();
+
+        // Auto-clear status messages after STATUS_DURATION, or
+        // ERROR_STATUS_DURATION for errors
         if let Some(time) = self.status_time {
-            if time.elapsed() >= STATUS_DURATION {
+            let duration = match self.status_severity {
+                Severity::Error => ERROR_STATUS_DURATION,
+                _ => STATUS_DURATION,
+            };
+            if time.elapsed() >= duration {
                 self.status_message.clear();
                 self.status_time = None;
             }
@@ -267,65 +715,228 @@ impl<'a> App<'a> {
 
     /// Counts the total number of words in the editor.
     fn word_count(&self) -> usize {
-        self.textarea
+        self.buf.textarea
             .lines()
             .iter()
             .map(|line| line.split_whitespace().count())
             .sum()
     }
 
+    /// Estimated minutes to read the document at 200 words a minute,
+    /// rounded.
+    fn reading_minutes(&self) -> usize {
+        (self.word_count() + 100) / 200
+    }
+
+    /// The word goal in effect: the one set with Alt+G, else the modeline's,
+    /// the front matter's, or the config's, in that order.
+    fn effective_word_goal(&self) -> Option<usize> {
+        match self.buf.word_goal {
+            Some(0) => None,
+            Some(goal) => Some(goal),
+            None => self.buf
+                .modeline
+                .goal
+                .or_else(|| word_goal::front_matter_goal(self.buf.textarea.lines()))
+                .or(self.buf.config.word_goal)
+                .filter(|&goal| goal > 0),
+        }
+    }
+
+    /// Called after an edit: counts it as an undo step (see
+    /// `transform::LineReplace`), then refreshes the `modified` flag.
+    fn update_modified(&mut self) {
+        // The edit is one more undo step above the last replace, and
+        // nothing undone can be redone past it
+        if let Some(replace) = self.buf.replaces.last_mut() {
+            replace.above += 1;
+        }
+        self.buf.undone_replaces.clear();
+        self.refresh_modified();
+    }
+
     /// Recomputes the `modified` flag by comparing current content to the
     /// wrapped original (original_content wrapped at last_wrap_width).
-    fn update_modified(&mut self) {
-        self.modified = self.textarea.lines().join("\n") != self.wrapped_original;
-        self.code_fence_dirty = true;
+    /// Undo and redo call this directly; they aren't new edits.
+    fn refresh_modified(&mut self) {
+        self.buf.modified = self.buf.textarea.lines().join("\n") != self.buf.wrapped_original;
+        self.buf.code_fence_dirty = true;
+        self.adjust_folds_after_edit();
+        if self.buf.git_repo.is_some() {
+            self.buf.last_edit = Some(Instant::now());
+        }
     }
 
-    /// Switches to a new mode, resetting scroll as needed.
+    /// Switches to a new mode, resetting scroll as needed. The preview opens
+    /// scrolled to the cursor's block (Home still goes to the top).
     fn set_mode(&mut self, target: Mode) {
         if self.mode == target {
             return;
         }
-        if target == Mode::Preview {
-            self.preview.scroll_offset = 0;
+        match target {
+            Mode::Preview => {
+                let (line, height) = self.preview_line_at_cursor(self.content_area.width);
+                let max_scroll = height.saturating_sub(self.viewport_height);
+                self.preview.scroll_offset = line.min(max_scroll);
+            }
+            Mode::Split => {
+                self.preview.scroll_offset = 0;
+                self.split_synced_row = None;
+            }
+            Mode::Editor => {}
         }
         self.mode = target;
     }
 
+    /// The preview line where the cursor's source line is rendered, and the
+    /// preview's height in lines, for a preview `width` columns wide.
+    fn preview_line_at_cursor(&self, width: u16) -> (u16, u16) {
+        let content = self.textarea_content();
+        let rendered = renderer::render_markdown(&content, width.saturating_sub(2) as usize);
+        let line = renderer::rendered_line_of(&rendered.source_line_map, self.buf.textarea.cursor().0);
+        (line as u16, rendered.text.lines.len() as u16)
+    }
+
     /// Recomputes gutter marks from the git HEAD version of the file.
     fn refresh_gutter_marks(&mut self) {
         // Discard any pending background computation
-        self.gutter_handle = None;
-        if let Some(ref git_repo) = self.git_repo {
-            self.gutter_marks =
-                git::diff::compute_gutter_marks(git_repo.repository(), &self.file_path);
+        self.buf.gutter_handle = None;
+        self.buf.last_edit = None;
+        if let Some(ref git_repo) = self.buf.git_repo {
+            let base = self.buf.diff_base.as_deref();
+            let gutter =
+                git::diff::compute_gutter_diff(git_repo.repository(), &self.buf.file_path, base);
+            self.buf.gutter_marks = gutter.marks;
+            self.buf.gutter_deletions = gutter.deletions;
         } else {
-            self.gutter_marks.clear();
+            self.buf.gutter_marks.clear();
+            self.buf.gutter_deletions.clear();
+        }
+    }
+
+    /// Recomputes gutter marks in the background, diffing what would be saved
+    /// (the buffer once edited, else the file as loaded) against HEAD.
+    fn spawn_gutter_refresh(&mut self) {
+        let content = if self.buf.modified {
+            self.textarea_content()
+        } else {
+            self.buf.original_content.clone()
+        };
+        let fp = self.buf.file_path.clone();
+        let base = self.buf.diff_base.clone();
+        self.buf.gutter_handle = Some(std::thread::spawn(move || {
+            match git2::Repository::discover(&fp) {
+                Ok(repo) => git::diff::compute_gutter_diff_for_content(
+                    &repo,
+                    &fp,
+                    &content,
+                    base.as_deref(),
+                ),
+                Err(_) => GutterDiff::default(),
+            }
+        }));
+    }
+
+    /// Re-diffs the gutter against `config.diff_base` once the config is
+    /// loaded (the initial diff is against HEAD), reporting a rev git can't
+    /// resolve.
+    pub fn apply_diff_base(&mut self) {
+        let (Some(base), Some(git_repo)) = (&self.buf.config.diff_base, &self.buf.git_repo) else {
+            return;
+        };
+        if !git_repo.resolves(base) {
+            let message = format!("Unknown diff base '{}', using HEAD", base);
+            self.set_status(&message);
+            return;
         }
+        self.buf.diff_base = Some(base.clone());
+        self.spawn_gutter_refresh();
     }
 
     /// Refreshes the git file status indicator in the status bar.
     fn refresh_git_status(&mut self) {
-        if let Some(ref git_repo) = self.git_repo {
-            self.git_file_status = git_repo.file_status(&self.file_path);
+        if let Some(ref git_repo) = self.buf.git_repo {
+            self.buf.git_file_status = git_repo.file_status(&self.buf.file_path);
         }
     }
 
     /// Shows a temporary message in the status bar.
     pub fn set_status(&mut self, msg: &str) {
+        self.set_status_as(msg, Severity::Info);
+    }
+
+    /// Shows a temporary message in the status bar, colored by `severity`.
+    pub fn set_status_as(&mut self, msg: &str, severity: Severity) {
         self.status_message = msg.to_string();
         self.status_time = Some(Instant::now());
+        self.status_severity = severity;
+        status_log::push_status(&mut self.status_log, msg, severity);
     }
 
-    /// Computes the available text width from the current content_area and gutter.
+    /// Shows the configured startup hint (see `Config::startup_hint`).
+    /// The read-only note, if the file opened read-only, takes precedence.
+    pub fn show_startup_hint(&mut self, first_run: bool) {
+        if self.buf.file_read_only {
+            return;
+        }
+        let hint = self.buf.config.startup_hint(first_run);
+        self.set_status(&hint);
+    }
+
+    /// True when the shown file can't be edited: `--read-only`, or the file
+    /// isn't writable.
+    pub fn is_read_only(&self) -> bool {
+        self.read_only || self.buf.file_read_only
+    }
+
+    /// True when long lines are hard-wrapped into the buffer: auto-wrap is on
+    /// and lines aren't soft-wrapped on screen instead.
+    pub(super) fn hard_wrapping(&self) -> bool {
+        self.buf.auto_wrap && !self.soft_wrap()
+    }
+
+    /// `config.soft_wrap`, unless the modeline says otherwise.
+    pub(super) fn soft_wrap(&self) -> bool {
+        self.buf.modeline.soft_wrap.unwrap_or(self.buf.config.soft_wrap)
+    }
+
+    /// `config.format_on_save`, unless the modeline says otherwise.
+    pub(super) fn format_on_save(&self) -> bool {
+        self.buf.modeline.format_on_save.unwrap_or(self.buf.config.format_on_save)
+    }
+
+    /// Computes the wrap width: the visible text width (content_area minus
+    /// gutter and scrollbar), capped at the modeline's `wrap` or `config.wrap_width` when
+    /// set.
     pub(super) fn available_text_width(&self) -> usize {
-        let total_lines = self.textarea.lines().len();
-        let gutter = if self.textarea.line_number_style().is_some() {
+        let total_lines = self.buf.textarea.lines().len();
+        let gutter = if self.buf.textarea.line_number_style().is_some() {
             (total_lines as f64).log10() as usize + 1 + 2
         } else {
             0
         };
-        (self.content_area.width as usize).saturating_sub(gutter)
+        let scrollbar = usize::from(editor::scrollbar_shown(self.content_area, total_lines));
+        let visible = (self.content_area.width as usize).saturating_sub(gutter + scrollbar);
+        match self.buf.modeline.wrap.or(self.buf.config.wrap_width) {
+            Some(configured) if configured > 0 => visible.min(configured),
+            _ => visible,
+        }
+    }
+
+    /// Replaces the editor buffer with `lines` in a freshly configured
+    /// textarea, putting the cursor back at `cursor` (clamped to new bounds).
+    pub(super) fn rebuild_textarea(&mut self, lines: Vec<String>, cursor: (usize, usize)) {
+        let mut textarea = TextArea::new(lines);
+        editor::configure_textarea(&mut textarea);
+
+        self.buf.textarea = textarea;
+
+        let max_row = self.buf.textarea.lines().len().saturating_sub(1);
+        let row = cursor.0.min(max_row);
+        let max_col = self.buf.textarea.lines().get(row).map_or(0, |l| l.len());
+        let col = cursor.1.min(max_col);
+        self.buf.textarea
+            .move_cursor(CursorMove::Jump(row as u16, col as u16));
     }
 
     /// Re-wraps all editor content to `new_width`, preserving cursor position.
@@ -337,17 +948,17 @@ impl<'a> App<'a> {
         }
 
         // Save cursor position
-        let (cursor_row, cursor_col) = self.textarea.cursor();
+        let (cursor_row, cursor_col) = self.buf.textarea.cursor();
 
         // When unmodified, re-wrap from the raw original so wider terminals
         // can "unwrap" lines that were split for a narrower viewport.
         // When the user has edits, best-effort re-wrap from current content.
-        let source = if self.modified {
+        let source = if self.buf.modified {
             self.textarea_content()
         } else {
-            self.original_content.clone()
+            self.buf.original_content.clone()
         };
-        let wrapped = table_format::hard_wrap(&source, new_width);
+        let wrapped = table_format::hard_wrap(&source, new_width, self.buf.config.wrap_indent());
 
         let lines: Vec<String> = if wrapped.is_empty() {
             vec![String::new()]
@@ -355,35 +966,53 @@ impl<'a> App<'a> {
             wrapped.lines().map(String::from).collect()
         };
 
-        // Recreate textarea with wrapped content
-        let mut textarea = TextArea::new(lines);
-        editor::configure_textarea(&mut textarea);
-
-        self.textarea = textarea;
-
-        // Restore cursor position (clamped to new bounds)
-        let max_row = self.textarea.lines().len().saturating_sub(1);
-        let row = cursor_row.min(max_row);
-        let max_col = self.textarea.lines().get(row).map_or(0, |l| l.len());
-        let col = cursor_col.min(max_col);
-        self.textarea
-            .move_cursor(CursorMove::Jump(row as u16, col as u16));
+        if lines != self.buf.textarea.lines() {
+            self.clear_folds();
+        }
+        self.rebuild_textarea(lines, (cursor_row, cursor_col));
 
         // Update tracking state — keep original_content raw (never wrap it).
         // Cache the wrapped version for modification detection.
-        self.wrapped_original = table_format::hard_wrap(&self.original_content, new_width);
-        self.last_wrap_width = new_width;
-        self.code_fence_dirty = true;
+        self.buf.wrapped_original = table_format::hard_wrap(&self.buf.original_content, new_width, self.buf.config.wrap_indent());
+        self.buf.last_wrap_width = new_width;
+        self.buf.code_fence_dirty = true;
         self.update_modified();
     }
 }
 
 mod clipboard;
+mod conflict;
+mod element_preview;
+mod files;
+mod filter;
+mod focus;
+mod fold;
+mod goal_column;
 mod input;
+mod jumps;
+mod kill_ring;
+mod live_table;
+mod macros;
+mod marks;
+mod new_table;
+mod outline_panel;
+mod prompt;
 mod render;
+mod rect_select;
 mod rename;
+mod repeat;
+mod run_block;
+mod replace;
 mod save;
+mod search;
 mod selection;
+mod soft_wrap;
+#[cfg(feature = "spellcheck")]
+mod spell;
+mod split;
+mod status_log;
+mod transform;
+mod vim;
 
 #[cfg(test)]
 mod tests;
@@ -0,0 +1,67 @@
+//! New table (Ctrl+Shift+T): asks for the number of body rows, then of
+//! columns, and inserts an empty table of that size at the cursor,
+//! formatted to the text width, with the cursor in its first header cell.
+
+use super::*;
+use super::live_table::table_col_of;
+
+/// Most body rows or columns a new table can have.
+const MAX_NEW_TABLE_SIZE: usize = 50;
+
+/// An empty table: a header row, the separator and `rows` body rows, each
+/// with `columns` blank cells.
+fn table_skeleton(rows: usize, columns: usize) -> String {
+    let row = format!("|{}", "   |".repeat(columns));
+    let separator = format!("|{}", "---|".repeat(columns));
+    let mut lines = vec![row.clone(), separator];
+    lines.extend(std::iter::repeat_n(row, rows));
+    lines.join("\n")
+}
+
+impl<'a> App<'a> {
+    // ─── New table (Ctrl+Shift+T) ────────────────────────────────────────
+
+    /// Reads a row or column count typed into the new table prompt. Empty
+    /// input cancels quietly; out of range counts say so.
+    pub(super) fn parse_table_size(&mut self, input: &str, min: usize) -> Option<usize> {
+        if input.is_empty() {
+            return None;
+        }
+        match input.parse::<usize>() {
+            Ok(n) if (min..=MAX_NEW_TABLE_SIZE).contains(&n) => Some(n),
+            _ => {
+                self.set_status_as(
+                    &format!("A table needs {}-{}", min, MAX_NEW_TABLE_SIZE),
+                    Severity::Warning,
+                );
+                None
+            }
+        }
+    }
+
+    /// Inserts an empty `rows` by `columns` table. On a blank line it
+    /// takes that line; otherwise it goes below the cursor's line, after a
+    /// blank one, since a table can't start mid-paragraph.
+    pub(super) fn insert_table(&mut self, rows: usize, columns: usize) {
+        let table = table_format::format_tables(&table_skeleton(rows, columns), self.available_text_width());
+        let (row, _) = self.buf.textarea.cursor();
+        let lines = self.buf.textarea.lines();
+        let current = lines[row].clone();
+        let next_blank = lines.get(row + 1).is_none_or(|line| line.trim().is_empty());
+
+        let (header_row, mut text) = if current.trim().is_empty() {
+            (row, table)
+        } else {
+            (row + 2, format!("{}\n\n{}", current, table))
+        };
+        if !next_blank {
+            text.push('\n');
+        }
+        self.buf.textarea.cancel_selection();
+        self.replace_lines(row, row, &text);
+
+        let col = table_col_of(&self.buf.textarea.lines()[header_row], 1, 0);
+        self.buf.textarea.move_cursor(CursorMove::Jump(header_row as u16, col as u16));
+        self.set_status(&format!("Inserted a {}x{} table", rows, columns));
+    }
+}
@@ -0,0 +1,101 @@
+//! Outline panel (Ctrl+O): a modal listing the buffer's headings, indented
+//! by level. Up/Down move the highlight, Enter jumps the cursor to the
+//! heading, Esc (or Ctrl+O again) closes it.
+
+use super::*;
+
+/// Headings skipped by PageUp / PageDown in the outline panel.
+const OUTLINE_PAGE: usize = 10;
+
+impl<'a> App<'a> {
+    // ─── Outline panel (Ctrl+O) ──────────────────────────────────────────
+
+    /// Opens the outline panel with the heading of the cursor's section
+    /// highlighted.
+    pub(super) fn open_outline(&mut self) {
+        let headings = outline::headings(self.buf.textarea.lines());
+        if headings.is_empty() {
+            self.set_status("No headings");
+            return;
+        }
+        let row = self.buf.textarea.cursor().0;
+        let selected = headings.iter().rposition(|h| h.line <= row).unwrap_or(0);
+        self.outline = Some(OutlineState { headings, selected });
+    }
+
+    /// Outline panel key handler: all keys go here while it is open.
+    pub(super) fn handle_outline_key(&mut self, key: KeyEvent) {
+        let Some(state) = self.outline.as_mut() else {
+            return;
+        };
+        let last = state.headings.len() - 1;
+        match (key.modifiers, key.code) {
+            (_, KeyCode::Esc) | (KeyModifiers::CONTROL, KeyCode::Char('o')) => {
+                self.outline = None;
+            }
+            (_, KeyCode::Up) => state.selected = state.selected.saturating_sub(1),
+            (_, KeyCode::Down) => state.selected = (state.selected + 1).min(last),
+            (_, KeyCode::PageUp) => state.selected = state.selected.saturating_sub(OUTLINE_PAGE),
+            (_, KeyCode::PageDown) => state.selected = (state.selected + OUTLINE_PAGE).min(last),
+            (_, KeyCode::Home) => state.selected = 0,
+            (_, KeyCode::End) => state.selected = last,
+            (_, KeyCode::Enter) => {
+                let line = state.headings[state.selected].line;
+                self.outline = None;
+                self.push_jump();
+                self.buf.textarea.cancel_selection();
+                self.buf.textarea.move_cursor(CursorMove::Jump(line as u16, 0));
+            }
+            _ => {}
+        }
+    }
+
+    /// Renders the outline panel as a centered modal, scrolled to keep the
+    /// highlighted heading in view.
+    pub(super) fn render_outline(&self, frame: &mut Frame) {
+        let Some(ref state) = self.outline else {
+            return;
+        };
+        let area = frame.area();
+        let width = 50u16.min(area.width.saturating_sub(4));
+        let height = (state.headings.len() as u16 + 2).min(area.height.saturating_sub(2));
+        let x = (area.width.saturating_sub(width)) / 2;
+        let y = (area.height.saturating_sub(height)) / 2;
+        let panel_area = Rect::new(x, y, width, height);
+
+        frame.render_widget(Clear, panel_area);
+
+        // Center the highlight once the list is taller than the panel
+        let rows = height.saturating_sub(2) as usize;
+        let max_offset = state.headings.len().saturating_sub(rows);
+        let offset = state.selected.saturating_sub(rows / 2).min(max_offset);
+
+        let lines: Vec<Line> = state
+            .headings
+            .iter()
+            .enumerate()
+            .skip(offset)
+            .take(rows)
+            .map(|(i, heading)| {
+                let indent = "  ".repeat(heading.level - 1);
+                let style = if i == state.selected {
+                    Style::default()
+                        .fg(theme::active().white)
+                        .bg(theme::active().active_tab)
+                        .add_modifier(Modifier::BOLD)
+                } else {
+                    Style::default().fg(theme::active().heading_levels[heading.level - 1])
+                };
+                Line::from(Span::styled(format!(" {}{} ", indent, heading.title), style))
+            })
+            .collect();
+
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(theme::active().border))
+            .title(" Outline ")
+            .style(Style::default().fg(theme::active().fg).bg(theme::active().bar_bg));
+
+        frame.render_widget(Paragraph::new(lines).block(block), panel_area);
+    }
+}
@@ -0,0 +1,176 @@
+//! Single-line input prompt shown in place of the status bar, used by
+//! commands that need a short answer (e.g. the language for a code block,
+//! the line number for Ctrl+G, or a shell command to filter through).
+//!
+//! Enter confirms and dispatches on the `Prompt` kind, Esc cancels.
+
+use super::*;
+
+impl<'a> App<'a> {
+    // ─── Prompt ──────────────────────────────────────────────────────────
+
+    /// Opens `prompt` with an empty input.
+    pub(super) fn start_prompt(&mut self, prompt: Prompt) {
+        self.prompt = Some(prompt);
+        self.prompt_buf.clear();
+        self.prompt_cursor = 0;
+    }
+
+    /// Handles keypresses while a prompt is open. `prompt_cursor` is a byte
+    /// offset that always sits on a char boundary.
+    pub(super) fn handle_prompt_key(&mut self, key: KeyEvent) {
+        match key.code {
+            KeyCode::Esc => {
+                self.prompt = None;
+                self.prompt_buf.clear();
+                self.set_status("Cancelled");
+            }
+            KeyCode::Enter => {
+                if let Some(prompt) = self.prompt.take() {
+                    let input = std::mem::take(&mut self.prompt_buf);
+                    self.confirm_prompt(prompt, input.trim());
+                }
+            }
+            KeyCode::Backspace if self.prompt_cursor > 0 => {
+                let prev = self.prompt_buf[..self.prompt_cursor]
+                    .chars()
+                    .next_back()
+                    .map_or(0, char::len_utf8);
+                self.prompt_cursor -= prev;
+                self.prompt_buf.remove(self.prompt_cursor);
+            }
+            KeyCode::Delete if self.prompt_cursor < self.prompt_buf.len() => {
+                self.prompt_buf.remove(self.prompt_cursor);
+            }
+            KeyCode::Left if self.prompt_cursor > 0 => {
+                let prev = self.prompt_buf[..self.prompt_cursor]
+                    .chars()
+                    .next_back()
+                    .map_or(0, char::len_utf8);
+                self.prompt_cursor -= prev;
+            }
+            KeyCode::Right if self.prompt_cursor < self.prompt_buf.len() => {
+                let next = self.prompt_buf[self.prompt_cursor..]
+                    .chars()
+                    .next()
+                    .map_or(0, char::len_utf8);
+                self.prompt_cursor += next;
+            }
+            KeyCode::Home => {
+                self.prompt_cursor = 0;
+            }
+            KeyCode::End => {
+                self.prompt_cursor = self.prompt_buf.len();
+            }
+            KeyCode::Char(ch) if self.prompt.as_ref().is_some_and(|p| p.accepts(ch)) => {
+                self.prompt_buf.insert(self.prompt_cursor, ch);
+                self.prompt_cursor += ch.len_utf8();
+            }
+            _ => {}
+        }
+    }
+
+    /// Inserts pasted text into the prompt input (newlines dropped).
+    pub(super) fn handle_prompt_paste(&mut self, text: &str) {
+        let Some(ref prompt) = self.prompt else {
+            return;
+        };
+        for ch in text.chars().filter(|&c| c != '\n' && c != '\r' && prompt.accepts(c)) {
+            self.prompt_buf.insert(self.prompt_cursor, ch);
+            self.prompt_cursor += ch.len_utf8();
+        }
+    }
+
+    /// Acts on a confirmed prompt.
+    fn confirm_prompt(&mut self, prompt: Prompt, input: &str) {
+        match prompt {
+            Prompt::CodeFenceLanguage { start_row, end_row } => {
+                self.wrap_lines_in_code_block(start_row, end_row, input);
+                self.last_action = Some(EditAction::WrapCodeBlock {
+                    lines: end_row - start_row + 1,
+                    lang: input.to_string(),
+                });
+            }
+            Prompt::GoToLine => self.go_to_line(input),
+            Prompt::FilterCommand { selection } => self.filter_through(input, selection),
+            Prompt::RunCodeBlock { start, end, language } => {
+                if input.eq_ignore_ascii_case("y") {
+                    self.run_code_block(start, end, &language);
+                } else {
+                    self.set_status("Cancelled");
+                }
+            }
+            Prompt::QuitUnsaved => {
+                if input.eq_ignore_ascii_case("y") {
+                    self.quit_if_saved(true);
+                } else if input.eq_ignore_ascii_case("n") {
+                    self.quit_if_saved(false);
+                } else {
+                    self.set_status("Cancelled");
+                }
+            }
+            Prompt::MacroRepeat => {
+                if let Ok(times) = input.parse::<usize>() {
+                    self.replay_macro(times);
+                }
+            }
+            Prompt::WordGoal => self.set_word_goal(input),
+            Prompt::VimCommand => self.run_vim_command(input),
+            Prompt::TableRows => {
+                if let Some(rows) = self.parse_table_size(input, 0) {
+                    self.start_prompt(Prompt::TableColumns { rows });
+                }
+            }
+            Prompt::TableColumns { rows } => {
+                if let Some(columns) = self.parse_table_size(input, 1) {
+                    self.insert_table(rows, columns);
+                }
+            }
+        }
+    }
+
+    /// Sets the word goal shown in the status bar: `input` words, or none
+    /// for 0 (even if the front matter sets one). Empty input changes
+    /// nothing.
+    fn set_word_goal(&mut self, input: &str) {
+        let Ok(goal) = input.parse::<usize>() else {
+            return;
+        };
+        self.buf.word_goal = Some(goal);
+        if goal == 0 {
+            self.set_status("Word goal cleared");
+        } else {
+            self.set_status(&format!("Word goal: {} words", goal));
+        }
+    }
+
+    /// Jumps to 1-based source line `input`. Lines are numbered as in the
+    /// unwrapped source (the file on disk, or the edited buffer once modified),
+    /// then mapped to the hard-wrapped row the same way `reflow_content` wraps.
+    fn go_to_line(&mut self, input: &str) {
+        if input.is_empty() {
+            return;
+        }
+        let source = if self.buf.modified {
+            self.textarea_content()
+        } else {
+            self.buf.original_content.clone()
+        };
+        let line_count = source.lines().count().max(1);
+        let line = match input.parse::<usize>() {
+            Ok(n) if (1..=line_count).contains(&n) => n,
+            _ => {
+                self.set_status(&format!("No line {} (1-{})", input, line_count));
+                return;
+            }
+        };
+
+        // The edited buffer is already wrapped: its lines are the rows
+        let width = if self.buf.modified { 0 } else { self.buf.last_wrap_width };
+        let max_row = self.buf.textarea.lines().len().saturating_sub(1);
+        let row = table_format::wrapped_row(&source, line - 1, width, self.buf.config.wrap_indent()).min(max_row);
+        self.push_jump();
+        self.buf.textarea.cancel_selection();
+        self.buf.textarea.move_cursor(CursorMove::Jump(row as u16, 0));
+    }
+}
@@ -0,0 +1,202 @@
+//! Rectangular (column) selection, for tables and aligned text: Alt+drag
+//! or Alt+Shift+arrows select the same columns on a run of lines, past the
+//! end of short ones too.
+//!
+//! While a block is selected, typing replaces its columns on every line
+//! (a zero-width block inserts on each), Backspace and Delete remove them
+//! (or the char before / after the column), and Ctrl+C / Ctrl+X / Ctrl+V
+//! copy, cut and paste it, one clipboard line per line. Each edit goes in
+//! as one replacement of the lines, so a single undo reverts it. Esc or
+//! any other key drops the block.
+
+use super::*;
+
+/// A block of columns `left..right` (chars, `right` exclusive) on lines
+/// `top..=bottom`. `left == right` is a column cursor.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RectSelection {
+    pub top: usize,
+    pub left: usize,
+    pub bottom: usize,
+    pub right: usize,
+}
+
+impl RectSelection {
+    /// The block between two corners.
+    pub fn new(anchor: (usize, usize), head: (usize, usize)) -> Self {
+        Self {
+            top: anchor.0.min(head.0),
+            left: anchor.1.min(head.1),
+            bottom: anchor.0.max(head.0),
+            right: anchor.1.max(head.1),
+        }
+    }
+}
+
+/// The chars of `line` in columns `left..right`; short lines give less.
+fn column_slice(line: &str, left: usize, right: usize) -> String {
+    line.chars().skip(left).take(right - left).collect()
+}
+
+/// `line` with columns `left..right` replaced by `text`. A line too short
+/// to reach `left` is padded with spaces first, unless `text` is empty.
+fn replace_columns(line: &str, left: usize, right: usize, text: &str) -> String {
+    let chars: Vec<char> = line.chars().collect();
+    let mut out: String = chars.iter().take(left).collect();
+    if !text.is_empty() {
+        out.extend(std::iter::repeat_n(' ', left.saturating_sub(chars.len())));
+        out.push_str(text);
+    }
+    out.extend(chars.iter().skip(right));
+    out
+}
+
+impl<'a> App<'a> {
+    // ─── Rectangular selection ───────────────────────────────────────────
+
+    /// Selects the block between `anchor` and `head`, with the cursor at
+    /// `head` (as near as the line allows).
+    pub(super) fn set_rect_selection(&mut self, anchor: (usize, usize), head: (usize, usize)) {
+        let last = self.buf.textarea.lines().len() - 1;
+        let (anchor, head) = ((anchor.0.min(last), anchor.1), (head.0.min(last), head.1));
+        self.rect_anchor = anchor;
+        self.rect_head = head;
+        self.rect_selection = Some(RectSelection::new(anchor, head));
+        self.buf.textarea.cancel_selection();
+        self.buf.textarea.move_cursor(CursorMove::Jump(head.0 as u16, head.1 as u16));
+    }
+
+    /// Handles a key while a block is selected, or Alt+Shift+arrows to
+    /// start or grow one. Returns true if the key was used; any other key
+    /// drops the block and goes on as usual.
+    pub(super) fn handle_rect_key(&mut self, key: KeyEvent) -> bool {
+        if key.modifiers == KeyModifiers::ALT | KeyModifiers::SHIFT {
+            let (row, col) = match self.rect_selection {
+                Some(_) => self.rect_head,
+                None => self.buf.textarea.cursor(),
+            };
+            let head = match key.code {
+                KeyCode::Up => (row.saturating_sub(1), col),
+                KeyCode::Down => (row + 1, col),
+                KeyCode::Left => (row, col.saturating_sub(1)),
+                KeyCode::Right => (row, col + 1),
+                _ => return false,
+            };
+            let anchor = match self.rect_selection {
+                Some(_) => self.rect_anchor,
+                None => (row, col),
+            };
+            self.set_rect_selection(anchor, head);
+            return true;
+        }
+        let Some(rect) = self.rect_selection else {
+            return false;
+        };
+        let edits = matches!(
+            (key.modifiers, key.code),
+            (KeyModifiers::NONE | KeyModifiers::SHIFT, KeyCode::Char(_))
+                | (KeyModifiers::NONE, KeyCode::Backspace | KeyCode::Delete)
+                | (KeyModifiers::CONTROL, KeyCode::Char('x' | 'v'))
+        );
+        if edits && self.is_read_only() {
+            self.set_status("Read-only");
+            return true;
+        }
+        match (key.modifiers, key.code) {
+            (KeyModifiers::NONE, KeyCode::Esc) => self.rect_selection = None,
+            (KeyModifiers::CONTROL, KeyCode::Char('c')) => self.copy_rect(rect),
+            (KeyModifiers::CONTROL, KeyCode::Char('x')) => {
+                self.copy_rect(rect);
+                self.replace_rect(rect, rect.left, rect.right, &[""]);
+            }
+            (KeyModifiers::CONTROL, KeyCode::Char('v')) => match self.paste_from_clipboard() {
+                Some(text) => self.paste_rect(rect, &text),
+                None => self.set_status("Clipboard has no text"),
+            },
+            (KeyModifiers::NONE | KeyModifiers::SHIFT, KeyCode::Char(ch)) => {
+                self.replace_rect(rect, rect.left, rect.right, &[&ch.to_string()]);
+            }
+            (KeyModifiers::NONE, KeyCode::Backspace) => {
+                let left = if rect.left == rect.right { rect.left.saturating_sub(1) } else { rect.left };
+                self.replace_rect(rect, left, rect.right, &[""]);
+            }
+            (KeyModifiers::NONE, KeyCode::Delete) => {
+                let right = if rect.left == rect.right { rect.right + 1 } else { rect.right };
+                self.replace_rect(rect, rect.left, right, &[""]);
+            }
+            _ => {
+                self.rect_selection = None;
+                return false;
+            }
+        }
+        true
+    }
+
+    /// Copies the block's columns, one line per line of the block.
+    fn copy_rect(&mut self, rect: RectSelection) {
+        let text = self.buf.textarea.lines()[rect.top..=rect.bottom]
+            .iter()
+            .map(|line| column_slice(line, rect.left, rect.right))
+            .collect::<Vec<_>>()
+            .join("\n");
+        self.copy_to_clipboard(&text);
+        self.set_status(&format!("Copied {} lines of columns", rect.bottom - rect.top + 1));
+    }
+
+    /// Pastes `text` over the block: one line of it per line, or the same
+    /// line on all of them if it has only one. A block shorter than the
+    /// text grows down to fit it.
+    fn paste_rect(&mut self, rect: RectSelection, text: &str) {
+        let pasted: Vec<&str> = text.lines().collect();
+        let last = self.buf.textarea.lines().len() - 1;
+        let bottom = (rect.top + pasted.len().saturating_sub(1)).max(rect.bottom).min(last);
+        self.replace_rect(RectSelection { bottom, ..rect }, rect.left, rect.right, &pasted);
+    }
+
+    /// Replaces columns `left..right` on each of the block's lines with
+    /// the matching line of `texts` (all with `texts[0]` if it has one
+    /// line, or nothing past its end), leaving a column cursor after the
+    /// first line's text.
+    fn replace_rect(&mut self, rect: RectSelection, left: usize, right: usize, texts: &[&str]) {
+        let lines: Vec<String> = (rect.top..=rect.bottom)
+            .map(|row| {
+                let text = match texts {
+                    [one] => one,
+                    _ => texts.get(row - rect.top).copied().unwrap_or(""),
+                };
+                replace_columns(&self.buf.textarea.lines()[row], left, right, text)
+            })
+            .collect();
+        self.replace_lines(rect.top, rect.bottom, &lines.join("\n"));
+        let col = left + texts.first().map_or(0, |text| text.chars().count());
+        self.set_rect_selection((rect.top, col), (rect.bottom, col));
+    }
+
+    /// Shades the selected block. `area` is where the textarea was
+    /// rendered, with its first row showing line `editor_scroll_top`; a
+    /// column cursor shades one cell per line.
+    pub(super) fn apply_rect_selection(&self, buf: &mut Buffer, area: Rect, gutter_width: u16) {
+        let Some(rect) = self.rect_selection else {
+            return;
+        };
+        let scroll_top = self.buf.editor_scroll_top as usize;
+        let text_start_x = area.x + gutter_width + 1;
+        let right = rect.right.max(rect.left + 1);
+        for row in rect.top.max(scroll_top)..=rect.bottom {
+            if row >= scroll_top + area.height as usize {
+                break;
+            }
+            let screen_row = area.y + (row - scroll_top) as u16;
+            for col in rect.left..right {
+                let x = text_start_x as usize + col;
+                if x >= (area.x + area.width) as usize {
+                    break;
+                }
+                if let Some(cell) = buf.cell_mut((x as u16, screen_row)) {
+                    cell.set_bg(theme::active().selection);
+                }
+            }
+        }
+    }
+}
+
@@ -11,10 +11,10 @@ impl<'a> App<'a> {
     /// Enter rename mode: populates the rename buffer with the current filename
     /// and places the cursor at the end.
     pub(super) fn start_rename(&mut self) {
-        let source_path = if let Some(ref ds) = self.docx_state {
+        let source_path = if let Some(ref ds) = self.buf.docx_state {
             &ds.docx_path
         } else {
-            &self.file_path
+            &self.buf.file_path
         };
         let filename = source_path
             .file_name()
@@ -24,6 +24,25 @@ impl<'a> App<'a> {
         self.rename_buf = filename;
         self.rename_cursor = self.rename_buf.len();
         self.renaming = true;
+        self.update_rename_preview();
+    }
+
+    /// Recomputes the full target path for the current rename buffer and
+    /// whether something other than the file being renamed already lives there.
+    fn update_rename_preview(&mut self) {
+        let source_path = match self.buf.docx_state {
+            Some(ref ds) => &ds.docx_path,
+            None => &self.buf.file_path,
+        };
+        let new_name = self.rename_buf.trim();
+        if new_name.is_empty() {
+            self.rename_target = None;
+            self.rename_conflict = false;
+            return;
+        }
+        let target = source_path.with_file_name(new_name);
+        self.rename_conflict = target != *source_path && target.exists();
+        self.rename_target = Some(target);
     }
 
     /// Handles keypresses while in rename mode.
@@ -37,26 +56,18 @@ impl<'a> App<'a> {
             KeyCode::Enter => {
                 self.confirm_rename();
             }
-            KeyCode::Backspace => {
-                if self.rename_cursor > 0 {
-                    self.rename_cursor -= 1;
-                    self.rename_buf.remove(self.rename_cursor);
-                }
+            KeyCode::Backspace if self.rename_cursor > 0 => {
+                self.rename_cursor -= 1;
+                self.rename_buf.remove(self.rename_cursor);
             }
-            KeyCode::Delete => {
-                if self.rename_cursor < self.rename_buf.len() {
-                    self.rename_buf.remove(self.rename_cursor);
-                }
+            KeyCode::Delete if self.rename_cursor < self.rename_buf.len() => {
+                self.rename_buf.remove(self.rename_cursor);
             }
-            KeyCode::Left => {
-                if self.rename_cursor > 0 {
-                    self.rename_cursor -= 1;
-                }
+            KeyCode::Left if self.rename_cursor > 0 => {
+                self.rename_cursor -= 1;
             }
-            KeyCode::Right => {
-                if self.rename_cursor < self.rename_buf.len() {
-                    self.rename_cursor += 1;
-                }
+            KeyCode::Right if self.rename_cursor < self.rename_buf.len() => {
+                self.rename_cursor += 1;
             }
             KeyCode::Home => {
                 self.rename_cursor = 0;
@@ -64,15 +75,16 @@ impl<'a> App<'a> {
             KeyCode::End => {
                 self.rename_cursor = self.rename_buf.len();
             }
-            KeyCode::Char(ch) => {
-                // Reject path separators to keep the name a bare filename
-                if ch != '/' && ch != '\\' {
-                    self.rename_buf.insert(self.rename_cursor, ch);
-                    self.rename_cursor += 1;
-                }
+            // Reject path separators to keep the name a bare filename
+            KeyCode::Char(ch) if ch != '/' && ch != '\\' => {
+                self.rename_buf.insert(self.rename_cursor, ch);
+                self.rename_cursor += 1;
             }
             _ => {}
         }
+        if self.renaming {
+            self.update_rename_preview();
+        }
     }
 
     /// Performs the actual file rename via fs::rename, updates internal state.
@@ -85,7 +97,7 @@ impl<'a> App<'a> {
             return;
         }
 
-        if let Some(ref ds) = self.docx_state {
+        if let Some(ref ds) = self.buf.docx_state {
             // Docx mode: the user is renaming the .docx file
             let current_name = ds
                 .docx_path
@@ -107,27 +119,33 @@ impl<'a> App<'a> {
             match std::fs::rename(&ds.docx_path, &new_docx_path) {
                 Ok(_) => {
                     // Rename the .md file too
-                    let md_renamed = std::fs::rename(&self.file_path, &new_md_path);
-                    self.file_path = new_md_path;
-                    self.docx_state = Some(DocxState {
+                    let md_renamed = std::fs::rename(&self.buf.file_path, &new_md_path);
+                    self.update_file_list_entry(&new_md_path);
+                    let old_path = std::mem::replace(&mut self.buf.file_path, new_md_path);
+                    self.move_marks(&old_path);
+                    focus::move_focus_time(&old_path, &self.buf.file_path);
+                    self.buf.docx_state = Some(DocxState {
                         docx_path: new_docx_path.clone(),
                         reference_doc: new_docx_path,
                     });
                     if md_renamed.is_ok() {
                         self.set_status("Renamed");
                     } else {
-                        self.set_status("Renamed .docx (but .md rename failed)");
+                        self.set_status_as("Renamed .docx (but .md rename failed)", Severity::Error);
                     }
                     self.refresh_git_status();
                     self.refresh_gutter_marks();
+                    if self.header_path.is_some() {
+                        self.refresh_header_path();
+                    }
                 }
                 Err(e) => {
-                    self.set_status(&format!("Rename failed: {}", e));
+                    self.set_status_as(&format!("Rename failed: {}", e), Severity::Error);
                 }
             }
         } else {
             // Regular .md mode
-            let current_name = self
+            let current_name = self.buf
                 .file_path
                 .file_name()
                 .and_then(|n| n.to_str())
@@ -139,16 +157,22 @@ impl<'a> App<'a> {
                 return;
             }
 
-            let new_path = self.file_path.with_file_name(&new_name);
-            match std::fs::rename(&self.file_path, &new_path) {
+            let new_path = self.buf.file_path.with_file_name(&new_name);
+            match std::fs::rename(&self.buf.file_path, &new_path) {
                 Ok(_) => {
-                    self.file_path = new_path;
+                    self.update_file_list_entry(&new_path);
+                    let old_path = std::mem::replace(&mut self.buf.file_path, new_path);
+                    self.move_marks(&old_path);
+                    focus::move_focus_time(&old_path, &self.buf.file_path);
                     self.set_status("Renamed");
                     self.refresh_git_status();
                     self.refresh_gutter_marks();
+                    if self.header_path.is_some() {
+                        self.refresh_header_path();
+                    }
                 }
                 Err(e) => {
-                    self.set_status(&format!("Rename failed: {}", e));
+                    self.set_status_as(&format!("Rename failed: {}", e), Severity::Error);
                 }
             }
         }
@@ -3,15 +3,20 @@
 
 use super::*;
 
+/// Most rows an inline image thumbnail takes up in the editor.
+const THUMBNAIL_ROWS: usize = 4;
+
+/// Width of an inline image thumbnail in the editor.
+const THUMBNAIL_COLS: u16 = 16;
+
 /// Pre-computes syntax highlighting for all code fence regions.
 /// Returns a parallel vec: [region_idx][line_offset] -> Vec<(fg_color, text)>.
 pub(super) fn highlight_code_regions(
     regions: &[CodeFenceRegion],
     lines: &[String],
     syntax_set: &SyntaxSet,
-    theme_set: &ThemeSet,
-) -> Vec<Vec<Vec<(ratatui::style::Color, String)>>> {
-    let syntax_theme = &theme_set.themes["base16-ocean.dark"];
+    syntax_theme: &Theme,
+) -> FenceHighlights {
     let mut all_highlights = Vec::with_capacity(regions.len());
 
     for region in regions {
@@ -62,6 +67,55 @@ pub(super) fn highlight_code_regions(
     all_highlights
 }
 
+/// Like `highlight_code_regions`, splitting the regions across a thread per
+/// core: they're highlighted independently of each other.
+pub(super) fn highlight_code_regions_in_parallel(
+    regions: &[CodeFenceRegion],
+    lines: &[String],
+    syntax_set: &SyntaxSet,
+    syntax_theme: &Theme,
+) -> FenceHighlights {
+    let threads = std::thread::available_parallelism().map_or(1, usize::from);
+    if threads < 2 || regions.len() < 2 {
+        return highlight_code_regions(regions, lines, syntax_set, syntax_theme);
+    }
+    let chunk_size = regions.len().div_ceil(threads);
+    std::thread::scope(|scope| {
+        let chunks: Vec<_> = regions
+            .chunks(chunk_size)
+            .map(|chunk| scope.spawn(move || highlight_code_regions(chunk, lines, syntax_set, syntax_theme)))
+            .collect();
+        chunks
+            .into_iter()
+            .flat_map(|chunk| chunk.join().unwrap_or_default())
+            .collect()
+    })
+}
+
+/// Renders vim-style `~` markers on the editor rows from `first_row` down,
+/// which lie past the end of the file.
+pub(super) fn render_tildes(frame: &mut Frame, area: Rect, first_row: u16, gutter_width: u16) {
+    for row in first_row..area.height {
+        let tilde_area = Rect {
+            x: area.x,
+            y: area.y + row,
+            width: area.width,
+            height: 1,
+        };
+        let tilde = Paragraph::new(Line::from(vec![
+            Span::styled(
+                " ".repeat(gutter_width as usize),
+                Style::default().fg(theme::active().tilde),
+            ),
+            Span::styled(
+                "~",
+                Style::default().fg(theme::active().tilde),
+            ),
+        ]));
+        frame.render_widget(tilde, tilde_area);
+    }
+}
+
 impl<'a> App<'a> {
     /// Runs one frame of the main loop: draw + tick.
     /// This is the canonical render path -- tested by render_test to ensure
@@ -75,6 +129,43 @@ impl<'a> App<'a> {
         Ok(())
     }
 
+    // ─── Theme (F8) ──────────────────────────────────────────────────────
+
+    /// Switches to the next built-in UI theme for this session.
+    pub(super) fn cycle_theme(&mut self) {
+        let next = theme::active().next();
+        self.session_theme = Some(next);
+        self.set_theme(next);
+        self.set_status(&format!("Theme: {}", next.name));
+    }
+
+    /// Draws with the theme the shown file's modeline names, or else the
+    /// session's. Called whenever a file is shown.
+    pub fn apply_file_theme(&mut self) {
+        let session = *self.session_theme.get_or_insert_with(theme::active);
+        let wanted = self
+            .buf
+            .modeline
+            .theme
+            .as_deref()
+            .and_then(theme::Theme::by_name)
+            .unwrap_or(session);
+        if wanted.name != theme::active().name {
+            self.set_theme(wanted);
+        }
+    }
+
+    fn set_theme(&mut self, theme: &'static theme::Theme) {
+        theme::set_active(theme);
+        // The textareas and highlighted code blocks hold on to old colors
+        editor::configure_textarea(&mut self.buf.textarea);
+        for buffer in &mut self.parked {
+            editor::configure_textarea(&mut buffer.textarea);
+            buffer.code_fence_dirty = true;
+        }
+        self.buf.code_fence_dirty = true;
+    }
+
     // ─── Rendering ───────────────────────────────────────────────────────
 
     pub fn render(&mut self, frame: &mut Frame) {
@@ -84,6 +175,15 @@ impl<'a> App<'a> {
         let bg = Paragraph::new("").style(theme::editor_style());
         frame.render_widget(bg, full);
 
+        if full.width < MIN_WIDTH || full.height < MIN_HEIGHT {
+            // Drop the cached content area so mouse events hit nothing until
+            // the terminal is large enough to lay out the real UI again.
+            self.content_area = Rect::default();
+            self.viewport_height = 0;
+            self.render_too_small(frame);
+            return;
+        }
+
         // Cap width and center horizontally
         let capped_width = full.width.min(MAX_WIDTH);
         let x_offset = (full.width - capped_width) / 2;
@@ -99,40 +199,55 @@ impl<'a> App<'a> {
         .split(usable_area);
 
         self.viewport_height = chunks[2].height;
-        self.content_area = chunks[2];
+        self.split_fits = chunks[2].width >= split::SPLIT_MIN_WIDTH;
+        let split = self.mode == Mode::Split && self.split_fits;
+        let (editor_pane, divider, preview_pane) = Self::split_panes(chunks[2]);
+        if split {
+            self.content_area = editor_pane;
+            self.preview_area = preview_pane;
+        } else {
+            self.content_area = chunks[2];
+            self.preview_area = Rect::default();
+        }
 
-        // Reflow editor content if terminal width changed
+        // Reflow editor content if terminal width changed (unless auto-wrap is off)
         let current_text_width = self.available_text_width();
-        if current_text_width > 0 && current_text_width != self.last_wrap_width {
+        if self.hard_wrapping()
+            && current_text_width > 0
+            && current_text_width != self.buf.last_wrap_width
+        {
             self.reflow_content(current_text_width);
         }
 
-        // Header bar: filename (or rename input) + mode tabs
+        // Header bar: filename or relative path (or rename input) + mode tabs
         // When editing a .docx, show the .docx filename instead of the .md sibling
-        let filename = if let Some(ref ds) = self.docx_state {
-            ds.docx_path
-                .file_name()
-                .and_then(|n| n.to_str())
-                .unwrap_or("untitled")
+        let filename = match self.header_path {
+            Some(ref path) => path.as_str(),
+            None => self.display_name(),
+        };
+        // "2/3" next to the name when several files are open
+        let buffer_position = Some(self.buffer_position()).filter(|&(_, count)| count > 1);
+        // Too narrow to split: just the editor shows
+        let shown_mode = if self.mode == Mode::Split && !split {
+            Mode::Editor
         } else {
-            self.file_path
-                .file_name()
-                .and_then(|n| n.to_str())
-                .unwrap_or("untitled")
+            self.mode.clone()
         };
         header::render(
             frame,
             chunks[0],
             filename,
-            self.modified,
-            &self.mode,
+            self.buf.modified,
+            self.is_read_only(),
+            buffer_position,
+            &shown_mode,
             self.renaming,
             &self.rename_buf,
             self.rename_cursor,
         );
 
         // Thin dividers between bars and content
-        let divider_style = Style::default().fg(theme::BORDER);
+        let divider_style = Style::default().fg(theme::active().border);
         let top_divider = Paragraph::new("\u{2500}".repeat(chunks[1].width as usize))
             .style(divider_style);
         frame.render_widget(top_divider, chunks[1]);
@@ -145,198 +260,694 @@ impl<'a> App<'a> {
             Mode::Editor => {
                 self.render_editor(frame, chunks[2]);
             }
+            Mode::Split if split => {
+                self.render_split(frame, editor_pane, divider, preview_pane);
+            }
+            Mode::Split => {
+                self.render_editor(frame, chunks[2]);
+            }
             Mode::Preview => {
                 let content = self.textarea_content();
-                let base_dir = self.file_path.parent().unwrap_or(std::path::Path::new("."));
+                let base_dir = self.buf.file_path.parent().unwrap_or(std::path::Path::new("."));
                 preview::render(frame, chunks[2], &content, &mut self.preview, base_dir);
             }
         }
+        // Drop editor thumbnails that weren't drawn this frame
+        self.inline_images.finish_frame();
+
+        // Status bar: prompt, find or replace input while one is open, otherwise cursor
+        // position, word count, save status
+        if let Some(ref prompt) = self.prompt {
+            status::render_prompt(
+                frame,
+                chunks[4],
+                prompt.label(),
+                &self.prompt_buf,
+                self.prompt_cursor,
+            );
+        } else if let Some(ref search) = self.search {
+            status::render_prompt(
+                frame,
+                chunks[4],
+                &search.label(),
+                &search.query,
+                search.cursor,
+            );
+        } else if let Some(ref replace) = self.replace {
+            status::render_replace_prompt(
+                frame,
+                chunks[4],
+                &replace.find,
+                &replace.replace,
+                replace.editing_replace,
+                replace.cursor,
+                replace.replace_all,
+            );
+        } else {
+            self.render_status(frame, chunks[4]);
+        }
 
-        // Status bar: cursor position, word count, save status
-        let (line, col) = self.textarea.cursor();
+        // Outline panel, then the help modal on top of everything
+        self.render_outline(frame);
+        self.render_element_preview(frame);
+        if self.show_status_log {
+            self.render_status_log(frame);
+        }
+        if self.show_help {
+            self.render_help(frame);
+        }
+    }
+
+    /// Renders the normal status bar: cursor position, scroll position,
+    /// message, word count, and save status.
+    fn render_status(&self, frame: &mut Frame, area: Rect) {
+        if self.renaming {
+            if let Some(ref target) = self.rename_target {
+                let target = target.display().to_string();
+                status::render_rename_preview(frame, area, &target, self.rename_conflict);
+                return;
+            }
+        }
+        let (line, col) = self.buf.textarea.cursor();
+        let focus_elapsed = self.focus_elapsed();
+        let position = match self.mode {
+            Mode::Editor | Mode::Split => status::scroll_position(
+                self.buf.editor_scroll_top as usize,
+                self.viewport_height as usize,
+                self.buf.textarea.lines().len(),
+            ),
+            Mode::Preview => status::scroll_position(
+                self.preview.scroll_offset as usize,
+                self.viewport_height as usize,
+                self.preview.content_height as usize,
+            ),
+        };
         status::render(
             frame,
-            chunks[4],
+            area,
             status::StatusInfo {
                 line: line + 1,
                 col,
                 message: &self.status_message,
+                severity: self.status_severity,
                 word_count: self.word_count(),
-                modified: self.modified,
+                reading_minutes: self.reading_minutes(),
+                modified: self.buf.modified,
+                position: &position,
+                diff_base: self.buf.diff_base.as_deref(),
+                word_goal: self.effective_word_goal().map(|goal| {
+                    (word_goal::prose_word_count(self.buf.textarea.lines()), goal)
+                }),
+                focus_elapsed: focus_elapsed.as_deref(),
+                vim_mode: (self.buf.config.editing == Editing::Vim && self.editing())
+                    .then_some(if self.vim_normal { "NORMAL" } else { "INSERT" }),
             },
         );
-
-        // Help modal overlay -- rendered last so it sits on top of everything
-        if self.show_help {
-            self.render_help(frame);
-        }
     }
 
-    /// Renders a centered modal overlay listing all keybindings.
-    /// Dismissed by pressing any key.
-    fn render_help(&self, frame: &mut Frame) {
+    /// Renders a centered notice in place of the normal UI when the terminal
+    /// is below `MIN_WIDTH` x `MIN_HEIGHT`.
+    fn render_too_small(&self, frame: &mut Frame) {
         let area = frame.area();
-        // Size the modal to fit content, clamped to terminal size
-        let width = 45u16.min(area.width.saturating_sub(4));
-        let height = 23u16.min(area.height.saturating_sub(2));
-        let x = (area.width.saturating_sub(width)) / 2;
-        let y = (area.height.saturating_sub(height)) / 2;
-        let help_area = Rect::new(x, y, width, height);
-
-        // Clear the area behind the modal
-        frame.render_widget(Clear, help_area);
+        let text = vec![
+            Line::from(Span::styled(
+                "Terminal too small",
+                Style::default()
+                    .fg(theme::active().warning)
+                    .add_modifier(Modifier::BOLD),
+            )),
+            Line::from(Span::styled(
+                format!("need {}x{}", MIN_WIDTH, MIN_HEIGHT),
+                Style::default().fg(theme::active().line_number),
+            )),
+        ];
+        // Vertically center as far as the wrapped text allows
+        let y = area.y + area.height.saturating_sub(2) / 2;
+        let msg_area = Rect::new(area.x, y, area.width, area.height - (y - area.y));
+        let paragraph = Paragraph::new(text)
+            .alignment(Alignment::Center)
+            .wrap(Wrap { trim: true });
+        frame.render_widget(paragraph, msg_area);
+    }
 
+    /// Renders a centered modal overlay listing all keybindings. Up/Down and
+    /// PgUp/PgDn scroll it when it doesn't fit; any other key dismisses it.
+    fn render_help(&mut self, frame: &mut Frame) {
         // Help content -- must match the actual keybinding handlers!
         // Grouped: global, editor, tui-textarea built-ins, mouse
         let help_text = vec![
             Line::from(Span::styled(
                 "Keybindings",
                 Style::default()
-                    .fg(theme::HEADING)
+                    .fg(theme::active().heading)
                     .add_modifier(Modifier::BOLD),
             )),
             Line::from(""),
             // -- Global (work in all modes) --
             Line::from(vec![
-                Span::styled("  Tab              ", Style::default().fg(theme::LINK)),
+                Span::styled("  Tab              ", Style::default().fg(theme::active().link)),
                 Span::raw("Switch mode"),
             ]),
             Line::from(vec![
-                Span::styled("  Ctrl+S           ", Style::default().fg(theme::LINK)),
+                Span::styled("  Ctrl+S           ", Style::default().fg(theme::active().link)),
                 Span::raw("Save"),
             ]),
             Line::from(vec![
-                Span::styled("  Ctrl+Q           ", Style::default().fg(theme::LINK)),
+                Span::styled("  Ctrl+Q           ", Style::default().fg(theme::active().link)),
                 Span::raw("Save & quit"),
             ]),
             Line::from(vec![
-                Span::styled("  Esc              ", Style::default().fg(theme::LINK)),
+                Span::styled("  Tab / Enter      ", Style::default().fg(theme::active().link)),
+                Span::raw("Preview: link/open, fold"),
+            ]),
+            Line::from(vec![
+                Span::styled("  M                ", Style::default().fg(theme::active().link)),
+                Span::raw("Preview: raw LaTeX math"),
+            ]),
+            Line::from(vec![
+                Span::styled("  Esc              ", Style::default().fg(theme::active().link)),
                 Span::raw("Back to editor"),
             ]),
             Line::from(vec![
-                Span::styled("  Ctrl+T           ", Style::default().fg(theme::LINK)),
+                Span::styled("  Ctrl+T           ", Style::default().fg(theme::active().link)),
                 Span::raw("Rename file"),
             ]),
             Line::from(vec![
-                Span::styled("  F1               ", Style::default().fg(theme::LINK)),
+                Span::styled("  Ctrl+PgUp/PgDn   ", Style::default().fg(theme::active().link)),
+                Span::raw("Previous / next file"),
+            ]),
+            Line::from(vec![
+                Span::styled("  Alt+P            ", Style::default().fg(theme::active().link)),
+                Span::raw("Show path in header"),
+            ]),
+            Line::from(vec![
+                Span::styled("  Alt+R            ", Style::default().fg(theme::active().link)),
+                Span::raw("Open file's folder"),
+            ]),
+            Line::from(vec![
+                Span::styled("  Alt+Shift+A / P  ", Style::default().fg(theme::active().link)),
+                Span::raw("Copy abs. / rel. path"),
+            ]),
+            Line::from(vec![
+                Span::styled("  Alt+V            ", Style::default().fg(theme::active().link)),
+                Span::raw("Split view"),
+            ]),
+            Line::from(vec![
+                Span::styled("  F1               ", Style::default().fg(theme::active().link)),
                 Span::raw("This help"),
             ]),
+            Line::from(vec![
+                Span::styled("  F2               ", Style::default().fg(theme::active().link)),
+                Span::raw("Message log"),
+            ]),
+            Line::from(vec![
+                Span::styled("  F3 / F4 / S+F4   ", Style::default().fg(theme::active().link)),
+                Span::raw("Record / replay macro"),
+            ]),
+            Line::from(vec![
+                Span::styled("  F6               ", Style::default().fg(theme::active().link)),
+                Span::raw("Start / stop focus timer"),
+            ]),
+            Line::from(vec![
+                Span::styled("  F8               ", Style::default().fg(theme::active().link)),
+                Span::raw("Next color theme"),
+            ]),
             Line::from(""),
             // -- Editor mode --
             Line::from(vec![
-                Span::styled("  Ctrl+Z / Ctrl+Y  ", Style::default().fg(theme::LINK)),
+                Span::styled("  Ctrl+Z / Ctrl+Y  ", Style::default().fg(theme::active().link)),
                 Span::raw("Undo / Redo"),
             ]),
             Line::from(vec![
-                Span::styled("  Ctrl+A           ", Style::default().fg(theme::LINK)),
+                Span::styled("  Ctrl+A           ", Style::default().fg(theme::active().link)),
                 Span::raw("Select all"),
             ]),
             Line::from(vec![
-                Span::styled("  Ctrl+L           ", Style::default().fg(theme::LINK)),
+                Span::styled("  Ctrl+F           ", Style::default().fg(theme::active().link)),
+                Span::raw("Find in document"),
+            ]),
+            Line::from(vec![
+                Span::styled("  Ctrl+O           ", Style::default().fg(theme::active().link)),
+                Span::raw("Outline of headings"),
+            ]),
+            Line::from(vec![
+                Span::styled("  Ctrl+R           ", Style::default().fg(theme::active().link)),
+                Span::raw("Find and replace"),
+            ]),
+            Line::from(vec![
+                Span::styled("  Ctrl+L           ", Style::default().fg(theme::active().link)),
                 Span::raw("Go to line start"),
             ]),
             Line::from(vec![
-                Span::styled("  Ctrl+C / Ctrl+V  ", Style::default().fg(theme::LINK)),
+                Span::styled("  Ctrl+G           ", Style::default().fg(theme::active().link)),
+                Span::raw("Go to line"),
+            ]),
+            Line::from(vec![
+                Span::styled("  Ctrl+Home/End    ", Style::default().fg(theme::active().link)),
+                Span::raw("Go to doc start / end"),
+            ]),
+            Line::from(vec![
+                Span::styled("  Alt+Left/Right   ", Style::default().fg(theme::active().link)),
+                Span::raw("Jump back / forward"),
+            ]),
+            Line::from(vec![
+                Span::styled("  Alt+K/J + letter ", Style::default().fg(theme::active().link)),
+                Span::raw("Set / jump to mark"),
+            ]),
+            Line::from(vec![
+                Span::styled("  Ctrl+C / Ctrl+V  ", Style::default().fg(theme::active().link)),
                 Span::raw("Copy / Paste (system)"),
             ]),
             Line::from(vec![
-                Span::styled("  Ctrl+H           ", Style::default().fg(theme::LINK)),
+                Span::styled("  Ctrl+Shift+V     ", Style::default().fg(theme::active().link)),
+                Span::raw("Paste as literal text"),
+            ]),
+            Line::from(vec![
+                Span::styled("  Alt+Shift+V      ", Style::default().fg(theme::active().link)),
+                Span::raw("Paste and reflow"),
+            ]),
+            Line::from(vec![
+                Span::styled("  Alt+E            ", Style::default().fg(theme::active().link)),
+                Span::raw("Preview block at cursor"),
+            ]),
+            Line::from(vec![
+                Span::styled("  Alt+Y            ", Style::default().fg(theme::active().link)),
+                Span::raw("Cycle pasted clipboard"),
+            ]),
+            Line::from(vec![
+                Span::styled("  Ctrl+Shift+T     ", Style::default().fg(theme::active().link)),
+                Span::raw("Insert a new table"),
+            ]),
+            Line::from(vec![
+                Span::styled("  Alt+Shift+arrows ", Style::default().fg(theme::active().link)),
+                Span::raw("Block (column) selection"),
+            ]),
+            Line::from(vec![
+                Span::styled("  Ctrl+Shift+W     ", Style::default().fg(theme::active().link)),
+                Span::raw("Toggle auto-wrap"),
+            ]),
+            Line::from(vec![
+                Span::styled("  Alt+.            ", Style::default().fg(theme::active().link)),
+                Span::raw("Repeat last edit"),
+            ]),
+            Line::from(vec![
+                Span::styled("  Alt+C            ", Style::default().fg(theme::active().link)),
+                Span::raw("Toggle code span / block"),
+            ]),
+            Line::from(vec![
+                Span::styled("  Alt+Q / Alt+S+Q  ", Style::default().fg(theme::active().link)),
+                Span::raw("Quote / unquote lines"),
+            ]),
+            Line::from(vec![
+                Span::styled("  Tab / Shift+Tab  ", Style::default().fg(theme::active().link)),
+                Span::raw("Indent/outdent lines"),
+            ]),
+            Line::from(vec![
+                Span::styled("  Ctrl+Space       ", Style::default().fg(theme::active().link)),
+                Span::raw("Toggle task checkbox"),
+            ]),
+            Line::from(vec![
+                Span::styled("  Alt+M            ", Style::default().fg(theme::active().link)),
+                Span::raw("Dim markdown markers"),
+            ]),
+            Line::from(vec![
+                Span::styled("  Alt+L            ", Style::default().fg(theme::active().link)),
+                Span::raw("Insert link from URL"),
+            ]),
+            Line::from(vec![
+                Span::styled("  Alt+!            ", Style::default().fg(theme::active().link)),
+                Span::raw("Filter through command"),
+            ]),
+            Line::from(vec![
+                Span::styled("  Alt+X            ", Style::default().fg(theme::active().link)),
+                Span::raw("Run code block"),
+            ]),
+            Line::from(vec![
+                Span::styled("  Alt+Z            ", Style::default().fg(theme::active().link)),
+                Span::raw("Fold / unfold code"),
+            ]),
+            Line::from(vec![
+                Span::styled("  F7               ", Style::default().fg(theme::active().link)),
+                Span::raw("Next misspelling"),
+            ]),
+            Line::from(vec![
+                Span::styled("  Alt+G            ", Style::default().fg(theme::active().link)),
+                Span::raw("Set word goal"),
+            ]),
+            Line::from(vec![
+                Span::styled("  Alt+N / Alt+S+N  ", Style::default().fg(theme::active().link)),
+                Span::raw("Next / prev conflict"),
+            ]),
+            Line::from(vec![
+                Span::styled("  Alt+O / T / B    ", Style::default().fg(theme::active().link)),
+                Span::raw("Keep ours/theirs/both"),
+            ]),
+            Line::from(vec![
+                Span::styled("  Ctrl+H           ", Style::default().fg(theme::active().link)),
                 Span::raw("Delete word before"),
             ]),
             Line::from(vec![
-                Span::styled("  Ctrl+D           ", Style::default().fg(theme::LINK)),
+                Span::styled("  Ctrl+D           ", Style::default().fg(theme::active().link)),
                 Span::raw("Delete word after"),
             ]),
             Line::from(vec![
-                Span::styled("  Ctrl+K           ", Style::default().fg(theme::LINK)),
+                Span::styled("  Ctrl+K           ", Style::default().fg(theme::active().link)),
                 Span::raw("Delete to end of line"),
             ]),
             Line::from(""),
             // -- Mouse --
             Line::from(vec![
-                Span::styled("  Click + drag     ", Style::default().fg(theme::LINK)),
+                Span::styled("  Click + drag     ", Style::default().fg(theme::active().link)),
                 Span::raw("Select text"),
             ]),
             Line::from(vec![
-                Span::styled("  Click filename   ", Style::default().fg(theme::LINK)),
+                Span::styled("  Alt+drag         ", Style::default().fg(theme::active().link)),
+                Span::raw("Select a block of columns"),
+            ]),
+            Line::from(vec![
+                Span::styled("  Shift+click      ", Style::default().fg(theme::active().link)),
+                Span::raw("Extend selection"),
+            ]),
+            Line::from(vec![
+                Span::styled("  Click filename   ", Style::default().fg(theme::active().link)),
                 Span::raw("Rename file"),
             ]),
             Line::from(vec![
-                Span::styled("  Click tabs       ", Style::default().fg(theme::LINK)),
+                Span::styled("  Click tabs       ", Style::default().fg(theme::active().link)),
                 Span::raw("Switch mode"),
             ]),
         ];
 
+        // Size the modal to fit content, clamped to terminal size
+        let area = frame.area();
+        let width = 45u16.min(area.width.saturating_sub(4));
+        let text_width = width.saturating_sub(2).max(1) as usize;
+        let rows: usize = help_text
+            .iter()
+            .map(|line| line.width().div_ceil(text_width).max(1))
+            .sum();
+        let height = (rows as u16).saturating_add(2).min(area.height.saturating_sub(2));
+        let x = (area.width.saturating_sub(width)) / 2;
+        let y = (area.height.saturating_sub(height)) / 2;
+        let help_area = Rect::new(x, y, width, height);
+
+        // Clear the area behind the modal
+        frame.render_widget(Clear, help_area);
+
+        // Keep the scroll in range now that the visible rows are known
+        self.help_page = height.saturating_sub(2).max(1);
+        let max_scroll = rows.saturating_sub(self.help_page as usize) as u16;
+        self.help_scroll = self.help_scroll.min(max_scroll);
+
         let block = Block::default()
             .borders(Borders::ALL)
-            .border_style(Style::default().fg(theme::BORDER))
-            .style(Style::default().fg(theme::FG).bg(theme::BAR_BG));
+            .border_style(Style::default().fg(theme::active().border))
+            .style(Style::default().fg(theme::active().fg).bg(theme::active().bar_bg));
 
         let paragraph = Paragraph::new(help_text)
             .block(block)
             .alignment(Alignment::Left)
-            .wrap(Wrap { trim: false });
+            .wrap(Wrap { trim: false })
+            .scroll((self.help_scroll, 0));
 
         frame.render_widget(paragraph, help_area);
     }
 
+    /// Renders the editor pane, with the scrollbar in its own column on the
+    /// right when the buffer is taller than the pane.
+    pub(super) fn render_editor(&mut self, frame: &mut Frame, area: Rect) {
+        self.reveal_cursor();
+        let total_lines = self.buf.textarea.lines().len();
+        let (area, track) = editor::split_scrollbar(area, total_lines);
+        if self.soft_wrap() || !self.buf.folded.is_empty() {
+            self.render_laid_out_editor(frame, area);
+        } else {
+            self.render_plain_editor(frame, area);
+        }
+        if let Some(track) = track {
+            editor::render_scrollbar(frame, track, total_lines, self.buf.editor_scroll_top as usize);
+        }
+    }
+
     /// Renders the tui-textarea widget plus tilde markers for empty lines,
     /// then overlays syntax highlighting for code fence regions.
-    fn render_editor(&mut self, frame: &mut Frame, area: Rect) {
-        frame.render_widget(&self.textarea, area);
+    fn render_plain_editor(&mut self, frame: &mut Frame, area: Rect) {
+        self.apply_scrolloff(area.height);
+        frame.render_widget(&self.buf.textarea, area);
 
         // Track scroll position (mirrors tui-textarea's internal viewport logic)
         // so we can translate mouse coordinates -> buffer positions correctly.
-        let cursor_row = self.textarea.cursor().0 as u16;
-        if cursor_row < self.editor_scroll_top {
-            self.editor_scroll_top = cursor_row;
-        } else if self.editor_scroll_top + area.height <= cursor_row {
-            self.editor_scroll_top = cursor_row + 1 - area.height;
+        let cursor_row = self.buf.textarea.cursor().0 as u16;
+        if cursor_row < self.buf.editor_scroll_top {
+            self.buf.editor_scroll_top = cursor_row;
+        } else if self.buf.editor_scroll_top + area.height <= cursor_row {
+            self.buf.editor_scroll_top = cursor_row + 1 - area.height;
         }
 
         // Render vim-style tilde markers for lines beyond the file content
-        let total_lines = self.textarea.lines().len();
+        let total_lines = self.buf.textarea.lines().len();
         let gutter_width = format!("{}", total_lines).len() as u16 + 1;
-        if (total_lines as u16) < area.height {
-            for row in total_lines as u16..area.height {
-                let tilde_area = Rect {
-                    x: area.x,
-                    y: area.y + row,
-                    width: area.width,
-                    height: 1,
+        render_tildes(frame, area, total_lines as u16, gutter_width);
+
+        self.apply_editor_overlays(frame.buffer_mut(), area, gutter_width);
+        self.render_inline_images(frame, area, gutter_width);
+    }
+
+    /// Post-processes the rendered textarea in `buf`: code fence syntax
+    /// highlighting, heading colors, the optional emphasis and marker
+    /// styling, spelling underlines, and git gutter markers. `area` is where the textarea was
+    /// rendered, with its first row showing line `editor_scroll_top`.
+    pub(super) fn apply_editor_overlays(&mut self, buf: &mut Buffer, area: Rect, gutter_width: u16) {
+        self.apply_code_fence_highlighting(buf, area, gutter_width);
+
+        self.apply_heading_colors(buf, area, gutter_width);
+        if self.buf.config.live_emphasis {
+            self.apply_emphasis_styling(buf, area, gutter_width);
+        }
+        if self.buf.config.dim_markers {
+            self.apply_marker_dimming(buf, area, gutter_width);
+        }
+        #[cfg(feature = "spellcheck")]
+        self.apply_spell_underlines(buf, area, gutter_width);
+        self.apply_conflict_backgrounds(buf, area, gutter_width);
+        self.apply_rect_selection(buf, area, gutter_width);
+        self.apply_gutter_marks(buf, area);
+    }
+
+    /// Draws a small thumbnail of each visible image reference on the blank
+    /// lines right below it, using the terminal's graphics protocol. Draws
+    /// nothing without one: half-blocks are too coarse at this size. Lines
+    /// with text and the cursor line are never covered, and remote images
+    /// only show once the preview has downloaded them. Only the plain layout
+    /// gets thumbnails; with soft wrap or folds, rows don't map to lines.
+    fn render_inline_images(&mut self, frame: &mut Frame, area: Rect, gutter_width: u16) {
+        if !self.preview.images.has_picker() {
+            return;
+        }
+        let text_start_x = area.x + gutter_width + 1;
+        let width = THUMBNAIL_COLS.min((area.x + area.width).saturating_sub(text_start_x));
+        if width == 0 {
+            return;
+        }
+        let lines = self.buf.textarea.lines();
+        let scroll_top = self.buf.editor_scroll_top as usize;
+        let visible_end = (scroll_top + area.height as usize).min(lines.len());
+        let cursor_row = self.buf.textarea.cursor().0;
+
+        let mut thumbnails = Vec::new();
+        for row in scroll_top..visible_end {
+            let Some(url) = images::image_url(&lines[row]) else {
+                continue;
+            };
+            let rows = (row + 1..visible_end)
+                .take(THUMBNAIL_ROWS)
+                .take_while(|&r| r != cursor_row && lines[r].trim().is_empty())
+                .count();
+            if rows > 0 {
+                let y = area.y + (row + 1 - scroll_top) as u16;
+                let rect = Rect::new(text_start_x, y, width, rows as u16);
+                thumbnails.push((url.to_string(), rect));
+            }
+        }
+
+        let base_dir = self.buf.file_path.parent().unwrap_or(std::path::Path::new("."));
+        for (url, rect) in thumbnails {
+            let Some(path) = self.preview.images.resolve(&url, base_dir, false) else {
+                continue;
+            };
+            // Non-blocking: a thumbnail appears once its decode finishes
+            if self.preview.images.request(&path) {
+                self.inline_images.draw(frame, rect, &path, &self.preview.images);
+            }
+        }
+    }
+
+    /// Shades visible merge conflict lines across the text area: marker
+    /// lines, our side, the diff3 base and their side each get their own
+    /// background. Selected and search-highlighted cells keep theirs.
+    fn apply_conflict_backgrounds(&self, buf: &mut Buffer, area: Rect, gutter_width: u16) {
+        let conflicts = git::conflict::find_conflicts(self.buf.textarea.lines());
+        if conflicts.is_empty() {
+            return;
+        }
+        let scroll_top = self.buf.editor_scroll_top as usize;
+        let text_start_x = area.x + gutter_width + 1;
+        for row in scroll_top..scroll_top + area.height as usize {
+            let Some(c) = conflicts.iter().find(|c| c.contains(row)) else {
+                continue;
+            };
+            let bg = if c.ours().contains(&row) {
+                theme::active().conflict_ours_bg
+            } else if c.ancestor().contains(&row) {
+                theme::active().conflict_base_bg
+            } else if c.theirs().contains(&row) {
+                theme::active().conflict_theirs_bg
+            } else {
+                theme::active().conflict_marker_bg
+            };
+            let screen_row = area.y + (row - scroll_top) as u16;
+            for x in text_start_x..area.x + area.width {
+                if let Some(cell) = buf.cell_mut((x, screen_row)) {
+                    if cell.bg == ratatui::style::Color::Reset {
+                        cell.set_bg(bg);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Overlays git gutter markers on the first column(s) of changed lines.
+    fn apply_gutter_marks(&self, buf: &mut Buffer, area: Rect) {
+        if self.buf.gutter_marks.is_empty() && self.buf.gutter_deletions.is_empty() {
+            return;
+        }
+        let total_lines = self.buf.textarea.lines().len();
+        let scroll_top = self.buf.editor_scroll_top as usize;
+        let visible_rows = area.height.min(total_lines.saturating_sub(scroll_top) as u16);
+        let glyphs: Vec<char> = self.buf.config.gutter_glyph.chars().collect();
+
+        // Deleted lines have no row of their own: mark the boundary with a
+        // bar along the top of the line below, or the bottom of the line
+        // above when the line below is taken or the file ends there.
+        let mut deletion_rows: HashMap<usize, char> = HashMap::new();
+        for &boundary in &self.buf.gutter_deletions {
+            let taken = |rows: &HashMap<usize, char>, line: usize| {
+                self.buf.gutter_marks.contains_key(&line) || rows.contains_key(&line)
+            };
+            if boundary < total_lines && !taken(&deletion_rows, boundary) {
+                deletion_rows.insert(boundary, '\u{2594}'); // upper one eighth block
+            } else if boundary > 0 && !taken(&deletion_rows, boundary - 1) {
+                deletion_rows.insert(boundary - 1, '\u{2581}'); // lower one eighth block
+            }
+        }
+
+        for row in 0..visible_rows {
+            let buf_line = scroll_top + row as usize;
+            let mark = match self.buf.gutter_marks.get(&buf_line) {
+                Some(&mark) => Some((mark, '\u{258E}')), // left quarter block
+                None => deletion_rows
+                    .get(&buf_line)
+                    .map(|&bar| (GutterMark::Removed, bar)),
+            };
+            let Some((mark, default)) = mark else {
+                continue;
+            };
+            let (color, kind) = match mark {
+                GutterMark::Added => (theme::active().git_added, 0),
+                GutterMark::Modified => (theme::active().git_modified, 1),
+                GutterMark::Removed => (theme::active().git_removed, 2),
+            };
+            // One configured glyph for every change, or one per kind.
+            // Deletions keep their boundary bar unless given their own.
+            let glyph = match glyphs.len() {
+                0 => default,
+                1 | 2 if mark == GutterMark::Removed => default,
+                1 | 2 => glyphs[0],
+                _ => glyphs[kind],
+            };
+            for dx in 0..self.buf.config.gutter_marker_width.max(1) {
+                let Some(cell) = buf.cell_mut((area.x + dx, area.y + row)) else {
+                    break;
                 };
-                let tilde = Paragraph::new(Line::from(vec![
-                    Span::styled(
-                        " ".repeat(gutter_width as usize),
-                        Style::default().fg(theme::TILDE),
-                    ),
-                    Span::styled(
-                        "~",
-                        Style::default().fg(theme::TILDE),
-                    ),
-                ]));
-                frame.render_widget(tilde, tilde_area);
+                // Widening never covers line numbers
+                if dx > 0 && cell.symbol() != " " {
+                    break;
+                }
+                cell.set_char(glyph);
+                cell.set_fg(color);
             }
         }
+    }
+
+    /// Scrolls the textarea early so `config.scrolloff` lines stay visible
+    /// between the cursor and the viewport edge. tui-textarea only keeps the
+    /// cursor itself in view, so the margin is applied before it renders.
+    fn apply_scrolloff(&mut self, height: u16) {
+        // Clamp so the margins never overlap (cursor stays roughly centered)
+        let margin = self.buf.config.scrolloff.min(height.saturating_sub(1) / 2);
+        if margin == 0 {
+            return;
+        }
+        let cursor = self.buf.textarea.cursor();
+        let cursor_row = cursor.0 as u16;
+        let total_lines = self.buf.textarea.lines().len() as u16;
+        let top = self.buf.editor_scroll_top;
 
-        // Apply syntax highlighting overlay for code fence regions
-        self.apply_code_fence_highlighting(frame, area, gutter_width);
-
-        // Overlay git gutter markers on the first column of changed lines
-        if !self.gutter_marks.is_empty() {
-            let scroll_top = self.editor_scroll_top as usize;
-            let visible_rows = area.height.min(total_lines.saturating_sub(scroll_top) as u16);
-            for row in 0..visible_rows {
-                let buf_line = scroll_top + row as usize;
-                if let Some(mark) = self.gutter_marks.get(&buf_line) {
-                    let color = match mark {
-                        GutterMark::Added => theme::GIT_ADDED,
-                        GutterMark::Modified => theme::GIT_MODIFIED,
-                        GutterMark::Removed => theme::GIT_REMOVED,
-                    };
-                    let buf = frame.buffer_mut();
-                    if let Some(cell) = buf.cell_mut((area.x, area.y + row)) {
-                        cell.set_char('\u{258E}'); // left quarter block
+        let target = if cursor_row < top + margin {
+            cursor_row.saturating_sub(margin)
+        } else if cursor_row + margin >= top + height {
+            // Don't scroll past the point where the last line reaches the bottom
+            (cursor_row + margin + 1 - height)
+                .min(total_lines.saturating_sub(height))
+                .max(top)
+        } else {
+            top
+        };
+        if target == top {
+            return;
+        }
+
+        self.buf.textarea.scroll((target as i16 - top as i16, 0));
+        // scroll() pulls the cursor into tui-textarea's last-rendered viewport;
+        // put it back where it was.
+        self.buf.textarea
+            .move_cursor(CursorMove::Jump(cursor.0 as u16, cursor.1 as u16));
+        self.buf.editor_scroll_top = target;
+    }
+
+    /// Colors visible heading lines by level (the theme's `heading_levels`).
+    /// Foreground only, and only on cells with the default background.
+    fn apply_heading_colors(&self, buf: &mut Buffer, area: Rect, gutter_width: u16) {
+        let scroll_top = self.buf.editor_scroll_top as usize;
+        let visible = self.buf
+            .textarea
+            .lines()
+            .iter()
+            .enumerate()
+            .skip(scroll_top)
+            .take(area.height as usize);
+        let text_start_x = area.x + gutter_width + 1; // +1 for leading space in gutter
+
+        for (line_idx, line) in visible {
+            let Some(level) = outline::heading_level(line) else {
+                continue;
+            };
+            let in_fence = self.buf
+                .code_fence_regions
+                .iter()
+                .any(|r| line_idx >= r.start_line && line_idx <= r.end_line);
+            if in_fence {
+                continue;
+            }
+            let color = theme::active().heading_levels[level - 1];
+            let screen_row = area.y + (line_idx - scroll_top) as u16;
+            for col in 0..line.chars().count() {
+                let cell_x = text_start_x + col as u16;
+                if cell_x >= area.x + area.width {
+                    break;
+                }
+                if let Some(cell) = buf.cell_mut((cell_x, screen_row)) {
+                    if cell.bg == ratatui::style::Color::Reset {
                         cell.set_fg(color);
                     }
                 }
@@ -344,39 +955,165 @@ impl<'a> App<'a> {
         }
     }
 
-    /// Overlays syntax highlighting on the ratatui buffer for code fence regions.
-    /// Post-processes cells after tui-textarea has rendered, overwriting foreground
-    /// colors only (preserving cursor/selection backgrounds).
-    fn apply_code_fence_highlighting(&mut self, frame: &mut Frame, area: Rect, gutter_width: u16) {
+    /// Overlays emphasis styling on visible prose lines: bold/italic/crossed-out
+    /// modifiers on the span content, with the delimiters dimmed. Lines inside
+    /// code fences are left alone.
+    fn apply_emphasis_styling(&self, buf: &mut Buffer, area: Rect, gutter_width: u16) {
+        let scroll_top = self.buf.editor_scroll_top as usize;
+        let visible = self.buf
+            .textarea
+            .lines()
+            .iter()
+            .enumerate()
+            .skip(scroll_top)
+            .take(area.height as usize);
+        let text_start_x = area.x + gutter_width + 1; // +1 for leading space in gutter
+
+        for (line_idx, line) in visible {
+            let in_fence = self.buf
+                .code_fence_regions
+                .iter()
+                .any(|r| line_idx >= r.start_line && line_idx <= r.end_line);
+            if in_fence {
+                continue;
+            }
+            let screen_row = area.y + (line_idx - scroll_top) as u16;
+
+            for span in emphasis::find_emphasis(line) {
+                let modifier = match span.kind {
+                    EmphasisKind::Bold => Modifier::BOLD,
+                    EmphasisKind::Italic => Modifier::ITALIC,
+                    EmphasisKind::Strikethrough => Modifier::CROSSED_OUT,
+                };
+                for col in span.start..span.end {
+                    let cell_x = text_start_x + col as u16;
+                    if cell_x >= area.x + area.width {
+                        break;
+                    }
+                    let is_marker =
+                        col < span.start + span.marker_len || col >= span.end - span.marker_len;
+                    if let Some(cell) = buf.cell_mut((cell_x, screen_row)) {
+                        let style = cell.style();
+                        let extra = if is_marker { Modifier::DIM } else { modifier };
+                        cell.set_style(style.add_modifier(extra));
+                    }
+                }
+            }
+        }
+    }
+
+    /// Recolors markdown markers on visible prose lines with the dim marker
+    /// color so the text stands out. Foreground only, and only on cells with
+    /// the default background, so the cursor and selection stay visible.
+    fn apply_marker_dimming(&self, buf: &mut Buffer, area: Rect, gutter_width: u16) {
+        let scroll_top = self.buf.editor_scroll_top as usize;
+        let visible = self.buf
+            .textarea
+            .lines()
+            .iter()
+            .enumerate()
+            .skip(scroll_top)
+            .take(area.height as usize);
+        let text_start_x = area.x + gutter_width + 1; // +1 for leading space in gutter
+
+        for (line_idx, line) in visible {
+            let in_fence = self.buf
+                .code_fence_regions
+                .iter()
+                .any(|r| line_idx >= r.start_line && line_idx <= r.end_line);
+            if in_fence {
+                continue;
+            }
+            let screen_row = area.y + (line_idx - scroll_top) as u16;
+
+            for (start, end) in emphasis::marker_ranges(line) {
+                for col in start..end {
+                    let cell_x = text_start_x + col as u16;
+                    if cell_x >= area.x + area.width {
+                        break;
+                    }
+                    if let Some(cell) = buf.cell_mut((cell_x, screen_row)) {
+                        if cell.bg == ratatui::style::Color::Reset {
+                            cell.set_fg(theme::active().marker);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    /// Finds the code fence regions again and starts highlighting them in
+    /// the background; `tick` applies the colors once they're done, and
+    /// until then the previous ones show. With `highlight_current_fence_only`
+    /// nothing is highlighted here. False (leaving the regions dirty) while
+    /// syntect is still loading.
+    pub(super) fn refresh_code_fences(&mut self) -> bool {
+        let Some((ss, ts)) = code_highlight::try_get() else {
+            return false;
+        };
+        let lines: Vec<String> = self.buf.textarea.lines().iter().map(|s| s.to_string()).collect();
+        self.buf.code_fence_regions = code_highlight::find_code_fence_regions(&lines);
+        if self.buf.config.highlight_current_fence_only {
+            self.buf.code_fence_highlights = vec![Vec::new(); self.buf.code_fence_regions.len()];
+            self.buf.code_fence_handle = None;
+        } else {
+            // Replaces any run still going: its result would be stale
+            let regions = self.buf.code_fence_regions.clone();
+            self.buf.code_fence_handle = Some(std::thread::spawn(move || {
+                highlight_code_regions_in_parallel(&regions, &lines, ss, ts)
+            }));
+        }
+        self.buf.code_fence_focus = None;
+        self.buf.code_fence_dirty = false;
+        true
+    }
+
+    /// Overlays syntax highlighting on the ratatui buffer for code fence regions
+    /// (only the cursor's with `highlight_current_fence_only`). Post-processes
+    /// cells after tui-textarea has rendered, overwriting foreground colors only
+    /// (preserving cursor/selection backgrounds).
+    fn apply_code_fence_highlighting(&mut self, buf: &mut Buffer, area: Rect, gutter_width: u16) {
         // Refresh code fence regions and cached highlights if dirty
-        if self.code_fence_dirty {
-            // Non-blocking: if syntect hasn't finished loading, skip and retry next frame
-            let (ss, ts) = match code_highlight::try_get() {
-                Some(pair) => pair,
-                None => return,
-            };
-            let lines: Vec<String> = self.textarea.lines().iter().map(|s| s.to_string()).collect();
-            self.code_fence_regions = code_highlight::find_code_fence_regions(&lines);
-            self.code_fence_highlights =
-                highlight_code_regions(&self.code_fence_regions, &lines, ss, ts);
-            self.code_fence_dirty = false;
+        if self.buf.code_fence_dirty && !self.refresh_code_fences() {
+            return;
+        }
+
+        // Only the block under the cursor is highlighted, on entering it
+        if self.buf.config.highlight_current_fence_only {
+            let row = self.buf.textarea.cursor().0;
+            let focus = self.buf
+                .code_fence_regions
+                .iter()
+                .position(|r| row >= r.start_line && row <= r.end_line);
+            if focus != self.buf.code_fence_focus {
+                let Some((ss, ts)) = code_highlight::try_get() else {
+                    return;
+                };
+                self.buf.code_fence_highlights.iter_mut().for_each(Vec::clear);
+                if let Some(i) = focus {
+                    let region = std::slice::from_ref(&self.buf.code_fence_regions[i]);
+                    let mut highlighted = highlight_code_regions(region, self.buf.textarea.lines(), ss, ts);
+                    self.buf.code_fence_highlights[i] = highlighted.pop().unwrap_or_default();
+                }
+                self.buf.code_fence_focus = focus;
+            }
         }
 
-        if self.code_fence_regions.is_empty() {
+        if self.buf.code_fence_regions.is_empty() {
             return;
         }
 
-        let scroll_top = self.editor_scroll_top as usize;
+        let scroll_top = self.buf.editor_scroll_top as usize;
         let visible_end = scroll_top + area.height as usize;
-        let cursor_pos = self.textarea.cursor();
+        let cursor_pos = self.buf.textarea.cursor();
 
-        for (region_idx, region) in self.code_fence_regions.iter().enumerate() {
+        for (region_idx, region) in self.buf.code_fence_regions.iter().enumerate() {
             // Skip regions completely outside the viewport
             if region.end_line < scroll_top || region.start_line >= visible_end {
                 continue;
             }
 
-            let highlights = match self.code_fence_highlights.get(region_idx) {
+            let highlights = match self.buf.code_fence_highlights.get(region_idx) {
                 Some(h) => h,
                 None => continue,
             };
@@ -412,7 +1149,6 @@ impl<'a> App<'a> {
                             && col_offset as usize == cursor_pos.1;
 
                         if !is_cursor_cell {
-                            let buf = frame.buffer_mut();
                             if let Some(cell) = buf.cell_mut((cell_x, screen_row)) {
                                 // Only override foreground, preserve background
                                 // (keeps selection/cursor highlighting intact)
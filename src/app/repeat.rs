@@ -0,0 +1,84 @@
+//! Repeating the last edit (Alt+.) at the cursor, a lighter alternative to
+//! keyboard macros for quick repeats. Plain "." still types a period.
+//!
+//! A run of typed text (Backspace included) counts as one edit as long as
+//! the cursor stays where the last keystroke left it; moving away starts a
+//! new one. Word deletions, quoting, and wrapping lines in a code block are
+//! remembered too. Other keys leave the last edit as it was.
+
+use super::*;
+
+impl<'a> App<'a> {
+    // ─── Repeat last edit (Alt+.) ────────────────────────────────────────
+
+    /// Remembers `key` if it was a repeatable edit. `before` is the cursor
+    /// before the key was handled.
+    pub(super) fn record_edit(&mut self, key: KeyEvent, before: (usize, usize)) {
+        let extends = self.insert_end.take() == Some(before);
+        let typed = match (key.modifiers, key.code) {
+            (KeyModifiers::NONE | KeyModifiers::SHIFT, KeyCode::Char(ch)) => Some(ch),
+            (KeyModifiers::NONE, KeyCode::Enter) => Some('\n'),
+            _ => None,
+        };
+        if let Some(ch) = typed {
+            match self.last_action {
+                Some(EditAction::Insert(ref mut text)) if extends => text.push(ch),
+                _ => self.last_action = Some(EditAction::Insert(ch.to_string())),
+            }
+            self.insert_end = Some(self.buf.textarea.cursor());
+            return;
+        }
+        if key.code == KeyCode::Backspace && key.modifiers.is_empty() {
+            if let (true, Some(EditAction::Insert(text))) = (extends, &mut self.last_action) {
+                text.pop();
+                self.insert_end = Some(self.buf.textarea.cursor());
+            }
+            return;
+        }
+        let action = match (key.modifiers, key.code) {
+            (KeyModifiers::CONTROL, KeyCode::Backspace | KeyCode::Char('h')) => {
+                EditAction::DeleteWord { forward: false }
+            }
+            (KeyModifiers::CONTROL, KeyCode::Delete | KeyCode::Char('d')) => {
+                EditAction::DeleteWord { forward: true }
+            }
+            (KeyModifiers::ALT, KeyCode::Char('q')) => EditAction::Quote(true),
+            (m, KeyCode::Char('Q')) if m.contains(KeyModifiers::ALT) => EditAction::Quote(false),
+            _ => return,
+        };
+        self.last_action = Some(action);
+    }
+
+    /// Re-applies the last repeatable edit at the cursor.
+    pub(super) fn repeat_last_edit(&mut self) {
+        let Some(action) = self.last_action.clone() else {
+            self.set_status("No edit to repeat");
+            return;
+        };
+        match action {
+            // Typed back key by key so list continuation and auto-closed
+            // pairs behave as they did the first time
+            EditAction::Insert(text) => {
+                for ch in text.chars() {
+                    let code = if ch == '\n' { KeyCode::Enter } else { KeyCode::Char(ch) };
+                    self.handle_editor_key(KeyEvent::new(code, KeyModifiers::NONE));
+                }
+            }
+            EditAction::DeleteWord { forward } => {
+                if forward {
+                    self.buf.textarea.delete_next_word();
+                } else {
+                    self.buf.textarea.delete_word();
+                }
+                self.update_modified();
+            }
+            EditAction::Quote(quote) => self.toggle_quote_level(quote),
+            EditAction::WrapCodeBlock { lines, lang } => {
+                let start_row = self.buf.textarea.cursor().0;
+                let last = self.buf.textarea.lines().len() - 1;
+                let end_row = (start_row + lines - 1).min(last);
+                self.wrap_lines_in_code_block(start_row, end_row, &lang);
+            }
+        }
+    }
+}
@@ -0,0 +1,211 @@
+//! Replace prompt (Ctrl+R): find a string and replace the next match or all
+//! of them.
+//!
+//! Tab switches between the find and replacement inputs, Alt+A toggles
+//! replace-all, Enter replaces, Esc closes. Matching is case-sensitive.
+//! Replaced lines are re-wrapped so they respect the editor width like typed
+//! text does, and a replacement (even of all matches) undoes in one step.
+
+use super::*;
+use super::search::{find_matches, replace_matches};
+
+impl<'a> App<'a> {
+    // ─── Replace prompt ──────────────────────────────────────────────────
+
+    /// Opens the replace prompt, pre-filling the find input with the selected
+    /// text when the selection is on a single line.
+    pub(super) fn start_replace(&mut self) {
+        let find = match self.buf.textarea.selection_range() {
+            Some(((start_row, _), (end_row, _))) if start_row == end_row => {
+                self.get_selected_text().unwrap_or_default()
+            }
+            _ => String::new(),
+        };
+        self.replace = Some(ReplaceState {
+            cursor: find.len(),
+            find,
+            ..ReplaceState::default()
+        });
+    }
+
+    /// Handles keypresses while the replace prompt is open.
+    pub(super) fn handle_replace_key(&mut self, key: KeyEvent) {
+        let Some(state) = self.replace.as_mut() else {
+            return;
+        };
+        let input = if state.editing_replace {
+            &mut state.replace
+        } else {
+            &mut state.find
+        };
+        match (key.modifiers, key.code) {
+            (_, KeyCode::Esc) => {
+                self.replace = None;
+            }
+            (_, KeyCode::Enter) => {
+                self.apply_replace();
+            }
+            (_, KeyCode::Tab | KeyCode::BackTab) => {
+                state.editing_replace = !state.editing_replace;
+                state.cursor = if state.editing_replace {
+                    state.replace.len()
+                } else {
+                    state.find.len()
+                };
+            }
+            (KeyModifiers::ALT, KeyCode::Char('a')) => {
+                state.replace_all = !state.replace_all;
+            }
+            (_, KeyCode::Backspace) if state.cursor > 0 => {
+                let prev = input[..state.cursor]
+                    .chars()
+                    .next_back()
+                    .map_or(0, char::len_utf8);
+                state.cursor -= prev;
+                input.remove(state.cursor);
+            }
+            (_, KeyCode::Delete) if state.cursor < input.len() => {
+                input.remove(state.cursor);
+            }
+            (_, KeyCode::Left) if state.cursor > 0 => {
+                let prev = input[..state.cursor]
+                    .chars()
+                    .next_back()
+                    .map_or(0, char::len_utf8);
+                state.cursor -= prev;
+            }
+            (_, KeyCode::Right) if state.cursor < input.len() => {
+                let next = input[state.cursor..]
+                    .chars()
+                    .next()
+                    .map_or(0, char::len_utf8);
+                state.cursor += next;
+            }
+            (_, KeyCode::Home) => {
+                state.cursor = 0;
+            }
+            (_, KeyCode::End) => {
+                state.cursor = input.len();
+            }
+            (m, KeyCode::Char(ch)) if !m.intersects(KeyModifiers::CONTROL | KeyModifiers::ALT) => {
+                input.insert(state.cursor, ch);
+                state.cursor += ch.len_utf8();
+            }
+            _ => {}
+        }
+    }
+
+    /// Inserts pasted text into the focused replace input (newlines dropped).
+    pub(super) fn handle_replace_paste(&mut self, text: &str) {
+        let Some(state) = self.replace.as_mut() else {
+            return;
+        };
+        let input = if state.editing_replace {
+            &mut state.replace
+        } else {
+            &mut state.find
+        };
+        for ch in text.chars().filter(|c| *c != '\n' && *c != '\r') {
+            input.insert(state.cursor, ch);
+            state.cursor += ch.len_utf8();
+        }
+    }
+
+    /// Replaces all matches (closing the prompt), or the first match at or
+    /// after the cursor (leaving the prompt open so Enter steps on).
+    fn apply_replace(&mut self) {
+        let Some(state) = self.replace.clone() else {
+            return;
+        };
+        if state.find.is_empty() {
+            self.set_status("Nothing to replace");
+            return;
+        }
+
+        let lines = self.buf.textarea.lines().to_vec();
+        let matches = find_matches(&lines, &state.find, true);
+        if matches.is_empty() {
+            self.set_status(&format!("No matches for '{}'", state.find));
+            return;
+        }
+
+        let cursor = self.buf.textarea.cursor();
+        let targets = if state.replace_all {
+            matches
+        } else {
+            let next = matches.iter().find(|&&m| m >= cursor).unwrap_or(&matches[0]);
+            vec![*next]
+        };
+        let replaced = replace_matches(&lines, &targets, state.find.chars().count(), &state.replace);
+
+        // One at a time: land after the replacement so the next Enter moves on
+        let cursor = if state.replace_all {
+            cursor
+        } else {
+            let (row, col) = targets[0];
+            (row, col + state.replace.chars().count())
+        };
+        let mut rows: Vec<usize> = targets.iter().map(|&(row, _)| row).collect();
+        rows.dedup();
+        let (first, last) = (rows[0], rows[rows.len() - 1]);
+        let (wrapped, (row, col)) = self.wrap_rows(replaced, &rows, cursor);
+        // Only the rows from the first match to the last change, so the
+        // replacement is one undo step
+        let after = lines.len() - 1 - last;
+        let text = wrapped[first..wrapped.len() - after].join("\n");
+        self.buf.textarea.cancel_selection();
+        self.replace_lines(first, last, &text);
+        self.buf.textarea.move_cursor(CursorMove::Jump(row as u16, col as u16));
+
+        let count = targets.len();
+        let noun = if count == 1 { "occurrence" } else { "occurrences" };
+        self.set_status(&format!("Replaced {} {}", count, noun));
+        if state.replace_all {
+            self.replace = None;
+        }
+    }
+
+    /// Hard-wraps the given `rows` of `lines` to the editor width (as typing
+    /// would via auto-wrap, so not while that is off), and maps `cursor` onto
+    /// the wrapped rows.
+    fn wrap_rows(
+        &self,
+        lines: Vec<String>,
+        rows: &[usize],
+        cursor: (usize, usize),
+    ) -> (Vec<String>, (usize, usize)) {
+        let width = self.available_text_width();
+        let mut out: Vec<String> = Vec::with_capacity(lines.len());
+        let mut new_cursor = cursor;
+        for (row, line) in lines.into_iter().enumerate() {
+            let pieces: Vec<String> = if self.hard_wrapping()
+                && width > 0
+                && rows.contains(&row)
+                && line.chars().count() > width
+            {
+                table_format::hard_wrap(&line, width, self.buf.config.wrap_indent())
+                    .lines()
+                    .map(String::from)
+                    .collect()
+            } else {
+                vec![line]
+            };
+
+            if row == cursor.0 {
+                // Walk the pieces, dropping the space consumed at each break
+                new_cursor = (out.len(), cursor.1);
+                let mut col = cursor.1;
+                for (i, piece) in pieces.iter().enumerate() {
+                    let len = piece.chars().count();
+                    if col <= len || i == pieces.len() - 1 {
+                        new_cursor = (out.len() + i, col.min(len));
+                        break;
+                    }
+                    col -= len + 1;
+                }
+            }
+            out.extend(pieces);
+        }
+        (out, new_cursor)
+    }
+}
@@ -0,0 +1,101 @@
+//! Running a code block (Alt+X) and capturing its output, for notes whose
+//! results stay reproducible.
+//!
+//! Strictly opt-in: only languages with a command in `Config::code_runners`
+//! can run, and every run asks for confirmation first. The block is fed to
+//! the command on stdin and its stdout goes into a ```` ```output ```` block
+//! right after it, replacing the one from an earlier run.
+
+use super::*;
+
+/// Info string of the block that holds a run's output.
+const OUTPUT_LANG: &str = "output";
+
+impl<'a> App<'a> {
+    // ─── Run code block (Alt+X) ──────────────────────────────────────────
+
+    /// Checks the code block under the cursor can run, then asks to confirm.
+    pub(super) fn start_run_code_block(&mut self) {
+        let row = self.buf.textarea.cursor().0;
+        let lines = self.buf.textarea.lines();
+        let regions = code_highlight::find_code_fence_regions(lines);
+        let Some(block) = regions
+            .iter()
+            .find(|r| (r.start_line..=r.end_line).contains(&row) && r.language != OUTPUT_LANG)
+        else {
+            self.set_status("Cursor is not in a code block");
+            return;
+        };
+        let closed = block.end_line > block.start_line
+            && lines[block.end_line].trim_start().starts_with("```");
+        if !closed {
+            self.set_status("Code block is not closed");
+            return;
+        }
+        if !self.buf.config.code_runners.contains_key(&block.language) {
+            let msg = format!("No runner for '{}' (see code_runners in config)", block.language);
+            self.set_status(&msg);
+            return;
+        }
+        let (start, end) = (block.start_line, block.end_line);
+        let language = block.language.clone();
+        self.start_prompt(Prompt::RunCodeBlock { start, end, language });
+    }
+
+    /// Runs the code block on lines `start..=end` (fences included) with the
+    /// runner for `language` and writes stdout into the output block after it.
+    pub(super) fn run_code_block(&mut self, start: usize, end: usize, language: &str) {
+        let Some(runner) = self.buf.config.code_runners.get(language).cloned() else {
+            return;
+        };
+        let lines = self.buf.textarea.lines();
+        let mut code = lines[start + 1..end].join("\n");
+        code.push('\n');
+        let output = match shell::run(&runner, &code) {
+            Ok(out) => out,
+            Err(e) => {
+                self.set_status_as(&format!("Run failed: {}", e), Severity::Error);
+                return;
+            }
+        };
+
+        let output = output.trim_end_matches('\n');
+        let block = if output.is_empty() {
+            format!("```{}\n```", OUTPUT_LANG)
+        } else {
+            format!("```{}\n{}\n```", OUTPUT_LANG, output)
+        };
+        match self.output_block_after(end) {
+            Some((out_start, out_end)) => {
+                let end_col = self.buf.textarea.lines()[out_end].chars().count();
+                self.set_selection((out_start, 0), (out_end, end_col));
+                self.buf.textarea.insert_str(block);
+            }
+            None => {
+                let end_col = self.buf.textarea.lines()[end].chars().count();
+                self.buf.textarea.cancel_selection();
+                self.buf.textarea
+                    .move_cursor(CursorMove::Jump(end as u16, end_col as u16));
+                self.buf.textarea.insert_str(format!("\n\n{}", block));
+            }
+        }
+        self.buf.code_fence_dirty = true;
+        self.update_modified();
+        self.set_status("Output updated");
+    }
+
+    /// Lines of the closed ```` ```output ```` block following the block
+    /// that ends on line `end`, allowing one blank line between them.
+    fn output_block_after(&self, end: usize) -> Option<(usize, usize)> {
+        let lines = self.buf.textarea.lines();
+        let next = (end + 1..lines.len()).find(|&r| !lines[r].trim().is_empty())?;
+        if next > end + 2 {
+            return None;
+        }
+        code_highlight::find_code_fence_regions(lines)
+            .into_iter()
+            .find(|r| r.start_line == next && r.language == OUTPUT_LANG)
+            .filter(|r| r.end_line > next && lines[r.end_line].trim_start().starts_with("```"))
+            .map(|r| (r.start_line, r.end_line))
+    }
+}
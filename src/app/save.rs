@@ -1,61 +1,146 @@
 //! File saving: write editor content to disk with table formatting and docx export.
 
 use super::*;
+use std::io::Write;
+use std::path::Path;
+
+/// Writes `content` to `path` by way of a sibling temp file
+/// (`.name.md.tmp`) renamed over it, so a crash mid-write can't leave the
+/// file truncated. The temp file is synced to disk before the rename, and
+/// gets the original's permissions first.
+fn write_atomic(path: &Path, content: &str) -> std::io::Result<()> {
+    // Renaming over a symlink would replace the link, so write its target
+    let path = &std::fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+    let name = path
+        .file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let tmp = path.with_file_name(format!(".{}.tmp", name));
+    let write = || -> std::io::Result<()> {
+        let mut file = std::fs::File::create(&tmp)?;
+        file.write_all(content.as_bytes())?;
+        file.sync_all()
+    };
+    let result = write().and_then(|()| {
+        if let Ok(meta) = std::fs::metadata(path) {
+            std::fs::set_permissions(&tmp, meta.permissions())?;
+        }
+        std::fs::rename(&tmp, path)
+    });
+    if result.is_err() {
+        let _ = std::fs::remove_file(&tmp);
+    }
+    result
+}
 
 impl<'a> App<'a> {
+    /// Ctrl+Q: saves and quits, first asking whether to save the other open
+    /// files if any have unsaved changes. Right after a failed save
+    /// (`quit_pending`), quits without saving.
+    pub(super) fn request_quit(&mut self, quit_pending: bool) {
+        if quit_pending {
+            self.should_quit = true;
+        } else if self.unsaved_parked_count() > 0 {
+            self.start_prompt(Prompt::QuitUnsaved);
+        } else {
+            self.quit_if_saved(true);
+        }
+    }
+
+    /// Saves the shown file (and, with `all`, the other open files), then
+    /// quits. If anything fails to save, stays open and arms `quit_pending`
+    /// so a second Ctrl+Q quits anyway.
+    pub(super) fn quit_if_saved(&mut self, all: bool) {
+        let failed_parked = if all { self.save_parked() } else { 0 };
+        if self.buf.modified {
+            self.save();
+        }
+        let error = if self.buf.modified {
+            self.status_message.clone()
+        } else if failed_parked > 0 {
+            let noun = if failed_parked == 1 { "file" } else { "files" };
+            format!("Could not save {} other {}", failed_parked, noun)
+        } else {
+            self.should_quit = true;
+            return;
+        };
+        self.quit_pending = true;
+        self.set_status_as(&format!("{}; Ctrl+Q again quits without saving", error), Severity::Error);
+    }
+
+    /// Asks the main loop to exit regardless of errors (SIGTERM / SIGINT),
+    /// first saving unsaved changes in every open file if `save_on_signal`
+    /// is set.
+    pub fn quit_on_signal(&mut self) {
+        if self.buf.config.save_on_signal {
+            self.save_parked();
+            if self.buf.modified {
+                self.save();
+            }
+        }
+        self.should_quit = true;
+    }
+
     /// Writes the current editor content to disk and resets the modified flag.
-    /// Runs table auto-formatting before writing.
+    /// Runs table auto-formatting (and hard-wrapping, if enabled) before
+    /// writing, unless `format_on_save` is off.
     pub(super) fn save(&mut self) {
+        if self.is_read_only() {
+            self.set_status("Read-only");
+            return;
+        }
         let content = self.textarea_content();
-        // Subtract the line-number gutter so tables fit the visible text area.
-        // tui-textarea gutter = leading space + digits + trailing space
-        let total_lines = self.textarea.lines().len();
-        let gutter: usize = if self.textarea.line_number_style().is_some() {
-            (total_lines as f64).log10() as usize + 1 + 2
+        // Tables and prose fit the visible text area (or the configured wrap width)
+        let width = self.available_text_width();
+        let formatted = if !self.format_on_save() {
+            content.clone()
+        } else if self.hard_wrapping() {
+            table_format::hard_wrap(
+                &table_format::format_tables(&content, width),
+                width,
+                self.buf.config.wrap_indent(),
+            )
         } else {
-            0
+            table_format::format_tables(&content, width)
         };
-        let width = (self.content_area.width as usize).saturating_sub(gutter);
-        let after_tables = table_format::format_tables(&content, width);
-        let formatted = table_format::hard_wrap(&after_tables, width);
 
         // If formatting changed the content, reconstruct the textarea
         if formatted != content {
-            let (row, col) = self.textarea.cursor();
+            let (row, col) = self.buf.textarea.cursor();
             let lines: Vec<String> = formatted.lines().map(String::from).collect();
-            self.textarea = TextArea::new(if lines.is_empty() { vec![String::new()] } else { lines });
-            editor::configure_textarea(&mut self.textarea);
+            self.buf.textarea = TextArea::new(if lines.is_empty() { vec![String::new()] } else { lines });
+            editor::configure_textarea(&mut self.buf.textarea);
             // Restore cursor position (clamped to valid range)
-            let max_row = self.textarea.lines().len().saturating_sub(1);
+            let max_row = self.buf.textarea.lines().len().saturating_sub(1);
             let target_row = row.min(max_row);
-            let max_col = self.textarea.lines().get(target_row).map_or(0, |l| l.len());
+            let max_col = self.buf.textarea.lines().get(target_row).map_or(0, |l| l.len());
             let target_col = col.min(max_col);
-            self.textarea
+            self.buf.textarea
                 .move_cursor(CursorMove::Jump(target_row as u16, target_col as u16));
         }
 
         let save_content = self.textarea_content();
-        match std::fs::write(&self.file_path, &save_content) {
+        match write_atomic(&self.buf.file_path, &save_content) {
             Ok(_) => {
-                self.original_content = save_content.clone();
-                self.wrapped_original = save_content;
-                self.modified = false;
+                self.buf.original_content = save_content.clone();
+                self.buf.wrapped_original = save_content;
+                self.buf.modified = false;
 
                 // Round-trip: also export back to .docx if we're in docx mode
-                if let Some(ref ds) = self.docx_state {
-                    match pandoc::md_to_docx(&self.file_path, &ds.docx_path, Some(&ds.reference_doc)) {
-                        Ok(_) => self.set_status("Saved (.md + .docx)"),
-                        Err(e) => self.set_status(&format!("Saved .md, but .docx failed: {}", e)),
+                if let Some(ref ds) = self.buf.docx_state {
+                    match pandoc::md_to_docx(&self.buf.file_path, &ds.docx_path, Some(&ds.reference_doc)) {
+                        Ok(_) => self.set_status_as("Saved (.md + .docx)", Severity::Success),
+                        Err(e) => self.set_status_as(&format!("Saved .md, but .docx failed: {}", e), Severity::Error),
                     }
                 } else {
-                    self.set_status("Saved");
+                    self.set_status_as("Saved", Severity::Success);
                 }
 
                 self.refresh_git_status();
                 self.refresh_gutter_marks();
             }
             Err(e) => {
-                self.set_status(&format!("Error saving: {}", e));
+                self.set_status_as(&format!("Error saving: {}", e), Severity::Error);
             }
         }
     }
@@ -0,0 +1,224 @@
+//! Find bar (Ctrl+F): incremental search through the editor buffer.
+//!
+//! Every match is highlighted as the query is typed and the active one is
+//! selected. Enter / Shift+Enter step through matches, Ctrl+I toggles case
+//! sensitivity, and Esc closes the bar.
+
+use super::*;
+
+/// Start positions `(row, col)` of every non-overlapping occurrence of `query`
+/// in `lines`, with columns counted in chars.
+pub(super) fn find_matches(
+    lines: &[String],
+    query: &str,
+    case_sensitive: bool,
+) -> Vec<(usize, usize)> {
+    let fold = |c: char| {
+        if case_sensitive {
+            c
+        } else {
+            c.to_lowercase().next().unwrap_or(c)
+        }
+    };
+    let needle: Vec<char> = query.chars().map(fold).collect();
+    if needle.is_empty() {
+        return Vec::new();
+    }
+
+    let mut matches = Vec::new();
+    for (row, line) in lines.iter().enumerate() {
+        let haystack: Vec<char> = line.chars().map(fold).collect();
+        let mut col = 0;
+        while col + needle.len() <= haystack.len() {
+            if haystack[col..col + needle.len()] == needle[..] {
+                matches.push((row, col));
+                col += needle.len();
+            } else {
+                col += 1;
+            }
+        }
+    }
+    matches
+}
+
+/// Replaces the `len`-char match starting at each `(row, col)` in `matches`
+/// with `replacement`. Returns the rewritten lines; rows without a match are
+/// copied unchanged.
+pub(super) fn replace_matches(
+    lines: &[String],
+    matches: &[(usize, usize)],
+    len: usize,
+    replacement: &str,
+) -> Vec<String> {
+    let mut out = lines.to_vec();
+    // Right to left, so earlier columns on the same row stay valid
+    for &(row, col) in matches.iter().rev() {
+        let Some(line) = out.get_mut(row) else {
+            continue;
+        };
+        let byte_at = |c: usize| line.char_indices().nth(c).map_or(line.len(), |(i, _)| i);
+        let (start, end) = (byte_at(col), byte_at(col + len));
+        line.replace_range(start..end, replacement);
+    }
+    out
+}
+
+impl<'a> App<'a> {
+    // ─── Find bar ────────────────────────────────────────────────────────
+
+    /// Opens the find bar with an empty query.
+    pub(super) fn start_search(&mut self) {
+        self.push_jump();
+        self.search = Some(SearchState::default());
+    }
+
+    /// Closes the find bar, clearing match highlights and the selection.
+    fn close_search(&mut self) {
+        self.search = None;
+        let _ = self.buf.textarea.set_search_pattern("");
+        self.buf.textarea.cancel_selection();
+    }
+
+    /// Handles keypresses while the find bar is open. The query cursor is a
+    /// byte offset that always sits on a char boundary.
+    pub(super) fn handle_search_key(&mut self, key: KeyEvent) {
+        let Some(search) = self.search.as_mut() else {
+            return;
+        };
+        match (key.modifiers, key.code) {
+            (_, KeyCode::Esc) => {
+                self.close_search();
+                return;
+            }
+            (m, KeyCode::Enter) => {
+                self.step_search(!m.contains(KeyModifiers::SHIFT));
+                return;
+            }
+            // Ctrl+I arrives as Tab on terminals without keyboard enhancement
+            (KeyModifiers::CONTROL, KeyCode::Char('i'))
+            | (KeyModifiers::NONE, KeyCode::Tab) => {
+                search.case_sensitive = !search.case_sensitive;
+            }
+            (_, KeyCode::Backspace) if search.cursor > 0 => {
+                let prev = search.query[..search.cursor]
+                    .chars()
+                    .next_back()
+                    .map_or(0, char::len_utf8);
+                search.cursor -= prev;
+                search.query.remove(search.cursor);
+            }
+            (_, KeyCode::Delete) if search.cursor < search.query.len() => {
+                search.query.remove(search.cursor);
+            }
+            (_, KeyCode::Left) if search.cursor > 0 => {
+                let prev = search.query[..search.cursor]
+                    .chars()
+                    .next_back()
+                    .map_or(0, char::len_utf8);
+                search.cursor -= prev;
+                return;
+            }
+            (_, KeyCode::Right) if search.cursor < search.query.len() => {
+                let next = search.query[search.cursor..]
+                    .chars()
+                    .next()
+                    .map_or(0, char::len_utf8);
+                search.cursor += next;
+                return;
+            }
+            (_, KeyCode::Home) => {
+                search.cursor = 0;
+                return;
+            }
+            (_, KeyCode::End) => {
+                search.cursor = search.query.len();
+                return;
+            }
+            (m, KeyCode::Char(ch)) if !m.intersects(KeyModifiers::CONTROL | KeyModifiers::ALT) => {
+                search.query.insert(search.cursor, ch);
+                search.cursor += ch.len_utf8();
+            }
+            _ => return,
+        }
+        self.refresh_search();
+    }
+
+    /// Inserts pasted text into the find query (newlines dropped).
+    pub(super) fn handle_search_paste(&mut self, text: &str) {
+        let Some(search) = self.search.as_mut() else {
+            return;
+        };
+        for ch in text.chars().filter(|c| *c != '\n' && *c != '\r') {
+            search.query.insert(search.cursor, ch);
+            search.cursor += ch.len_utf8();
+        }
+        self.refresh_search();
+    }
+
+    /// Recomputes matches after the query or case sensitivity changed, and
+    /// makes the first match at or after the previous position active, so
+    /// typing more of a word keeps the view where it was.
+    fn refresh_search(&mut self) {
+        let Some(search) = self.search.as_mut() else {
+            return;
+        };
+        search.matches =
+            find_matches(self.buf.textarea.lines(), &search.query, search.case_sensitive);
+
+        let pattern = if search.query.is_empty() {
+            String::new()
+        } else if search.case_sensitive {
+            regex::escape(&search.query)
+        } else {
+            format!("(?i){}", regex::escape(&search.query))
+        };
+        let _ = self.buf.textarea.set_search_pattern(pattern);
+
+        // Search from the start of the active match (or the cursor if none)
+        let from = self.buf
+            .textarea
+            .selection_range()
+            .map_or(self.buf.textarea.cursor(), |(start, _)| start);
+        search.current = search
+            .matches
+            .iter()
+            .position(|&m| m >= from)
+            .unwrap_or(0);
+        if search.matches.is_empty() {
+            // Drop the stale selection but stay put for when matches return
+            self.buf.textarea.cancel_selection();
+            self.buf.textarea
+                .move_cursor(CursorMove::Jump(from.0 as u16, from.1 as u16));
+        } else {
+            self.select_current_match();
+        }
+    }
+
+    /// Moves to the next (or previous) match, wrapping around the buffer.
+    fn step_search(&mut self, forward: bool) {
+        let Some(search) = self.search.as_mut() else {
+            return;
+        };
+        let count = search.matches.len();
+        if count == 0 {
+            return;
+        }
+        search.current = if forward {
+            (search.current + 1) % count
+        } else {
+            (search.current + count - 1) % count
+        };
+        self.select_current_match();
+    }
+
+    /// Jumps to the active match and selects it.
+    fn select_current_match(&mut self) {
+        let Some(search) = self.search.as_ref() else {
+            return;
+        };
+        if let Some(&(row, col)) = search.matches.get(search.current) {
+            let end = col + search.query.chars().count();
+            self.set_selection((row, col), (row, end));
+        }
+    }
+}
@@ -1,14 +1,37 @@
-//! Text selection helpers: get selected text, word selection, paragraph selection.
+//! Text selection helpers: get selected text, word selection, paragraph selection,
+//! and block indent/outdent.
 //!
-//! Used by clipboard copy (Ctrl+C), double-click (word), and triple-click (paragraph).
+//! Used by clipboard copy (Ctrl+C), double-click (word), triple-click (paragraph),
+//! word/paragraph-granular drag extension after a multi-click, and Tab /
+//! Shift+Tab with a selection.
 
 use super::*;
 
+/// Spaces added or removed per Tab / Shift+Tab on a selection.
+const INDENT_WIDTH: usize = 2;
+
+/// Indents each line by `INDENT_WIDTH` spaces, or outdents it by up to that
+/// many leading spaces. Returns the new lines and how many chars each one
+/// gained (positive) or lost (negative).
+fn shift_lines(lines: &[String], indent: bool) -> (Vec<String>, Vec<isize>) {
+    lines
+        .iter()
+        .map(|line| {
+            if indent {
+                (format!("{}{}", " ".repeat(INDENT_WIDTH), line), INDENT_WIDTH as isize)
+            } else {
+                let spaces = line.chars().take(INDENT_WIDTH).take_while(|&c| c == ' ').count();
+                (line[spaces..].to_string(), -(spaces as isize))
+            }
+        })
+        .unzip()
+}
+
 impl<'a> App<'a> {
     /// Extracts the currently selected text from tui-textarea using selection_range().
     pub(super) fn get_selected_text(&self) -> Option<String> {
-        let ((sr, sc), (er, ec)) = self.textarea.selection_range()?;
-        let lines = self.textarea.lines();
+        let ((sr, sc), (er, ec)) = self.buf.textarea.selection_range()?;
+        let lines = self.buf.textarea.lines();
 
         if sr == er {
             // Single line selection
@@ -35,18 +58,27 @@ impl<'a> App<'a> {
 
     // ─── Selection helpers ────────────────────────────────────────────────
 
-    /// Selects the word under the cursor (for double-click).
-    /// Groups: alphanumeric+underscore, whitespace, punctuation.
-    pub(super) fn select_word_at_cursor(&mut self) {
-        let (row, col) = self.textarea.cursor();
-        let lines = self.textarea.lines();
-        if row >= lines.len() {
-            return;
+    /// Rows touched by the selection, or the cursor row without one. A
+    /// multi-line selection ending at column 0 doesn't include that last row.
+    pub(super) fn selected_line_range(&self) -> (usize, usize) {
+        match self.buf.textarea.selection_range() {
+            Some(((start_row, _), (end_row, 0))) if end_row > start_row => (start_row, end_row - 1),
+            Some(((start_row, _), (end_row, _))) => (start_row, end_row),
+            None => {
+                let row = self.buf.textarea.cursor().0;
+                (row, row)
+            }
         }
-        let line = &lines[row];
+    }
+
+    /// Returns the (start, end) char columns of the word at `(row, col)`, or None
+    /// if the position is past the end of the line.
+    /// Groups: alphanumeric+underscore, whitespace, punctuation.
+    pub(super) fn word_bounds_at(&self, row: usize, col: usize) -> Option<(usize, usize)> {
+        let line = self.buf.textarea.lines().get(row)?;
         let chars: Vec<char> = line.chars().collect();
         if col >= chars.len() {
-            return;
+            return None;
         }
 
         let target = char_class(chars[col]);
@@ -61,21 +93,15 @@ impl<'a> App<'a> {
             end += 1;
         }
 
-        self.textarea.cancel_selection();
-        self.textarea
-            .move_cursor(CursorMove::Jump(row as u16, start as u16));
-        self.textarea.start_selection();
-        self.textarea
-            .move_cursor(CursorMove::Jump(row as u16, end as u16));
+        Some((start, end))
     }
 
-    /// Selects the paragraph around the cursor (for triple-click).
+    /// Returns the (start_row, end_row) of the paragraph around `row`.
     /// A paragraph is a contiguous block of non-empty lines.
-    pub(super) fn select_paragraph_at_cursor(&mut self) {
-        let (row, _) = self.textarea.cursor();
-        let lines = self.textarea.lines();
+    pub(super) fn paragraph_bounds_at(&self, row: usize) -> Option<(usize, usize)> {
+        let lines = self.buf.textarea.lines();
         if row >= lines.len() {
-            return;
+            return None;
         }
 
         // Find paragraph start: walk backward to first empty line
@@ -90,12 +116,116 @@ impl<'a> App<'a> {
             end += 1;
         }
 
-        let end_col = lines[end].len();
-        self.textarea.cancel_selection();
-        self.textarea
-            .move_cursor(CursorMove::Jump(start as u16, 0));
-        self.textarea.start_selection();
-        self.textarea
-            .move_cursor(CursorMove::Jump(end as u16, end_col as u16));
+        Some((start, end))
+    }
+
+    /// Returns the (start_row, end_row) of the markdown block around `row`:
+    /// the whole code fence for a row inside one, otherwise the paragraph
+    /// (see `paragraph_bounds_at`) without any neighbouring fence. None on a
+    /// blank line.
+    pub(super) fn element_bounds_at(&self, row: usize) -> Option<(usize, usize)> {
+        let lines = self.buf.textarea.lines();
+        let regions = code_highlight::find_code_fence_regions(lines);
+        let in_fence = |r: usize| regions.iter().find(|reg| (reg.start_line..=reg.end_line).contains(&r));
+        if let Some(region) = in_fence(row) {
+            return Some((region.start_line, region.end_line));
+        }
+        if lines.get(row)?.trim().is_empty() {
+            return None;
+        }
+        let (mut start, mut end) = self.paragraph_bounds_at(row)?;
+        start = (start..row).rev().find(|&r| in_fence(r).is_some()).map_or(start, |r| r + 1);
+        end = (row + 1..=end).find(|&r| in_fence(r).is_some()).map_or(end, |r| r - 1);
+        Some((start, end))
+    }
+
+    /// Replaces the selection with one anchored at `anchor` and ending (cursor) at `cursor`.
+    pub(super) fn set_selection(&mut self, anchor: (usize, usize), cursor: (usize, usize)) {
+        self.buf.textarea.cancel_selection();
+        self.buf.textarea
+            .move_cursor(CursorMove::Jump(anchor.0 as u16, anchor.1 as u16));
+        self.buf.textarea.start_selection();
+        self.buf.textarea
+            .move_cursor(CursorMove::Jump(cursor.0 as u16, cursor.1 as u16));
+    }
+
+    /// Selects the word under the cursor (for double-click).
+    pub(super) fn select_word_at_cursor(&mut self) {
+        let (row, col) = self.buf.textarea.cursor();
+        if let Some((start, end)) = self.word_bounds_at(row, col) {
+            self.set_selection((row, start), (row, end));
+        }
+    }
+
+    /// Selects the paragraph around the cursor (for triple-click).
+    pub(super) fn select_paragraph_at_cursor(&mut self) {
+        let (row, _) = self.buf.textarea.cursor();
+        if let Some((start, end)) = self.paragraph_bounds_at(row) {
+            let end_col = self.buf.textarea.lines()[end].chars().count();
+            self.set_selection((start, 0), (end, end_col));
+        }
+    }
+
+    /// Moves the drag endpoint to `(row, col)`, snapping to whole words or
+    /// paragraphs when the drag began with a double- or triple-click. The
+    /// originally clicked word/paragraph always stays selected.
+    pub(super) fn extend_drag_selection(&mut self, row: usize, col: usize) {
+        let (target_start, target_end) = match self.drag_granularity {
+            SelectGranularity::Char => {
+                self.buf.textarea
+                    .move_cursor(CursorMove::Jump(row as u16, col as u16));
+                return;
+            }
+            SelectGranularity::Word => match self.word_bounds_at(row, col) {
+                Some((start, end)) => ((row, start), (row, end)),
+                None => ((row, col), (row, col)),
+            },
+            SelectGranularity::Paragraph => match self.paragraph_bounds_at(row) {
+                Some((start, end)) => {
+                    let end_col = self.buf.textarea.lines()[end].chars().count();
+                    ((start, 0), (end, end_col))
+                }
+                None => return,
+            },
+        };
+
+        let (origin_start, origin_end) = self.drag_origin;
+        if target_start < origin_start {
+            self.set_selection(origin_end, target_start);
+        } else {
+            self.set_selection(origin_start, target_end.max(origin_end));
+        }
+    }
+
+    // ─── Block indent (Tab / Shift+Tab) ─────────────────────────────────
+
+    /// Indents (`indent`) or outdents every line touched by the selection,
+    /// then re-selects the same text so repeated presses keep shifting it.
+    pub(super) fn indent_selection(&mut self, indent: bool) {
+        let Some((start, end)) = self.buf.textarea.selection_range() else {
+            return;
+        };
+        let cursor_at_start = self.buf.textarea.cursor() == start;
+        let (start_row, end_row) = self.selected_line_range();
+        let (shifted, deltas) = shift_lines(&self.buf.textarea.lines()[start_row..=end_row], indent);
+        if deltas.iter().all(|&d| d == 0) {
+            return;
+        }
+        self.replace_lines(start_row, end_row, &shifted.join("\n"));
+
+        // Shift the selection ends with their text. Column 0 stays put so
+        // whole-line selections keep their indent, and a row past the range
+        // (a selection ending at column 0) didn't move.
+        let shift = |(row, col): (usize, usize)| {
+            let delta = if row <= end_row && col > 0 { deltas[row - start_row] } else { 0 };
+            (row, col.saturating_add_signed(delta))
+        };
+        let (start, end) = (shift(start), shift(end));
+        if cursor_at_start {
+            self.set_selection(end, start);
+        } else {
+            self.set_selection(start, end);
+        }
     }
 }
+
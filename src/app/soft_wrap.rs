@@ -0,0 +1,241 @@
+//! Soft wrap (`soft_wrap = true`): long lines are wrapped on screen only, so
+//! the buffer and the file on disk keep them whole.
+//!
+//! tui-textarea can't wrap, so the visible lines are rendered into an
+//! off-screen buffer, the usual overlays are applied there, and each line is
+//! then copied to the screen one segment per row. The buffer only spans the
+//! columns whose segments get a row (and the cursor's, so tui-textarea never
+//! scrolls sideways); it's bounded by the screen size, not the longest line.
+//! `editor_scroll_top` still counts buffer lines.
+//!
+//! Folded code blocks (see `fold`) go through the same path, with their
+//! hidden lines taking no rows; without soft wrap every other line takes
+//! exactly one row and keeps tui-textarea's horizontal scrolling.
+
+use ratatui::widgets::Widget;
+
+use super::*;
+
+/// Splits `line` into the char ranges shown on each screen row when wrapping
+/// at `width` columns. Rows break after the last space that fits, or
+/// mid-word when there is none. A line that exactly fills its last row gets
+/// an empty row after it, where a cursor at the end of the line is drawn.
+pub(super) fn wrap_segments(line: &str, width: usize) -> Vec<(usize, usize)> {
+    let chars: Vec<char> = line.chars().collect();
+    let width = width.max(1);
+    let mut segments = Vec::new();
+    let mut start = 0;
+    while chars.len() - start > width {
+        let limit = start + width;
+        let end = (start + 2..=limit)
+            .rev()
+            .find(|&end| chars[end - 1] == ' ')
+            .unwrap_or(limit);
+        segments.push((start, end));
+        start = end;
+    }
+    segments.push((start, chars.len()));
+    if chars.len() - start == width {
+        segments.push((chars.len(), chars.len()));
+    }
+    segments
+}
+
+/// Index of the segment showing char column `col`. A column on a break
+/// belongs to the row it starts.
+fn segment_of(segments: &[(usize, usize)], col: usize) -> usize {
+    segments
+        .iter()
+        .rposition(|&(start, _)| start <= col)
+        .unwrap_or(0)
+}
+
+impl<'a> App<'a> {
+    // ─── Soft wrap ───────────────────────────────────────────────────────
+
+    /// Screen rows of buffer line `idx`, as char ranges: its wrap segments
+    /// when wrapping at `wrap` columns, the whole line otherwise, and none
+    /// when a fold in `folds` hides it.
+    fn line_rows(&self, idx: usize, wrap: Option<usize>, folds: &[(usize, usize)]) -> Vec<(usize, usize)> {
+        if folds.iter().any(|&(start, end)| idx > start && idx <= end) {
+            return Vec::new();
+        }
+        let line = &self.buf.textarea.lines()[idx];
+        match wrap {
+            Some(width) => wrap_segments(line, width),
+            None => vec![(0, line.chars().count())],
+        }
+    }
+
+    /// Columns to wrap at, or None when soft wrap is off (folds only).
+    fn soft_wrap_width(&self) -> Option<usize> {
+        self.soft_wrap()
+            .then(|| self.available_text_width().max(1))
+    }
+
+    /// Renders the editor with long lines wrapped across screen rows and
+    /// folded code blocks collapsed to their opening fence.
+    pub(super) fn render_laid_out_editor(&mut self, frame: &mut Frame, area: Rect) {
+        let wrap = self.soft_wrap_width();
+        let folds = self.fold_spans();
+        let total_lines = self.buf.textarea.lines().len();
+        let gutter_width = format!("{}", total_lines).len() as u16 + 1;
+        // tui-textarea's gutter: the line number plus a space either side
+        let text_x = gutter_width + 1;
+
+        let top = self.soft_scroll_top(area.height as usize, wrap, &folds);
+        // Buffer lines with at least one row on screen, and the furthest
+        // column any of their on-screen segments reach
+        let mut bottom = top;
+        let mut rows = 0;
+        let mut visible_cols = 0;
+        while bottom < total_lines && rows < area.height as usize {
+            let segments = self.line_rows(bottom, wrap, &folds);
+            let shown = segments.len().min(area.height as usize - rows);
+            if let Some(&(_, end)) = segments[..shown].last() {
+                visible_cols = visible_cols.max(end);
+            }
+            rows += segments.len();
+            bottom += 1;
+        }
+        let width = match wrap {
+            Some(_) => {
+                let (cursor_row, cursor_col) = self.buf.textarea.cursor();
+                if (top..bottom).contains(&cursor_row) {
+                    visible_cols = visible_cols.max(cursor_col);
+                }
+                (text_x as usize + visible_cols + 1).min(u16::MAX as usize) as u16
+            }
+            None => area.width,
+        };
+        let unwrapped = Rect {
+            x: area.x,
+            y: area.y,
+            width,
+            height: (bottom - top).clamp(1, u16::MAX as usize) as u16,
+        };
+
+        // Point tui-textarea's viewport at `top` (scrolled fully left when
+        // wrapping). scroll() pulls the cursor into the old viewport; put it
+        // back where it was.
+        let cursor = self.buf.textarea.cursor();
+        let delta = top as i32 - self.buf.editor_scroll_top as i32;
+        let cols = if wrap.is_some() { -i16::MAX } else { 0 };
+        self.buf.textarea
+            .scroll((delta.clamp(-(i16::MAX as i32), i16::MAX as i32) as i16, cols));
+        self.buf.textarea
+            .move_cursor(CursorMove::Jump(cursor.0 as u16, cursor.1 as u16));
+        self.buf.editor_scroll_top = top as u16;
+
+        let mut offscreen = std::mem::take(&mut self.laid_out_buffer);
+        offscreen.resize(unwrapped);
+        offscreen.reset();
+        (&self.buf.textarea).render(unwrapped, &mut offscreen);
+        self.apply_editor_overlays(&mut offscreen, unwrapped, gutter_width);
+
+        // Copy each line to the screen, one segment per row
+        frame.render_widget(Block::default().style(theme::editor_style()), area);
+        let screen = frame.buffer_mut();
+        let mut row: u16 = 0;
+        for idx in top..bottom {
+            let src_y = area.y + (idx - top) as u16;
+            let segments = self.line_rows(idx, wrap, &folds);
+            for (k, &(start, end)) in segments.iter().enumerate() {
+                if row >= area.height {
+                    break;
+                }
+                let dst_y = area.y + row;
+                // The line number only on the first row of the line
+                for x in area.x..area.x + text_x.min(area.width) {
+                    if let (Some(src), Some(dst)) =
+                        (offscreen.cell((x, src_y)), screen.cell_mut((x, dst_y)))
+                    {
+                        *dst = src.clone();
+                        if k > 0 {
+                            dst.set_symbol(" ");
+                        }
+                    }
+                }
+                // The last row also carries the cell after the text, where an
+                // end-of-line cursor is drawn. Unwrapped rows are copied whole,
+                // as tui-textarea scrolled them.
+                let span = match wrap {
+                    Some(_) => end - start + usize::from(k == segments.len() - 1),
+                    None => area.width as usize,
+                };
+                for offset in 0..span {
+                    let src_x = area.x + text_x + (start + offset) as u16;
+                    let dst_x = area.x + text_x + offset as u16;
+                    if dst_x >= area.x + area.width {
+                        break;
+                    }
+                    if let (Some(src), Some(dst)) =
+                        (offscreen.cell((src_x, src_y)), screen.cell_mut((dst_x, dst_y)))
+                    {
+                        *dst = src.clone();
+                    }
+                }
+                // A folded block's summary follows its opening fence
+                if let Some(&(_, fold_end)) = folds.iter().find(|&&(s, _)| s == idx) {
+                    if k == segments.len() - 1 {
+                        let x = area.x + text_x + (end - start) as u16 + 1;
+                        let right = area.x + area.width;
+                        if x < right {
+                            let summary = format!("\u{2026} ({} lines)", fold_end - idx - 1);
+                            let style = Style::default().fg(theme::active().marker);
+                            screen.set_stringn(x, dst_y, summary, (right - x) as usize, style);
+                        }
+                    }
+                }
+                row += 1;
+            }
+        }
+        self.laid_out_buffer = offscreen;
+
+        if bottom == total_lines {
+            render::render_tildes(frame, area, row, gutter_width);
+        }
+    }
+
+    /// First buffer line to show so the cursor's screen row stays visible,
+    /// moving as little as possible from the current `editor_scroll_top`.
+    fn soft_scroll_top(&self, height: usize, wrap: Option<usize>, folds: &[(usize, usize)]) -> usize {
+        let (row, col) = self.buf.textarea.cursor();
+        let mut top = (self.buf.editor_scroll_top as usize).min(row);
+
+        // Screen rows from the top line down to the cursor's row, inclusive
+        let cursor_segments = self.line_rows(row, wrap, folds);
+        let mut rows: usize = (top..row)
+            .map(|idx| self.line_rows(idx, wrap, folds).len())
+            .sum::<usize>()
+            + segment_of(&cursor_segments, col)
+            + 1;
+        while top < row && rows > height {
+            rows -= self.line_rows(top, wrap, folds).len();
+            top += 1;
+        }
+        top
+    }
+
+    /// Maps a click on screen row `row` and text column `col` (both relative
+    /// to the editor's text area) to a buffer `(row, col)` under soft wrap
+    /// or folding. Clicks past the end of a wrapped row land on its last char.
+    pub(super) fn laid_out_pos(&self, row: u16, col: u16) -> (u16, u16) {
+        let wrap = self.soft_wrap_width();
+        let folds = self.fold_spans();
+        let lines = self.buf.textarea.lines();
+        let mut remaining = row as usize;
+        for line_idx in self.buf.editor_scroll_top as usize..lines.len() {
+            let segments = self.line_rows(line_idx, wrap, &folds);
+            if let Some(&(start, end)) = segments.get(remaining) {
+                let last = remaining == segments.len() - 1;
+                let row_end = if last { end } else { end.saturating_sub(1).max(start) };
+                let col = (start + col as usize).min(row_end);
+                return (line_idx as u16, col as u16);
+            }
+            remaining -= segments.len();
+        }
+        // Below the text: tui-textarea clamps this to the last line
+        (lines.len() as u16, col)
+    }
+}
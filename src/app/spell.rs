@@ -0,0 +1,147 @@
+//! Spell checking (`spellcheck = true`): misspelled words in prose are
+//! underlined, and F7 jumps to the next one.
+//!
+//! The dictionary is loaded at startup, or on switching to a file whose
+//! config turns spell check on (F7 loads it too), never while rendering.
+//! Misspellings are cached per line text, so typing only rechecks the line
+//! being edited. Code fences are never checked.
+
+use super::*;
+use std::path::Path;
+use unicode_width::UnicodeWidthChar;
+
+/// The checker for the word list at `path`, or the first system one found.
+fn load_checker(path: Option<&Path>) -> Result<spellcheck::Checker, String> {
+    let path = match path {
+        Some(path) => path.to_path_buf(),
+        None => spellcheck::Dictionary::default_path()
+            .ok_or("No dictionary for spell check (set spell_dictionary)")?,
+    };
+    let dict = spellcheck::Dictionary::load(&path)
+        .map_err(|e| format!("Can't read dictionary {}: {}", path.display(), e))?;
+    Ok(spellcheck::Checker::new(dict))
+}
+
+impl<'a> App<'a> {
+    // ─── Spell check (F7) ────────────────────────────────────────────────
+
+    /// Loads the dictionary if spell checking is on and it isn't loaded yet,
+    /// reporting in the status bar if it can't be read.
+    pub fn load_spell_dictionary(&mut self) {
+        if self.buf.config.spellcheck && self.spell.is_none() {
+            let checker = load_checker(self.buf.config.spell_dictionary.as_deref());
+            if let Err(e) = &checker {
+                self.set_status_as(e, Severity::Error);
+            }
+            self.spell = Some(checker);
+        }
+    }
+
+    /// Underlines the misspelled words on visible prose lines. Lines inside
+    /// code fences are left alone.
+    pub(super) fn apply_spell_underlines(&mut self, buf: &mut Buffer, area: Rect, gutter_width: u16) {
+        if !self.buf.config.spellcheck {
+            return;
+        }
+        let Some(Ok(checker)) = self.spell.as_mut() else {
+            return;
+        };
+        // The cached regions lag while syntect is still loading
+        let fresh_regions;
+        let regions = if self.buf.code_fence_dirty {
+            fresh_regions = code_highlight::find_code_fence_regions(self.buf.textarea.lines());
+            &fresh_regions
+        } else {
+            &self.buf.code_fence_regions
+        };
+        let scroll_top = self.buf.editor_scroll_top as usize;
+        let visible = self.buf
+            .textarea
+            .lines()
+            .iter()
+            .enumerate()
+            .skip(scroll_top)
+            .take(area.height as usize);
+        let text_start_x = area.x + gutter_width + 1; // +1 for leading space in gutter
+
+        for (line_idx, line) in visible {
+            let in_fence = regions
+                .iter()
+                .any(|r| line_idx >= r.start_line && line_idx <= r.end_line);
+            if in_fence {
+                continue;
+            }
+            let screen_row = area.y + (line_idx - scroll_top) as u16;
+
+            for &(start, end) in checker.check(line) {
+                // Misspellings are char ranges; wide chars before them take
+                // two cells each
+                let prefix: usize = line.chars().take(start).map(|c| c.width().unwrap_or(0)).sum();
+                let word: usize = line.chars().skip(start).take(end - start).map(|c| c.width().unwrap_or(0)).sum();
+                for offset in prefix..prefix + word {
+                    let cell_x = text_start_x as usize + offset;
+                    if cell_x >= (area.x + area.width) as usize {
+                        break;
+                    }
+                    if let Some(cell) = buf.cell_mut((cell_x as u16, screen_row)) {
+                        let style = cell
+                            .style()
+                            .add_modifier(Modifier::UNDERLINED)
+                            .underline_color(theme::active().misspelled);
+                        cell.set_style(style);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Moves the cursor to the next misspelled word, wrapping around to the
+    /// top of the file.
+    pub(super) fn next_misspelling(&mut self) {
+        if !self.buf.config.spellcheck {
+            self.set_status("Spell check is off (set spellcheck = true)");
+            return;
+        }
+        self.load_spell_dictionary();
+        let checker = match self.spell.as_mut() {
+            Some(Ok(checker)) => checker,
+            Some(Err(e)) => {
+                let msg = e.clone();
+                self.set_status_as(&msg, Severity::Error);
+                return;
+            }
+            None => return,
+        };
+        let lines = self.buf.textarea.lines();
+        let regions = code_highlight::find_code_fence_regions(lines);
+        let (row, col) = self.buf.textarea.cursor();
+        let count = lines.len();
+        // The cursor line after the cursor, the lines below, then from the
+        // top round to the cursor line again, up to the cursor
+        let found = (0..=count).find_map(|i| {
+            let r = (row + i) % count;
+            if regions.iter().any(|reg| r >= reg.start_line && r <= reg.end_line) {
+                return None;
+            }
+            checker
+                .check(&lines[r])
+                .iter()
+                .find(|&&(start, _)| match i {
+                    0 => start > col,
+                    _ if i == count => start <= col,
+                    _ => true,
+                })
+                .map(|&(start, end)| (r, start, end))
+        });
+        let Some((r, start, end)) = found else {
+            self.set_status("No misspellings");
+            return;
+        };
+        let word: String = lines[r].chars().skip(start).take(end - start).collect();
+        self.push_jump();
+        self.buf.textarea.cancel_selection();
+        self.buf.textarea.move_cursor(CursorMove::Jump(r as u16, start as u16));
+        self.reveal_cursor();
+        self.set_status(&format!("Misspelled: {}", word));
+    }
+}
@@ -0,0 +1,75 @@
+//! Split view (Alt+V): the editor on the left and the live preview on the
+//! right. Keys go to the editor; the preview follows the cursor so the
+//! block being edited stays in view, while the mouse wheel can still scroll
+//! it freely until the cursor moves to another line.
+//!
+//! Below `SPLIT_MIN_WIDTH` columns there's no room for two panes: Alt+V
+//! toggles between editor and preview like Tab, and an open split view
+//! shows just the editor until the terminal is wide enough again.
+
+use super::*;
+
+/// Narrowest content area that shows the split view side by side.
+pub(super) const SPLIT_MIN_WIDTH: u16 = 60;
+
+impl<'a> App<'a> {
+    // ─── Split view (Alt+V) ──────────────────────────────────────────────
+
+    /// Opens or closes the split view.
+    pub(super) fn toggle_split(&mut self) {
+        let target = match self.mode {
+            Mode::Split => Mode::Editor,
+            Mode::Editor if !self.split_fits => Mode::Preview,
+            Mode::Preview if !self.split_fits => Mode::Editor,
+            _ => Mode::Split,
+        };
+        self.set_mode(target);
+    }
+
+    /// True while keys and clicks go to the editor, alone or split.
+    pub(super) fn editing(&self) -> bool {
+        matches!(self.mode, Mode::Editor | Mode::Split)
+    }
+
+    /// The editor and preview panes of the split view, either side of a
+    /// one-column divider.
+    pub(super) fn split_panes(area: Rect) -> (Rect, Rect, Rect) {
+        let panes = Layout::horizontal([
+            Constraint::Fill(1),
+            Constraint::Length(1),
+            Constraint::Fill(1),
+        ])
+        .split(area);
+        (panes[0], panes[1], panes[2])
+    }
+
+    /// Renders the editor, a divider and the preview side by side.
+    pub(super) fn render_split(&mut self, frame: &mut Frame, editor: Rect, divider: Rect, preview_area: Rect) {
+        self.render_editor(frame, editor);
+
+        let line = Paragraph::new(vec![Line::from("\u{2502}"); divider.height as usize])
+            .style(Style::default().fg(theme::active().border));
+        frame.render_widget(line, divider);
+
+        self.sync_preview_scroll(preview_area);
+        let content = self.textarea_content();
+        let base_dir = self.buf.file_path.parent().unwrap_or(std::path::Path::new("."));
+        preview::render(frame, preview_area, &content, &mut self.preview, base_dir);
+    }
+
+    /// Scrolls the preview to the cursor's source line when the cursor has
+    /// moved to another line and that line is out of view.
+    fn sync_preview_scroll(&mut self, area: Rect) {
+        let row = self.buf.textarea.cursor().0;
+        if self.split_synced_row == Some(row) {
+            return;
+        }
+        self.split_synced_row = Some(row);
+        let (target, _) = self.preview_line_at_cursor(area.width);
+        let top = self.preview.scroll_offset;
+        if target < top || target >= top + area.height {
+            // Leave a third of the pane above for context
+            self.preview.scroll_offset = target.saturating_sub(area.height / 3);
+        }
+    }
+}
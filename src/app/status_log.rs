@@ -0,0 +1,78 @@
+//! Message log (F2): the last `STATUS_LOG_SIZE` status bar messages, newest
+//! first, with how long ago each was shown. Messages leave the status bar
+//! after a few seconds; the log keeps them for the session, so a failed
+//! export or save can still be read afterwards. Any key closes it.
+
+use super::*;
+
+/// Messages kept in the log.
+pub const STATUS_LOG_SIZE: usize = 100;
+
+/// Widest the message log modal gets.
+const STATUS_LOG_WIDTH: u16 = 80;
+
+/// Adds `msg` to the front of `log`, dropping the oldest past
+/// `STATUS_LOG_SIZE`. Empty messages (clears) aren't logged.
+pub(super) fn push_status(log: &mut VecDeque<(Instant, String, Severity)>, msg: &str, severity: Severity) {
+    if msg.is_empty() {
+        return;
+    }
+    log.push_front((Instant::now(), msg.to_string(), severity));
+    log.truncate(STATUS_LOG_SIZE);
+}
+
+/// A short age, as "12s", "5m" or "2h".
+fn age(elapsed: Duration) -> String {
+    match elapsed.as_secs() {
+        secs @ 0..60 => format!("{}s", secs),
+        secs @ 60..3600 => format!("{}m", secs / 60),
+        secs => format!("{}h", secs / 3600),
+    }
+}
+
+impl<'a> App<'a> {
+    // ─── Message log (F2) ────────────────────────────────────────────────
+
+    /// The log's lines, newest message first.
+    pub fn status_log_lines(&self) -> Vec<Line<'static>> {
+        if self.status_log.is_empty() {
+            return vec![Line::from(Span::styled(
+                "  No messages yet",
+                Style::default().fg(theme::active().line_number),
+            ))];
+        }
+        self.status_log
+            .iter()
+            .map(|(time, msg, severity)| {
+                Line::from(vec![
+                    Span::styled(
+                        format!("{:>5} ", age(time.elapsed())),
+                        Style::default().fg(theme::active().line_number),
+                    ),
+                    Span::styled(msg.clone(), Style { fg: severity.color(), ..Style::default() }),
+                ])
+            })
+            .collect()
+    }
+
+    /// Draws the message log, centered and sized to its messages.
+    pub(super) fn render_status_log(&self, frame: &mut Frame) {
+        let area = frame.area();
+        let lines = self.status_log_lines();
+        let width = STATUS_LOG_WIDTH.min(area.width.saturating_sub(4));
+        let height = (lines.len() as u16 + 2).min(area.height.saturating_sub(2));
+        let x = (area.width.saturating_sub(width)) / 2;
+        let y = (area.height.saturating_sub(height)) / 2;
+        let log_area = Rect::new(x, y, width, height);
+
+        frame.render_widget(Clear, log_area);
+
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(theme::active().border))
+            .title(" Messages ")
+            .style(theme::editor_style());
+
+        frame.render_widget(Paragraph::new(lines).block(block), log_area);
+    }
+}
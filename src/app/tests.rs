@@ -78,6 +78,158 @@ fn esc_in_rename_mode_cancels_rename_not_mode_switch() {
     assert!(!app.should_quit);
 }
 
+// ─── Quit Tests ──────────────────────────────────────────────────
+
+/// An app with an unsaved edit to a file whose directory doesn't exist,
+/// so saving fails.
+fn app_with_unwritable_file() -> (App<'static>, tempfile::TempDir) {
+    let dir = tempfile::TempDir::new().unwrap();
+    let mut app = App::new(dir.path().join("missing/notes.md"));
+    app.handle_event(char_event('x'));
+    assert!(app.buf.modified);
+    (app, dir)
+}
+
+#[test]
+fn ctrl_q_stays_open_when_save_fails() {
+    let (mut app, _dir) = app_with_unwritable_file();
+    app.handle_event(ctrl_key('q'));
+    assert!(!app.should_quit);
+    assert!(app.buf.modified);
+    assert!(app.status_message.starts_with("Error saving"), "{}", app.status_message);
+    assert!(app.status_message.ends_with("Ctrl+Q again quits without saving"));
+
+    app.handle_event(ctrl_key('q'));
+    assert!(app.should_quit, "a second Ctrl+Q quits without saving");
+}
+
+#[test]
+fn other_key_after_failed_quit_disarms_it() {
+    let (mut app, _dir) = app_with_unwritable_file();
+    app.handle_event(ctrl_key('q'));
+    app.handle_event(key_event(KeyCode::Left));
+    app.handle_event(ctrl_key('q'));
+    assert!(!app.should_quit, "the save is retried first");
+}
+
+#[test]
+fn signal_saves_only_with_save_on_signal() {
+    let (mut app, tmp) = app_with_content("hello");
+    app.handle_event(char_event('x'));
+    app.quit_on_signal();
+    assert!(app.should_quit);
+    assert_eq!(std::fs::read_to_string(tmp.path()).unwrap(), "hello", "edits dropped by default");
+
+    let (mut app, tmp) = app_with_content("hello");
+    app.buf.config.save_on_signal = true;
+    app.handle_event(char_event('x'));
+    app.quit_on_signal();
+    assert!(app.should_quit);
+    assert_eq!(std::fs::read_to_string(tmp.path()).unwrap(), "xhello");
+}
+
+// ─── Save Tests ──────────────────────────────────────────────────
+
+#[test]
+fn save_replaces_file_without_leaving_temp_file() {
+    let dir = tempfile::TempDir::new().unwrap();
+    let path = dir.path().join("notes.md");
+    std::fs::write(&path, "old").unwrap();
+    let mut app = App::new(path.clone());
+    app.handle_event(ctrl_key('a'));
+    type_str(&mut app, "new");
+
+    app.handle_event(ctrl_key('s'));
+    assert!(!app.buf.modified);
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), "new");
+    let names: Vec<_> = std::fs::read_dir(dir.path())
+        .unwrap()
+        .map(|e| e.unwrap().file_name())
+        .collect();
+    assert_eq!(names, ["notes.md"], "no .tmp file left behind");
+}
+
+#[cfg(unix)]
+#[test]
+fn save_keeps_file_permissions() {
+    use std::os::unix::fs::PermissionsExt;
+    let dir = tempfile::TempDir::new().unwrap();
+    let path = dir.path().join("script.md");
+    std::fs::write(&path, "old").unwrap();
+    std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o640)).unwrap();
+    let mut app = App::new(path.clone());
+    type_str(&mut app, "x");
+
+    app.handle_event(ctrl_key('s'));
+    let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+    assert_eq!(mode & 0o777, 0o640);
+}
+
+#[cfg(unix)]
+#[test]
+fn save_through_symlink_keeps_the_link() {
+    let dir = tempfile::TempDir::new().unwrap();
+    let target = dir.path().join("real.md");
+    let link = dir.path().join("link.md");
+    std::fs::write(&target, "old").unwrap();
+    std::os::unix::fs::symlink(&target, &link).unwrap();
+    let mut app = App::new(link.clone());
+    type_str(&mut app, "x");
+
+    app.handle_event(ctrl_key('s'));
+    assert!(std::fs::symlink_metadata(&link).unwrap().file_type().is_symlink());
+    assert_eq!(std::fs::read_to_string(&target).unwrap(), "xold");
+}
+
+#[test]
+fn project_config_disables_format_on_save() {
+    let dir = tempfile::TempDir::new().unwrap();
+    std::fs::write(dir.path().join(".marko.toml"), "format_on_save = false").unwrap();
+    let path = dir.path().join("notes.md");
+    let table = "|a|b|\n|-|-|\n|long cell|x|";
+    std::fs::write(&path, table).unwrap();
+    let mut app = App::new(path.clone());
+    app.buf.config = Config::load_for(&path).unwrap();
+    type_str(&mut app, "x");
+
+    app.handle_event(ctrl_key('s'));
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), format!("x{}", table));
+}
+
+#[test]
+fn modeline_overrides_wrap_width_and_format_on_save() {
+    let table = "|a|b|\n|-|-|\n|long cell|x|";
+    let content = format!("<!-- marko: wrap=20 format=off theme=light -->\n{}", table);
+    let (mut app, tmp) = app_with_content(&content);
+    setup_viewport(&mut app, 100, 20);
+    assert_eq!(app.available_text_width(), 20);
+
+    app.buf.textarea.move_cursor(CursorMove::Bottom);
+    app.buf.textarea.move_cursor(CursorMove::End);
+    type_str(&mut app, "y");
+    app.handle_event(ctrl_key('s'));
+    assert_eq!(std::fs::read_to_string(tmp.path()).unwrap(), format!("{}y", content));
+}
+
+#[test]
+fn modeline_theme_applies_while_its_file_is_shown() {
+    let dir = tempfile::TempDir::new().unwrap();
+    let a = dir.path().join("a.md");
+    let b = dir.path().join("b.md");
+    std::fs::write(&a, "<!-- marko: theme=light -->\nalpha").unwrap();
+    std::fs::write(&b, "beta").unwrap();
+    theme::set_active(&theme::DARK);
+    let mut app = App::new(a.clone());
+    app.file_list.push(b.clone());
+    app.apply_file_theme();
+    assert_eq!(theme::active().name, "light");
+
+    app.handle_event(Event::Key(KeyEvent::new(KeyCode::PageDown, KeyModifiers::CONTROL)));
+    assert_eq!(theme::active().name, "dark", "back to the session's theme");
+    app.handle_event(Event::Key(KeyEvent::new(KeyCode::PageDown, KeyModifiers::CONTROL)));
+    assert_eq!(theme::active().name, "light");
+}
+
 // ─── Preview Scrolling Tests ─────────────────────────────────────
 
 #[test]
@@ -198,6 +350,37 @@ fn mouse_scroll_down_in_preview() {
     assert_eq!(app.preview.scroll_offset, 3); // 0 + SCROLL_LINES(3)
 }
 
+#[test]
+fn tab_into_preview_scrolls_to_cursor_block() {
+    let content: String = (1..=60).map(|i| format!("Paragraph {}\n\n", i)).collect();
+    let (mut app, _tmp) = app_with_content(&content);
+    setup_viewport(&mut app, 80, 20);
+
+    // Source line 58 is "Paragraph 30"
+    app.buf.textarea.move_cursor(CursorMove::Jump(58, 0));
+    app.handle_event(key_event(KeyCode::Tab));
+    assert_eq!(app.mode, Mode::Preview);
+    let top = app.preview.scroll_offset as usize;
+    let rendered = renderer::render_lines(&content, 78);
+    let first: String = rendered[top].spans.iter().map(|s| s.content.as_ref()).collect();
+    assert_eq!(first.trim(), "Paragraph 30");
+
+    // Home still goes back to the top
+    app.handle_event(key_event(KeyCode::Home));
+    assert_eq!(app.preview.scroll_offset, 0);
+}
+
+#[test]
+fn tab_into_preview_at_end_does_not_overscroll() {
+    let content: String = (1..=60).map(|i| format!("Paragraph {}\n\n", i)).collect();
+    let (mut app, _tmp) = app_with_content(&content);
+    setup_viewport(&mut app, 80, 20);
+    app.buf.textarea.move_cursor(CursorMove::Bottom);
+    app.handle_event(key_event(KeyCode::Tab));
+    let height = renderer::render_lines(&content, 78).len() as u16;
+    assert_eq!(app.preview.scroll_offset, height - 20);
+}
+
 #[test]
 fn mouse_click_editor_tab_switches_to_editor() {
     let (mut app, _tmp) = app_with_content("hello");
@@ -265,7 +448,7 @@ fn mouse_release_cancels_zero_length_selection() {
     ));
     assert!(!app.mouse_dragging);
     // Zero-length selection should be cancelled
-    assert!(app.textarea.selection_range().is_none());
+    assert!(app.buf.textarea.selection_range().is_none());
 }
 
 #[test]
@@ -310,9 +493,9 @@ fn tick_keeps_fresh_status_message() {
 fn select_word_at_cursor_selects_word() {
     let (mut app, _tmp) = app_with_content("hello world");
     // Move cursor to col 1 (in "hello")
-    app.textarea.move_cursor(CursorMove::Jump(0, 1));
+    app.buf.textarea.move_cursor(CursorMove::Jump(0, 1));
     app.select_word_at_cursor();
-    let range = app.textarea.selection_range();
+    let range = app.buf.textarea.selection_range();
     assert!(range.is_some(), "Should have a selection");
     let ((sr, sc), (er, ec)) = range.unwrap();
     assert_eq!((sr, sc), (0, 0));
@@ -322,9 +505,9 @@ fn select_word_at_cursor_selects_word() {
 #[test]
 fn select_word_at_cursor_selects_second_word() {
     let (mut app, _tmp) = app_with_content("hello world");
-    app.textarea.move_cursor(CursorMove::Jump(0, 7));
+    app.buf.textarea.move_cursor(CursorMove::Jump(0, 7));
     app.select_word_at_cursor();
-    let range = app.textarea.selection_range();
+    let range = app.buf.textarea.selection_range();
     assert!(range.is_some());
     let ((sr, sc), (er, ec)) = range.unwrap();
     assert_eq!((sr, sc), (0, 6));
@@ -334,9 +517,9 @@ fn select_word_at_cursor_selects_second_word() {
 #[test]
 fn select_word_at_cursor_selects_punctuation() {
     let (mut app, _tmp) = app_with_content("hello...world");
-    app.textarea.move_cursor(CursorMove::Jump(0, 6));
+    app.buf.textarea.move_cursor(CursorMove::Jump(0, 6));
     app.select_word_at_cursor();
-    let range = app.textarea.selection_range();
+    let range = app.buf.textarea.selection_range();
     assert!(range.is_some());
     let ((sr, sc), (er, ec)) = range.unwrap();
     assert_eq!((sr, sc), (0, 5));
@@ -346,9 +529,9 @@ fn select_word_at_cursor_selects_punctuation() {
 #[test]
 fn select_paragraph_single_paragraph() {
     let (mut app, _tmp) = app_with_content("line one\nline two\nline three");
-    app.textarea.move_cursor(CursorMove::Jump(1, 0));
+    app.buf.textarea.move_cursor(CursorMove::Jump(1, 0));
     app.select_paragraph_at_cursor();
-    let range = app.textarea.selection_range();
+    let range = app.buf.textarea.selection_range();
     assert!(range.is_some());
     let ((sr, sc), (er, ec)) = range.unwrap();
     assert_eq!((sr, sc), (0, 0));
@@ -360,9 +543,9 @@ fn select_paragraph_single_paragraph() {
 fn select_paragraph_stops_at_empty_line() {
     let (mut app, _tmp) = app_with_content("para one\n\npara two");
     // Cursor on "para two" (line 2)
-    app.textarea.move_cursor(CursorMove::Jump(2, 0));
+    app.buf.textarea.move_cursor(CursorMove::Jump(2, 0));
     app.select_paragraph_at_cursor();
-    let range = app.textarea.selection_range();
+    let range = app.buf.textarea.selection_range();
     assert!(range.is_some());
     let ((sr, sc), (er, ec)) = range.unwrap();
     assert_eq!((sr, sc), (2, 0)); // starts at line 2 (after empty line)
@@ -374,9 +557,9 @@ fn ctrl_l_moves_to_line_start() {
     let (mut app, _tmp) = app_with_content("hello world");
     // Move cursor to middle of line
     app.handle_event(key_event(KeyCode::End));
-    assert_eq!(app.textarea.cursor().1, 11);
+    assert_eq!(app.buf.textarea.cursor().1, 11);
     app.handle_event(ctrl_key('l'));
-    assert_eq!(app.textarea.cursor().1, 0, "Ctrl+L should move to column 0");
+    assert_eq!(app.buf.textarea.cursor().1, 0, "Ctrl+L should move to column 0");
 }
 
 #[test]
@@ -384,10 +567,10 @@ fn ctrl_l_cancels_selection() {
     let (mut app, _tmp) = app_with_content("hello world");
     // Create a selection
     app.handle_event(ctrl_key('a'));
-    assert!(app.textarea.selection_range().is_some());
+    assert!(app.buf.textarea.selection_range().is_some());
     app.handle_event(ctrl_key('l'));
     assert!(
-        app.textarea.selection_range().is_none(),
+        app.buf.textarea.selection_range().is_none(),
         "Ctrl+L should cancel any active selection"
     );
 }
@@ -398,7 +581,7 @@ fn ctrl_l_cancels_selection() {
 fn gutter_marks_empty_for_non_git_file() {
     let (app, _tmp) = app_with_content("hello");
     // Temp files are not in a git repo, so gutter_marks should be empty
-    assert!(app.gutter_marks.is_empty());
+    assert!(app.buf.gutter_marks.is_empty());
 }
 
 // ─── Auto-Wrap Tests ─────────────────────────────────────────
@@ -407,10 +590,10 @@ fn gutter_marks_empty_for_non_git_file() {
 fn navigation_keys_do_not_trigger_wrap() {
     // Create a line longer than the viewport width
     let long_line = "a ".repeat(50); // 100 chars
-    let (mut app, _tmp) = app_with_content(&long_line.trim());
+    let (mut app, _tmp) = app_with_content(long_line.trim());
     setup_viewport(&mut app, 40, 20);
     // Store line content before navigation
-    let line_before = app.textarea.lines()[0].to_string();
+    let line_before = app.buf.textarea.lines()[0].to_string();
 
     // Press various navigation keys
     for code in &[
@@ -428,7 +611,7 @@ fn navigation_keys_do_not_trigger_wrap() {
 
     // Line should be unchanged — navigation must not trigger wrapping
     assert_eq!(
-        app.textarea.lines()[0], line_before,
+        app.buf.textarea.lines()[0], line_before,
         "Navigation keys should not modify the line"
     );
 }
@@ -444,7 +627,7 @@ fn typing_triggers_wrap() {
     }
     // Should have wrapped into more than one line
     assert!(
-        app.textarea.lines().len() > 1,
+        app.buf.textarea.lines().len() > 1,
         "Typing past viewport width should trigger auto-wrap"
     );
 }
@@ -459,7 +642,7 @@ fn first_render_wraps_long_lines() {
 
     let mut app = App::new(tmp.path().to_path_buf());
     // Before first render, content is raw (unwrapped)
-    assert_eq!(app.textarea.lines().len(), 1);
+    assert_eq!(app.buf.textarea.lines().len(), 1);
 
     // Simulate first render: set content_area and trigger reflow
     setup_viewport(&mut app, 40, 20);
@@ -470,12 +653,12 @@ fn first_render_wraps_long_lines() {
 
     // Content should now be wrapped into multiple lines
     assert!(
-        app.textarea.lines().len() > 1,
+        app.buf.textarea.lines().len() > 1,
         "Long lines should be hard-wrapped on first render reflow"
     );
     // File should not be marked as modified
     assert!(
-        !app.modified,
+        !app.buf.modified,
         "Reflowed content should not mark file as modified"
     );
 }
@@ -489,21 +672,21 @@ fn reflow_to_wider_width_unwraps_lines() {
     setup_viewport(&mut app, 30, 20);
     let narrow_width = app.available_text_width();
     app.reflow_content(narrow_width);
-    let narrow_line_count = app.textarea.lines().len();
+    let narrow_line_count = app.buf.textarea.lines().len();
     assert!(narrow_line_count > 1, "Should wrap at narrow width");
 
     // Now expand to wider width
     setup_viewport(&mut app, 80, 20);
     let wide_width = app.available_text_width();
     app.reflow_content(wide_width);
-    let wide_line_count = app.textarea.lines().len();
+    let wide_line_count = app.buf.textarea.lines().len();
     assert!(
         wide_line_count < narrow_line_count,
         "Expanding should unwrap lines: {} should be less than {}",
         wide_line_count,
         narrow_line_count
     );
-    assert!(!app.modified, "Reflow should not mark file as modified");
+    assert!(!app.buf.modified, "Reflow should not mark file as modified");
 }
 
 // ─── Docx State Tests ──────────────────────────────────────────
@@ -511,7 +694,7 @@ fn reflow_to_wider_width_unwraps_lines() {
 #[test]
 fn docx_state_is_none_for_regular_md() {
     let (app, _tmp) = app_with_content("hello");
-    assert!(app.docx_state.is_none());
+    assert!(app.buf.docx_state.is_none());
 }
 
 // ─── Scroll Tracking Tests ────────────────────────────────────────
@@ -525,7 +708,7 @@ fn mouse_scroll_down_updates_editor_scroll_top() {
     for _ in 0..5 {
         app.handle_event(mouse_event(MouseEventKind::ScrollDown, 40, 10));
     }
-    assert_eq!(app.editor_scroll_top, 5);
+    assert_eq!(app.buf.editor_scroll_top, 5);
 }
 
 #[test]
@@ -540,16 +723,16 @@ fn mouse_scroll_up_updates_editor_scroll_top() {
     for _ in 0..3 {
         app.handle_event(mouse_event(MouseEventKind::ScrollUp, 40, 10));
     }
-    assert_eq!(app.editor_scroll_top, 7);
+    assert_eq!(app.buf.editor_scroll_top, 7);
 }
 
 #[test]
 fn mouse_scroll_up_clamps_at_zero() {
     let (mut app, _tmp) = app_with_content("hello");
     setup_viewport(&mut app, 80, 20);
-    assert_eq!(app.editor_scroll_top, 0);
+    assert_eq!(app.buf.editor_scroll_top, 0);
     app.handle_event(mouse_event(MouseEventKind::ScrollUp, 40, 10));
-    assert_eq!(app.editor_scroll_top, 0);
+    assert_eq!(app.buf.editor_scroll_top, 0);
 }
 
 #[test]
@@ -561,7 +744,7 @@ fn mouse_scroll_down_clamps_at_max() {
     for _ in 0..20 {
         app.handle_event(mouse_event(MouseEventKind::ScrollDown, 40, 10));
     }
-    assert!(app.editor_scroll_top <= 4, "scroll should clamp at max (got {})", app.editor_scroll_top);
+    assert!(app.buf.editor_scroll_top <= 4, "scroll should clamp at max (got {})", app.buf.editor_scroll_top);
 }
 
 #[test]
@@ -573,9 +756,2337 @@ fn click_after_scroll_maps_to_correct_buffer_row() {
     for _ in 0..10 {
         app.handle_event(mouse_event(MouseEventKind::ScrollDown, 40, 10));
     }
-    assert_eq!(app.editor_scroll_top, 10);
+    assert_eq!(app.buf.editor_scroll_top, 10);
     // Click on row 1 of the content area (content_area.y = 1, so click row = 2)
     let (buffer_row, _) = app.mouse_to_buffer_pos(10, 2);
     // row 2 - content_area.y(1) = relative_row 1, + scroll 10 = buffer_row 11
     assert_eq!(buffer_row, 11);
 }
+
+// ─── Drag Auto-Scroll Tests ───────────────────────────────────────
+
+#[test]
+fn drag_below_viewport_sets_down_auto_scroll() {
+    let content = (0..50).map(|i| format!("line {}", i)).collect::<Vec<_>>().join("\n");
+    let (mut app, _tmp) = app_with_content(&content);
+    setup_viewport(&mut app, 80, 20);
+    app.handle_event(mouse_event(MouseEventKind::Down(MouseButton::Left), 5, 5));
+    // content_area spans rows 1..21; row 21 is just below it
+    app.handle_event(mouse_event(MouseEventKind::Drag(MouseButton::Left), 5, 21));
+    assert_eq!(app.drag_auto_scroll, Some(AutoScroll::Down));
+
+    let row_before = app.buf.textarea.cursor().0;
+    app.tick();
+    assert_eq!(app.buf.textarea.cursor().0, row_before + 1, "tick should extend the drag downward");
+    assert!(app.buf.textarea.selection_range().is_some());
+}
+
+#[test]
+fn drag_back_inside_or_release_clears_auto_scroll() {
+    let content = (0..50).map(|i| format!("line {}", i)).collect::<Vec<_>>().join("\n");
+    let (mut app, _tmp) = app_with_content(&content);
+    setup_viewport(&mut app, 80, 20);
+    app.handle_event(mouse_event(MouseEventKind::Down(MouseButton::Left), 5, 5));
+    app.handle_event(mouse_event(MouseEventKind::Drag(MouseButton::Left), 5, 0));
+    assert_eq!(app.drag_auto_scroll, Some(AutoScroll::Up));
+    app.handle_event(mouse_event(MouseEventKind::Drag(MouseButton::Left), 5, 10));
+    assert_eq!(app.drag_auto_scroll, None);
+    app.handle_event(mouse_event(MouseEventKind::Drag(MouseButton::Left), 5, 21));
+    app.handle_event(mouse_event(MouseEventKind::Up(MouseButton::Left), 5, 21));
+    assert_eq!(app.drag_auto_scroll, None);
+}
+
+// ─── Shift+Click Tests ────────────────────────────────────────────
+
+fn shift_mouse_event(kind: MouseEventKind, col: u16, row: u16) -> Event {
+    Event::Mouse(MouseEvent {
+        kind,
+        column: col,
+        row,
+        modifiers: KeyModifiers::SHIFT,
+    })
+}
+
+#[test]
+fn shift_click_extends_selection_from_cursor() {
+    let (mut app, _tmp) = app_with_content("hello world\nsecond line");
+    setup_viewport(&mut app, 80, 20);
+    app.buf.textarea.move_cursor(CursorMove::Jump(0, 2));
+    let gutter = 3; // " 2 " for a two-line buffer
+    app.handle_event(shift_mouse_event(
+        MouseEventKind::Down(MouseButton::Left),
+        gutter + 4, 2,
+    ));
+    assert_eq!(app.buf.textarea.selection_range(), Some(((0, 2), (1, 4))));
+}
+
+#[test]
+fn shift_click_keeps_existing_anchor() {
+    let (mut app, _tmp) = app_with_content("hello world\nsecond line");
+    setup_viewport(&mut app, 80, 20);
+    app.buf.textarea.move_cursor(CursorMove::Jump(0, 1));
+    app.buf.textarea.start_selection();
+    app.buf.textarea.move_cursor(CursorMove::Jump(0, 5));
+    let gutter = 3;
+    app.handle_event(shift_mouse_event(
+        MouseEventKind::Down(MouseButton::Left),
+        gutter + 8, 1,
+    ));
+    // Anchor stays at (0, 1) rather than resetting to the click position
+    assert_eq!(app.buf.textarea.selection_range(), Some(((0, 1), (0, 8))));
+}
+
+// ─── Granular Drag Tests ──────────────────────────────────────────
+
+fn double_click(app: &mut App, col: u16, row: u16) {
+    app.handle_event(mouse_event(MouseEventKind::Down(MouseButton::Left), col, row));
+    app.handle_event(mouse_event(MouseEventKind::Up(MouseButton::Left), col, row));
+    app.handle_event(mouse_event(MouseEventKind::Down(MouseButton::Left), col, row));
+}
+
+#[test]
+fn double_click_drag_forward_extends_by_words() {
+    let (mut app, _tmp) = app_with_content("alpha beta gamma delta");
+    setup_viewport(&mut app, 80, 20);
+    let gutter = 3; // " 1 " for a one-line buffer
+    double_click(&mut app, gutter + 7, 1); // inside "beta"
+    assert_eq!(app.buf.textarea.selection_range(), Some(((0, 6), (0, 10))));
+    // Drag into the middle of "gamma": the whole word joins the selection
+    app.handle_event(mouse_event(MouseEventKind::Drag(MouseButton::Left), gutter + 12, 1));
+    assert_eq!(app.buf.textarea.selection_range(), Some(((0, 6), (0, 16))));
+}
+
+#[test]
+fn double_click_drag_backward_keeps_origin_word() {
+    let (mut app, _tmp) = app_with_content("alpha beta gamma delta");
+    setup_viewport(&mut app, 80, 20);
+    let gutter = 3;
+    double_click(&mut app, gutter + 12, 1); // inside "gamma"
+    app.handle_event(mouse_event(MouseEventKind::Drag(MouseButton::Left), gutter + 2, 1));
+    // From the start of "alpha" through the end of the originally clicked "gamma"
+    assert_eq!(app.buf.textarea.selection_range(), Some(((0, 0), (0, 16))));
+}
+
+#[test]
+fn double_click_drag_past_the_edge_keeps_extending_by_words() {
+    let content = vec!["alpha beta gamma delta"; 50].join("\n");
+    let (mut app, _tmp) = app_with_content(&content);
+    setup_viewport(&mut app, 80, 20);
+    let gutter = 4; // " 50 "
+    double_click(&mut app, gutter + 7, 5); // inside "beta" on line 5
+    // Below the content area, level with the middle of "gamma"
+    app.handle_event(mouse_event(MouseEventKind::Drag(MouseButton::Left), gutter + 13, 21));
+    app.tick();
+
+    let (start, end) = app.buf.textarea.selection_range().unwrap();
+    assert_eq!(start, (4, 6), "still anchored at the start of \"beta\"");
+    assert_eq!(end.0, 20, "auto-scroll moved one line past the edge row");
+    assert!([0, 5, 6, 10, 11, 16, 17, 22].contains(&end.1), "ends on a word boundary: {:?}", end);
+}
+
+#[test]
+fn triple_click_drag_extends_by_paragraphs() {
+    let (mut app, _tmp) = app_with_content("one\ntwo\n\nthree\nfour");
+    setup_viewport(&mut app, 80, 20);
+    let gutter = 3;
+    double_click(&mut app, gutter + 1, 1);
+    app.handle_event(mouse_event(MouseEventKind::Up(MouseButton::Left), gutter + 1, 1));
+    app.handle_event(mouse_event(MouseEventKind::Down(MouseButton::Left), gutter + 1, 1));
+    assert_eq!(app.buf.textarea.selection_range(), Some(((0, 0), (1, 3))));
+    app.handle_event(mouse_event(MouseEventKind::Drag(MouseButton::Left), gutter + 1, 4));
+    assert_eq!(app.buf.textarea.selection_range(), Some(((0, 0), (4, 4))));
+}
+
+// ─── Multi-Click Tolerance Tests ──────────────────────────────────
+
+#[test]
+fn near_position_second_click_counts_as_double_click() {
+    let (mut app, _tmp) = app_with_content("alpha beta gamma delta");
+    setup_viewport(&mut app, 80, 20);
+    let gutter = 3;
+    app.handle_event(mouse_event(MouseEventKind::Down(MouseButton::Left), gutter + 7, 1));
+    app.handle_event(mouse_event(MouseEventKind::Up(MouseButton::Left), gutter + 7, 1));
+    // One cell to the right, still inside "beta"
+    app.handle_event(mouse_event(MouseEventKind::Down(MouseButton::Left), gutter + 8, 1));
+    assert_eq!(app.click_count, 2);
+    assert_eq!(app.buf.textarea.selection_range(), Some(((0, 6), (0, 10))));
+}
+
+#[test]
+fn far_second_click_resets_click_count() {
+    let (mut app, _tmp) = app_with_content("alpha beta gamma delta");
+    setup_viewport(&mut app, 80, 20);
+    let gutter = 3;
+    app.handle_event(mouse_event(MouseEventKind::Down(MouseButton::Left), gutter + 7, 1));
+    app.handle_event(mouse_event(MouseEventKind::Up(MouseButton::Left), gutter + 7, 1));
+    app.handle_event(mouse_event(MouseEventKind::Down(MouseButton::Left), gutter + 12, 1));
+    assert_eq!(app.click_count, 1);
+}
+
+#[test]
+fn zero_multi_click_window_disables_double_click() {
+    let (mut app, _tmp) = app_with_content("alpha beta gamma delta");
+    setup_viewport(&mut app, 80, 20);
+    app.buf.config.multi_click_ms = 0;
+    double_click(&mut app, 10, 1);
+    assert_eq!(app.click_count, 1);
+}
+
+#[test]
+fn quadruple_click_selects_whole_document() {
+    let (mut app, _tmp) = app_with_content("one\ntwo\n\nthree\nfour");
+    setup_viewport(&mut app, 80, 20);
+    let gutter = 3;
+    for _ in 0..4 {
+        app.handle_event(mouse_event(MouseEventKind::Down(MouseButton::Left), gutter + 1, 1));
+        app.handle_event(mouse_event(MouseEventKind::Up(MouseButton::Left), gutter + 1, 1));
+    }
+    assert_eq!(app.click_count, 4);
+    assert_eq!(app.buf.textarea.selection_range(), Some(((0, 0), (4, 4))));
+}
+
+// ─── Middle-Click Paste Tests ─────────────────────────────────────
+
+#[cfg(target_os = "linux")]
+#[test]
+fn middle_click_pastes_at_mapped_position() {
+    let (mut app, _tmp) = app_with_content("hello world\nsecond line");
+    setup_viewport(&mut app, 80, 20);
+    let gutter = 3;
+    app.paste_at_mouse(gutter + 7, 2, "new ");
+    assert_eq!(app.buf.textarea.lines()[1], "second new line");
+    assert_eq!(app.buf.textarea.cursor(), (1, 11));
+    assert!(app.buf.modified);
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn middle_click_outside_content_area_does_not_paste() {
+    let (mut app, _tmp) = app_with_content("hello world");
+    setup_viewport(&mut app, 80, 20);
+    app.paste_at_mouse(10, 0, "nope");
+    assert_eq!(app.buf.textarea.lines()[0], "hello world");
+}
+
+// ─── File Switching Tests ─────────────────────────────────────────
+
+#[test]
+fn ctrl_page_down_cycles_through_file_list() {
+    let dir = tempfile::TempDir::new().unwrap();
+    let a = dir.path().join("a.md");
+    let b = dir.path().join("docs/b.md");
+    std::fs::create_dir(dir.path().join("docs")).unwrap();
+    std::fs::write(dir.path().join("docs/.marko.toml"), "format_on_save = false").unwrap();
+    std::fs::write(&a, "alpha").unwrap();
+    std::fs::write(&b, "beta").unwrap();
+    let mut app = App::new(a.clone());
+    app.file_list.push(b.clone());
+    app.buf.config.scrolloff = 2;
+
+    app.handle_event(Event::Key(KeyEvent::new(KeyCode::PageDown, KeyModifiers::CONTROL)));
+    assert_eq!(app.buf.file_path, b);
+    assert_eq!(app.buf.textarea.lines()[0], "beta");
+    assert_eq!(app.file_list, vec![a.clone(), b.clone()]);
+    assert!(!app.buf.config.format_on_save, "b.md gets its project's config");
+
+    app.handle_event(Event::Key(KeyEvent::new(KeyCode::PageDown, KeyModifiers::CONTROL)));
+    assert_eq!(app.buf.file_path, a, "wraps back to the first file");
+    assert_eq!(app.buf.config.scrolloff, 2, "a.md keeps its own config");
+    assert!(app.buf.config.format_on_save);
+}
+
+#[test]
+fn switching_files_keeps_unsaved_edits_without_saving() {
+    let dir = tempfile::TempDir::new().unwrap();
+    let a = dir.path().join("a.md");
+    let b = dir.path().join("b.md");
+    std::fs::write(&a, "alpha").unwrap();
+    std::fs::write(&b, "beta").unwrap();
+    let mut app = App::new(a.clone());
+    app.file_list.push(b.clone());
+    app.handle_event(key_event(KeyCode::Char('x')));
+    assert!(app.buf.modified);
+
+    app.handle_event(Event::Key(KeyEvent::new(KeyCode::PageUp, KeyModifiers::CONTROL)));
+    assert_eq!(app.buf.file_path, b);
+    assert!(!app.buf.modified);
+    assert_eq!(std::fs::read_to_string(&a).unwrap(), "alpha", "switching doesn't save");
+
+    app.handle_event(Event::Key(KeyEvent::new(KeyCode::PageUp, KeyModifiers::CONTROL)));
+    assert_eq!(app.buf.file_path, a);
+    assert!(app.buf.modified);
+    assert_eq!(app.buf.textarea.lines()[0], "xalpha");
+    // Undo history comes back with the buffer
+    app.handle_event(ctrl_key('z'));
+    assert_eq!(app.buf.textarea.lines()[0], "alpha");
+}
+
+#[test]
+fn save_writes_only_the_shown_file() {
+    let dir = tempfile::TempDir::new().unwrap();
+    let a = dir.path().join("a.md");
+    let b = dir.path().join("b.md");
+    std::fs::write(&a, "alpha").unwrap();
+    std::fs::write(&b, "beta").unwrap();
+    let mut app = App::new(a.clone());
+    app.file_list.push(b.clone());
+    app.handle_event(key_event(KeyCode::Char('x')));
+    app.handle_event(Event::Key(KeyEvent::new(KeyCode::PageDown, KeyModifiers::CONTROL)));
+    app.handle_event(key_event(KeyCode::Char('y')));
+
+    app.handle_event(ctrl_key('s'));
+    assert_eq!(std::fs::read_to_string(&b).unwrap(), "ybeta");
+    assert_eq!(std::fs::read_to_string(&a).unwrap(), "alpha");
+}
+
+#[test]
+fn ctrl_q_asks_before_quitting_with_other_unsaved_files() {
+    let dir = tempfile::TempDir::new().unwrap();
+    let a = dir.path().join("a.md");
+    let b = dir.path().join("b.md");
+    std::fs::write(&a, "alpha").unwrap();
+    std::fs::write(&b, "beta").unwrap();
+    let mut app = App::new(a.clone());
+    app.file_list.push(b.clone());
+    app.handle_event(key_event(KeyCode::Char('x')));
+    app.handle_event(Event::Key(KeyEvent::new(KeyCode::PageDown, KeyModifiers::CONTROL)));
+
+    app.handle_event(ctrl_key('q'));
+    assert_eq!(app.prompt, Some(Prompt::QuitUnsaved));
+    assert!(!app.should_quit);
+
+    app.handle_event(char_event('y'));
+    app.handle_event(key_event(KeyCode::Enter));
+    assert!(app.should_quit);
+    assert_eq!(std::fs::read_to_string(&a).unwrap(), "xalpha");
+}
+
+#[test]
+fn declining_quit_prompt_leaves_other_files_unsaved() {
+    let dir = tempfile::TempDir::new().unwrap();
+    let a = dir.path().join("a.md");
+    let b = dir.path().join("b.md");
+    std::fs::write(&a, "alpha").unwrap();
+    std::fs::write(&b, "beta").unwrap();
+    let mut app = App::new(a.clone());
+    app.file_list.push(b.clone());
+    app.handle_event(key_event(KeyCode::Char('x')));
+    app.handle_event(Event::Key(KeyEvent::new(KeyCode::PageDown, KeyModifiers::CONTROL)));
+    app.handle_event(key_event(KeyCode::Char('y')));
+
+    app.handle_event(ctrl_key('q'));
+    app.handle_event(char_event('n'));
+    app.handle_event(key_event(KeyCode::Enter));
+    assert!(app.should_quit);
+    assert_eq!(std::fs::read_to_string(&a).unwrap(), "alpha");
+    assert_eq!(std::fs::read_to_string(&b).unwrap(), "ybeta", "the shown file still saves");
+}
+
+#[test]
+fn alt_p_toggles_relative_path_in_header() {
+    let dir = tempfile::TempDir::new().unwrap();
+    std::fs::create_dir_all(dir.path().join("a")).unwrap();
+    std::fs::create_dir_all(dir.path().join("b")).unwrap();
+    let a = dir.path().join("a/index.md");
+    let b = dir.path().join("b/index.md");
+    std::fs::write(&a, "alpha").unwrap();
+    std::fs::write(&b, "beta").unwrap();
+    git2::Repository::init(dir.path()).unwrap();
+    let mut app = App::new(a.clone());
+    app.file_list.push(b.clone());
+
+    app.handle_event(alt_key('p'));
+    assert_eq!(app.header_path.as_deref(), Some("a/index.md"));
+
+    // The setting follows the switch to a same-named file
+    app.handle_event(Event::Key(KeyEvent::new(KeyCode::PageDown, KeyModifiers::CONTROL)));
+    assert_eq!(app.header_path.as_deref(), Some("b/index.md"));
+
+    app.handle_event(alt_key('p'));
+    assert_eq!(app.header_path, None);
+}
+
+#[test]
+fn new_buffer_is_written_only_on_save() {
+    let dir = tempfile::TempDir::new().unwrap();
+    let file = dir.path().join("draft.md");
+    let mut app = App::new(file.clone());
+    type_str(&mut app, "hello");
+    assert!(!file.exists(), "editing alone doesn't create the file");
+
+    app.save();
+    assert_eq!(std::fs::read_to_string(&file).unwrap(), "hello");
+    assert!(!app.buf.modified);
+}
+
+#[test]
+fn copied_file_path_is_absolute_or_workspace_relative() {
+    let dir = tempfile::TempDir::new().unwrap();
+    std::fs::create_dir_all(dir.path().join("docs")).unwrap();
+    let file = dir.path().join("docs/notes.md");
+    std::fs::write(&file, "notes").unwrap();
+    git2::Repository::init(dir.path()).unwrap();
+    let mut app = App::new(file.clone());
+
+    let absolute = std::fs::canonicalize(&file).unwrap();
+    assert_eq!(app.file_path_text(false), absolute.display().to_string());
+    assert_eq!(app.file_path_text(true), std::path::Path::new("docs").join("notes.md").display().to_string());
+
+    app.handle_event(Event::Key(KeyEvent::new(
+        KeyCode::Char('P'),
+        KeyModifiers::ALT | KeyModifiers::SHIFT,
+    )));
+    assert!(app.status_message.ends_with("notes.md"), "{}", app.status_message);
+}
+
+// ─── Link Insertion Tests ─────────────────────────────────────────
+
+/// Ticks until the background title fetch has been applied (or times out).
+fn wait_for_link_title(app: &mut App) {
+    let start = Instant::now();
+    while app.status_message == "Fetching link title..." && start.elapsed() < Duration::from_secs(2) {
+        std::thread::sleep(Duration::from_millis(5));
+        app.tick();
+    }
+}
+
+#[test]
+fn inserted_url_becomes_titled_link() {
+    let (mut app, _tmp) = app_with_content("See  for details");
+    app.buf.textarea.move_cursor(CursorMove::Jump(0, 4));
+    app.insert_link_with_fetcher("https://example.com".to_string(), |_| {
+        Some("Example Domain".to_string())
+    });
+    assert_eq!(app.buf.textarea.lines()[0], "See https://example.com for details");
+    wait_for_link_title(&mut app);
+    assert_eq!(
+        app.buf.textarea.lines()[0],
+        "See [Example Domain](https://example.com) for details"
+    );
+    // Cursor stays right after the inserted link
+    assert_eq!(app.buf.textarea.cursor(), (0, 41));
+}
+
+#[test]
+fn failed_title_fetch_leaves_bare_url() {
+    let (mut app, _tmp) = app_with_content("");
+    app.insert_link_with_fetcher("https://example.com".to_string(), |_| None);
+    wait_for_link_title(&mut app);
+    assert_eq!(app.buf.textarea.lines()[0], "https://example.com");
+}
+
+#[test]
+fn title_fetched_after_switching_files_goes_to_the_original_file() {
+    let (mut app, _tmp) = app_with_content("See  for details");
+    let dir = tempfile::TempDir::new().unwrap();
+    let other = dir.path().join("other.md");
+    std::fs::write(&other, "See https://example.com for details").unwrap();
+
+    app.buf.textarea.move_cursor(CursorMove::Jump(0, 4));
+    app.insert_link_with_fetcher("https://example.com".to_string(), |_| {
+        std::thread::sleep(Duration::from_millis(50));
+        Some("Example Domain".to_string())
+    });
+    app.switch_to_file(other);
+    let start = Instant::now();
+    while app.status_message != "Inserted link" && start.elapsed() < Duration::from_secs(2) {
+        std::thread::sleep(Duration::from_millis(5));
+        app.tick();
+    }
+
+    assert_eq!(app.buf.textarea.lines()[0], "See https://example.com for details");
+    assert_eq!(
+        app.parked[0].textarea.lines()[0],
+        "See [Example Domain](https://example.com) for details"
+    );
+    assert!(app.parked[0].modified);
+}
+
+// ─── Code Toggle Tests ────────────────────────────────────────────
+
+fn alt_key(ch: char) -> Event {
+    Event::Key(KeyEvent::new(KeyCode::Char(ch), KeyModifiers::ALT))
+}
+
+#[test]
+fn alt_c_wraps_single_line_selection_in_code_span() {
+    let (mut app, _tmp) = app_with_content("call foo() now");
+    app.set_selection((0, 5), (0, 10));
+    app.handle_event(alt_key('c'));
+    assert_eq!(app.buf.textarea.lines()[0], "call `foo()` now");
+
+    // Selecting the span and toggling again unwraps it
+    app.set_selection((0, 5), (0, 12));
+    app.handle_event(alt_key('c'));
+    assert_eq!(app.buf.textarea.lines()[0], "call foo() now");
+}
+
+#[test]
+fn alt_c_uses_longer_fence_for_nested_backticks() {
+    let (mut app, _tmp) = app_with_content("run `ls` here");
+    app.set_selection((0, 0), (0, 13));
+    app.handle_event(alt_key('c'));
+    assert_eq!(app.buf.textarea.lines()[0], "``run `ls` here``");
+}
+
+#[test]
+fn alt_c_prompts_for_language_and_wraps_lines_in_fence() {
+    let (mut app, _tmp) = app_with_content("intro\nlet x = 1;\nlet y = 2;\noutro");
+    app.set_selection((1, 3), (2, 4));
+    app.handle_event(alt_key('c'));
+    assert!(matches!(app.prompt, Some(Prompt::CodeFenceLanguage { start_row: 1, end_row: 2 })));
+    for ch in "rust".chars() {
+        app.handle_event(key_event(KeyCode::Char(ch)));
+    }
+    app.handle_event(key_event(KeyCode::Enter));
+    assert!(app.prompt.is_none());
+    assert_eq!(
+        app.buf.textarea.lines(),
+        ["intro", "```rust", "let x = 1;", "let y = 2;", "```", "outro"]
+    );
+}
+
+#[test]
+fn alt_c_unwraps_selected_code_block() {
+    let (mut app, _tmp) = app_with_content("```sh\nls\n```\nafter");
+    app.set_selection((0, 0), (3, 0));
+    app.handle_event(alt_key('c'));
+    assert!(app.prompt.is_none());
+    assert_eq!(app.buf.textarea.lines(), ["ls", "after"]);
+}
+
+#[test]
+fn esc_cancels_code_block_prompt() {
+    let (mut app, _tmp) = app_with_content("a\nb");
+    app.set_selection((0, 0), (1, 1));
+    app.handle_event(alt_key('c'));
+    app.handle_event(key_event(KeyCode::Esc));
+    assert!(app.prompt.is_none());
+    assert_eq!(app.buf.textarea.lines(), ["a", "b"]);
+    assert_eq!(app.mode, Mode::Editor, "Esc closes the prompt, nothing else");
+}
+
+// ─── Blockquote Toggle Tests ──────────────────────────────────────
+
+#[test]
+fn alt_q_quotes_selected_lines_and_nests() {
+    let (mut app, _tmp) = app_with_content("one\ntwo\nthree");
+    app.set_selection((0, 1), (1, 2));
+    app.handle_event(alt_key('q'));
+    assert_eq!(app.buf.textarea.lines(), ["> one", "> two", "three"]);
+    // The quoted lines stay selected, so a second press nests
+    app.handle_event(alt_key('q'));
+    assert_eq!(app.buf.textarea.lines(), ["> > one", "> > two", "three"]);
+}
+
+#[test]
+fn alt_shift_q_removes_one_quote_level() {
+    let (mut app, _tmp) = app_with_content("> > deep\n> shallow\nplain");
+    app.set_selection((0, 0), (2, 5));
+    app.handle_event(Event::Key(KeyEvent::new(
+        KeyCode::Char('Q'),
+        KeyModifiers::ALT | KeyModifiers::SHIFT,
+    )));
+    assert_eq!(app.buf.textarea.lines(), ["> deep", "shallow", "plain"]);
+}
+
+#[test]
+fn alt_q_without_selection_quotes_cursor_line() {
+    let (mut app, _tmp) = app_with_content("one\ntwo");
+    app.buf.textarea.move_cursor(CursorMove::Jump(1, 1));
+    app.handle_event(alt_key('q'));
+    assert_eq!(app.buf.textarea.lines(), ["one", "> two"]);
+}
+
+// ─── Literal Paste Tests ──────────────────────────────────────────
+
+#[test]
+fn literal_paste_escapes_markdown_syntax() {
+    let (mut app, _tmp) = app_with_content("");
+    app.insert_literal("# not a *heading*");
+    assert_eq!(app.buf.textarea.lines()[0], "\\# not a \\*heading\\*");
+    assert!(app.buf.modified);
+}
+
+#[test]
+fn reflowed_paste_joins_lines_and_wraps() {
+    let (mut app, _tmp) = app_with_content("");
+    setup_viewport(&mut app, 40, 20);
+    let pasted = "Text copied from a PDF\ncomes in with hard\nbreaks in every line.\n\n• a list\n• items";
+    app.insert_reflowed(pasted);
+    let width = app.available_text_width();
+    let lines = app.buf.textarea.lines();
+    assert!(lines.iter().all(|l| l.chars().count() <= width), "got {:?}", lines);
+    let blank = lines.iter().position(|l| l.is_empty()).expect("paragraph break kept");
+    assert_eq!(
+        lines[..blank].join(" "),
+        "Text copied from a PDF comes in with hard breaks in every line."
+    );
+    assert_eq!(&lines[blank + 1..], ["- a list", "- items"]);
+    assert!(app.buf.modified);
+}
+
+// ─── Clipboard History Tests ──────────────────────────────────────
+
+#[test]
+fn kill_ring_skips_repeats_and_caps_size() {
+    let mut ring = std::collections::VecDeque::new();
+    kill_ring::push_kill(&mut ring, "a");
+    kill_ring::push_kill(&mut ring, "a");
+    kill_ring::push_kill(&mut ring, "");
+    assert_eq!(ring, ["a"]);
+    for i in 0..kill_ring::KILL_RING_SIZE + 5 {
+        kill_ring::push_kill(&mut ring, &i.to_string());
+    }
+    assert_eq!(ring.len(), kill_ring::KILL_RING_SIZE);
+    assert_eq!(ring.front().map(String::as_str), Some("20"));
+}
+
+#[test]
+fn cuts_join_the_clipboard_history() {
+    let (mut app, _tmp) = app_with_content("first line\nsecond");
+    app.handle_event(ctrl_key('k'));
+    assert_eq!(app.kill_ring.front().map(String::as_str), Some("first line"));
+}
+
+#[test]
+fn alt_y_after_paste_cycles_through_history() {
+    let (mut app, _tmp) = app_with_content("");
+    for text in ["oldest", "older", "newest"] {
+        kill_ring::push_kill(&mut app.kill_ring, text);
+    }
+    type_str(&mut app, "<>");
+    app.handle_event(key_event(KeyCode::Left));
+    app.insert_paste("newest");
+    assert_eq!(app.buf.textarea.lines()[0], "<newest>");
+
+    app.handle_event(alt_key('y'));
+    assert_eq!(app.buf.textarea.lines()[0], "<older>");
+    app.handle_event(alt_key('y'));
+    assert_eq!(app.buf.textarea.lines()[0], "<oldest>");
+    app.handle_event(alt_key('y'));
+    assert_eq!(app.buf.textarea.lines()[0], "<newest>", "wraps around to the newest");
+}
+
+#[test]
+fn alt_y_does_nothing_once_another_key_follows_the_paste() {
+    let (mut app, _tmp) = app_with_content("");
+    kill_ring::push_kill(&mut app.kill_ring, "older");
+    app.insert_paste("pasted");
+    type_str(&mut app, "!");
+    app.handle_event(alt_key('y'));
+    assert_eq!(app.buf.textarea.lines()[0], "pasted!");
+}
+
+// ─── Find Bar Tests ───────────────────────────────────────────────
+
+fn type_str(app: &mut App, text: &str) {
+    for ch in text.chars() {
+        app.handle_event(key_event(KeyCode::Char(ch)));
+    }
+}
+
+#[test]
+fn find_matches_ignores_case_unless_asked() {
+    let lines = vec!["Foo foo".to_string(), "café FOO".to_string()];
+    assert_eq!(
+        search::find_matches(&lines, "foo", false),
+        vec![(0, 0), (0, 4), (1, 5)]
+    );
+    assert_eq!(search::find_matches(&lines, "foo", true), vec![(0, 4)]);
+    assert!(search::find_matches(&lines, "", false).is_empty());
+}
+
+#[test]
+fn find_matches_do_not_overlap() {
+    let lines = vec!["aaaa".to_string()];
+    assert_eq!(search::find_matches(&lines, "aa", false), vec![(0, 0), (0, 2)]);
+}
+
+#[test]
+fn ctrl_f_typing_selects_first_match() {
+    let (mut app, _tmp) = app_with_content("alpha\nbeta gamma\ngamma");
+    app.handle_event(ctrl_key('f'));
+    assert!(app.search.is_some());
+    type_str(&mut app, "gam");
+
+    let search = app.search.as_ref().unwrap();
+    assert_eq!(search.matches, vec![(1, 5), (2, 0)]);
+    assert_eq!(search.current, 0);
+    assert_eq!(app.buf.textarea.selection_range(), Some(((1, 5), (1, 8))));
+}
+
+#[test]
+fn find_enter_and_shift_enter_cycle_matches() {
+    let (mut app, _tmp) = app_with_content("x\nx\nx");
+    app.handle_event(ctrl_key('f'));
+    type_str(&mut app, "x");
+    assert_eq!(app.buf.textarea.cursor(), (0, 1));
+
+    app.handle_event(key_event(KeyCode::Enter));
+    assert_eq!(app.search.as_ref().unwrap().current, 1);
+    assert_eq!(app.buf.textarea.cursor(), (1, 1));
+
+    app.handle_event(Event::Key(KeyEvent::new(KeyCode::Enter, KeyModifiers::SHIFT)));
+    app.handle_event(Event::Key(KeyEvent::new(KeyCode::Enter, KeyModifiers::SHIFT)));
+    // Wraps from the first match back to the last
+    assert_eq!(app.search.as_ref().unwrap().current, 2);
+    assert_eq!(app.buf.textarea.cursor(), (2, 1));
+    // Searching never edits the buffer
+    assert_eq!(app.buf.textarea.lines(), ["x", "x", "x"]);
+}
+
+#[test]
+fn find_ctrl_i_toggles_case_sensitivity() {
+    let (mut app, _tmp) = app_with_content("Word word");
+    app.handle_event(ctrl_key('f'));
+    type_str(&mut app, "word");
+    assert_eq!(app.search.as_ref().unwrap().matches.len(), 2);
+
+    app.handle_event(ctrl_key('i'));
+    let search = app.search.as_ref().unwrap();
+    assert!(search.case_sensitive);
+    assert_eq!(search.matches, vec![(0, 5)]);
+    assert_eq!(search.label(), "Find [Aa] (1/1):");
+}
+
+#[test]
+fn find_esc_closes_and_cancels_selection() {
+    let (mut app, _tmp) = app_with_content("needle in haystack");
+    app.handle_event(ctrl_key('f'));
+    type_str(&mut app, "hay");
+    assert!(app.buf.textarea.selection_range().is_some());
+
+    app.handle_event(key_event(KeyCode::Esc));
+    assert!(app.search.is_none());
+    assert!(app.buf.textarea.selection_range().is_none());
+    assert!(app.buf.textarea.search_pattern().is_none());
+    assert_eq!(app.mode, Mode::Editor);
+}
+
+#[test]
+fn find_with_no_matches_keeps_cursor() {
+    let (mut app, _tmp) = app_with_content("abc");
+    app.buf.textarea.move_cursor(CursorMove::Jump(0, 1));
+    app.handle_event(ctrl_key('f'));
+    type_str(&mut app, "zz");
+    let search = app.search.as_ref().unwrap();
+    assert!(search.matches.is_empty());
+    assert_eq!(search.label(), "Find (no matches):");
+    assert_eq!(app.buf.textarea.cursor(), (0, 1));
+}
+
+// ─── Replace Tests ────────────────────────────────────────────────
+
+fn open_replace(app: &mut App, find: &str, replacement: &str) {
+    app.handle_event(ctrl_key('r'));
+    type_str(app, find);
+    app.handle_event(key_event(KeyCode::Tab));
+    type_str(app, replacement);
+}
+
+#[test]
+fn replace_matches_rewrites_each_occurrence() {
+    let lines = vec!["a cat, a cat".to_string(), "no match".to_string()];
+    assert_eq!(
+        search::replace_matches(&lines, &[(0, 2), (0, 9)], 3, "dog"),
+        vec!["a dog, a dog".to_string(), "no match".to_string()]
+    );
+}
+
+#[test]
+fn ctrl_r_replaces_next_match_and_steps_on() {
+    let (mut app, _tmp) = app_with_content("cat\ncat\ncat");
+    setup_viewport(&mut app, 80, 20);
+    open_replace(&mut app, "cat", "dog");
+
+    app.handle_event(key_event(KeyCode::Enter));
+    assert_eq!(app.buf.textarea.lines(), ["dog", "cat", "cat"]);
+    assert_eq!(app.status_message, "Replaced 1 occurrence");
+    assert!(app.replace.is_some(), "prompt stays open to replace the next one");
+
+    app.handle_event(key_event(KeyCode::Enter));
+    assert_eq!(app.buf.textarea.lines(), ["dog", "dog", "cat"]);
+    assert!(app.buf.modified);
+}
+
+#[test]
+fn replace_all_reports_count_and_closes() {
+    let (mut app, _tmp) = app_with_content("one fish two fish\nred fish");
+    setup_viewport(&mut app, 80, 20);
+    open_replace(&mut app, "fish", "cod");
+    app.handle_event(alt_key('a'));
+    assert!(app.replace.as_ref().unwrap().replace_all);
+
+    app.handle_event(key_event(KeyCode::Enter));
+    assert_eq!(app.buf.textarea.lines(), ["one cod two cod", "red cod"]);
+    assert_eq!(app.status_message, "Replaced 3 occurrences");
+    assert!(app.replace.is_none());
+}
+
+#[test]
+fn replace_all_undoes_and_redoes_in_one_step() {
+    let (mut app, _tmp) = app_with_content("intro\none fish\nmiddle\nred fish\noutro");
+    setup_viewport(&mut app, 80, 20);
+    type_str(&mut app, "x");
+    open_replace(&mut app, "fish", "cod");
+    app.handle_event(alt_key('a'));
+    app.handle_event(key_event(KeyCode::Enter));
+    assert_eq!(app.buf.textarea.lines(), ["xintro", "one cod", "middle", "red cod", "outro"]);
+
+    app.handle_event(ctrl_key('z'));
+    assert_eq!(app.buf.textarea.lines(), ["xintro", "one fish", "middle", "red fish", "outro"]);
+    app.handle_event(ctrl_key('y'));
+    assert_eq!(app.buf.textarea.lines(), ["xintro", "one cod", "middle", "red cod", "outro"]);
+    app.handle_event(ctrl_key('z'));
+    app.handle_event(ctrl_key('z'));
+    assert_eq!(app.buf.textarea.lines()[0], "intro", "the typing before it is a step of its own");
+    assert!(!app.buf.modified);
+}
+
+#[test]
+fn undo_after_editing_by_hand_to_a_replace_midway_state_is_one_step() {
+    let (mut app, _tmp) = app_with_content("one fish");
+    setup_viewport(&mut app, 80, 20);
+    open_replace(&mut app, "fish", "cod");
+    app.handle_event(alt_key('a'));
+    app.handle_event(key_event(KeyCode::Enter));
+    assert_eq!(app.buf.textarea.lines(), ["one cod"]);
+
+    // Empty the line by hand: the state between the replace's two edits
+    app.handle_event(key_event(KeyCode::End));
+    for _ in 0.."one cod".len() {
+        app.handle_event(key_event(KeyCode::Backspace));
+    }
+    type_str(&mut app, "x");
+    app.handle_event(ctrl_key('z'));
+    assert_eq!(app.buf.textarea.lines(), [""], "only the typing is undone");
+    app.handle_event(ctrl_key('z'));
+    assert_eq!(app.buf.textarea.lines(), ["o"], "then only the last backspace");
+    for _ in 1.."one cod".len() {
+        app.handle_event(ctrl_key('z'));
+    }
+    assert_eq!(app.buf.textarea.lines(), ["one cod"]);
+    app.handle_event(ctrl_key('z'));
+    assert_eq!(app.buf.textarea.lines(), ["one fish"], "then the replace, in one step");
+}
+
+#[test]
+fn replace_is_case_sensitive_and_reports_no_match() {
+    let (mut app, _tmp) = app_with_content("Cat");
+    setup_viewport(&mut app, 80, 20);
+    open_replace(&mut app, "cat", "dog");
+    app.handle_event(key_event(KeyCode::Enter));
+    assert_eq!(app.buf.textarea.lines(), ["Cat"]);
+    assert_eq!(app.status_message, "No matches for 'cat'");
+}
+
+#[test]
+fn replace_wraps_long_replacement_text() {
+    let (mut app, _tmp) = app_with_content("see X here\nnext");
+    setup_viewport(&mut app, 23, 20);
+    let width = app.available_text_width();
+    open_replace(&mut app, "X", "a much longer replacement phrase");
+    app.handle_event(alt_key('a'));
+    app.handle_event(key_event(KeyCode::Enter));
+
+    let lines = app.buf.textarea.lines();
+    assert!(lines.len() > 2, "long line should be wrapped, got {:?}", lines);
+    assert!(lines.iter().all(|l| l.chars().count() <= width), "got {:?}", lines);
+    assert_eq!(lines.join(" "), "see a much longer replacement phrase here next");
+}
+
+#[test]
+fn ctrl_r_prefills_find_from_selection() {
+    let (mut app, _tmp) = app_with_content("hello world");
+    app.set_selection((0, 6), (0, 11));
+    app.handle_event(ctrl_key('r'));
+    let state = app.replace.as_ref().unwrap();
+    assert_eq!(state.find, "world");
+    assert_eq!(state.cursor, 5);
+    assert!(!state.editing_replace);
+}
+
+// ─── Go To Line Tests ─────────────────────────────────────────────
+
+#[test]
+fn ctrl_g_jumps_to_line() {
+    let (mut app, _tmp) = app_with_content("one\ntwo\nthree\nfour");
+    app.handle_event(ctrl_key('g'));
+    assert_eq!(app.prompt, Some(Prompt::GoToLine));
+    type_str(&mut app, "3");
+    app.handle_event(key_event(KeyCode::Enter));
+    assert_eq!(app.prompt, None);
+    assert_eq!(app.buf.textarea.cursor(), (2, 0));
+}
+
+#[test]
+fn go_to_line_ignores_non_digits() {
+    let (mut app, _tmp) = app_with_content("one\ntwo");
+    app.handle_event(ctrl_key('g'));
+    type_str(&mut app, "a2b");
+    assert_eq!(app.prompt_buf, "2");
+}
+
+#[test]
+fn go_to_line_out_of_range_shows_error() {
+    let (mut app, _tmp) = app_with_content("one\ntwo");
+    app.buf.textarea.move_cursor(CursorMove::Jump(1, 2));
+    app.handle_event(ctrl_key('g'));
+    type_str(&mut app, "9");
+    app.handle_event(key_event(KeyCode::Enter));
+    assert_eq!(app.status_message, "No line 9 (1-2)");
+    assert_eq!(app.buf.textarea.cursor(), (1, 2), "cursor stays put");
+}
+
+#[test]
+fn go_to_line_maps_source_line_past_wrapped_rows() {
+    let long = "word ".repeat(12);
+    let content = format!("{}\nafter the long line\nlast", long.trim_end());
+    let (mut app, _tmp) = app_with_content(&content);
+    setup_viewport(&mut app, 30, 20);
+    let width = app.available_text_width();
+    app.reflow_content(width);
+    assert!(app.buf.textarea.lines().len() > 3, "first line should wrap");
+
+    app.handle_event(ctrl_key('g'));
+    type_str(&mut app, "2");
+    app.handle_event(key_event(KeyCode::Enter));
+    let (row, col) = app.buf.textarea.cursor();
+    assert_eq!(app.buf.textarea.lines()[row], "after the long line");
+    assert_eq!(col, 0);
+}
+
+// ─── Wrap Width Tests ─────────────────────────────────────────────
+
+#[test]
+fn configured_wrap_width_caps_reflow() {
+    let long = "lorem ipsum ".repeat(17);
+    let long = long.trim_end();
+    assert!(long.chars().count() >= 200);
+    let (mut app, _tmp) = app_with_content(long);
+    setup_viewport(&mut app, 200, 20);
+    app.buf.config.wrap_width = Some(80);
+    assert_eq!(app.available_text_width(), 80);
+
+    app.reflow_content(app.available_text_width());
+    let lines = app.buf.textarea.lines();
+    assert!(lines.len() > 2, "got {:?}", lines);
+    assert!(lines.iter().all(|l| l.chars().count() <= 80), "got {:?}", lines);
+}
+
+#[test]
+fn configured_wrap_width_applies_while_typing() {
+    let (mut app, _tmp) = app_with_content("");
+    setup_viewport(&mut app, 200, 20);
+    app.buf.config.wrap_width = Some(80);
+    type_str(&mut app, &"typing away ".repeat(17));
+    let lines = app.buf.textarea.lines();
+    assert!(lines.len() > 1, "got {:?}", lines);
+    assert!(lines.iter().all(|l| l.chars().count() <= 80), "got {:?}", lines);
+}
+
+#[test]
+fn narrow_terminal_wins_over_wrap_width() {
+    let (mut app, _tmp) = app_with_content("text");
+    setup_viewport(&mut app, 40, 20);
+    app.buf.config.wrap_width = Some(80);
+    assert!(app.available_text_width() < 40);
+}
+
+#[test]
+fn reference_definitions_and_urls_survive_wrapping() {
+    let reference = format!("[docs]: https://example.com/{}", "path/".repeat(12));
+    let url = format!("https://example.com/{}", "x".repeat(70));
+    let (mut app, tmp) = app_with_content("[docs]: https://example.com/\nhttps://example.com/");
+    setup_viewport(&mut app, 40, 20);
+    // Type the rest of each line past the wrap width
+    app.handle_event(key_event(KeyCode::End));
+    type_str(&mut app, &reference["[docs]: https://example.com/".len()..]);
+    app.handle_event(key_event(KeyCode::Down));
+    app.handle_event(key_event(KeyCode::End));
+    type_str(&mut app, &"x".repeat(70));
+    let expected = vec![reference, url];
+    assert_eq!(app.buf.textarea.lines(), expected.as_slice(), "typing leaves them whole");
+
+    app.save();
+    let saved = std::fs::read_to_string(tmp.path()).unwrap();
+    assert_eq!(saved.lines().collect::<Vec<_>>(), expected, "saving leaves them whole");
+}
+
+// ─── Auto-Wrap Toggle Tests ───────────────────────────────────────
+
+fn ctrl_shift_w() -> Event {
+    Event::Key(KeyEvent::new(
+        KeyCode::Char('W'),
+        KeyModifiers::CONTROL | KeyModifiers::SHIFT,
+    ))
+}
+
+#[test]
+fn typing_does_not_wrap_when_auto_wrap_is_off() {
+    let (mut app, _tmp) = app_with_content("");
+    setup_viewport(&mut app, 40, 20);
+    app.buf.auto_wrap = false;
+    type_str(&mut app, &"no wrapping here ".repeat(5));
+    assert_eq!(app.buf.textarea.lines().len(), 1);
+}
+
+#[test]
+fn save_formats_tables_but_skips_hard_wrap_when_auto_wrap_is_off() {
+    let long = "long prose line ".repeat(8);
+    let content = format!("{}\n\n|a|b|\n|-|-|\n|1|2|", long.trim_end());
+    let (mut app, tmp) = app_with_content(&content);
+    setup_viewport(&mut app, 40, 20);
+    app.buf.auto_wrap = false;
+    app.save();
+
+    let saved = std::fs::read_to_string(tmp.path()).unwrap();
+    assert_eq!(saved.lines().next(), Some(long.trim_end()), "prose left unwrapped");
+    assert!(saved.contains("| ---"), "table formatted: {:?}", saved);
+}
+
+#[test]
+fn ctrl_shift_w_toggles_auto_wrap_without_unwrapping() {
+    let (mut app, _tmp) = app_with_content("short\nlines");
+    setup_viewport(&mut app, 40, 20);
+    assert!(app.buf.auto_wrap);
+
+    app.handle_event(ctrl_shift_w());
+    assert!(!app.buf.auto_wrap);
+    assert_eq!(app.status_message, "Auto-wrap off");
+    assert_eq!(app.buf.textarea.lines(), ["short", "lines"]);
+
+    app.handle_event(ctrl_shift_w());
+    assert!(app.buf.auto_wrap);
+    assert_eq!(app.status_message, "Auto-wrap on");
+}
+
+// ─── Soft Wrap Tests ──────────────────────────────────────────────
+
+#[test]
+fn wrap_segments_break_after_spaces() {
+    assert_eq!(soft_wrap::wrap_segments("aaa bbb ccc", 8), vec![(0, 8), (8, 11)]);
+    assert_eq!(soft_wrap::wrap_segments("abcdefghij", 4), vec![(0, 4), (4, 8), (8, 10)]);
+    assert_eq!(soft_wrap::wrap_segments("short", 10), vec![(0, 5)]);
+    // A full last row leaves room for the end-of-line cursor
+    assert_eq!(soft_wrap::wrap_segments("abcd", 4), vec![(0, 4), (4, 4)]);
+}
+
+#[test]
+fn soft_wrap_never_changes_the_buffer() {
+    let long = "soft wrapped prose ".repeat(8);
+    let (mut app, tmp) = app_with_content(long.trim_end());
+    setup_viewport(&mut app, 40, 20);
+    app.buf.config.soft_wrap = true;
+    app.handle_event(key_event(KeyCode::End));
+    type_str(&mut app, " and more typing past the edge");
+    assert_eq!(app.buf.textarea.lines().len(), 1);
+
+    app.save();
+    let saved = std::fs::read_to_string(tmp.path()).unwrap();
+    assert_eq!(saved.lines().count(), 1, "saved: {:?}", saved);
+}
+
+#[test]
+fn soft_wrap_click_on_continuation_row_maps_into_line() {
+    let (mut app, _tmp) = app_with_content("abcdefghijklmnopqrstuvwxyz\nnext");
+    setup_viewport(&mut app, 13, 20);
+    app.buf.config.soft_wrap = true;
+    // Gutter is 3 columns, so rows hold 10 chars: "abcdefghij", "klmnopqrst", "uvwxyz"
+    assert_eq!(app.available_text_width(), 10);
+    assert_eq!(app.mouse_to_buffer_pos(3 + 2, 1 + 1), (0, 12));
+    assert_eq!(app.mouse_to_buffer_pos(3 + 9, 1 + 2), (0, 26), "past the end of the line");
+    assert_eq!(app.mouse_to_buffer_pos(3, 1 + 3), (1, 0));
+}
+
+#[test]
+fn soft_wrap_renders_only_the_columns_that_reach_the_screen() {
+    let long = "word ".repeat(20_000);
+    let (mut app, _tmp) = app_with_content(&format!("top\n{}", long.trim_end()));
+    app.buf.config.soft_wrap = true;
+    let mut terminal = ratatui::Terminal::new(ratatui::backend::TestBackend::new(40, 24)).unwrap();
+    terminal.draw(|f| app.render(f)).unwrap();
+
+    // 20 editor rows of under 40 columns each, not the line's 100k chars
+    assert!(app.laid_out_buffer.area.width < 20 * 40, "{:?}", app.laid_out_buffer.area);
+    let screen = terminal.backend().buffer();
+    let last_row: String = (0..40).map(|x| screen[(x, 21)].symbol()).collect();
+    assert!(last_row.trim_start().starts_with("word word"), "{:?}", last_row);
+}
+
+// ─── Live Gutter Tests ────────────────────────────────────────────
+
+/// Ticks until no gutter recompute is pending or running.
+fn settle_gutter(app: &mut App) {
+    for _ in 0..500 {
+        app.tick();
+        if app.buf.gutter_handle.is_none() && app.buf.last_edit.is_none() {
+            return;
+        }
+        std::thread::sleep(Duration::from_millis(10));
+    }
+    panic!("gutter recompute never finished");
+}
+
+#[test]
+fn unsaved_edits_update_gutter_marks_once_settled() {
+    let dir = tempfile::TempDir::new().unwrap();
+    let repo = git2::Repository::init(dir.path()).unwrap();
+    let file = dir.path().join("doc.md");
+    std::fs::write(&file, "one\ntwo\nthree").unwrap();
+    let mut index = repo.index().unwrap();
+    index.add_path(std::path::Path::new("doc.md")).unwrap();
+    let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+    let sig = git2::Signature::now("test", "test@example.com").unwrap();
+    repo.commit(Some("HEAD"), &sig, &sig, "init", &tree, &[]).unwrap();
+
+    let mut app = App::new(file.clone());
+    settle_gutter(&mut app);
+    assert!(app.buf.gutter_marks.is_empty());
+
+    // Insert a line after "one" without saving
+    app.handle_event(key_event(KeyCode::End));
+    app.handle_event(key_event(KeyCode::Enter));
+    type_str(&mut app, "new");
+    assert!(app.buf.last_edit.is_some(), "an edit schedules a recompute");
+    app.buf.last_edit = Some(Instant::now() - GUTTER_REFRESH_DELAY);
+    settle_gutter(&mut app);
+
+    assert_eq!(app.buf.gutter_marks.get(&1), Some(&GutterMark::Added));
+    assert_eq!(app.buf.gutter_marks.len(), 1);
+    assert_eq!(std::fs::read_to_string(&file).unwrap(), "one\ntwo\nthree");
+}
+
+// ─── Merge Conflict Tests ─────────────────────────────────────────
+
+const CONFLICTED: &str = "intro\n<<<<<<< HEAD\nours\n=======\ntheirs\n>>>>>>> branch\nmiddle\n<<<<<<< HEAD\na\n=======\nb\n>>>>>>> branch\noutro";
+
+#[test]
+fn alt_n_jumps_between_conflicts_and_wraps() {
+    let (mut app, _tmp) = app_with_content(CONFLICTED);
+    app.handle_event(alt_key('n'));
+    assert_eq!(app.buf.textarea.cursor(), (1, 0));
+    app.handle_event(alt_key('n'));
+    assert_eq!(app.buf.textarea.cursor(), (7, 0));
+    app.handle_event(alt_key('n'));
+    assert_eq!(app.buf.textarea.cursor(), (1, 0), "wraps to the first conflict");
+    app.handle_event(Event::Key(KeyEvent::new(
+        KeyCode::Char('N'),
+        KeyModifiers::ALT | KeyModifiers::SHIFT,
+    )));
+    assert_eq!(app.buf.textarea.cursor(), (7, 0), "previous wraps to the last");
+}
+
+#[test]
+fn alt_o_keeps_our_side() {
+    let (mut app, _tmp) = app_with_content(CONFLICTED);
+    app.buf.textarea.move_cursor(CursorMove::Jump(4, 0));
+    app.handle_event(alt_key('o'));
+    assert_eq!(
+        app.buf.textarea.lines().join("\n"),
+        "intro\nours\nmiddle\n<<<<<<< HEAD\na\n=======\nb\n>>>>>>> branch\noutro"
+    );
+    assert!(app.buf.modified);
+    assert!(app.status_message.contains("1 conflict left"));
+}
+
+#[test]
+fn alt_t_keeps_their_side_and_undo_restores_markers() {
+    let (mut app, _tmp) = app_with_content(CONFLICTED);
+    app.buf.textarea.move_cursor(CursorMove::Jump(7, 0));
+    app.handle_event(alt_key('t'));
+    assert_eq!(
+        app.buf.textarea.lines().join("\n"),
+        "intro\n<<<<<<< HEAD\nours\n=======\ntheirs\n>>>>>>> branch\nmiddle\nb\noutro"
+    );
+    // Undo the inserted side, then the removed conflict
+    app.handle_event(ctrl_key('z'));
+    app.handle_event(ctrl_key('z'));
+    assert_eq!(app.buf.textarea.lines().join("\n"), CONFLICTED);
+}
+
+#[test]
+fn alt_b_keeps_both_sides_inside_a_conflict() {
+    let (mut app, _tmp) = app_with_content("<<<<<<< HEAD\nours\n=======\ntheirs\n>>>>>>> branch");
+    app.buf.textarea.move_cursor(CursorMove::Jump(2, 0));
+    app.handle_event(alt_key('b'));
+    assert_eq!(app.buf.textarea.lines(), ["ours", "theirs"]);
+}
+
+#[test]
+fn resolve_outside_a_conflict_leaves_text_alone() {
+    let (mut app, _tmp) = app_with_content(CONFLICTED);
+    app.handle_event(alt_key('o'));
+    assert_eq!(app.buf.textarea.lines().join("\n"), CONFLICTED);
+    assert_eq!(app.status_message, "No conflict at cursor");
+}
+
+// ─── Task Toggle Tests ────────────────────────────────────────────
+
+fn ctrl_space() -> Event {
+    Event::Key(KeyEvent::new(KeyCode::Char(' '), KeyModifiers::CONTROL))
+}
+
+#[test]
+fn ctrl_space_toggles_task_from_any_column() {
+    let (mut app, _tmp) = app_with_content("# Todo\n  - [ ] nested task\n");
+    app.buf.textarea.move_cursor(CursorMove::Jump(1, 12));
+    app.handle_event(ctrl_space());
+    assert_eq!(app.buf.textarea.lines()[1], "  - [x] nested task");
+    assert_eq!(app.buf.textarea.cursor(), (1, 12), "cursor stays put");
+    assert!(app.buf.modified);
+
+    app.buf.textarea.move_cursor(CursorMove::Jump(1, 0));
+    app.handle_event(ctrl_space());
+    assert_eq!(app.buf.textarea.lines()[1], "  - [ ] nested task");
+}
+
+#[test]
+fn ctrl_space_on_non_task_line_is_a_no_op() {
+    let (mut app, _tmp) = app_with_content("- plain item\n");
+    app.handle_event(ctrl_space());
+    assert_eq!(app.buf.textarea.lines()[0], "- plain item");
+    assert!(!app.buf.modified);
+}
+
+// ─── Block Indent Tests ───────────────────────────────────────────
+
+#[test]
+fn tab_indents_selected_lines_and_keeps_selection() {
+    let (mut app, _tmp) = app_with_content("- one\n- two\n- three");
+    app.set_selection((0, 0), (1, 5));
+    app.handle_event(key_event(KeyCode::Tab));
+    assert_eq!(app.mode, Mode::Editor, "Tab with a selection stays in the editor");
+    assert_eq!(app.buf.textarea.lines(), ["  - one", "  - two", "- three"]);
+    assert_eq!(app.buf.textarea.selection_range(), Some(((0, 0), (1, 7))));
+
+    app.handle_event(key_event(KeyCode::Tab));
+    assert_eq!(app.buf.textarea.lines(), ["    - one", "    - two", "- three"]);
+    assert!(app.buf.modified);
+}
+
+#[test]
+fn shift_tab_outdents_up_to_two_spaces() {
+    let (mut app, _tmp) = app_with_content("   a\n b\nc\nd");
+    app.set_selection((0, 3), (3, 0));
+    app.handle_event(key_event(KeyCode::BackTab));
+    assert_eq!(app.buf.textarea.lines(), [" a", "b", "c", "d"], "row 3 isn't selected");
+    assert_eq!(app.buf.textarea.selection_range(), Some(((0, 1), (3, 0))));
+}
+
+#[test]
+fn tab_without_selection_still_switches_to_preview() {
+    let (mut app, _tmp) = app_with_content("- one");
+    app.handle_event(key_event(KeyCode::Tab));
+    assert_eq!(app.mode, Mode::Preview);
+    assert_eq!(app.buf.textarea.lines(), ["- one"]);
+}
+
+// ─── Shell Filter Tests ───────────────────────────────────────────
+
+fn alt_bang() -> Event {
+    Event::Key(KeyEvent::new(KeyCode::Char('!'), KeyModifiers::ALT | KeyModifiers::SHIFT))
+}
+
+#[cfg(unix)]
+#[test]
+fn filter_replaces_selection_with_command_output() {
+    let (mut app, _tmp) = app_with_content("keep\nshout this\nkeep");
+    app.set_selection((1, 6), (1, 10));
+    app.handle_event(alt_bang());
+    assert!(app.prompt.is_some());
+    type_str(&mut app, "tr a-z A-Z");
+    app.handle_event(key_event(KeyCode::Enter));
+
+    assert_eq!(app.buf.textarea.lines(), ["keep", "shout THIS", "keep"]);
+    assert!(app.buf.modified);
+}
+
+#[cfg(unix)]
+#[test]
+fn filter_without_selection_pipes_whole_buffer() {
+    let (mut app, _tmp) = app_with_content("pear\napple\nfig");
+    app.handle_event(alt_bang());
+    type_str(&mut app, "sort");
+    app.handle_event(key_event(KeyCode::Enter));
+    assert_eq!(app.buf.textarea.lines(), ["apple", "fig", "pear"]);
+}
+
+#[cfg(unix)]
+#[test]
+fn failed_filter_leaves_text_and_shows_stderr() {
+    let (mut app, _tmp) = app_with_content("text");
+    app.handle_event(alt_bang());
+    type_str(&mut app, "echo bad input >&2; exit 1");
+    app.handle_event(key_event(KeyCode::Enter));
+    assert_eq!(app.buf.textarea.lines(), ["text"]);
+    assert!(!app.buf.modified);
+    assert!(app.status_message.contains("bad input"), "{}", app.status_message);
+}
+
+// ─── Run Code Block Tests ─────────────────────────────────────────
+
+fn app_with_sh_runner(content: &str) -> (App<'static>, NamedTempFile) {
+    let (mut app, tmp) = app_with_content(content);
+    app.buf.config.code_runners.insert("sh".to_string(), "sh".to_string());
+    app.buf.textarea.move_cursor(CursorMove::Jump(1, 0));
+    (app, tmp)
+}
+
+fn run_block_and_confirm(app: &mut App) {
+    app.handle_event(alt_key('x'));
+    assert!(matches!(app.prompt, Some(Prompt::RunCodeBlock { .. })));
+    app.handle_event(char_event('y'));
+    app.handle_event(key_event(KeyCode::Enter));
+}
+
+#[cfg(unix)]
+#[test]
+fn run_code_block_inserts_output_block() {
+    let (mut app, _tmp) = app_with_sh_runner("```sh\necho hello\necho world\n```\nafter");
+    run_block_and_confirm(&mut app);
+    assert_eq!(
+        app.buf.textarea.lines(),
+        ["```sh", "echo hello", "echo world", "```", "", "```output", "hello", "world", "```", "after"]
+    );
+    assert!(app.buf.modified);
+}
+
+#[cfg(unix)]
+#[test]
+fn rerunning_code_block_replaces_previous_output() {
+    let (mut app, _tmp) =
+        app_with_sh_runner("```sh\necho new\n```\n\n```output\nold\nstale\n```\nafter");
+    run_block_and_confirm(&mut app);
+    assert_eq!(
+        app.buf.textarea.lines(),
+        ["```sh", "echo new", "```", "", "```output", "new", "```", "after"]
+    );
+}
+
+#[cfg(unix)]
+#[test]
+fn run_code_block_with_fence_options_runs() {
+    let (mut app, _tmp) = app_with_sh_runner("```sh {linenos}\necho hi\n```");
+    run_block_and_confirm(&mut app);
+    assert_eq!(app.status_message, "Output updated");
+    assert_eq!(app.buf.textarea.lines()[4..], ["```output", "hi", "```"]);
+}
+
+#[test]
+fn run_code_block_needs_confirmation_and_a_runner() {
+    let (mut app, _tmp) = app_with_sh_runner("```sh\necho hi\n```");
+    app.handle_event(alt_key('x'));
+    app.handle_event(char_event('n'));
+    app.handle_event(key_event(KeyCode::Enter));
+    assert_eq!(app.buf.textarea.lines().len(), 3, "declined: nothing ran");
+
+    let (mut app, _tmp) = app_with_content("```python\nprint(1)\n```");
+    app.buf.textarea.move_cursor(CursorMove::Jump(1, 0));
+    app.handle_event(alt_key('x'));
+    assert!(app.prompt.is_none());
+    assert!(app.status_message.contains("No runner for 'python'"));
+}
+
+// ─── Fold Tests ───────────────────────────────────────────────────
+
+const FOLDABLE: &str = "intro\n```rust\nfn a() {}\nfn b() {}\n```\noutro";
+
+#[test]
+fn alt_z_folds_block_and_down_steps_over_it() {
+    let (mut app, _tmp) = app_with_content(FOLDABLE);
+    app.buf.textarea.move_cursor(CursorMove::Jump(2, 3));
+    app.handle_event(alt_key('z'));
+    assert!(app.buf.folded.contains(&1));
+    assert_eq!(app.buf.textarea.cursor(), (1, 0), "cursor moves to the fence");
+
+    app.handle_event(key_event(KeyCode::Down));
+    assert_eq!(app.buf.textarea.cursor().0, 5, "skips the hidden body");
+    app.handle_event(key_event(KeyCode::Up));
+    assert_eq!(app.buf.textarea.cursor().0, 1);
+
+    app.handle_event(alt_key('z'));
+    assert!(app.buf.folded.is_empty());
+}
+
+#[test]
+fn folds_shift_with_edits_above_and_open_on_edits_inside() {
+    let (mut app, _tmp) = app_with_content(FOLDABLE);
+    app.buf.textarea.move_cursor(CursorMove::Jump(1, 0));
+    app.handle_event(alt_key('z'));
+
+    // A new line above moves the fold down with its block
+    app.buf.textarea.move_cursor(CursorMove::Jump(0, 5));
+    app.handle_event(key_event(KeyCode::Enter));
+    assert_eq!(app.buf.folded.iter().copied().collect::<Vec<_>>(), [2]);
+
+    // Typing on the fence line unfolds it
+    app.buf.textarea.move_cursor(CursorMove::Jump(2, 7));
+    type_str(&mut app, "x");
+    assert!(app.buf.folded.is_empty());
+}
+
+#[test]
+fn click_below_folded_block_maps_to_buffer_line() {
+    let (mut app, _tmp) = app_with_content(FOLDABLE);
+    setup_viewport(&mut app, 80, 20);
+    app.buf.textarea.move_cursor(CursorMove::Jump(1, 0));
+    app.handle_event(alt_key('z'));
+    // Screen rows: intro, ```rust (folded), outro
+    assert_eq!(app.mouse_to_buffer_pos(5, 3), (5, 2));
+}
+
+// ─── Split View Tests ─────────────────────────────────────────────
+
+#[test]
+fn alt_v_toggles_split_view_and_keys_go_to_editor() {
+    let (mut app, _tmp) = app_with_content("hello");
+    app.handle_event(alt_key('v'));
+    assert_eq!(app.mode, Mode::Split);
+
+    app.buf.textarea.move_cursor(CursorMove::End);
+    type_str(&mut app, "!");
+    assert_eq!(app.buf.textarea.lines()[0], "hello!");
+
+    app.handle_event(alt_key('v'));
+    assert_eq!(app.mode, Mode::Editor);
+}
+
+#[test]
+fn alt_v_toggles_preview_when_too_narrow_to_split() {
+    let (mut app, _tmp) = app_with_content("hello");
+    app.split_fits = false;
+    app.handle_event(alt_key('v'));
+    assert_eq!(app.mode, Mode::Preview);
+    app.handle_event(alt_key('v'));
+    assert_eq!(app.mode, Mode::Editor);
+}
+
+#[test]
+fn wheel_over_split_preview_scrolls_preview_only() {
+    let (mut app, _tmp) = app_with_content("line\n".repeat(100).as_str());
+    setup_viewport(&mut app, 40, 20);
+    app.mode = Mode::Split;
+    app.preview_area = Rect::new(41, 2, 39, 20);
+    app.preview.content_height = 200;
+
+    app.handle_event(mouse_event(MouseEventKind::ScrollDown, 50, 5));
+    assert_eq!(app.preview.scroll_offset, SCROLL_LINES);
+    assert_eq!(app.buf.editor_scroll_top, 0);
+}
+
+// ─── Outline Panel Tests ──────────────────────────────────────────
+
+const OUTLINED: &str = "# Intro\ntext\n## Setup\n```sh\n# not a heading\n```\n## Usage\nmore";
+
+#[test]
+fn ctrl_o_opens_outline_at_cursor_section() {
+    let (mut app, _tmp) = app_with_content(OUTLINED);
+    app.buf.textarea.move_cursor(CursorMove::Jump(4, 0));
+    app.handle_event(ctrl_key('o'));
+    let outline = app.outline.as_ref().unwrap();
+    let titles: Vec<&str> = outline.headings.iter().map(|h| h.title.as_str()).collect();
+    assert_eq!(titles, ["Intro", "Setup", "Usage"]);
+    assert_eq!(outline.selected, 1, "cursor is in the Setup section");
+}
+
+#[test]
+fn outline_enter_jumps_to_heading() {
+    let (mut app, _tmp) = app_with_content(OUTLINED);
+    app.handle_event(ctrl_key('o'));
+    app.handle_event(key_event(KeyCode::Down));
+    app.handle_event(key_event(KeyCode::Down));
+    app.handle_event(key_event(KeyCode::Down)); // stays on the last heading
+    app.handle_event(key_event(KeyCode::Enter));
+    assert!(app.outline.is_none());
+    assert_eq!(app.buf.textarea.cursor(), (6, 0));
+}
+
+#[test]
+fn outline_esc_closes_without_moving() {
+    let (mut app, _tmp) = app_with_content(OUTLINED);
+    app.handle_event(ctrl_key('o'));
+    app.handle_event(key_event(KeyCode::End));
+    app.handle_event(key_event(KeyCode::Esc));
+    assert!(app.outline.is_none());
+    assert_eq!(app.buf.textarea.cursor(), (0, 0));
+    assert_eq!(app.mode, Mode::Editor);
+}
+
+#[test]
+fn outline_without_headings_shows_status() {
+    let (mut app, _tmp) = app_with_content("just text");
+    app.handle_event(ctrl_key('o'));
+    assert!(app.outline.is_none());
+    assert_eq!(app.status_message, "No headings");
+}
+
+// ─── Macro Tests ──────────────────────────────────────────────────
+
+#[test]
+fn recorded_macro_replays_edits() {
+    let (mut app, _tmp) = app_with_content("a\nb\nc");
+    app.handle_event(key_event(KeyCode::F(3)));
+    app.handle_event(key_event(KeyCode::End));
+    type_str(&mut app, ";");
+    app.handle_event(key_event(KeyCode::Down));
+    app.handle_event(key_event(KeyCode::F(3)));
+    assert_eq!(app.macro_keys.len(), 3, "F3 itself isn't recorded");
+
+    app.handle_event(key_event(KeyCode::F(4)));
+    assert_eq!(app.buf.textarea.lines(), ["a;", "b;", "c"]);
+}
+
+#[test]
+fn shift_f4_replays_macro_count_times() {
+    let (mut app, _tmp) = app_with_content("");
+    app.handle_event(key_event(KeyCode::F(3)));
+    type_str(&mut app, "x");
+    app.handle_event(key_event(KeyCode::F(3)));
+
+    app.handle_event(Event::Key(KeyEvent::new(KeyCode::F(4), KeyModifiers::SHIFT)));
+    assert_eq!(app.prompt, Some(Prompt::MacroRepeat));
+    type_str(&mut app, "3");
+    app.handle_event(key_event(KeyCode::Enter));
+    assert_eq!(app.buf.textarea.lines(), ["xxxx"]);
+}
+
+#[test]
+fn macro_cannot_replay_while_recording() {
+    let (mut app, _tmp) = app_with_content("");
+    app.handle_event(key_event(KeyCode::F(3)));
+    type_str(&mut app, "x");
+    app.handle_event(key_event(KeyCode::F(4)));
+    assert_eq!(app.buf.textarea.lines(), ["x"]);
+    assert!(app.macro_recording.is_some(), "still recording");
+    app.handle_event(key_event(KeyCode::F(3)));
+    assert_eq!(app.macro_keys.len(), 1, "F4 wasn't recorded");
+}
+
+// ─── Repeat Edit Tests ────────────────────────────────────────────
+
+#[test]
+fn alt_period_repeats_typed_text_at_new_cursor() {
+    let (mut app, _tmp) = app_with_content("one\ntwo");
+    type_str(&mut app, "- ");
+    app.handle_event(key_event(KeyCode::Down));
+    app.handle_event(key_event(KeyCode::Home));
+    app.handle_event(alt_key('.'));
+    assert_eq!(app.buf.textarea.lines(), ["- one", "- two"]);
+    assert!(app.buf.modified);
+}
+
+#[test]
+fn typed_run_includes_backspace_and_ends_when_cursor_moves() {
+    let (mut app, _tmp) = app_with_content("ab\ncd");
+    type_str(&mut app, "xz");
+    app.handle_event(key_event(KeyCode::Backspace));
+    type_str(&mut app, "y");
+    assert_eq!(app.last_action, Some(EditAction::Insert("xy".to_string())));
+
+    // Moving away starts a new run
+    app.handle_event(key_event(KeyCode::Right));
+    type_str(&mut app, "!");
+    assert_eq!(app.last_action, Some(EditAction::Insert("!".to_string())));
+}
+
+#[test]
+fn alt_period_repeats_delete_word() {
+    let (mut app, _tmp) = app_with_content("alpha beta\ngamma delta");
+    app.handle_event(ctrl_key('d'));
+    assert_eq!(app.buf.textarea.lines()[0], " beta");
+    app.buf.textarea.move_cursor(CursorMove::Jump(1, 0));
+    app.handle_event(alt_key('.'));
+    assert_eq!(app.buf.textarea.lines(), [" beta", " delta"]);
+}
+
+#[test]
+fn alt_period_without_edit_does_nothing() {
+    let (mut app, _tmp) = app_with_content("hello");
+    app.handle_event(alt_key('.'));
+    assert_eq!(app.buf.textarea.lines(), ["hello"]);
+    assert_eq!(app.status_message, "No edit to repeat");
+}
+
+// ─── Jump List Tests ──────────────────────────────────────────────
+
+#[test]
+fn alt_left_returns_to_position_before_search() {
+    let (mut app, _tmp) = app_with_content("alpha\nbeta\ngamma target\ndelta");
+    app.buf.textarea.move_cursor(CursorMove::Jump(1, 2));
+    app.handle_event(ctrl_key('f'));
+    type_str(&mut app, "target");
+    app.handle_event(key_event(KeyCode::Esc));
+    assert_eq!(app.buf.textarea.cursor().0, 2);
+
+    app.handle_event(Event::Key(KeyEvent::new(KeyCode::Left, KeyModifiers::ALT)));
+    assert_eq!(app.buf.textarea.cursor(), (1, 2));
+
+    // And forward again to where the search left off
+    app.handle_event(Event::Key(KeyEvent::new(KeyCode::Right, KeyModifiers::ALT)));
+    assert_eq!(app.buf.textarea.cursor(), (2, 12));
+}
+
+#[test]
+fn jump_list_steps_back_through_several_jumps() {
+    let (mut app, _tmp) = app_with_content("one\ntwo\nthree\nfour");
+    app.buf.textarea.move_cursor(CursorMove::Jump(1, 0));
+    app.handle_event(Event::Key(KeyEvent::new(KeyCode::End, KeyModifiers::CONTROL)));
+    app.handle_event(Event::Key(KeyEvent::new(KeyCode::Home, KeyModifiers::CONTROL)));
+    assert_eq!(app.buf.textarea.cursor(), (0, 0));
+
+    let alt_left = Event::Key(KeyEvent::new(KeyCode::Left, KeyModifiers::ALT));
+    app.handle_event(alt_left.clone());
+    assert_eq!(app.buf.textarea.cursor(), (3, 4));
+    app.handle_event(alt_left.clone());
+    assert_eq!(app.buf.textarea.cursor(), (1, 0));
+    app.handle_event(alt_left);
+    assert_eq!(app.buf.textarea.cursor(), (1, 0), "nothing earlier");
+    assert_eq!(app.status_message, "No earlier position");
+}
+
+// ─── Mark Tests ───────────────────────────────────────────────────
+
+/// Creates an App for `notes.md` in its own temp dir, so the `.marko`
+/// sidecar directory doesn't leak between tests.
+fn app_in_dir(content: &str) -> (App<'static>, tempfile::TempDir) {
+    let dir = tempfile::TempDir::new().unwrap();
+    let path = dir.path().join("notes.md");
+    std::fs::write(&path, content).unwrap();
+    (App::new(path), dir)
+}
+
+#[test]
+fn alt_k_sets_mark_and_alt_j_jumps_to_it() {
+    let (mut app, _dir) = app_in_dir("one\ntwo\nthree");
+    app.buf.textarea.move_cursor(CursorMove::Jump(1, 2));
+    app.handle_event(alt_key('k'));
+    app.handle_event(char_event('a'));
+    assert_eq!(app.buf.marks.get(&'a'), Some(&(1, 2)));
+
+    app.buf.textarea.move_cursor(CursorMove::Jump(2, 0));
+    app.handle_event(alt_key('j'));
+    app.handle_event(char_event('a'));
+    assert_eq!(app.buf.textarea.cursor(), (1, 2));
+
+    // The jump goes through the jump list
+    app.handle_event(Event::Key(KeyEvent::new(KeyCode::Left, KeyModifiers::ALT)));
+    assert_eq!(app.buf.textarea.cursor(), (2, 0));
+}
+
+#[test]
+fn jumping_to_unset_mark_reports_it() {
+    let (mut app, _dir) = app_in_dir("one\ntwo");
+    app.handle_event(alt_key('j'));
+    app.handle_event(char_event('z'));
+    assert_eq!(app.status_message, "No mark 'z'");
+    assert_eq!(app.buf.textarea.cursor(), (0, 0));
+    // The letter was consumed, not typed
+    assert_eq!(app.buf.textarea.lines(), ["one", "two"]);
+}
+
+#[test]
+fn marks_survive_reopening_and_clamp_to_shrunk_file() {
+    let (mut app, dir) = app_in_dir("a\nb\nc\nd");
+    let path = dir.path().join("notes.md");
+    app.buf.textarea.move_cursor(CursorMove::Jump(3, 1));
+    app.handle_event(alt_key('k'));
+    app.handle_event(char_event('m'));
+    assert!(dir.path().join(".marko/marks.toml").exists());
+
+    std::fs::write(&path, "a\nb").unwrap();
+    let mut reopened = App::new(path);
+    assert_eq!(reopened.buf.marks.get(&'m'), Some(&(3, 1)));
+    reopened.handle_event(alt_key('j'));
+    reopened.handle_event(char_event('m'));
+    assert_eq!(reopened.buf.textarea.cursor(), (1, 1));
+}
+
+// ─── Read-Only Tests ──────────────────────────────────────────────
+
+#[test]
+fn read_only_ignores_edits_but_allows_navigation() {
+    let (mut app, _tmp) = app_with_content("hello\nworld");
+    app.read_only = true;
+    type_str(&mut app, "xy");
+    app.handle_event(key_event(KeyCode::Enter));
+    app.handle_event(key_event(KeyCode::Backspace));
+    app.handle_event(ctrl_key('v'));
+    app.handle_event(Event::Paste("pasted".to_string()));
+    assert_eq!(app.buf.textarea.lines(), ["hello", "world"]);
+    assert!(!app.buf.modified);
+    assert_eq!(app.status_message, "Read-only");
+
+    app.handle_event(key_event(KeyCode::Down));
+    app.handle_event(key_event(KeyCode::End));
+    assert_eq!(app.buf.textarea.cursor(), (1, 5));
+    app.handle_event(ctrl_key('f'));
+    assert!(app.search.is_some());
+    app.handle_event(key_event(KeyCode::Esc));
+    app.handle_event(key_event(KeyCode::Tab));
+    assert_eq!(app.mode, Mode::Preview);
+}
+
+#[test]
+fn read_only_ctrl_s_reports_instead_of_saving() {
+    let (mut app, tmp) = app_with_content("hello");
+    app.read_only = true;
+    app.handle_event(ctrl_key('s'));
+    assert_eq!(app.status_message, "Read-only");
+    assert_eq!(std::fs::read_to_string(tmp.path()).unwrap(), "hello");
+
+    app.handle_event(ctrl_key('q'));
+    assert!(app.should_quit);
+}
+
+#[cfg(unix)]
+#[test]
+fn unwritable_file_opens_read_only() {
+    use std::os::unix::fs::PermissionsExt;
+    let dir = tempfile::TempDir::new().unwrap();
+    let path = dir.path().join("locked.md");
+    std::fs::write(&path, "hello").unwrap();
+    std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o444)).unwrap();
+
+    let mut app = App::new(path);
+    assert!(app.is_read_only());
+    assert!(app.status_message.starts_with("Read-only"), "{}", app.status_message);
+    app.show_startup_hint(false);
+    assert!(app.status_message.starts_with("Read-only"), "note outlasts the hint");
+    type_str(&mut app, "x");
+    assert_eq!(app.buf.textarea.lines(), ["hello"]);
+}
+
+// ─── Spell Check Tests ────────────────────────────────────────────
+
+#[cfg(feature = "spellcheck")]
+fn app_with_spellcheck(content: &str) -> (App<'static>, tempfile::TempDir) {
+    let dir = tempfile::TempDir::new().unwrap();
+    let words = dir.path().join("words.txt");
+    std::fs::write(&words, "the\ncat\nsat\non\nmat\n").unwrap();
+    let path = dir.path().join("notes.md");
+    std::fs::write(&path, content).unwrap();
+    let mut app = App::new(path);
+    app.buf.config.spellcheck = true;
+    app.buf.config.spell_dictionary = Some(words);
+    (app, dir)
+}
+
+#[cfg(feature = "spellcheck")]
+#[test]
+fn f7_jumps_to_next_misspelling_skipping_code_fences() {
+    let (mut app, _dir) = app_with_spellcheck("the cat\n```\nxyzzy\n```\nthe kat sat\non teh mat");
+    app.handle_event(key_event(KeyCode::F(7)));
+    assert_eq!(app.buf.textarea.cursor(), (4, 4));
+    assert_eq!(app.status_message, "Misspelled: kat");
+
+    app.handle_event(key_event(KeyCode::F(7)));
+    assert_eq!(app.buf.textarea.cursor(), (5, 3));
+    app.handle_event(key_event(KeyCode::F(7)));
+    assert_eq!(app.buf.textarea.cursor(), (4, 4), "wraps around");
+}
+
+#[cfg(feature = "spellcheck")]
+#[test]
+fn f7_reports_when_off_or_clean() {
+    let (mut app, _dir) = app_with_spellcheck("the cat sat");
+    app.handle_event(key_event(KeyCode::F(7)));
+    assert_eq!(app.status_message, "No misspellings");
+
+    app.buf.config.spellcheck = false;
+    app.handle_event(key_event(KeyCode::F(7)));
+    assert!(app.status_message.starts_with("Spell check is off"));
+}
+
+#[cfg(feature = "spellcheck")]
+#[test]
+fn missing_dictionary_is_reported() {
+    let (mut app, dir) = app_with_spellcheck("teh");
+    app.buf.config.spell_dictionary = Some(dir.path().join("nope.txt"));
+    app.handle_event(key_event(KeyCode::F(7)));
+    assert!(app.status_message.starts_with("Can't read dictionary"), "{}", app.status_message);
+    assert_eq!(app.buf.textarea.cursor(), (0, 0));
+}
+
+#[cfg(feature = "spellcheck")]
+#[test]
+fn rendering_never_loads_the_dictionary() {
+    let (mut app, _dir) = app_with_spellcheck("teh");
+    let mut terminal = ratatui::Terminal::new(ratatui::backend::TestBackend::new(40, 24)).unwrap();
+    terminal.draw(|f| app.render(f)).unwrap();
+    assert!(app.spell.is_none());
+
+    app.load_spell_dictionary();
+    assert!(matches!(app.spell, Some(Ok(_))));
+}
+
+#[cfg(feature = "spellcheck")]
+#[test]
+fn spell_underline_lines_up_after_wide_chars() {
+    let (mut app, _dir) = app_with_spellcheck("the 日本 kat");
+    app.load_spell_dictionary();
+    let mut terminal = ratatui::Terminal::new(ratatui::backend::TestBackend::new(40, 24)).unwrap();
+    terminal.draw(|f| app.render(f)).unwrap();
+
+    // " 1 " gutter, then "the " and two double-width chars: "kat" is at 12..15
+    let screen = terminal.backend().buffer();
+    let underlined = |x: u16| screen[(x, 2)].modifier.contains(Modifier::UNDERLINED);
+    assert!((12..15).all(underlined));
+    assert!(!underlined(11) && !underlined(15));
+}
+
+// ─── Word Goal Tests ──────────────────────────────────────────────
+
+#[test]
+fn alt_g_sets_word_goal_over_front_matter() {
+    let (mut app, _tmp) = app_with_content("---\ngoal: 1000\n---\ntext");
+    assert_eq!(app.effective_word_goal(), Some(1000));
+
+    app.handle_event(alt_key('g'));
+    assert_eq!(app.prompt, Some(Prompt::WordGoal));
+    type_str(&mut app, "5x0");
+    app.handle_event(key_event(KeyCode::Enter));
+    assert_eq!(app.effective_word_goal(), Some(50));
+    assert_eq!(app.status_message, "Word goal: 50 words");
+
+    app.handle_event(alt_key('g'));
+    type_str(&mut app, "0");
+    app.handle_event(key_event(KeyCode::Enter));
+    assert_eq!(app.effective_word_goal(), None, "0 clears even the front matter's");
+}
+
+#[test]
+fn word_goal_from_modeline_front_matter_or_config() {
+    let (mut app, _tmp) = app_with_content("---\ngoal: 1000\n---\ntext\n<!-- marko:goal 300 -->");
+    assert_eq!(app.effective_word_goal(), Some(300), "modeline over front matter");
+
+    let (mut app2, _tmp2) = app_with_content("text");
+    assert_eq!(app2.effective_word_goal(), None);
+    app2.buf.config.word_goal = Some(800);
+    assert_eq!(app2.effective_word_goal(), Some(800));
+
+    app.buf.config.word_goal = Some(800);
+    assert_eq!(app.effective_word_goal(), Some(300), "file over config");
+}
+
+// ─── Focus Timer Tests ────────────────────────────────────────────
+
+#[test]
+fn focus_timer_start_stop_accumulates() {
+    let t0 = Instant::now();
+    let mut timer = focus::FocusTimer::new(Duration::from_secs(60));
+    assert!(!timer.is_running());
+    assert_eq!(timer.stop(t0), Duration::ZERO, "stopping a stopped timer");
+
+    timer.start(t0);
+    timer.start(t0 + Duration::from_secs(5)); // already running
+    assert!(timer.is_running());
+    assert_eq!(timer.session(t0 + Duration::from_secs(30)), Duration::from_secs(30));
+    assert_eq!(timer.total(t0 + Duration::from_secs(30)), Duration::from_secs(90));
+
+    assert_eq!(timer.stop(t0 + Duration::from_secs(40)), Duration::from_secs(40));
+    assert!(!timer.is_running());
+    assert_eq!(timer.total(t0 + Duration::from_secs(100)), Duration::from_secs(100));
+}
+
+#[test]
+fn focus_timer_pomodoro_due_once_per_interval() {
+    let t0 = Instant::now();
+    let minute = Duration::from_secs(60);
+    let mut timer = focus::FocusTimer::new(Duration::ZERO);
+    assert!(!timer.pomodoro_due(t0, minute), "not running");
+    timer.start(t0);
+    assert!(!timer.pomodoro_due(t0 + minute / 2, minute));
+    assert!(timer.pomodoro_due(t0 + minute, minute));
+    assert!(!timer.pomodoro_due(t0 + minute + minute / 2, minute), "flashed once");
+    assert!(timer.pomodoro_due(t0 + minute * 2, minute));
+}
+
+#[test]
+fn focus_elapsed_formatting() {
+    assert_eq!(focus::format_elapsed(Duration::from_secs(0)), "0:00");
+    assert_eq!(focus::format_elapsed(Duration::from_secs(65)), "1:05");
+    assert_eq!(focus::format_elapsed(Duration::from_secs(25 * 60)), "25:00");
+    assert_eq!(focus::format_elapsed(Duration::from_secs(3600 + 62)), "1:01:02");
+}
+
+#[test]
+fn f6_toggles_focus_timer_and_records_time() {
+    let (mut app, dir) = app_in_dir("text");
+    app.handle_event(key_event(KeyCode::F(6)));
+    assert!(app.focus_elapsed().is_some());
+    assert_eq!(app.status_message, "Focus timer started");
+
+    app.handle_event(key_event(KeyCode::F(6)));
+    assert!(app.focus_elapsed().is_none());
+    assert!(app.status_message.starts_with("Focus timer stopped: 0:00"), "{}", app.status_message);
+    let store = std::fs::read_to_string(dir.path().join(".marko/focus.toml")).unwrap();
+    assert!(store.contains("\"notes.md\" = 0"), "{}", store);
+}
+
+// ─── Vim Editing Tests ────────────────────────────────────────────
+
+fn vim_app(content: &str) -> (App<'static>, NamedTempFile) {
+    let (mut app, tmp) = app_with_content(content);
+    app.buf.config.editing = Editing::Vim;
+    app.vim_normal = true;
+    (app, tmp)
+}
+
+#[test]
+fn vim_normal_mode_moves_instead_of_typing() {
+    let (mut app, _tmp) = vim_app("hello\nworld");
+    type_str(&mut app, "jllq");
+    assert_eq!(app.buf.textarea.lines(), ["hello", "world"]);
+    assert_eq!(app.buf.textarea.cursor(), (1, 2));
+    type_str(&mut app, "kh");
+    assert_eq!(app.buf.textarea.cursor(), (0, 1));
+}
+
+#[test]
+fn vim_insert_and_escape() {
+    let (mut app, _tmp) = vim_app("hello");
+    type_str(&mut app, "iab");
+    assert_eq!(app.buf.textarea.lines(), ["abhello"]);
+    assert!(!app.vim_normal);
+
+    app.handle_event(key_event(KeyCode::Esc));
+    assert!(app.vim_normal);
+    assert_eq!(app.buf.textarea.cursor(), (0, 1), "steps back onto the last typed char");
+    type_str(&mut app, "ac");
+    assert_eq!(app.buf.textarea.lines(), ["abchello"], "a appends after the cursor");
+}
+
+#[test]
+fn vim_x_and_dd_delete() {
+    let (mut app, _tmp) = vim_app("one\ntwo\nthree");
+    type_str(&mut app, "x");
+    assert_eq!(app.buf.textarea.lines(), ["ne", "two", "three"]);
+
+    type_str(&mut app, "jdd");
+    assert_eq!(app.buf.textarea.lines(), ["ne", "three"]);
+    assert_eq!(app.buf.textarea.cursor(), (1, 0));
+    type_str(&mut app, "dd");
+    assert_eq!(app.buf.textarea.lines(), ["ne"]);
+    assert!(app.buf.modified);
+
+    app.handle_event(ctrl_key('z'));
+    assert_eq!(app.buf.textarea.lines(), ["ne", "three"], "dd undoes in one step");
+}
+
+#[test]
+fn vim_command_line_saves_and_quits() {
+    let (mut app, tmp) = vim_app("hello");
+    type_str(&mut app, "x:q");
+    app.handle_event(key_event(KeyCode::Enter));
+    assert!(!app.should_quit, "unsaved changes");
+    assert!(app.status_message.starts_with("No write"));
+
+    type_str(&mut app, ":w");
+    app.handle_event(key_event(KeyCode::Enter));
+    assert_eq!(std::fs::read_to_string(tmp.path()).unwrap(), "ello");
+    type_str(&mut app, ":q");
+    app.handle_event(key_event(KeyCode::Enter));
+    assert!(app.should_quit);
+}
+
+#[test]
+fn vim_quit_minds_unsaved_edits_in_other_files() {
+    let dir = tempfile::TempDir::new().unwrap();
+    let a = dir.path().join("a.md");
+    let b = dir.path().join("b.md");
+    std::fs::write(&a, "alpha").unwrap();
+    std::fs::write(&b, "beta").unwrap();
+    let mut app = App::new(a.clone());
+    app.file_list.push(b.clone());
+    app.buf.config.editing = Editing::Vim;
+    app.vim_normal = true;
+    type_str(&mut app, "x");
+    app.handle_event(Event::Key(KeyEvent::new(KeyCode::PageDown, KeyModifiers::CONTROL)));
+    assert_eq!(app.buf.file_path, b);
+    // b.md's config is read from disk, not copied from a.md's
+    app.buf.config.editing = Editing::Vim;
+
+    type_str(&mut app, ":q");
+    app.handle_event(key_event(KeyCode::Enter));
+    assert!(!app.should_quit, "a.md has unsaved changes");
+    assert_eq!(app.prompt, Some(Prompt::QuitUnsaved));
+    app.handle_event(key_event(KeyCode::Esc));
+
+    type_str(&mut app, ":wq");
+    app.handle_event(key_event(KeyCode::Enter));
+    assert!(app.should_quit);
+    assert_eq!(std::fs::read_to_string(&a).unwrap(), "lpha", "saved before quitting");
+}
+
+#[test]
+fn vim_slash_starts_search_and_modeless_is_untouched() {
+    let (mut app, _tmp) = vim_app("hello");
+    type_str(&mut app, "/");
+    assert!(app.search.is_some());
+
+    let (mut app, _tmp) = app_with_content("hello");
+    type_str(&mut app, "jx");
+    app.handle_event(key_event(KeyCode::Esc));
+    assert_eq!(app.buf.textarea.lines(), ["jxhello"]);
+    assert!(!app.vim_normal);
+}
+
+// ─── Element Preview Tests ────────────────────────────────────────
+
+#[test]
+fn element_source_is_the_table_around_the_cursor() {
+    let content = "Intro text.\n\n| a | b |\n|---|---|\n| 1 | 2 |\n\nAfter.";
+    let (mut app, _tmp) = app_with_content(content);
+    app.buf.textarea.move_cursor(CursorMove::Jump(3, 2));
+    assert_eq!(
+        app.element_source_at_cursor().as_deref(),
+        Some("| a | b |\n|---|---|\n| 1 | 2 |")
+    );
+
+    app.handle_event(alt_key('e'));
+    assert!(app.element_preview.is_some());
+    app.handle_event(key_event(KeyCode::Down));
+    assert!(app.element_preview.is_none());
+    assert_eq!(app.buf.textarea.cursor().0, 3, "the closing key is not applied");
+}
+
+#[test]
+fn element_source_stops_at_code_fences() {
+    let (mut app, _tmp) = app_with_content("Text\n```\ncode\n\nmore\n```\nTail");
+    app.buf.textarea.move_cursor(CursorMove::Jump(4, 0));
+    assert_eq!(app.element_source_at_cursor().as_deref(), Some("```\ncode\n\nmore\n```"));
+    app.buf.textarea.move_cursor(CursorMove::Jump(0, 0));
+    assert_eq!(app.element_source_at_cursor().as_deref(), Some("Text"));
+    app.buf.textarea.move_cursor(CursorMove::Jump(3, 0));
+    assert!(app.element_source_at_cursor().unwrap().starts_with("```"), "blank line in a fence");
+}
+
+// ─── Theme Tests ──────────────────────────────────────────────────
+
+#[test]
+fn f8_cycles_the_ui_theme() {
+    let (mut app, _tmp) = app_with_content("text");
+    app.buf.textarea.select_all();
+    app.handle_event(key_event(KeyCode::F(8)));
+    assert_eq!(theme::active(), &theme::LIGHT);
+    assert_eq!(app.status_message, "Theme: light");
+    assert_eq!(app.buf.textarea.selection_style().bg, Some(theme::LIGHT.selection));
+    assert!(app.buf.code_fence_dirty, "code blocks are highlighted again");
+
+    app.handle_event(key_event(KeyCode::F(8)));
+    app.handle_event(key_event(KeyCode::F(8)));
+    assert_eq!(theme::active(), &theme::DARK);
+}
+
+// ─── Code Fence Highlighting Tests ────────────────────────────────
+
+#[test]
+fn code_fences_are_highlighted_in_the_background() {
+    code_highlight::syntax_set();
+    code_highlight::theme_set();
+    let blocks: Vec<String> = (0..6).map(|i| format!("```rust\nfn f{}() {{}}\n```", i)).collect();
+    let (mut app, _tmp) = app_with_content(&blocks.join("\n\n"));
+
+    assert!(app.refresh_code_fences());
+    assert_eq!(app.buf.code_fence_regions.len(), 6);
+    assert!(app.buf.code_fence_handle.is_some(), "highlighting runs off the main thread");
+    assert!(app.buf.code_fence_highlights.is_empty(), "not applied until it's done");
+
+    for _ in 0..500 {
+        app.tick();
+        if app.buf.code_fence_handle.is_none() {
+            break;
+        }
+        std::thread::sleep(Duration::from_millis(10));
+    }
+    assert!(app.buf.code_fence_handle.is_none());
+    assert_eq!(app.buf.code_fence_highlights.len(), 6);
+    for (i, region) in app.buf.code_fence_highlights.iter().enumerate() {
+        let text: String = region[0].iter().map(|(_, text)| text.as_str()).collect();
+        assert_eq!(text, format!("fn f{}() {{}}", i), "regions stay in order");
+    }
+}
+
+// ─── Live Table Formatting Tests ──────────────────────────────────
+
+#[test]
+fn leaving_a_table_row_formats_the_table() {
+    let (mut app, _tmp) = app_with_content("| a | b |\n|---|---|\n| 1 | 2 |\n\nafter");
+    app.buf.textarea.move_cursor(CursorMove::Jump(2, 7));
+    type_str(&mut app, "longer");
+    assert_eq!(app.buf.textarea.lines()[2], "| 1 | 2longer |", "not while typing in the row");
+
+    app.handle_event(key_event(KeyCode::Up));
+    let lines = app.buf.textarea.lines();
+    assert_ne!(lines[2], "| 1 | 2longer |", "formatted on leaving the row");
+    assert!(lines[2].contains("| 2longer"));
+    let width = lines[0].chars().count();
+    assert!(lines[..3].iter().all(|l| l.chars().count() == width), "{:#?}", lines);
+    assert_eq!(lines[4], "after");
+    assert_eq!(app.buf.textarea.cursor().0, 1);
+
+    // Moving within the table keeps the cursor in its cell
+    app.handle_event(key_event(KeyCode::Up));
+    let (row, col) = app.buf.textarea.cursor();
+    let before: String = app.buf.textarea.lines()[row].chars().take(col).collect();
+    assert_eq!(before.matches('|').count(), 2, "still in the second column");
+}
+
+#[test]
+fn live_table_format_can_be_turned_off() {
+    let (mut app, _tmp) = app_with_content("| a | b |\n|---|---|\n| 1 | 2 |");
+    app.buf.config.live_table_format = false;
+    app.handle_event(key_event(KeyCode::Down));
+    app.handle_event(key_event(KeyCode::Down));
+    assert_eq!(app.buf.textarea.lines()[0], "| a | b |");
+}
+
+// ─── New Table Tests ──────────────────────────────────────────────
+
+/// Ctrl+Shift+T, answering the prompts with `rows` and `columns`.
+fn insert_new_table(app: &mut App, rows: &str, columns: &str) {
+    app.handle_event(Event::Key(KeyEvent::new(
+        KeyCode::Char('T'),
+        KeyModifiers::CONTROL | KeyModifiers::SHIFT,
+    )));
+    assert_eq!(app.prompt, Some(Prompt::TableRows));
+    type_str(app, rows);
+    app.handle_event(key_event(KeyCode::Enter));
+    type_str(app, columns);
+    app.handle_event(key_event(KeyCode::Enter));
+}
+
+#[test]
+fn new_table_inserts_a_formatted_skeleton() {
+    let (mut app, _tmp) = app_with_content("");
+    setup_viewport(&mut app, 40, 20);
+    insert_new_table(&mut app, "2", "3");
+
+    let lines = app.buf.textarea.lines();
+    assert_eq!(lines.len(), 4, "{:#?}", lines);
+    assert!(lines[1].starts_with("| ---"));
+    assert!(lines.iter().all(|l| l.matches('|').count() == 4), "{:#?}", lines);
+    let width = lines[0].chars().count();
+    assert!(lines.iter().all(|l| l.chars().count() == width));
+    assert!(width <= app.available_text_width());
+    assert_eq!(app.buf.textarea.cursor(), (0, 2), "in the first header cell");
+
+    type_str(&mut app, "Name");
+    assert!(app.buf.textarea.lines()[0].starts_with("| Name"));
+}
+
+#[test]
+fn new_table_goes_below_a_non_blank_line() {
+    let (mut app, _tmp) = app_with_content("Intro text\nmore");
+    insert_new_table(&mut app, "1", "2");
+
+    let lines = app.buf.textarea.lines();
+    assert_eq!(lines[0], "Intro text");
+    assert_eq!(lines[1], "");
+    assert!(lines[2].starts_with('|') && lines[4].starts_with('|'));
+    assert_eq!(lines[5], "", "blank line before the text after it");
+    assert_eq!(lines[6], "more");
+    assert_eq!(app.buf.textarea.cursor(), (2, 2));
+}
+
+#[test]
+fn new_table_rejects_zero_columns() {
+    let (mut app, _tmp) = app_with_content("");
+    insert_new_table(&mut app, "2", "0");
+    assert_eq!(app.buf.textarea.lines(), [""]);
+    assert_eq!(app.status_severity, Severity::Warning);
+}
+
+// ─── Goal Column Tests ────────────────────────────────────────────
+
+#[test]
+fn vertical_moves_keep_the_goal_column() {
+    let (mut app, _tmp) = app_with_content("a longer first line\nab\nanother long line");
+    app.buf.textarea.move_cursor(CursorMove::Jump(0, 10));
+    app.handle_event(key_event(KeyCode::Down));
+    assert_eq!(app.buf.textarea.cursor(), (1, 2), "clamped on the short line");
+    app.handle_event(key_event(KeyCode::Up));
+    assert_eq!(app.buf.textarea.cursor(), (0, 10), "back to the original column");
+
+    app.handle_event(key_event(KeyCode::Down));
+    app.handle_event(key_event(KeyCode::Down));
+    assert_eq!(app.buf.textarea.cursor(), (2, 10), "restored on a longer line below");
+}
+
+// ─── Auto-Close Pair Deletion Tests ───────────────────────────────
+
+#[test]
+fn backspace_deletes_each_empty_auto_closed_pair() {
+    for open in ['(', '[', '{', '`', '"', '\''] {
+        let (mut app, _tmp) = app_with_content("");
+        type_str(&mut app, &format!("a {}", open));
+        assert_eq!(app.buf.textarea.lines()[0].chars().count(), 4, "{} was auto-closed", open);
+        app.handle_event(key_event(KeyCode::Backspace));
+        assert_eq!(app.buf.textarea.lines()[0], "a ", "both halves of {} deleted", open);
+        assert_eq!(app.buf.textarea.cursor(), (0, 2));
+    }
+}
+
+#[test]
+fn backspace_keeps_a_pair_with_text_between() {
+    let (mut app, _tmp) = app_with_content("");
+    type_str(&mut app, "(x");
+    app.handle_event(key_event(KeyCode::Backspace));
+    assert_eq!(app.buf.textarea.lines()[0], "()", "only the x");
+    app.handle_event(key_event(KeyCode::Backspace));
+    assert_eq!(app.buf.textarea.lines()[0], "");
+
+    // Unmatched neighbours are not a pair
+    let (mut app, _tmp) = app_with_content("(]");
+    app.buf.textarea.move_cursor(CursorMove::Jump(0, 1));
+    app.handle_event(key_event(KeyCode::Backspace));
+    assert_eq!(app.buf.textarea.lines()[0], "]");
+}
+
+// ─── Rectangular Selection Tests ──────────────────────────────────
+
+fn alt_shift_key(code: KeyCode) -> Event {
+    Event::Key(KeyEvent::new(code, KeyModifiers::ALT | KeyModifiers::SHIFT))
+}
+
+fn alt_mouse_event(kind: MouseEventKind, col: u16, row: u16) -> Event {
+    Event::Mouse(MouseEvent {
+        kind,
+        column: col,
+        row,
+        modifiers: KeyModifiers::ALT,
+    })
+}
+
+#[test]
+fn alt_shift_arrows_select_a_block_and_copy_its_columns() {
+    let (mut app, _tmp) = app_with_content("abcdef\nghijkl\nmn");
+    app.buf.textarea.move_cursor(CursorMove::Jump(0, 1));
+    for code in [KeyCode::Down, KeyCode::Down, KeyCode::Right, KeyCode::Right, KeyCode::Right] {
+        app.handle_event(alt_shift_key(code));
+    }
+    assert_eq!(
+        app.rect_selection,
+        Some(rect_select::RectSelection { top: 0, left: 1, bottom: 2, right: 4 })
+    );
+
+    app.handle_event(ctrl_key('c'));
+    assert_eq!(app.kill_ring.front().map(String::as_str), Some("bcd\nhij\nn"));
+    assert!(app.rect_selection.is_some(), "copying keeps the block");
+}
+
+#[test]
+fn typing_replaces_the_block_on_every_line() {
+    let (mut app, _tmp) = app_with_content("| a | b |\n| c | d |\n| e | f |");
+    app.buf.textarea.move_cursor(CursorMove::Jump(0, 2));
+    app.handle_event(alt_shift_key(KeyCode::Down));
+    app.handle_event(alt_shift_key(KeyCode::Down));
+    app.handle_event(alt_shift_key(KeyCode::Right));
+    type_str(&mut app, "xy");
+    assert_eq!(app.buf.textarea.lines(), ["| xy | b |", "| xy | d |", "| xy | f |"]);
+    assert_eq!(
+        app.rect_selection,
+        Some(rect_select::RectSelection { top: 0, left: 4, bottom: 2, right: 4 }),
+        "a column cursor after the typed text"
+    );
+
+    app.handle_event(key_event(KeyCode::Backspace));
+    assert_eq!(app.buf.textarea.lines(), ["| x | b |", "| x | d |", "| x | f |"]);
+    app.handle_event(key_event(KeyCode::Delete));
+    assert_eq!(app.buf.textarea.lines(), ["| x| b |", "| x| d |", "| x| f |"]);
+    assert!(app.buf.modified);
+}
+
+#[test]
+fn column_cursor_pads_short_lines() {
+    let (mut app, _tmp) = app_with_content("abcd\na\nabcd");
+    app.buf.textarea.move_cursor(CursorMove::Jump(0, 3));
+    app.handle_event(alt_shift_key(KeyCode::Down));
+    app.handle_event(alt_shift_key(KeyCode::Down));
+    type_str(&mut app, "|");
+    assert_eq!(app.buf.textarea.lines(), ["abc|d", "a  |", "abc|d"]);
+}
+
+#[test]
+fn cut_removes_the_block() {
+    let (mut app, _tmp) = app_with_content("abcdef\nghijkl");
+    app.buf.textarea.move_cursor(CursorMove::Jump(0, 1));
+    app.handle_event(alt_shift_key(KeyCode::Down));
+    app.handle_event(alt_shift_key(KeyCode::Right));
+    app.handle_event(alt_shift_key(KeyCode::Right));
+    app.handle_event(ctrl_key('x'));
+    assert_eq!(app.buf.textarea.lines(), ["adef", "gjkl"]);
+    assert_eq!(app.kill_ring.front().map(String::as_str), Some("bc\nhi"));
+}
+
+#[test]
+fn other_keys_drop_the_block() {
+    let (mut app, _tmp) = app_with_content("abc\ndef");
+    app.handle_event(alt_shift_key(KeyCode::Right));
+    app.handle_event(key_event(KeyCode::Esc));
+    assert!(app.rect_selection.is_none());
+
+    app.handle_event(alt_shift_key(KeyCode::Down));
+    app.handle_event(key_event(KeyCode::Down));
+    assert!(app.rect_selection.is_none());
+    type_str(&mut app, "x");
+    assert_eq!(app.buf.textarea.lines().concat().matches('x').count(), 1, "typed once");
+}
+
+#[test]
+fn alt_drag_selects_a_block() {
+    let (mut app, _tmp) = app_with_content("hello world\nsecond line\nthird");
+    setup_viewport(&mut app, 80, 20);
+    let gutter = 3;
+    app.handle_event(alt_mouse_event(MouseEventKind::Down(MouseButton::Left), gutter + 2, 1));
+    app.handle_event(alt_mouse_event(MouseEventKind::Drag(MouseButton::Left), gutter + 8, 3));
+    app.handle_event(alt_mouse_event(MouseEventKind::Up(MouseButton::Left), gutter + 8, 3));
+    assert_eq!(
+        app.rect_selection,
+        Some(rect_select::RectSelection { top: 0, left: 2, bottom: 2, right: 8 })
+    );
+    assert!(app.buf.textarea.selection_range().is_none());
+}
+
+// ─── Raw Math Toggle Tests ────────────────────────────────────────
+
+#[test]
+fn m_in_preview_toggles_raw_math() {
+    let (mut app, _tmp) = app_with_content("$x^2$");
+    app.mode = Mode::Preview;
+    app.handle_event(char_event('m'));
+    assert!(app.preview.show_raw_math);
+    assert_eq!(app.status_message, "Math shown as LaTeX source");
+    app.handle_event(char_event('m'));
+    assert!(!app.preview.show_raw_math);
+}
+
+// ─── Type-Over Closer Tests ───────────────────────────────────────
+
+#[test]
+fn typing_a_closer_steps_over_the_auto_closed_one() {
+    for (open, close) in [('(', ')'), ('[', ']'), ('{', '}'), ('"', '"'), ('\'', '\''), ('`', '`')] {
+        let (mut app, _tmp) = app_with_content("");
+        type_str(&mut app, &format!("a {}x{}", open, close));
+        assert_eq!(app.buf.textarea.lines()[0], format!("a {}x{}", open, close), "one {}", close);
+        assert_eq!(app.buf.textarea.cursor(), (0, 5), "past the {}", close);
+    }
+}
+
+#[test]
+fn closer_is_typed_when_the_next_char_differs() {
+    let (mut app, _tmp) = app_with_content("(x]");
+    app.buf.textarea.move_cursor(CursorMove::Jump(0, 2));
+    type_str(&mut app, ")");
+    assert_eq!(app.buf.textarea.lines()[0], "(x)]");
+}
+
+#[test]
+fn type_over_can_be_turned_off() {
+    let (mut app, _tmp) = app_with_content("");
+    app.buf.config.type_over_closers = false;
+    type_str(&mut app, "(x)");
+    assert_eq!(app.buf.textarea.lines()[0], "(x))");
+}
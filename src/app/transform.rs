@@ -0,0 +1,199 @@
+//! Selection transforms: toggling code spans and fenced code blocks,
+//! quoting/unquoting lines, and checking off task list items, plus the
+//! whole-line replacement they share and the undo that keeps it one step.
+//!
+//! The text rewriting itself lives in `markdown::transform`; this module
+//! maps the editor selection onto it and writes the result back.
+
+use super::*;
+use crate::markdown::transform;
+
+impl<'a> App<'a> {
+    // ─── Code toggle (Alt+C) ─────────────────────────────────────────────
+
+    /// Toggles code formatting on the selection. A single-line selection is
+    /// wrapped in (or unwrapped from) an inline code span. A multi-line
+    /// selection is unwrapped if it is already a fenced block, otherwise the
+    /// user is prompted for a language and it is wrapped in a fence.
+    pub(super) fn toggle_code(&mut self) {
+        let Some(((start_row, _), (end_row, _))) = self.buf.textarea.selection_range() else {
+            self.set_status("Select text to format as code");
+            return;
+        };
+
+        if start_row == end_row {
+            let Some(text) = self.get_selected_text() else {
+                return;
+            };
+            let replacement = transform::unwrap_code_span(&text)
+                .unwrap_or_else(|| transform::wrap_code_span(&text));
+            self.buf.textarea.insert_str(replacement);
+            self.update_modified();
+            return;
+        }
+
+        let (start_row, end_row) = self.selected_line_range();
+        let block = self.buf.textarea.lines()[start_row..=end_row].join("\n");
+        match transform::unwrap_code_block(&block) {
+            Some(inner) => self.replace_lines(start_row, end_row, &inner),
+            None => self.start_prompt(Prompt::CodeFenceLanguage { start_row, end_row }),
+        }
+    }
+
+    /// Wraps lines `start_row..=end_row` in a fenced code block tagged `lang`.
+    pub(super) fn wrap_lines_in_code_block(&mut self, start_row: usize, end_row: usize, lang: &str) {
+        let lines = self.buf.textarea.lines();
+        if end_row >= lines.len() || start_row > end_row {
+            return;
+        }
+        let block = lines[start_row..=end_row].join("\n");
+        self.replace_lines(start_row, end_row, &transform::wrap_code_block(&block, lang));
+    }
+
+    // ─── Blockquote (Alt+Q / Alt+Shift+Q) ───────────────────────────────
+
+    /// Adds (`quote`) or removes one level of `> ` on the selected lines (or the
+    /// cursor line). The result stays selected so repeated presses nest/unnest.
+    pub(super) fn toggle_quote_level(&mut self, quote: bool) {
+        let (start_row, end_row) = self.selected_line_range();
+        let lines = &self.buf.textarea.lines()[start_row..=end_row];
+        let transformed = if quote {
+            transform::quote_lines(lines)
+        } else {
+            transform::unquote_lines(lines)
+        };
+        if transformed == lines {
+            return;
+        }
+        self.replace_lines(start_row, end_row, &transformed.join("\n"));
+        let end_col = self.buf.textarea.lines()[end_row].chars().count();
+        self.set_selection((start_row, 0), (end_row, end_col));
+    }
+
+    // ─── Task toggle (Ctrl+Space) ───────────────────────────────────────
+
+    /// Flips the `[ ]` / `[x]` checkbox of the task list item on the cursor
+    /// line, wherever the cursor is on it. Other lines are left alone.
+    pub(super) fn toggle_task_at_cursor(&mut self) {
+        let (row, col) = self.buf.textarea.cursor();
+        let Some(toggled) = transform::toggle_task(&self.buf.textarea.lines()[row]) else {
+            return;
+        };
+        self.replace_lines(row, row, &toggled);
+        self.buf.textarea.move_cursor(CursorMove::Jump(row as u16, col as u16));
+    }
+
+    /// Replaces whole lines `start_row..=end_row` with `text`, as one undo
+    /// step.
+    pub(super) fn replace_lines(&mut self, start_row: usize, end_row: usize, text: &str) {
+        let end_col = self.buf.textarea.lines()[end_row].chars().count();
+        let before = self.buf.textarea.lines()[start_row..=end_row].to_vec();
+        self.set_selection((start_row, 0), (end_row, end_col));
+        // The textarea records deleting the selection and inserting the
+        // text as two edits, unless one of them is empty
+        let paired = (start_row, 0) != (end_row, end_col) && !text.is_empty();
+        self.buf.textarea.insert_str(text);
+        self.buf.code_fence_dirty = true;
+        self.update_modified();
+        if paired {
+            if self.buf.replaces.len() == MAX_REPLACES {
+                self.buf.replaces.remove(0);
+            }
+            self.buf.replaces.push(LineReplace {
+                start_row,
+                before,
+                after: text.split('\n').map(String::from).collect(),
+                line_count: self.buf.textarea.lines().len(),
+                above: 0,
+            });
+        }
+    }
+
+    // ─── Undo (Ctrl+Z / Ctrl+Y) ──────────────────────────────────────────
+
+    /// Undoes the last edit, or both of a `replace_lines`' edits when that
+    /// replace is the next undo step.
+    pub(super) fn undo(&mut self) {
+        let lines = self.buf.textarea.lines();
+        let paired = self.buf.replaces.last().is_some_and(|r| r.above == 0 && r.shown_after(lines));
+        if !self.buf.textarea.undo() {
+            return;
+        }
+        if paired {
+            self.buf.textarea.undo();
+            if let Some(replace) = self.buf.replaces.pop() {
+                self.buf.undone_replaces.push(replace);
+            }
+        } else {
+            if let Some(replace) = self.buf.replaces.last_mut() {
+                replace.above = replace.above.saturating_sub(1);
+            }
+            if let Some(replace) = self.buf.undone_replaces.last_mut() {
+                replace.above += 1;
+            }
+        }
+        self.refresh_modified();
+    }
+
+    /// Redoes the last undone edit, or both of a `replace_lines`' edits when
+    /// that replace is the next redo step.
+    pub(super) fn redo(&mut self) {
+        let lines = self.buf.textarea.lines();
+        let paired = self.buf.undone_replaces.last().is_some_and(|r| r.above == 0 && r.shown_before(lines));
+        if !self.buf.textarea.redo() {
+            return;
+        }
+        if paired {
+            self.buf.textarea.redo();
+            if let Some(replace) = self.buf.undone_replaces.pop() {
+                self.buf.replaces.push(replace);
+            }
+        } else {
+            if let Some(replace) = self.buf.undone_replaces.last_mut() {
+                replace.above = replace.above.saturating_sub(1);
+            }
+            if let Some(replace) = self.buf.replaces.last_mut() {
+                replace.above += 1;
+            }
+        }
+        self.refresh_modified();
+    }
+}
+
+/// Replaces remembered for undo; tui-textarea keeps 50 edits, so older
+/// ones can't be undone anyway.
+const MAX_REPLACES: usize = 50;
+
+/// A `replace_lines` call, which tui-textarea records as two edits (deleting
+/// the rows, inserting the new ones) with no way to group them. The App
+/// counts the undo steps between it and the current state instead, and
+/// undo or redo takes both edits only when it is the very next step.
+pub(super) struct LineReplace {
+    start_row: usize,
+    /// The rewritten rows, before and after.
+    before: Vec<String>,
+    after: Vec<String>,
+    /// Buffer lines right after the replace.
+    line_count: usize,
+    /// Undo (or, once undone, redo) steps between this replace and the
+    /// current state.
+    pub(super) above: usize,
+}
+
+impl LineReplace {
+    /// True if `lines` has the rows this replace wrote, a check on top of
+    /// `above` in case an edit was counted as one step but made several.
+    fn shown_after(&self, lines: &[String]) -> bool {
+        lines.len() == self.line_count && self.rows_are(lines, &self.after)
+    }
+
+    /// True if `lines` is the buffer right before this replace.
+    fn shown_before(&self, lines: &[String]) -> bool {
+        lines.len() + self.after.len() == self.line_count + self.before.len()
+            && self.rows_are(lines, &self.before)
+    }
+
+    fn rows_are(&self, lines: &[String], rows: &[String]) -> bool {
+        lines.get(self.start_row..self.start_row + rows.len()) == Some(rows)
+    }
+}
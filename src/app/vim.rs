@@ -0,0 +1,139 @@
+//! Vim-style modal editing (`editing = "vim"`): a Normal mode layered on the
+//! editor. Esc enters it and `i` / `a` return to Insert mode, where keys
+//! type as usual.
+//!
+//! Normal mode knows a small core: `h` `j` `k` `l` to move, `x` to delete
+//! the character under the cursor, `dd` to cut the line, `/` to find, and
+//! `:w`, `:q`, `:q!`, `:wq` / `:x`. Ctrl and Alt shortcuts and the arrow
+//! keys keep working in both modes.
+
+use super::*;
+
+impl<'a> App<'a> {
+    // ─── Vim layer ───────────────────────────────────────────────────────
+
+    /// Runs `key` through the vim layer ahead of the editor. Returns false
+    /// if the editor should handle it as usual.
+    pub(super) fn handle_vim_key(&mut self, key: KeyEvent) -> bool {
+        if !self.vim_normal {
+            if key.code == KeyCode::Esc && key.modifiers.is_empty() {
+                self.enter_vim_normal();
+                return true;
+            }
+            return false;
+        }
+
+        if let Some(first) = self.vim_pending.take() {
+            match (first, key.code) {
+                ('d', KeyCode::Char('d')) if key.modifiers.is_empty() => self.vim_delete_line(),
+                _ => {}
+            }
+            return true;
+        }
+
+        if !key.modifiers.difference(KeyModifiers::SHIFT).is_empty() {
+            return false;
+        }
+        match key.code {
+            KeyCode::Char('i') => self.vim_normal = false,
+            KeyCode::Char('a') => {
+                let (row, col) = self.buf.textarea.cursor();
+                if col < self.buf.textarea.lines()[row].chars().count() {
+                    self.buf.textarea.move_cursor(CursorMove::Forward);
+                }
+                self.vim_normal = false;
+            }
+            KeyCode::Char('h') | KeyCode::Backspace => self.buf.textarea.move_cursor(CursorMove::Back),
+            KeyCode::Char('l') => self.buf.textarea.move_cursor(CursorMove::Forward),
+            KeyCode::Char('j') | KeyCode::Enter => {
+                let before = self.buf.textarea.cursor();
+                self.buf.textarea.move_cursor(CursorMove::Down);
+                self.skip_folded(true);
+                self.apply_goal_column(before);
+            }
+            KeyCode::Char('k') => {
+                let before = self.buf.textarea.cursor();
+                self.buf.textarea.move_cursor(CursorMove::Up);
+                self.skip_folded(false);
+                self.apply_goal_column(before);
+            }
+            KeyCode::Char('x') | KeyCode::Delete => self.vim_delete_char(),
+            KeyCode::Char('d') => self.vim_pending = Some('d'),
+            KeyCode::Char('/') => self.start_search(),
+            KeyCode::Char(':') => self.start_prompt(Prompt::VimCommand),
+            // Other letters would type; in Normal mode they do nothing
+            KeyCode::Char(_) | KeyCode::Esc => {}
+            _ => return false,
+        }
+        true
+    }
+
+    /// Leaves Insert mode, stepping back onto the last typed character as
+    /// vim does.
+    fn enter_vim_normal(&mut self) {
+        self.vim_normal = true;
+        self.vim_pending = None;
+        self.buf.textarea.cancel_selection();
+        if self.buf.textarea.cursor().1 > 0 {
+            self.buf.textarea.move_cursor(CursorMove::Back);
+        }
+    }
+
+    /// `x`: deletes the character under the cursor.
+    fn vim_delete_char(&mut self) {
+        if self.is_read_only() {
+            self.set_status("Read-only");
+            return;
+        }
+        let (row, col) = self.buf.textarea.cursor();
+        if col < self.buf.textarea.lines()[row].chars().count() {
+            self.buf.textarea.delete_next_char();
+            self.update_modified();
+        }
+    }
+
+    /// `dd`: cuts the cursor line (into the yank buffer) as one undo step.
+    fn vim_delete_line(&mut self) {
+        if self.is_read_only() {
+            self.set_status("Read-only");
+            return;
+        }
+        let row = self.buf.textarea.cursor().0;
+        let lines = self.buf.textarea.lines();
+        let last = lines.len() - 1;
+        let line_end = |r: usize| lines[r].chars().count() as u16;
+        // Take the line with the newline after it, or before it on the
+        // last line
+        let (from, to) = if row < last {
+            ((row as u16, 0), (row as u16 + 1, 0))
+        } else if row > 0 {
+            ((row as u16 - 1, line_end(row - 1)), (row as u16, line_end(row)))
+        } else {
+            ((0, 0), (0, line_end(0)))
+        };
+        self.buf.textarea.cancel_selection();
+        self.buf.textarea.move_cursor(CursorMove::Jump(from.0, from.1));
+        self.buf.textarea.start_selection();
+        self.buf.textarea.move_cursor(CursorMove::Jump(to.0, to.1));
+        self.buf.textarea.cut();
+        kill_ring::push_kill(&mut self.kill_ring, &self.buf.textarea.yank_text());
+        self.buf.textarea.move_cursor(CursorMove::Head);
+        self.update_modified();
+    }
+
+    /// Runs a `:` command line.
+    pub(super) fn run_vim_command(&mut self, command: &str) {
+        match command {
+            "w" => self.save(),
+            "q" if self.buf.modified => {
+                self.set_status("No write since last change (add ! to override)");
+            }
+            // Other open files with unsaved changes ask first, like Ctrl+Q
+            "q" if self.unsaved_parked_count() > 0 => self.start_prompt(Prompt::QuitUnsaved),
+            "q" | "q!" => self.should_quit = true,
+            "wq" | "x" => self.quit_if_saved(true),
+            "" => {}
+            _ => self.set_status(&format!("Not an editor command: {}", command)),
+        }
+    }
+}
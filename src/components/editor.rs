@@ -1,4 +1,8 @@
-use ratatui::style::{Modifier, Style};
+use ratatui::{
+    layout::Rect,
+    style::{Modifier, Style},
+    Frame,
+};
 use tui_textarea::TextArea;
 
 use crate::theme;
@@ -19,7 +23,10 @@ pub fn configure_textarea(textarea: &mut TextArea) {
     );
 
     // Selection style
-    textarea.set_selection_style(Style::default().bg(theme::SELECTION));
+    textarea.set_selection_style(Style::default().bg(theme::active().selection));
+
+    // Find (Ctrl+F) match highlighting
+    textarea.set_search_style(theme::search_match_style());
 
     // Tab = 2 spaces
     textarea.set_tab_length(2);
@@ -27,3 +34,70 @@ pub fn configure_textarea(textarea: &mut TextArea) {
     // Hard tab to spaces
     textarea.set_hard_tab_indent(false);
 }
+
+/// Computes the scrollbar thumb as (start row, length) within a track of
+/// `track` rows, for a buffer of `total_lines` scrolled to `scroll_top`.
+/// The viewport is assumed to be as tall as the track.
+pub fn scrollbar_thumb(track: u16, total_lines: usize, scroll_top: usize) -> (u16, u16) {
+    let track_len = track as usize;
+    if track_len == 0 || total_lines <= track_len {
+        return (0, track);
+    }
+    let len = (track_len * track_len / total_lines).clamp(1, track_len);
+    let max_scroll = total_lines - track_len;
+    let free = track_len - len;
+    let start = (scroll_top.min(max_scroll) * free + max_scroll / 2) / max_scroll;
+    (start as u16, len as u16)
+}
+
+/// Inverse of `scrollbar_thumb`: the scroll offset that centers the thumb on
+/// `row` (relative to the top of the track), clamped to the valid range.
+pub fn scroll_for_scrollbar_row(track: u16, total_lines: usize, row: u16) -> usize {
+    let track_len = track as usize;
+    if track_len == 0 || total_lines <= track_len {
+        return 0;
+    }
+    let (_, len) = scrollbar_thumb(track, total_lines, 0);
+    let free = track_len - len as usize;
+    if free == 0 {
+        return 0;
+    }
+    let start = (row as usize).saturating_sub(len as usize / 2).min(free);
+    let max_scroll = total_lines - track_len;
+    (start * max_scroll + free / 2) / free
+}
+
+/// True when the editor shows a scrollbar: the buffer is taller than the
+/// viewport `area`. The scrollbar then takes the rightmost column.
+pub fn scrollbar_shown(area: Rect, total_lines: usize) -> bool {
+    area.width > 0 && total_lines > area.height as usize
+}
+
+/// Splits the editor `area` into the text area and, when a scrollbar is
+/// shown, the one-column track reserved for it on the right.
+pub fn split_scrollbar(area: Rect, total_lines: usize) -> (Rect, Option<Rect>) {
+    if !scrollbar_shown(area, total_lines) {
+        return (area, None);
+    }
+    let text = Rect { width: area.width - 1, ..area };
+    let track = Rect { x: area.x + area.width - 1, width: 1, ..area };
+    (text, Some(track))
+}
+
+/// Draws the editor scrollbar in the one-column `track`.
+pub fn render_scrollbar(frame: &mut Frame, track: Rect, total_lines: usize, scroll_top: usize) {
+    let (thumb_start, thumb_len) = scrollbar_thumb(track.height, total_lines, scroll_top);
+    let buf = frame.buffer_mut();
+    for row in 0..track.height {
+        let is_thumb = row >= thumb_start && row < thumb_start + thumb_len;
+        if let Some(cell) = buf.cell_mut((track.x, track.y + row)) {
+            if is_thumb {
+                cell.set_char('\u{2588}'); // full block
+                cell.set_fg(theme::active().line_number);
+            } else {
+                cell.set_char('\u{2502}'); // light vertical
+                cell.set_fg(theme::active().border);
+            }
+        }
+    }
+}
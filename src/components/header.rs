@@ -6,26 +6,24 @@ use ratatui::{
     Frame,
 };
 
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+
 use crate::app::Mode;
 use crate::theme;
 
+#[allow(clippy::too_many_arguments)]
 pub fn render(
     frame: &mut Frame,
     area: Rect,
     filename: &str,
     modified: bool,
+    read_only: bool,
+    buffer_position: Option<(usize, usize)>,
     mode: &Mode,
     renaming: bool,
     rename_buf: &str,
     rename_cursor: usize,
 ) {
-    // Left side: filename (or rename input) + modified indicator
-    let left_spans = if renaming {
-        render_rename_input(rename_buf, rename_cursor, modified)
-    } else {
-        render_filename(filename, modified)
-    };
-
     // Right side: mode tabs
     let modes = [
         ("EDITOR", Mode::Editor),
@@ -34,19 +32,20 @@ pub fn render(
 
     let mut right_spans: Vec<Span> = Vec::new();
     for (label, tab_mode) in &modes {
-        let is_active = std::mem::discriminant(mode) == std::mem::discriminant(tab_mode);
+        // The split view shows both
+        let is_active = mode == tab_mode || *mode == Mode::Split;
         if is_active {
             right_spans.push(Span::styled(
                 format!(" {} ", label),
                 Style::default()
-                    .fg(theme::WHITE)
-                    .bg(theme::ACTIVE_TAB)
+                    .fg(theme::active().white)
+                    .bg(theme::active().active_tab)
                     .add_modifier(Modifier::BOLD),
             ));
         } else {
             right_spans.push(Span::styled(
                 format!(" {} ", label),
-                Style::default().fg(theme::INACTIVE_TAB).bg(theme::BAR_BG),
+                Style::default().fg(theme::active().inactive_tab).bg(theme::active().bar_bg),
             ));
         }
     }
@@ -57,6 +56,13 @@ pub fn render(
     ])
     .split(area);
 
+    // Left side: filename (or rename input) + modified indicator
+    let left_spans = if renaming {
+        render_rename_input(rename_buf, rename_cursor, modified)
+    } else {
+        render_filename(filename, modified, read_only, buffer_position, chunks[0].width as usize)
+    };
+
     // Fill background
     let bg = Paragraph::new("").style(theme::header_style());
     frame.render_widget(bg, area);
@@ -68,15 +74,80 @@ pub fn render(
     frame.render_widget(right, chunks[1]);
 }
 
-fn render_filename<'a>(filename: &str, modified: bool) -> Vec<Span<'a>> {
+/// Shortens `name` to at most `max` columns by replacing its middle with an
+/// ellipsis, keeping both the start and the extension readable.
+fn truncate_middle(name: &str, max: usize) -> String {
+    if name.width() <= max {
+        return name.to_string();
+    }
+    if max == 0 {
+        return String::new();
+    }
+    let keep = max - 1;
+    let mut head = String::new();
+    let mut head_width = 0;
+    for c in name.chars() {
+        let w = c.width().unwrap_or(0);
+        if head_width + w > keep - keep / 2 {
+            break;
+        }
+        head_width += w;
+        head.push(c);
+    }
+    // The tail takes whatever the head left, so a wide char that didn't fit
+    // the head's half doesn't waste a column
+    let mut tail = Vec::new();
+    let mut tail_width = 0;
+    for c in name.chars().rev() {
+        let w = c.width().unwrap_or(0);
+        if tail_width + w > keep - head_width {
+            break;
+        }
+        tail_width += w;
+        tail.push(c);
+    }
+    let end: String = tail.into_iter().rev().collect();
+    format!("{}\u{2026}{}", head, end)
+}
+
+/// Filename spans fitted into `width` columns: the name is middle-truncated
+/// so the buffer indicator ("2/3"), the read-only and modified indicators,
+/// and a gap before the tabs always fit.
+fn render_filename<'a>(
+    filename: &str,
+    modified: bool,
+    read_only: bool,
+    buffer_position: Option<(usize, usize)>,
+    width: usize,
+) -> Vec<Span<'a>> {
+    let position = buffer_position.map(|(index, count)| format!(" {}/{}", index, count));
+    // Two columns of indent, one of gap before the tabs, five for " [RO]",
+    // two for " •"
+    let reserved = 3
+        + position.as_ref().map_or(0, |p| p.chars().count())
+        + if read_only { 5 } else { 0 }
+        + if modified { 2 } else { 0 };
+    let name = truncate_middle(filename, width.saturating_sub(reserved));
     let mut spans = vec![Span::styled(
-        format!("  {}", filename),
+        format!("  {}", name),
         theme::header_style(),
     )];
+    if let Some(position) = position {
+        spans.push(Span::styled(
+            position,
+            Style::default().fg(theme::active().inactive_tab).bg(theme::active().bar_bg),
+        ));
+    }
+    if read_only {
+        spans.push(Span::styled(
+            " [RO]",
+            Style::default().fg(theme::active().warning).bg(theme::active().bar_bg),
+        ));
+    }
     if modified {
         spans.push(Span::styled(
             " \u{2022}",
-            Style::default().fg(theme::WARNING).bg(theme::BAR_BG),
+            Style::default().fg(theme::active().warning).bg(theme::active().bar_bg),
         ));
     }
     spans
@@ -90,7 +161,7 @@ fn render_rename_input<'a>(rename_buf: &str, rename_cursor: usize, modified: boo
     if !before.is_empty() {
         spans.push(Span::styled(
             before.to_string(),
-            Style::default().fg(theme::WHITE).bg(theme::BAR_BG),
+            Style::default().fg(theme::active().white).bg(theme::active().bar_bg),
         ));
     }
 
@@ -111,7 +182,7 @@ fn render_rename_input<'a>(rename_buf: &str, rename_cursor: usize, modified: boo
         if !after.is_empty() {
             spans.push(Span::styled(
                 after.to_string(),
-                Style::default().fg(theme::WHITE).bg(theme::BAR_BG),
+                Style::default().fg(theme::active().white).bg(theme::active().bar_bg),
             ));
         }
     }
@@ -119,7 +190,7 @@ fn render_rename_input<'a>(rename_buf: &str, rename_cursor: usize, modified: boo
     if modified {
         spans.push(Span::styled(
             " \u{2022}",
-            Style::default().fg(theme::WARNING).bg(theme::BAR_BG),
+            Style::default().fg(theme::active().warning).bg(theme::active().bar_bg),
         ));
     }
 
@@ -0,0 +1,591 @@
+//! Images shared by the preview and the editor's inline thumbnails:
+//! resolving image URLs to files, decoding them off the main thread, and
+//! drawing them with the terminal's graphics protocol or as half-blocks.
+
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::path::{Path, PathBuf};
+use std::sync::{mpsc, OnceLock};
+use std::time::SystemTime;
+
+use image::DynamicImage;
+use ratatui::{buffer::Buffer, layout::Rect, style::Color, Frame};
+use ratatui_image::picker::Picker;
+use ratatui_image::protocol::StatefulProtocol;
+use ratatui_image::StatefulImage;
+use regex::Regex;
+
+/// A decoded image sent from a background thread to the main thread.
+pub struct DecodedImage {
+    pub path: PathBuf,
+    pub image: Option<DynamicImage>,
+    /// Relative URL for pre-populating file_cache (e.g. ".marko/images/screenshot-XXX.png").
+    pub url_hint: Option<String>,
+}
+
+/// Decoded (and resized) images kept when `image_cache_size` isn't set.
+pub const DEFAULT_CACHE_SIZE: usize = 32;
+
+/// Least-recently-used order of a cache's keys, to cap it at a size.
+#[derive(Debug, Default)]
+pub(crate) struct LruOrder {
+    /// Oldest first.
+    keys: VecDeque<PathBuf>,
+}
+
+impl LruOrder {
+    /// Marks `path` as just used.
+    pub(crate) fn touch(&mut self, path: &Path) {
+        if let Some(i) = self.keys.iter().position(|key| key == path) {
+            self.keys.remove(i);
+        }
+        self.keys.push_back(path.to_path_buf());
+    }
+
+    /// Forgets `path`.
+    pub(crate) fn remove(&mut self, path: &Path) {
+        self.keys.retain(|key| key != path);
+    }
+
+    /// Drops the least recently used keys beyond `capacity` and returns
+    /// them, for the cache to evict.
+    pub(crate) fn evict(&mut self, capacity: usize) -> Vec<PathBuf> {
+        let excess = self.keys.len().saturating_sub(capacity);
+        self.keys.drain(..excess).collect()
+    }
+}
+
+/// Resolved paths and decoded images, plus the graphics protocol picker.
+/// Decoding happens on background threads; results are picked up by
+/// `poll_decoded()` from `tick()`.
+pub struct ImageCache {
+    /// Cache: image URL → local file path (None = failed to fetch/not fetchable).
+    file_cache: HashMap<String, Option<PathBuf>>,
+    /// Cache: file path → decoded DynamicImage (None = failed to decode).
+    decode_cache: HashMap<PathBuf, Option<DynamicImage>>,
+    /// Use order of `decode_cache`, which keeps at most `capacity` images.
+    decode_order: LruOrder,
+    capacity: usize,
+    /// Sender for background decode threads to deliver decoded images.
+    image_tx: mpsc::Sender<DecodedImage>,
+    /// Receiver drained in poll_decoded() (~10fps from tick()).
+    image_rx: mpsc::Receiver<DecodedImage>,
+    /// Paths currently being decoded in background threads (prevents duplicate spawns).
+    decoding_in_flight: HashSet<PathBuf>,
+    /// Modification time of each file when its decode started, to notice
+    /// it changing on disk.
+    decoded_mtimes: HashMap<PathBuf, Option<SystemTime>>,
+    /// Graphics protocol picker (Sixel/Kitty/iTerm2). None = no protocol detected.
+    picker: Option<Picker>,
+}
+
+impl Default for ImageCache {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ImageCache {
+    pub fn new() -> Self {
+        let (image_tx, image_rx) = mpsc::channel();
+        Self {
+            file_cache: HashMap::new(),
+            decode_cache: HashMap::new(),
+            decode_order: LruOrder::default(),
+            capacity: DEFAULT_CACHE_SIZE,
+            image_tx,
+            image_rx,
+            decoding_in_flight: HashSet::new(),
+            decoded_mtimes: HashMap::new(),
+            picker: Picker::from_query_stdio().ok(),
+        }
+    }
+
+    /// True if the terminal supports a graphics protocol.
+    pub fn has_picker(&self) -> bool {
+        self.picker.is_some()
+    }
+
+    /// Keeps at most `capacity` decoded images (at least one), evicting the
+    /// least recently used.
+    pub fn set_capacity(&mut self, capacity: usize) {
+        self.capacity = capacity.max(1);
+        self.evict();
+    }
+
+    fn evict(&mut self) {
+        for path in self.decode_order.evict(self.capacity) {
+            self.decode_cache.remove(&path);
+            self.decoded_mtimes.remove(&path);
+        }
+    }
+
+    /// Returns a clone of the sender for background threads to deliver decoded images.
+    pub fn sender(&self) -> mpsc::Sender<DecodedImage> {
+        self.image_tx.clone()
+    }
+
+    /// Drains all pending decoded images from background threads and
+    /// returns their paths, so callers can drop anything derived from an
+    /// earlier decode.
+    pub fn poll_decoded(&mut self) -> Vec<PathBuf> {
+        let mut paths = Vec::new();
+        while let Ok(msg) = self.image_rx.try_recv() {
+            self.decoding_in_flight.remove(&msg.path);
+            self.decode_cache.insert(msg.path.clone(), msg.image);
+            self.decode_order.touch(&msg.path);
+            // Pre-populate file_cache so resolve_image_path() isn't needed
+            if let Some(url) = msg.url_hint {
+                self.file_cache.insert(url, Some(msg.path.clone()));
+            }
+            paths.push(msg.path);
+        }
+        self.evict();
+        paths
+    }
+
+    /// Resolves an image URL to a local file, downloading remote images
+    /// unless `fetch` is false (then only an earlier download is found).
+    pub fn resolve(&mut self, url: &str, base_dir: &Path, fetch: bool) -> Option<PathBuf> {
+        if let Some(cached) = self.file_cache.get(url) {
+            return cached.clone();
+        }
+        if !fetch && is_remote(url) {
+            return None;
+        }
+        let resolved = resolve_image_path(url, base_dir);
+        // Only cache successful resolutions — None may become Some
+        // once a background thread finishes writing the file.
+        if resolved.is_some() {
+            self.file_cache.insert(url.to_string(), resolved.clone());
+        }
+        resolved
+    }
+
+    /// True once `path` has been decoded (successfully or not). Until then
+    /// a background decode is started, once, and false is returned. A file
+    /// changed on disk since is decoded again, showing the old image until
+    /// the new one arrives from `poll_decoded`.
+    pub fn request(&mut self, path: &Path) -> bool {
+        if self.decode_cache.contains_key(path) {
+            self.decode_order.touch(path);
+            if self.decoded_mtimes.get(path).is_some_and(|&mtime| mtime != modified(path)) {
+                self.start_decode(path);
+            }
+            return true;
+        }
+        self.start_decode(path);
+        false
+    }
+
+    /// Decodes `path` in a background thread, unless that's already underway.
+    fn start_decode(&mut self, path: &Path) {
+        if !self.decoding_in_flight.insert(path.to_path_buf()) {
+            return;
+        }
+        self.decoded_mtimes.insert(path.to_path_buf(), modified(path));
+        let tx = self.image_tx.clone();
+        let decode_path = path.to_path_buf();
+        std::thread::spawn(move || {
+            let img = load_image(&decode_path);
+            if let Some(ref i) = img {
+                save_thumbnail(i, &decode_path);
+            }
+            let _ = tx.send(DecodedImage {
+                path: decode_path,
+                image: img,
+                url_hint: None,
+            });
+        });
+    }
+
+    /// The decoded image at `path`, if decoding finished and succeeded.
+    pub fn get(&self, path: &Path) -> Option<&DynamicImage> {
+        self.decode_cache.get(path)?.as_ref()
+    }
+}
+
+/// Images drawn with the graphics protocol by one view. Each keeps its
+/// protocol state between frames; an image not drawn in a frame has it
+/// dropped so the terminal clears the placement.
+#[derive(Default)]
+pub struct Placements {
+    /// Cache: file path → StatefulProtocol for graphics protocol rendering.
+    protocols: HashMap<PathBuf, Box<StatefulProtocol>>,
+    /// Paths drawn so far this frame.
+    drawn: HashSet<PathBuf>,
+}
+
+impl Placements {
+    /// Forgets the protocol state for `path` (e.g. after it was re-decoded).
+    pub fn invalidate(&mut self, path: &Path) {
+        self.protocols.remove(path);
+    }
+
+    /// Forgets every protocol state (e.g. after the view was resized).
+    pub fn clear(&mut self) {
+        self.protocols.clear();
+    }
+
+    /// Draws the image at `path` into `rect`, scaled to fit. The cells are
+    /// cleared first so nothing underneath shows through.
+    pub fn draw(&mut self, frame: &mut Frame, rect: Rect, path: &Path, images: &ImageCache) {
+        clear_cells(frame.buffer_mut(), rect);
+        if !self.protocols.contains_key(path) {
+            let (Some(picker), Some(img)) = (images.picker.as_ref(), images.get(path)) else {
+                return;
+            };
+            let protocol = picker.new_resize_protocol(img.clone());
+            self.protocols.insert(path.to_path_buf(), Box::new(protocol));
+        }
+        if let Some(protocol) = self.protocols.get_mut(path) {
+            frame.render_stateful_widget(StatefulImage::default(), rect, protocol.as_mut());
+            self.drawn.insert(path.to_path_buf());
+        }
+    }
+
+    /// Ends a frame: any image that was drawn last frame but NOT this
+    /// frame has its protocol dropped.
+    pub fn finish_frame(&mut self) {
+        let drawn = std::mem::take(&mut self.drawn);
+        self.protocols.retain(|path, _| drawn.contains(path));
+    }
+}
+
+/// Resets every cell in `rect`.
+pub(crate) fn clear_cells(buf: &mut Buffer, rect: Rect) {
+    for y in rect.y..rect.y + rect.height {
+        for x in rect.x..rect.x + rect.width {
+            if let Some(cell) = buf.cell_mut((x, y)) {
+                cell.reset();
+            }
+        }
+    }
+}
+
+/// The URL of the first image reference (`![alt](url)`) in a line of
+/// markdown source, without any title.
+pub fn image_url(line: &str) -> Option<&str> {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    let re = RE.get_or_init(|| Regex::new(r"!\[[^\]]*\]\(\s*<?([^)\s>]+)>?").unwrap());
+    re.captures(line).map(|c| c.get(1).unwrap().as_str())
+}
+
+/// True for `http://` and `https://` URLs.
+fn is_remote(url: &str) -> bool {
+    url.starts_with("http://") || url.starts_with("https://")
+}
+
+/// Composite an RGBA pixel over a background color using alpha blending.
+#[inline]
+fn blend(pixel: &image::Rgba<u8>, bg: (u8, u8, u8)) -> (u8, u8, u8) {
+    let a = pixel[3] as u16;
+    let inv_a = 255 - a;
+    (
+        ((pixel[0] as u16 * a + bg.0 as u16 * inv_a) / 255) as u8,
+        ((pixel[1] as u16 * a + bg.1 as u16 * inv_a) / 255) as u8,
+        ((pixel[2] as u16 * a + bg.2 as u16 * inv_a) / 255) as u8,
+    )
+}
+
+/// Render a pre-resized RGBA image into the buffer using half-block Unicode characters.
+/// Each cell shows two vertical pixels: upper pixel as fg color, lower as bg color.
+/// Preserves aspect ratio centering within the rect.
+/// `full_cols` is the full image area width (for centering math).
+/// `y_offset` is the number of rows clipped from the top (due to scrolling).
+pub(crate) fn render_halfblock_image(
+    buf: &mut Buffer,
+    rect: Rect,
+    rgba: &image::RgbaImage,
+    full_cols: u16,
+    y_offset: u16,
+) {
+    if rect.width == 0 || rect.height == 0 {
+        return;
+    }
+
+    let bg = (30u8, 30u8, 30u8);
+    let img_w = rgba.width();
+    let img_h = rgba.height();
+
+    // Center horizontally within the full column width
+    let x_pad = (full_cols as u32).saturating_sub(img_w) / 2;
+
+    for dy in 0..rect.height {
+        let img_row = dy + y_offset;
+        for dx in 0..rect.width {
+            let img_x = (dx as u32).wrapping_sub(x_pad);
+            let upper_y = (img_row * 2) as u32;
+            let lower_y = upper_y + 1;
+
+            let (ur, ug, ub) = if img_x < img_w && upper_y < img_h {
+                blend(rgba.get_pixel(img_x, upper_y), bg)
+            } else {
+                bg
+            };
+            let (lr, lg, lb) = if img_x < img_w && lower_y < img_h {
+                blend(rgba.get_pixel(img_x, lower_y), bg)
+            } else {
+                bg
+            };
+
+            if let Some(cell) = buf.cell_mut((rect.x + dx, rect.y + dy)) {
+                let is_bg = (ur, ug, ub) == bg && (lr, lg, lb) == bg;
+                if is_bg {
+                    cell.reset();
+                } else {
+                    cell.set_symbol("\u{2580}") // ▀
+                        .set_fg(Color::Rgb(ur, ug, ub))
+                        .set_bg(Color::Rgb(lr, lg, lb));
+                }
+            }
+        }
+    }
+}
+
+/// When the file at `path` was last modified, if that can be read.
+fn modified(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+/// Returns the path for a pre-computed thumbnail of the given image.
+/// e.g. `/path/to/screenshot-123.png` → `/path/to/screenshot-123.thumb.png`
+fn thumbnail_path(path: &Path) -> PathBuf {
+    let stem = path.file_stem().and_then(|s| s.to_str()).unwrap_or("img");
+    path.with_file_name(format!("{}.thumb.png", stem))
+}
+
+/// Saves a downscaled thumbnail alongside the original for fast reload.
+/// Skips if the image is already small enough that decoding is fast.
+pub(crate) fn save_thumbnail(img: &DynamicImage, original_path: &Path) {
+    let pixels = img.width() as u64 * img.height() as u64;
+    if pixels <= 640_000 {
+        return; // already small, thumbnail not needed
+    }
+    let thumb = thumbnail_path(original_path);
+    let max_dim = 800u32;
+    let resized = img.resize(max_dim, max_dim, image::imageops::FilterType::Triangle);
+    let _ = resized.save(&thumb);
+}
+
+/// Load an image file and return a DynamicImage. Handles PNG, JPEG, GIF, BMP.
+/// For SVG/SVGZ, uses resvg. Checks for a pre-computed thumbnail first
+/// so large images (e.g. retina screenshots) load in milliseconds on reload.
+pub(crate) fn load_image(path: &std::path::Path) -> Option<DynamicImage> {
+    #[cfg(target_os = "macos")]
+    {
+        let ext = path
+            .extension()
+            .and_then(|e| e.to_str())
+            .unwrap_or("")
+            .to_lowercase();
+        if ext == "svg" || ext == "svgz" {
+            return load_svg(path);
+        }
+    }
+
+    // Check for pre-computed thumbnail first (much faster for large images),
+    // unless the image was changed after it was made
+    let thumb = thumbnail_path(path);
+    if modified(&thumb).is_some_and(|made| modified(path).is_some_and(|changed| made >= changed)) {
+        if let Some(img) = load_image_raw(&thumb) {
+            return Some(img);
+        }
+    }
+
+    load_image_raw(path)
+}
+
+/// Low-level image decode from a file path (no thumbnail lookup).
+fn load_image_raw(path: &Path) -> Option<DynamicImage> {
+    // Guess format from file content (not extension) so mismatched files
+    // (e.g. TIFF data with .png extension from macOS clipboard) still load.
+    let reader = image::ImageReader::open(path).ok()?;
+    let reader = reader.with_guessed_format().ok()?;
+    reader.decode().ok()
+}
+
+/// Decode an image from raw bytes (PNG, TIFF, etc.) without touching disk.
+/// Used by the paste thread to avoid a redundant file→decode round-trip.
+pub(crate) fn load_image_from_bytes(bytes: &[u8]) -> Option<DynamicImage> {
+    use std::io::Cursor;
+    let reader = image::ImageReader::new(Cursor::new(bytes))
+        .with_guessed_format()
+        .ok()?;
+    reader.decode().ok()
+}
+
+/// Render SVG to a DynamicImage using resvg (pure Rust, no external tools).
+/// Renders at a higher resolution than the SVG's native size for better quality
+/// when downscaled to terminal cells.
+#[cfg(target_os = "macos")]
+fn load_svg(path: &std::path::Path) -> Option<DynamicImage> {
+    let svg_data = std::fs::read(path).ok()?;
+    let tree = resvg::usvg::Tree::from_data(&svg_data, &Default::default()).ok()?;
+    let size = tree.size();
+    if size.width() == 0.0 || size.height() == 0.0 {
+        return None;
+    }
+    // Render at a larger size for better detail when downscaled
+    let target_w = 800u32;
+    let scale = target_w as f32 / size.width();
+    let target_h = (size.height() * scale) as u32;
+    let mut pixmap = resvg::tiny_skia::Pixmap::new(target_w, target_h)?;
+    let transform = resvg::tiny_skia::Transform::from_scale(scale, scale);
+    resvg::render(&tree, transform, &mut pixmap.as_mut());
+    image::RgbaImage::from_raw(target_w, target_h, pixmap.take()).map(DynamicImage::ImageRgba8)
+}
+
+/// Resolve an image URL to a local file path.
+/// Downloads remote images via curl; returns None if unavailable.
+fn resolve_image_path(url: &str, base_dir: &Path) -> Option<PathBuf> {
+    if is_remote(url) {
+        fetch_remote_image(url)
+    } else {
+        let path = PathBuf::from(url);
+        // Try as-is (absolute path), then relative to the markdown file's directory
+        let candidate = if path.is_absolute() {
+            path
+        } else {
+            base_dir.join(path)
+        };
+        if candidate.exists() {
+            Some(candidate)
+        } else {
+            None
+        }
+    }
+}
+
+/// Fetch a remote image via curl, caching in a temp directory.
+fn fetch_remote_image(url: &str) -> Option<PathBuf> {
+    let cache_dir = std::env::temp_dir().join("marko_images");
+    std::fs::create_dir_all(&cache_dir).ok()?;
+
+    // Preserve file extension for format detection
+    let ext = url.rsplit('.').next().unwrap_or("png");
+    let ext = if ext.len() <= 4 && ext.chars().all(|c| c.is_alphanumeric()) {
+        ext
+    } else {
+        "png"
+    };
+    let key: String = url
+        .chars()
+        .filter(|c| c.is_alphanumeric())
+        .rev()
+        .take(50)
+        .collect();
+    let cache_path = cache_dir.join(format!("{}.{}", key, ext));
+
+    if cache_path.exists() && std::fs::metadata(&cache_path).ok()?.len() > 0 {
+        return Some(cache_path);
+    }
+
+    let status = std::process::Command::new("curl")
+        .args(["-s", "-L", "--max-time", "10", "-o"])
+        .arg(&cache_path)
+        .arg(url)
+        .status()
+        .ok()?;
+
+    if status.success() && cache_path.exists() && std::fs::metadata(&cache_path).ok()?.len() > 0 {
+        Some(cache_path)
+    } else {
+        let _ = std::fs::remove_file(&cache_path);
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_image_url() {
+        assert_eq!(image_url("![logo](img/logo.png)"), Some("img/logo.png"));
+        assert_eq!(image_url("See ![a b](x.png \"Title\") here"), Some("x.png"));
+        assert_eq!(image_url("![](<dir/pic.jpg>)"), Some("dir/pic.jpg"));
+        assert_eq!(image_url("![a](one.png) ![b](two.png)"), Some("one.png"));
+    }
+
+    #[test]
+    fn test_image_url_ignores_links() {
+        assert_eq!(image_url("[text](page.md)"), None);
+        assert_eq!(image_url("no images here"), None);
+        assert_eq!(image_url("![unfinished]("), None);
+    }
+
+    #[test]
+    fn test_lru_order_evicts_least_recently_used() {
+        let mut order = LruOrder::default();
+        for name in ["a", "b", "c"] {
+            order.touch(Path::new(name));
+        }
+        order.touch(Path::new("a"));
+        assert_eq!(order.evict(2), [PathBuf::from("b")]);
+        order.remove(Path::new("c"));
+        assert_eq!(order.evict(0), [PathBuf::from("a")]);
+    }
+
+    #[test]
+    fn test_decode_cache_stays_within_capacity() {
+        let mut cache = ImageCache::new();
+        cache.set_capacity(4);
+        let tx = cache.sender();
+        for i in 0..20 {
+            let path = PathBuf::from(format!("img{}.png", i));
+            let image = Some(DynamicImage::new_rgba8(1, 1));
+            tx.send(DecodedImage { path, image, url_hint: None }).unwrap();
+            // Keep the first image in use so it outlives newer ones
+            cache.poll_decoded();
+            assert!(cache.request(Path::new("img0.png")));
+            assert!(cache.decode_cache.len() <= 4);
+        }
+        assert!(cache.get(Path::new("img0.png")).is_some());
+        assert!(cache.get(Path::new("img19.png")).is_some());
+        assert!(cache.get(Path::new("img10.png")).is_none());
+
+        cache.set_capacity(1);
+        assert_eq!(cache.decode_cache.len(), 1);
+    }
+
+    /// Waits for the background decode of `path` to arrive.
+    fn wait_for_decode(cache: &mut ImageCache, path: &Path) {
+        for _ in 0..500 {
+            if cache.poll_decoded().iter().any(|p| p == path) {
+                return;
+            }
+            std::thread::sleep(std::time::Duration::from_millis(10));
+        }
+        panic!("{} was never decoded", path.display());
+    }
+
+    #[test]
+    fn test_changed_file_is_decoded_again() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("diagram.png");
+        DynamicImage::new_rgba8(2, 2).save(&path).unwrap();
+
+        let mut cache = ImageCache::new();
+        assert!(!cache.request(&path), "first request starts a decode");
+        wait_for_decode(&mut cache, &path);
+        assert!(cache.request(&path));
+        assert!(cache.decoding_in_flight.is_empty(), "unchanged: served from the cache");
+
+        // Re-exported: bigger, and newer
+        DynamicImage::new_rgba8(3, 3).save(&path).unwrap();
+        let later = SystemTime::now() + std::time::Duration::from_secs(10);
+        std::fs::File::options().write(true).open(&path).unwrap().set_modified(later).unwrap();
+        assert!(cache.request(&path), "the old image shows meanwhile");
+        assert!(cache.decoding_in_flight.contains(&path));
+        wait_for_decode(&mut cache, &path);
+        assert_eq!(cache.get(&path).map(|img| img.width()), Some(3));
+    }
+
+    #[test]
+    fn test_thumbnail_path() {
+        assert_eq!(
+            thumbnail_path(Path::new("/notes/.marko/images/shot-1.png")),
+            PathBuf::from("/notes/.marko/images/shot-1.thumb.png")
+        );
+    }
+}
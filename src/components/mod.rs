@@ -1,4 +1,5 @@
 pub mod header;
 pub mod status;
 pub mod editor;
+pub mod images;
 pub mod preview;
@@ -1,30 +1,20 @@
 use std::collections::{HashMap, HashSet};
 use std::path::{Path, PathBuf};
-use std::sync::mpsc;
 
-use image::DynamicImage;
 use ratatui::{
-    buffer::Buffer,
     layout::Rect,
-    style::{Color, Modifier, Style},
+    style::{Modifier, Style},
+    text::{Span, Text},
     widgets::{Paragraph, Scrollbar, ScrollbarOrientation, ScrollbarState},
     Frame,
 };
-use ratatui_image::picker::Picker;
-use ratatui_image::protocol::StatefulProtocol;
-use ratatui_image::StatefulImage;
 
+use super::images::{self, DecodedImage, ImageCache, LruOrder, Placements};
 use crate::markdown;
+use crate::markdown::diagram::Diagram;
+use crate::markdown::renderer::RenderedMarkdown;
 use crate::theme;
 
-/// A decoded image sent from a background thread to the main thread.
-pub struct DecodedImage {
-    pub path: PathBuf,
-    pub image: Option<DynamicImage>,
-    /// Relative URL for pre-populating file_cache (e.g. ".marko/images/screenshot-XXX.png").
-    pub url_hint: Option<String>,
-}
-
 /// A clickable link region in the rendered preview buffer.
 pub struct ClickableLink {
     pub y: u16,
@@ -46,45 +36,56 @@ pub struct PreviewState {
     pub content_height: u16,
     /// Clickable link regions from the last render.
     pub click_links: Vec<ClickableLink>,
-    /// Cache: image URL → local file path (None = failed to fetch/not fetchable).
-    file_cache: HashMap<String, Option<PathBuf>>,
-    /// Cache: file path → decoded DynamicImage (None = failed to decode).
-    image_decode_cache: HashMap<PathBuf, Option<DynamicImage>>,
+    /// Every link in the last rendered document as (line, url), in order.
+    pub links: Vec<(u16, String)>,
+    /// The link Tab / Shift+Tab moved to, as (line, url). Kept by value, not
+    /// index, since the link lists are rebuilt on every render.
+    pub focused_link: Option<(u16, String)>,
+    /// Source lines of the headings whose sections are collapsed.
+    pub collapsed: HashSet<usize>,
+    /// Show math as its LaTeX source instead of converted to Unicode.
+    pub show_raw_math: bool,
+    /// Each heading shown in the last render as (line, source line).
+    heading_lines: Vec<(u16, usize)>,
+    /// Resolved and decoded images, shared with the editor's inline thumbnails.
+    pub images: ImageCache,
     /// Cache: file path → resized RGBA at specific dimensions (avoids per-frame resize).
     resize_cache: HashMap<PathBuf, ResizedImage>,
+    /// Use order of `resize_cache`, capped like the decoded images.
+    resize_order: LruOrder,
+    cache_size: usize,
     /// Screen area used during last render.
     last_area: Rect,
-    /// Sender for background decode threads to deliver decoded images.
-    image_tx: mpsc::Sender<DecodedImage>,
-    /// Receiver drained in poll_decoded_images() (~10fps from tick()).
-    image_rx: mpsc::Receiver<DecodedImage>,
-    /// Paths currently being decoded in background threads (prevents duplicate spawns).
-    decoding_in_flight: HashSet<PathBuf>,
-    /// Graphics protocol picker (Sixel/Kitty/iTerm2). None = half-block fallback only.
-    picker: Option<Picker>,
-    /// Cache: file path → StatefulProtocol for graphics protocol rendering.
-    protocol_cache: HashMap<PathBuf, Box<StatefulProtocol>>,
-    /// Paths that were rendered via graphics protocol last frame (for cleanup).
-    last_gfx_paths: HashSet<PathBuf>,
+    /// Images drawn via graphics protocol. Without a picker, half-block fallback only.
+    placements: Placements,
+    /// Diagram PNGs being rendered in background threads (or that failed to).
+    diagrams_started: HashSet<PathBuf>,
+}
+
+impl Default for PreviewState {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl PreviewState {
     pub fn new() -> Self {
-        let (image_tx, image_rx) = mpsc::channel();
         Self {
             scroll_offset: 0,
             content_height: 0,
             click_links: Vec::new(),
-            file_cache: HashMap::new(),
-            image_decode_cache: HashMap::new(),
+            links: Vec::new(),
+            focused_link: None,
+            collapsed: HashSet::new(),
+            show_raw_math: false,
+            heading_lines: Vec::new(),
+            images: ImageCache::new(),
             resize_cache: HashMap::new(),
+            resize_order: LruOrder::default(),
+            cache_size: images::DEFAULT_CACHE_SIZE,
             last_area: Rect::default(),
-            image_tx,
-            image_rx,
-            decoding_in_flight: HashSet::new(),
-            picker: Picker::from_query_stdio().ok(),
-            protocol_cache: HashMap::new(),
-            last_gfx_paths: HashSet::new(),
+            placements: Placements::default(),
+            diagrams_started: HashSet::new(),
         }
     }
 
@@ -105,6 +106,20 @@ impl PreviewState {
         self.scroll_down(viewport_height.saturating_sub(2), viewport_height);
     }
 
+    /// Keeps at most `size` decoded and at most `size` resized images
+    /// (`image_cache_size`), evicting the least recently drawn.
+    pub fn set_cache_size(&mut self, size: usize) {
+        self.cache_size = size.max(1);
+        self.images.set_capacity(size);
+        self.evict_resized();
+    }
+
+    fn evict_resized(&mut self) {
+        for path in self.resize_order.evict(self.cache_size) {
+            self.resize_cache.remove(&path);
+        }
+    }
+
     /// Find the URL at a given screen position, if any.
     pub fn url_at(&self, x: u16, y: u16) -> Option<&str> {
         for link in &self.click_links {
@@ -115,39 +130,133 @@ impl PreviewState {
         None
     }
 
-    /// Returns a clone of the sender for background threads to deliver decoded images.
-    pub fn image_sender(&self) -> mpsc::Sender<DecodedImage> {
-        self.image_tx.clone()
+    /// Moves the link focus to the next (or previous) link, wrapping around,
+    /// and scrolls it into view. Without a focus yet, starts from the first
+    /// link on screen (or the last, going back). False if there are no links.
+    pub fn focus_link(&mut self, forward: bool, viewport_height: u16) -> bool {
+        if self.links.is_empty() {
+            return false;
+        }
+        let count = self.links.len();
+        let current = self.focused_link.as_ref().and_then(|f| self.links.iter().position(|l| l == f));
+        let index = match current {
+            Some(i) if forward => (i + 1) % count,
+            Some(i) => (i + count - 1) % count,
+            None if forward => {
+                let top = self.scroll_offset;
+                self.links.iter().position(|(line, _)| *line >= top).unwrap_or(0)
+            }
+            None => {
+                let bottom = self.scroll_offset.saturating_add(viewport_height);
+                self.links.iter().rposition(|(line, _)| *line < bottom).unwrap_or(count - 1)
+            }
+        };
+        let (line, _) = self.links[index];
+        if line < self.scroll_offset {
+            self.scroll_offset = line;
+        } else if line >= self.scroll_offset.saturating_add(viewport_height) {
+            self.scroll_offset = line + 1 - viewport_height.max(1);
+        }
+        self.focused_link = Some(self.links[index].clone());
+        true
+    }
+
+    /// The URL of the focused link, if any.
+    pub fn focused_url(&self) -> Option<&str> {
+        self.focused_link.as_ref().map(|(_, url)| url.as_str())
+    }
+
+    /// Renders `diagram` to its cached PNG at `path` in a background thread,
+    /// once; the decoded result arrives like any other image.
+    /// The source line of the heading drawn at screen position (x, y).
+    pub fn heading_at(&self, x: u16, y: u16) -> Option<usize> {
+        let area = self.last_area;
+        if x < area.x || x >= area.x + area.width || y < area.y || y >= area.y + area.height {
+            return None;
+        }
+        let line = y - area.y + self.scroll_offset;
+        self.heading_lines.iter().find(|(l, _)| *l == line).map(|(_, source)| *source)
+    }
+
+    /// The source line of the heading over the top of the view (the
+    /// section being read), else of the first heading on screen.
+    pub fn section_at_top(&self) -> Option<usize> {
+        let top = self.scroll_offset;
+        let above = self.heading_lines.iter().take_while(|(line, _)| *line <= top).last();
+        above
+            .or_else(|| self.heading_lines.first().filter(|(line, _)| *line < top + self.last_area.height))
+            .map(|(_, source)| *source)
+    }
+
+    /// Collapses the section under the heading on `source_line`, or expands
+    /// it if collapsed. Returns true if it's now collapsed.
+    pub fn toggle_section(&mut self, source_line: usize) -> bool {
+        if self.collapsed.remove(&source_line) {
+            return false;
+        }
+        self.collapsed.insert(source_line);
+        // Keep the heading in view when folding the section being read
+        if let Some(&(line, _)) = self.heading_lines.iter().find(|(_, source)| *source == source_line) {
+            self.scroll_offset = self.scroll_offset.min(line);
+        }
+        true
+    }
+
+    fn start_diagram(&mut self, diagram: &Diagram, path: &Path) {
+        if !self.diagrams_started.insert(path.to_path_buf()) {
+            return;
+        }
+        let tx = self.images.sender();
+        let diagram = diagram.clone();
+        let path = path.to_path_buf();
+        std::thread::spawn(move || {
+            let image = match diagram.render_to(&path) {
+                Ok(()) => images::load_image(&path),
+                Err(_) => None,
+            };
+            let _ = tx.send(DecodedImage { path, image, url_hint: None });
+        });
     }
 
-    /// Drains all pending decoded images from background threads.
-    /// Call from tick() to pick up results without blocking.
-    pub fn poll_decoded_images(&mut self) {
-        while let Ok(msg) = self.image_rx.try_recv() {
-            self.decoding_in_flight.remove(&msg.path);
+    /// Drains all pending decoded images from background threads and
+    /// returns their paths. Call from tick() to pick up results without blocking.
+    pub fn poll_decoded_images(&mut self) -> Vec<PathBuf> {
+        let paths = self.images.poll_decoded();
+        for path in &paths {
             // Invalidate caches so next render re-processes
-            self.resize_cache.remove(&msg.path);
-            self.protocol_cache.remove(&msg.path);
-            self.image_decode_cache.insert(msg.path.clone(), msg.image);
-            // Pre-populate file_cache so resolve_image_path() isn't needed
-            if let Some(url) = msg.url_hint {
-                self.file_cache.insert(url, Some(msg.path));
-            }
+            self.resize_cache.remove(path);
+            self.resize_order.remove(path);
+            self.placements.invalidate(path);
         }
+        paths
     }
 }
 
 pub fn render(frame: &mut Frame, area: Rect, content: &str, state: &mut PreviewState, base_dir: &Path) {
-    let rendered = markdown::renderer::render_markdown(content, area.width.saturating_sub(2) as usize);
+    let mut rendered = markdown::renderer::render_markdown_with_math(
+        content,
+        area.width.saturating_sub(2) as usize,
+        state.show_raw_math,
+    );
+    let mut links = link_lines(&rendered.text, &rendered.link_urls);
+    if !state.collapsed.is_empty() {
+        collapse_sections(&mut rendered, &mut links, &state.collapsed);
+    }
+    state.heading_lines = rendered.headings.iter().map(|h| (h.line as u16, h.source_line)).collect();
 
     state.content_height = rendered.text.lines.len() as u16;
 
     if state.last_area.width != area.width || state.last_area.height != area.height {
-        state.protocol_cache.clear();
+        state.placements.clear();
     }
     state.last_area = area;
 
-    let link_urls = rendered.link_urls;
+    let link_urls: Vec<String> = links.iter().map(|(_, url)| url.clone()).collect();
+    state.links = links;
+    // Forget the focus once its link is gone from the document
+    if state.focused_link.as_ref().is_some_and(|f| !state.links.contains(f)) {
+        state.focused_link = None;
+    }
 
     // Collect image info before moving rendered.text into Paragraph
     let image_infos = rendered.image_infos;
@@ -174,38 +283,23 @@ pub fn render(frame: &mut Frame, area: Rect, content: &str, state: &mut PreviewS
             continue;
         }
 
-        let file_path = match state.file_cache.get(&info.url) {
-            Some(cached) => cached.clone(),
-            None => {
-                let resolved = resolve_image_path(&info.url, base_dir);
-                // Only cache successful resolutions — None may become Some
-                // once a background thread finishes writing the file.
-                if resolved.is_some() {
-                    state.file_cache.insert(info.url.clone(), resolved.clone());
+        let path = match &info.diagram {
+            // Rendered once per content; the placeholder shows until then
+            Some(diagram) => {
+                let Some(path) = diagram.cache_path() else {
+                    continue;
+                };
+                if !path.exists() {
+                    state.start_diagram(diagram, &path);
+                    continue;
                 }
-                resolved
+                Some(path)
             }
+            None => state.images.resolve(&info.url, base_dir, true),
         };
-
-        if let Some(path) = file_path {
+        if let Some(path) = path {
             // Non-blocking: if not yet decoded, spawn background thread and skip this frame
-            if !state.image_decode_cache.contains_key(&path) {
-                if !state.decoding_in_flight.contains(&path) {
-                    state.decoding_in_flight.insert(path.clone());
-                    let tx = state.image_tx.clone();
-                    let decode_path = path.clone();
-                    std::thread::spawn(move || {
-                        let img = load_image(&decode_path);
-                        if let Some(ref i) = img {
-                            save_thumbnail(i, &decode_path);
-                        }
-                        let _ = tx.send(DecodedImage {
-                            path: decode_path,
-                            image: img,
-                            url_hint: None,
-                        });
-                    });
-                }
+            if !state.images.request(&path) {
                 continue; // skip this image until decode finishes
             }
 
@@ -215,11 +309,11 @@ pub fn render(frame: &mut Frame, area: Rect, content: &str, state: &mut PreviewS
             // Pre-compute resized RGBA (only when dimensions change)
             let target_w = full_cols as u32;
             let target_h = (full_rows * 2) as u32;
-            let needs_resize = state.resize_cache.get(&path).map_or(true, |cached| {
+            let needs_resize = state.resize_cache.get(&path).is_none_or(|cached| {
                 cached.target_w != target_w || cached.target_h != target_h
             });
             if needs_resize {
-                if let Some(Some(ref img)) = state.image_decode_cache.get(&path) {
+                if let Some(img) = state.images.get(&path) {
                     use image::imageops::FilterType;
                     // Use fast Triangle filter for large images (>2MP) since
                     // we're downscaling to terminal cells anyway.
@@ -238,6 +332,9 @@ pub fn render(frame: &mut Frame, area: Rect, content: &str, state: &mut PreviewS
                     });
                 }
             }
+            if state.resize_cache.contains_key(&path) {
+                state.resize_order.touch(&path);
+            }
 
             // Use signed arithmetic so images partially above the viewport
             // correctly compute y_offset (rows clipped from top).
@@ -260,68 +357,51 @@ pub fn render(frame: &mut Frame, area: Rect, content: &str, state: &mut PreviewS
         }
     }
 
+    state.evict_resized();
+
     // Render images. When a graphics protocol picker is available, use it for
     // full-resolution rendering. Images that are partially scrolled off the top
     // (y_offset > 0) are hidden entirely — the placeholder box shows instead.
     // When no picker is available, fall back to half-block rendering everywhere.
-    let has_picker = state.picker.is_some();
-    let mut this_frame_gfx: HashSet<PathBuf> = HashSet::new();
+    let has_picker = state.images.has_picker();
 
     for job in &jobs {
         if has_picker && job.y_offset == 0 {
             // Graphics protocol: full-res, image top is within viewport
-            if !state.protocol_cache.contains_key(&job.path) {
-                if let Some(Some(ref img)) = state.image_decode_cache.get(&job.path) {
-                    if let Some(ref picker) = state.picker {
-                        let protocol = picker.new_resize_protocol(img.clone());
-                        state.protocol_cache.insert(job.path.clone(), Box::new(protocol));
-                    }
-                }
-            }
-            // Clear cells so placeholder doesn't show through
-            {
-                let buf = frame.buffer_mut();
-                for y in job.rect.y..job.rect.y + job.rect.height {
-                    for x in job.rect.x..job.rect.x + job.rect.width {
-                        if let Some(cell) = buf.cell_mut((x, y)) {
-                            cell.reset();
-                        }
-                    }
-                }
-            }
-            if let Some(protocol) = state.protocol_cache.get_mut(&job.path) {
-                frame.render_stateful_widget(StatefulImage::default(), job.rect, protocol.as_mut());
-                this_frame_gfx.insert(job.path.clone());
-            }
+            state.placements.draw(frame, job.rect, &job.path, &state.images);
         } else if has_picker {
             // y_offset > 0: image partially scrolled off top — hide it entirely
             // but still clear cells so the placeholder text doesn't show through.
-            let buf = frame.buffer_mut();
-            for y in job.rect.y..job.rect.y + job.rect.height {
-                for x in job.rect.x..job.rect.x + job.rect.width {
-                    if let Some(cell) = buf.cell_mut((x, y)) {
-                        cell.reset();
-                    }
-                }
-            }
+            images::clear_cells(frame.buffer_mut(), job.rect);
         } else {
             // No graphics protocol available: half-block fallback with cropping
             let buf = frame.buffer_mut();
             if let Some(cached) = state.resize_cache.get(&job.path) {
-                render_halfblock_image(buf, job.rect, &cached.rgba, job.full_cols, job.y_offset);
+                images::render_halfblock_image(buf, job.rect, &cached.rgba, job.full_cols, job.y_offset);
             }
         }
     }
 
     // Any image that was rendered via graphics protocol last frame but NOT this
     // frame needs its protocol dropped so the terminal clears the placement.
-    for old_path in state.last_gfx_paths.difference(&this_frame_gfx) {
-        state.protocol_cache.remove(old_path);
+    state.placements.finish_frame();
+
+    // Build clickable link regions, counting from the first link on screen
+    let first_visible = state.links.iter().take_while(|(line, _)| *line < state.scroll_offset).count();
+    build_link_regions(frame, area, &link_urls[first_visible.min(link_urls.len())..], &mut state.click_links);
+
+    // Highlight the keyboard-focused link
+    if let Some((line, url)) = &state.focused_link {
+        let y = (area.y + *line).wrapping_sub(state.scroll_offset);
+        let buf = frame.buffer_mut();
+        for link in state.click_links.iter().filter(|l| l.y == y && &l.url == url) {
+            for x in link.x_start..link.x_end {
+                if let Some(cell) = buf.cell_mut((x, y)) {
+                    cell.set_bg(theme::active().selection);
+                }
+            }
+        }
     }
-    state.last_gfx_paths = this_frame_gfx;
-
-    // Build clickable link regions
-    build_link_regions(frame, area, &link_urls, &mut state.click_links);
 
     // Scrollbar
     if state.content_height > area.height {
@@ -329,175 +409,83 @@ pub fn render(frame: &mut Frame, area: Rect, content: &str, state: &mut PreviewS
             .position(state.scroll_offset as usize)
             .viewport_content_length(area.height as usize);
         let scrollbar = Scrollbar::new(ScrollbarOrientation::VerticalRight)
-            .thumb_style(Style::default().fg(theme::LINE_NUMBER))
-            .track_style(Style::default().fg(theme::BORDER));
+            .thumb_style(Style::default().fg(theme::active().line_number))
+            .track_style(Style::default().fg(theme::active().border));
         frame.render_stateful_widget(scrollbar, area, &mut scrollbar_state);
     }
 }
 
-/// Composite an RGBA pixel over a background color using alpha blending.
-#[inline]
-fn blend(pixel: &image::Rgba<u8>, bg: (u8, u8, u8)) -> (u8, u8, u8) {
-    let a = pixel[3] as u16;
-    let inv_a = 255 - a;
-    (
-        ((pixel[0] as u16 * a + bg.0 as u16 * inv_a) / 255) as u8,
-        ((pixel[1] as u16 * a + bg.1 as u16 * inv_a) / 255) as u8,
-        ((pixel[2] as u16 * a + bg.2 as u16 * inv_a) / 255) as u8,
-    )
-}
-
-/// Render a pre-resized RGBA image into the buffer using half-block Unicode characters.
-/// Each cell shows two vertical pixels: upper pixel as fg color, lower as bg color.
-/// Preserves aspect ratio centering within the rect.
-/// `full_cols` is the full image area width (for centering math).
-/// `y_offset` is the number of rows clipped from the top (due to scrolling).
-fn render_halfblock_image(
-    buf: &mut Buffer,
-    rect: Rect,
-    rgba: &image::RgbaImage,
-    full_cols: u16,
-    y_offset: u16,
-) {
-    if rect.width == 0 || rect.height == 0 {
-        return;
-    }
-
-    let bg = (30u8, 30u8, 30u8);
-    let img_w = rgba.width();
-    let img_h = rgba.height();
-
-    // Center horizontally within the full column width
-    let x_pad = (full_cols as u32).saturating_sub(img_w) / 2;
-
-    for dy in 0..rect.height {
-        let img_row = dy + y_offset;
-        for dx in 0..rect.width {
-            let img_x = (dx as u32).wrapping_sub(x_pad);
-            let upper_y = (img_row * 2) as u32;
-            let lower_y = upper_y + 1;
-
-            let (ur, ug, ub) = if img_x < img_w && upper_y < img_h {
-                blend(rgba.get_pixel(img_x, upper_y), bg)
-            } else {
-                bg
-            };
-            let (lr, lg, lb) = if img_x < img_w && lower_y < img_h {
-                blend(rgba.get_pixel(img_x, lower_y), bg)
-            } else {
-                bg
-            };
-
-            if let Some(cell) = buf.cell_mut((rect.x + dx, rect.y + dy)) {
-                let is_bg = (ur, ug, ub) == bg && (lr, lg, lb) == bg;
-                if is_bg {
-                    cell.reset();
-                } else {
-                    cell.set_symbol("\u{2580}") // ▀
-                        .set_fg(Color::Rgb(ur, ug, ub))
-                        .set_bg(Color::Rgb(lr, lg, lb));
-                }
-            }
-        }
-    }
-}
-
-/// Returns the path for a pre-computed thumbnail of the given image.
-/// e.g. `/path/to/screenshot-123.png` → `/path/to/screenshot-123.thumb.png`
-fn thumbnail_path(path: &Path) -> PathBuf {
-    let stem = path.file_stem().and_then(|s| s.to_str()).unwrap_or("img");
-    path.with_file_name(format!("{}.thumb.png", stem))
-}
-
-/// Saves a downscaled thumbnail alongside the original for fast reload.
-/// Skips if the image is already small enough that decoding is fast.
-pub(crate) fn save_thumbnail(img: &DynamicImage, original_path: &Path) {
-    let pixels = img.width() as u64 * img.height() as u64;
-    if pixels <= 640_000 {
-        return; // already small, thumbnail not needed
-    }
-    let thumb = thumbnail_path(original_path);
-    let max_dim = 800u32;
-    let resized = img.resize(max_dim, max_dim, image::imageops::FilterType::Triangle);
-    let _ = resized.save(&thumb);
-}
-
-/// Load an image file and return a DynamicImage. Handles PNG, JPEG, GIF, BMP.
-/// For SVG/SVGZ, uses resvg. Checks for a pre-computed thumbnail first
-/// so large images (e.g. retina screenshots) load in milliseconds on reload.
-pub(crate) fn load_image(path: &std::path::Path) -> Option<DynamicImage> {
-    let ext = path
-        .extension()
-        .and_then(|e| e.to_str())
-        .unwrap_or("")
-        .to_lowercase();
-
+/// Open a URL in the system default browser.
+pub fn open_url(url: &str) {
     #[cfg(target_os = "macos")]
-    if ext == "svg" || ext == "svgz" {
-        return load_svg(path);
-    }
+    let _ = std::process::Command::new("open").arg(url).spawn();
+    #[cfg(target_os = "linux")]
+    let _ = std::process::Command::new("xdg-open").arg(url).spawn();
+    #[cfg(target_os = "windows")]
+    let _ = std::process::Command::new("cmd")
+        .args(["/C", "start", url])
+        .spawn();
+}
 
-    // Check for pre-computed thumbnail first (much faster for large images)
-    let thumb = thumbnail_path(path);
-    if thumb.exists() {
-        if let Some(img) = load_image_raw(&thumb) {
-            return Some(img);
+/// Folds the sections under the headings on the `collapsed` source lines
+/// out of `rendered`, leaving each heading with a count of the lines it
+/// hides. The images, headings and `links` below move up to match.
+fn collapse_sections(rendered: &mut RenderedMarkdown, links: &mut Vec<(u16, String)>, collapsed: &HashSet<usize>) {
+    let hidden = rendered.collapsed_lines(collapsed);
+    for heading in &rendered.headings {
+        let count = hidden[heading.line + 1..].iter().take_while(|&&h| h).count();
+        if count > 0 && collapsed.contains(&heading.source_line) && !hidden[heading.line] {
+            rendered.text.lines[heading.line].spans.push(Span::styled(
+                format!("  ▸ {} lines hidden", count),
+                Style::default().fg(theme::active().line_number),
+            ));
         }
     }
 
-    load_image_raw(path)
-}
+    // Where each line moves to, None if hidden
+    let mut next_row = 0;
+    let rows: Vec<Option<usize>> = hidden
+        .iter()
+        .map(|&h| {
+            let row = (!h).then_some(next_row);
+            next_row += !h as usize;
+            row
+        })
+        .collect();
 
-/// Low-level image decode from a file path (no thumbnail lookup).
-fn load_image_raw(path: &Path) -> Option<DynamicImage> {
-    // Guess format from file content (not extension) so mismatched files
-    // (e.g. TIFF data with .png extension from macOS clipboard) still load.
-    let reader = image::ImageReader::open(path).ok()?;
-    let reader = reader.with_guessed_format().ok()?;
-    reader.decode().ok()
+    let lines = std::mem::take(&mut rendered.text.lines);
+    rendered.text.lines = lines.into_iter().zip(&hidden).filter(|(_, &h)| !h).map(|(l, _)| l).collect();
+    let map = std::mem::take(&mut rendered.source_line_map);
+    rendered.source_line_map = map.into_iter().zip(&hidden).filter(|(_, &h)| !h).map(|(s, _)| s).collect();
+    rendered.image_infos.retain_mut(|info| rows[info.start_line].map(|row| info.start_line = row).is_some());
+    rendered.headings.retain_mut(|h| rows[h.line].map(|row| h.line = row).is_some());
+    links.retain_mut(|(line, _)| rows[*line as usize].map(|row| *line = row as u16).is_some());
 }
 
-/// Decode an image from raw bytes (PNG, TIFF, etc.) without touching disk.
-/// Used by the paste thread to avoid a redundant file→decode round-trip.
-pub(crate) fn load_image_from_bytes(bytes: &[u8]) -> Option<DynamicImage> {
-    use std::io::Cursor;
-    let reader = image::ImageReader::new(Cursor::new(bytes))
-        .with_guessed_format()
-        .ok()?;
-    reader.decode().ok()
+/// True for the style the renderer gives link text.
+fn is_link_style(style: Style) -> bool {
+    style.fg == theme::link_style().fg && style.add_modifier.contains(Modifier::UNDERLINED)
 }
 
-/// Render SVG to a DynamicImage using resvg (pure Rust, no external tools).
-/// Renders at a higher resolution than the SVG's native size for better quality
-/// when downscaled to terminal cells.
-#[cfg(target_os = "macos")]
-fn load_svg(path: &std::path::Path) -> Option<DynamicImage> {
-    let svg_data = std::fs::read(path).ok()?;
-    let tree = resvg::usvg::Tree::from_data(&svg_data, &Default::default()).ok()?;
-    let size = tree.size();
-    if size.width() == 0.0 || size.height() == 0.0 {
-        return None;
+/// The line of each link in `text`: each run of link-styled spans on a line
+/// takes the next URL, the way `build_link_regions` reads them off the screen.
+fn link_lines(text: &Text, link_urls: &[String]) -> Vec<(u16, String)> {
+    let mut urls = link_urls.iter();
+    let mut out = Vec::new();
+    for (y, line) in text.lines.iter().enumerate() {
+        let mut in_link = false;
+        for span in line.spans.iter().filter(|s| !s.content.is_empty()) {
+            let is_link = is_link_style(line.style.patch(span.style));
+            if is_link && !in_link {
+                match urls.next() {
+                    Some(url) => out.push((y as u16, url.clone())),
+                    None => return out,
+                }
+            }
+            in_link = is_link;
+        }
     }
-    // Render at a larger size for better detail when downscaled
-    let target_w = 800u32;
-    let scale = target_w as f32 / size.width();
-    let target_h = (size.height() * scale) as u32;
-    let mut pixmap = resvg::tiny_skia::Pixmap::new(target_w, target_h)?;
-    let transform = resvg::tiny_skia::Transform::from_scale(scale, scale);
-    resvg::render(&tree, transform, &mut pixmap.as_mut());
-    image::RgbaImage::from_raw(target_w, target_h, pixmap.take()).map(DynamicImage::ImageRgba8)
-}
-
-/// Open a URL in the system default browser.
-pub fn open_url(url: &str) {
-    #[cfg(target_os = "macos")]
-    let _ = std::process::Command::new("open").arg(url).spawn();
-    #[cfg(target_os = "linux")]
-    let _ = std::process::Command::new("xdg-open").arg(url).spawn();
-    #[cfg(target_os = "windows")]
-    let _ = std::process::Command::new("cmd")
-        .args(["/C", "start", url])
-        .spawn();
+    out
 }
 
 /// Scan the rendered buffer for link-styled cell runs and map them to URLs.
@@ -512,7 +500,6 @@ fn build_link_regions(
         return;
     }
 
-    let link_fg = theme::link_style().fg;
     let buf = frame.buffer_mut();
     let mut url_index = 0;
     let mut in_link = false;
@@ -521,8 +508,7 @@ fn build_link_regions(
     for y in area.y..area.y.saturating_add(area.height) {
         for x in area.x..area.x.saturating_add(area.width) {
             if let Some(cell) = buf.cell((x, y)) {
-                let is_link = cell.style().fg == link_fg
-                    && cell.style().add_modifier.contains(Modifier::UNDERLINED);
+                let is_link = is_link_style(cell.style());
 
                 if is_link && !in_link {
                     in_link = true;
@@ -555,63 +541,3 @@ fn build_link_regions(
         }
     }
 }
-
-/// Resolve an image URL to a local file path.
-/// Downloads remote images via curl; returns None if unavailable.
-fn resolve_image_path(url: &str, base_dir: &Path) -> Option<PathBuf> {
-    if url.starts_with("http://") || url.starts_with("https://") {
-        fetch_remote_image(url)
-    } else {
-        let path = PathBuf::from(url);
-        // Try as-is (absolute path), then relative to the markdown file's directory
-        let candidate = if path.is_absolute() {
-            path
-        } else {
-            base_dir.join(path)
-        };
-        if candidate.exists() {
-            Some(candidate)
-        } else {
-            None
-        }
-    }
-}
-
-/// Fetch a remote image via curl, caching in a temp directory.
-fn fetch_remote_image(url: &str) -> Option<PathBuf> {
-    let cache_dir = std::env::temp_dir().join("marko_images");
-    std::fs::create_dir_all(&cache_dir).ok()?;
-
-    // Preserve file extension for format detection
-    let ext = url.rsplit('.').next().unwrap_or("png");
-    let ext = if ext.len() <= 4 && ext.chars().all(|c| c.is_alphanumeric()) {
-        ext
-    } else {
-        "png"
-    };
-    let key: String = url
-        .chars()
-        .filter(|c| c.is_alphanumeric())
-        .rev()
-        .take(50)
-        .collect();
-    let cache_path = cache_dir.join(format!("{}.{}", key, ext));
-
-    if cache_path.exists() && std::fs::metadata(&cache_path).ok()?.len() > 0 {
-        return Some(cache_path);
-    }
-
-    let status = std::process::Command::new("curl")
-        .args(["-s", "-L", "--max-time", "10", "-o"])
-        .arg(&cache_path)
-        .arg(url)
-        .status()
-        .ok()?;
-
-    if status.success() && cache_path.exists() && std::fs::metadata(&cache_path).ok()?.len() > 0 {
-        Some(cache_path)
-    } else {
-        let _ = std::fs::remove_file(&cache_path);
-        None
-    }
-}
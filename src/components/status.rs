@@ -1,18 +1,84 @@
 use ratatui::{
     layout::{Alignment, Constraint, Layout, Rect},
+    style::{Color, Modifier, Style},
     text::{Line, Span},
     widgets::Paragraph,
     Frame,
 };
 
+use crate::markdown::word_goal;
 use crate::theme;
 
+/// Cells in the word-goal progress bar.
+const GOAL_BAR_WIDTH: usize = 10;
+
+/// How serious a status message is: it picks the message's color, and
+/// errors stay up longer.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Severity {
+    #[default]
+    Info,
+    Success,
+    Warning,
+    Error,
+}
+
+impl Severity {
+    /// The message color, or None for the plain text color.
+    pub fn color(self) -> Option<Color> {
+        match self {
+            Severity::Info => None,
+            Severity::Success => Some(theme::active().success),
+            Severity::Warning => Some(theme::active().warning),
+            Severity::Error => Some(theme::active().error),
+        }
+    }
+
+    /// The style of a message with this severity on the status bar.
+    pub fn style(self) -> Style {
+        let style = theme::status_style();
+        match self.color() {
+            Some(color) if self == Severity::Error => style.fg(color).add_modifier(Modifier::BOLD),
+            Some(color) => style.fg(color),
+            None => style,
+        }
+    }
+}
+
 pub struct StatusInfo<'a> {
     pub line: usize,
     pub col: usize,
     pub message: &'a str,
+    pub severity: Severity,
     pub word_count: usize,
+    /// Estimated reading time in minutes (0 under half a minute).
+    pub reading_minutes: usize,
     pub modified: bool,
+    /// Vim-style position indicator from `scroll_position` ("Top", "Bot", "All", "42%").
+    pub position: &'a str,
+    /// Git rev the gutter diffs against, when not HEAD.
+    pub diff_base: Option<&'a str>,
+    /// Prose words and the word goal they count toward, when one is set.
+    /// Shown as a progress bar in place of `word_count`.
+    pub word_goal: Option<(usize, usize)>,
+    /// Length of the running focus timer session, if any.
+    pub focus_elapsed: Option<&'a str>,
+    /// "NORMAL" or "INSERT" with vim editing on.
+    pub vim_mode: Option<&'a str>,
+}
+
+/// Describes how far through the document the viewport is, like vim's ruler:
+/// "All" when everything fits, "Top"/"Bot" at either end, otherwise the
+/// percentage of lines above the viewport top.
+pub fn scroll_position(top: usize, visible: usize, total: usize) -> String {
+    let above = top;
+    let below = total.saturating_sub(top + visible);
+    match (above, below) {
+        (0, 0) => "All".to_string(),
+        (0, _) => "Top".to_string(),
+        (_, 0) => "Bot".to_string(),
+        _ => format!("{}%", above * 100 / (above + below)),
+    }
 }
 
 pub fn render(frame: &mut Frame, area: Rect, info: StatusInfo) {
@@ -20,36 +86,173 @@ pub fn render(frame: &mut Frame, area: Rect, info: StatusInfo) {
     let bg = Paragraph::new("").style(theme::status_style());
     frame.render_widget(bg, area);
 
+    // Right: diff base + focus timer + word count (or goal progress) +
+    // reading time + save status
+    let save_status = if info.modified { "Modified" } else { "Saved" };
+    let reading = match info.reading_minutes {
+        0 => "<1 min".to_string(),
+        minutes => format!("{} min", minutes),
+    };
+    let base = info
+        .diff_base
+        .map(|base| format!("vs {} | ", base))
+        .unwrap_or_default();
+    let focus = info
+        .focus_elapsed
+        .map(|elapsed| format!("\u{25CF} {} | ", elapsed))
+        .unwrap_or_default();
+    let mut right_spans = vec![Span::styled(base + &focus, theme::status_style())];
+    match info.word_goal {
+        Some((words, goal)) => {
+            let filled = word_goal::progress_cells(words, goal, GOAL_BAR_WIDTH);
+            let bar = format!(
+                "{}{}",
+                "\u{2588}".repeat(filled),
+                "\u{2591}".repeat(GOAL_BAR_WIDTH - filled)
+            );
+            let bar_style = if words >= goal {
+                Style::default().fg(theme::active().success).bg(theme::active().bar_bg)
+            } else {
+                theme::status_style()
+            };
+            right_spans.push(Span::styled(bar, bar_style));
+            right_spans.push(Span::styled(
+                format!(
+                    " {}/{} ({}%) | {} | {}  ",
+                    words,
+                    goal,
+                    words * 100 / goal,
+                    reading,
+                    save_status
+                ),
+                theme::status_style(),
+            ));
+        }
+        None => right_spans.push(Span::styled(
+            format!("{} words | {} | {}  ", info.word_count, reading, save_status),
+            theme::status_style(),
+        )),
+    }
+    let right_line = Line::from(right_spans);
+
+    // Left: vim mode + Ln/Col
+    let vim_mode = info
+        .vim_mode
+        .map(|mode| format!("{}  ", mode))
+        .unwrap_or_default();
+    let left_text = format!("  {}Ln {}, Col {}  {}", vim_mode, info.line, info.col, info.position);
+
+    // Either side grows past its quarter rather than clipping (a long base,
+    // the vim mode)
+    let side = |width: usize| {
+        if width as u16 > area.width / 4 {
+            Constraint::Length(width as u16)
+        } else {
+            Constraint::Fill(1)
+        }
+    };
     let chunks = Layout::horizontal([
-        Constraint::Fill(1),
+        side(left_text.chars().count()),
         Constraint::Fill(2),
-        Constraint::Fill(1),
+        side(right_line.width()),
     ])
     .split(area);
 
-    // Left: Ln/Col
-    let left = Paragraph::new(Line::from(Span::styled(
-        format!("  Ln {}, Col {}", info.line, info.col),
-        theme::status_style(),
-    )));
+    let left = Paragraph::new(Line::from(Span::styled(left_text, theme::status_style())));
     frame.render_widget(left, chunks[0]);
 
     // Center: status message
     if !info.message.is_empty() {
         let center = Paragraph::new(Line::from(Span::styled(
             info.message.to_string(),
-            theme::status_style(),
+            info.severity.style(),
         )))
         .alignment(Alignment::Center);
         frame.render_widget(center, chunks[1]);
     }
 
-    // Right: word count + save status
-    let save_status = if info.modified { "Modified" } else { "Saved" };
-    let right = Paragraph::new(Line::from(Span::styled(
-        format!("{} words | {}  ", info.word_count, save_status),
-        theme::status_style(),
-    )))
-    .alignment(Alignment::Right);
+    let right = Paragraph::new(right_line).alignment(Alignment::Right);
     frame.render_widget(right, chunks[2]);
 }
+
+/// Renders a prompt (`label` followed by the editable `input`) across the whole
+/// status bar, with a block cursor at byte offset `cursor`.
+pub fn render_prompt(frame: &mut Frame, area: Rect, label: &str, input: &str, cursor: usize) {
+    let bg = Paragraph::new("").style(theme::status_style());
+    frame.render_widget(bg, area);
+
+    let mut spans = vec![Span::styled(format!("  {} ", label), theme::status_style())];
+    spans.extend(input_spans(input, Some(cursor)));
+
+    frame.render_widget(Paragraph::new(Line::from(spans)), area);
+}
+
+/// Renders the two-field replace prompt: `find` and `replacement`, with the
+/// cursor (byte offset) in the active field and "all" flagged when set.
+pub fn render_replace_prompt(
+    frame: &mut Frame,
+    area: Rect,
+    find: &str,
+    replacement: &str,
+    editing_replacement: bool,
+    cursor: usize,
+    replace_all: bool,
+) {
+    let bg = Paragraph::new("").style(theme::status_style());
+    frame.render_widget(bg, area);
+
+    let label = if replace_all { "Replace all:" } else { "Replace:" };
+    let mut spans = vec![Span::styled(format!("  {} ", label), theme::status_style())];
+    spans.extend(input_spans(find, (!editing_replacement).then_some(cursor)));
+    spans.push(Span::styled("  with: ", theme::status_style()));
+    spans.extend(input_spans(replacement, editing_replacement.then_some(cursor)));
+
+    frame.render_widget(Paragraph::new(Line::from(spans)), area);
+}
+
+/// Spans for an editable input, with a block cursor at byte offset `cursor`
+/// when the input has focus.
+fn input_spans(input: &str, cursor: Option<usize>) -> Vec<Span<'static>> {
+    let input_style = Style::default().fg(theme::active().white).bg(theme::active().bar_bg);
+    let Some(cursor) = cursor else {
+        return vec![Span::styled(input.to_string(), input_style)];
+    };
+
+    // Cursor character (or space if at end)
+    let mut rest = input[cursor..].chars();
+    let cursor_char = rest.next().map_or(" ".to_string(), |c| c.to_string());
+    vec![
+        Span::styled(input[..cursor].to_string(), input_style),
+        Span::styled(
+            cursor_char,
+            Style::default().add_modifier(Modifier::REVERSED | Modifier::BOLD),
+        ),
+        Span::styled(rest.as_str().to_string(), input_style),
+    ]
+}
+
+/// Renders the live rename preview: the full path the rename would produce,
+/// flagged red if that path is already taken and green if it is free.
+pub fn render_rename_preview(frame: &mut Frame, area: Rect, target: &str, exists: bool) {
+    let bg = Paragraph::new("").style(theme::status_style());
+    frame.render_widget(bg, area);
+
+    let (color, note) = if exists {
+        (theme::active().error, "exists")
+    } else {
+        (theme::active().success, "available")
+    };
+    let spans = vec![
+        Span::styled("  \u{2192} ", theme::status_style()),
+        Span::styled(target.to_string(), Style::default().fg(color).bg(theme::active().bar_bg)),
+        Span::styled(
+            format!("  ({})", note),
+            Style::default()
+                .fg(color)
+                .bg(theme::active().bar_bg)
+                .add_modifier(Modifier::BOLD),
+        ),
+    ];
+
+    frame.render_widget(Paragraph::new(Line::from(spans)), area);
+}
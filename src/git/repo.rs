@@ -65,6 +65,14 @@ impl GitRepo {
         }
     }
 
+    /// True if `rev` (a branch, tag, commit or other revspec) names a tree.
+    pub fn resolves(&self, rev: &str) -> bool {
+        self.repo
+            .revparse_single(rev)
+            .and_then(|obj| obj.peel_to_tree())
+            .is_ok()
+    }
+
     pub fn repository(&self) -> &Repository {
         &self.repo
     }
@@ -1,8 +1,12 @@
 pub mod app;
 pub mod components;
+pub mod config;
 pub mod git;
 pub mod markdown;
 pub mod pandoc;
+pub mod reveal;
+pub mod shell;
 #[allow(dead_code)]
 pub mod theme;
 pub mod upgrade;
+pub mod workspace;
@@ -1,17 +1,29 @@
-use std::io;
+use std::io::{self, IsTerminal, Write};
 use std::panic;
 use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
 use std::time::Duration;
 
-use clap::{Parser, Subcommand};
+use clap::{Parser, Subcommand, ValueEnum};
 use crossterm::{
     event::{self, DisableBracketedPaste, DisableMouseCapture, EnableBracketedPaste, EnableMouseCapture},
     execute,
     terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
 };
 use ratatui::{backend::CrosstermBackend, Terminal};
-
-use marko::{app, pandoc, upgrade};
+use signal_hook::consts::{SIGINT, SIGTERM};
+
+use marko::{
+    app,
+    components::status::Severity,
+    config::{Config, Editing, MissingFile},
+    markdown::{code_highlight, html, renderer},
+    pandoc,
+    theme::{self, Theme},
+    upgrade,
+    workspace::{self, WalkOptions},
+};
 
 #[derive(Parser)]
 #[command(name = "marko", version, about = "A terminal markdown editor")]
@@ -19,22 +31,70 @@ struct Cli {
     #[command(subcommand)]
     command: Option<Commands>,
 
-    /// File to open for editing
-    file: Option<PathBuf>,
+    /// Files to open for editing; the first is shown and Ctrl+PageUp/PageDown
+    /// switches between them. A folder adds the markdown files in it
+    files: Vec<PathBuf>,
+
+    /// Open the files for reading only: edits and saving are disabled
+    #[arg(long)]
+    read_only: bool,
+
+    /// What to do when the file doesn't exist: create it, prompt first, or
+    /// open a buffer written only on save (overrides `missing_file`)
+    #[arg(long, value_name = "create|prompt|buffer")]
+    missing_file: Option<MissingFile>,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum ExportFormat {
+    Docx,
+    Html,
 }
 
 #[derive(Subcommand)]
 enum Commands {
-    /// Export markdown to .docx
+    /// Export markdown to .docx (via pandoc) or standalone HTML
     Export {
         /// Markdown file to export
         file: PathBuf,
-        /// Output .docx path (defaults to same name with .docx extension)
+        /// Output path (defaults to same name with the format's extension)
         #[arg(short, long)]
         output: Option<PathBuf>,
-        /// Reference .docx for styling (passed as --reference-doc to pandoc)
+        /// Reference .docx for styling (passed as --reference-doc to pandoc;
+        /// ignored for HTML)
         #[arg(long)]
         reference_doc: Option<PathBuf>,
+        /// Output format
+        #[arg(long, value_enum, default_value_t = ExportFormat::Docx)]
+        to: ExportFormat,
+        /// For HTML: one portable file with images and CSS embedded (via
+        /// pandoc)
+        #[arg(long)]
+        self_contained: bool,
+        /// For HTML: copy it to the clipboard as rich text instead of
+        /// writing a file
+        #[arg(long, conflicts_with_all = ["self_contained", "output"])]
+        to_clipboard: bool,
+    },
+    /// Print markdown rendered with terminal styling, without the editor
+    Render {
+        /// Markdown file to render
+        file: PathBuf,
+        /// Wrap width (defaults to the terminal width, or 80 when piped)
+        #[arg(short, long)]
+        width: Option<usize>,
+        /// Copy the rendered text (without colors) to the clipboard instead
+        /// of printing it
+        #[arg(long)]
+        to_clipboard: bool,
+    },
+    /// List the syntax-highlighting themes `code_theme` can name
+    Themes,
+    /// Show where a custom UI theme is read from
+    Theme {
+        /// Print the current UI theme as a theme.toml to start from
+        #[arg(long)]
+        dump: bool,
     },
     /// Update marko to the latest version
     Upgrade,
@@ -51,18 +111,60 @@ fn main() -> io::Result<()> {
             file,
             output,
             reference_doc,
-        }) => return handle_export(&file, output.as_deref(), reference_doc.as_deref()),
+            to: ExportFormat::Html,
+            self_contained,
+            to_clipboard,
+        }) => {
+            if reference_doc.is_some() {
+                eprintln!("Note: --reference-doc only applies to .docx export; ignoring it");
+            }
+            return handle_html_export(&file, output.as_deref(), self_contained, to_clipboard);
+        }
+        Some(Commands::Export {
+            file,
+            output,
+            reference_doc,
+            to: ExportFormat::Docx,
+            self_contained,
+            to_clipboard,
+        }) => {
+            if self_contained || to_clipboard {
+                eprintln!("Note: --self-contained and --to-clipboard only apply to HTML export; ignoring them");
+            }
+            return handle_export(&file, output.as_deref(), reference_doc.as_deref());
+        }
+        Some(Commands::Render { file, width, to_clipboard }) => return handle_render(&file, width, to_clipboard),
+        Some(Commands::Themes) => {
+            for name in code_highlight::theme_names() {
+                println!("{}", name);
+            }
+            return Ok(());
+        }
+        Some(Commands::Theme { dump }) => return handle_theme(dump),
         Some(Commands::Upgrade) => return upgrade::run_upgrade(),
         None => {}
     }
 
-    // No subcommand — must have a file argument
-    let file = match cli.file {
+    // No subcommand — must have at least one file argument
+    let mut files = cli.files.into_iter();
+    let file = match files.next() {
         Some(f) => f,
         None => {
-            eprintln!("Usage: marko <FILE> or marko export <FILE>");
+            eprintln!("Usage: marko <FILE>..., marko export <FILE> or marko render <FILE>");
+            std::process::exit(1);
+        }
+    };
+    let walk = WalkOptions::from_config(&Config::load().unwrap_or_default());
+    // A folder first opens the first markdown file in it
+    let (file, others) = if file.is_dir() {
+        let mut all = quick_switch_files(std::iter::once(file.clone()).chain(files), &walk);
+        if all.is_empty() {
+            eprintln!("Error: no markdown files in {}", file.display());
             std::process::exit(1);
         }
+        (all.remove(0), all)
+    } else {
+        (file, quick_switch_files(files, &walk))
     };
 
     // Detect .docx files — import via pandoc
@@ -72,21 +174,81 @@ fn main() -> io::Result<()> {
         .unwrap_or(false);
 
     if is_docx {
-        return handle_docx_open(&file);
+        return handle_docx_open(&file, others, cli.read_only);
     }
 
     // Regular .md file — existing flow
     if !file.exists() {
-        std::fs::write(&file, "")?;
+        if cli.read_only {
+            eprintln!("Error: file not found: {}", file.display());
+            std::process::exit(1);
+        }
+        let mode = cli.missing_file.unwrap_or_else(|| {
+            Config::load_for(&file).map(|c| c.missing_file).unwrap_or_default()
+        });
+        if !open_missing_file(&file, mode)? {
+            return Ok(());
+        }
+    }
+    // A new buffer's file doesn't exist yet to canonicalize
+    let file_path = file.canonicalize().or_else(|_| std::path::absolute(&file))?;
+
+    run_editor(file_path, None, others, cli.read_only)
+}
+
+/// Prepares to edit `file`, which doesn't exist, as `mode` says: creates
+/// it, or asks first, or leaves it for the first save. Returns false if
+/// the user declined to create it.
+fn open_missing_file(file: &std::path::Path, mode: MissingFile) -> io::Result<bool> {
+    let create = match mode {
+        MissingFile::Create => true,
+        MissingFile::Buffer => false,
+        MissingFile::Prompt => {
+            eprint!("{} doesn't exist. Create it? [Y/n] ", file.display());
+            io::stderr().flush()?;
+            let mut answer = String::new();
+            io::stdin().read_line(&mut answer)?;
+            if !matches!(answer.trim(), "" | "y" | "Y" | "yes") {
+                return Ok(false);
+            }
+            true
+        }
+    };
+    if create {
+        std::fs::write(file, "")?;
     }
-    let file_path = file.canonicalize()?;
+    Ok(true)
+}
 
-    run_editor(file_path, None)
+/// Resolves the extra command-line files into absolute paths for the
+/// quick-switch list. A folder stands for the markdown files in it, as the
+/// workspace walk under `walk` lists them. .docx files need a pandoc
+/// round-trip, so they can only be opened on their own and are skipped here.
+fn quick_switch_files(files: impl Iterator<Item = PathBuf>, walk: &WalkOptions) -> Vec<PathBuf> {
+    files
+        .flat_map(|f| {
+            if f.is_dir() {
+                workspace::markdown_files(&f, walk)
+            } else {
+                vec![f]
+            }
+        })
+        .filter(|f| {
+            let is_docx = f
+                .extension()
+                .is_some_and(|ext| ext.eq_ignore_ascii_case("docx"));
+            if is_docx {
+                eprintln!("Skipping {}: open .docx files on their own", f.display());
+            }
+            !is_docx
+        })
+        .filter_map(|f| f.canonicalize().or_else(|_| std::path::absolute(&f)).ok())
+        .collect()
 }
 
 /// Handles `marko export file.md` — converts to .docx and exits.
 fn handle_export(
-    file: &PathBuf,
+    file: &std::path::Path,
     output: Option<&std::path::Path>,
     reference_doc: Option<&std::path::Path>,
 ) -> io::Result<()> {
@@ -118,8 +280,182 @@ fn handle_export(
     }
 }
 
+/// Where `--to-clipboard` puts its output: the system clipboard, or a
+/// stand-in in tests.
+trait ClipboardSink {
+    fn set_text(&mut self, text: &str) -> Result<(), String>;
+    /// Rich HTML, with `alt` as the plain text for apps that don't take it.
+    fn set_html(&mut self, html: &str, alt: &str) -> Result<(), String>;
+}
+
+/// How long `--to-clipboard` keeps serving the clipboard on Linux, where
+/// it empties when marko exits unless a clipboard manager took it first.
+#[cfg(target_os = "linux")]
+const CLIPBOARD_HOLD: Duration = Duration::from_secs(30);
+
+struct SystemClipboard(arboard::Clipboard);
+
+impl SystemClipboard {
+    fn new() -> Result<Self, String> {
+        arboard::Clipboard::new().map(Self).map_err(|e| e.to_string())
+    }
+
+    fn set(&mut self) -> arboard::Set<'_> {
+        let set = self.0.set();
+        #[cfg(target_os = "linux")]
+        let set = {
+            use arboard::SetExtLinux;
+            set.wait_until(std::time::Instant::now() + CLIPBOARD_HOLD)
+        };
+        set
+    }
+}
+
+impl ClipboardSink for SystemClipboard {
+    fn set_text(&mut self, text: &str) -> Result<(), String> {
+        self.set().text(text).map_err(|e| e.to_string())
+    }
+
+    fn set_html(&mut self, html: &str, alt: &str) -> Result<(), String> {
+        self.set().html(html, Some(alt)).map_err(|e| e.to_string())
+    }
+}
+
+/// Puts markdown `content` on the clipboard as rich HTML, with the
+/// markdown itself as the plain-text version.
+fn copy_html(content: &str, clipboard: &mut dyn ClipboardSink) -> Result<(), String> {
+    clipboard.set_html(&html::to_html(content), content)
+}
+
+/// Sends `what` to the system clipboard with `copy`, reporting how it went.
+fn to_clipboard(what: &str, copy: impl FnOnce(&mut dyn ClipboardSink) -> Result<(), String>) -> io::Result<()> {
+    match SystemClipboard::new().and_then(|mut clipboard| copy(&mut clipboard)) {
+        Ok(()) => {
+            println!("Copied {} to the clipboard", what);
+            Ok(())
+        }
+        Err(e) => {
+            eprintln!("Error: cannot copy to the clipboard: {}", e);
+            std::process::exit(1);
+        }
+    }
+}
+
+/// Handles `marko export --to html file.md` — writes a standalone HTML page
+/// (no pandoc needed) and exits. With `--self-contained`, pandoc embeds the
+/// images too; with `--to-clipboard`, the HTML goes to the clipboard.
+fn handle_html_export(
+    file: &std::path::Path,
+    output: Option<&std::path::Path>,
+    self_contained: bool,
+    clipboard: bool,
+) -> io::Result<()> {
+    if self_contained && !pandoc::is_available() {
+        eprintln!("Error: --self-contained needs pandoc, which is not installed.");
+        eprintln!("Install it from https://pandoc.org/installing.html");
+        std::process::exit(1);
+    }
+    let content = match std::fs::read_to_string(file) {
+        Ok(c) => c,
+        Err(e) => {
+            eprintln!("Error: cannot read {}: {}", file.display(), e);
+            std::process::exit(1);
+        }
+    };
+
+    if clipboard {
+        return to_clipboard("HTML", |sink| copy_html(&content, sink));
+    }
+
+    let html_path = match output {
+        Some(p) => p.to_path_buf(),
+        None => file.with_extension("html"),
+    };
+    let title = file
+        .file_stem()
+        .map(|s| s.to_string_lossy().into_owned())
+        .unwrap_or_default();
+
+    let result = if self_contained {
+        pandoc::md_to_self_contained_html(file, &html_path, &title, html::STYLE).map_err(|e| e.to_string())
+    } else {
+        std::fs::write(&html_path, html::to_html_document(&content, &title)).map_err(|e| e.to_string())
+    };
+    match result {
+        Ok(_) => {
+            println!("Exported to {}", html_path.display());
+            Ok(())
+        }
+        Err(e) => {
+            eprintln!("Export failed: {}", e);
+            std::process::exit(1);
+        }
+    }
+}
+
+/// Handles `marko theme` — shows where the custom theme lives, or with
+/// `--dump` prints the current one as TOML.
+fn handle_theme(dump: bool) -> io::Result<()> {
+    let config = Config::load().unwrap_or_default();
+    if let Err(e) = apply_themes(&config) {
+        eprintln!("{}", e);
+    }
+    if dump {
+        print!("{}", theme::active().to_toml());
+        return Ok(());
+    }
+    match theme::custom_path() {
+        Some(path) => println!("Custom theme: {}", path.display()),
+        None => println!("No config directory for a custom theme"),
+    }
+    println!("Using: {} (`marko theme --dump` prints it as TOML)", theme::active().name);
+    Ok(())
+}
+
+/// Handles `marko render file.md` — prints the rendered markdown as ANSI
+/// styled text (plain when `$NO_COLOR` is set), or with `--to-clipboard`
+/// copies it as plain text, and exits.
+fn handle_render(file: &std::path::Path, width: Option<usize>, clipboard: bool) -> io::Result<()> {
+    let content = match std::fs::read_to_string(file) {
+        Ok(c) => c,
+        Err(e) => {
+            eprintln!("Error: cannot read {}: {}", file.display(), e);
+            std::process::exit(1);
+        }
+    };
+    let width = width.unwrap_or_else(|| {
+        if io::stdout().is_terminal() {
+            crossterm::terminal::size().map_or(80, |(w, _)| w as usize)
+        } else {
+            80
+        }
+    });
+    let color = std::env::var_os("NO_COLOR").is_none_or(|v| v.is_empty());
+    if let Ok(config) = Config::load_for(file) {
+        if let Err(e) = apply_themes(&config) {
+            eprintln!("{}", e);
+        }
+    }
+
+    let lines = renderer::render_lines(&content, width);
+    if clipboard {
+        let text = renderer::lines_to_ansi(&lines, false);
+        return to_clipboard("the rendered text", |sink| sink.set_text(&text));
+    }
+    let mut stdout = io::stdout().lock();
+    match stdout.write_all(renderer::lines_to_ansi(&lines, color).as_bytes()) {
+        // A closed pipe (e.g. `| head`) isn't an error worth reporting
+        Err(e) if e.kind() == io::ErrorKind::BrokenPipe => Ok(()),
+        result => result,
+    }
+}
+
 /// Handles opening a .docx file: converts to .md, then opens the editor with docx state.
-fn handle_docx_open(docx_file: &PathBuf) -> io::Result<()> {
+fn handle_docx_open(
+    docx_file: &std::path::Path,
+    others: Vec<PathBuf>,
+    read_only: bool,
+) -> io::Result<()> {
     if !pandoc::is_available() {
         eprintln!("Error: pandoc is not installed.");
         eprintln!("Install it from https://pandoc.org/installing.html");
@@ -151,11 +487,16 @@ fn handle_docx_open(docx_file: &PathBuf) -> io::Result<()> {
         reference_doc: docx_path,
     };
 
-    run_editor(md_path, Some(docx_state))
+    run_editor(md_path, Some(docx_state), others, read_only)
 }
 
 /// Sets up the terminal, runs the TUI editor, and restores the terminal on exit.
-fn run_editor(file_path: PathBuf, docx_state: Option<app::DocxState>) -> io::Result<()> {
+fn run_editor(
+    file_path: PathBuf,
+    docx_state: Option<app::DocxState>,
+    others: Vec<PathBuf>,
+    read_only: bool,
+) -> io::Result<()> {
     // Setup panic hook to restore terminal
     let original_hook = panic::take_hook();
     panic::set_hook(Box::new(move |info| {
@@ -164,6 +505,7 @@ fn run_editor(file_path: PathBuf, docx_state: Option<app::DocxState>) -> io::Res
     }));
 
     // Setup terminal
+    TERMINAL_ACTIVE.store(true, Ordering::SeqCst);
     enable_raw_mode()?;
     let mut stdout = io::stdout();
     execute!(stdout, EnterAlternateScreen, EnableMouseCapture, EnableBracketedPaste)?;
@@ -172,7 +514,7 @@ fn run_editor(file_path: PathBuf, docx_state: Option<app::DocxState>) -> io::Res
     terminal.clear()?;
 
     // Run app
-    let result = run_app(&mut terminal, file_path, docx_state);
+    let result = run_app(&mut terminal, file_path, docx_state, others, read_only);
 
     // Restore terminal
     restore_terminal()?;
@@ -185,12 +527,66 @@ fn run_editor(file_path: PathBuf, docx_state: Option<app::DocxState>) -> io::Res
     Ok(())
 }
 
+/// Draws with the UI and code block themes `config` names, or the custom
+/// theme from `theme.toml`. Unknown names and a broken `theme.toml` fall
+/// back to the defaults and are reported.
+fn apply_themes(config: &Config) -> Result<(), String> {
+    let mut errors = Vec::new();
+    let custom = theme::load_custom().unwrap_or_else(|e| {
+        errors.push(format!("Ignoring theme: {}", e));
+        None
+    });
+    let ui_theme = match config.theme.as_deref() {
+        Some(name) => Theme::by_name(name).or_else(|| {
+            errors.push(format!("Unknown theme {:?}, using dark", name));
+            None
+        }),
+        None => custom,
+    };
+    theme::set_active(ui_theme.unwrap_or(&theme::DARK));
+    if let Err(e) = code_highlight::set_theme(config.code_theme.as_deref()) {
+        errors.push(e);
+    }
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors.join("; "))
+    }
+}
+
 fn run_app(
     terminal: &mut Terminal<CrosstermBackend<io::Stdout>>,
     file_path: PathBuf,
     docx_state: Option<app::DocxState>,
+    others: Vec<PathBuf>,
+    read_only: bool,
 ) -> io::Result<()> {
     let mut app = app::App::new(file_path);
+    app.file_list.extend(others);
+    app.read_only = read_only;
+
+    // A broken config shouldn't block editing: report it and keep the defaults.
+    let first_run = Config::take_first_run();
+    match Config::load_for(&app.buf.file_path) {
+        Ok(config) => {
+            app.buf.config = config;
+            app.show_startup_hint(first_run);
+        }
+        Err(e) => app.set_status_as(&format!("Ignoring config: {}", e), Severity::Error),
+    }
+    if let Err(e) = apply_themes(&app.buf.config) {
+        app.set_status_as(&e, Severity::Error);
+    }
+    app.apply_file_theme();
+    if !app.buf.file_path.exists() {
+        app.set_status("New file: written on first save");
+    }
+    app.buf.auto_wrap = app.buf.config.auto_wrap;
+    app.preview.set_cache_size(app.buf.config.image_cache_size);
+    app.vim_normal = app.buf.config.editing == Editing::Vim;
+    app.apply_diff_base();
+    #[cfg(feature = "spellcheck")]
+    app.load_spell_dictionary();
 
     if let Some(ds) = docx_state {
         let docx_name = ds
@@ -199,13 +595,44 @@ fn run_app(
             .and_then(|n| n.to_str())
             .unwrap_or("document.docx")
             .to_string();
-        app.docx_state = Some(ds);
+        app.buf.docx_state = Some(ds);
         app.set_status(&format!("Opened {} (editing as markdown)", docx_name));
     }
 
+    // Save the terminal's own title so it can be put back on exit
+    let set_title = app.buf.config.window_title;
+    if set_title {
+        write!(terminal.backend_mut(), "{}", PUSH_TITLE)?;
+    }
+    let mut last_title = String::new();
+
+    // SIGTERM / SIGINT (raw mode turns Ctrl+C into a key, so only `kill`
+    // sends it) leave the loop like Ctrl+Q, so the terminal gets restored,
+    // saving first only with `save_on_signal`.
+    // A second signal while the first is handled exits at once.
+    let terminate = Arc::new(AtomicBool::new(false));
+    for signal in [SIGTERM, SIGINT] {
+        signal_hook::flag::register_conditional_shutdown(signal, 1, Arc::clone(&terminate))?;
+        signal_hook::flag::register(signal, Arc::clone(&terminate))?;
+    }
+
     loop {
+        if terminate.load(Ordering::Relaxed) {
+            app.quit_on_signal();
+            break;
+        }
+
         app.render_frame(terminal)?;
 
+        if set_title {
+            let title = title_escape(app.display_name(), app.buf.modified, app.mode == app::Mode::Preview);
+            if title != last_title {
+                write!(terminal.backend_mut(), "{}", title)?;
+                terminal.backend_mut().flush()?;
+                last_title = title;
+            }
+        }
+
         // Block up to 100ms waiting for the first event (prevents busy-loop,
         // gives tick() a chance to run ~10x/sec for timer expiry).
         if event::poll(Duration::from_millis(100))? {
@@ -226,11 +653,39 @@ fn run_app(
             break;
         }
     }
+    app.stop_focus_timer();
 
+    if set_title {
+        write!(terminal.backend_mut(), "{}", POP_TITLE)?;
+        terminal.backend_mut().flush()?;
+    }
     Ok(())
 }
 
+/// XTWINOPS: save / restore the window title on the terminal's title stack.
+const PUSH_TITLE: &str = "\x1b[22;0t";
+const POP_TITLE: &str = "\x1b[23;0t";
+
+/// OSC 2 escape setting the terminal window title to `filename`, with a
+/// bullet when it has unsaved changes and a tag in preview mode. Control
+/// chars are dropped so a strange filename can't end the escape early.
+fn title_escape(filename: &str, modified: bool, preview: bool) -> String {
+    let name: String = filename.chars().filter(|c| !c.is_control()).collect();
+    let modified = if modified { " \u{2022}" } else { "" };
+    let mode = if preview { " (preview)" } else { "" };
+    format!("\x1b]2;{}{}{} - marko\x07", name, modified, mode)
+}
+
+/// True between terminal setup and restore, so restoring twice (from the
+/// panic hook and on exit) is harmless.
+static TERMINAL_ACTIVE: AtomicBool = AtomicBool::new(false);
+
+/// Leaves raw mode and the alternate screen. Does nothing if the terminal
+/// isn't set up (or was already restored).
 fn restore_terminal() -> io::Result<()> {
+    if !TERMINAL_ACTIVE.swap(false, Ordering::SeqCst) {
+        return Ok(());
+    }
     disable_raw_mode()?;
     execute!(
         io::stdout(),
@@ -240,3 +695,194 @@ fn restore_terminal() -> io::Result<()> {
     )?;
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parses_multiple_files() {
+        let cli = Cli::try_parse_from(["marko", "a.md", "b.md", "notes/c.md"]).unwrap();
+        assert!(cli.command.is_none());
+        assert_eq!(
+            cli.files,
+            vec![PathBuf::from("a.md"), PathBuf::from("b.md"), PathBuf::from("notes/c.md")]
+        );
+    }
+
+    #[test]
+    fn test_read_only_flag_parses() {
+        let cli = Cli::try_parse_from(["marko", "--read-only", "a.md"]).unwrap();
+        assert!(cli.read_only);
+        assert_eq!(cli.files, vec![PathBuf::from("a.md")]);
+        assert!(!Cli::try_parse_from(["marko", "a.md"]).unwrap().read_only);
+    }
+
+    #[test]
+    fn test_missing_file_flag_parses() {
+        let cli = Cli::try_parse_from(["marko", "--missing-file", "buffer", "a.md"]).unwrap();
+        assert_eq!(cli.missing_file, Some(MissingFile::Buffer));
+        assert!(Cli::try_parse_from(["marko", "--missing-file", "ask", "a.md"]).is_err());
+    }
+
+    #[test]
+    fn test_buffer_mode_leaves_missing_file_uncreated() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let file = dir.path().join("new.md");
+        assert!(open_missing_file(&file, MissingFile::Buffer).unwrap());
+        assert!(!file.exists());
+        assert!(open_missing_file(&file, MissingFile::Create).unwrap());
+        assert!(file.exists());
+    }
+
+    #[test]
+    fn test_restore_terminal_is_idempotent() {
+        // Never set up here, so nothing is written to the test's stdout
+        assert!(restore_terminal().is_ok());
+        assert!(restore_terminal().is_ok());
+        assert!(!TERMINAL_ACTIVE.load(Ordering::SeqCst));
+    }
+
+    #[test]
+    fn test_title_escape() {
+        assert_eq!(title_escape("notes.md", false, false), "\x1b]2;notes.md - marko\x07");
+        assert_eq!(
+            title_escape("notes.md", true, false),
+            "\x1b]2;notes.md \u{2022} - marko\x07"
+        );
+        assert_eq!(
+            title_escape("notes.md", false, true),
+            "\x1b]2;notes.md (preview) - marko\x07"
+        );
+        assert_eq!(title_escape("a\x07b.md", false, false), "\x1b]2;ab.md - marko\x07");
+    }
+
+    #[test]
+    fn test_export_to_html_parses() {
+        let cli = Cli::try_parse_from(["marko", "export", "a.md", "--to", "html"]).unwrap();
+        assert!(matches!(
+            cli.command,
+            Some(Commands::Export { to: ExportFormat::Html, .. })
+        ));
+        let cli = Cli::try_parse_from(["marko", "export", "a.md"]).unwrap();
+        assert!(matches!(
+            cli.command,
+            Some(Commands::Export { to: ExportFormat::Docx, .. })
+        ));
+    }
+
+    #[test]
+    fn test_export_self_contained_parses() {
+        let cli = Cli::try_parse_from(["marko", "export", "a.md", "--to", "html", "--self-contained"]).unwrap();
+        assert!(matches!(
+            cli.command,
+            Some(Commands::Export { to: ExportFormat::Html, self_contained: true, .. })
+        ));
+    }
+
+    /// Keeps what was copied, in place of the system clipboard.
+    #[derive(Default)]
+    struct RecordingClipboard {
+        text: Option<String>,
+        html: Option<(String, String)>,
+    }
+
+    impl ClipboardSink for RecordingClipboard {
+        fn set_text(&mut self, text: &str) -> Result<(), String> {
+            self.text = Some(text.to_string());
+            Ok(())
+        }
+
+        fn set_html(&mut self, html: &str, alt: &str) -> Result<(), String> {
+            self.html = Some((html.to_string(), alt.to_string()));
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_copy_html_puts_rendered_html_on_the_clipboard() {
+        let mut clipboard = RecordingClipboard::default();
+        copy_html("# Hi\n\nSome **bold** text.", &mut clipboard).unwrap();
+        let (html, alt) = clipboard.html.unwrap();
+        assert!(html.contains("<h1>Hi</h1>"));
+        assert!(html.contains("<strong>bold</strong>"));
+        assert_eq!(alt, "# Hi\n\nSome **bold** text.", "markdown as the plain-text version");
+        assert!(clipboard.text.is_none());
+    }
+
+    #[test]
+    fn test_to_clipboard_flags_parse() {
+        let cli = Cli::try_parse_from(["marko", "export", "a.md", "--to", "html", "--to-clipboard"]).unwrap();
+        assert!(matches!(cli.command, Some(Commands::Export { to_clipboard: true, .. })));
+        let cli = Cli::try_parse_from(["marko", "render", "a.md", "--to-clipboard"]).unwrap();
+        assert!(matches!(cli.command, Some(Commands::Render { to_clipboard: true, .. })));
+        assert!(Cli::try_parse_from(["marko", "export", "a.md", "--to-clipboard", "-o", "b.html"]).is_err());
+    }
+
+    #[test]
+    fn test_render_subcommand_parses_width() {
+        let cli = Cli::try_parse_from(["marko", "render", "a.md", "--width", "60"]).unwrap();
+        assert!(matches!(
+            cli.command,
+            Some(Commands::Render { width: Some(60), .. })
+        ));
+    }
+
+    #[test]
+    fn test_themes_subcommand_parses() {
+        let cli = Cli::try_parse_from(["marko", "themes"]).unwrap();
+        assert!(matches!(cli.command, Some(Commands::Themes)));
+    }
+
+    #[test]
+    fn test_theme_subcommand_parses_dump() {
+        let cli = Cli::try_parse_from(["marko", "theme", "--dump"]).unwrap();
+        assert!(matches!(cli.command, Some(Commands::Theme { dump: true })));
+    }
+
+    #[test]
+    fn test_export_subcommand_still_parses() {
+        let cli = Cli::try_parse_from(["marko", "export", "a.md"]).unwrap();
+        assert!(matches!(cli.command, Some(Commands::Export { .. })));
+        assert!(cli.files.is_empty());
+    }
+
+    #[test]
+    fn test_quick_switch_skips_docx() {
+        let files = quick_switch_files(
+            vec![PathBuf::from("b.md"), PathBuf::from("report.docx")].into_iter(),
+            &WalkOptions::default(),
+        );
+        assert_eq!(files.len(), 1);
+        assert!(files[0].is_absolute());
+        assert!(files[0].ends_with("b.md"));
+    }
+
+    #[test]
+    fn test_quick_switch_expands_folders_with_the_workspace_walk() {
+        let dir = tempfile::TempDir::new().unwrap();
+        std::fs::write(dir.path().join(".gitignore"), "build/\n").unwrap();
+        std::fs::write(dir.path().join("a.md"), "").unwrap();
+        std::fs::write(dir.path().join("notes.txt"), "").unwrap();
+        std::fs::create_dir(dir.path().join("build")).unwrap();
+        std::fs::write(dir.path().join("build/out.md"), "").unwrap();
+        let args = || vec![dir.path().to_path_buf()].into_iter();
+
+        let files = quick_switch_files(args(), &WalkOptions::default());
+        assert_eq!(files.len(), 1, "{:?}", files);
+        assert!(files[0].ends_with("a.md"));
+
+        let ignored = WalkOptions {
+            include_ignored: true,
+            ..WalkOptions::default()
+        };
+        let all = quick_switch_files(args(), &ignored);
+        assert!(all.iter().any(|f| f.ends_with("build/out.md")), "{:?}", all);
+
+        let excluded = WalkOptions {
+            include_ignored: true,
+            exclude_dirs: vec!["build".to_string()],
+        };
+        assert_eq!(quick_switch_files(args(), &excluded), files, "exclude_dirs applies here too");
+    }
+}
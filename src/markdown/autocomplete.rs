@@ -79,8 +79,7 @@ pub fn analyze_line_for_continuation(line: &str) -> Continuation {
         }
     }
     // Also handle "N." at end (empty ordered item)
-    if trimmed.ends_with('.') {
-        let num_part = &trimmed[..trimmed.len() - 1];
+    if let Some(num_part) = trimmed.strip_suffix('.') {
         if num_part.parse::<u64>().is_ok() {
             return Continuation::ClearLine;
         }
@@ -103,6 +102,12 @@ pub fn auto_close_pair(ch: char) -> Option<char> {
     }
 }
 
+/// Returns true if `ch` closes an auto-close pair, so typing it just
+/// before the same character can step over it instead.
+pub fn is_auto_close_closer(ch: char) -> bool {
+    matches!(ch, ')' | ']' | '}' | '`' | '"' | '\'')
+}
+
 /// Returns true if backtick auto-pairing should be skipped.
 /// Skip when the previous character is also a backtick (code fence typing).
 pub fn should_skip_backtick_pair(prev_char: Option<char>) -> bool {
@@ -275,6 +280,15 @@ mod tests {
         assert_eq!(auto_close_pair('a'), None);
     }
 
+    #[test]
+    fn test_auto_close_closers() {
+        for ch in [')', ']', '}', '`', '"', '\''] {
+            assert!(is_auto_close_closer(ch), "{}", ch);
+        }
+        assert!(!is_auto_close_closer('('));
+        assert!(!is_auto_close_closer('a'));
+    }
+
     #[test]
     fn test_skip_backtick_after_backtick() {
         assert!(should_skip_backtick_pair(Some('`')));
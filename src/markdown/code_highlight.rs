@@ -1,11 +1,11 @@
-use std::sync::OnceLock;
+use std::sync::{OnceLock, RwLock};
 
 use ratatui::{
     style::Style,
     text::{Line, Span},
 };
 use syntect::easy::HighlightLines;
-use syntect::highlighting::ThemeSet;
+use syntect::highlighting::{Theme, ThemeSet};
 use syntect::parsing::SyntaxSet;
 use syntect::util::LinesWithEndings;
 
@@ -13,6 +13,9 @@ use crate::theme;
 
 static SYNTAX_SET: OnceLock<SyntaxSet> = OnceLock::new();
 static THEME_SET: OnceLock<ThemeSet> = OnceLock::new();
+/// Name of the theme code is highlighted with; empty to follow the UI
+/// theme's `code_theme`.
+static CODE_THEME: RwLock<String> = RwLock::new(String::new());
 
 /// Returns a shared reference to the default SyntaxSet, initializing if needed.
 pub fn syntax_set() -> &'static SyntaxSet {
@@ -33,13 +36,45 @@ pub fn ensure_loaded() {
     });
 }
 
-/// Non-blocking check: returns references only if both statics are already initialized.
-pub fn try_get() -> Option<(&'static SyntaxSet, &'static ThemeSet)> {
-    Some((SYNTAX_SET.get()?, THEME_SET.get()?))
+/// Non-blocking check: returns the syntaxes and the selected theme only if
+/// both statics are already initialized.
+pub fn try_get() -> Option<(&'static SyntaxSet, &'static Theme)> {
+    Some((SYNTAX_SET.get()?, selected_theme(THEME_SET.get()?)))
+}
+
+/// Names of the bundled themes `code_theme` can pick, sorted.
+pub fn theme_names() -> Vec<&'static str> {
+    theme_set().themes.keys().map(String::as_str).collect()
+}
+
+/// Highlights code with the theme called `name` from now on, or with the
+/// UI theme's own for None. An unknown name is treated as None and is an
+/// error to show the user.
+pub fn set_theme(name: Option<&str>) -> Result<(), String> {
+    let mut selected = CODE_THEME.write().unwrap_or_else(|e| e.into_inner());
+    selected.clear();
+    match name {
+        Some(name) if theme_set().themes.contains_key(name) => {
+            selected.push_str(name);
+            Ok(())
+        }
+        Some(name) => Err(format!("Unknown code_theme {:?} (see `marko themes`)", name)),
+        None => Ok(()),
+    }
+}
+
+/// The theme picked with `set_theme` out of `themes`.
+fn selected_theme(themes: &ThemeSet) -> &Theme {
+    let selected = CODE_THEME.read().unwrap_or_else(|e| e.into_inner());
+    themes
+        .themes
+        .get(selected.as_str())
+        .or_else(|| themes.themes.get(theme::active().code_theme))
+        .unwrap_or_else(|| &themes.themes[theme::DARK.code_theme])
 }
 
 /// Map common language aliases to tokens that syntect's default set recognizes.
-fn resolve_lang<'a>(lang: &'a str) -> &'a str {
+fn resolve_lang(lang: &str) -> &str {
     match lang {
         "typescript" | "ts" => "javascript",
         "tsx" | "jsx" => "javascript",
@@ -53,9 +88,69 @@ fn resolve_lang<'a>(lang: &'a str) -> &'a str {
     }
 }
 
+/// A fenced code block's info string, split into the language and the
+/// options in braces after it: ```` ```rust {linenos start=10 highlight=2-3,5} ````.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct FenceInfo {
+    pub lang: String,
+    /// Number of the first line when line numbers are shown (`linenos`,
+    /// or any `start=N`).
+    pub first_line_number: Option<usize>,
+    /// Inclusive ranges of lines to highlight, counted from 1 within the block.
+    pub highlight: Vec<(usize, usize)>,
+}
+
+impl FenceInfo {
+    fn is_highlighted(&self, line: usize) -> bool {
+        self.highlight.iter().any(|&(from, to)| (from..=to).contains(&line))
+    }
+}
+
+/// Parses a code fence info string (see `FenceInfo`). Unknown options and
+/// malformed values are ignored.
+pub fn parse_fence_info(info: &str) -> FenceInfo {
+    let info = info.trim();
+    let (head, options) = match info.find('{') {
+        Some(i) => (&info[..i], info[i + 1..].trim_end_matches('}')),
+        None => (info, ""),
+    };
+    let mut fence = FenceInfo {
+        lang: head.split_whitespace().next().unwrap_or("").to_string(),
+        ..FenceInfo::default()
+    };
+    for option in options.split_whitespace() {
+        match option.split_once('=') {
+            None if option == "linenos" => {
+                fence.first_line_number.get_or_insert(1);
+            }
+            Some(("start", n)) => {
+                if let Ok(n) = n.parse() {
+                    fence.first_line_number = Some(n);
+                }
+            }
+            Some(("highlight", ranges)) => {
+                fence.highlight.extend(ranges.split(',').filter_map(|range| {
+                    let (from, to) = range.split_once('-').unwrap_or((range, range));
+                    Some((from.trim().parse().ok()?, to.trim().parse().ok()?))
+                }));
+            }
+            _ => {}
+        }
+    }
+    fence
+}
+
 pub fn highlight_code(code: &str, lang: &str, width: usize) -> Vec<Line<'static>> {
+    let info = FenceInfo { lang: lang.to_string(), ..FenceInfo::default() };
+    highlight_fenced_code(code, &info, width)
+}
+
+/// Highlights a code block with its fence options: a line number gutter
+/// and highlighted lines.
+pub fn highlight_fenced_code(code: &str, info: &FenceInfo, width: usize) -> Vec<Line<'static>> {
+    let lang = info.lang.as_str();
     let ss = syntax_set();
-    let syntax_theme = &theme_set().themes["base16-ocean.dark"];
+    let syntax_theme = selected_theme(theme_set());
 
     let syntax = if lang.is_empty() {
         ss.find_syntax_plain_text()
@@ -67,33 +162,54 @@ pub fn highlight_code(code: &str, lang: &str, width: usize) -> Vec<Line<'static>
 
     let mut highlighter = HighlightLines::new(syntax, syntax_theme);
     let mut code_lines: Vec<Line<'static>> = Vec::new();
-    let border_style = Style::default().fg(theme::BORDER).bg(theme::CODE_BG);
-    let bg_style = Style::default().bg(theme::CODE_BG);
+    let border_style = Style::default().fg(theme::active().border).bg(theme::active().code_bg);
+
+    // Each line starts with a two-space indent, or its number and a rule
+    let number_width = info
+        .first_line_number
+        .map(|first| (first + code.lines().count().max(1) - 1).to_string().len());
+    let line_bg = |index: usize| {
+        if info.is_highlighted(index + 1) {
+            theme::active().code_highlight_bg
+        } else {
+            theme::active().code_bg
+        }
+    };
+    let line_start = |index: usize| -> (Span<'static>, usize) {
+        let bg = line_bg(index);
+        match (info.first_line_number, number_width) {
+            (Some(first), Some(digits)) => {
+                let prefix = format!(" {:>digits$} \u{2502} ", first + index);
+                (Span::styled(prefix, Style::default().fg(theme::active().line_number).bg(bg)), digits + 4)
+            }
+            _ => (Span::styled("  ", Style::default().bg(bg)), 2),
+        }
+    };
 
-    for line in LinesWithEndings::from(code) {
+    for (index, line) in LinesWithEndings::from(code).enumerate() {
+        let bg = line_bg(index);
+        let bg_style = Style::default().bg(bg);
+        let (prefix, mut col) = line_start(index);
+        let mut spans = vec![prefix];
         let regions = match highlighter.highlight_line(line, ss) {
             Ok(r) => r,
             Err(_) => {
-                let text = format!("  {}", line.trim_end_matches('\n'));
-                let text_len = text.len();
-                let mut spans = vec![Span::styled(text, Style::default().fg(theme::CODE).bg(theme::CODE_BG))];
-                pad_to_width(&mut spans, text_len, width, bg_style);
+                let text = line.trim_end_matches('\n').to_string();
+                col += text.len();
+                spans.push(Span::styled(text, Style::default().fg(theme::active().code).bg(bg)));
+                pad_to_width(&mut spans, col, width, bg_style);
                 code_lines.push(Line::from(spans));
                 continue;
             }
         };
 
-        let mut spans: Vec<Span<'static>> = Vec::new();
-        spans.push(Span::styled("  ", bg_style));
-        let mut col = 2usize;
-
         for (style, content) in regions {
             let text = content.trim_end_matches('\n');
             if text.is_empty() {
                 continue;
             }
             let fg = ratatui::style::Color::Rgb(style.foreground.r, style.foreground.g, style.foreground.b);
-            let span_style = Style::default().fg(fg).bg(theme::CODE_BG);
+            let span_style = Style::default().fg(fg).bg(bg);
             col += text.len();
             spans.push(Span::styled(text.to_string(), span_style));
         }
@@ -103,11 +219,15 @@ pub fn highlight_code(code: &str, lang: &str, width: usize) -> Vec<Line<'static>
     }
 
     if code_lines.is_empty() && !code.is_empty() {
-        for code_line in code.lines() {
-            let text = format!("  {}", code_line);
-            let text_len = text.len();
-            let mut spans = vec![Span::styled(text, Style::default().fg(theme::CODE).bg(theme::CODE_BG))];
-            pad_to_width(&mut spans, text_len, width, bg_style);
+        for (index, code_line) in code.lines().enumerate() {
+            let bg = line_bg(index);
+            let (prefix, col) = line_start(index);
+            let text_len = col + code_line.len();
+            let mut spans = vec![
+                prefix,
+                Span::styled(code_line.to_string(), Style::default().fg(theme::active().code).bg(bg)),
+            ];
+            pad_to_width(&mut spans, text_len, width, Style::default().bg(bg));
             code_lines.push(Line::from(spans));
         }
     }
@@ -156,8 +276,8 @@ pub fn find_code_fence_regions(lines: &[String]) -> Vec<CodeFenceRegion> {
 
     while i < lines.len() {
         let trimmed = lines[i].trim_start();
-        if trimmed.starts_with("```") {
-            let lang = trimmed[3..].trim().to_string();
+        if let Some(info) = trimmed.strip_prefix("```") {
+            let lang = parse_fence_info(info).lang;
             let start = i;
             i += 1;
 
@@ -190,6 +310,49 @@ pub fn find_code_fence_regions(lines: &[String]) -> Vec<CodeFenceRegion> {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_parse_fence_info_options() {
+        let info = parse_fence_info("rust {linenos start=10 highlight=2-3,5}");
+        assert_eq!(
+            info,
+            FenceInfo {
+                lang: "rust".to_string(),
+                first_line_number: Some(10),
+                highlight: vec![(2, 3), (5, 5)],
+            }
+        );
+        assert_eq!(parse_fence_info("py {linenos}").first_line_number, Some(1));
+        assert_eq!(parse_fence_info("py {start=0}").first_line_number, Some(0));
+    }
+
+    #[test]
+    fn test_parse_fence_info_plain_and_malformed() {
+        assert_eq!(parse_fence_info("rust"), FenceInfo { lang: "rust".into(), ..FenceInfo::default() });
+        assert_eq!(parse_fence_info(""), FenceInfo::default());
+        let info = parse_fence_info("rust{highlight=x-2,4 start=ten color=red}");
+        assert_eq!(info.lang, "rust");
+        assert_eq!(info.first_line_number, None);
+        assert_eq!(info.highlight, vec![(4, 4)]);
+    }
+
+    #[test]
+    fn test_line_numbers_and_highlighted_lines_render() {
+        let info = parse_fence_info("text {start=9 highlight=2}");
+        let lines = highlight_fenced_code("a\nb\nc\n", &info, 40);
+        let text = |line: &Line| line.spans.iter().map(|s| s.content.as_ref()).collect::<String>();
+        assert!(text(&lines[1]).starts_with("  9 \u{2502} a"), "{:?}", text(&lines[1]));
+        assert!(text(&lines[3]).starts_with(" 11 \u{2502} c"), "numbers right-aligned");
+        assert_eq!(lines[2].spans[0].style.bg, Some(theme::active().code_highlight_bg));
+        assert_eq!(lines[1].spans[0].style.bg, Some(theme::active().code_bg));
+        assert!(lines.iter().all(|l| l.width() == 40), "padded to the block width");
+    }
+
+    #[test]
+    fn test_fence_region_language_drops_options() {
+        let lines: Vec<String> = ["```rust {linenos}", "fn a() {}", "```"].map(String::from).to_vec();
+        assert_eq!(find_code_fence_regions(&lines)[0].language, "rust");
+    }
+
     #[test]
     fn test_highlight_typescript_has_colored_spans() {
         let code = "const a = 5;\n";
@@ -215,6 +378,19 @@ mod tests {
         assert!(has_colored_fg, "Rust code should have syntax-colored spans");
     }
 
+    #[test]
+    fn test_unknown_theme_follows_ui_theme() {
+        // Every built-in UI theme names a bundled code theme
+        for ui_theme in theme::PRESETS {
+            assert!(theme_names().contains(&ui_theme.code_theme), "{}", ui_theme.name);
+        }
+        let err = set_theme(Some("no-such-theme")).unwrap_err();
+        assert!(err.contains("no-such-theme"));
+        let expected = &theme_set().themes[theme::active().code_theme];
+        assert!(std::ptr::eq(selected_theme(theme_set()), expected));
+        assert_eq!(set_theme(None), Ok(()));
+    }
+
     #[test]
     fn test_resolve_lang_aliases() {
         assert_eq!(resolve_lang("typescript"), "javascript");
@@ -0,0 +1,211 @@
+//! Graphviz, PlantUML and Mermaid diagrams in fenced code blocks
+//! (```` ```dot ````, ```` ```plantuml ````, ```` ```mermaid ````), drawn as
+//! images in the preview when the `dot`, `plantuml` or `mmdc` (mermaid-cli)
+//! command is installed. Without it, the block shows as code.
+//!
+//! Rendered diagrams are PNGs in the user's cache directory
+//! (`$XDG_CACHE_HOME/marko/diagrams`, falling back to `~/.cache`), named by
+//! a hash of the block's source, so an unchanged diagram is only rendered
+//! once. A shared temp directory would let other users plant the images.
+
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::OnceLock;
+
+/// A diagram language with a command to render it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DiagramKind {
+    Dot,
+    PlantUml,
+    Mermaid,
+}
+
+impl DiagramKind {
+    /// The diagram language a code fence's language names, if any.
+    pub fn from_lang(lang: &str) -> Option<Self> {
+        match lang.to_ascii_lowercase().as_str() {
+            "dot" | "graphviz" | "gv" => Some(Self::Dot),
+            "plantuml" | "puml" => Some(Self::PlantUml),
+            "mermaid" | "mmd" => Some(Self::Mermaid),
+            _ => None,
+        }
+    }
+
+    /// The command that renders this kind of diagram.
+    pub fn command(self) -> &'static str {
+        match self {
+            Self::Dot => "dot",
+            Self::PlantUml => "plantuml",
+            Self::Mermaid => "mmdc",
+        }
+    }
+
+    /// True if `command` is on the PATH (looked up once per run).
+    pub fn is_available(self) -> bool {
+        static DOT: OnceLock<bool> = OnceLock::new();
+        static PLANTUML: OnceLock<bool> = OnceLock::new();
+        static MERMAID: OnceLock<bool> = OnceLock::new();
+        let found = match self {
+            Self::Dot => &DOT,
+            Self::PlantUml => &PLANTUML,
+            Self::Mermaid => &MERMAID,
+        };
+        *found.get_or_init(|| on_path(self.command()))
+    }
+}
+
+/// A diagram to draw in place of a code block.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Diagram {
+    pub kind: DiagramKind,
+    pub source: String,
+}
+
+impl Diagram {
+    /// The diagram for a code block in `lang`, if it is a diagram language
+    /// whose command is installed.
+    pub fn for_code_block(lang: &str, source: &str) -> Option<Self> {
+        let kind = DiagramKind::from_lang(lang).filter(|kind| kind.is_available())?;
+        Some(Self { kind, source: source.to_string() })
+    }
+
+    /// Hex hash of the kind and source: the same diagram always gets the
+    /// same key, across runs too.
+    pub fn cache_key(&self) -> String {
+        // FNV-1a, stable where std's hasher isn't
+        let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+        let bytes = self.kind.command().bytes().chain([0]).chain(self.source.bytes());
+        for byte in bytes {
+            hash ^= byte as u64;
+            hash = hash.wrapping_mul(0x0000_0100_0000_01b3);
+        }
+        format!("{:016x}", hash)
+    }
+
+    /// Where the rendered PNG is cached, or None if no home/cache directory
+    /// is known.
+    pub fn cache_path(&self) -> Option<PathBuf> {
+        let base = std::env::var_os("XDG_CACHE_HOME")
+            .filter(|v| !v.is_empty())
+            .map(PathBuf::from)
+            .or_else(|| std::env::var_os("HOME").map(|h| PathBuf::from(h).join(".cache")))?;
+        Some(base.join("marko").join("diagrams").join(format!("{}.png", self.cache_key())))
+    }
+
+    /// Arguments for the render command; `tmp` is where mmdc writes the PNG
+    /// (the others write it to stdout).
+    fn args<'t>(&self, tmp: &'t str) -> Vec<&'t str> {
+        match self.kind {
+            DiagramKind::Dot => vec!["-Tpng"],
+            DiagramKind::PlantUml => vec!["-tpng", "-pipe"],
+            // mmdc can't write a PNG to stdout, so it writes to `tmp` itself
+            DiagramKind::Mermaid => vec!["--input", "-", "--output", tmp, "--quiet"],
+        }
+    }
+
+    /// Renders the diagram to a PNG at `path`. Slow; call it off the main
+    /// thread.
+    pub fn render_to(&self, path: &Path) -> Result<(), String> {
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+        }
+        let tmp = tmp_path(path);
+        let _ = std::fs::remove_file(&tmp);
+        let mut child = Command::new(self.kind.command())
+            .args(self.args(&tmp.to_string_lossy()))
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|e| format!("{}: {}", self.kind.command(), e))?;
+        if let Some(mut stdin) = child.stdin.take() {
+            stdin.write_all(self.source.as_bytes()).map_err(|e| e.to_string())?;
+        }
+        let output = child.wait_with_output().map_err(|e| e.to_string())?;
+        let written = match self.kind {
+            DiagramKind::Mermaid => tmp.is_file(),
+            _ => !output.stdout.is_empty(),
+        };
+        if !output.status.success() || !written {
+            let _ = std::fs::remove_file(&tmp);
+            return Err(String::from_utf8_lossy(&output.stderr).trim().to_string());
+        }
+        let saved = match self.kind {
+            DiagramKind::Mermaid => Ok(()),
+            _ => std::fs::write(&tmp, &output.stdout),
+        };
+        saved.and_then(|()| std::fs::rename(&tmp, path)).map_err(|e| e.to_string())
+    }
+}
+
+/// Where a diagram bound for `path` is written first. It sits next to
+/// `path` and is renamed over it, so a half-written file is never drawn.
+fn tmp_path(path: &Path) -> PathBuf {
+    path.with_extension("tmp.png")
+}
+
+/// True if an executable named `command` is in a `PATH` directory.
+fn on_path(command: &str) -> bool {
+    let Some(path) = std::env::var_os("PATH") else {
+        return false;
+    };
+    std::env::split_paths(&path).any(|dir| {
+        let candidate = dir.join(command);
+        candidate.is_file() || candidate.with_extension("exe").is_file()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn diagram(kind: DiagramKind, source: &str) -> Diagram {
+        Diagram { kind, source: source.to_string() }
+    }
+
+    #[test]
+    fn test_from_lang() {
+        assert_eq!(DiagramKind::from_lang("dot"), Some(DiagramKind::Dot));
+        assert_eq!(DiagramKind::from_lang("Graphviz"), Some(DiagramKind::Dot));
+        assert_eq!(DiagramKind::from_lang("plantuml"), Some(DiagramKind::PlantUml));
+        assert_eq!(DiagramKind::from_lang("puml"), Some(DiagramKind::PlantUml));
+        assert_eq!(DiagramKind::from_lang("mermaid"), Some(DiagramKind::Mermaid));
+        assert_eq!(DiagramKind::from_lang("mmd"), Some(DiagramKind::Mermaid));
+        assert_eq!(DiagramKind::from_lang("markdown"), None);
+        assert_eq!(DiagramKind::from_lang(""), None);
+    }
+
+    #[test]
+    fn test_cache_key_follows_content() {
+        let a = diagram(DiagramKind::Dot, "digraph { a -> b }");
+        assert_eq!(a.cache_key(), diagram(DiagramKind::Dot, "digraph { a -> b }").cache_key());
+        assert_eq!(a.cache_key().len(), 16);
+        assert_ne!(a.cache_key(), diagram(DiagramKind::Dot, "digraph { a -> c }").cache_key());
+        assert_ne!(a.cache_key(), diagram(DiagramKind::PlantUml, "digraph { a -> b }").cache_key());
+        assert_ne!(a.cache_key(), diagram(DiagramKind::Mermaid, "digraph { a -> b }").cache_key());
+        if let Some(path) = a.cache_path() {
+            assert!(path.ends_with(format!("marko/diagrams/{}.png", a.cache_key())));
+        }
+    }
+
+    #[test]
+    fn test_mermaid_writes_to_tmp_beside_the_cache_path() {
+        let path = Path::new("/cache/marko/diagrams/0123456789abcdef.png");
+        let tmp = tmp_path(path);
+        assert_eq!(tmp, Path::new("/cache/marko/diagrams/0123456789abcdef.tmp.png"));
+        let tmp_arg = tmp.to_string_lossy();
+        let mermaid = diagram(DiagramKind::Mermaid, "graph TD; a-->b");
+        assert_eq!(
+            mermaid.args(&tmp_arg),
+            ["--input", "-", "--output", "/cache/marko/diagrams/0123456789abcdef.tmp.png", "--quiet"]
+        );
+        assert_eq!(diagram(DiagramKind::Dot, "").args(&tmp_arg), ["-Tpng"]);
+        assert_eq!(diagram(DiagramKind::PlantUml, "").args(&tmp_arg), ["-tpng", "-pipe"]);
+    }
+
+    #[test]
+    fn test_missing_command_is_not_on_path() {
+        assert!(!on_path("marko-no-such-command"));
+    }
+}
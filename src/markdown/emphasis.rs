@@ -0,0 +1,305 @@
+//! Detection of inline emphasis (`**bold**`, `*italic*`, `~~strike~~`) and
+//! other markdown markers in a single editor line, for styling the raw
+//! markdown as it is typed.
+//!
+//! This is a lightweight approximation of CommonMark's delimiter rules, good
+//! enough for live highlighting: code spans and backslash escapes are never
+//! emphasis, openers must be followed and closers preceded by non-whitespace,
+//! and `_` only counts at word boundaries.
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EmphasisKind {
+    Bold,
+    Italic,
+    Strikethrough,
+}
+
+/// An emphasis span covering chars `start..end` of the line, including the
+/// `marker_len` delimiter chars at each end.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EmphasisSpan {
+    pub kind: EmphasisKind,
+    pub start: usize,
+    pub end: usize,
+    pub marker_len: usize,
+}
+
+/// Finds the emphasis spans in `line`, with positions counted in chars.
+/// Bold spans come first; italic spans may nest inside them.
+pub fn find_emphasis(line: &str) -> Vec<EmphasisSpan> {
+    let chars: Vec<char> = line.chars().collect();
+    // Chars that can never be delimiters: code spans and escaped chars
+    let mut taken = literal_mask(&chars);
+    let mut spans = Vec::new();
+
+    for (delim, kind) in [
+        ("**", EmphasisKind::Bold),
+        ("__", EmphasisKind::Bold),
+        ("~~", EmphasisKind::Strikethrough),
+        ("*", EmphasisKind::Italic),
+        ("_", EmphasisKind::Italic),
+    ] {
+        let delim: Vec<char> = delim.chars().collect();
+        let len = delim.len();
+        let mut i = 0;
+        while i + len <= chars.len() {
+            if !is_opener(&chars, &taken, i, &delim) {
+                i += 1;
+                continue;
+            }
+            let Some(close) = (i + len + 1..=chars.len() - len)
+                .find(|&j| is_closer(&chars, &taken, j, &delim))
+            else {
+                i += 1;
+                continue;
+            };
+            for k in (i..i + len).chain(close..close + len) {
+                taken[k] = true;
+            }
+            spans.push(EmphasisSpan {
+                kind,
+                start: i,
+                end: close + len,
+                marker_len: len,
+            });
+            i = close + len;
+        }
+    }
+    spans
+}
+
+/// Marks chars inside inline code spans (backticks included) and chars
+/// escaped by a backslash (backslash included).
+fn literal_mask(chars: &[char]) -> Vec<bool> {
+    let mut mask = vec![false; chars.len()];
+    let mut i = 0;
+    while i < chars.len() {
+        match chars[i] {
+            '\\' if i + 1 < chars.len() => {
+                mask[i] = true;
+                mask[i + 1] = true;
+                i += 2;
+            }
+            '`' => {
+                let (end, _) = code_span_at(chars, i);
+                mask[i..end].iter_mut().for_each(|m| *m = true);
+                i = end;
+            }
+            _ => i += 1,
+        }
+    }
+    mask
+}
+
+/// For a backtick run starting at `start`, returns where the code span ends
+/// (exclusive) and its fence length. An unclosed run spans only itself.
+fn code_span_at(chars: &[char], start: usize) -> (usize, usize) {
+    let run = chars[start..].iter().take_while(|&&c| c == '`').count();
+    // The span closes at the next backtick run of the same length
+    let mut j = start + run;
+    while j < chars.len() {
+        let other = chars[j..].iter().take_while(|&&c| c == '`').count();
+        if other == run {
+            return (j + other, run);
+        }
+        j += other.max(1);
+    }
+    (start + run, run)
+}
+
+/// Char ranges of the markdown syntax markers in `line`: block prefixes
+/// (`#` headings, `>` quotes, list bullets and numbers), code span backticks,
+/// and emphasis delimiters. The caller skips lines inside code fences.
+pub fn marker_ranges(line: &str) -> Vec<(usize, usize)> {
+    let chars: Vec<char> = line.chars().collect();
+    let mut ranges = Vec::new();
+
+    // Block prefix: any number of `>` levels, then a heading or list marker
+    let mut i = chars.iter().take_while(|c| c.is_whitespace()).count();
+    while chars.get(i) == Some(&'>') {
+        ranges.push((i, i + 1));
+        i += 1;
+        i += chars[i..].iter().take_while(|c| c.is_whitespace()).count();
+    }
+    let followed_by_space = |end: usize| chars.get(end).is_some_and(|c| c.is_whitespace());
+    let hashes = chars[i..].iter().take_while(|&&c| c == '#').count();
+    let digits = chars[i..].iter().take_while(|c| c.is_ascii_digit()).count();
+    if (1..=6).contains(&hashes) && followed_by_space(i + hashes) {
+        ranges.push((i, i + hashes));
+    } else if matches!(chars.get(i), Some('-' | '*' | '+')) && followed_by_space(i + 1) {
+        ranges.push((i, i + 1));
+    } else if digits > 0
+        && matches!(chars.get(i + digits), Some('.' | ')'))
+        && followed_by_space(i + digits + 1)
+    {
+        ranges.push((i, i + digits + 1));
+    }
+
+    // Code span fences (not their content)
+    let mut j = 0;
+    while j < chars.len() {
+        match chars[j] {
+            '\\' => j += 2,
+            '`' => {
+                let (end, fence) = code_span_at(&chars, j);
+                ranges.push((j, j + fence));
+                if end > j + fence {
+                    ranges.push((end - fence, end));
+                }
+                j = end;
+            }
+            _ => j += 1,
+        }
+    }
+
+    for span in find_emphasis(line) {
+        ranges.push((span.start, span.start + span.marker_len));
+        ranges.push((span.end - span.marker_len, span.end));
+    }
+    ranges.sort_unstable();
+    ranges
+}
+
+/// True if `delim` sits at `at` with none of its chars already used.
+fn is_free(chars: &[char], taken: &[bool], at: usize, delim: &[char]) -> bool {
+    let len = delim.len();
+    at + len <= chars.len()
+        && chars[at..at + len] == *delim
+        && !taken[at..at + len].contains(&true)
+}
+
+/// A run of free delimiter chars can be used as `len`: exactly one char for
+/// italic, or two (three when bold and italic share the run) for bold.
+fn run_fits(run: usize, len: usize) -> bool {
+    if len == 1 {
+        run == 1
+    } else {
+        run == 2 || run == 3
+    }
+}
+
+/// Number of consecutive free `marker` chars produced by `chars`.
+fn free_run(
+    chars: &[char],
+    taken: &[bool],
+    marker: char,
+    indices: impl Iterator<Item = usize>,
+) -> usize {
+    let mut run = 0;
+    for i in indices {
+        if chars[i] != marker || taken[i] {
+            break;
+        }
+        run += 1;
+    }
+    run
+}
+
+/// An opener starts a delimiter run and is followed by non-whitespace.
+fn is_opener(chars: &[char], taken: &[bool], at: usize, delim: &[char]) -> bool {
+    let marker = delim[0];
+    if !is_free(chars, taken, at, delim)
+        || (at > 0 && chars[at - 1] == marker && !taken[at - 1])
+    {
+        return false;
+    }
+    if !run_fits(free_run(chars, taken, marker, at..chars.len()), delim.len()) {
+        return false;
+    }
+    let Some(&next) = chars.get(at + delim.len()) else {
+        return false;
+    };
+    if next.is_whitespace() {
+        return false;
+    }
+    // Intraword underscores (snake_case) are not emphasis
+    marker != '_' || at == 0 || !chars[at - 1].is_alphanumeric()
+}
+
+/// A closer ends a delimiter run and is preceded by non-whitespace.
+fn is_closer(chars: &[char], taken: &[bool], at: usize, delim: &[char]) -> bool {
+    let marker = delim[0];
+    let end = at + delim.len();
+    if at == 0
+        || !is_free(chars, taken, at, delim)
+        || (end < chars.len() && chars[end] == marker && !taken[end])
+    {
+        return false;
+    }
+    if !run_fits(free_run(chars, taken, marker, (0..end).rev()), delim.len()) {
+        return false;
+    }
+    if chars[at - 1].is_whitespace() {
+        return false;
+    }
+    marker != '_' || end == chars.len() || !chars[end].is_alphanumeric()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn kinds(line: &str) -> Vec<(EmphasisKind, usize, usize)> {
+        find_emphasis(line)
+            .into_iter()
+            .map(|s| (s.kind, s.start, s.end))
+            .collect()
+    }
+
+    #[test]
+    fn test_bold() {
+        assert_eq!(kinds("a **bold** b"), vec![(EmphasisKind::Bold, 2, 10)]);
+        assert_eq!(kinds("__bold__"), vec![(EmphasisKind::Bold, 0, 8)]);
+    }
+
+    #[test]
+    fn test_italic() {
+        assert_eq!(kinds("an *italic* word"), vec![(EmphasisKind::Italic, 3, 11)]);
+        assert_eq!(kinds("_it_"), vec![(EmphasisKind::Italic, 0, 4)]);
+    }
+
+    #[test]
+    fn test_strikethrough() {
+        let spans = find_emphasis("~~gone~~ now");
+        assert_eq!(
+            spans,
+            vec![EmphasisSpan {
+                kind: EmphasisKind::Strikethrough,
+                start: 0,
+                end: 8,
+                marker_len: 2,
+            }]
+        );
+    }
+
+    #[test]
+    fn test_italic_nested_in_bold() {
+        assert_eq!(
+            kinds("**bold *and italic***"),
+            vec![(EmphasisKind::Bold, 0, 21), (EmphasisKind::Italic, 7, 19)]
+        );
+    }
+
+    #[test]
+    fn test_code_spans_and_escapes_are_skipped() {
+        assert!(kinds("`**not bold**`").is_empty());
+        assert!(kinds("\\*not italic\\*").is_empty());
+        assert_eq!(kinds("`x` *y*"), vec![(EmphasisKind::Italic, 4, 7)]);
+    }
+
+    #[test]
+    fn test_marker_ranges() {
+        assert_eq!(marker_ranges("## Title"), vec![(0, 2)]);
+        assert_eq!(marker_ranges("> - *a*"), vec![(0, 1), (2, 3), (4, 5), (6, 7)]);
+        assert_eq!(marker_ranges("  12. `x`"), vec![(2, 5), (6, 7), (8, 9)]);
+        assert!(marker_ranges("#hashtag and 3.5").is_empty());
+    }
+
+    #[test]
+    fn test_flanking_rules() {
+        assert!(kinds("2 * 3 * 4").is_empty());
+        assert!(kinds("snake_case_name").is_empty());
+        assert!(kinds("- list item").is_empty());
+        assert!(kinds("**unclosed").is_empty());
+    }
+}
@@ -0,0 +1,69 @@
+//! Standalone HTML export without pandoc: pulldown-cmark's HTML writer with
+//! the same extensions as the preview, wrapped in a minimal styled page.
+
+use pulldown_cmark::{html, Parser};
+
+use crate::markdown::renderer::parser_options;
+
+/// The page style of exported HTML: marko's clean look, light for print.
+pub const STYLE: &str = "\
+body { max-width: 48rem; margin: 2rem auto; padding: 0 1rem; \
+font-family: system-ui, sans-serif; line-height: 1.6; color: #222; }
+pre, code { font-family: ui-monospace, monospace; background: #f4f4f6; }
+pre { padding: 0.75rem; overflow-x: auto; }
+code { padding: 0.1rem 0.25rem; }
+pre code { padding: 0; }
+blockquote { margin-left: 0; padding-left: 1rem; border-left: 3px solid #6abe78; color: #555; }
+table { border-collapse: collapse; }
+th, td { border: 1px solid #ccc; padding: 0.3rem 0.6rem; }
+img { max-width: 100%; }
+";
+
+/// Converts markdown to an HTML fragment.
+pub fn to_html(content: &str) -> String {
+    let mut out = String::new();
+    html::push_html(&mut out, Parser::new_ext(content, parser_options()));
+    out
+}
+
+/// Converts markdown to a complete HTML document titled `title`.
+pub fn to_html_document(content: &str, title: &str) -> String {
+    let escaped_title = title
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;");
+    format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n\
+         <meta name=\"viewport\" content=\"width=device-width, initial-scale=1\">\n\
+         <title>{}</title>\n<style>\n{}</style>\n</head>\n<body>\n{}</body>\n</html>\n",
+        escaped_title,
+        STYLE,
+        to_html(content)
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_headings_tables_and_code_fences() {
+        let md = "# Title\n\n## Sub\n\n| a | b |\n|---|---|\n| 1 | 2 |\n\n```rust\nfn main() {}\n```\n";
+        let html = to_html(md);
+        assert!(html.contains("<h1>Title</h1>"));
+        assert!(html.contains("<h2>Sub</h2>"));
+        assert!(html.contains("<table>"));
+        assert!(html.contains("<th>a</th>"));
+        assert!(html.contains("<td>2</td>"));
+        assert!(html.contains("<pre><code class=\"language-rust\">fn main() {}\n</code></pre>"));
+    }
+
+    #[test]
+    fn test_document_wraps_and_escapes() {
+        let doc = to_html_document("text with <tag>", "a & b");
+        assert!(doc.starts_with("<!DOCTYPE html>"));
+        assert!(doc.contains("<title>a &amp; b</title>"));
+        assert!(doc.contains("<p>text with <tag></p>"), "raw HTML passes through");
+        assert!(doc.trim_end().ends_with("</html>"));
+    }
+}
@@ -0,0 +1,106 @@
+//! Helpers for turning a copied URL into a markdown link: URL detection,
+//! fetching the page, and pulling the `<title>` out of the returned HTML.
+
+/// True if `text` is a single http(s) URL with no surrounding text.
+pub fn is_url(text: &str) -> bool {
+    (text.starts_with("http://") || text.starts_with("https://"))
+        && text.len() > "https://".len()
+        && !text.chars().any(char::is_whitespace)
+}
+
+/// Extracts the contents of the first `<title>` element, with entities decoded
+/// and whitespace collapsed. Returns None if there is no non-empty title.
+pub fn extract_title(html: &str) -> Option<String> {
+    let lower = html.to_ascii_lowercase();
+    let open = lower.find("<title")?;
+    let content_start = open + lower[open..].find('>')? + 1;
+    let content_end = content_start + lower[content_start..].find("</title")?;
+
+    let title = decode_entities(&html[content_start..content_end]);
+    let title = title.split_whitespace().collect::<Vec<_>>().join(" ");
+    if title.is_empty() {
+        None
+    } else {
+        Some(title)
+    }
+}
+
+/// Decodes the handful of HTML entities common in page titles.
+fn decode_entities(text: &str) -> String {
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&#x27;", "'")
+        .replace("&apos;", "'")
+        .replace("&nbsp;", " ")
+        .replace("&amp;", "&")
+}
+
+/// Fetches `url` via curl (same as remote preview images) and returns its
+/// page title. Blocks for up to 5 seconds; call from a background thread.
+pub fn fetch_title(url: &str) -> Option<String> {
+    let output = std::process::Command::new("curl")
+        .args(["-s", "-L", "--max-time", "5", "--max-filesize", "2000000"])
+        .arg(url)
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    extract_title(&String::from_utf8_lossy(&output.stdout))
+}
+
+/// Formats a markdown link, escaping brackets in the title so it can't end
+/// the link text early.
+pub fn markdown_link(title: &str, url: &str) -> String {
+    let title = title.replace('[', "\\[").replace(']', "\\]");
+    format!("[{}]({})", title, url)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_extract_simple_title() {
+        let html = "<html><head><title>Example Domain</title></head></html>";
+        assert_eq!(extract_title(html), Some("Example Domain".to_string()));
+    }
+
+    #[test]
+    fn test_extract_title_with_attributes_and_whitespace() {
+        let html = "<HEAD>\n<Title lang=\"en\">\n   Rust  Docs\n </TITLE>";
+        assert_eq!(extract_title(html), Some("Rust Docs".to_string()));
+    }
+
+    #[test]
+    fn test_extract_title_decodes_entities() {
+        let html = "<title>Tom &amp; Jerry &#39;s &lt;Show&gt;</title>";
+        assert_eq!(extract_title(html), Some("Tom & Jerry 's <Show>".to_string()));
+    }
+
+    #[test]
+    fn test_missing_or_empty_title() {
+        assert_eq!(extract_title("<html><body>no title</body></html>"), None);
+        assert_eq!(extract_title("<title>   </title>"), None);
+        assert_eq!(extract_title("<title>unterminated"), None);
+    }
+
+    #[test]
+    fn test_is_url() {
+        assert!(is_url("https://example.com/page"));
+        assert!(is_url("http://x.io"));
+        assert!(!is_url("https://"));
+        assert!(!is_url("see https://example.com"));
+        assert!(!is_url("ftp://example.com"));
+    }
+
+    #[test]
+    fn test_markdown_link_escapes_brackets() {
+        assert_eq!(
+            markdown_link("[PATCH] fix", "https://x.io"),
+            "[\\[PATCH\\] fix](https://x.io)"
+        );
+    }
+}
@@ -1,57 +1,103 @@
 //! LaTeX-to-Unicode conversion for inline and display math.
 //!
 //! Converts common LaTeX commands (Greek letters, operators, arrows, etc.)
-//! to their Unicode equivalents, and handles superscript/subscript notation.
+//! to their Unicode equivalents, puts accents (`\hat{x}`) on as combining
+//! marks, and handles superscript/subscript notation. Commands with no
+//! equivalent are left as written.
 
-/// Convert LaTeX math to Unicode approximation.
-pub fn latex_to_unicode(input: &str) -> String {
-    let mut s = input.to_string();
+/// A math span converted for display, with the LaTeX it came from.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MathText {
+    pub source: String,
+    pub unicode: String,
+}
 
+/// Converts `input` with `latex_to_unicode`, keeping the source too.
+pub fn convert_math(input: &str) -> MathText {
+    MathText {
+        source: input.to_string(),
+        unicode: latex_to_unicode(input),
+    }
+}
+
+/// LaTeX commands (without the backslash) and the Unicode they stand for.
+const SYMBOLS: &[(&str, &str)] = &[
     // Greek lowercase
-    let replacements = [
-        ("\\alpha", "α"), ("\\beta", "β"), ("\\gamma", "γ"), ("\\delta", "δ"),
-        ("\\epsilon", "ε"), ("\\varepsilon", "ε"), ("\\zeta", "ζ"), ("\\eta", "η"),
-        ("\\theta", "θ"), ("\\vartheta", "ϑ"), ("\\iota", "ι"), ("\\kappa", "κ"),
-        ("\\lambda", "λ"), ("\\mu", "μ"), ("\\nu", "ν"), ("\\xi", "ξ"),
-        ("\\pi", "π"), ("\\rho", "ρ"), ("\\sigma", "σ"), ("\\tau", "τ"),
-        ("\\upsilon", "υ"), ("\\phi", "φ"), ("\\varphi", "ϕ"), ("\\chi", "χ"),
-        ("\\psi", "ψ"), ("\\omega", "ω"),
-        // Greek uppercase
-        ("\\Gamma", "Γ"), ("\\Delta", "Δ"), ("\\Theta", "Θ"), ("\\Lambda", "Λ"),
-        ("\\Xi", "Ξ"), ("\\Pi", "Π"), ("\\Sigma", "Σ"), ("\\Phi", "Φ"),
-        ("\\Psi", "Ψ"), ("\\Omega", "Ω"),
-        // Operators
-        ("\\int", "∫"), ("\\iint", "∬"), ("\\iiint", "∭"),
-        ("\\oint", "∮"), ("\\sum", "∑"), ("\\prod", "∏"),
-        ("\\sqrt", "√"), ("\\partial", "∂"), ("\\nabla", "∇"), ("\\infty", "∞"),
-        // Relations
-        ("\\leq", "≤"), ("\\geq", "≥"), ("\\neq", "≠"), ("\\approx", "≈"),
-        ("\\equiv", "≡"), ("\\sim", "∼"), ("\\propto", "∝"),
-        ("\\pm", "±"), ("\\mp", "∓"), ("\\times", "×"), ("\\div", "÷"),
-        ("\\cdot", "·"), ("\\circ", "∘"), ("\\star", "⋆"),
-        // Arrows
-        ("\\rightarrow", "→"), ("\\leftarrow", "←"), ("\\leftrightarrow", "↔"),
-        ("\\Rightarrow", "⇒"), ("\\Leftarrow", "⇐"), ("\\Leftrightarrow", "⇔"),
-        ("\\to", "→"), ("\\mapsto", "↦"),
-        ("\\uparrow", "↑"), ("\\downarrow", "↓"),
-        // Sets & logic
-        ("\\in", "∈"), ("\\notin", "∉"), ("\\subset", "⊂"), ("\\supset", "⊃"),
-        ("\\subseteq", "⊆"), ("\\supseteq", "⊇"), ("\\cup", "∪"), ("\\cap", "∩"),
-        ("\\emptyset", "∅"), ("\\varnothing", "∅"),
-        ("\\forall", "∀"), ("\\exists", "∃"), ("\\nexists", "∄"),
-        ("\\neg", "¬"), ("\\wedge", "∧"), ("\\vee", "∨"),
-        // Dots
-        ("\\ldots", "…"), ("\\cdots", "⋯"), ("\\dots", "…"), ("\\vdots", "⋮"),
-        // Misc
-        ("\\hbar", "ℏ"), ("\\ell", "ℓ"), ("\\Re", "ℜ"), ("\\Im", "ℑ"),
-        ("\\aleph", "ℵ"), ("\\wp", "℘"), ("\\degree", "°"),
-        // Spacing/formatting (remove)
-        ("\\quad", " "), ("\\qquad", "  "), ("\\,", ""), ("\\;", " "),
-        ("\\!", ""), ("\\left", ""), ("\\right", ""), ("\\big", ""),
-        ("\\Big", ""), ("\\bigg", ""), ("\\Bigg", ""),
-    ];
-    for (cmd, repl) in &replacements {
-        s = s.replace(cmd, repl);
+    ("alpha", "α"), ("beta", "β"), ("gamma", "γ"), ("delta", "δ"),
+    ("epsilon", "ε"), ("varepsilon", "ε"), ("zeta", "ζ"), ("eta", "η"),
+    ("theta", "θ"), ("vartheta", "ϑ"), ("iota", "ι"), ("kappa", "κ"),
+    ("lambda", "λ"), ("mu", "μ"), ("nu", "ν"), ("xi", "ξ"),
+    ("pi", "π"), ("rho", "ρ"), ("sigma", "σ"), ("tau", "τ"),
+    ("upsilon", "υ"), ("phi", "φ"), ("varphi", "ϕ"), ("chi", "χ"),
+    ("psi", "ψ"), ("omega", "ω"),
+    // Greek uppercase
+    ("Gamma", "Γ"), ("Delta", "Δ"), ("Theta", "Θ"), ("Lambda", "Λ"),
+    ("Xi", "Ξ"), ("Pi", "Π"), ("Sigma", "Σ"), ("Phi", "Φ"),
+    ("Psi", "Ψ"), ("Omega", "Ω"),
+    // Operators
+    ("int", "∫"), ("iint", "∬"), ("iiint", "∭"),
+    ("oint", "∮"), ("sum", "∑"), ("prod", "∏"),
+    ("sqrt", "√"), ("partial", "∂"), ("nabla", "∇"), ("infty", "∞"),
+    ("oplus", "⊕"), ("otimes", "⊗"), ("setminus", "∖"),
+    // Relations
+    ("leq", "≤"), ("le", "≤"), ("geq", "≥"), ("ge", "≥"),
+    ("neq", "≠"), ("ne", "≠"), ("approx", "≈"), ("ll", "≪"), ("gg", "≫"),
+    ("equiv", "≡"), ("sim", "∼"), ("simeq", "≃"), ("cong", "≅"), ("propto", "∝"),
+    ("perp", "⊥"), ("parallel", "∥"), ("mid", "∣"),
+    ("pm", "±"), ("mp", "∓"), ("times", "×"), ("div", "÷"),
+    ("cdot", "·"), ("circ", "∘"), ("star", "⋆"),
+    // Arrows
+    ("rightarrow", "→"), ("leftarrow", "←"), ("leftrightarrow", "↔"),
+    ("Rightarrow", "⇒"), ("Leftarrow", "⇐"), ("Leftrightarrow", "⇔"),
+    ("to", "→"), ("gets", "←"), ("mapsto", "↦"),
+    ("implies", "⟹"), ("iff", "⟺"),
+    ("uparrow", "↑"), ("downarrow", "↓"),
+    // Sets & logic
+    ("in", "∈"), ("notin", "∉"), ("subset", "⊂"), ("supset", "⊃"),
+    ("subseteq", "⊆"), ("supseteq", "⊇"), ("cup", "∪"), ("cap", "∩"),
+    ("emptyset", "∅"), ("varnothing", "∅"),
+    ("forall", "∀"), ("exists", "∃"), ("nexists", "∄"),
+    ("neg", "¬"), ("wedge", "∧"), ("vee", "∨"),
+    ("therefore", "∴"), ("because", "∵"),
+    // Dots
+    ("ldots", "…"), ("cdots", "⋯"), ("dots", "…"), ("vdots", "⋮"), ("ddots", "⋱"),
+    // Delimiters
+    ("langle", "⟨"), ("rangle", "⟩"), ("lfloor", "⌊"), ("rfloor", "⌋"),
+    ("lceil", "⌈"), ("rceil", "⌉"),
+    // Misc
+    ("hbar", "ℏ"), ("ell", "ℓ"), ("Re", "ℜ"), ("Im", "ℑ"),
+    ("aleph", "ℵ"), ("wp", "℘"), ("degree", "°"), ("prime", "′"), ("angle", "∠"),
+    // Spacing/formatting (remove)
+    ("quad", " "), ("qquad", "  "), (",", ""), (";", " "),
+    ("!", ""), ("left", ""), ("right", ""), ("big", ""),
+    ("Big", ""), ("bigg", ""), ("Bigg", ""),
+];
+
+/// Accent commands and the combining mark each puts on its argument.
+const ACCENTS: &[(&str, char)] = &[
+    ("hat", '\u{0302}'), ("vec", '\u{20D7}'), ("bar", '\u{0304}'),
+    ("overline", '\u{0305}'), ("tilde", '\u{0303}'), ("dot", '\u{0307}'),
+    ("ddot", '\u{0308}'),
+];
+
+/// Convert LaTeX math to Unicode approximation. Commands it doesn't know
+/// are left as written.
+pub fn latex_to_unicode(input: &str) -> String {
+    let mut s = replace_commands(input);
+
+    // Handle \hat{x} → x̂ and the other accents
+    for (cmd, mark) in ACCENTS {
+        let open = format!("\\{}{{", cmd);
+        let mut from = 0;
+        while let Some(start) = s[from..].find(&open).map(|i| from + i) {
+            let after_open = start + open.len();
+            let Some(close) = find_matching_brace(&s, after_open) else {
+                break;
+            };
+            let accented: String = s[after_open..close].chars().flat_map(|c| [c, *mark]).collect();
+            s = format!("{}{}{}", &s[..start], accented, &s[close + 1..]);
+            from = start + accented.len();
+        }
     }
 
     // Handle \frac{a}{b} → a⁄b
@@ -80,6 +126,33 @@ pub fn latex_to_unicode(input: &str) -> String {
     process_super_sub(&s)
 }
 
+/// Replaces each known `\command` in `input` with its symbol. A command
+/// is a backslash and a run of letters, or one other char (`\,`).
+fn replace_commands(input: &str) -> String {
+    let mut out = String::new();
+    let mut rest = input;
+    while let Some(i) = rest.find('\\') {
+        out.push_str(&rest[..i]);
+        let after = &rest[i + 1..];
+        let len = match after.find(|c: char| !c.is_ascii_alphabetic()) {
+            Some(0) => after.chars().next().map_or(0, char::len_utf8),
+            Some(len) => len,
+            None => after.len(),
+        };
+        let name = &after[..len];
+        match SYMBOLS.iter().find(|(cmd, _)| *cmd == name) {
+            Some((_, symbol)) => out.push_str(symbol),
+            None => {
+                out.push('\\');
+                out.push_str(name);
+            }
+        }
+        rest = &after[len..];
+    }
+    out.push_str(rest);
+    out
+}
+
 /// Process ^{} and _{} groups in a string, recursively handling nested groups.
 /// Only converts to Unicode super/subscript if ALL chars in the group have equivalents.
 fn process_super_sub(s: &str) -> String {
@@ -135,14 +208,11 @@ fn process_super_sub(s: &str) -> String {
 }
 
 fn can_superscript(s: &str) -> bool {
-    s.chars().all(|c| matches!(c, '0'..='9' | '+' | '-' | '=' | '(' | ')' | 'n' | 'i'))
+    s.chars().all(|c| to_superscript(&c.to_string()) != c.to_string())
 }
 
 fn can_subscript(s: &str) -> bool {
-    s.chars().all(|c| matches!(c,
-        '0'..='9' | '+' | '-' | '=' | '(' | ')' |
-        'a' | 'e' | 'o' | 'x' | 'h' | 'k' | 'l' | 'm' | 'n' | 'p' | 's' | 't'
-    ))
+    s.chars().all(|c| to_subscript(&c.to_string()) != c.to_string())
 }
 
 fn find_matching_brace(s: &str, start: usize) -> Option<usize> {
@@ -164,8 +234,8 @@ fn find_matching_brace(s: &str, start: usize) -> Option<usize> {
 
 fn find_matching_brace_chars(chars: &[char], start: usize) -> Option<usize> {
     let mut depth = 1;
-    for i in start..chars.len() {
-        match chars[i] {
+    for (i, &c) in chars.iter().enumerate().skip(start) {
+        match c {
             '{' => depth += 1,
             '}' => {
                 depth -= 1;
@@ -185,7 +255,12 @@ pub fn to_superscript(s: &str) -> String {
             '0' => '⁰', '1' => '¹', '2' => '²', '3' => '³', '4' => '⁴',
             '5' => '⁵', '6' => '⁶', '7' => '⁷', '8' => '⁸', '9' => '⁹',
             '+' => '⁺', '-' => '⁻', '=' => '⁼', '(' => '⁽', ')' => '⁾',
-            'n' => 'ⁿ', 'i' => 'ⁱ',
+            'a' => 'ᵃ', 'b' => 'ᵇ', 'c' => 'ᶜ', 'd' => 'ᵈ', 'e' => 'ᵉ',
+            'f' => 'ᶠ', 'g' => 'ᵍ', 'h' => 'ʰ', 'i' => 'ⁱ', 'j' => 'ʲ',
+            'k' => 'ᵏ', 'l' => 'ˡ', 'm' => 'ᵐ', 'n' => 'ⁿ', 'o' => 'ᵒ',
+            'p' => 'ᵖ', 'r' => 'ʳ', 's' => 'ˢ', 't' => 'ᵗ', 'u' => 'ᵘ',
+            'v' => 'ᵛ', 'w' => 'ʷ', 'x' => 'ˣ', 'y' => 'ʸ', 'z' => 'ᶻ',
+            'T' => 'ᵀ',
             _ => c,
         })
         .collect()
@@ -200,7 +275,63 @@ pub fn to_subscript(s: &str) -> String {
             'a' => 'ₐ', 'e' => 'ₑ', 'o' => 'ₒ', 'x' => 'ₓ',
             'h' => 'ₕ', 'k' => 'ₖ', 'l' => 'ₗ', 'm' => 'ₘ',
             'n' => 'ₙ', 'p' => 'ₚ', 's' => 'ₛ', 't' => 'ₜ',
+            'i' => 'ᵢ', 'j' => 'ⱼ', 'r' => 'ᵣ', 'u' => 'ᵤ', 'v' => 'ᵥ',
             _ => c,
         })
         .collect()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_latex_to_unicode_table() {
+        let cases = [
+            ("a \\times b", "a × b"),
+            ("a \\div b", "a ÷ b"),
+            ("\\pm 1", "± 1"),
+            ("x \\leq y \\geq z", "x ≤ y ≥ z"),
+            ("x \\le y \\ge z \\ne w", "x ≤ y ≥ z ≠ w"),
+            ("\\pi \\approx 3.14", "π ≈ 3.14"),
+            ("a \\rightarrow b", "a → b"),
+            ("\\partial f", "∂ f"),
+            ("\\nabla \\cdot F", "∇ · F"),
+            ("x^2", "x²"),
+            ("e^{i\\pi}", "e^(iπ)"),
+            ("x^{2n}", "x²ⁿ"),
+            ("x_1", "x₁"),
+            ("a_{ij}", "aᵢⱼ"),
+            ("A^T", "Aᵀ"),
+            ("\\hat{x}", "x\u{0302}"),
+            ("\\vec{v}", "v\u{20D7}"),
+            ("\\bar{x} + \\dot{y}", "x\u{0304} + y\u{0307}"),
+            ("\\frac{1}{2}", "1⁄2"),
+            // Longer commands aren't mangled by shorter ones they start with
+            ("A \\subseteq B", "A ⊆ B"),
+            ("1, \\cdots, n", "1, ⋯, n"),
+            ("a \\simeq b", "a ≃ b"),
+            ("\\bigg( x \\bigg)", "( x )"),
+            ("\\infty", "∞"),
+            ("x \\in S", "x ∈ S"),
+        ];
+        for (input, expected) in cases {
+            assert_eq!(latex_to_unicode(input), expected, "{}", input);
+        }
+    }
+
+    #[test]
+    fn test_unsupported_commands_are_kept() {
+        assert_eq!(latex_to_unicode("\\mathcal L"), "\\mathcal L");
+        assert_eq!(latex_to_unicode("\\alphabet"), "\\alphabet", "not \\alpha + bet");
+        assert_eq!(latex_to_unicode("x^\\alpha"), "x^α", "no superscript α");
+        assert_eq!(latex_to_unicode("x_Q"), "x_Q");
+    }
+
+    #[test]
+    fn test_convert_math_keeps_source() {
+        let math = convert_math("\\alpha^2");
+        assert_eq!(math.source, "\\alpha^2");
+        assert_eq!(math.unicode, "α²");
+    }
+}
@@ -1,6 +1,16 @@
 pub mod autocomplete;
 pub mod code_highlight;
+pub mod diagram;
+pub mod emphasis;
+pub mod html;
+pub mod link;
 pub mod math;
+pub mod modeline;
+pub mod outline;
 pub mod renderer;
+#[cfg(feature = "spellcheck")]
+pub mod spellcheck;
 pub mod style_ext;
 pub mod table_format;
+pub mod transform;
+pub mod word_goal;
@@ -0,0 +1,160 @@
+//! Per-file options from a modeline: an HTML comment such as
+//! `<!-- marko: wrap=80 softwrap=off -->` in the first or last few lines of
+//! the file, so a document can carry its own formatting preferences.
+//!
+//! The comment holds whitespace-separated `key=value` pairs (or `key value`,
+//! as in `<!-- marko:goal 1000 -->`). Recognized keys override the config
+//! for that file only:
+//!
+//! - `wrap`: hard-wrap column (like `wrap_width`; `0` for the window width)
+//! - `softwrap`: `on` / `off` (like `soft_wrap`)
+//! - `format`: `on` / `off` (like `format_on_save`)
+//! - `goal`: word goal shown in the status bar (like `word_goal`)
+//! - `theme`: UI theme while the file is shown (like `theme`)
+//!
+//! Unknown keys and unparsable values are ignored.
+
+/// How many lines at each end of the file are searched for a modeline.
+pub const SCAN_LINES: usize = 5;
+
+/// Options set by a file's modeline; None where it doesn't set one.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Modeline {
+    pub wrap: Option<usize>,
+    pub soft_wrap: Option<bool>,
+    pub format_on_save: Option<bool>,
+    pub goal: Option<usize>,
+    /// Name of the UI theme; an unknown one is ignored when applied.
+    pub theme: Option<String>,
+}
+
+/// The `key=value` pairs of `line` if it is a modeline comment, in order.
+/// A word without an `=` takes the next word as its value, unless that is
+/// a `key=value` pair itself; then it has an empty value.
+pub fn parse_line(line: &str) -> Option<Vec<(String, String)>> {
+    let body = line
+        .trim()
+        .strip_prefix("<!--")?
+        .strip_suffix("-->")?
+        .trim()
+        .strip_prefix("marko:")?;
+    let mut words = body.split_whitespace().peekable();
+    let mut pairs = Vec::new();
+    while let Some(word) = words.next() {
+        let (key, value) = match word.split_once('=') {
+            Some(pair) => pair,
+            None => (word, words.next_if(|next| !next.contains('=')).unwrap_or("")),
+        };
+        pairs.push((key.to_string(), value.to_string()));
+    }
+    Some(pairs)
+}
+
+/// Options from the first modeline within `SCAN_LINES` of either end of
+/// `lines`.
+pub fn find(lines: &[String]) -> Modeline {
+    let head = lines.iter().take(SCAN_LINES);
+    let tail = lines.iter().skip(SCAN_LINES).rev().take(SCAN_LINES);
+    let Some(pairs) = head.chain(tail).find_map(|line| parse_line(line)) else {
+        return Modeline::default();
+    };
+    let mut modeline = Modeline::default();
+    for (key, value) in pairs {
+        match key.as_str() {
+            "wrap" => modeline.wrap = value.parse().ok().or(modeline.wrap),
+            "softwrap" => modeline.soft_wrap = parse_switch(&value).or(modeline.soft_wrap),
+            "format" => {
+                modeline.format_on_save = parse_switch(&value).or(modeline.format_on_save)
+            }
+            "goal" => modeline.goal = value.parse().ok().or(modeline.goal),
+            "theme" => modeline.theme = Some(value),
+            _ => {}
+        }
+    }
+    modeline
+}
+
+/// `on` / `off` (or `true` / `false`).
+fn parse_switch(value: &str) -> Option<bool> {
+    match value {
+        "on" | "true" => Some(true),
+        "off" | "false" => Some(false),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lines(text: &str) -> Vec<String> {
+        text.lines().map(String::from).collect()
+    }
+
+    #[test]
+    fn test_parse_line_with_wrap_and_theme() {
+        let pairs = parse_line("<!-- marko: wrap=80 theme=light -->").unwrap();
+        assert_eq!(
+            pairs,
+            vec![
+                ("wrap".to_string(), "80".to_string()),
+                ("theme".to_string(), "light".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_line_rejects_other_comments() {
+        assert_eq!(parse_line("<!-- just a note -->"), None);
+        assert_eq!(parse_line("marko: wrap=80"), None);
+        assert_eq!(parse_line("<!--marko:wrap=72-->"), Some(vec![("wrap".into(), "72".into())]));
+    }
+
+    #[test]
+    fn test_find_applies_known_keys_and_ignores_unknown() {
+        let text = lines("# Title\n<!-- marko: wrap=80 theme=light softwrap=on author=me format=off -->\nbody");
+        assert_eq!(
+            find(&text),
+            Modeline {
+                wrap: Some(80),
+                soft_wrap: Some(true),
+                format_on_save: Some(false),
+                goal: None,
+                theme: Some("light".to_string()),
+            }
+        );
+    }
+
+    #[test]
+    fn test_goal_with_or_without_equals() {
+        assert_eq!(find(&lines("text\n<!-- marko:goal 1000 -->")).goal, Some(1000));
+        assert_eq!(find(&lines("<!-- marko: wrap=72 goal=500 -->")).goal, Some(500));
+    }
+
+    #[test]
+    fn test_bare_word_does_not_swallow_the_next_pair() {
+        let pairs = parse_line("<!-- marko: draft wrap=80 -->").unwrap();
+        assert_eq!(
+            pairs,
+            vec![("draft".to_string(), String::new()), ("wrap".to_string(), "80".to_string())]
+        );
+        assert_eq!(find(&lines("<!-- marko: draft wrap=80 goal -->")).wrap, Some(80));
+    }
+
+    #[test]
+    fn test_find_ignores_bad_values() {
+        let text = lines("<!-- marko: wrap=wide softwrap=maybe -->");
+        assert_eq!(find(&text), Modeline::default());
+    }
+
+    #[test]
+    fn test_find_checks_only_the_ends_of_the_file() {
+        let mut text = vec![String::new(); 20];
+        text[19] = "<!-- marko: wrap=60 -->".to_string();
+        assert_eq!(find(&text).wrap, Some(60));
+
+        text[19].clear();
+        text[10] = "<!-- marko: wrap=60 -->".to_string();
+        assert_eq!(find(&text), Modeline::default());
+    }
+}
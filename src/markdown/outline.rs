@@ -0,0 +1,91 @@
+//! Heading scan over the raw markdown in the editor buffer: the document
+//! outline, used to style heading lines by level.
+
+/// An ATX heading found in the buffer.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Heading {
+    /// Buffer row of the heading line.
+    pub line: usize,
+    /// 1 for `#`, up to 6 for `######`.
+    pub level: usize,
+    /// Heading text without the `#` markers.
+    pub title: String,
+}
+
+/// Level (1-6) of `line` if it is an ATX heading: up to three spaces of
+/// indent, a run of `#`, then a space or the end of the line.
+pub fn heading_level(line: &str) -> Option<usize> {
+    let trimmed = line.trim_start_matches(' ');
+    if line.len() - trimmed.len() > 3 {
+        return None;
+    }
+    let level = trimmed.chars().take_while(|&c| c == '#').count();
+    let rest = &trimmed[level..];
+    if (1..=6).contains(&level) && (rest.is_empty() || rest.starts_with([' ', '\t'])) {
+        Some(level)
+    } else {
+        None
+    }
+}
+
+/// Every heading in `lines`, skipping fenced code blocks.
+pub fn headings(lines: &[String]) -> Vec<Heading> {
+    let mut headings = Vec::new();
+    let mut in_code_fence = false;
+    for (line_idx, line) in lines.iter().enumerate() {
+        let trimmed = line.trim_start();
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            in_code_fence = !in_code_fence;
+            continue;
+        }
+        if in_code_fence {
+            continue;
+        }
+        if let Some(level) = heading_level(line) {
+            // Closing sequence ("## Title ##") is not part of the title
+            let title = trimmed[level..].trim().trim_end_matches('#').trim_end();
+            headings.push(Heading {
+                line: line_idx,
+                level,
+                title: title.to_string(),
+            });
+        }
+    }
+    headings
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_heading_levels() {
+        assert_eq!(heading_level("# One"), Some(1));
+        assert_eq!(heading_level("### Three"), Some(3));
+        assert_eq!(heading_level("   ## Indented"), Some(2));
+        assert_eq!(heading_level("##"), Some(2));
+    }
+
+    #[test]
+    fn test_non_headings() {
+        assert_eq!(heading_level("#hashtag"), None);
+        assert_eq!(heading_level("####### seven"), None);
+        assert_eq!(heading_level("    # code"), None);
+        assert_eq!(heading_level("text # not"), None);
+    }
+
+    #[test]
+    fn test_headings_skip_code_fences() {
+        let lines: Vec<String> = ["# Title", "```sh", "# comment", "```", "## Part ##"]
+            .iter()
+            .map(|l| l.to_string())
+            .collect();
+        assert_eq!(
+            headings(&lines),
+            vec![
+                Heading { line: 0, level: 1, title: "Title".to_string() },
+                Heading { line: 4, level: 2, title: "Part".to_string() },
+            ]
+        );
+    }
+}
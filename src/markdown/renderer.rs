@@ -1,11 +1,14 @@
+use std::collections::HashSet;
+
 use pulldown_cmark::{Alignment, Event, Options, Parser, Tag, TagEnd, CodeBlockKind};
 use ratatui::{
-    style::{Modifier, Style},
+    style::{Color, Modifier, Style},
     text::{Line, Span, Text},
 };
 
 use crate::markdown::code_highlight;
-use crate::markdown::math::latex_to_unicode;
+use crate::markdown::diagram::Diagram;
+use crate::markdown::math::convert_math;
 use crate::markdown::style_ext::style_extensions;
 use crate::theme;
 
@@ -16,27 +19,90 @@ pub struct RenderedMarkdown {
     pub link_urls: Vec<String>,
     /// Image positions and URLs for inline rendering.
     pub image_infos: Vec<ImageInfo>,
+    /// For each output line, the 0-indexed source line of the block it
+    /// renders (None for lines not tied to one).
+    pub source_line_map: Vec<Option<usize>>,
+    /// Footnote labels by number: footnote 1's label first.
+    pub footnote_order: Vec<String>,
+    /// Every heading, in order: the sections the preview can collapse.
+    pub headings: Vec<HeadingInfo>,
+}
+
+/// A heading's place in the rendered output.
+#[derive(Debug, Clone, PartialEq)]
+pub struct HeadingInfo {
+    /// Output line of the heading text (its last, if it wraps).
+    pub line: usize,
+    pub level: u8,
+    /// 0-indexed source line of the heading.
+    pub source_line: usize,
+}
+
+impl RenderedMarkdown {
+    /// Output lines hidden when the sections whose heading source lines
+    /// are in `collapsed` are folded: everything after such a heading up to
+    /// the next heading of the same or a higher level.
+    pub fn collapsed_lines(&self, collapsed: &HashSet<usize>) -> Vec<bool> {
+        let mut hidden = vec![false; self.text.lines.len()];
+        for (index, heading) in self.headings.iter().enumerate() {
+            if !collapsed.contains(&heading.source_line) {
+                continue;
+            }
+            let end = self.headings[index + 1..]
+                .iter()
+                .find(|next| next.level <= heading.level)
+                .map_or(hidden.len(), |next| next.line);
+            for line in hidden.iter_mut().take(end).skip(heading.line + 1) {
+                *line = true;
+            }
+        }
+        hidden
+    }
 }
 
 /// Metadata for an image in the rendered output.
 pub struct ImageInfo {
     pub url: String,
+    /// A diagram code block to draw instead of the image at `url`.
+    pub diagram: Option<Diagram>,
     pub start_line: usize,
     pub line_count: usize,
 }
 
-pub fn render_markdown(content: &str, width: usize) -> RenderedMarkdown {
-    let options = Options::ENABLE_STRIKETHROUGH
+/// Markdown extensions marko understands, shared by every renderer.
+pub fn parser_options() -> Options {
+    Options::ENABLE_STRIKETHROUGH
         | Options::ENABLE_TABLES
         | Options::ENABLE_FOOTNOTES
         | Options::ENABLE_TASKLISTS
         | Options::ENABLE_MATH
-        | Options::ENABLE_DEFINITION_LIST;
-    let parser = Parser::new_ext(content, options);
+        | Options::ENABLE_DEFINITION_LIST
+}
+
+pub fn render_markdown(content: &str, width: usize) -> RenderedMarkdown {
+    render(content, width, true, false)
+}
+
+/// `render_markdown`, with math shown as its LaTeX source (between `$` or
+/// `$$`, as written) instead of converted to Unicode when `raw_math` is set.
+pub fn render_markdown_with_math(content: &str, width: usize, raw_math: bool) -> RenderedMarkdown {
+    render(content, width, true, raw_math)
+}
+
+/// `render_markdown`, with diagram code blocks left as code unless
+/// `diagrams` is set, and math as LaTeX source if `raw_math` is.
+fn render(content: &str, width: usize, diagrams: bool, raw_math: bool) -> RenderedMarkdown {
+    let parser = Parser::new_ext(content, parser_options()).into_offset_iter();
+
+    // Byte offset where each source line starts, to map events to lines
+    let line_starts: Vec<usize> = std::iter::once(0)
+        .chain(content.match_indices('\n').map(|(i, _)| i + 1))
+        .collect();
+    let mut source_line_map: Vec<Option<usize>> = Vec::new();
 
     let mut lines: Vec<Line<'static>> = Vec::new();
     let mut current_spans: Vec<Span<'static>> = Vec::new();
-    let mut style_stack: Vec<Style> = vec![Style::default().fg(theme::FG)];
+    let mut style_stack: Vec<Style> = vec![Style::default().fg(theme::active().fg)];
     let mut in_code_block = false;
     let mut code_block_lang = String::new();
     let mut code_block_content = String::new();
@@ -55,9 +121,18 @@ pub fn render_markdown(content: &str, width: usize) -> RenderedMarkdown {
     let mut _in_table_head = false;
     let mut table_alignments: Vec<Alignment> = Vec::new();
 
-    // Footnote/definition list state
-    let mut _in_footnote_def = false;
-    let mut footnote_label = String::new();
+    // Footnotes are numbered in order of first reference; their
+    // definitions are set aside and rendered together at the end
+    let footnote_order = footnote_order(content);
+    let footnote_number = |label: &str| {
+        footnote_order.iter().position(|l| l.eq_ignore_ascii_case(label)).map_or(0, |i| i + 1)
+    };
+    // Start line, number and source line of the definition being rendered
+    let mut footnote_start: Option<(usize, usize, usize)> = None;
+    // Rendered definitions: number, lines, source line
+    let mut footnotes: Vec<(usize, Vec<Line<'static>>, usize)> = Vec::new();
+
+    // Definition list state
     let mut _in_definition_title = false;
     let mut _in_definition_def = false;
 
@@ -66,8 +141,12 @@ pub fn render_markdown(content: &str, width: usize) -> RenderedMarkdown {
     let mut image_url = String::new();
     let mut link_urls: Vec<String> = Vec::new();
     let mut image_infos: Vec<ImageInfo> = Vec::new();
+    let mut headings: Vec<HeadingInfo> = Vec::new();
 
-    for event in parser {
+    for (event, range) in parser {
+        // Lines output while handling an event belong to the source line it
+        // starts on (for a block's End event, where the block starts)
+        let source_line = line_starts.partition_point(|&start| start <= range.start) - 1;
         match event {
             Event::Start(tag) => match tag {
                 Tag::Heading { level, .. } => {
@@ -75,7 +154,7 @@ pub fn render_markdown(content: &str, width: usize) -> RenderedMarkdown {
                     _heading_level = level as u8;
                     // Extra spacing before headings (avoid doubles if previous line is blank)
                     flush_line(&mut lines, &mut current_spans);
-                    let prev_blank = lines.last().map_or(true, |l| l.spans.is_empty() || l.spans.iter().all(|s| s.content.trim().is_empty() || s.content.trim() == "│"));
+                    let prev_blank = lines.last().is_none_or(|l| l.spans.is_empty() || l.spans.iter().all(|s| s.content.trim().is_empty() || s.content.trim() == "│"));
                     if _heading_level <= 1 {
                         // 2 blank lines before H1
                         if !prev_blank { push_blank_line(&mut lines, blockquote_depth); }
@@ -105,7 +184,7 @@ pub fn render_markdown(content: &str, width: usize) -> RenderedMarkdown {
                     style_stack.push(compose_style(
                         base,
                         Style::default()
-                            .fg(theme::FG)
+                            .fg(theme::active().fg)
                             .add_modifier(Modifier::CROSSED_OUT),
                     ));
                 }
@@ -118,7 +197,7 @@ pub fn render_markdown(content: &str, width: usize) -> RenderedMarkdown {
                     image_url = dest_url.to_string();
                     // Flush any pending content before the image box
                     flush_line(&mut lines, &mut current_spans);
-                    style_stack.push(Style::default().fg(theme::FG));
+                    style_stack.push(Style::default().fg(theme::active().fg));
                 }
                 Tag::CodeBlock(kind) => {
                     in_code_block = true;
@@ -151,7 +230,7 @@ pub fn render_markdown(content: &str, width: usize) -> RenderedMarkdown {
                     };
                     current_spans.push(Span::styled(
                         bullet,
-                        Style::default().fg(theme::FG),
+                        Style::default().fg(theme::active().fg),
                     ));
                 }
                 Tag::Table(alignments) => {
@@ -171,12 +250,13 @@ pub fn render_markdown(content: &str, width: usize) -> RenderedMarkdown {
                     current_cell.clear();
                 }
                 Tag::FootnoteDefinition(label) => {
-                    _in_footnote_def = true;
-                    footnote_label = label.to_string();
+                    flush_line(&mut lines, &mut current_spans);
+                    let number = footnote_number(&label);
+                    footnote_start = Some((lines.len(), number, source_line));
                     push_bq_prefix(&mut current_spans, blockquote_depth);
                     current_spans.push(Span::styled(
-                        format!("[{}]: ", footnote_label),
-                        Style::default().fg(theme::BORDER),
+                        format!("{} ", superscript(number)),
+                        Style::default().fg(theme::active().link),
                     ));
                 }
                 Tag::DefinitionList => {}
@@ -189,7 +269,7 @@ pub fn render_markdown(content: &str, width: usize) -> RenderedMarkdown {
                     push_bq_prefix(&mut current_spans, blockquote_depth);
                     current_spans.push(Span::styled(
                         ":  ".to_string(),
-                        Style::default().fg(theme::BORDER),
+                        Style::default().fg(theme::active().border),
                     ));
                 }
                 Tag::Paragraph => {}
@@ -202,23 +282,24 @@ pub fn render_markdown(content: &str, width: usize) -> RenderedMarkdown {
                     _heading_level = 0;
                     style_stack.pop();
                     flush_line(&mut lines, &mut current_spans);
+                    headings.push(HeadingInfo { line: lines.len() - 1, level: hlevel, source_line });
                     // Add underline for H1 (heavy) and H2 (light)
                     let bq_w = blockquote_depth * 2;
                     if hlevel == 1 {
                         let rule = "━".repeat(width.saturating_sub(bq_w));
                         let mut spans: Vec<Span<'static>> = Vec::new();
                         if blockquote_depth > 0 {
-                            spans.push(Span::styled("│ ".repeat(blockquote_depth), Style::default().fg(theme::QUOTE_BORDER)));
+                            spans.push(bq_prefix(blockquote_depth));
                         }
-                        spans.push(Span::styled(rule, Style::default().fg(theme::HEADING)));
+                        spans.push(Span::styled(rule, Style::default().fg(theme::active().heading)));
                         lines.push(Line::from(spans));
                     } else if hlevel == 2 {
                         let rule = "─".repeat(width.saturating_sub(bq_w));
                         let mut spans: Vec<Span<'static>> = Vec::new();
                         if blockquote_depth > 0 {
-                            spans.push(Span::styled("│ ".repeat(blockquote_depth), Style::default().fg(theme::QUOTE_BORDER)));
+                            spans.push(bq_prefix(blockquote_depth));
                         }
-                        spans.push(Span::styled(rule, Style::default().fg(theme::HEADING)));
+                        spans.push(Span::styled(rule, Style::default().fg(theme::active().heading)));
                         lines.push(Line::from(spans));
                     }
                     push_blank_line(&mut lines, blockquote_depth);
@@ -235,7 +316,7 @@ pub fn render_markdown(content: &str, width: usize) -> RenderedMarkdown {
                         // Append the URL in dimmed parentheses after the link text
                         current_spans.push(Span::styled(
                             format!(" ({})", link_url),
-                            Style::default().fg(theme::LINE_NUMBER),
+                            Style::default().fg(theme::active().line_number),
                         ));
                         link_urls.push(link_url.clone());
                         link_url.clear();
@@ -250,62 +331,11 @@ pub fn render_markdown(content: &str, width: usize) -> RenderedMarkdown {
 
                     // Extract filename from URL
                     let filename = image_url.rsplit('/').next().unwrap_or(&image_url).to_string();
-                    let border_style = Style::default().fg(theme::BORDER);
-                    let text_style = Style::default().fg(theme::FG).add_modifier(Modifier::ITALIC);
-                    let dim_style = Style::default().fg(theme::LINE_NUMBER);
-
-                    let inner_width = alt_display.len().max(filename.len()).max(6) + 2;
-                    let top = format!("╭─{}─╮", "─".repeat(inner_width));
-                    let bot = format!("╰─{}─╯", "─".repeat(inner_width));
-
-                    let bq = |spans: &mut Vec<Span<'static>>| {
-                        if blockquote_depth > 0 {
-                            spans.push(Span::styled("│ ".repeat(blockquote_depth), border_style));
-                        }
-                    };
-
-                    // Top border
-                    let mut top_spans = Vec::new();
-                    bq(&mut top_spans);
-                    top_spans.push(Span::styled(top, border_style));
-                    lines.push(Line::from(top_spans));
-
-                    // Alt text line
-                    let alt_pad = inner_width.saturating_sub(alt_display.len());
-                    let mut alt_spans = Vec::new();
-                    bq(&mut alt_spans);
-                    alt_spans.push(Span::styled("│ ", border_style));
-                    alt_spans.push(Span::styled(alt_display, text_style));
-                    alt_spans.push(Span::styled(format!("{} │", " ".repeat(alt_pad)), border_style));
-                    lines.push(Line::from(alt_spans));
-
-                    // Filename line
-                    let fn_pad = inner_width.saturating_sub(filename.len());
-                    let mut fn_spans = Vec::new();
-                    bq(&mut fn_spans);
-                    fn_spans.push(Span::styled("│ ", border_style));
-                    fn_spans.push(Span::styled(filename, dim_style));
-                    fn_spans.push(Span::styled(format!("{} │", " ".repeat(fn_pad)), border_style));
-                    lines.push(Line::from(fn_spans));
-
-                    // Bottom border
-                    let mut bot_spans = Vec::new();
-                    bq(&mut bot_spans);
-                    bot_spans.push(Span::styled(bot, border_style));
-                    lines.push(Line::from(bot_spans));
-
-                    // Reserve extra blank lines so the image overlay has room.
-                    // The half-block renderer will overwrite these.
-                    let target_height = 15usize;
-                    let current_height = lines.len() - img_start_line;
-                    for _ in current_height..target_height {
-                        let mut blank = Vec::new();
-                        bq(&mut blank);
-                        lines.push(Line::from(blank));
-                    }
+                    push_image_box(&mut lines, &alt_display, &filename, width, blockquote_depth);
 
                     image_infos.push(ImageInfo {
                         url: image_url.clone(),
+                        diagram: None,
                         start_line: img_start_line,
                         line_count: lines.len() - img_start_line,
                     });
@@ -313,22 +343,34 @@ pub fn render_markdown(content: &str, width: usize) -> RenderedMarkdown {
                 }
                 TagEnd::CodeBlock => {
                     in_code_block = false;
-                    let code_width = width.saturating_sub(blockquote_depth * 2);
-                    let highlighted = code_highlight::highlight_code(
-                        &code_block_content,
-                        &code_block_lang,
-                        code_width,
-                    );
-                    for line in highlighted {
-                        if blockquote_depth > 0 {
-                            let mut bq_spans = vec![Span::styled(
-                                "│ ".repeat(blockquote_depth),
-                                Style::default().fg(theme::QUOTE_BORDER),
-                            )];
-                            bq_spans.extend(line.spans);
-                            lines.push(Line::from(bq_spans));
-                        } else {
-                            lines.push(line);
+                    let info = code_highlight::parse_fence_info(&code_block_lang);
+                    // Diagrams show as an image, once their command renders them
+                    let diagram = diagrams
+                        .then(|| Diagram::for_code_block(&info.lang, &code_block_content))
+                        .flatten();
+                    if let Some(diagram) = diagram {
+                        let start_line = lines.len();
+                        let label = format!("{} diagram", info.lang);
+                        let detail = format!("rendering with {}", diagram.kind.command());
+                        push_image_box(&mut lines, &label, &detail, width, blockquote_depth);
+                        image_infos.push(ImageInfo {
+                            url: String::new(),
+                            diagram: Some(diagram),
+                            start_line,
+                            line_count: lines.len() - start_line,
+                        });
+                    } else {
+                        let code_width = width.saturating_sub(blockquote_depth * 2);
+                        let highlighted =
+                            code_highlight::highlight_fenced_code(&code_block_content, &info, code_width);
+                        for line in highlighted {
+                            if blockquote_depth > 0 {
+                                let mut bq_spans = vec![bq_prefix(blockquote_depth)];
+                                bq_spans.extend(line.spans);
+                                lines.push(Line::from(bq_spans));
+                            } else {
+                                lines.push(line);
+                            }
                         }
                     }
                     push_blank_line(&mut lines, blockquote_depth);
@@ -362,7 +404,7 @@ pub fn render_markdown(content: &str, width: usize) -> RenderedMarkdown {
                 TagEnd::TableRow => {}
                 TagEnd::TableCell => {
                     if let Some(row) = table_rows.last_mut() {
-                        row.push(current_cell.drain(..).collect());
+                        row.push(std::mem::take(&mut current_cell));
                     }
                 }
                 TagEnd::Paragraph => {
@@ -370,9 +412,15 @@ pub fn render_markdown(content: &str, width: usize) -> RenderedMarkdown {
                     push_blank_line(&mut lines, blockquote_depth);
                 }
                 TagEnd::FootnoteDefinition => {
-                    _in_footnote_def = false;
-                    footnote_label.clear();
                     flush_line(&mut lines, &mut current_spans);
+                    if let Some((start, number, def_line)) = footnote_start.take() {
+                        let mut def_lines = lines.split_off(start);
+                        // Footnotes are listed without blank lines between
+                        while def_lines.last().is_some_and(|l| l.width() == 0) {
+                            def_lines.pop();
+                        }
+                        footnotes.push((number, def_lines, def_line));
+                    }
                 }
                 TagEnd::DefinitionList => {
                     lines.push(Line::from(""));
@@ -425,43 +473,48 @@ pub fn render_markdown(content: &str, width: usize) -> RenderedMarkdown {
                     ));
                 }
             }
-            Event::SoftBreak | Event::HardBreak => {
-                if !in_table {
-                    flush_line(&mut lines, &mut current_spans);
-                }
+            Event::SoftBreak | Event::HardBreak if !in_table => {
+                flush_line(&mut lines, &mut current_spans);
             }
             Event::FootnoteReference(label) => {
                 push_bq_prefix(&mut current_spans, blockquote_depth);
+                // Not underlined: only links with a URL look clickable
                 current_spans.push(Span::styled(
-                    format!("[{}]", label),
-                    theme::link_style(),
+                    superscript(footnote_number(&label)),
+                    Style::default().fg(theme::active().link),
                 ));
             }
             Event::TaskListMarker(checked) => {
                 let marker = if checked { "[x] " } else { "[ ] " };
                 let style = if checked {
-                    Style::default().fg(theme::SUCCESS)
+                    Style::default().fg(theme::active().success)
                 } else {
-                    Style::default().fg(theme::FG)
+                    Style::default().fg(theme::active().fg)
                 };
                 current_spans.push(Span::styled(marker.to_string(), style));
             }
             Event::InlineMath(text) => {
                 push_bq_prefix(&mut current_spans, blockquote_depth);
-                let converted = latex_to_unicode(&text);
+                let math = convert_math(&text);
+                let shown = if raw_math { format!("${}$", math.source) } else { math.unicode };
                 current_spans.push(Span::styled(
-                    converted,
-                    Style::default().fg(theme::CODE).add_modifier(Modifier::ITALIC),
+                    shown,
+                    Style::default().fg(theme::active().code).add_modifier(Modifier::ITALIC),
                 ));
             }
             Event::DisplayMath(text) => {
                 flush_line(&mut lines, &mut current_spans);
-                let math_style = Style::default().fg(theme::CODE).add_modifier(Modifier::ITALIC);
-                let converted = latex_to_unicode(&text);
-                for math_line in converted.split('\n') {
+                let math_style = Style::default().fg(theme::active().code).add_modifier(Modifier::ITALIC);
+                let math = convert_math(&text);
+                let shown = if raw_math {
+                    format!("$$\n{}\n$$", math.source.trim_matches('\n'))
+                } else {
+                    math.unicode
+                };
+                for math_line in shown.split('\n') {
                     let mut ml = Vec::new();
                     if blockquote_depth > 0 {
-                        ml.push(Span::styled("│ ".repeat(blockquote_depth), Style::default().fg(theme::QUOTE_BORDER)));
+                        ml.push(bq_prefix(blockquote_depth));
                     }
                     ml.push(Span::styled(format!("  {}", math_line), math_style));
                     lines.push(Line::from(ml));
@@ -478,28 +531,152 @@ pub fn render_markdown(content: &str, width: usize) -> RenderedMarkdown {
                 };
                 let mut rule_spans: Vec<Span<'static>> = Vec::new();
                 if blockquote_depth > 0 {
-                    rule_spans.push(Span::styled(
-                        "│ ".repeat(blockquote_depth),
-                        Style::default().fg(theme::QUOTE_BORDER),
-                    ));
+                    rule_spans.push(bq_prefix(blockquote_depth));
                 }
-                rule_spans.push(Span::styled(rule, Style::default().fg(theme::BORDER)));
+                rule_spans.push(Span::styled(rule, Style::default().fg(theme::active().border)));
                 lines.push(Line::from(rule_spans));
                 push_blank_line(&mut lines, blockquote_depth);
             }
             _ => {}
         }
+        source_line_map.resize(lines.len(), Some(source_line));
     }
 
     // Flush remaining spans
     if !current_spans.is_empty() {
         flush_line(&mut lines, &mut current_spans);
     }
+    source_line_map.resize(lines.len(), None);
+
+    // Footnote definitions, in number order, below a short rule
+    if !footnotes.is_empty() {
+        footnotes.sort_by_key(|(number, _, _)| *number);
+        if lines.last().is_some_and(|l| l.width() > 0) {
+            lines.push(Line::from(""));
+        }
+        let rule = "─".repeat(width.min(FOOTNOTE_RULE_WIDTH));
+        lines.push(Line::from(Span::styled(rule, Style::default().fg(theme::active().border))));
+        source_line_map.resize(lines.len(), None);
+        for (_, def_lines, def_line) in footnotes {
+            lines.extend(def_lines);
+            source_line_map.resize(lines.len(), Some(def_line));
+        }
+    }
+
+    style_blockquotes(&mut lines, width);
 
     RenderedMarkdown {
         text: Text::from(lines),
         link_urls,
         image_infos,
+        source_line_map,
+        footnote_order,
+        headings,
+    }
+}
+
+/// Columns of the rule above the footnotes.
+const FOOTNOTE_RULE_WIDTH: usize = 20;
+
+/// Footnote labels in the order their footnotes are numbered: by first
+/// reference, then any never referenced, by definition.
+fn footnote_order(content: &str) -> Vec<String> {
+    let mut referenced: Vec<String> = Vec::new();
+    let mut defined: Vec<String> = Vec::new();
+    for event in Parser::new_ext(content, parser_options()) {
+        let (list, label) = match event {
+            Event::FootnoteReference(label) => (&mut referenced, label),
+            Event::Start(Tag::FootnoteDefinition(label)) => (&mut defined, label),
+            _ => continue,
+        };
+        if !list.iter().any(|l| l.eq_ignore_ascii_case(&label)) {
+            list.push(label.to_string());
+        }
+    }
+    for label in defined {
+        if !referenced.iter().any(|l| l.eq_ignore_ascii_case(&label)) {
+            referenced.push(label);
+        }
+    }
+    referenced
+}
+
+/// `n` in superscript digits, as footnote numbers are shown.
+fn superscript(n: usize) -> String {
+    const DIGITS: [char; 10] = ['⁰', '¹', '²', '³', '⁴', '⁵', '⁶', '⁷', '⁸', '⁹'];
+    n.to_string()
+        .chars()
+        .filter_map(|d| d.to_digit(10).map(|d| DIGITS[d as usize]))
+        .collect()
+}
+
+/// Pushes the placeholder box an image (or diagram) is drawn over: the
+/// `label` and `detail` in a border, then blank lines to make room.
+fn push_image_box(
+    lines: &mut Vec<Line<'static>>,
+    label: &str,
+    detail: &str,
+    width: usize,
+    blockquote_depth: usize,
+) {
+    let img_start_line = lines.len();
+    let border_style = Style::default().fg(theme::active().border);
+    let text_style = Style::default().fg(theme::active().fg).add_modifier(Modifier::ITALIC);
+    let dim_style = Style::default().fg(theme::active().line_number);
+
+    // Clamp the box to the available width (minus "╭─", "─╮" and any
+    // blockquote prefix), truncating the labels to match.
+    let max_inner = width.saturating_sub(4 + blockquote_depth * 2);
+    let inner_width = (label.len().max(detail.len()).max(6) + 2).min(max_inner);
+    let label: String = label.chars().take(inner_width).collect();
+    let detail: String = detail.chars().take(inner_width).collect();
+    let top = format!("╭─{}─╮", "─".repeat(inner_width));
+    let bot = format!("╰─{}─╯", "─".repeat(inner_width));
+
+    let bq = |spans: &mut Vec<Span<'static>>| {
+        if blockquote_depth > 0 {
+            spans.push(bq_prefix(blockquote_depth));
+        }
+    };
+
+    // Top border
+    let mut top_spans = Vec::new();
+    bq(&mut top_spans);
+    top_spans.push(Span::styled(top, border_style));
+    lines.push(Line::from(top_spans));
+
+    // Label line
+    let label_pad = inner_width.saturating_sub(label.len());
+    let mut label_spans = Vec::new();
+    bq(&mut label_spans);
+    label_spans.push(Span::styled("│ ", border_style));
+    label_spans.push(Span::styled(label, text_style));
+    label_spans.push(Span::styled(format!("{} │", " ".repeat(label_pad)), border_style));
+    lines.push(Line::from(label_spans));
+
+    // Detail line (an image's filename)
+    let detail_pad = inner_width.saturating_sub(detail.len());
+    let mut detail_spans = Vec::new();
+    bq(&mut detail_spans);
+    detail_spans.push(Span::styled("│ ", border_style));
+    detail_spans.push(Span::styled(detail, dim_style));
+    detail_spans.push(Span::styled(format!("{} │", " ".repeat(detail_pad)), border_style));
+    lines.push(Line::from(detail_spans));
+
+    // Bottom border
+    let mut bot_spans = Vec::new();
+    bq(&mut bot_spans);
+    bot_spans.push(Span::styled(bot, border_style));
+    lines.push(Line::from(bot_spans));
+
+    // Reserve extra blank lines so the image overlay has room.
+    // The half-block renderer will overwrite these.
+    let target_height = 15usize;
+    let current_height = lines.len() - img_start_line;
+    for _ in current_height..target_height {
+        let mut blank = Vec::new();
+        bq(&mut blank);
+        lines.push(Line::from(blank));
     }
 }
 
@@ -543,6 +720,17 @@ fn render_table(
         }
     }
 
+    // The longest word in each column: narrower than that, cells break
+    // mid-word
+    let mut longest_words: Vec<usize> = vec![3; num_cols];
+    for row in rows {
+        for (j, cell) in row.iter().enumerate().take(num_cols) {
+            let text: String = cell.iter().map(|s| s.content.as_ref()).collect();
+            let longest = text.split_whitespace().map(|word| word.chars().count()).max().unwrap_or(0);
+            longest_words[j] = longest_words[j].max(longest).min(col_widths[j]);
+        }
+    }
+
     // Calculate available width and scale columns
     // Layout: │ col1 │ col2 │  → (num_cols+1) borders + 2 spaces per col
     let border_chars = num_cols + 1 + num_cols * 2;
@@ -565,6 +753,22 @@ fn render_table(
                 distributed += share;
             }
         }
+    } else if natural_total > available && available > 0 && longest_words.iter().sum::<usize>() <= available {
+        // Cells wrap, so shrink only the columns with room to: each keeps
+        // its longest word and shares the rest by how much it gives up
+        let excess: Vec<usize> = col_widths.iter().zip(&longest_words).map(|(w, word)| w - word).collect();
+        let total_excess: usize = excess.iter().sum();
+        let slack = available - longest_words.iter().sum::<usize>();
+        let mut distributed = 0;
+        for i in 0..col_widths.len() {
+            let share = if i == col_widths.len() - 1 {
+                slack - distributed
+            } else {
+                slack * excess[i] / total_excess.max(1)
+            };
+            col_widths[i] = longest_words[i] + share;
+            distributed += share;
+        }
     } else if natural_total > available && available > 0 {
         // Shrink columns proportionally to fit within available space
         let min_col: usize = 3;
@@ -580,89 +784,87 @@ fn render_table(
         }
     }
 
-    let border_style = Style::default().fg(theme::BORDER);
+    let border_style = Style::default().fg(theme::active().border);
 
-    // Render each row
+    // Render each row, its cells wrapped to the column widths: a row is as
+    // many lines tall as its tallest cell
     for (i, row) in rows.iter().enumerate() {
-        let mut spans: Vec<Span<'static>> = Vec::new();
-        if bq_depth > 0 {
-            spans.push(Span::styled("│ ".repeat(bq_depth), border_style));
-        }
-        spans.push(Span::styled("│ ".to_string(), border_style));
+        let wrapped: Vec<Vec<Vec<Span<'static>>>> = col_widths
+            .iter()
+            .enumerate()
+            .map(|(j, &max_w)| row.get(j).map_or_else(Vec::new, |cell| wrap_cell(cell, max_w)))
+            .collect();
+        let height = wrapped.iter().map(|cell| cell.len()).max().unwrap_or(0).max(1);
+
+        for k in 0..height {
+            let mut spans: Vec<Span<'static>> = Vec::new();
+            if bq_depth > 0 {
+                spans.push(bq_prefix(bq_depth));
+            }
+            spans.push(Span::styled("│ ".to_string(), border_style));
 
-        for j in 0..num_cols {
-            let cell = row.get(j);
-            let max_w = col_widths[j];
-            let cell_width: usize = cell.map_or(0, |c| c.iter().map(|s| s.width()).sum());
-            let pad = max_w.saturating_sub(cell_width);
-            let align = alignments.get(j).copied().unwrap_or(Alignment::None);
-            let pad_style = Style::default().fg(theme::FG);
+            for (j, &max_w) in col_widths.iter().enumerate() {
+                let cell = wrapped[j].get(k);
+                let cell_width: usize = cell.map_or(0, |c| c.iter().map(|s| s.width()).sum());
+                let pad = max_w.saturating_sub(cell_width);
+                let align = alignments.get(j).copied().unwrap_or(Alignment::None);
+                let pad_style = Style::default().fg(theme::active().fg);
 
-            // Left padding for right/center alignment
-            match align {
-                Alignment::Right => {
-                    spans.push(Span::styled(" ".repeat(pad), pad_style));
+                // Left padding for right/center alignment
+                match align {
+                    Alignment::Right => {
+                        spans.push(Span::styled(" ".repeat(pad), pad_style));
+                    }
+                    Alignment::Center => {
+                        let left_pad = pad / 2;
+                        spans.push(Span::styled(" ".repeat(left_pad), pad_style));
+                    }
+                    _ => {}
                 }
-                Alignment::Center => {
-                    let left_pad = pad / 2;
-                    spans.push(Span::styled(" ".repeat(left_pad), pad_style));
+
+                if let Some(cell_spans) = cell {
+                    spans.extend(cell_spans.iter().cloned());
                 }
-                _ => {}
-            }
 
-            if let Some(cell_spans) = cell {
-                if cell_width <= max_w {
-                    for s in cell_spans {
-                        spans.push(s.clone());
+                // Right padding for left/none/center alignment
+                match align {
+                    Alignment::Right => {}
+                    Alignment::Center => {
+                        let right_pad = pad - pad / 2;
+                        spans.push(Span::styled(" ".repeat(right_pad), pad_style));
                     }
-                } else {
-                    // Truncate cell content to fit column width
-                    let mut remaining = max_w;
-                    for s in cell_spans {
-                        let sw = s.width();
-                        if sw <= remaining {
-                            spans.push(s.clone());
-                            remaining -= sw;
-                        } else if remaining > 0 {
-                            let truncated: String = s.content.chars().take(remaining).collect();
-                            spans.push(Span::styled(truncated, s.style));
-                            remaining = 0;
-                        }
+                    _ => {
+                        spans.push(Span::styled(" ".repeat(pad), pad_style));
                     }
                 }
-            }
 
-            // Right padding for left/none/center alignment
-            match align {
-                Alignment::Right => {}
-                Alignment::Center => {
-                    let right_pad = pad - pad / 2;
-                    spans.push(Span::styled(" ".repeat(right_pad), pad_style));
-                }
-                _ => {
-                    spans.push(Span::styled(" ".repeat(pad), pad_style));
+                if j < num_cols - 1 {
+                    spans.push(Span::styled(" │ ".to_string(), border_style));
+                } else {
+                    spans.push(Span::styled(" │".to_string(), border_style));
                 }
             }
 
-            if j < num_cols - 1 {
-                spans.push(Span::styled(" │ ".to_string(), border_style));
-            } else {
-                spans.push(Span::styled(" │".to_string(), border_style));
-            }
+            lines.push(Line::from(spans));
         }
 
-        lines.push(Line::from(spans));
-
         // Add separator line after header
         if i + 1 == header_count {
             let mut sep_spans: Vec<Span<'static>> = Vec::new();
             if bq_depth > 0 {
-                sep_spans.push(Span::styled("│ ".repeat(bq_depth), border_style));
+                sep_spans.push(bq_prefix(bq_depth));
             }
             sep_spans.push(Span::styled("├".to_string(), border_style));
-            for j in 0..num_cols {
+            for (j, &col_w) in col_widths.iter().enumerate() {
+                // Colons at the aligned ends, as in the markdown (`:---:`)
+                let (left, right) = match alignments.get(j).copied().unwrap_or(Alignment::None) {
+                    Alignment::Left => (":", "─"),
+                    Alignment::Center => (":", ":"),
+                    Alignment::Right => ("─", ":"),
+                    Alignment::None => ("─", "─"),
+                };
                 sep_spans.push(Span::styled(
-                    "─".repeat(col_widths[j] + 2),
+                    format!("{}{}{}", left, "─".repeat(col_w), right),
                     border_style,
                 ));
                 if j < num_cols - 1 {
@@ -676,14 +878,56 @@ fn render_table(
     }
 }
 
+/// Word-wraps a table cell's spans to `width` columns, one Vec of spans
+/// per line. Words wider than the column are broken.
+fn wrap_cell(cell: &[Span<'static>], width: usize) -> Vec<Vec<Span<'static>>> {
+    let width = width.max(1);
+    let mut out: Vec<Vec<Span<'static>>> = vec![Vec::new()];
+    let mut col = 0;
+    for span in cell {
+        for word in span.content.split_inclusive(' ') {
+            let mut word = word.to_string();
+            if col > 0 && col + word.trim_end().chars().count() > width {
+                out.push(Vec::new());
+                col = 0;
+            }
+            while word.trim_end().chars().count() > width - col.min(width) {
+                let room = width - col.min(width);
+                let head: String = word.chars().take(room).collect();
+                word = word.chars().skip(room).collect();
+                out.last_mut().unwrap().push(Span::styled(head, span.style));
+                out.push(Vec::new());
+                col = 0;
+            }
+            if !word.is_empty() {
+                col += word.chars().count();
+                out.last_mut().unwrap().push(Span::styled(word, span.style));
+            }
+        }
+    }
+    // The space a line broke at doesn't take up a column
+    for line in &mut out {
+        while line.last().is_some_and(|span| span.content.trim_end().is_empty()) {
+            line.pop();
+        }
+        if let Some(last) = line.last_mut() {
+            last.content = last.content.trim_end().to_string().into();
+        }
+    }
+    while out.len() > 1 && out.last().is_some_and(|line| line.is_empty()) {
+        out.pop();
+    }
+    out
+}
+
 fn flush_line(lines: &mut Vec<Line<'static>>, spans: &mut Vec<Span<'static>>) {
     if !spans.is_empty() {
-        lines.push(Line::from(spans.drain(..).collect::<Vec<_>>()));
+        lines.push(Line::from(std::mem::take(spans)));
     }
 }
 
 fn current_style(stack: &[Style]) -> Style {
-    stack.last().copied().unwrap_or(Style::default().fg(theme::FG))
+    stack.last().copied().unwrap_or(Style::default().fg(theme::active().fg))
 }
 
 fn word_wrap(text: &str, max_width: usize, existing_spans: &[Span]) -> Vec<String> {
@@ -726,28 +970,171 @@ fn compose_style(base: Style, overlay: Style) -> Style {
     result
 }
 
+/// The `│ ` bar in front of a line `depth` blockquotes deep.
+fn bq_prefix(depth: usize) -> Span<'static> {
+    Span::styled("│ ".repeat(depth), Style::default().fg(theme::active().quote_border))
+}
+
+/// How many blockquotes deep `line` is, from its `bq_prefix`.
+fn quote_depth(line: &Line) -> usize {
+    let Some(first) = line.spans.first() else {
+        return 0;
+    };
+    let depth = first.content.chars().count() / 2;
+    let is_prefix = first.style.fg == Some(theme::active().quote_border) && first.content == "│ ".repeat(depth);
+    if is_prefix { depth } else { 0 }
+}
+
+/// Applies the active theme's blockquote look: a `quote_bg` tint across
+/// the full `width` of quoted lines, and the bar only with `quote_bar`.
+fn style_blockquotes(lines: &mut [Line<'static>], width: usize) {
+    let theme = theme::active();
+    if theme.quote_bar && theme.quote_bg == Color::Reset {
+        return;
+    }
+    for line in lines {
+        let depth = quote_depth(line);
+        if depth == 0 {
+            continue;
+        }
+        if !theme.quote_bar {
+            line.spans[0].content = "  ".repeat(depth).into();
+        }
+        if let Some(bg) = quote_tint(theme.quote_bg, depth) {
+            // Code blocks and inline code keep their own background
+            for span in &mut line.spans {
+                span.style.bg = span.style.bg.or(Some(bg));
+            }
+            let pad = width.saturating_sub(line.width());
+            if pad > 0 {
+                line.spans.push(Span::styled(" ".repeat(pad), Style::default().bg(bg)));
+            }
+        }
+    }
+}
+
+/// The background of a line `depth` blockquotes deep: `quote_bg`, a
+/// fifth darker per level past the first. None when `quote_bg` is Reset.
+fn quote_tint(quote_bg: Color, depth: usize) -> Option<Color> {
+    match quote_bg {
+        Color::Reset => None,
+        Color::Rgb(r, g, b) => {
+            let shade = 0.8f32.powi(depth.saturating_sub(1) as i32);
+            let darken = |c: u8| (c as f32 * shade).round() as u8;
+            Some(Color::Rgb(darken(r), darken(g), darken(b)))
+        }
+        // Named colors have no darker shade to step to
+        color => Some(color),
+    }
+}
+
 /// Push blockquote `│ ` prefix to spans if at start of a new line (spans empty).
 fn push_bq_prefix(spans: &mut Vec<Span<'static>>, depth: usize) {
     if depth > 0 && spans.is_empty() {
-        spans.push(Span::styled(
-            "│ ".repeat(depth),
-            Style::default().fg(theme::QUOTE_BORDER),
-        ));
+        spans.push(bq_prefix(depth));
     }
 }
 
 /// Push a blank line, with blockquote prefix if inside a blockquote.
 fn push_blank_line(lines: &mut Vec<Line<'static>>, bq_depth: usize) {
     if bq_depth > 0 {
-        lines.push(Line::from(Span::styled(
-            "│ ".repeat(bq_depth),
-            Style::default().fg(theme::QUOTE_BORDER),
-        )));
+        lines.push(Line::from(bq_prefix(bq_depth)));
     } else {
         lines.push(Line::from(""));
     }
 }
 
+/// Renders `content` at `width` columns to styled lines, as the preview
+/// shows them (images appear as their placeholders, diagrams as code).
+pub fn render_lines(content: &str, width: usize) -> Vec<Line<'static>> {
+    render(content, width, false, false).text.lines
+}
+
+/// The first output line of the block that source line `line` (0-indexed)
+/// is part of, given a `RenderedMarkdown::source_line_map`: the nearest
+/// block starting at or above `line`.
+pub fn rendered_line_of(source_line_map: &[Option<usize>], line: usize) -> usize {
+    let mut best: Option<(usize, usize)> = None; // (source line, output line)
+    for (row, source) in source_line_map.iter().enumerate() {
+        if let Some(source) = *source {
+            if source <= line && best.is_none_or(|(s, _)| source > s) {
+                best = Some((source, row));
+            }
+        }
+    }
+    best.map_or(0, |(_, row)| row)
+}
+
+/// Serializes styled lines to text with ANSI escape sequences, one line per
+/// `\n`. With `color` off the plain text is returned, as for `$NO_COLOR`.
+pub fn lines_to_ansi(lines: &[Line], color: bool) -> String {
+    let mut out = String::new();
+    for line in lines {
+        for span in &line.spans {
+            let style = line.style.patch(span.style);
+            let codes = if color { sgr_codes(style) } else { Vec::new() };
+            if codes.is_empty() {
+                out.push_str(&span.content);
+            } else {
+                out.push_str(&format!("\x1b[{}m{}\x1b[0m", codes.join(";"), span.content));
+            }
+        }
+        out.push('\n');
+    }
+    out
+}
+
+/// SGR parameters for `style`'s colors and modifiers.
+fn sgr_codes(style: Style) -> Vec<String> {
+    let mut codes = Vec::new();
+    for (modifier, code) in [
+        (Modifier::BOLD, "1"),
+        (Modifier::DIM, "2"),
+        (Modifier::ITALIC, "3"),
+        (Modifier::UNDERLINED, "4"),
+        (Modifier::REVERSED, "7"),
+        (Modifier::CROSSED_OUT, "9"),
+    ] {
+        if style.add_modifier.contains(modifier) {
+            codes.push(code.to_string());
+        }
+    }
+    if let Some(fg) = style.fg.and_then(|c| color_code(c, false)) {
+        codes.push(fg);
+    }
+    if let Some(bg) = style.bg.and_then(|c| color_code(c, true)) {
+        codes.push(bg);
+    }
+    codes
+}
+
+/// SGR color parameter; None for `Reset` (the terminal default).
+fn color_code(color: Color, background: bool) -> Option<String> {
+    let base = if background { 40 } else { 30 };
+    let code = match color {
+        Color::Reset => return None,
+        Color::Black => base,
+        Color::Red => base + 1,
+        Color::Green => base + 2,
+        Color::Yellow => base + 3,
+        Color::Blue => base + 4,
+        Color::Magenta => base + 5,
+        Color::Cyan => base + 6,
+        Color::Gray => base + 7,
+        Color::DarkGray => base + 60,
+        Color::LightRed => base + 61,
+        Color::LightGreen => base + 62,
+        Color::LightYellow => base + 63,
+        Color::LightBlue => base + 64,
+        Color::LightMagenta => base + 65,
+        Color::LightCyan => base + 66,
+        Color::White => base + 67,
+        Color::Indexed(i) => return Some(format!("{};5;{}", base + 8, i)),
+        Color::Rgb(r, g, b) => return Some(format!("{};2;{};{};{}", base + 8, r, g, b)),
+    };
+    Some(code.to_string())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -792,7 +1179,7 @@ mod tests {
         assert!(!text.lines.is_empty());
         let first_line = &text.lines[0];
         let has_code = first_line.spans.iter().any(|s| {
-            s.style.fg == Some(theme::CODE) && s.content.contains("code")
+            s.style.fg == Some(theme::active().code) && s.content.contains("code")
         });
         assert!(has_code);
     }
@@ -905,4 +1292,214 @@ mod tests {
         });
         assert!(has_strikethrough, "Should render strikethrough text");
     }
+
+    #[test]
+    fn test_image_box_clamped_to_width() {
+        let md = "![a very long alt text that is wider than the pane](images/some-long-file-name.png)";
+        let narrow_width = 20;
+        let text = render_markdown(md, narrow_width).text;
+        for line in &text.lines {
+            let line_width: usize = line.spans.iter().map(|s| s.width()).sum();
+            assert!(
+                line_width <= narrow_width,
+                "Image box line width {} exceeds available width {}",
+                line_width,
+                narrow_width
+            );
+        }
+    }
+
+    #[test]
+    fn test_render_code_block_line_numbers() {
+        let md = "```rust {start=10 highlight=2}\nlet a = 1;\nlet b = 2;\n```";
+        let text = render_markdown(md, 40).text;
+        let contents: Vec<String> = text
+            .lines
+            .iter()
+            .map(|line| line.spans.iter().map(|s| s.content.as_ref()).collect())
+            .collect();
+        assert!(contents.iter().any(|l| l.contains("┌─ rust ─")), "label without options");
+        let first = contents.iter().position(|l| l.contains("10 │ let a")).expect("numbered line");
+        assert!(contents[first + 1].contains("11 │ let b"));
+        let highlighted = &text.lines[first + 1];
+        assert!(highlighted
+            .spans
+            .iter()
+            .all(|s| s.style.bg == Some(theme::active().code_highlight_bg)));
+    }
+
+    #[test]
+    fn test_footnotes_numbered_by_first_reference() {
+        let md = "[^b]: Bee note.\n\nFirst[^a] then[^b] and[^a] again.\n\n[^a]: Ay note.\n\n[^unused]: Never cited.\n";
+        let rendered = render_markdown(md, 60);
+        assert_eq!(rendered.footnote_order, ["a", "b", "unused"]);
+        let contents: Vec<String> = rendered
+            .text
+            .lines
+            .iter()
+            .map(|line| line.spans.iter().map(|s| s.content.as_ref()).collect())
+            .collect();
+        assert!(contents.iter().any(|l| l == "First¹ then² and¹ again."), "{:?}", contents);
+        // Definitions come last, in number order, whatever their position
+        let rule = contents.iter().position(|l| l.starts_with('─')).expect("rule above footnotes");
+        assert_eq!(contents[rule + 1..], ["¹ Ay note.", "² Bee note.", "³ Never cited."]);
+        assert_eq!(rendered.source_line_map.len(), rendered.text.lines.len());
+        assert_eq!(rendered.source_line_map[rule + 2], Some(0), "maps back to its definition");
+    }
+
+    #[test]
+    fn test_superscript() {
+        assert_eq!(superscript(1), "¹");
+        assert_eq!(superscript(10), "¹⁰");
+    }
+
+    #[test]
+    fn test_lines_to_ansi_plain() {
+        let lines = render_lines("# Title\n\nSome **bold** text", 80);
+        let plain = lines_to_ansi(&lines, false);
+        assert!(!plain.contains('\x1b'));
+        assert!(plain.contains("Title"));
+        assert!(plain.contains("Some bold text"));
+    }
+
+    #[test]
+    fn test_lines_to_ansi_color() {
+        let lines = vec![Line::from(vec![
+            Span::raw("plain "),
+            Span::styled("bold", Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD)),
+            Span::styled(" rgb", Style::default().bg(Color::Rgb(1, 2, 3))),
+        ])];
+        assert_eq!(
+            lines_to_ansi(&lines, true),
+            "plain \x1b[1;33mbold\x1b[0m\x1b[48;2;1;2;3m rgb\x1b[0m\n"
+        );
+    }
+
+    #[test]
+    fn test_rendered_line_of() {
+        let content = "# Title\n\nFirst paragraph.\n\n- one\n- two\n\nLast.";
+        let text = render_markdown(content, 80).text;
+        let line_text = |i: usize| -> String {
+            text.lines[i].spans.iter().map(|s| s.content.as_ref()).collect()
+        };
+        let map = render_markdown(content, 80).source_line_map;
+        assert_eq!(map.len(), text.lines.len());
+        assert_eq!(rendered_line_of(&map, 0), 0);
+        for (source, needle) in [(2, "First paragraph."), (4, "one"), (7, "Last.")] {
+            let at = rendered_line_of(&map, source);
+            let found = (at..text.lines.len()).find(|&i| line_text(i).contains(needle));
+            assert!(found.is_some_and(|i| i - at <= 1), "{} at {}", needle, at);
+        }
+    }
+
+    #[test]
+    fn test_blockquote_tint_fills_row_and_darkens_with_depth() {
+        let text = |content: &str| render_markdown(content, 40).text;
+        let bar_only = text("> outer
+>
+> > inner");
+        let outer = &bar_only.lines[0];
+        assert_eq!(outer.spans[0].content, "│ ");
+        assert!(outer.spans.iter().all(|span| span.style.bg.is_none()), "presets draw the bar only");
+
+        let tinted: &'static theme::Theme = Box::leak(Box::new(theme::Theme {
+            quote_bg: Color::Rgb(50, 60, 70),
+            quote_bar: false,
+            ..theme::DARK.clone()
+        }));
+        theme::set_active(tinted);
+        let rendered = text("> outer
+>
+> > inner");
+        theme::set_active(&theme::DARK);
+
+        let line_of = |needle: &str| {
+            rendered.lines.iter()
+                .find(|line| line.spans.iter().any(|span| span.content.contains(needle)))
+                .unwrap()
+        };
+        let (outer, inner) = (line_of("outer"), line_of("inner"));
+        for (line, bg) in [(outer, Color::Rgb(50, 60, 70)), (inner, Color::Rgb(40, 48, 56))] {
+            assert_eq!(line.width(), 40, "tint fills the row");
+            assert!(line.spans.iter().all(|span| span.style.bg == Some(bg)));
+            assert!(!line.spans.iter().any(|span| span.content.contains('│')));
+        }
+    }
+
+    #[test]
+    fn test_collapsed_lines_hide_section_to_next_equal_heading() {
+        let content = "# One\n\ntext\n\n## Sub\n\nsub text\n\n# Two\n\nmore";
+        let rendered = render_markdown(content, 40);
+        let levels: Vec<(u8, usize)> = rendered.headings.iter().map(|h| (h.level, h.source_line)).collect();
+        assert_eq!(levels, vec![(1, 0), (2, 4), (1, 8)]);
+        let line_text = |i: usize| -> String {
+            rendered.text.lines[i].spans.iter().map(|s| s.content.as_ref()).collect()
+        };
+        assert_eq!(line_text(rendered.headings[1].line), "## Sub");
+
+        let shown = |collapsed: &[usize]| -> Vec<String> {
+            let hidden = rendered.collapsed_lines(&collapsed.iter().copied().collect());
+            (0..hidden.len()).filter(|&i| !hidden[i]).map(line_text).filter(|l| !l.trim().is_empty()).collect()
+        };
+        let one = shown(&[0]);
+        assert!(one.contains(&"# One".to_string()) && one.contains(&"# Two".to_string()));
+        assert!(!one.iter().any(|l| l.contains("text") || l.contains("Sub")), "H1 folds its H2");
+        let sub = shown(&[4]);
+        assert!(sub.contains(&"text".to_string()) && !sub.contains(&"sub text".to_string()));
+        assert!(sub.contains(&"more".to_string()), "ends at the next H1");
+    }
+
+    #[test]
+    fn test_render_table_wraps_long_cells() {
+        let md = "| Name | Notes |\n|---|---|\n| a | the quick brown fox jumps over the lazy dog |\n| b | short |";
+        let text = render_markdown(md, 30).text;
+        let rows: Vec<String> = text.lines.iter()
+            .map(|l| l.spans.iter().map(|s| s.content.as_ref()).collect::<String>())
+            .filter(|l| l.starts_with('│'))
+            .collect();
+        assert!(rows.iter().all(|r| r.chars().count() == 30 && r.ends_with('│')), "{:#?}", rows);
+        let notes: String = rows[1..rows.len() - 1].iter()
+            .map(|r| r.rsplit('│').nth(1).unwrap().trim().to_string())
+            .collect::<Vec<_>>()
+            .join(" ");
+        assert_eq!(notes, "the quick brown fox jumps over the lazy dog", "wrapped, nothing lost: {:#?}", rows);
+        assert!(rows.len() > 4, "the long row takes several lines: {:#?}", rows);
+        assert!(rows[1].contains(" a "), "first line carries the short cell");
+        assert!(rows.last().unwrap().contains("short"));
+    }
+
+    #[test]
+    fn test_render_table_separator_shows_alignment() {
+        let md = "| L | C | R | N |\n|:--|:-:|--:|---|\n| 1 | 2 | 3 | 4 |";
+        let text = render_markdown(md, 25).text;
+        let separator: String = text.lines.iter()
+            .map(|l| l.spans.iter().map(|s| s.content.as_ref()).collect::<String>())
+            .find(|l| l.starts_with('├'))
+            .unwrap();
+        let segments: Vec<&str> = separator.trim_start_matches('├').trim_end_matches('┤').split('┼').collect();
+        assert_eq!(segments.len(), 4);
+        assert!(segments[0].starts_with(':') && !segments[0].ends_with(':'), "{}", separator);
+        assert!(segments[1].starts_with(':') && segments[1].ends_with(':'), "center: colons at both ends");
+        assert!(!segments[2].starts_with(':') && segments[2].ends_with(':'));
+        assert!(!segments[3].contains(':'));
+        assert_eq!(separator.chars().count(), 25, "same width as the rows");
+    }
+
+    #[test]
+    fn test_render_raw_math_shows_latex_source() {
+        let md = "Area $\\pi r^2$ here.\n\n$$\n\\alpha \\leq \\beta\n$$";
+        let text = |raw_math: bool| -> Vec<String> {
+            render_markdown_with_math(md, 40, raw_math).text.lines.iter()
+                .map(|l| l.spans.iter().map(|s| s.content.as_ref()).collect::<String>())
+                .collect()
+        };
+        let converted = text(false).join("\n");
+        assert!(converted.contains("Area π r² here."), "{}", converted);
+        assert!(converted.contains("α ≤ β"));
+
+        let raw = text(true);
+        assert!(raw[0].contains("Area $\\pi r^2$ here."), "{:#?}", raw);
+        let display: Vec<&str> = raw.iter().map(|l| l.trim()).filter(|l| !l.is_empty()).skip(1).collect();
+        assert_eq!(display, ["$$", "\\alpha \\leq \\beta", "$$"]);
+    }
 }
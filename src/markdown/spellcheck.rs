@@ -0,0 +1,235 @@
+//! Spell checking for the editor: a word list loaded from disk and a scanner
+//! that finds the words of a line missing from it.
+//!
+//! The dictionary is a plain word list, one word per line (such as
+//! `/usr/share/dict/words`); hunspell `.dic` files work too, their affix
+//! flags and leading word count are skipped. Only prose is checked: inline
+//! code, URLs, link destinations, and words joined to digits or underscores
+//! are left alone. Code fences are the caller's to skip.
+
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+/// Word lists tried, in order, when `spell_dictionary` isn't set.
+pub const DEFAULT_DICTIONARIES: &[&str] = &[
+    "/usr/share/dict/words",
+    "/usr/share/hunspell/en_US.dic",
+    "/usr/share/myspell/en_US.dic",
+    "/Library/Spelling/en_US.dic",
+];
+
+/// Lines kept in a `Checker`'s cache before it starts over.
+const MAX_CACHED_LINES: usize = 10_000;
+
+/// The set of correctly spelled words.
+#[derive(Debug, Default)]
+pub struct Dictionary {
+    words: HashSet<String>,
+}
+
+impl Dictionary {
+    /// Parses a word list (see the module docs).
+    pub fn from_words(text: &str) -> Self {
+        let words = text
+            .lines()
+            .map(|line| line.split('/').next().unwrap_or("").trim())
+            .filter(|word| !word.is_empty() && !word.chars().all(|c| c.is_ascii_digit()))
+            .map(String::from)
+            .collect();
+        Self { words }
+    }
+
+    /// Reads the word list at `path`.
+    pub fn load(path: &Path) -> std::io::Result<Self> {
+        let bytes = std::fs::read(path)?;
+        Ok(Self::from_words(&String::from_utf8_lossy(&bytes)))
+    }
+
+    /// The first of `DEFAULT_DICTIONARIES` that exists.
+    pub fn default_path() -> Option<PathBuf> {
+        DEFAULT_DICTIONARIES
+            .iter()
+            .map(PathBuf::from)
+            .find(|path| path.is_file())
+    }
+
+    /// True if `word` is spelled correctly: listed as is, or lowercased
+    /// when capitalized (at the start of a sentence) or in all caps. A
+    /// possessive `'s` is dropped.
+    pub fn contains(&self, word: &str) -> bool {
+        if self.knows(word) {
+            return true;
+        }
+        word.strip_suffix("'s")
+            .or_else(|| word.strip_suffix("’s"))
+            .is_some_and(|stem| self.knows(stem))
+    }
+
+    fn knows(&self, word: &str) -> bool {
+        if self.words.contains(word) {
+            return true;
+        }
+        let capitalized = word.chars().skip(1).all(|c| !c.is_uppercase());
+        let all_caps = word.chars().all(|c| !c.is_lowercase());
+        (capitalized || all_caps) && self.words.contains(&word.to_lowercase())
+    }
+}
+
+/// Character ranges `(start, end)` of the misspelled words in `line`.
+pub fn misspellings(line: &str, dict: &Dictionary) -> Vec<(usize, usize)> {
+    let chars: Vec<char> = line.chars().collect();
+    let skipped = skipped_columns(&chars);
+    let mut ranges = Vec::new();
+    let mut col = 0;
+    while col < chars.len() {
+        if !chars[col].is_alphabetic() {
+            col += 1;
+            continue;
+        }
+        let start = col;
+        while col < chars.len()
+            && (chars[col].is_alphabetic()
+                || is_apostrophe(chars[col])
+                    && chars.get(col + 1).is_some_and(|c| c.is_alphabetic()))
+        {
+            col += 1;
+        }
+        let joined = |c: Option<&char>| c.is_some_and(|&c| c.is_ascii_digit() || c == '_');
+        if skipped[start]
+            || joined(start.checked_sub(1).and_then(|i| chars.get(i)))
+            || joined(chars.get(col))
+        {
+            continue;
+        }
+        let word: String = chars[start..col].iter().collect();
+        if word.chars().count() > 1 && !dict.contains(&word) {
+            ranges.push((start, col));
+        }
+    }
+    ranges
+}
+
+fn is_apostrophe(c: char) -> bool {
+    c == '\'' || c == '’'
+}
+
+/// Marks the columns of `chars` that aren't prose: code spans, URLs and
+/// `www.` addresses, link destinations `](...)` and autolinks `<...>`.
+fn skipped_columns(chars: &[char]) -> Vec<bool> {
+    let mut skipped = vec![false; chars.len()];
+    let mut col = 0;
+    while col < chars.len() {
+        let end = match chars[col] {
+            '`' => end_of(chars, col + 1, '`'),
+            '<' => end_of(chars, col + 1, '>'),
+            ']' if chars.get(col + 1) == Some(&'(') => end_of(chars, col + 2, ')'),
+            _ if col == 0 || chars[col - 1].is_whitespace() => url_end(chars, col),
+            _ => None,
+        };
+        match end {
+            Some(end) => {
+                skipped[col..end].fill(true);
+                col = end;
+            }
+            None => col += 1,
+        }
+    }
+    skipped
+}
+
+/// The index just past the first `close` at or after `from`.
+fn end_of(chars: &[char], from: usize, close: char) -> Option<usize> {
+    let offset = chars.get(from..)?.iter().position(|&c| c == close)?;
+    Some(from + offset + 1)
+}
+
+/// The end of the whitespace-delimited token at `col` if it is a URL.
+fn url_end(chars: &[char], col: usize) -> Option<usize> {
+    let end = chars[col..]
+        .iter()
+        .position(|c| c.is_whitespace())
+        .map_or(chars.len(), |offset| col + offset);
+    let token: String = chars[col..end].iter().collect();
+    let is_url = token.contains("://") || token.trim_start_matches('(').starts_with("www.");
+    is_url.then_some(end)
+}
+
+/// A dictionary with the misspellings of each line checked so far, keyed by
+/// the line's text, so only edited lines are checked again.
+#[derive(Debug, Default)]
+pub struct Checker {
+    dict: Dictionary,
+    cache: HashMap<String, Vec<(usize, usize)>>,
+}
+
+impl Checker {
+    pub fn new(dict: Dictionary) -> Self {
+        Self { dict, cache: HashMap::new() }
+    }
+
+    /// The misspelled word ranges of `line` (see `misspellings`).
+    pub fn check(&mut self, line: &str) -> &[(usize, usize)] {
+        if self.cache.len() >= MAX_CACHED_LINES && !self.cache.contains_key(line) {
+            self.cache.clear();
+        }
+        if !self.cache.contains_key(line) {
+            let ranges = misspellings(line, &self.dict);
+            self.cache.insert(line.to_string(), ranges);
+        }
+        &self.cache[line]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dict() -> Dictionary {
+        Dictionary::from_words("the\ncat\nsat\non\nor\nmat\nit\nisn't\nlink\nrun\ncode\n")
+    }
+
+    #[test]
+    fn test_misspellings_finds_unknown_words() {
+        assert_eq!(misspellings("the cat szat on the mta", &dict()), vec![(8, 12), (20, 23)]);
+    }
+
+    #[test]
+    fn test_capitalized_and_all_caps_words_match() {
+        assert!(misspellings("The CAT sat. It isn't", &dict()).is_empty());
+        assert_eq!(misspellings("tHe", &dict()), vec![(0, 3)]);
+    }
+
+    #[test]
+    fn test_possessives_and_quotes_match_the_word() {
+        assert!(misspellings("the cat's mat, 'the' cat", &dict()).is_empty());
+    }
+
+    #[test]
+    fn test_code_urls_and_links_are_skipped() {
+        let line = "run `fooo` on https://exmple.com or [link](docs/setupp.md) <http://x.yz>";
+        assert!(misspellings(line, &dict()).is_empty());
+        assert!(misspellings("the (www.exmple.com)", &dict()).is_empty());
+    }
+
+    #[test]
+    fn test_words_joined_to_digits_and_single_letters_are_skipped() {
+        assert!(misspellings("abc123 snake_casee x v2 the", &dict()).is_empty());
+    }
+
+    #[test]
+    fn test_hunspell_dic_format() {
+        let dict = Dictionary::from_words("3\nwalk/GSD\ntalk/S\nhello\n");
+        assert!(dict.contains("walk"));
+        assert!(dict.contains("talk"));
+        assert!(!dict.contains("3"));
+    }
+
+    #[test]
+    fn test_checker_caches_by_line() {
+        let mut checker = Checker::new(dict());
+        assert_eq!(checker.check("teh cat"), &[(0, 3)]);
+        assert_eq!(checker.check("teh cat"), &[(0, 3)]);
+        assert_eq!(checker.cache.len(), 1);
+        assert!(checker.check("the cat").is_empty());
+    }
+}
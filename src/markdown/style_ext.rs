@@ -100,10 +100,5 @@ fn find_double(chars: &[char], start: usize, c: char) -> Option<usize> {
 }
 
 fn find_single(chars: &[char], start: usize, c: char) -> Option<usize> {
-    for i in start..chars.len() {
-        if chars[i] == c {
-            return Some(i);
-        }
-    }
-    None
+    (start..chars.len()).find(|&i| chars[i] == c)
 }
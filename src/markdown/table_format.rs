@@ -1,12 +1,58 @@
+use serde::Deserialize;
+
+/// Where the wrapped lines of a list item continue.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ListIndent {
+    /// Under the item's text, past the marker.
+    #[default]
+    Text,
+    /// Under the marker.
+    Marker,
+}
+
+/// How wrapped list and blockquote lines are indented.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WrapIndent {
+    pub list: ListIndent,
+    /// Spaces after a blockquote's `>` on its wrapped lines.
+    pub quote_spaces: usize,
+}
+
+impl Default for WrapIndent {
+    fn default() -> Self {
+        Self { list: ListIndent::Text, quote_spaces: 1 }
+    }
+}
+
 /// Hard-wraps long lines to fit within `width` (measured in characters).
-/// Skips table lines (tables are handled by `format_tables`).
+/// Skips table lines (tables are handled by `format_tables`), link reference
+/// definitions and bare URLs (see `keeps_whole_line`).
 /// Code fences, headings, and all other content are wrapped so nothing is truncated.
-pub fn hard_wrap(content: &str, width: usize) -> String {
+pub fn hard_wrap(content: &str, width: usize, indent: WrapIndent) -> String {
     if width == 0 {
         return content.to_string();
     }
+    wrap_by_line(content, width, indent).concat().join("\n")
+}
+
+/// Row at which source line `line` (0-based) of `content` starts once
+/// `hard_wrap`ped to `width`. Lines past the end map past the last row.
+pub fn wrapped_row(content: &str, line: usize, width: usize, indent: WrapIndent) -> usize {
+    if width == 0 {
+        return line;
+    }
+    wrap_by_line(content, width, indent)
+        .iter()
+        .take(line)
+        .map(Vec::len)
+        .sum()
+}
+
+/// The `hard_wrap` pass, keeping the rows each source line produced apart.
+fn wrap_by_line(content: &str, width: usize, indent: WrapIndent) -> Vec<Vec<String>> {
     let lines: Vec<&str> = content.lines().collect();
-    let mut result: Vec<String> = Vec::new();
+    let mut wrapped: Vec<Vec<String>> = Vec::with_capacity(lines.len());
     let mut in_code_fence = false;
 
     for line in &lines {
@@ -15,39 +61,61 @@ pub fn hard_wrap(content: &str, width: usize) -> String {
         // Track code fence state
         if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
             in_code_fence = !in_code_fence;
-            result.push(line.to_string());
+            wrapped.push(vec![line.to_string()]);
             continue;
         }
 
         // Skip table lines outside code fences (tables are formatted separately)
         if !in_code_fence {
             if line.contains('|') && is_separator_row(line) {
-                result.push(line.to_string());
+                wrapped.push(vec![line.to_string()]);
                 continue;
             }
             if line.contains('|') && trimmed.starts_with('|') {
-                result.push(line.to_string());
+                wrapped.push(vec![line.to_string()]);
+                continue;
+            }
+            if keeps_whole_line(line) {
+                wrapped.push(vec![line.to_string()]);
                 continue;
             }
         }
 
         // Line fits — keep as-is (use char count, not byte count)
         if char_len(line) <= width {
-            result.push(line.to_string());
+            wrapped.push(vec![line.to_string()]);
             continue;
         }
 
         // Inside code fences: no continuation indent (plain wrap)
         // Outside: use markdown-aware continuation indent
-        let indent = if in_code_fence {
+        let continuation = if in_code_fence {
             String::new()
         } else {
-            continuation_indent(line)
+            continuation_indent(line, indent)
         };
-        wrap_line(line, width, &indent, &mut result);
+        let mut rows = Vec::new();
+        wrap_line(line, width, &continuation, &mut rows);
+        wrapped.push(rows);
     }
 
-    result.join("\n")
+    wrapped
+}
+
+/// True for lines that break if wrapped: link reference definitions
+/// (`[id]: https://...`) and a bare URL alone on its line.
+pub fn keeps_whole_line(line: &str) -> bool {
+    let trimmed = line.trim();
+    let is_reference_definition = trimmed
+        .strip_prefix('[')
+        .and_then(|rest| rest.split_once(']'))
+        .and_then(|(label, rest)| Some((label, rest.strip_prefix(':')?)))
+        .is_some_and(|(label, rest)| {
+            !label.is_empty() && rest.starts_with(char::is_whitespace)
+        });
+    let is_bare_url = !trimmed.contains(char::is_whitespace)
+        && (trimmed.contains("://") || trimmed.starts_with("www."));
+    is_reference_definition || is_bare_url
 }
 
 /// Returns the number of characters in a string (not bytes).
@@ -64,26 +132,31 @@ fn byte_offset_at_char(s: &str, n: usize) -> usize {
 }
 
 /// Figures out what indent continuation lines should use.
-/// e.g. "- item text" → "  " (align with content after bullet)
-///      "> quoted"    → "> "
+/// e.g. "- item text" → "  " (align with content after bullet; with
+///                            `ListIndent::Marker`, "" under the bullet)
+///      "> quoted"    → "> " (`quote_spaces` spaces after the `>`)
 ///      "  text"      → "  " (preserve leading whitespace)
-pub fn continuation_indent(line: &str) -> String {
+pub fn continuation_indent(line: &str, indent: WrapIndent) -> String {
     let leading_ws: String = line.chars().take_while(|c| c.is_whitespace()).collect();
     let rest = &line[leading_ws.len()..];
+    let list_indent = |marker_len: usize| match indent.list {
+        ListIndent::Text => " ".repeat(leading_ws.len() + marker_len),
+        ListIndent::Marker => leading_ws.clone(),
+    };
 
     // Ordered list: "1. ", "12. ", etc.
     if let Some(pos) = rest.find(". ") {
         if rest[..pos].chars().all(|c| c.is_ascii_digit()) && pos <= 4 {
-            return " ".repeat(leading_ws.len() + pos + 2);
+            return list_indent(pos + 2);
         }
     }
     // Unordered list: "- ", "* ", "+ "
     if rest.starts_with("- ") || rest.starts_with("* ") || rest.starts_with("+ ") {
-        return " ".repeat(leading_ws.len() + 2);
+        return list_indent(2);
     }
     // Blockquote: "> "
     if rest.starts_with("> ") {
-        return format!("{}> ", leading_ws);
+        return format!("{}>{}", leading_ws, " ".repeat(indent.quote_spaces));
     }
     // Plain text: preserve leading whitespace
     leading_ws
@@ -243,9 +316,8 @@ fn try_format_table(lines: &[&str], start: usize, terminal_width: usize) -> Opti
         } else {
             // Data row — pad or truncate each cell to fit column width
             let mut cells: Vec<String> = Vec::new();
-            for j in 0..num_cols {
+            for (j, &width) in col_widths.iter().enumerate() {
                 let content = row.get(j).map(|s| s.as_str()).unwrap_or("");
-                let width = col_widths[j];
                 let truncated: String = if content.len() > width {
                     content.chars().take(width).collect()
                 } else {
@@ -268,12 +340,12 @@ fn is_separator_row(line: &str) -> bool {
     }
     // Check that cells contain only dashes, colons, and whitespace
     let cells: Vec<&str> = trimmed.split('|').collect();
-    let inner = if cells.first().map_or(false, |c| c.trim().is_empty()) {
+    let inner = if cells.first().is_some_and(|c| c.trim().is_empty()) {
         &cells[1..]
     } else {
         &cells[..]
     };
-    let inner = if inner.last().map_or(false, |c| c.trim().is_empty()) {
+    let inner = if inner.last().is_some_and(|c| c.trim().is_empty()) {
         &inner[..inner.len() - 1]
     } else {
         inner
@@ -458,13 +530,13 @@ mod tests {
     #[test]
     fn test_hard_wrap_short_lines_unchanged() {
         let input = "short line\nanother short";
-        assert_eq!(hard_wrap(input, 40), input);
+        assert_eq!(hard_wrap(input, 40, WrapIndent::default()), input);
     }
 
     #[test]
     fn test_hard_wrap_long_line_wraps() {
         let input = "this is a somewhat long line that should be wrapped at a reasonable boundary";
-        let result = hard_wrap(input, 30);
+        let result = hard_wrap(input, 30, WrapIndent::default());
         for line in result.lines() {
             assert!(
                 line.len() <= 30,
@@ -481,7 +553,7 @@ mod tests {
     fn test_hard_wrap_wraps_code_fence_content() {
         let long_code = "x".repeat(80);
         let input = format!("```\n{}\n```", long_code);
-        let result = hard_wrap(&input, 40);
+        let result = hard_wrap(&input, 40, WrapIndent::default());
         // Code fence content should be wrapped so nothing is truncated
         for line in result.lines() {
             assert!(
@@ -498,15 +570,34 @@ mod tests {
     #[test]
     fn test_hard_wrap_preserves_table_lines() {
         let input = "| a very long cell value here | another long cell |\n|---|---|\n| data | more |";
-        let result = hard_wrap(input, 20);
+        let result = hard_wrap(input, 20, WrapIndent::default());
         // Table lines should pass through unchanged
         assert_eq!(result, input);
     }
 
+    #[test]
+    fn test_hard_wrap_keeps_reference_definitions_and_urls() {
+        let reference = format!("[docs]: https://example.com/{} \"The docs\"", "a/".repeat(30));
+        let url = format!("  https://example.com/{}", "b".repeat(60));
+        let input = format!("{}\n{}", reference, url);
+        assert_eq!(hard_wrap(&input, 40, WrapIndent::default()), input);
+    }
+
+    #[test]
+    fn test_keeps_whole_line() {
+        assert!(keeps_whole_line("[id]: https://example.com"));
+        assert!(keeps_whole_line("  [Some Label]:\thttps://example.com \"t\""));
+        assert!(keeps_whole_line("www.example.com/long/path"));
+        assert!(!keeps_whole_line("[]: https://example.com"));
+        assert!(!keeps_whole_line("[id]:https://example.com and more"));
+        assert!(!keeps_whole_line("see https://example.com for more"));
+        assert!(!keeps_whole_line("[link](https://example.com) text"));
+    }
+
     #[test]
     fn test_hard_wrap_list_continuation_indent() {
         let input = "- this is a very long list item that should wrap with proper indentation";
-        let result = hard_wrap(input, 40);
+        let result = hard_wrap(input, 40, WrapIndent::default());
         let lines: Vec<&str> = result.lines().collect();
         assert!(lines.len() > 1, "Should wrap into multiple lines");
         // Continuation lines should start with 2-space indent
@@ -518,10 +609,42 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_continuation_indent_list_alignment() {
+        let text = WrapIndent::default();
+        let marker = WrapIndent { list: ListIndent::Marker, ..text };
+        assert_eq!(continuation_indent("- item", text), "  ");
+        assert_eq!(continuation_indent("- item", marker), "");
+        assert_eq!(continuation_indent("  * nested", text), "    ");
+        assert_eq!(continuation_indent("  * nested", marker), "  ");
+        assert_eq!(continuation_indent("12. item", text), "    ");
+        assert_eq!(continuation_indent("12. item", marker), "");
+        assert_eq!(continuation_indent("   3. nested", text), "      ");
+        assert_eq!(continuation_indent("   3. nested", marker), "   ");
+        assert_eq!(continuation_indent("  plain", marker), "  ", "not a list");
+    }
+
+    #[test]
+    fn test_continuation_indent_quote_spaces() {
+        let indent = |quote_spaces| WrapIndent { quote_spaces, ..WrapIndent::default() };
+        assert_eq!(continuation_indent("> quoted", indent(1)), "> ");
+        assert_eq!(continuation_indent("  > quoted", indent(3)), "  >   ");
+        assert_eq!(continuation_indent("> quoted", indent(0)), ">");
+    }
+
+    #[test]
+    fn test_hard_wrap_list_under_marker() {
+        let indent = WrapIndent { list: ListIndent::Marker, ..WrapIndent::default() };
+        let result = hard_wrap("1. one two three four five six seven", 20, indent);
+        let lines: Vec<&str> = result.lines().collect();
+        assert!(lines.len() > 1);
+        assert!(lines[1..].iter().all(|l| !l.starts_with(' ')), "{:#?}", lines);
+    }
+
     #[test]
     fn test_hard_wrap_blockquote_continuation() {
         let input = "> this is a long blockquote line that should wrap while preserving the quote marker";
-        let result = hard_wrap(input, 40);
+        let result = hard_wrap(input, 40, WrapIndent::default());
         let lines: Vec<&str> = result.lines().collect();
         assert!(lines.len() > 1);
         for line in &lines[1..] {
@@ -531,4 +654,15 @@ mod tests {
             );
         }
     }
+
+    #[test]
+    fn test_wrapped_row_counts_rows_of_earlier_lines() {
+        let input = "short\nthis line is long enough to wrap twice at twenty\n\nlast";
+        let wrapped: Vec<String> = hard_wrap(input, 20, WrapIndent::default()).lines().map(String::from).collect();
+        let last = wrapped_row(input, 3, 20, WrapIndent::default());
+        assert_eq!(wrapped_row(input, 0, 20, WrapIndent::default()), 0);
+        assert_eq!(wrapped_row(input, 1, 20, WrapIndent::default()), 1);
+        assert_eq!(wrapped[last], "last");
+        assert_eq!(wrapped[last - 1], "", "blank source line keeps its own row");
+    }
 }
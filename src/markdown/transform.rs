@@ -0,0 +1,462 @@
+//! Text transforms applied to an editor selection: wrapping in and
+//! unwrapping from inline code spans and fenced code blocks, adding or
+//! removing a level of blockquote, escaping or reflowing pasted text, and
+//! checking off task list items.
+
+use std::sync::OnceLock;
+
+use regex::Regex;
+
+/// Length of the longest run of `ch` in `text`.
+fn longest_run(text: &str, ch: char) -> usize {
+    let mut longest = 0;
+    let mut current = 0;
+    for c in text.chars() {
+        if c == ch {
+            current += 1;
+            longest = longest.max(current);
+        } else {
+            current = 0;
+        }
+    }
+    longest
+}
+
+/// Backtick fence long enough that no backtick run inside `content` can
+/// close it early: one longer than the longest run, and at least `min`.
+pub fn code_fence_for(content: &str, min: usize) -> String {
+    "`".repeat((longest_run(content, '`') + 1).max(min))
+}
+
+/// Wraps `text` in an inline code span. Content starting or ending with a
+/// backtick gets a padding space so it doesn't merge with the fence.
+pub fn wrap_code_span(text: &str) -> String {
+    let fence = code_fence_for(text, 1);
+    if text.starts_with('`') || text.ends_with('`') {
+        format!("{} {} {}", fence, text, fence)
+    } else {
+        format!("{}{}{}", fence, text, fence)
+    }
+}
+
+/// Inverse of `wrap_code_span`: returns the span content if `text` is exactly
+/// one code span, stripping a single padding space from both sides.
+pub fn unwrap_code_span(text: &str) -> Option<String> {
+    let fence_len = text.chars().take_while(|&c| c == '`').count();
+    let closing_len = text.chars().rev().take_while(|&c| c == '`').count();
+    if fence_len == 0 || closing_len != fence_len || text.len() <= fence_len * 2 {
+        return None;
+    }
+    let inner = &text[fence_len..text.len() - fence_len];
+    // A run of the same length inside would have closed the span early
+    if inner.split(|c| c != '`').any(|run| run.len() == fence_len) {
+        return None;
+    }
+    let inner = if inner.len() > 2
+        && inner.starts_with(' ')
+        && inner.ends_with(' ')
+        && !inner.trim().is_empty()
+    {
+        &inner[1..inner.len() - 1]
+    } else {
+        inner
+    };
+    Some(inner.to_string())
+}
+
+/// Wraps `text` (one or more whole lines) in a fenced code block tagged with
+/// `lang`. The fence is lengthened past any backtick run in the content.
+pub fn wrap_code_block(text: &str, lang: &str) -> String {
+    let fence = code_fence_for(text, 3);
+    format!("{}{}\n{}\n{}", fence, lang.trim(), text, fence)
+}
+
+/// Inverse of `wrap_code_block`: returns the inner lines if `text` starts with
+/// an opening fence (``` or ~~~, optionally with a language) and ends with a
+/// matching closing fence.
+pub fn unwrap_code_block(text: &str) -> Option<String> {
+    let lines: Vec<&str> = text.lines().collect();
+    if lines.len() < 2 {
+        return None;
+    }
+    let first = lines[0].trim_start();
+    let fence_char = first.chars().next().filter(|&c| c == '`' || c == '~')?;
+    let open_len = first.chars().take_while(|&c| c == fence_char).count();
+    if open_len < 3 {
+        return None;
+    }
+
+    let last = lines[lines.len() - 1].trim();
+    let close_len = last.chars().take_while(|&c| c == fence_char).count();
+    if close_len < open_len || close_len != last.chars().count() {
+        return None;
+    }
+
+    Some(lines[1..lines.len() - 1].join("\n"))
+}
+
+/// Adds one level of blockquote to each line. Already-quoted lines nest
+/// (`> a` becomes `> > a`); blank lines get a bare `>` so the quote stays
+/// continuous.
+pub fn quote_lines(lines: &[String]) -> Vec<String> {
+    lines
+        .iter()
+        .map(|line| {
+            if line.trim().is_empty() {
+                ">".to_string()
+            } else {
+                format!("> {}", line)
+            }
+        })
+        .collect()
+}
+
+/// Removes one level of blockquote from each line (the first `>` and the
+/// single space after it). Unquoted lines are returned unchanged.
+pub fn unquote_lines(lines: &[String]) -> Vec<String> {
+    lines
+        .iter()
+        .map(|line| {
+            let trimmed = line.trim_start();
+            match trimmed.strip_prefix('>') {
+                Some(rest) => {
+                    let indent = &line[..line.len() - trimmed.len()];
+                    format!("{}{}", indent, rest.strip_prefix(' ').unwrap_or(rest))
+                }
+                None => line.clone(),
+            }
+        })
+        .collect()
+}
+
+/// Backslash-escapes characters that would otherwise be read as markdown
+/// syntax, so the text renders literally. Inline markers are always escaped;
+/// block markers (`#`, `>`, `-`, `+`, `1.`) only at the start of a line.
+pub fn escape_markdown(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for (i, line) in text.split('\n').enumerate() {
+        if i > 0 {
+            out.push('\n');
+        }
+        let trimmed = line.trim_start();
+        out.push_str(&line[..line.len() - trimmed.len()]);
+
+        // Block markers only matter as the first thing on the line
+        let mut rest = trimmed;
+        if let Some(first) = trimmed.chars().next().filter(|c| matches!(c, '#' | '>' | '-' | '+' | '=')) {
+            out.push('\\');
+            out.push(first);
+            rest = &trimmed[1..];
+        } else {
+            // Ordered list: "12." / "12)" -> "12\." / "12\)"
+            let digits = trimmed.chars().take_while(char::is_ascii_digit).count();
+            if digits > 0 && matches!(trimmed[digits..].chars().next(), Some('.' | ')')) {
+                out.push_str(&trimmed[..digits]);
+                out.push('\\');
+                out.push_str(&trimmed[digits..digits + 1]);
+                rest = &trimmed[digits + 1..];
+            }
+        }
+
+        for ch in rest.chars() {
+            if matches!(ch, '\\' | '`' | '*' | '_' | '[' | ']' | '<' | '|' | '~') {
+                out.push('\\');
+            }
+            out.push(ch);
+        }
+    }
+    out
+}
+
+/// Bullets that text copied from PDFs and word processors uses for list
+/// items, rewritten as `-`.
+const PASTED_BULLETS: &[char] = &['•', '◦', '▪', '‣', '●', '○', '■'];
+
+/// Joins the hard line breaks inside the paragraphs of pasted prose (as
+/// copied from a PDF) with spaces. Blank lines, list items, headings,
+/// quotes, table rows and code fences keep their lines; a list item's
+/// broken lines join onto it, and PDF bullets become `-`.
+pub fn reflow_paste(text: &str) -> String {
+    let mut out: Vec<String> = Vec::new();
+    let mut in_fence = false;
+    // Whether the next line may join onto the last one
+    let mut joinable = false;
+    for line in text.lines() {
+        let trimmed = line.trim();
+        let is_fence = trimmed.starts_with("```") || trimmed.starts_with("~~~");
+        if in_fence || is_fence {
+            in_fence ^= is_fence;
+            out.push(line.to_string());
+            joinable = false;
+            continue;
+        }
+        if trimmed.is_empty() {
+            out.push(String::new());
+            joinable = false;
+            continue;
+        }
+        let indent = &line[..line.len() - line.trim_start().len()];
+        let (line, is_item) = match trimmed.strip_prefix(PASTED_BULLETS) {
+            Some(rest) => (format!("{}- {}", indent, rest.trim_start()), true),
+            None => (line.trim_end().to_string(), starts_list_item(trimmed)),
+        };
+        let stands_alone = matches!(trimmed.chars().next(), Some('#' | '>' | '|'));
+        match out.last_mut() {
+            Some(last) if joinable && !is_item && !stands_alone => {
+                last.push(' ');
+                last.push_str(trimmed);
+            }
+            _ => out.push(line),
+        }
+        joinable = !stands_alone;
+    }
+    out.join("\n")
+}
+
+/// True if `trimmed` starts with a list marker: `-`, `*`, `+`, `1.` or `1)`.
+fn starts_list_item(trimmed: &str) -> bool {
+    let digits = trimmed.chars().take_while(char::is_ascii_digit).count();
+    let rest = if digits > 0 {
+        match trimmed[digits..].strip_prefix(['.', ')']) {
+            Some(rest) => rest,
+            None => return false,
+        }
+    } else {
+        match trimmed.strip_prefix(['-', '*', '+']) {
+            Some(rest) => rest,
+            None => return false,
+        }
+    };
+    rest.is_empty() || rest.starts_with(' ')
+}
+
+/// A task list item: indentation and quote levels, a bullet or number, then
+/// the `[ ]` / `[x]` checkbox followed by a space or the end of the line.
+/// Group 1 is everything before the checkbox state.
+fn task_item_regex() -> &'static Regex {
+    static TASK_ITEM: OnceLock<Regex> = OnceLock::new();
+    TASK_ITEM.get_or_init(|| {
+        Regex::new(r"^(\s*(?:>\s*)*(?:[-*+]|\d{1,9}[.)])\s+\[)[ xX]\](?:\s|$)").unwrap()
+    })
+}
+
+/// Flips a task list item between `[ ]` and `[x]`, leaving the indentation,
+/// marker and text alone. None if `line` is not a task list item.
+pub fn toggle_task(line: &str) -> Option<String> {
+    let caps = task_item_regex().captures(line)?;
+    let prefix = caps.get(1)?.end();
+    let state = if &line[prefix..prefix + 1] == " " { "x" } else { " " };
+    Some(format!("{}{}{}", &line[..prefix], state, &line[prefix + 1..]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // ─── Fence length ───────────────────────────────────────────────
+
+    #[test]
+    fn test_fence_without_backticks() {
+        assert_eq!(code_fence_for("plain", 1), "`");
+        assert_eq!(code_fence_for("plain", 3), "```");
+    }
+
+    #[test]
+    fn test_fence_longer_than_inner_run() {
+        assert_eq!(code_fence_for("a `b` c", 1), "``");
+        assert_eq!(code_fence_for("use ``x``", 1), "```");
+        assert_eq!(code_fence_for("```rust\nfn main() {}\n```", 3), "````");
+    }
+
+    // ─── Code spans ─────────────────────────────────────────────────
+
+    #[test]
+    fn test_wrap_plain_span() {
+        assert_eq!(wrap_code_span("foo()"), "`foo()`");
+    }
+
+    #[test]
+    fn test_wrap_span_with_nested_backticks() {
+        assert_eq!(wrap_code_span("a `b` c"), "``a `b` c``");
+        assert_eq!(wrap_code_span("`tick`"), "`` `tick` ``");
+    }
+
+    #[test]
+    fn test_unwrap_span_round_trips() {
+        for text in ["foo()", "a `b` c", "`tick`", "``double``"] {
+            assert_eq!(unwrap_code_span(&wrap_code_span(text)).as_deref(), Some(text));
+        }
+    }
+
+    #[test]
+    fn test_unwrap_span_rejects_non_spans() {
+        assert_eq!(unwrap_code_span("plain"), None);
+        assert_eq!(unwrap_code_span("`a` and `b`"), None);
+        assert_eq!(unwrap_code_span("``a`"), None);
+    }
+
+    // ─── Code blocks ────────────────────────────────────────────────
+
+    #[test]
+    fn test_wrap_block_with_language() {
+        assert_eq!(
+            wrap_code_block("let x = 1;\nlet y = 2;", "rust"),
+            "```rust\nlet x = 1;\nlet y = 2;\n```"
+        );
+    }
+
+    #[test]
+    fn test_wrap_block_containing_fence_uses_longer_fence() {
+        let inner = "```python\nprint(1)\n```";
+        let wrapped = wrap_code_block(inner, "markdown");
+        assert_eq!(wrapped, format!("````markdown\n{}\n````", inner));
+        assert_eq!(unwrap_code_block(&wrapped).as_deref(), Some(inner));
+    }
+
+    #[test]
+    fn test_unwrap_block() {
+        assert_eq!(
+            unwrap_code_block("```\na\nb\n```").as_deref(),
+            Some("a\nb")
+        );
+        assert_eq!(unwrap_code_block("~~~sh\nls\n~~~").as_deref(), Some("ls"));
+    }
+
+    #[test]
+    fn test_unwrap_block_rejects_mismatched_fences() {
+        assert_eq!(unwrap_code_block("````\na\n```"), None);
+        assert_eq!(unwrap_code_block("```\na\n~~~"), None);
+        assert_eq!(unwrap_code_block("just\ntext"), None);
+    }
+
+    // ─── Blockquotes ────────────────────────────────────────────────
+
+    fn owned(lines: &[&str]) -> Vec<String> {
+        lines.iter().map(|l| l.to_string()).collect()
+    }
+
+    #[test]
+    fn test_quote_multi_line_selection() {
+        assert_eq!(
+            quote_lines(&owned(&["first", "", "second"])),
+            owned(&["> first", ">", "> second"])
+        );
+    }
+
+    #[test]
+    fn test_quote_nests_existing_quotes() {
+        assert_eq!(
+            quote_lines(&owned(&["> already", "plain"])),
+            owned(&["> > already", "> plain"])
+        );
+    }
+
+    #[test]
+    fn test_unquote_removes_one_level() {
+        assert_eq!(
+            unquote_lines(&owned(&["> > nested", ">> tight", "> single", ">", "plain"])),
+            owned(&["> nested", "> tight", "single", "", "plain"])
+        );
+    }
+
+    #[test]
+    fn test_quote_then_unquote_round_trips() {
+        let lines = owned(&["a", "> b", "  c"]);
+        assert_eq!(unquote_lines(&quote_lines(&lines)), lines);
+    }
+
+    // ─── Markdown escaping ──────────────────────────────────────────
+
+    #[test]
+    fn test_escape_inline_characters() {
+        for (input, expected) in [
+            ("a*b", "a\\*b"),
+            ("snake_case", "snake\\_case"),
+            ("[link]", "\\[link\\]"),
+            ("`code`", "\\`code\\`"),
+            ("back\\slash", "back\\\\slash"),
+            ("<div>", "\\<div>"),
+            ("a | b", "a \\| b"),
+            ("~~strike~~", "\\~\\~strike\\~\\~"),
+        ] {
+            assert_eq!(escape_markdown(input), expected, "escaping {:?}", input);
+        }
+    }
+
+    #[test]
+    fn test_escape_line_start_markers() {
+        for (input, expected) in [
+            ("# Title", "\\# Title"),
+            ("> quote", "\\> quote"),
+            ("- item", "\\- item"),
+            ("+ item", "\\+ item"),
+            ("===", "\\==="),
+            ("1. first", "1\\. first"),
+            ("12) twelfth", "12\\) twelfth"),
+            ("  - indented", "  \\- indented"),
+        ] {
+            assert_eq!(escape_markdown(input), expected, "escaping {:?}", input);
+        }
+    }
+
+    #[test]
+    fn test_escape_leaves_mid_line_block_markers() {
+        assert_eq!(escape_markdown("C# is well-known + 1.5 > 1"), "C# is well-known + 1.5 > 1");
+    }
+
+    #[test]
+    fn test_reflow_paste_joins_broken_lines() {
+        let pasted = "The quick brown\nfox jumps over\nthe dog.\n\nA second\r\nparagraph.";
+        assert_eq!(
+            reflow_paste(pasted),
+            "The quick brown fox jumps over the dog.\n\nA second paragraph."
+        );
+    }
+
+    #[test]
+    fn test_reflow_paste_keeps_list_items_and_fixes_bullets() {
+        let pasted = "Steps:\n• first item that\nwraps\n• second\n1. one\n2) two\n  - nested";
+        assert_eq!(
+            reflow_paste(pasted),
+            "Steps:\n- first item that wraps\n- second\n1. one\n2) two\n  - nested"
+        );
+    }
+
+    #[test]
+    fn test_reflow_paste_keeps_code_fences_and_block_lines() {
+        let pasted = "# Title\ntext\n```\nlet a = 1;\nlet b = 2;\n```\n| a |\n| b |\n> quote";
+        assert_eq!(reflow_paste(pasted), pasted);
+        assert_eq!(reflow_paste("-5 degrees\nis cold"), "-5 degrees is cold");
+    }
+
+    #[test]
+    fn test_escape_multi_line() {
+        assert_eq!(escape_markdown("# a\n* b\nplain"), "\\# a\n\\* b\nplain");
+    }
+
+    // ─── Task lists ─────────────────────────────────────────────────
+
+    #[test]
+    fn test_toggle_task_checks_and_unchecks() {
+        assert_eq!(toggle_task("- [ ] buy milk").as_deref(), Some("- [x] buy milk"));
+        assert_eq!(toggle_task("- [x] buy milk").as_deref(), Some("- [ ] buy milk"));
+        assert_eq!(toggle_task("* [X] done").as_deref(), Some("* [ ] done"));
+        assert_eq!(toggle_task("- [ ]").as_deref(), Some("- [x]"));
+    }
+
+    #[test]
+    fn test_toggle_task_keeps_indent_and_marker() {
+        assert_eq!(toggle_task("    + [ ] nested").as_deref(), Some("    + [x] nested"));
+        assert_eq!(toggle_task("\t12. [x] step").as_deref(), Some("\t12. [ ] step"));
+        assert_eq!(toggle_task("> - [ ] quoted").as_deref(), Some("> - [x] quoted"));
+    }
+
+    #[test]
+    fn test_toggle_task_ignores_non_tasks() {
+        assert_eq!(toggle_task("- plain item"), None);
+        assert_eq!(toggle_task("[ ] no bullet"), None);
+        assert_eq!(toggle_task("- [link](url)"), None);
+        assert_eq!(toggle_task("- [ ]x"), None);
+        assert_eq!(toggle_task("text with - [ ] inside"), None);
+    }
+}
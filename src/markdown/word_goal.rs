@@ -0,0 +1,91 @@
+//! Word-count goals for long-form writing: the goal a document sets in its
+//! front matter (`goal: 1000`) and the prose word count measured against it.
+//!
+//! Only prose counts toward a goal: the front matter and fenced code blocks
+//! are left out.
+
+use crate::markdown::code_highlight;
+
+/// Index of the line closing the YAML front matter (`---` at the top of the
+/// file, closed by `---` or `...`), if there is one.
+fn front_matter_end(lines: &[String]) -> Option<usize> {
+    if lines.first()?.trim_end() != "---" {
+        return None;
+    }
+    lines
+        .iter()
+        .skip(1)
+        .position(|line| matches!(line.trim_end(), "---" | "..."))
+        .map(|i| i + 1)
+}
+
+/// The `goal:` set in the front matter, if any.
+pub fn front_matter_goal(lines: &[String]) -> Option<usize> {
+    let end = front_matter_end(lines)?;
+    lines[1..end].iter().find_map(|line| {
+        let value = line.strip_prefix("goal:")?.trim();
+        value.parse().ok().filter(|&goal| goal > 0)
+    })
+}
+
+/// Words in `lines` outside the front matter and fenced code blocks.
+pub fn prose_word_count(lines: &[String]) -> usize {
+    let body_start = front_matter_end(lines).map_or(0, |end| end + 1);
+    let regions = code_highlight::find_code_fence_regions(lines);
+    lines
+        .iter()
+        .enumerate()
+        .skip(body_start)
+        .filter(|(i, _)| !regions.iter().any(|r| (r.start_line..=r.end_line).contains(i)))
+        .map(|(_, line)| line.split_whitespace().count())
+        .sum()
+}
+
+/// How many of `width` cells of a progress bar `words` of `goal` fill; a met
+/// goal fills them all.
+pub fn progress_cells(words: usize, goal: usize, width: usize) -> usize {
+    if goal == 0 {
+        return width;
+    }
+    (words * width / goal).min(width)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lines(text: &str) -> Vec<String> {
+        text.lines().map(String::from).collect()
+    }
+
+    #[test]
+    fn test_front_matter_goal() {
+        let doc = lines("---\ntitle: Draft\ngoal: 1000\n---\n# Chapter one");
+        assert_eq!(front_matter_goal(&doc), Some(1000));
+        assert_eq!(front_matter_goal(&lines("---\ngoal: 250\n...\ntext")), Some(250));
+    }
+
+    #[test]
+    fn test_front_matter_goal_needs_front_matter() {
+        assert_eq!(front_matter_goal(&lines("goal: 1000\ntext")), None);
+        assert_eq!(front_matter_goal(&lines("text\n---\ngoal: 1000\n---")), None);
+        assert_eq!(front_matter_goal(&lines("---\ngoal: 1000\nno closing line")), None);
+        assert_eq!(front_matter_goal(&lines("---\ngoal: lots\n---")), None);
+        assert_eq!(front_matter_goal(&lines("---\ngoal: 0\n---")), None);
+    }
+
+    #[test]
+    fn test_prose_word_count_skips_front_matter_and_code() {
+        let doc = lines("---\ngoal: 10\n---\nOne two three.\n```sh\necho not counted\n```\nfour five");
+        assert_eq!(prose_word_count(&doc), 5);
+    }
+
+    #[test]
+    fn test_progress_cells() {
+        assert_eq!(progress_cells(0, 1000, 10), 0);
+        assert_eq!(progress_cells(499, 1000, 10), 4);
+        assert_eq!(progress_cells(500, 1000, 10), 5);
+        assert_eq!(progress_cells(1000, 1000, 10), 10);
+        assert_eq!(progress_cells(2500, 1000, 10), 10, "capped when exceeded");
+    }
+}
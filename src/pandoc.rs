@@ -79,6 +79,52 @@ pub fn md_to_docx(
     }
 }
 
+/// Converts a markdown file to a single self-contained HTML page via pandoc:
+/// images become data URIs and `css` is embedded, so the page works on its
+/// own. Relative image paths (such as pasted `.marko/images/...`) resolve
+/// from the markdown file's directory.
+pub fn md_to_self_contained_html(
+    md_path: &Path,
+    html_path: &Path,
+    title: &str,
+    css: &str,
+) -> Result<(), PandocError> {
+    // pandoc embeds a stylesheet from a file, so the CSS goes through one
+    let css_path = std::env::temp_dir().join(format!("marko-export-{}.css", std::process::id()));
+    std::fs::write(&css_path, css)?;
+    let resource_dir = md_path.parent().filter(|dir| !dir.as_os_str().is_empty()).unwrap_or(Path::new("."));
+
+    let output = Command::new("pandoc")
+        .arg(md_path)
+        .arg("-o")
+        .arg(html_path)
+        .arg("--from=markdown")
+        .arg("--to=html5")
+        .arg("--standalone")
+        .arg("--embed-resources")
+        .arg(format!("--resource-path={}", resource_dir.display()))
+        .arg(format!("--css={}", css_path.display()))
+        .arg(format!("--metadata=pagetitle:{}", title))
+        .output();
+    let _ = std::fs::remove_file(&css_path);
+    let output = output.map_err(|e| {
+        if e.kind() == io::ErrorKind::NotFound {
+            PandocError::NotInstalled
+        } else {
+            PandocError::Io(e)
+        }
+    })?;
+
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(PandocError::ConversionFailed {
+            stderr: String::from_utf8_lossy(&output.stderr).to_string(),
+            exit_code: output.status.code().unwrap_or(-1),
+        })
+    }
+}
+
 /// Converts a .docx file to GitHub-Flavored Markdown via pandoc.
 ///
 /// Returns the markdown content as a string.
@@ -198,4 +244,24 @@ mod tests {
         let result = md_to_docx(&md_path, &docx_path, None);
         assert!(result.is_err(), "Should fail on nonexistent input");
     }
+
+    #[test]
+    fn self_contained_html_embeds_images() {
+        if !is_available() {
+            return;
+        }
+        let dir = TempDir::new().unwrap();
+        let images = dir.path().join(".marko").join("images");
+        fs::create_dir_all(&images).unwrap();
+        image::RgbImage::new(2, 2).save(images.join("shot.png")).unwrap();
+        let md_path = dir.path().join("notes.md");
+        let html_path = dir.path().join("notes.html");
+        fs::write(&md_path, "# Notes\n\n![shot](.marko/images/shot.png)\n").unwrap();
+
+        let result = md_to_self_contained_html(&md_path, &html_path, "notes", "body { color: #222; }");
+        assert!(result.is_ok(), "export failed: {:?}", result.err());
+        let html = fs::read_to_string(&html_path).unwrap();
+        assert!(html.contains("data:image/png;base64,"), "image inlined");
+        assert!(html.contains("color: #222"), "CSS embedded");
+    }
 }
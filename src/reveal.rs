@@ -0,0 +1,71 @@
+//! Opening a directory in the system file manager (Finder, the desktop's
+//! default via `xdg-open`, or Explorer), following the same per-platform
+//! dispatch as `preview::open_url`.
+
+use std::io;
+use std::path::Path;
+use std::process::Command;
+
+/// Builds the command that opens `dir` in the file manager on `os` (a value
+/// of `std::env::consts::OS`), or None on platforms without one we know.
+pub fn file_manager_command(os: &str, dir: &Path) -> Option<Command> {
+    let program = match os {
+        "macos" => "open",
+        "windows" => "explorer",
+        "linux" | "freebsd" | "openbsd" | "netbsd" | "dragonfly" => "xdg-open",
+        _ => return None,
+    };
+    let mut cmd = Command::new(program);
+    cmd.arg(dir);
+    Some(cmd)
+}
+
+/// Opens `dir` in the system file manager without waiting for it.
+pub fn open_in_file_manager(dir: &Path) -> io::Result<()> {
+    let mut cmd = file_manager_command(std::env::consts::OS, dir).ok_or_else(|| {
+        io::Error::new(io::ErrorKind::Unsupported, "no file manager on this platform")
+    })?;
+    cmd.spawn().map(|_| ())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::ffi::OsStr;
+
+    fn program_and_args(os: &str) -> Option<(String, Vec<String>)> {
+        let cmd = file_manager_command(os, Path::new("/notes/assets"))?;
+        let args = cmd.get_args().map(|a| a.to_string_lossy().into_owned()).collect();
+        Some((cmd.get_program().to_string_lossy().into_owned(), args))
+    }
+
+    #[test]
+    fn test_macos_uses_open() {
+        assert_eq!(
+            program_and_args("macos"),
+            Some(("open".to_string(), vec!["/notes/assets".to_string()]))
+        );
+    }
+
+    #[test]
+    fn test_linux_and_bsd_use_xdg_open() {
+        for os in ["linux", "freebsd"] {
+            assert_eq!(
+                program_and_args(os),
+                Some(("xdg-open".to_string(), vec!["/notes/assets".to_string()]))
+            );
+        }
+    }
+
+    #[test]
+    fn test_windows_uses_explorer() {
+        let cmd = file_manager_command("windows", Path::new(r"C:\notes")).unwrap();
+        assert_eq!(cmd.get_program(), OsStr::new("explorer"));
+        assert_eq!(cmd.get_args().collect::<Vec<_>>(), [OsStr::new(r"C:\notes")]);
+    }
+
+    #[test]
+    fn test_unknown_platform_has_no_command() {
+        assert!(file_manager_command("ios", Path::new("/tmp")).is_none());
+    }
+}
@@ -0,0 +1,168 @@
+//! Running text through external shell commands (`sh -c`, or `cmd /C` on
+//! Windows), feeding it on stdin and capturing stdout.
+//!
+//! Output is capped and commands are killed after a timeout, so a runaway
+//! command can't flood the buffer or hang the editor.
+
+use std::fmt;
+use std::io::{self, Read, Write};
+use std::process::{Command, Stdio};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Most stdout bytes a command may produce before it is rejected.
+pub const MAX_OUTPUT: usize = 1024 * 1024;
+
+/// How long a command may run before it is killed.
+pub const TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Errors that can occur when running a shell command.
+#[derive(Debug)]
+pub enum ShellError {
+    /// The command ran but exited with a non-zero status.
+    Failed { stderr: String, exit_code: i32 },
+    /// The command wrote more than `MAX_OUTPUT` bytes to stdout.
+    OutputTooLarge,
+    /// The command was still running after `TIMEOUT` and was killed.
+    TimedOut,
+    /// An I/O error occurred while spawning or talking to the process.
+    Io(io::Error),
+}
+
+impl fmt::Display for ShellError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ShellError::Failed { stderr, exit_code } => {
+                let stderr = stderr.trim();
+                if stderr.is_empty() {
+                    write!(f, "command exited with code {}", exit_code)
+                } else {
+                    write!(f, "command exited with code {}: {}", exit_code, stderr)
+                }
+            }
+            ShellError::OutputTooLarge => {
+                write!(f, "command output exceeds {} KiB", MAX_OUTPUT / 1024)
+            }
+            ShellError::TimedOut => write!(f, "command timed out after {}s", TIMEOUT.as_secs()),
+            ShellError::Io(e) => write!(f, "I/O error: {}", e),
+        }
+    }
+}
+
+impl From<io::Error> for ShellError {
+    fn from(e: io::Error) -> Self {
+        ShellError::Io(e)
+    }
+}
+
+/// The platform shell invocation for `command`.
+fn shell_command(command: &str) -> Command {
+    #[cfg(windows)]
+    let mut cmd = Command::new("cmd");
+    #[cfg(windows)]
+    cmd.args(["/C", command]);
+    #[cfg(not(windows))]
+    let mut cmd = Command::new("sh");
+    #[cfg(not(windows))]
+    cmd.args(["-c", command]);
+    cmd
+}
+
+/// Runs `command` in the shell with `input` on stdin and returns its stdout.
+pub fn run(command: &str, input: &str) -> Result<String, ShellError> {
+    let mut child = shell_command(command)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()?;
+
+    // Feed stdin and drain the outputs on their own threads so a command
+    // that fills one pipe while we write the other can't deadlock.
+    let mut stdin = child.stdin.take().expect("stdin is piped");
+    let input = input.to_string();
+    let writer = thread::spawn(move || {
+        // A command may exit without reading all its input (e.g. `head`)
+        let _ = stdin.write_all(input.as_bytes());
+    });
+    let stdout = child.stdout.take().expect("stdout is piped");
+    let reader = thread::spawn(move || {
+        let mut out = Vec::new();
+        // One byte past the cap is enough to know it was exceeded
+        stdout.take(MAX_OUTPUT as u64 + 1).read_to_end(&mut out).map(|_| out)
+    });
+    let mut stderr = child.stderr.take().expect("stderr is piped");
+    let err_reader = thread::spawn(move || {
+        let mut err = String::new();
+        let _ = stderr.read_to_string(&mut err);
+        err
+    });
+
+    let deadline = Instant::now() + TIMEOUT;
+    let mut reader = Some(reader);
+    let mut out = None;
+    let status = loop {
+        if let Some(status) = child.try_wait()? {
+            break status;
+        }
+        // The reader stops at the cap, so a command still writing would
+        // block forever on the full pipe: stop it as soon as that happens
+        if reader.as_ref().is_some_and(|r| r.is_finished()) {
+            let bytes = reader.take().unwrap().join().unwrap_or_else(|_| Ok(Vec::new()))?;
+            if bytes.len() > MAX_OUTPUT {
+                let _ = child.kill();
+                let _ = child.wait();
+                return Err(ShellError::OutputTooLarge);
+            }
+            out = Some(bytes);
+        }
+        if Instant::now() >= deadline {
+            let _ = child.kill();
+            let _ = child.wait();
+            return Err(ShellError::TimedOut);
+        }
+        thread::sleep(Duration::from_millis(10));
+    };
+    let _ = writer.join();
+    let out = match (out, reader) {
+        (Some(out), _) => out,
+        (None, Some(reader)) => reader.join().unwrap_or_else(|_| Ok(Vec::new()))?,
+        (None, None) => Vec::new(),
+    };
+    let stderr = err_reader.join().unwrap_or_default();
+
+    // Checked first: a command cut off at the cap may die of a broken pipe
+    if out.len() > MAX_OUTPUT {
+        return Err(ShellError::OutputTooLarge);
+    }
+    if !status.success() {
+        return Err(ShellError::Failed {
+            stderr,
+            exit_code: status.code().unwrap_or(-1),
+        });
+    }
+    Ok(String::from_utf8_lossy(&out).into_owned())
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_run_pipes_input_through_command() {
+        assert_eq!(run("tr a-z A-Z", "hello\n").unwrap(), "HELLO\n");
+        assert_eq!(run("sort", "b\na\n").unwrap(), "a\nb\n");
+    }
+
+    #[test]
+    fn test_run_reports_failure_with_stderr() {
+        let err = run("echo oops >&2; exit 3", "").unwrap_err();
+        assert!(matches!(err, ShellError::Failed { exit_code: 3, .. }));
+        assert_eq!(err.to_string(), "command exited with code 3: oops");
+    }
+
+    #[test]
+    fn test_run_rejects_huge_output() {
+        let err = run("yes", "").unwrap_err();
+        assert!(matches!(err, ShellError::OutputTooLarge), "{}", err);
+    }
+}
@@ -1,97 +1,465 @@
+//! UI colors. A `Theme` holds every color the editor and preview draw with;
+//! `DARK` (the default), `LIGHT` and `HIGH_CONTRAST` are built in, chosen
+//! with the `theme` config key and cycled at runtime with F8. Drawing code
+//! reads the colors off `active()`.
+//!
+//! A custom theme can be defined in `theme.toml` next to `config.toml`:
+//! each key is a color's field name and each value a color name (`"red"`,
+//! `"darkgray"`, `"reset"`) or hex (`"#82aaff"`). Missing colors come from
+//! the preset named by `base` (default `"dark"`). When the file exists, its
+//! theme is used unless the config picks a preset. `marko theme --dump`
+//! prints a complete one to start from.
+//!
+//! Blockquotes draw a `│` bar per level by default. `quote_bg` tints them
+//! instead or as well (darker as they nest), and `quote_bar = false` drops
+//! the bar; the presets keep the bar only, as some terminals draw
+//! backgrounds poorly.
+
+use std::cell::Cell;
+use std::path::PathBuf;
+use std::sync::OnceLock;
+
 use ratatui::style::{Color, Modifier, Style};
 
-// Base colors — Color::Reset inherits terminal defaults
-pub const BG: Color = Color::Reset;
-pub const FG: Color = Color::Reset;
-pub const BORDER: Color = Color::DarkGray;
-
-// UI elements
-pub const BAR_BG: Color = Color::Reset;
-pub const BAR_FG: Color = Color::Reset;
-pub const LINE_NUMBER: Color = Color::DarkGray;
-pub const SELECTION: Color = Color::Blue;
-
-// Markdown syntax
-pub const HEADING: Color = Color::Rgb(130, 170, 255);
-pub const BOLD: Color = Color::Yellow;
-pub const ITALIC: Color = Color::Cyan;
-pub const LINK: Color = Color::Cyan;
-pub const CODE: Color = Color::Red;
-pub const CODE_BG: Color = Color::Rgb(40, 42, 54);
-pub const QUOTE: Color = Color::Green;
-pub const QUOTE_BORDER: Color = Color::Rgb(106, 190, 120);
-
-// Git diff
-pub const GIT_ADDED: Color = Color::Green;
-pub const GIT_REMOVED: Color = Color::Red;
-pub const GIT_MODIFIED: Color = Color::Yellow;
-
-// Status indicators
-pub const SUCCESS: Color = Color::Green;
-pub const WARNING: Color = Color::Yellow;
-pub const ERROR: Color = Color::Red;
-
-// White for text on colored backgrounds
-pub const WHITE: Color = Color::White;
-
-// Tilde color for empty lines beyond file content
-pub const TILDE: Color = Color::DarkGray;
-
-// Tab colors
-pub const ACTIVE_TAB: Color = Color::Blue;
-pub const INACTIVE_TAB: Color = Color::Gray;
+/// A named set of UI colors.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Theme {
+    pub name: &'static str,
+
+    // Base colors — Color::Reset inherits terminal defaults
+    pub bg: Color,
+    pub fg: Color,
+    pub border: Color,
+
+    // UI elements
+    pub bar_bg: Color,
+    pub bar_fg: Color,
+    pub line_number: Color,
+    pub selection: Color,
+    pub search_match: Color,
+
+    // Markdown syntax
+    pub heading: Color,
+    /// Editor heading lines by level (H1..H6), brightest first.
+    pub heading_levels: [Color; 6],
+    pub bold: Color,
+    pub italic: Color,
+    pub link: Color,
+    pub code: Color,
+    pub code_bg: Color,
+    pub code_highlight_bg: Color,
+    pub quote: Color,
+    pub quote_border: Color,
+    /// Blockquote background, a shade darker per nesting level past the
+    /// first; Color::Reset leaves blockquotes untinted.
+    pub quote_bg: Color,
+    /// Draw the `│` bar in front of blockquote lines. Turn it off when a
+    /// `quote_bg` tint shows the nesting on its own.
+    pub quote_bar: bool,
+    pub marker: Color,
+    pub misspelled: Color,
+    /// Syntect theme for code blocks, unless `code_theme` picks another.
+    pub code_theme: &'static str,
+
+    // Git diff
+    pub git_added: Color,
+    pub git_removed: Color,
+    pub git_modified: Color,
+
+    // Merge conflict regions (backgrounds)
+    pub conflict_marker_bg: Color,
+    pub conflict_ours_bg: Color,
+    pub conflict_base_bg: Color,
+    pub conflict_theirs_bg: Color,
+
+    // Status indicators
+    pub success: Color,
+    pub warning: Color,
+    pub error: Color,
+
+    /// Text on colored backgrounds.
+    pub white: Color,
+    /// Tilde color for empty lines beyond file content.
+    pub tilde: Color,
+
+    // Tab colors
+    pub active_tab: Color,
+    pub inactive_tab: Color,
+}
+
+/// For dark terminals, inheriting their background.
+pub const DARK: Theme = Theme {
+    name: "dark",
+    bg: Color::Reset,
+    fg: Color::Reset,
+    border: Color::DarkGray,
+    bar_bg: Color::Reset,
+    bar_fg: Color::Reset,
+    line_number: Color::DarkGray,
+    selection: Color::Blue,
+    search_match: Color::Yellow,
+    heading: Color::Rgb(130, 170, 255),
+    heading_levels: [
+        Color::Rgb(130, 170, 255),
+        Color::Rgb(110, 200, 220),
+        Color::Rgb(140, 200, 140),
+        Color::Rgb(220, 200, 120),
+        Color::Rgb(200, 160, 210),
+        Color::Rgb(160, 160, 170),
+    ],
+    bold: Color::Yellow,
+    italic: Color::Cyan,
+    link: Color::Cyan,
+    code: Color::Red,
+    code_bg: Color::Rgb(40, 42, 54),
+    code_highlight_bg: Color::Rgb(62, 66, 88),
+    quote: Color::Green,
+    quote_border: Color::Rgb(106, 190, 120),
+    quote_bg: Color::Reset,
+    quote_bar: true,
+    marker: Color::DarkGray,
+    misspelled: Color::Red,
+    code_theme: "base16-ocean.dark",
+    git_added: Color::Green,
+    git_removed: Color::Red,
+    git_modified: Color::Yellow,
+    conflict_marker_bg: Color::Rgb(68, 71, 90),
+    conflict_ours_bg: Color::Rgb(30, 60, 40),
+    conflict_base_bg: Color::Rgb(60, 50, 30),
+    conflict_theirs_bg: Color::Rgb(30, 45, 80),
+    success: Color::Green,
+    warning: Color::Yellow,
+    error: Color::Red,
+    white: Color::White,
+    tilde: Color::DarkGray,
+    active_tab: Color::Blue,
+    inactive_tab: Color::Gray,
+};
+
+/// For light terminals: darker text colors and pale backgrounds.
+pub const LIGHT: Theme = Theme {
+    name: "light",
+    bg: Color::Reset,
+    fg: Color::Reset,
+    border: Color::Rgb(160, 160, 170),
+    bar_bg: Color::Reset,
+    bar_fg: Color::Reset,
+    line_number: Color::Rgb(140, 140, 150),
+    selection: Color::Rgb(180, 205, 245),
+    search_match: Color::Rgb(250, 220, 100),
+    heading: Color::Rgb(30, 80, 200),
+    heading_levels: [
+        Color::Rgb(30, 80, 200),
+        Color::Rgb(0, 120, 140),
+        Color::Rgb(40, 130, 40),
+        Color::Rgb(160, 110, 0),
+        Color::Rgb(140, 60, 150),
+        Color::Rgb(90, 90, 100),
+    ],
+    bold: Color::Rgb(150, 90, 0),
+    italic: Color::Rgb(0, 120, 140),
+    link: Color::Rgb(0, 90, 180),
+    code: Color::Rgb(180, 40, 40),
+    code_bg: Color::Rgb(240, 240, 245),
+    code_highlight_bg: Color::Rgb(222, 226, 240),
+    quote: Color::Rgb(40, 130, 40),
+    quote_border: Color::Rgb(60, 150, 80),
+    quote_bg: Color::Reset,
+    quote_bar: true,
+    marker: Color::Rgb(160, 160, 170),
+    misspelled: Color::Red,
+    code_theme: "InspiredGitHub",
+    git_added: Color::Rgb(30, 140, 50),
+    git_removed: Color::Rgb(200, 40, 40),
+    git_modified: Color::Rgb(180, 130, 0),
+    conflict_marker_bg: Color::Rgb(215, 215, 225),
+    conflict_ours_bg: Color::Rgb(215, 240, 215),
+    conflict_base_bg: Color::Rgb(245, 235, 205),
+    conflict_theirs_bg: Color::Rgb(215, 225, 250),
+    success: Color::Rgb(30, 140, 50),
+    warning: Color::Rgb(180, 130, 0),
+    error: Color::Rgb(200, 40, 40),
+    white: Color::White,
+    tilde: Color::Rgb(170, 170, 180),
+    active_tab: Color::Blue,
+    inactive_tab: Color::DarkGray,
+};
+
+/// Bright colors on black, for the most legibility.
+pub const HIGH_CONTRAST: Theme = Theme {
+    name: "high-contrast",
+    bg: Color::Black,
+    fg: Color::White,
+    border: Color::White,
+    bar_bg: Color::Black,
+    bar_fg: Color::White,
+    line_number: Color::Gray,
+    selection: Color::Blue,
+    search_match: Color::Yellow,
+    heading: Color::LightBlue,
+    heading_levels: [
+        Color::LightBlue,
+        Color::LightCyan,
+        Color::LightGreen,
+        Color::LightYellow,
+        Color::LightMagenta,
+        Color::White,
+    ],
+    bold: Color::LightYellow,
+    italic: Color::LightCyan,
+    link: Color::LightCyan,
+    code: Color::LightRed,
+    code_bg: Color::Black,
+    code_highlight_bg: Color::DarkGray,
+    quote: Color::LightGreen,
+    quote_border: Color::LightGreen,
+    quote_bg: Color::Reset,
+    quote_bar: true,
+    marker: Color::Gray,
+    misspelled: Color::LightRed,
+    code_theme: "base16-eighties.dark",
+    git_added: Color::LightGreen,
+    git_removed: Color::LightRed,
+    git_modified: Color::LightYellow,
+    conflict_marker_bg: Color::DarkGray,
+    conflict_ours_bg: Color::Rgb(0, 70, 0),
+    conflict_base_bg: Color::Rgb(80, 60, 0),
+    conflict_theirs_bg: Color::Rgb(0, 40, 110),
+    success: Color::LightGreen,
+    warning: Color::LightYellow,
+    error: Color::LightRed,
+    white: Color::White,
+    tilde: Color::Gray,
+    active_tab: Color::Blue,
+    inactive_tab: Color::White,
+};
+
+/// The built-in themes, in the order F8 cycles through them.
+pub const PRESETS: [&Theme; 3] = [&DARK, &LIGHT, &HIGH_CONTRAST];
+
+/// Name of the theme read from `theme.toml`.
+pub const CUSTOM_NAME: &str = "custom";
+
+/// The theme from `theme.toml`, once `load_custom` has read it.
+static CUSTOM: OnceLock<&'static Theme> = OnceLock::new();
+
+thread_local! {
+    // Per thread, so each test draws with its own
+    static ACTIVE: Cell<&'static Theme> = const { Cell::new(&DARK) };
+}
+
+impl Theme {
+    /// The built-in or custom theme called `name`, if there is one.
+    pub fn by_name(name: &str) -> Option<&'static Theme> {
+        all().into_iter().find(|theme| theme.name == name)
+    }
+
+    /// The theme after this one, wrapping around.
+    pub fn next(&self) -> &'static Theme {
+        let themes = all();
+        let index = themes.iter().position(|theme| theme.name == self.name).unwrap_or(0);
+        themes[(index + 1) % themes.len()]
+    }
+
+    /// Every single color with its key in `theme.toml`, except
+    /// `heading_levels`.
+    fn colors_mut(&mut self) -> [(&'static str, &mut Color); 34] {
+        [
+            ("bg", &mut self.bg),
+            ("fg", &mut self.fg),
+            ("border", &mut self.border),
+            ("bar_bg", &mut self.bar_bg),
+            ("bar_fg", &mut self.bar_fg),
+            ("line_number", &mut self.line_number),
+            ("selection", &mut self.selection),
+            ("search_match", &mut self.search_match),
+            ("heading", &mut self.heading),
+            ("bold", &mut self.bold),
+            ("italic", &mut self.italic),
+            ("link", &mut self.link),
+            ("code", &mut self.code),
+            ("code_bg", &mut self.code_bg),
+            ("code_highlight_bg", &mut self.code_highlight_bg),
+            ("quote", &mut self.quote),
+            ("quote_border", &mut self.quote_border),
+            ("quote_bg", &mut self.quote_bg),
+            ("marker", &mut self.marker),
+            ("misspelled", &mut self.misspelled),
+            ("git_added", &mut self.git_added),
+            ("git_removed", &mut self.git_removed),
+            ("git_modified", &mut self.git_modified),
+            ("conflict_marker_bg", &mut self.conflict_marker_bg),
+            ("conflict_ours_bg", &mut self.conflict_ours_bg),
+            ("conflict_base_bg", &mut self.conflict_base_bg),
+            ("conflict_theirs_bg", &mut self.conflict_theirs_bg),
+            ("success", &mut self.success),
+            ("warning", &mut self.warning),
+            ("error", &mut self.error),
+            ("white", &mut self.white),
+            ("tilde", &mut self.tilde),
+            ("active_tab", &mut self.active_tab),
+            ("inactive_tab", &mut self.inactive_tab),
+        ]
+    }
+
+    /// Parses a `theme.toml` (see the module docs) into a theme named
+    /// `CUSTOM_NAME`.
+    pub fn parse(text: &str) -> Result<Theme, String> {
+        let table: toml::Table = toml::from_str(text).map_err(|e| e.message().to_string())?;
+        let base = match table.get("base") {
+            None => &DARK,
+            Some(toml::Value::String(name)) => {
+                PRESETS.into_iter().find(|theme| theme.name == name).ok_or_else(|| format!("unknown base {:?}", name))?
+            }
+            Some(_) => return Err("base must be a theme name".to_string()),
+        };
+        let mut theme = Theme { name: CUSTOM_NAME, ..base.clone() };
+        for (key, value) in &table {
+            match (key.as_str(), value) {
+                ("base", _) => {}
+                ("code_theme", toml::Value::String(name)) => {
+                    if !crate::markdown::code_highlight::theme_names().contains(&name.as_str()) {
+                        return Err(format!("unknown code_theme {:?} (see `marko themes`)", name));
+                    }
+                    theme.code_theme = name.clone().leak();
+                }
+                ("heading_levels", toml::Value::Array(levels)) if levels.len() == 6 => {
+                    for (level, value) in theme.heading_levels.iter_mut().zip(levels) {
+                        *level = parse_color(key, value)?;
+                    }
+                }
+                ("heading_levels", _) => return Err("heading_levels must list 6 colors".to_string()),
+                ("quote_bar", toml::Value::Boolean(bar)) => theme.quote_bar = *bar,
+                ("quote_bar", _) => return Err("quote_bar must be true or false".to_string()),
+                _ => {
+                    let mut colors = theme.colors_mut();
+                    let Some((_, color)) = colors.iter_mut().find(|(name, _)| name == key) else {
+                        return Err(format!("unknown color {:?}", key));
+                    };
+                    **color = parse_color(key, value)?;
+                }
+            }
+        }
+        Ok(theme)
+    }
+
+    /// The theme as a complete `theme.toml`.
+    pub fn to_toml(&self) -> String {
+        let mut out = String::from("# marko theme: save as theme.toml next to config.toml\n");
+        if PRESETS.iter().any(|theme| theme.name == self.name) {
+            out.push_str(&format!("base = {:?}\n", self.name));
+        }
+        out.push_str(&format!("code_theme = {:?}\n", self.code_theme));
+        let levels: Vec<String> = self.heading_levels.iter().map(|c| format!("{:?}", c.to_string())).collect();
+        out.push_str(&format!("heading_levels = [{}]\n", levels.join(", ")));
+        out.push_str(&format!("quote_bar = {}\n", self.quote_bar));
+        for (name, color) in self.clone().colors_mut() {
+            out.push_str(&format!("{} = {:?}\n", name, color.to_string()));
+        }
+        out
+    }
+}
+
+/// A color value from `theme.toml`.
+fn parse_color(key: &str, value: &toml::Value) -> Result<Color, String> {
+    value
+        .as_str()
+        .and_then(|text| text.parse().ok())
+        .ok_or_else(|| format!("{}: expected a color name or \"#rrggbb\", got {}", key, value))
+}
+
+/// Where a custom theme is read from: `theme.toml` next to `config.toml`.
+pub fn custom_path() -> Option<PathBuf> {
+    Some(crate::config::Config::path()?.with_file_name("theme.toml"))
+}
+
+/// Reads the custom theme from `custom_path`, once; None if there's no
+/// such file. A malformed file is an error describing what went wrong.
+pub fn load_custom() -> Result<Option<&'static Theme>, String> {
+    if let Some(theme) = CUSTOM.get() {
+        return Ok(Some(theme));
+    }
+    let Some(path) = custom_path().filter(|path| path.is_file()) else {
+        return Ok(None);
+    };
+    let text = std::fs::read_to_string(&path).map_err(|e| format!("{}: {}", path.display(), e))?;
+    let theme = Theme::parse(&text).map_err(|e| format!("{}: {}", path.display(), e))?;
+    Ok(Some(CUSTOM.get_or_init(|| Box::leak(Box::new(theme)))))
+}
+
+/// The built-in themes, then the custom one if it was loaded.
+fn all() -> Vec<&'static Theme> {
+    PRESETS.into_iter().chain(CUSTOM.get().copied()).collect()
+}
+
+/// The theme everything is drawn with.
+pub fn active() -> &'static Theme {
+    ACTIVE.with(Cell::get)
+}
+
+/// Draws with `theme` from now on.
+pub fn set_active(theme: &'static Theme) {
+    ACTIVE.with(|active| active.set(theme));
+}
 
 // Pre-built styles
 pub fn editor_style() -> Style {
-    Style::default()
+    let theme = active();
+    Style::default().fg(theme.fg).bg(theme.bg)
 }
 
 pub fn header_style() -> Style {
-    Style::default()
+    let theme = active();
+    Style::default().fg(theme.bar_fg).bg(theme.bar_bg)
 }
 
 pub fn status_style() -> Style {
-    Style::default()
+    let theme = active();
+    Style::default().fg(theme.bar_fg).bg(theme.bar_bg)
 }
 
 pub fn line_number_style() -> Style {
-    Style::default().fg(LINE_NUMBER)
+    Style::default().fg(active().line_number)
 }
 
 pub fn cursor_line_style() -> Style {
     Style::default()
 }
 
+pub fn search_match_style() -> Style {
+    Style::default().fg(Color::Black).bg(active().search_match)
+}
+
 pub fn heading_style() -> Style {
     Style::default()
-        .fg(HEADING)
+        .fg(active().heading)
         .add_modifier(Modifier::BOLD)
 }
 
 pub fn bold_style() -> Style {
     Style::default()
-        .fg(BOLD)
+        .fg(active().bold)
         .add_modifier(Modifier::BOLD)
 }
 
 pub fn italic_style() -> Style {
     Style::default()
-        .fg(ITALIC)
+        .fg(active().italic)
         .add_modifier(Modifier::ITALIC)
 }
 
 pub fn code_style() -> Style {
-    Style::default().fg(CODE)
+    Style::default().fg(active().code)
 }
 
 pub fn quote_style() -> Style {
     Style::default()
-        .fg(QUOTE)
+        .fg(active().quote)
         .add_modifier(Modifier::ITALIC)
 }
 
 pub fn link_style() -> Style {
     Style::default()
-        .fg(LINK)
+        .fg(active().link)
         .add_modifier(Modifier::UNDERLINED)
 }
@@ -12,7 +12,7 @@ pub fn run_upgrade() -> io::Result<()> {
         .current_version(cargo_crate_version!())
         .build()
         .and_then(|u| u.update())
-        .map_err(|e| io::Error::new(io::ErrorKind::Other, e))?;
+        .map_err(io::Error::other)?;
 
     let current = cargo_crate_version!();
     if status.version() == current {
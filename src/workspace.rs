@@ -0,0 +1,92 @@
+//! Recursive workspace scanning for features that look beyond the open file,
+//! such as opening a folder's markdown files from the command line.
+//!
+//! Every scan goes through `files` so they all share the same rules:
+//! `.gitignore`/`.ignore` are honored unless `include_ignored` is set,
+//! directories named in `exclude_dirs` are always skipped, and hidden files
+//! and directories (`.git`, `.marko`) are never listed.
+
+use std::path::{Path, PathBuf};
+
+use ignore::WalkBuilder;
+
+use crate::config::Config;
+
+/// Rules shared by every workspace scan.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct WalkOptions {
+    /// Include files matched by `.gitignore`/`.ignore` rules.
+    pub include_ignored: bool,
+    /// Directory names skipped wherever they appear (e.g. `target`, `.obsidian`).
+    pub exclude_dirs: Vec<String>,
+}
+
+impl WalkOptions {
+    pub fn from_config(config: &Config) -> Self {
+        Self {
+            include_ignored: config.include_ignored_files,
+            exclude_dirs: config.exclude_dirs.clone(),
+        }
+    }
+}
+
+/// Returns the directory to scan for `file`: the enclosing git work tree,
+/// or the file's own directory outside a repository.
+pub fn root_for(file: &Path) -> PathBuf {
+    let dir = file.parent().unwrap_or(Path::new("."));
+    git2::Repository::discover(dir)
+        .ok()
+        .and_then(|repo| repo.workdir().map(Path::to_path_buf))
+        .unwrap_or_else(|| dir.to_path_buf())
+}
+
+/// `file` relative to the workspace `root`, or `file` unchanged when it
+/// lies outside the workspace.
+pub fn relative_path(file: &Path, root: &Path) -> PathBuf {
+    file.strip_prefix(root)
+        .map(Path::to_path_buf)
+        .unwrap_or_else(|_| file.to_path_buf())
+}
+
+/// Lists every file under `root` allowed by `options`, sorted.
+pub fn files(root: &Path, options: &WalkOptions) -> Vec<PathBuf> {
+    let respect_ignores = !options.include_ignored;
+    let exclude_dirs = options.exclude_dirs.clone();
+    let mut files: Vec<PathBuf> = WalkBuilder::new(root)
+        .hidden(true)
+        .ignore(respect_ignores)
+        .git_ignore(respect_ignores)
+        .git_exclude(respect_ignores)
+        .git_global(respect_ignores)
+        .parents(respect_ignores)
+        // Apply .gitignore even when the workspace isn't a git repo yet
+        .require_git(false)
+        .filter_entry(move |entry| {
+            let is_dir = entry.file_type().is_some_and(|t| t.is_dir());
+            !(is_dir
+                && entry.depth() > 0
+                && entry
+                    .file_name()
+                    .to_str()
+                    .is_some_and(|name| exclude_dirs.iter().any(|d| d == name)))
+        })
+        .build()
+        .filter_map(Result::ok)
+        .filter(|entry| entry.file_type().is_some_and(|t| t.is_file()))
+        .map(ignore::DirEntry::into_path)
+        .collect();
+    files.sort();
+    files
+}
+
+/// Lists the markdown files (`.md`, `.markdown`) under `root`, sorted.
+pub fn markdown_files(root: &Path, options: &WalkOptions) -> Vec<PathBuf> {
+    files(root, options)
+        .into_iter()
+        .filter(|p| {
+            p.extension()
+                .and_then(|e| e.to_str())
+                .is_some_and(|e| e.eq_ignore_ascii_case("md") || e.eq_ignore_ascii_case("markdown"))
+        })
+        .collect()
+}
@@ -25,3 +25,97 @@ fn test_no_git_repo_returns_none() {
     assert!(test_file.exists());
     std::fs::remove_dir_all(&tmp).ok();
 }
+
+/// Writes `content` to `doc.md` and commits it on top of HEAD.
+fn commit_doc(repo: &git2::Repository, content: &str, message: &str) -> git2::Oid {
+    let file = repo.workdir().unwrap().join("doc.md");
+    std::fs::write(&file, content).unwrap();
+
+    let mut index = repo.index().unwrap();
+    index.add_path(std::path::Path::new("doc.md")).unwrap();
+    index.write().unwrap();
+    let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+    let sig = git2::Signature::now("test", "test@example.com").unwrap();
+    let parent = repo.head().ok().and_then(|h| h.peel_to_commit().ok());
+    let parents: Vec<&git2::Commit> = parent.iter().collect();
+    repo.commit(Some("HEAD"), &sig, &sig, message, &tree, &parents)
+        .unwrap()
+}
+
+/// Commits `committed` as `doc.md` in a fresh repo, then overwrites the
+/// working copy with `working`. Returns the repo dir and the file path.
+fn repo_with_change(committed: &str, working: &str) -> (tempfile::TempDir, PathBuf) {
+    let dir = tempfile::TempDir::new().unwrap();
+    let repo = git2::Repository::init(dir.path()).unwrap();
+    commit_doc(&repo, committed, "init");
+
+    let file = dir.path().join("doc.md");
+    std::fs::write(&file, working).unwrap();
+    (dir, file)
+}
+
+fn gutter_diff(file: &std::path::Path) -> marko::git::diff::GutterDiff {
+    let repo = git2::Repository::discover(file).unwrap();
+    marko::git::diff::compute_gutter_diff(&repo, file, None)
+}
+
+#[test]
+fn test_removed_lines_report_boundary() {
+    let (_dir, file) = repo_with_change("a\nb\nc\nd\ne\nf\ng\nh\n", "a\nb\nc\nf\ng\nh\n");
+    let gutter = gutter_diff(&file);
+    // d and e were removed between c (line 2) and f (line 3)
+    assert_eq!(gutter.deletions, vec![3]);
+    assert!(gutter.marks.is_empty(), "no line is marked: {:?}", gutter.marks);
+}
+
+#[test]
+fn test_removed_first_and_last_lines() {
+    let (_dir, file) = repo_with_change("a\nb\nc\nd\n", "b\nc\n");
+    let gutter = gutter_diff(&file);
+    assert_eq!(gutter.deletions, vec![0, 2]);
+}
+
+#[test]
+fn test_replaced_lines_are_modified_not_deleted() {
+    use marko::git::diff::GutterMark;
+    let (_dir, file) = repo_with_change("a\nb\nc\n", "a\nB\nc\nd\n");
+    let gutter = gutter_diff(&file);
+    assert!(gutter.deletions.is_empty());
+    assert_eq!(gutter.marks.get(&1), Some(&GutterMark::Modified));
+    assert_eq!(gutter.marks.get(&3), Some(&GutterMark::Added));
+}
+
+#[test]
+fn test_in_memory_content_is_diffed_instead_of_disk() {
+    use marko::git::diff::GutterMark;
+    let (_dir, file) = repo_with_change("a\nb\nc\n", "a\nb\nc\n");
+    let repo = git2::Repository::discover(&file).unwrap();
+    assert!(gutter_diff(&file).marks.is_empty(), "disk matches HEAD");
+
+    let gutter = marko::git::diff::compute_gutter_diff_for_content(&repo, &file, "a\nnew\nb\nc\n", None);
+    assert_eq!(gutter.marks.get(&1), Some(&GutterMark::Added));
+    assert_eq!(gutter.marks.len(), 1);
+}
+
+#[test]
+fn test_diff_against_earlier_commit() {
+    use marko::git::diff::GutterMark;
+    let dir = tempfile::TempDir::new().unwrap();
+    let repo = git2::Repository::init(dir.path()).unwrap();
+    let first = commit_doc(&repo, "a\nb\n", "first");
+    commit_doc(&repo, "a\nb\nc\n", "second");
+    repo.tag_lightweight("v1", &repo.find_object(first, None).unwrap(), false)
+        .unwrap();
+    let file = dir.path().join("doc.md");
+
+    // Clean against HEAD, but "c" is new since the first commit
+    assert!(marko::git::diff::compute_gutter_diff(&repo, &file, None).marks.is_empty());
+    for base in [first.to_string(), "v1".to_string(), "HEAD~1".to_string()] {
+        let gutter = marko::git::diff::compute_gutter_diff(&repo, &file, Some(&base));
+        assert_eq!(gutter.marks.get(&2), Some(&GutterMark::Added), "base {}", base);
+        assert_eq!(gutter.marks.len(), 1, "base {}", base);
+    }
+
+    let unknown = marko::git::diff::compute_gutter_diff(&repo, &file, Some("no-such-ref"));
+    assert_eq!(unknown, marko::git::diff::GutterDiff::default());
+}
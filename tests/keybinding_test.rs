@@ -32,6 +32,10 @@ fn ctrl_char(ch: char) -> Event {
     Event::Key(KeyEvent::new(KeyCode::Char(ch), KeyModifiers::CONTROL))
 }
 
+fn ctrl_key(code: KeyCode) -> Event {
+    Event::Key(KeyEvent::new(code, KeyModifiers::CONTROL))
+}
+
 fn char_key(ch: char) -> Event {
     Event::Key(KeyEvent::new(KeyCode::Char(ch), KeyModifiers::NONE))
 }
@@ -60,11 +64,11 @@ fn ctrl_q_saves_modified_content_before_quitting() {
     let (mut app, tmp) = app_with_content("hello");
     // Modify content
     app.handle_event(char_key('x'));
-    assert!(app.modified);
+    assert!(app.buf.modified);
     // Ctrl+Q should save then quit
     app.handle_event(ctrl_char('q'));
     assert!(app.should_quit);
-    assert!(!app.modified);
+    assert!(!app.buf.modified);
     // Verify file was persisted to disk
     let on_disk = std::fs::read_to_string(tmp.path()).unwrap();
     assert!(on_disk.contains('x'));
@@ -84,9 +88,9 @@ fn ctrl_s_saves_from_editor() {
     let (mut app, tmp) = app_with_content("hello");
     // Modify content
     app.handle_event(char_key('x'));
-    assert!(app.modified);
+    assert!(app.buf.modified);
     app.handle_event(ctrl_char('s'));
-    assert!(!app.modified);
+    assert!(!app.buf.modified);
     // Verify file content on disk
     let on_disk = std::fs::read_to_string(tmp.path()).unwrap();
     assert!(on_disk.contains('x'));
@@ -97,12 +101,12 @@ fn ctrl_s_saves_from_preview() {
     let (mut app, _tmp) = app_with_content("hello");
     // Modify in editor first
     app.handle_event(char_key('x'));
-    assert!(app.modified);
+    assert!(app.buf.modified);
     // Switch to Preview, then save
     app.handle_event(key(KeyCode::Tab));
     assert_eq!(app.mode, Mode::Preview);
     app.handle_event(ctrl_char('s'));
-    assert!(!app.modified);
+    assert!(!app.buf.modified);
 }
 
 #[test]
@@ -236,7 +240,7 @@ fn rename_esc_cancels() {
 #[test]
 fn rename_enter_with_new_name_renames_file() {
     let (mut app, dir) = app_in_tempdir("hello");
-    let old_path = app.file_path.clone();
+    let old_path = app.buf.file_path.clone();
     app.handle_event(ctrl_char('t'));
     assert!(app.renaming);
 
@@ -250,7 +254,7 @@ fn rename_enter_with_new_name_renames_file() {
     assert!(!app.renaming);
     assert!(!old_path.exists());
     assert!(dir.path().join("renamed.md").exists());
-    assert_eq!(app.file_path, dir.path().join("renamed.md"));
+    assert_eq!(app.buf.file_path, dir.path().join("renamed.md"));
 }
 
 #[test]
@@ -267,7 +271,7 @@ fn rename_enter_with_empty_name_shows_cancelled() {
 #[test]
 fn rename_enter_with_same_name_silently_exits() {
     let (mut app, _tmp) = app_with_content("hello");
-    let original_name = app
+    let original_name = app.buf
         .file_path
         .file_name()
         .unwrap()
@@ -365,6 +369,34 @@ fn rename_char_insertion_at_mid_cursor() {
     assert_eq!(app.rename_cursor, 3);
 }
 
+#[test]
+fn rename_preview_tracks_target_path() {
+    let (mut app, dir) = app_in_tempdir("hello");
+    app.handle_event(ctrl_char('t'));
+    app.rename_buf = "notes".to_string();
+    app.rename_cursor = app.rename_buf.len();
+    app.handle_event(char_key('2'));
+    assert_eq!(app.rename_target, Some(dir.path().join("notes2")));
+    assert!(!app.rename_conflict);
+}
+
+#[test]
+fn rename_preview_flags_existing_target() {
+    let (mut app, dir) = app_in_tempdir("hello");
+    std::fs::write(dir.path().join("taken.md"), "other").unwrap();
+    app.handle_event(ctrl_char('t'));
+    // The current name is never a conflict with itself
+    assert!(!app.rename_conflict);
+
+    app.rename_buf = "taken.m".to_string();
+    app.rename_cursor = app.rename_buf.len();
+    app.handle_event(char_key('d'));
+    assert!(app.rename_conflict);
+
+    app.handle_event(key(KeyCode::Backspace));
+    assert!(!app.rename_conflict);
+}
+
 #[test]
 fn rename_right_at_end_is_noop() {
     let (mut app, _tmp) = app_with_content("hello");
@@ -415,9 +447,9 @@ fn editor_ctrl_z_undo() {
     let (mut app, _tmp) = app_with_content("hello");
     // Type a char to create undo history
     app.handle_event(char_key('x'));
-    assert!(app.textarea.lines()[0].starts_with('x'));
+    assert!(app.buf.textarea.lines()[0].starts_with('x'));
     app.handle_event(ctrl_char('z'));
-    assert_eq!(app.textarea.lines()[0], "hello");
+    assert_eq!(app.buf.textarea.lines()[0], "hello");
 }
 
 #[test]
@@ -425,9 +457,9 @@ fn editor_ctrl_y_redo() {
     let (mut app, _tmp) = app_with_content("hello");
     app.handle_event(char_key('x'));
     app.handle_event(ctrl_char('z')); // undo
-    assert_eq!(app.textarea.lines()[0], "hello");
+    assert_eq!(app.buf.textarea.lines()[0], "hello");
     app.handle_event(ctrl_char('y')); // redo
-    assert!(app.textarea.lines()[0].starts_with('x'));
+    assert!(app.buf.textarea.lines()[0].starts_with('x'));
 }
 
 #[test]
@@ -435,9 +467,9 @@ fn editor_ctrl_shift_z_redo() {
     let (mut app, _tmp) = app_with_content("hello");
     app.handle_event(char_key('x'));
     app.handle_event(ctrl_char('z')); // undo
-    assert_eq!(app.textarea.lines()[0], "hello");
+    assert_eq!(app.buf.textarea.lines()[0], "hello");
     app.handle_event(ctrl_shift_key(KeyCode::Char('Z')));
-    assert!(app.textarea.lines()[0].starts_with('x'));
+    assert!(app.buf.textarea.lines()[0].starts_with('x'));
 }
 
 #[test]
@@ -445,21 +477,21 @@ fn editor_ctrl_z_with_nothing_to_undo_no_crash() {
     let (mut app, _tmp) = app_with_content("hello");
     // No edits yet — undo should be a no-op
     app.handle_event(ctrl_char('z'));
-    assert_eq!(app.textarea.lines()[0], "hello");
+    assert_eq!(app.buf.textarea.lines()[0], "hello");
 }
 
 #[test]
 fn editor_ctrl_y_with_nothing_to_redo_no_crash() {
     let (mut app, _tmp) = app_with_content("hello");
     app.handle_event(ctrl_char('y'));
-    assert_eq!(app.textarea.lines()[0], "hello");
+    assert_eq!(app.buf.textarea.lines()[0], "hello");
 }
 
 #[test]
 fn editor_ctrl_a_select_all() {
     let (mut app, _tmp) = app_with_content("hello\nworld");
     app.handle_event(ctrl_char('a'));
-    let range = app.textarea.selection_range();
+    let range = app.buf.textarea.selection_range();
     assert!(range.is_some());
     let ((sr, sc), (er, ec)) = range.unwrap();
     assert_eq!((sr, sc), (0, 0));
@@ -472,18 +504,35 @@ fn editor_ctrl_l_goes_to_line_start() {
     let (mut app, _tmp) = app_with_content("hello\nworld\nfoo");
     // Move cursor to end of first line
     app.handle_event(key(KeyCode::End));
-    assert_eq!(app.textarea.cursor().1, 5);
+    assert_eq!(app.buf.textarea.cursor().1, 5);
     app.handle_event(ctrl_char('l'));
-    assert_eq!(app.textarea.cursor().1, 0, "Ctrl+L should move to column 0");
+    assert_eq!(app.buf.textarea.cursor().1, 0, "Ctrl+L should move to column 0");
     // Should not have a selection
-    assert!(app.textarea.selection_range().is_none());
+    assert!(app.buf.textarea.selection_range().is_none());
 }
 
 #[test]
 fn editor_ctrl_l_on_empty_file_no_crash() {
     let (mut app, _tmp) = app_with_content("");
     app.handle_event(ctrl_char('l'));
-    assert_eq!(app.textarea.cursor(), (0, 0));
+    assert_eq!(app.buf.textarea.cursor(), (0, 0));
+}
+
+#[test]
+fn editor_ctrl_home_goes_to_document_start() {
+    let (mut app, _tmp) = app_with_content("hello\nworld\nfoo bar");
+    app.handle_event(ctrl_key(KeyCode::End));
+    app.handle_event(ctrl_key(KeyCode::Home));
+    assert_eq!(app.buf.textarea.cursor(), (0, 0));
+}
+
+#[test]
+fn editor_ctrl_end_goes_to_document_end() {
+    let (mut app, _tmp) = app_with_content("hello\nworld\nfoo bar");
+    app.handle_event(ctrl_char('a'));
+    app.handle_event(ctrl_key(KeyCode::End));
+    assert_eq!(app.buf.textarea.cursor(), (2, 7));
+    assert!(app.buf.textarea.selection_range().is_none());
 }
 
 #[test]
@@ -493,7 +542,7 @@ fn editor_ctrl_h_delete_word_backward() {
     app.handle_event(key(KeyCode::End));
     app.handle_event(ctrl_char('h'));
     // "world" should be deleted
-    let line = &app.textarea.lines()[0];
+    let line = &app.buf.textarea.lines()[0];
     assert!(!line.contains("world"));
 }
 
@@ -503,16 +552,16 @@ fn editor_ctrl_d_delete_word_forward() {
     // Cursor at start
     app.handle_event(ctrl_char('d'));
     // "hello" should be deleted (or partially)
-    let line = &app.textarea.lines()[0];
+    let line = &app.buf.textarea.lines()[0];
     assert!(!line.starts_with("hello"));
 }
 
 #[test]
 fn typing_sets_modified_flag() {
     let (mut app, _tmp) = app_with_content("hello");
-    assert!(!app.modified);
+    assert!(!app.buf.modified);
     app.handle_event(char_key('x'));
-    assert!(app.modified);
+    assert!(app.buf.modified);
 }
 
 // ═══════════════════════════════════════════════════════════════════════
@@ -522,18 +571,18 @@ fn typing_sets_modified_flag() {
 #[test]
 fn arrow_keys_move_cursor() {
     let (mut app, _tmp) = app_with_content("hello\nworld");
-    let (start_row, _) = app.textarea.cursor();
+    let (start_row, _) = app.buf.textarea.cursor();
     app.handle_event(key(KeyCode::Down));
-    let (new_row, _) = app.textarea.cursor();
+    let (new_row, _) = app.buf.textarea.cursor();
     assert_eq!(new_row, start_row + 1);
 }
 
 #[test]
 fn enter_inserts_newline() {
     let (mut app, _tmp) = app_with_content("hello");
-    let lines_before = app.textarea.lines().len();
+    let lines_before = app.buf.textarea.lines().len();
     app.handle_event(key(KeyCode::Enter));
-    assert_eq!(app.textarea.lines().len(), lines_before + 1);
+    assert_eq!(app.buf.textarea.lines().len(), lines_before + 1);
 }
 
 #[test]
@@ -542,26 +591,26 @@ fn backspace_deletes_character() {
     // Move to end of line, then backspace
     app.handle_event(key(KeyCode::End));
     app.handle_event(key(KeyCode::Backspace));
-    assert_eq!(app.textarea.lines()[0], "hell");
+    assert_eq!(app.buf.textarea.lines()[0], "hell");
 }
 
 #[test]
 fn home_end_navigation() {
     let (mut app, _tmp) = app_with_content("hello");
     app.handle_event(key(KeyCode::End));
-    let (_, col) = app.textarea.cursor();
+    let (_, col) = app.buf.textarea.cursor();
     assert_eq!(col, 5); // "hello".len()
     app.handle_event(key(KeyCode::Home));
-    let (_, col) = app.textarea.cursor();
+    let (_, col) = app.buf.textarea.cursor();
     assert_eq!(col, 0);
 }
 
 #[test]
 fn passthrough_updates_modified_flag() {
     let (mut app, _tmp) = app_with_content("hello");
-    assert!(!app.modified);
+    assert!(!app.buf.modified);
     app.handle_event(key(KeyCode::Enter));
-    assert!(app.modified);
+    assert!(app.buf.modified);
 }
 
 #[test]
@@ -569,7 +618,7 @@ fn delete_key_works() {
     let (mut app, _tmp) = app_with_content("hello");
     // Cursor at start, delete should remove 'h'
     app.handle_event(key(KeyCode::Delete));
-    assert_eq!(app.textarea.lines()[0], "ello");
+    assert_eq!(app.buf.textarea.lines()[0], "ello");
 }
 
 // ═══════════════════════════════════════════════════════════════════════
@@ -579,10 +628,10 @@ fn delete_key_works() {
 #[test]
 fn paste_in_editor_inserts_text_and_sets_modified() {
     let (mut app, _tmp) = app_with_content("hello");
-    assert!(!app.modified);
+    assert!(!app.buf.modified);
     app.handle_event(Event::Paste("world".to_string()));
-    assert!(app.modified);
-    assert!(app.textarea.lines()[0].contains("world"));
+    assert!(app.buf.modified);
+    assert!(app.buf.textarea.lines()[0].contains("world"));
 }
 
 #[test]
@@ -601,7 +650,7 @@ fn paste_in_preview_is_ignored() {
     app.handle_event(key(KeyCode::Tab)); // Switch to Preview
     assert_eq!(app.mode, Mode::Preview);
     app.handle_event(Event::Paste("pasted".to_string()));
-    assert!(!app.modified);
+    assert!(!app.buf.modified);
 }
 
 #[test]
@@ -609,7 +658,7 @@ fn paste_empty_string_in_editor() {
     let (mut app, _tmp) = app_with_content("hello");
     app.handle_event(Event::Paste(String::new()));
     // Empty paste should not set modified (content hasn't changed)
-    assert!(!app.modified);
+    assert!(!app.buf.modified);
 }
 
 // ═══════════════════════════════════════════════════════════════════════
@@ -619,39 +668,39 @@ fn paste_empty_string_in_editor() {
 #[test]
 fn initial_state_not_modified() {
     let (app, _tmp) = app_with_content("hello");
-    assert!(!app.modified);
+    assert!(!app.buf.modified);
 }
 
 #[test]
 fn typing_sets_modified() {
     let (mut app, _tmp) = app_with_content("hello");
     app.handle_event(char_key('x'));
-    assert!(app.modified);
+    assert!(app.buf.modified);
 }
 
 #[test]
 fn undo_back_to_original_clears_modified() {
     let (mut app, _tmp) = app_with_content("hello");
     app.handle_event(char_key('x'));
-    assert!(app.modified);
+    assert!(app.buf.modified);
     app.handle_event(ctrl_char('z'));
-    assert!(!app.modified);
+    assert!(!app.buf.modified);
 }
 
 #[test]
 fn save_clears_modified() {
     let (mut app, _tmp) = app_with_content("hello");
     app.handle_event(char_key('x'));
-    assert!(app.modified);
+    assert!(app.buf.modified);
     app.handle_event(ctrl_char('s'));
-    assert!(!app.modified);
+    assert!(!app.buf.modified);
 }
 
 #[test]
 fn paste_sets_modified() {
     let (mut app, _tmp) = app_with_content("hello");
     app.handle_event(Event::Paste("inserted".to_string()));
-    assert!(app.modified);
+    assert!(app.buf.modified);
 }
 
 #[test]
@@ -659,7 +708,7 @@ fn delete_word_sets_modified() {
     let (mut app, _tmp) = app_with_content("hello world");
     app.handle_event(key(KeyCode::End));
     app.handle_event(ctrl_char('h'));
-    assert!(app.modified);
+    assert!(app.buf.modified);
 }
 
 // ═══════════════════════════════════════════════════════════════════════
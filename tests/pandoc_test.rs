@@ -118,5 +118,5 @@ fn docx_state_is_none_for_regular_md_via_lib() {
     tmp.write_all(b"hello world").unwrap();
     tmp.flush().unwrap();
     let app = App::new(tmp.path().to_path_buf());
-    assert!(app.docx_state.is_none(), "docx_state should be None for .md files");
+    assert!(app.buf.docx_state.is_none(), "docx_state should be None for .md files");
 }
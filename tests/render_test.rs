@@ -90,7 +90,7 @@ fn header_shows_filename() {
 #[test]
 fn header_shows_modified_indicator_when_modified() {
     let (mut app, _dir) = app_with_named_file("hello", "test.md");
-    app.modified = true;
+    app.buf.modified = true;
     let buf = render_app(&mut app, 80, 24);
     let header = buffer_line_text(&buf, 0);
     // The bullet character U+2022 (•) is the modified indicator
@@ -104,7 +104,7 @@ fn header_shows_modified_indicator_when_modified() {
 #[test]
 fn header_no_modified_indicator_when_clean() {
     let (mut app, _dir) = app_with_named_file("hello", "test.md");
-    assert!(!app.modified);
+    assert!(!app.buf.modified);
     let buf = render_app(&mut app, 80, 24);
     let header = buffer_line_text(&buf, 0);
     assert!(
@@ -161,6 +161,72 @@ fn header_rename_input_replaces_filename() {
     );
 }
 
+#[test]
+fn header_truncates_long_filename_keeping_tabs() {
+    use crossterm::event::{Event, KeyCode, KeyEvent, KeyModifiers};
+    let name = format!("{}.md", "verylongfilename".repeat(6));
+    let (mut app, _dir) = app_with_named_file("hello", &name);
+    // The first render reflows from disk, so edit after it to mark modified
+    render_app(&mut app, 80, 24);
+    app.handle_event(Event::Key(KeyEvent::new(KeyCode::Char('x'), KeyModifiers::NONE)));
+    let buf = render_app(&mut app, 80, 24);
+    let header = buffer_line_text(&buf, 0);
+    assert!(!header.contains(&name), "Long name should be truncated, got: '{}'", header);
+    assert!(header.contains("\u{2026}"), "Truncation should use an ellipsis, got: '{}'", header);
+    assert!(header.contains("verylong"), "Name start should stay visible, got: '{}'", header);
+    assert!(header.contains("ame.md"), "Extension should stay visible, got: '{}'", header);
+    assert!(header.contains("\u{2022}"), "Modified indicator should stay visible, got: '{}'", header);
+    assert!(
+        header.ends_with(" EDITOR  PREVIEW"),
+        "Mode tabs should be intact, got: '{}'",
+        header
+    );
+}
+
+#[test]
+fn header_truncates_wide_filename_by_columns() {
+    use crossterm::event::{Event, KeyCode, KeyEvent, KeyModifiers};
+    let name = format!("{}.md", "日本語のファイル名".repeat(5));
+    let (mut app, _dir) = app_with_named_file("hello", &name);
+    render_app(&mut app, 80, 24);
+    app.handle_event(Event::Key(KeyEvent::new(KeyCode::Char('x'), KeyModifiers::NONE)));
+    let buf = render_app(&mut app, 80, 24);
+    let header = buffer_line_text(&buf, 0);
+    assert!(header.contains("\u{2026}"), "Truncation should use an ellipsis, got: '{}'", header);
+    assert!(header.contains(".md"), "Extension should stay visible, got: '{}'", header);
+    assert!(header.contains("\u{2022}"), "Modified indicator should stay visible, got: '{}'", header);
+    assert!(
+        header.ends_with(" EDITOR  PREVIEW"),
+        "Mode tabs should be intact, got: '{}'",
+        header
+    );
+}
+
+#[test]
+fn header_shows_buffer_position_with_several_files() {
+    let (mut app, dir) = app_with_named_file("hello", "a.md");
+    let header = buffer_line_text(&render_app(&mut app, 80, 24), 0);
+    assert!(!header.contains("1/1"), "No indicator for a single file, got: '{}'", header);
+
+    app.file_list.push(dir.path().join("b.md"));
+    app.file_list.push(dir.path().join("c.md"));
+    let header = buffer_line_text(&render_app(&mut app, 80, 24), 0);
+    assert!(header.starts_with("  a.md 1/3"), "Header should show '1/3', got: '{}'", header);
+}
+
+#[test]
+fn header_shows_read_only_indicator() {
+    let (mut app, _dir) = app_with_named_file("hello", "notes.md");
+    let header = buffer_line_text(&render_app(&mut app, 80, 24), 0);
+    assert!(!header.contains("[RO]"), "got: '{}'", header);
+
+    app.read_only = true;
+    let buf = render_app(&mut app, 80, 24);
+    let header = buffer_line_text(&buf, 0);
+    assert!(header.starts_with("  notes.md [RO]"), "got: '{}'", header);
+    assert_eq!(cell_fg(&buf, 12, 0), Color::Yellow);
+}
+
 // ═══════════════════════════════════════════════════════════════════════
 // B. Editor Rendering
 // ═══════════════════════════════════════════════════════════════════════
@@ -255,6 +321,31 @@ fn status_bar_shows_status_message() {
     );
 }
 
+#[test]
+fn error_status_uses_error_color_and_stays_longer() {
+    use marko::components::status::Severity;
+    use std::time::{Duration, Instant};
+    let (mut app, _tmp) = app_with_content("hello");
+    app.set_status_as("Error saving: disk full", Severity::Error);
+    let buf = render_app(&mut app, 80, 24);
+    let status = buffer_line_text(&buf, 23);
+    let col = status.find("Error saving").unwrap() as u16;
+    assert_eq!(cell_fg(&buf, col, 23), marko::theme::active().error);
+
+    // Past the usual 3 seconds, the error is still up
+    app.status_time = Some(Instant::now() - Duration::from_secs(5));
+    app.tick();
+    assert_eq!(app.status_message, "Error saving: disk full");
+    app.status_time = Some(Instant::now() - Duration::from_secs(11));
+    app.tick();
+    assert!(app.status_message.is_empty());
+
+    app.set_status("Saved");
+    let buf = render_app(&mut app, 80, 24);
+    let col = buffer_line_text(&buf, 23).find("Saved").unwrap() as u16;
+    assert_ne!(cell_fg(&buf, col, 23), marko::theme::active().error, "info keeps the plain color");
+}
+
 #[test]
 fn status_bar_shows_word_count_and_save_status() {
     let (mut app, _tmp) = app_with_content("hello world foo");
@@ -272,6 +363,44 @@ fn status_bar_shows_word_count_and_save_status() {
     );
 }
 
+#[test]
+fn status_bar_shows_reading_time() {
+    let (mut app, _tmp) = app_with_content(&"word ".repeat(450));
+    let buf = render_app(&mut app, 80, 24);
+    let status = buffer_line_text(&buf, 23);
+    assert!(status.contains("450 words | 2 min | Saved"), "got: '{}'", status);
+}
+
+#[test]
+fn status_bar_shows_word_goal_progress() {
+    let (mut app, _tmp) = app_with_content("---\ngoal: 10\n---\none two three four five");
+    let buf = render_app(&mut app, 80, 24);
+    let status = buffer_line_text(&buf, 23);
+    assert!(status.contains("\u{2588}\u{2588}\u{2588}\u{2588}\u{2588}\u{2591}"), "half full: '{}'", status);
+    assert!(status.contains(" 5/10 (50%) | <1 min | Saved"), "got: '{}'", status);
+
+    app.buf.word_goal = Some(5);
+    let buf = render_app(&mut app, 80, 24);
+    let status = buffer_line_text(&buf, 23);
+    let bar = status.find('\u{2588}').expect("bar shown");
+    let x = status[..bar].chars().count() as u16;
+    assert_eq!(cell_fg(&buf, x, 23), marko::theme::active().success, "green once reached");
+    assert!(status.contains(" 5/5 (100%)"), "got: '{}'", status);
+}
+
+#[test]
+fn status_bar_shows_vim_mode() {
+    let (mut app, _tmp) = app_with_content("hello");
+    app.buf.config.editing = marko::config::Editing::Vim;
+    app.vim_normal = true;
+    let buf = render_app(&mut app, 80, 24);
+    assert!(buffer_line_text(&buf, 23).starts_with("  NORMAL  Ln 1, Col 0  All"));
+
+    app.vim_normal = false;
+    let buf = render_app(&mut app, 80, 24);
+    assert!(buffer_line_text(&buf, 23).starts_with("  INSERT  Ln 1, Col 0"));
+}
+
 #[test]
 fn status_bar_has_correct_background() {
     let (mut app, _tmp) = app_with_content("hello");
@@ -281,6 +410,40 @@ fn status_bar_has_correct_background() {
     assert_eq!(bg, Color::Reset, "Status bar should have terminal default background");
 }
 
+#[test]
+fn status_bar_shows_rename_conflict_in_red() {
+    let (mut app, dir) = app_with_named_file("hello", "original.md");
+    let target = dir.path().join("taken.md");
+    app.renaming = true;
+    app.rename_buf = "taken.md".to_string();
+    app.rename_cursor = app.rename_buf.len();
+    app.rename_target = Some(target.clone());
+    app.rename_conflict = true;
+    let buf = render_app(&mut app, 200, 24);
+    let status = buffer_line_text(&buf, 23);
+    assert!(
+        status.contains(&target.display().to_string()),
+        "Status bar should preview the full target path, got: '{}'",
+        status
+    );
+    let note = status.find("(exists)").expect("conflict should be flagged");
+    let x = status[..note].chars().count() as u16;
+    assert_eq!(cell_fg(&buf, x, 23), Color::Red);
+}
+
+#[test]
+fn status_bar_shows_available_rename_in_green() {
+    let (mut app, dir) = app_with_named_file("hello", "original.md");
+    app.renaming = true;
+    app.rename_target = Some(dir.path().join("fresh.md"));
+    app.rename_conflict = false;
+    let buf = render_app(&mut app, 200, 24);
+    let status = buffer_line_text(&buf, 23);
+    let note = status.find("(available)").expect("free target should be flagged");
+    let x = status[..note].chars().count() as u16;
+    assert_eq!(cell_fg(&buf, x, 23), Color::Green);
+}
+
 // ═══════════════════════════════════════════════════════════════════════
 // D. Help Modal Rendering
 // ═══════════════════════════════════════════════════════════════════════
@@ -322,6 +485,30 @@ fn help_modal_not_visible_when_show_help_false() {
     );
 }
 
+#[test]
+fn help_modal_scrolls_to_its_last_entry() {
+    use crossterm::event::{Event, KeyCode, KeyEvent, KeyModifiers};
+    let (mut app, _tmp) = app_with_content("hello");
+    app.show_help = true;
+    let buf = render_app(&mut app, 80, 30);
+    assert!(buffer_contains(&buf, "Keybindings"));
+    assert!(!buffer_contains(&buf, "Click tabs"), "the end doesn't fit in 30 rows");
+
+    for _ in 0..5 {
+        app.handle_event(Event::Key(KeyEvent::new(KeyCode::PageDown, KeyModifiers::NONE)));
+        render_app(&mut app, 80, 30);
+    }
+    assert!(app.show_help, "scrolling keeps the modal open");
+    let buf = render_app(&mut app, 80, 30);
+    assert!(buffer_contains(&buf, "Click tabs"));
+    assert!(!buffer_contains(&buf, "Keybindings"));
+
+    // Scrolled past the end, Up still moves right away
+    app.handle_event(Event::Key(KeyEvent::new(KeyCode::Up, KeyModifiers::NONE)));
+    let buf = render_app(&mut app, 80, 30);
+    assert!(!buffer_contains(&buf, "Click tabs"));
+}
+
 // ═══════════════════════════════════════════════════════════════════════
 // E. Mode-Specific Content
 // ═══════════════════════════════════════════════════════════════════════
@@ -475,3 +662,685 @@ fn render_frame_idle_writes_zero_cells() {
         terminal.backend().last_draw_count
     );
 }
+
+// ═══════════════════════════════════════════════════════════════════════
+// G. Small Terminals
+// ═══════════════════════════════════════════════════════════════════════
+
+#[test]
+fn tiny_terminal_shows_too_small_message() {
+    let (mut app, _tmp) = app_with_content("# Title\n\nSome text here.");
+    let buf = render_app(&mut app, 10, 5);
+    assert!(
+        buffer_contains(&buf, "too small"),
+        "10x5 terminal should show the 'too small' notice"
+    );
+    assert!(
+        !buffer_contains(&buf, "EDITOR"),
+        "Normal header should not render on a tiny terminal"
+    );
+}
+
+#[test]
+fn tiny_terminal_with_help_and_preview_does_not_panic() {
+    let (mut app, _tmp) = app_with_content("![alt text](some/long/path/image.png)");
+    app.show_help = true;
+    let _ = render_app(&mut app, 10, 5);
+    app.mode = Mode::Preview;
+    let buf = render_app(&mut app, 3, 1);
+    assert_eq!(buf.area.width, 3);
+}
+
+// ═══════════════════════════════════════════════════════════════════════
+// H. Editor Scrollbar
+// ═══════════════════════════════════════════════════════════════════════
+
+#[test]
+fn scrollbar_thumb_tracks_scroll_position() {
+    use marko::components::editor::{scroll_for_scrollbar_row, scrollbar_thumb};
+    // 100 lines in a 20-row viewport: thumb is 4 rows, 16 rows of travel
+    assert_eq!(scrollbar_thumb(20, 100, 0), (0, 4));
+    assert_eq!(scrollbar_thumb(20, 100, 40), (8, 4));
+    assert_eq!(scrollbar_thumb(20, 100, 80), (16, 4));
+    // Scrolling past the last full page keeps the thumb at the bottom
+    assert_eq!(scrollbar_thumb(20, 100, 99), (16, 4));
+    // Everything fits: thumb fills the track
+    assert_eq!(scrollbar_thumb(20, 10, 0), (0, 20));
+    // Clicking a row centers the thumb there
+    assert_eq!(scroll_for_scrollbar_row(20, 100, 10), 40);
+    assert_eq!(scroll_for_scrollbar_row(20, 100, 0), 0);
+    assert_eq!(scroll_for_scrollbar_row(20, 100, 19), 80);
+}
+
+#[test]
+fn editor_scrollbar_shown_only_for_long_files() {
+    let long_content = (0..100).map(|i| format!("Line {}", i)).collect::<Vec<_>>().join("\n");
+    let (mut app, _tmp) = app_with_content(&long_content);
+    let buf = render_app(&mut app, 80, 24);
+    // Content area spans rows 2..22; the thumb starts at the top
+    assert_eq!(buf.cell((79, 2)).unwrap().symbol(), "\u{2588}");
+    assert_eq!(buf.cell((79, 21)).unwrap().symbol(), "\u{2502}");
+
+    let (mut app, _tmp) = app_with_content("short\nfile");
+    let buf = render_app(&mut app, 80, 24);
+    assert_ne!(buf.cell((79, 2)).unwrap().symbol(), "\u{2588}");
+}
+
+#[test]
+fn editor_text_stops_short_of_the_scrollbar() {
+    let digits = "0123456789".repeat(20);
+    let long_content = std::iter::once(digits)
+        .chain((1..100).map(|i| format!("Line {}", i)))
+        .collect::<Vec<_>>()
+        .join("\n");
+    // " 100 " gutter, then 74 text columns and the scrollbar at column 79
+
+    // Soft wrap breaks the line before the scrollbar column
+    let (mut app, _tmp) = app_with_content(&long_content);
+    app.buf.config.soft_wrap = true;
+    let buf = render_app(&mut app, 80, 24);
+    assert_eq!(buf.cell((78, 2)).unwrap().symbol(), "3");
+    assert_eq!(buf.cell((79, 2)).unwrap().symbol(), "\u{2588}");
+    assert_eq!(buf.cell((5, 3)).unwrap().symbol(), "4", "no char hidden under the scrollbar");
+
+    // Without wrapping, a cursor past the last text column scrolls it into view
+    let (mut app, _tmp) = app_with_content(&long_content);
+    app.buf.auto_wrap = false;
+    app.buf.textarea.move_cursor(tui_textarea::CursorMove::Jump(0, 74));
+    let buf = render_app(&mut app, 80, 24);
+    assert_eq!(buf.cell((78, 2)).unwrap().symbol(), "4");
+    assert_eq!(buf.cell((79, 2)).unwrap().symbol(), "\u{2588}");
+}
+
+#[test]
+fn clicking_editor_scrollbar_scrolls_to_bottom() {
+    use crossterm::event::{Event, KeyModifiers, MouseButton, MouseEvent, MouseEventKind};
+    let long_content = (0..100).map(|i| format!("Line {}", i)).collect::<Vec<_>>().join("\n");
+    let (mut app, _tmp) = app_with_content(&long_content);
+    let _ = render_app(&mut app, 80, 24);
+    app.handle_event(Event::Mouse(MouseEvent {
+        kind: MouseEventKind::Down(MouseButton::Left),
+        column: 79,
+        row: 21,
+        modifiers: KeyModifiers::NONE,
+    }));
+    let buf = render_app(&mut app, 80, 24);
+    assert!(buffer_contains(&buf, "Line 99"), "last line should be visible");
+    assert!(
+        buffer_line_text(&buf, 2).contains("Line 80"),
+        "top row should show the last full page"
+    );
+    assert_eq!(buf.cell((79, 21)).unwrap().symbol(), "\u{2588}");
+}
+
+// ═══════════════════════════════════════════════════════════════════════
+// I. Scroll Position Indicator
+// ═══════════════════════════════════════════════════════════════════════
+
+#[test]
+fn scroll_position_edge_cases() {
+    use marko::components::status::scroll_position;
+    assert_eq!(scroll_position(0, 20, 10), "All");
+    assert_eq!(scroll_position(0, 20, 20), "All");
+    assert_eq!(scroll_position(0, 20, 100), "Top");
+    assert_eq!(scroll_position(80, 20, 100), "Bot");
+    assert_eq!(scroll_position(95, 20, 100), "Bot");
+    assert_eq!(scroll_position(40, 20, 100), "50%");
+    assert_eq!(scroll_position(1, 20, 100), "1%");
+}
+
+#[test]
+fn status_bar_shows_scroll_position() {
+    let long_content = (0..100).map(|i| format!("Line {}", i)).collect::<Vec<_>>().join("\n");
+    let (mut app, _tmp) = app_with_content(&long_content);
+    let buf = render_app(&mut app, 80, 24);
+    assert!(buffer_line_text(&buf, 23).contains("Top"));
+
+    let (mut app, _tmp) = app_with_content("short");
+    let buf = render_app(&mut app, 80, 24);
+    assert!(buffer_line_text(&buf, 23).contains("All"));
+}
+
+// ═══════════════════════════════════════════════════════════════════════
+// J. Scrolloff
+// ═══════════════════════════════════════════════════════════════════════
+
+#[test]
+fn scrolloff_keeps_margin_below_cursor() {
+    use crossterm::event::{Event, KeyCode, KeyEvent, KeyModifiers};
+    let long_content = (0..100).map(|i| format!("Line {}", i)).collect::<Vec<_>>().join("\n");
+    let (mut app, _tmp) = app_with_content(&long_content);
+    app.buf.config.scrolloff = 3;
+    let _ = render_app(&mut app, 80, 24);
+    // Content area is 20 rows; row 17 is inside the 3-line bottom margin
+    for _ in 0..17 {
+        app.handle_event(Event::Key(KeyEvent::new(KeyCode::Down, KeyModifiers::NONE)));
+    }
+    let buf = render_app(&mut app, 80, 24);
+    assert!(
+        buffer_line_text(&buf, 2).contains("Line 1 "),
+        "viewport should scroll one line early to keep 3 lines below the cursor"
+    );
+    assert_eq!(app.buf.textarea.cursor(), (17, 0));
+}
+
+#[test]
+fn scrolloff_zero_keeps_default_scrolling() {
+    use crossterm::event::{Event, KeyCode, KeyEvent, KeyModifiers};
+    let long_content = (0..100).map(|i| format!("Line {}", i)).collect::<Vec<_>>().join("\n");
+    let (mut app, _tmp) = app_with_content(&long_content);
+    let _ = render_app(&mut app, 80, 24);
+    for _ in 0..17 {
+        app.handle_event(Event::Key(KeyEvent::new(KeyCode::Down, KeyModifiers::NONE)));
+    }
+    let buf = render_app(&mut app, 80, 24);
+    assert!(buffer_line_text(&buf, 2).contains("Line 0 "));
+}
+
+// ═══════════════════════════════════════════════════════════════════════
+// K. Status-Bar Prompt
+// ═══════════════════════════════════════════════════════════════════════
+
+#[test]
+fn open_prompt_replaces_status_bar() {
+    use crossterm::event::{Event, KeyCode, KeyEvent, KeyModifiers};
+    let (mut app, _tmp) = app_with_content("one\ntwo");
+    app.handle_event(Event::Key(KeyEvent::new(KeyCode::Char('a'), KeyModifiers::CONTROL)));
+    app.handle_event(Event::Key(KeyEvent::new(KeyCode::Char('c'), KeyModifiers::ALT)));
+    app.handle_event(Event::Key(KeyEvent::new(KeyCode::Char('p'), KeyModifiers::NONE)));
+    let buf = render_app(&mut app, 80, 24);
+    let status = buffer_line_text(&buf, 23);
+    assert!(status.contains("Code block language: p"), "got: {:?}", status);
+    assert!(!status.contains("Ln "));
+}
+
+// ═══════════════════════════════════════════════════════════════════════
+// L. Live Emphasis
+// ═══════════════════════════════════════════════════════════════════════
+
+#[test]
+fn live_emphasis_styles_bold_and_dims_markers() {
+    use ratatui::style::Modifier;
+    let (mut app, _tmp) = app_with_content("plain **bold** *it*");
+    app.buf.config.live_emphasis = true;
+    let buf = render_app(&mut app, 80, 24);
+    let row = buffer_line_text(&buf, 2);
+    let x = |needle: &str| row.find(needle).unwrap() as u16;
+
+    let marker = buf[(x("**bold"), 2)].modifier;
+    assert!(marker.contains(Modifier::DIM), "markers should be dimmed");
+    let bold = buf[(x("bold"), 2)].modifier;
+    assert!(bold.contains(Modifier::BOLD) && !bold.contains(Modifier::DIM));
+    assert!(buf[(x("it*"), 2)].modifier.contains(Modifier::ITALIC));
+    // ("p" holds the cursor, which is always bold)
+    assert!(!buf[(x("lain"), 2)].modifier.contains(Modifier::BOLD));
+}
+
+#[test]
+fn live_emphasis_is_off_by_default() {
+    use ratatui::style::Modifier;
+    let (mut app, _tmp) = app_with_content("plain **bold**");
+    let buf = render_app(&mut app, 80, 24);
+    let row = buffer_line_text(&buf, 2);
+    let x = row.find("bold").unwrap() as u16;
+    assert!(!buf[(x, 2)].modifier.contains(Modifier::BOLD));
+}
+
+// ═══════════════════════════════════════════════════════════════════════
+// M. Dimmed Markers
+// ═══════════════════════════════════════════════════════════════════════
+
+#[test]
+fn dim_markers_recolors_heading_and_emphasis_markers() {
+    let (mut app, _tmp) = app_with_content("x\n# Title with *word*");
+    app.buf.config.dim_markers = true;
+    let buf = render_app(&mut app, 80, 24);
+    let row = buffer_line_text(&buf, 3);
+    let hash = row.find("# Title").unwrap() as u16;
+    let star = row.find("*word").unwrap() as u16;
+
+    assert_eq!(cell_fg(&buf, hash, 3), Color::DarkGray);
+    assert_eq!(cell_fg(&buf, star, 3), Color::DarkGray);
+    assert_eq!(cell_fg(&buf, star + 5, 3), Color::DarkGray, "closing marker");
+    assert_ne!(cell_fg(&buf, star + 1, 3), Color::DarkGray, "prose keeps its color");
+}
+
+#[test]
+fn alt_m_toggles_marker_dimming() {
+    use crossterm::event::{Event, KeyCode, KeyEvent, KeyModifiers};
+    let (mut app, _tmp) = app_with_content("x\n> quoted");
+    let alt_m = Event::Key(KeyEvent::new(KeyCode::Char('m'), KeyModifiers::ALT));
+    let buf = render_app(&mut app, 80, 24);
+    let quote = buffer_line_text(&buf, 3).find("> quoted").unwrap() as u16;
+    assert_ne!(cell_fg(&buf, quote, 3), Color::DarkGray);
+
+    app.handle_event(alt_m.clone());
+    let buf = render_app(&mut app, 80, 24);
+    assert_eq!(cell_fg(&buf, quote, 3), Color::DarkGray);
+
+    app.handle_event(alt_m);
+    let buf = render_app(&mut app, 80, 24);
+    assert_ne!(cell_fg(&buf, quote, 3), Color::DarkGray);
+}
+
+// ═══════════════════════════════════════════════════════════════════════
+// N. Heading Colors
+// ═══════════════════════════════════════════════════════════════════════
+
+#[test]
+fn heading_levels_get_different_colors() {
+    let (mut app, _tmp) = app_with_content("x\n# Top\n## Sub\nplain");
+    let buf = render_app(&mut app, 80, 24);
+    let h1 = buffer_line_text(&buf, 3).find("Top").unwrap() as u16;
+    let h2 = buffer_line_text(&buf, 4).find("Sub").unwrap() as u16;
+    let plain = buffer_line_text(&buf, 5).find("plain").unwrap() as u16;
+
+    let h1_fg = cell_fg(&buf, h1, 3);
+    let h2_fg = cell_fg(&buf, h2, 4);
+    assert_ne!(h1_fg, Color::Reset, "H1 should be colored");
+    assert_ne!(h2_fg, Color::Reset, "H2 should be colored");
+    assert_ne!(h1_fg, h2_fg, "H1 and H2 should differ");
+    assert_eq!(cell_fg(&buf, plain, 5), Color::Reset);
+}
+
+// ═══════════════════════════════════════════════════════════════════════
+// O. Git Gutter
+// ═══════════════════════════════════════════════════════════════════════
+
+#[test]
+fn gutter_uses_configured_glyph_per_change_kind() {
+    use marko::git::diff::GutterMark;
+    let (mut app, _tmp) = app_with_content("x\nadded\nchanged\nsame");
+    app.buf.config.gutter_glyph = "+~-".to_string();
+    app.buf.gutter_marks.insert(1, GutterMark::Added);
+    app.buf.gutter_marks.insert(2, GutterMark::Modified);
+    let buf = render_app(&mut app, 80, 24);
+
+    assert!(buffer_line_text(&buf, 3).starts_with('+'), "added line marked with +");
+    assert!(buffer_line_text(&buf, 4).starts_with('~'), "modified line marked with ~");
+    assert!(!buffer_line_text(&buf, 5).starts_with(['+', '~', '-']));
+}
+
+#[test]
+fn gutter_marker_width_widens_without_covering_line_numbers() {
+    use marko::git::diff::GutterMark;
+    // Ten lines, so single-digit line numbers leave a blank padding column
+    let (mut app, _tmp) = app_with_content("x\nadded\n\n\n\n\n\n\n\nlast");
+    app.buf.config.gutter_glyph = "┃".to_string();
+    app.buf.config.gutter_marker_width = 2;
+    app.buf.gutter_marks.insert(1, GutterMark::Added);
+    let buf = render_app(&mut app, 80, 24);
+
+    let line = buffer_line_text(&buf, 3);
+    assert!(line.starts_with("┃┃"), "marker fills two columns: {:?}", line);
+    assert!(line.contains("2 "), "line number still visible: {:?}", line);
+
+    app.buf.gutter_marks.insert(9, GutterMark::Added);
+    let buf = render_app(&mut app, 80, 24);
+    assert!(buffer_line_text(&buf, 11).starts_with("┃10"), "digits are never covered");
+}
+
+#[test]
+fn gutter_marks_deletions_between_lines() {
+    let (mut app, _tmp) = app_with_content("x\nbefore\nafter");
+    // Lines were removed between "before" and "after"
+    app.buf.gutter_deletions = vec![2];
+    let buf = render_app(&mut app, 80, 24);
+
+    assert!(buffer_line_text(&buf, 4).starts_with('\u{2594}'), "bar on top of the line below");
+    assert!(!buffer_line_text(&buf, 3).starts_with('\u{2594}'));
+    assert_eq!(cell_fg(&buf, 0, 4), Color::Red);
+
+    // At the end of the file the bar goes along the bottom of the last line
+    app.buf.gutter_deletions = vec![3];
+    let buf = render_app(&mut app, 80, 24);
+    assert!(buffer_line_text(&buf, 4).starts_with('\u{2581}'), "bar under the last line");
+}
+
+// ═══════════════════════════════════════════════════════════════════════
+// P. Soft Wrap
+// ═══════════════════════════════════════════════════════════════════════
+
+#[test]
+fn soft_wrap_shows_long_line_across_rows() {
+    let long = "alpha beta gamma delta epsilon zeta eta theta iota kappa lambda mu";
+    let (mut app, _tmp) = app_with_content(&format!("x\n{}\nend", long));
+    app.buf.config.soft_wrap = true;
+    let buf = render_app(&mut app, 40, 24);
+
+    assert_eq!(app.buf.textarea.lines().len(), 3, "buffer keeps the long line whole");
+    assert_eq!(buffer_line_text(&buf, 3), " 2 alpha beta gamma delta epsilon zeta");
+    // Continuation rows break at a space and leave the line number blank
+    assert_eq!(buffer_line_text(&buf, 4), "   eta theta iota kappa lambda mu");
+    assert!(buffer_line_text(&buf, 5).contains("3 end"));
+}
+
+#[test]
+fn status_bar_shows_diff_base() {
+    let (mut app, _tmp) = app_with_content("hello");
+    let buf = render_app(&mut app, 80, 24);
+    assert!(!buffer_line_text(&buf, 23).contains("vs "));
+
+    app.buf.diff_base = Some("origin/main".to_string());
+    let buf = render_app(&mut app, 80, 24);
+    assert!(buffer_line_text(&buf, 23).contains("vs origin/main | 1 words"));
+}
+
+// ═══════════════════════════════════════════════════════════════════════
+// Q. Merge Conflicts
+// ═══════════════════════════════════════════════════════════════════════
+
+#[test]
+fn conflict_regions_get_distinct_backgrounds() {
+    let (mut app, _tmp) =
+        app_with_content("intro\n<<<<<<< HEAD\nours\n=======\ntheirs\n>>>>>>> branch\noutro");
+    let buf = render_app(&mut app, 80, 24);
+
+    assert_eq!(cell_bg(&buf, 10, 2), Color::Reset, "text outside a conflict");
+    assert_eq!(cell_bg(&buf, 10, 3), marko::theme::active().conflict_marker_bg);
+    assert_eq!(cell_bg(&buf, 10, 4), marko::theme::active().conflict_ours_bg);
+    assert_eq!(cell_bg(&buf, 10, 5), marko::theme::active().conflict_marker_bg);
+    assert_eq!(cell_bg(&buf, 10, 6), marko::theme::active().conflict_theirs_bg);
+    assert_eq!(cell_bg(&buf, 10, 7), marko::theme::active().conflict_marker_bg);
+    assert_eq!(cell_bg(&buf, 10, 8), Color::Reset);
+}
+
+// ═══════════════════════════════════════════════════════════════════════
+// R. Folding
+// ═══════════════════════════════════════════════════════════════════════
+
+#[test]
+fn folded_code_block_renders_summary_line() {
+    use crossterm::event::{Event, KeyCode, KeyEvent, KeyModifiers};
+    let (mut app, _tmp) = app_with_content("intro\n```rust\nfn a() {}\nfn b() {}\n```\noutro");
+    app.handle_event(Event::Key(KeyEvent::new(KeyCode::Down, KeyModifiers::NONE)));
+    app.handle_event(Event::Key(KeyEvent::new(KeyCode::Char('z'), KeyModifiers::ALT)));
+    let buf = render_app(&mut app, 80, 24);
+
+    assert_eq!(buffer_line_text(&buf, 3), " 2 ```rust \u{2026} (2 lines)");
+    assert_eq!(buffer_line_text(&buf, 4), " 6 outro");
+    assert_eq!(cell_fg(&buf, 11, 3), marko::theme::active().marker);
+}
+
+// ═══════════════════════════════════════════════════════════════════════
+// S. Inline Images
+// ═══════════════════════════════════════════════════════════════════════
+
+#[test]
+fn image_reference_without_graphics_protocol_draws_no_thumbnail() {
+    // The test terminal has no graphics protocol, so nothing is drawn
+    let (mut app, _tmp) = app_with_content("![logo](logo.png)\n\nafter");
+    let buf = render_app(&mut app, 80, 24);
+
+    assert_eq!(buffer_line_text(&buf, 2), " 1 ![logo](logo.png)");
+    assert_eq!(buffer_line_text(&buf, 3), " 2");
+    assert_eq!(buffer_line_text(&buf, 4), " 3 after");
+}
+
+// ═══════════════════════════════════════════════════════════════════════
+// T. Split View
+// ═══════════════════════════════════════════════════════════════════════
+
+fn press_alt(app: &mut App, ch: char) {
+    use crossterm::event::{Event, KeyCode, KeyEvent, KeyModifiers};
+    app.handle_event(Event::Key(KeyEvent::new(KeyCode::Char(ch), KeyModifiers::ALT)));
+}
+
+#[test]
+fn split_view_shows_editor_and_preview_side_by_side() {
+    let (mut app, _tmp) = app_with_content("# Title\n\nSome body text");
+    press_alt(&mut app, 'v');
+    let buf = render_app(&mut app, 80, 24);
+
+    let row = buffer_line_text(&buf, 2);
+    assert!(row.starts_with(" 1 # Title"), "editor on the left: {:?}", row);
+    assert_eq!(buf.cell((40, 2)).unwrap().symbol(), "\u{2502}", "divider");
+    let right: String = (2..8)
+        .flat_map(|y| (41..80).map(move |x| (x, y)))
+        .map(|pos| buf.cell(pos).unwrap().symbol().to_string())
+        .collect();
+    assert!(right.contains("Some body text"), "preview on the right: {:?}", right);
+
+    // Both header tabs are highlighted
+    // Both header tabs (" EDITOR " then " PREVIEW " at the right end) are highlighted
+    assert_eq!(cell_bg(&buf, 65, 0), marko::theme::active().active_tab, "EDITOR tab");
+    assert_eq!(cell_bg(&buf, 75, 0), marko::theme::active().active_tab, "PREVIEW tab");
+}
+
+#[test]
+fn split_view_preview_follows_cursor() {
+    use crossterm::event::{Event, KeyCode, KeyEvent, KeyModifiers};
+    let content: String = (1..=60).map(|i| format!("Paragraph {}\n\n", i)).collect();
+    let (mut app, _tmp) = app_with_content(&content);
+    press_alt(&mut app, 'v');
+    render_app(&mut app, 80, 24);
+    assert_eq!(app.preview.scroll_offset, 0);
+
+    app.handle_event(Event::Key(KeyEvent::new(KeyCode::End, KeyModifiers::CONTROL)));
+    let buf = render_app(&mut app, 80, 24);
+    assert!(app.preview.scroll_offset > 0);
+    let preview_text: String = (2..22)
+        .flat_map(|y| (41..80).map(move |x| (x, y)))
+        .map(|pos| buf.cell(pos).unwrap().symbol().to_string())
+        .collect();
+    assert!(preview_text.contains("Paragraph 60"));
+}
+
+#[test]
+fn split_view_falls_back_to_editor_when_narrow() {
+    let (mut app, _tmp) = app_with_content("# Title");
+    press_alt(&mut app, 'v');
+    let buf = render_app(&mut app, 50, 24);
+
+    assert_eq!(buffer_line_text(&buf, 2), " 1 # Title");
+    // Only the EDITOR tab is highlighted (the tabs end the header row)
+    assert_eq!(cell_bg(&buf, 35, 0), marko::theme::active().active_tab, "EDITOR tab");
+    assert_ne!(cell_bg(&buf, 45, 0), marko::theme::active().active_tab, "PREVIEW tab");
+}
+
+// ═══════════════════════════════════════════════════════════════════════
+// U. Outline Panel
+// ═══════════════════════════════════════════════════════════════════════
+
+#[test]
+fn outline_panel_lists_headings_with_highlight() {
+    use crossterm::event::{Event, KeyCode, KeyEvent, KeyModifiers};
+    let (mut app, _tmp) = app_with_content("# Intro\n## Setup\ntext");
+    app.handle_event(Event::Key(KeyEvent::new(KeyCode::Char('o'), KeyModifiers::CONTROL)));
+    let buf = render_app(&mut app, 80, 24);
+
+    let text: Vec<String> = (0..24).map(|y| buffer_line_text(&buf, y)).collect();
+    let intro = text.iter().position(|l| l.contains("│ Intro")).expect("Intro listed");
+    assert!(text[intro - 1].contains("Outline"), "titled border above");
+    assert!(text[intro + 1].contains("│   Setup"), "H2 indented under H1");
+
+    let byte = text[intro].find("Intro").unwrap();
+    let x = text[intro][..byte].chars().count() as u16;
+    assert_eq!(cell_bg(&buf, x, intro as u16), marko::theme::active().active_tab);
+}
+
+// ═══════════════════════════════════════════════════════════════════════
+// V. Spell Check
+// ═══════════════════════════════════════════════════════════════════════
+
+#[cfg(feature = "spellcheck")]
+#[test]
+fn misspelled_words_are_underlined_outside_code() {
+    use ratatui::style::Modifier;
+    let (mut app, dir) = app_with_named_file("the kat\n```\nkat\n```", "notes.md");
+    let words = dir.path().join("words.txt");
+    std::fs::write(&words, "the\ncat\n").unwrap();
+    app.buf.config.spellcheck = true;
+    app.buf.config.spell_dictionary = Some(words);
+    app.load_spell_dictionary();
+    let buf = render_app(&mut app, 80, 24);
+
+    assert_eq!(buffer_line_text(&buf, 2), " 1 the kat");
+    let underlined = |x: u16, y: u16| buf[(x, y)].modifier.contains(Modifier::UNDERLINED);
+    assert!(underlined(7, 2), "kat");
+    assert_eq!(buf[(7, 2)].underline_color, marko::theme::active().misspelled);
+    assert!(!underlined(3, 2), "the");
+    assert!(!underlined(3, 4), "kat in a code block");
+}
+
+// ═══════════════════════════════════════════════════════════════════════
+// W. Code Fence Highlighting
+// ═══════════════════════════════════════════════════════════════════════
+
+#[test]
+fn highlight_current_fence_only_highlights_the_cursor_block() {
+    use crossterm::event::{Event, KeyCode, KeyEvent, KeyModifiers};
+    marko::markdown::code_highlight::syntax_set();
+    marko::markdown::code_highlight::theme_set();
+    let (mut app, _tmp) = app_with_content("```rust\nfn a() {}\n```\ntext\n```rust\nfn b() {}\n```");
+    app.buf.config.highlight_current_fence_only = true;
+    for _ in 0..5 {
+        app.handle_event(Event::Key(KeyEvent::new(KeyCode::Down, KeyModifiers::NONE)));
+    }
+    let buf = render_app(&mut app, 80, 24);
+    assert_eq!(buffer_line_text(&buf, 3), " 2 fn a() {}");
+    assert_eq!(cell_fg(&buf, 4, 3), Color::Reset, "other block left plain");
+    assert!(matches!(cell_fg(&buf, 4, 7), Color::Rgb(..)), "cursor block highlighted");
+
+    for _ in 0..4 {
+        app.handle_event(Event::Key(KeyEvent::new(KeyCode::Up, KeyModifiers::NONE)));
+    }
+    let buf = render_app(&mut app, 80, 24);
+    assert!(matches!(cell_fg(&buf, 4, 3), Color::Rgb(..)), "highlighted once entered");
+    assert_eq!(cell_fg(&buf, 4, 7), Color::Reset);
+}
+
+// ═══════════════════════════════════════════════════════════════════════
+// X. Preview Link Focus
+// ═══════════════════════════════════════════════════════════════════════
+
+/// Content rows where some cell has the link focus background.
+fn focused_rows(buf: &Buffer) -> Vec<u16> {
+    (2..buf.area.height - 2)
+        .filter(|&y| (0..buf.area.width).any(|x| cell_bg(buf, x, y) == marko::theme::active().selection))
+        .collect()
+}
+
+#[test]
+fn tab_cycles_preview_links_and_scrolls_to_them() {
+    use crossterm::event::{Event, KeyCode, KeyEvent, KeyModifiers};
+    let filler: Vec<String> = (0..40).map(|i| format!("Paragraph {}", i)).collect();
+    let content = format!(
+        "See [one](http://a.example) and [two](http://b.example).\n\n{}\n\n[far](http://c.example)",
+        filler.join("\n\n")
+    );
+    let (mut app, _tmp) = app_with_content(&content);
+    app.mode = Mode::Preview;
+    let buf = render_app(&mut app, 80, 24);
+    assert!(focused_rows(&buf).is_empty(), "nothing focused at first");
+
+    let tab = |app: &mut App, code| app.handle_event(Event::Key(KeyEvent::new(code, KeyModifiers::NONE)));
+    tab(&mut app, KeyCode::Tab);
+    assert_eq!(app.mode, Mode::Preview, "Tab stays in the preview while it has links");
+    assert_eq!(app.preview.focused_url(), Some("http://a.example"));
+    let buf = render_app(&mut app, 80, 24);
+    let rows = focused_rows(&buf);
+    assert_eq!(rows.len(), 1);
+    assert!(buffer_line_text(&buf, rows[0]).contains("See one"));
+
+    tab(&mut app, KeyCode::Tab);
+    tab(&mut app, KeyCode::Tab);
+    assert_eq!(app.preview.focused_url(), Some("http://c.example"));
+    assert!(app.preview.scroll_offset > 0, "scrolled down to the last link");
+    let buf = render_app(&mut app, 80, 24);
+    let rows = focused_rows(&buf);
+    assert_eq!(rows.len(), 1);
+    assert!(buffer_line_text(&buf, rows[0]).contains("far"));
+
+    tab(&mut app, KeyCode::BackTab);
+    assert_eq!(app.preview.focused_url(), Some("http://b.example"));
+    assert_eq!(app.preview.scroll_offset, 0, "scrolled back up");
+    tab(&mut app, KeyCode::Tab);
+    tab(&mut app, KeyCode::Tab);
+    assert_eq!(app.preview.focused_url(), Some("http://a.example"), "wraps around");
+}
+
+#[test]
+fn tab_leaves_a_preview_without_links() {
+    use crossterm::event::{Event, KeyCode, KeyEvent, KeyModifiers};
+    let (mut app, _tmp) = app_with_content("# Plain\n\nNo links here.");
+    app.mode = Mode::Preview;
+    let _ = render_app(&mut app, 80, 24);
+    app.handle_event(Event::Key(KeyEvent::new(KeyCode::Tab, KeyModifiers::NONE)));
+    assert_eq!(app.mode, Mode::Editor);
+}
+
+// ═══════════════════════════════════════════════════════════════════════
+// Y. Message Log
+// ═══════════════════════════════════════════════════════════════════════
+
+#[test]
+fn message_log_lists_status_messages_newest_first() {
+    use crossterm::event::{Event, KeyCode, KeyEvent, KeyModifiers};
+    let (mut app, _tmp) = app_with_content("text");
+    app.set_status("Export failed: pandoc not found");
+    app.set_status("Saved");
+    app.status_message.clear();
+
+    app.handle_event(Event::Key(KeyEvent::new(KeyCode::F(2), KeyModifiers::NONE)));
+    assert!(app.show_status_log);
+    let buf = render_app(&mut app, 80, 24);
+    let rows: Vec<u16> = (0..24).filter(|&y| buffer_line_text(&buf, y).contains("0s ")).collect();
+    assert_eq!(rows.len(), 2, "both messages logged");
+    assert!(buffer_line_text(&buf, rows[0]).contains("Saved"));
+    assert!(buffer_line_text(&buf, rows[1]).contains("Export failed: pandoc not found"));
+
+    app.handle_event(Event::Key(KeyEvent::new(KeyCode::Char('x'), KeyModifiers::NONE)));
+    assert!(!app.show_status_log, "any key closes it");
+    assert_eq!(app.buf.textarea.lines()[0], "text", "without typing");
+}
+
+// ═══════════════════════════════════════════════════════════════════════
+// Z. Collapsible Preview Sections
+// ═══════════════════════════════════════════════════════════════════════
+
+#[test]
+fn clicking_a_heading_collapses_its_section() {
+    use crossterm::event::{Event, MouseButton, MouseEvent, MouseEventKind};
+    let (mut app, _tmp) = app_with_content("## Intro\n\nfirst\n\nsecond\n\n## Next\n\nafter");
+    app.mode = Mode::Preview;
+    let buf = render_app(&mut app, 80, 24);
+    let row_of = |buf: &Buffer, needle: &str| (0..24).find(|&y| buffer_line_text(buf, y).contains(needle));
+    let heading = row_of(&buf, "## Intro").unwrap();
+
+    let click = |app: &mut App, row: u16| {
+        for kind in [MouseEventKind::Down(MouseButton::Left), MouseEventKind::Up(MouseButton::Left)] {
+            app.handle_event(Event::Mouse(MouseEvent {
+                kind,
+                column: 4,
+                row,
+                modifiers: crossterm::event::KeyModifiers::NONE,
+            }));
+        }
+    };
+    click(&mut app, heading);
+    let buf = render_app(&mut app, 80, 24);
+    assert!(buffer_line_text(&buf, heading).contains("▸ 6 lines hidden"), "{}", buffer_line_text(&buf, heading));
+    assert_eq!(row_of(&buf, "first"), None);
+    assert_eq!(row_of(&buf, "second"), None);
+    assert_eq!(row_of(&buf, "## Next"), Some(heading + 1), "next section moves up");
+    assert!(row_of(&buf, "after").is_some());
+
+    click(&mut app, heading);
+    let buf = render_app(&mut app, 80, 24);
+    assert!(row_of(&buf, "first").is_some(), "a second click expands it");
+    assert!(!buffer_line_text(&buf, heading).contains("hidden"));
+}
+
+#[test]
+fn enter_collapses_the_section_at_the_top() {
+    use crossterm::event::{Event, KeyCode, KeyEvent, KeyModifiers};
+    let (mut app, _tmp) = app_with_content("# Title\n\nbody\n\n# Other\n\nrest");
+    app.mode = Mode::Preview;
+    let _ = render_app(&mut app, 80, 24);
+    app.handle_event(Event::Key(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE)));
+    assert!(app.preview.collapsed.contains(&0));
+    let buf = render_app(&mut app, 80, 24);
+    assert!((0..24).all(|y| !buffer_line_text(&buf, y).contains("body")));
+    assert!((0..24).any(|y| buffer_line_text(&buf, y).contains("# Other")));
+}
@@ -1,55 +1,121 @@
+use marko::theme::{self, Theme, DARK, HIGH_CONTRAST, LIGHT};
 use ratatui::style::Color;
 
 // Theme color verification tests.
-// These ensure the ANSI color constants match the terminal-inherited theme.
+// These ensure the default theme's ANSI colors match the terminal-inherited theme.
 
 #[test]
 fn test_base_colors() {
-    assert_eq!(marko::theme::BG, Color::Reset);
-    assert_eq!(marko::theme::FG, Color::Reset);
-    assert_eq!(marko::theme::BORDER, Color::DarkGray);
+    assert_eq!(DARK.bg, Color::Reset);
+    assert_eq!(DARK.fg, Color::Reset);
+    assert_eq!(DARK.border, Color::DarkGray);
 }
 
 #[test]
 fn test_ui_colors() {
-    assert_eq!(marko::theme::BAR_BG, Color::Reset);
-    assert_eq!(marko::theme::BAR_FG, Color::Reset);
-    assert_eq!(marko::theme::LINE_NUMBER, Color::DarkGray);
-    assert_eq!(marko::theme::SELECTION, Color::Blue);
+    assert_eq!(DARK.bar_bg, Color::Reset);
+    assert_eq!(DARK.bar_fg, Color::Reset);
+    assert_eq!(DARK.line_number, Color::DarkGray);
+    assert_eq!(DARK.selection, Color::Blue);
 }
 
 #[test]
 fn test_markdown_syntax_colors() {
-    assert_eq!(marko::theme::HEADING, Color::Blue);
-    assert_eq!(marko::theme::BOLD, Color::Yellow);
-    assert_eq!(marko::theme::ITALIC, Color::Cyan);
-    assert_eq!(marko::theme::LINK, Color::Cyan);
-    assert_eq!(marko::theme::CODE, Color::Red);
-    assert_eq!(marko::theme::QUOTE, Color::Green);
+    assert_eq!(DARK.heading, Color::Blue);
+    assert_eq!(DARK.bold, Color::Yellow);
+    assert_eq!(DARK.italic, Color::Cyan);
+    assert_eq!(DARK.link, Color::Cyan);
+    assert_eq!(DARK.code, Color::Red);
+    assert_eq!(DARK.quote, Color::Green);
 }
 
 #[test]
 fn test_git_diff_colors() {
-    assert_eq!(marko::theme::GIT_ADDED, Color::Green);
-    assert_eq!(marko::theme::GIT_REMOVED, Color::Red);
-    assert_eq!(marko::theme::GIT_MODIFIED, Color::Yellow);
+    assert_eq!(DARK.git_added, Color::Green);
+    assert_eq!(DARK.git_removed, Color::Red);
+    assert_eq!(DARK.git_modified, Color::Yellow);
 }
 
 #[test]
 fn test_status_indicator_colors() {
-    assert_eq!(marko::theme::SUCCESS, Color::Green);
-    assert_eq!(marko::theme::WARNING, Color::Yellow);
-    assert_eq!(marko::theme::ERROR, Color::Red);
+    assert_eq!(DARK.success, Color::Green);
+    assert_eq!(DARK.warning, Color::Yellow);
+    assert_eq!(DARK.error, Color::Red);
 }
 
 #[test]
 fn test_tab_colors() {
-    assert_eq!(marko::theme::ACTIVE_TAB, Color::Blue);
-    assert_eq!(marko::theme::INACTIVE_TAB, Color::Gray);
+    assert_eq!(DARK.active_tab, Color::Blue);
+    assert_eq!(DARK.inactive_tab, Color::Gray);
 }
 
 #[test]
 fn test_misc_colors() {
-    assert_eq!(marko::theme::WHITE, Color::White);
-    assert_eq!(marko::theme::TILDE, Color::DarkGray);
+    assert_eq!(DARK.white, Color::White);
+    assert_eq!(DARK.tilde, Color::DarkGray);
+}
+
+#[test]
+fn test_presets_by_name() {
+    assert_eq!(Theme::by_name("dark"), Some(&DARK));
+    assert_eq!(Theme::by_name("light"), Some(&LIGHT));
+    assert_eq!(Theme::by_name("high-contrast"), Some(&HIGH_CONTRAST));
+    assert_eq!(Theme::by_name("solarized"), None);
+}
+
+#[test]
+fn test_presets_cycle_and_differ() {
+    assert_eq!(DARK.next(), &LIGHT);
+    assert_eq!(LIGHT.next(), &HIGH_CONTRAST);
+    assert_eq!(HIGH_CONTRAST.next(), &DARK);
+    assert_ne!(DARK.link, LIGHT.link);
+    assert_ne!(DARK.code_theme, LIGHT.code_theme);
+}
+
+#[test]
+fn test_active_theme_drives_styles() {
+    assert_eq!(theme::active(), &DARK);
+    theme::set_active(&LIGHT);
+    assert_eq!(theme::link_style().fg, Some(LIGHT.link));
+    theme::set_active(&DARK);
+    assert_eq!(theme::link_style().fg, Some(DARK.link));
+}
+
+#[test]
+fn test_custom_theme_fills_missing_colors_from_base() {
+    let custom = Theme::parse("base = \"light\"\nlink = \"#ff8800\"\nborder = \"dark gray\"").unwrap();
+    assert_eq!(custom.name, theme::CUSTOM_NAME);
+    assert_eq!(custom.link, Color::Rgb(255, 136, 0));
+    assert_eq!(custom.border, Color::DarkGray);
+    assert_eq!(custom.heading, LIGHT.heading);
+
+    let custom = Theme::parse("heading = \"red\"").unwrap();
+    assert_eq!(custom.heading, Color::Red);
+    assert_eq!(custom.code_bg, DARK.code_bg, "dark without a base");
+}
+
+#[test]
+fn test_custom_theme_blockquote_style() {
+    let custom = Theme::parse("quote_bg = \"#303040\"\nquote_bar = false").unwrap();
+    assert_eq!(custom.quote_bg, Color::Rgb(48, 48, 64));
+    assert!(!custom.quote_bar);
+    assert!(DARK.quote_bar && DARK.quote_bg == Color::Reset, "bar only by default");
+    assert!(Theme::parse("quote_bar = \"no\"").unwrap_err().contains("quote_bar"));
+}
+
+#[test]
+fn test_custom_theme_errors_name_the_problem() {
+    assert!(Theme::parse("link = \"blurple\"").unwrap_err().contains("link"));
+    assert!(Theme::parse("sparkle = \"red\"").unwrap_err().contains("sparkle"));
+    assert!(Theme::parse("base = \"neon\"").unwrap_err().contains("neon"));
+    assert!(Theme::parse("heading_levels = [\"red\"]").is_err());
+    assert!(Theme::parse("link = ").is_err());
+}
+
+#[test]
+fn test_dump_round_trips() {
+    for preset in theme::PRESETS {
+        let parsed = Theme::parse(&preset.to_toml()).unwrap();
+        assert_eq!(Theme { name: preset.name, ..parsed }, *preset);
+    }
 }
@@ -0,0 +1,115 @@
+use std::fs;
+
+use marko::config::Config;
+use marko::workspace::{self, WalkOptions};
+use tempfile::TempDir;
+
+/// Builds a workspace with a `.gitignore` that excludes the `build/` folder.
+fn workspace_with_gitignore() -> TempDir {
+    let dir = TempDir::new().unwrap();
+    let root = dir.path();
+    fs::write(root.join(".gitignore"), "build/\n").unwrap();
+    fs::write(root.join("README.md"), "# Readme").unwrap();
+    fs::create_dir_all(root.join("notes")).unwrap();
+    fs::write(root.join("notes/todo.md"), "- [ ] thing").unwrap();
+    fs::write(root.join("notes/data.txt"), "not markdown").unwrap();
+    fs::create_dir_all(root.join("build")).unwrap();
+    fs::write(root.join("build/generated.md"), "generated").unwrap();
+    fs::create_dir_all(root.join(".marko/images")).unwrap();
+    fs::write(root.join(".marko/cache.md"), "hidden").unwrap();
+    dir
+}
+
+#[test]
+fn test_gitignored_folder_is_skipped() {
+    let dir = workspace_with_gitignore();
+    let files = workspace::markdown_files(dir.path(), &WalkOptions::default());
+    let names: Vec<_> = files
+        .iter()
+        .map(|p| p.strip_prefix(dir.path()).unwrap().to_path_buf())
+        .collect();
+    assert_eq!(names, vec!["README.md".into(), std::path::PathBuf::from("notes/todo.md")]);
+}
+
+#[test]
+fn test_include_ignored_lists_gitignored_files() {
+    let dir = workspace_with_gitignore();
+    let files = workspace::markdown_files(
+        dir.path(),
+        &WalkOptions {
+            include_ignored: true,
+            ..WalkOptions::default()
+        },
+    );
+    assert!(files.iter().any(|p| p.ends_with("build/generated.md")));
+    // Hidden directories stay excluded either way
+    assert!(!files.iter().any(|p| p.ends_with(".marko/cache.md")));
+}
+
+#[test]
+fn test_dot_ignore_file_is_honored() {
+    let dir = workspace_with_gitignore();
+    fs::write(dir.path().join(".ignore"), "notes/\n").unwrap();
+    let files = workspace::files(dir.path(), &WalkOptions::default());
+    assert!(!files.iter().any(|p| p.starts_with(dir.path().join("notes"))));
+    assert!(files.iter().any(|p| p.ends_with("README.md")));
+}
+
+#[test]
+fn test_excluded_dirs_skipped_by_every_scan() {
+    let dir = workspace_with_gitignore();
+    fs::create_dir_all(dir.path().join("notes/target")).unwrap();
+    fs::write(dir.path().join("notes/target/out.md"), "").unwrap();
+    fs::write(dir.path().join("notes/target/out.txt"), "").unwrap();
+    let config = Config::parse("exclude_dirs = [\"target\"]\ninclude_ignored_files = true").unwrap();
+    let options = WalkOptions::from_config(&config);
+    assert!(options.include_ignored);
+
+    let all = workspace::files(dir.path(), &options);
+    let markdown = workspace::markdown_files(dir.path(), &options);
+    for list in [&all, &markdown] {
+        assert!(!list.iter().any(|p| p.components().any(|c| c.as_os_str() == "target")));
+        assert!(list.iter().any(|p| p.ends_with("notes/todo.md")));
+    }
+    // Excludes apply on top of include_ignored, not instead of it
+    assert!(markdown.iter().any(|p| p.ends_with("build/generated.md")));
+}
+
+#[test]
+fn test_exclude_matches_whole_directory_names_only() {
+    let dir = workspace_with_gitignore();
+    let options = WalkOptions {
+        exclude_dirs: vec!["note".to_string()],
+        ..WalkOptions::default()
+    };
+    let files = workspace::markdown_files(dir.path(), &options);
+    assert!(files.iter().any(|p| p.ends_with("notes/todo.md")));
+}
+
+#[test]
+fn test_relative_path_inside_workspace() {
+    let root = std::path::Path::new("/home/me/notes");
+    assert_eq!(
+        workspace::relative_path(&root.join("daily/index.md"), root),
+        std::path::PathBuf::from("daily/index.md")
+    );
+}
+
+#[test]
+fn test_relative_path_outside_workspace_is_unchanged() {
+    let root = std::path::Path::new("/home/me/notes");
+    let outside = std::path::Path::new("/tmp/index.md");
+    assert_eq!(workspace::relative_path(outside, root), outside);
+}
+
+#[test]
+fn test_relative_path_against_discovered_root() {
+    let dir = workspace_with_gitignore();
+    git2::Repository::init(dir.path()).unwrap();
+    let file = dir.path().join("notes/todo.md");
+    let root = workspace::root_for(&file);
+    assert_eq!(
+        workspace::relative_path(&file, &root),
+        std::path::PathBuf::from("notes/todo.md")
+    );
+}